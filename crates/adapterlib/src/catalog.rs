@@ -9,7 +9,7 @@ use apache_avro::{schema::NamesRef, types::Value as AvroValue, Schema as AvroSch
 use arrow::record_batch::RecordBatch;
 use dbsp::circuit::NodeId;
 use dyn_clone::DynClone;
-use feldera_types::format::csv::CsvParserConfig;
+use feldera_types::format::csv::{CsvEncoderConfig, CsvParserConfig};
 use feldera_types::format::json::JsonFlavor;
 use feldera_types::program_schema::{Relation, SqlIdentifier};
 use feldera_types::serde_with_context::SqlSerdeConfig;
@@ -30,6 +30,9 @@ pub enum RecordFormat {
     // tables that store raw JSON or binary data to be parsed using SQL.
     Json(JsonFlavor),
     Csv(CsvParserConfig),
+    /// CSV records produced by the CSV encoder.  Only supported by output
+    /// handles.
+    CsvEncoder(CsvEncoderConfig),
     Parquet(SqlSerdeConfig),
     #[cfg(feature = "with-avro")]
     Avro,
@@ -308,6 +311,17 @@ pub trait SerCursor: Send {
     /// Serialize current value. Panics if invalid.
     fn serialize_val(&mut self, dst: &mut Vec<u8>) -> AnyResult<()>;
 
+    /// Serialize current value into arrow format. Panics if invalid.
+    fn serialize_val_to_arrow(&mut self, dst: &mut ArrayBuilder) -> AnyResult<()>;
+
+    /// Serialize current value into arrow format, adding additional metadata columns.
+    /// `metadata` must be a struct or a map.
+    fn serialize_val_to_arrow_with_metadata(
+        &mut self,
+        metadata: &dyn erased_serde::Serialize,
+        dst: &mut ArrayBuilder,
+    ) -> AnyResult<()>;
+
     /// Convert value to JSON. Used for error reporting to generate a human-readable
     /// representation of the value.
     fn val_to_json(&mut self) -> AnyResult<serde_json::Value>;
@@ -478,6 +492,19 @@ impl SerCursor for CursorWithPolarity<'_> {
         self.cursor.serialize_val(dst)
     }
 
+    fn serialize_val_to_arrow(&mut self, dst: &mut ArrayBuilder) -> AnyResult<()> {
+        self.cursor.serialize_val_to_arrow(dst)
+    }
+
+    fn serialize_val_to_arrow_with_metadata(
+        &mut self,
+        metadata: &dyn erased_serde::Serialize,
+        dst: &mut ArrayBuilder,
+    ) -> AnyResult<()> {
+        self.cursor
+            .serialize_val_to_arrow_with_metadata(metadata, dst)
+    }
+
     fn val_to_json(&mut self) -> AnyResult<serde_json::Value> {
         self.cursor.val_to_json()
     }
@@ -114,6 +114,13 @@ pub enum ConfigError {
         index_name: String,
     },
 
+    /// Endpoint requests a snapshot of a table or view that is not
+    /// materialized.
+    NotMaterialized {
+        endpoint_name: String,
+        stream_name: String,
+    },
+
     InputFormatNotSupported {
         endpoint_name: String,
         error: String,
@@ -152,6 +159,16 @@ pub enum ConfigError {
         error: String,
     },
 
+    InvalidDeadLetterConfig {
+        endpoint_name: String,
+        error: String,
+    },
+
+    InvalidRateLimit {
+        endpoint_name: String,
+        error: String,
+    },
+
     CyclicDependency {
         cycle: Vec<(String, String)>,
     },
@@ -184,6 +201,7 @@ impl DbspDetailedError for ConfigError {
             Self::UnknownOutputStream { .. } => Cow::from("UnknownOutputStream"),
             Self::UnknownIndex { .. } => Cow::from("UnknownIndex"),
             Self::NotAnIndex { .. } => Cow::from("NotAnIndex"),
+            Self::NotMaterialized { .. } => Cow::from("NotMaterialized"),
             Self::InputFormatNotSupported { .. } => Cow::from("InputFormatNotSupported"),
             Self::OutputFormatNotSupported { .. } => Cow::from("OutputFormatNotSupported"),
             Self::InputFormatNotSpecified { .. } => Cow::from("InputFormatNotSpecified"),
@@ -192,6 +210,8 @@ impl DbspDetailedError for ConfigError {
             Self::InvalidParserConfig { .. } => Cow::from("InvalidParserConfig"),
             Self::InvalidTransportConfig { .. } => Cow::from("InvalidTransportConfig"),
             Self::InvalidOutputBufferConfig { .. } => Cow::from("InvalidOutputBufferConfig"),
+            Self::InvalidDeadLetterConfig { .. } => Cow::from("InvalidDeadLetterConfig"),
+            Self::InvalidRateLimit { .. } => Cow::from("InvalidRateLimit"),
             Self::FtRequiresStorage => Cow::from("FtRequiresStorage"),
             Self::FtRequiresFtInput => Cow::from("FtWithNonFtInput"),
             Self::CyclicDependency { .. } => Cow::from("CyclicDependency"),
@@ -291,6 +311,13 @@ impl Display for ConfigError {
                 write!(f, "Output endpoint '{endpoint_name}' specifies unknown index '{index_name}'")
             }
 
+            Self::NotMaterialized {
+                endpoint_name,
+                stream_name,
+            } => {
+                write!(f, "Output endpoint '{endpoint_name}' requests a snapshot of '{stream_name}', which is not materialized; use `with ('materialized' = 'true')` for tables, or `create materialized view` for views")
+            }
+
             Self::InputFormatNotSupported {
                 endpoint_name,
                 error,
@@ -357,6 +384,21 @@ impl Display for ConfigError {
                     "invalid output buffer configuration for endpoint '{endpoint_name}': {error}"
                 )
             }
+            Self::InvalidDeadLetterConfig {
+                endpoint_name,
+                error,
+            } => {
+                write!(
+                    f,
+                    "invalid dead-letter configuration for endpoint '{endpoint_name}': {error}"
+                )
+            }
+            Self::InvalidRateLimit {
+                endpoint_name,
+                error,
+            } => {
+                write!(f, "invalid rate limit for endpoint '{endpoint_name}': {error}")
+            }
             Self::CyclicDependency { cycle } => {
                 let mut cycle = cycle.clone();
                 cycle.push(cycle[0].clone());
@@ -484,6 +526,13 @@ impl ConfigError {
         }
     }
 
+    pub fn not_materialized(endpoint_name: &str, stream_name: &str) -> Self {
+        Self::NotMaterialized {
+            endpoint_name: endpoint_name.to_owned(),
+            stream_name: stream_name.to_owned(),
+        }
+    }
+
     pub fn input_format_not_supported(endpoint_name: &str, error: &str) -> Self {
         Self::InputFormatNotSupported {
             endpoint_name: endpoint_name.to_owned(),
@@ -538,6 +587,20 @@ impl ConfigError {
         }
     }
 
+    pub fn invalid_dead_letter_configuration(endpoint_name: &str, error: &str) -> Self {
+        Self::InvalidDeadLetterConfig {
+            endpoint_name: endpoint_name.to_string(),
+            error: error.to_string(),
+        }
+    }
+
+    pub fn invalid_rate_limit(endpoint_name: &str, error: &str) -> Self {
+        Self::InvalidRateLimit {
+            endpoint_name: endpoint_name.to_string(),
+            error: error.to_string(),
+        }
+    }
+
     pub fn cyclic_dependency(cycle: Vec<(String, String)>) -> Self {
         Self::CyclicDependency { cycle }
     }
@@ -1206,6 +1269,12 @@ impl ControllerError {
         }
     }
 
+    pub fn not_materialized(endpoint_name: &str, stream_name: &str) -> Self {
+        Self::Config {
+            config_error: Box::new(ConfigError::not_materialized(endpoint_name, stream_name)),
+        }
+    }
+
     pub fn input_format_not_supported(endpoint_name: &str, error: &str) -> Self {
         Self::Config {
             config_error: Box::new(ConfigError::input_format_not_supported(
@@ -1272,6 +1341,21 @@ impl ControllerError {
         }
     }
 
+    pub fn invalid_dead_letter_configuration(endpoint_name: &str, error: &str) -> Self {
+        Self::Config {
+            config_error: Box::new(ConfigError::invalid_dead_letter_configuration(
+                endpoint_name,
+                error,
+            )),
+        }
+    }
+
+    pub fn invalid_rate_limit(endpoint_name: &str, error: &str) -> Self {
+        Self::Config {
+            config_error: Box::new(ConfigError::invalid_rate_limit(endpoint_name, error)),
+        }
+    }
+
     pub fn input_transport_error(endpoint_name: &str, fatal: bool, error: AnyError) -> Self {
         Self::InputTransportError {
             endpoint_name: endpoint_name.to_owned(),
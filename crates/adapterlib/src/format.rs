@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fmt::{Display, Error as FmtError, Formatter};
 use std::hash::Hasher;
 
@@ -132,6 +133,31 @@ pub trait Parser: Send + Sync {
     /// state entirely.
     fn parse(&mut self, data: &[u8]) -> (Option<Box<dyn InputBuffer>>, Vec<ParseError>);
 
+    /// Parses `data` like [Parser::parse], and additionally sets the columns
+    /// named in `metadata` to the corresponding values in every parsed record.
+    ///
+    /// Transports use this to ingest metadata that isn't part of the payload,
+    /// such as the key of a Kafka message.  The default implementation
+    /// rejects nonempty `metadata`, for formats that don't support it.
+    fn parse_with_metadata(
+        &mut self,
+        data: &[u8],
+        metadata: &ConnectorMetadata,
+    ) -> (Option<Box<dyn InputBuffer>>, Vec<ParseError>) {
+        if metadata.is_empty() {
+            self.parse(data)
+        } else {
+            (
+                None,
+                vec![ParseError::bin_envelope_error(
+                    "this data format does not support connector metadata columns".to_string(),
+                    data,
+                    None,
+                )],
+            )
+        }
+    }
+
     /// Returns an object that can be used to break a stream of incoming data
     /// into complete records to pass to [Parser::parse].
     fn splitter(&self) -> Box<dyn Splitter>;
@@ -143,6 +169,22 @@ pub trait Parser: Send + Sync {
     fn fork(&self) -> Box<dyn Parser>;
 }
 
+/// A metadata value that a transport attaches to records.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MetadataValue {
+    Null,
+    Integer(i64),
+    String(String),
+    Bytes(Vec<u8>),
+    /// Milliseconds since the UNIX epoch.
+    Timestamp(i64),
+    Map(BTreeMap<String, Option<String>>),
+}
+
+/// Metadata values that a transport attaches to all of the records parsed from
+/// a chunk of data, as pairs of a column name and the column's value.
+pub type ConnectorMetadata = Vec<(String, MetadataValue)>;
+
 /// Splits a data stream at boundaries between records.
 ///
 /// [Parser::parse] can only parse complete records. For a byte stream source, a
@@ -221,6 +263,21 @@ pub trait OutputConsumer: Send {
         headers: &[(&str, Option<&[u8]>)],
         num_records: usize,
     );
+
+    /// See OutputEndpoint::push_key_to_topic.
+    ///
+    /// Consumers that don't route messages to topics ignore `topic`.
+    fn push_key_to_topic(
+        &mut self,
+        _topic: &str,
+        key: Option<&[u8]>,
+        val: Option<&[u8]>,
+        headers: &[(&str, Option<&[u8]>)],
+        num_records: usize,
+    ) {
+        self.push_key(key, val, headers, num_records)
+    }
+
     fn batch_end(&mut self);
 }
 
@@ -324,6 +381,26 @@ impl ParseError {
             suggestion,
         )))
     }
+
+    /// Error description.
+    pub fn description(&self) -> &str {
+        &self.0.description
+    }
+
+    /// Event number relative to the start of the stream, if known.
+    pub fn event_number(&self) -> Option<u64> {
+        self.0.event_number
+    }
+
+    /// Invalid fragment of the input text, for text-based formats.
+    pub fn invalid_text(&self) -> Option<&str> {
+        self.0.invalid_text.as_deref()
+    }
+
+    /// Invalid fragment of input data, for binary formats.
+    pub fn invalid_bytes(&self) -> Option<&[u8]> {
+        self.0.invalid_bytes.as_deref()
+    }
 }
 
 #[derive(Clone, Debug, Serialize, PartialEq, Eq)]
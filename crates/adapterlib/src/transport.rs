@@ -4,7 +4,7 @@ use std::hash::Hasher;
 use std::marker::PhantomData;
 use std::sync::Mutex;
 
-use anyhow::{Error as AnyError, Result as AnyResult};
+use anyhow::{anyhow, Error as AnyError, Result as AnyResult};
 use dyn_clone::DynClone;
 use feldera_types::config::FtModel;
 use feldera_types::program_schema::Relation;
@@ -479,6 +479,18 @@ pub trait InputReader: Send + Sync {
     fn disconnect(&self) {
         self.request(InputReaderCommand::Disconnect);
     }
+
+    /// Notifies the input reader that the controller has durably written a
+    /// checkpoint in which the position of this reader is `metadata` (the
+    /// `seek` value of one of the [Resume]s that it reported).
+    ///
+    /// The pipeline will never have to resume reading from a position
+    /// earlier than `metadata`, so the reader may release any upstream
+    /// resources that it held onto for the purpose of replay, e.g., by
+    /// acknowledging messages or advancing a replication slot.
+    ///
+    /// The default implementation does nothing.
+    fn checkpointed(&self, _metadata: &JsonValue) {}
 }
 
 /// Input stream consumer.
@@ -743,6 +755,15 @@ pub trait OutputEndpoint: Send {
 
     fn push_buffer(&mut self, buffer: &[u8]) -> AnyResult<()>;
 
+    /// Like [`OutputEndpoint::push_buffer`], but also receives the number of
+    /// records encoded in `buffer`.
+    ///
+    /// Endpoints that need to track the number of records they output, e.g., to
+    /// limit the number of records per output file, override this method.
+    fn push_buffer_with_records(&mut self, buffer: &[u8], _num_records: usize) -> AnyResult<()> {
+        self.push_buffer(buffer)
+    }
+
     /// Output a message consisting of a key/value pair, with optional headers.
     ///
     /// This API is implemented by Kafka and other transports that transmit
@@ -760,6 +781,24 @@ pub trait OutputEndpoint: Send {
         headers: &[(&str, Option<&[u8]>)],
     ) -> AnyResult<()>;
 
+    /// Like [`OutputEndpoint::push_key`], but writes the message to `topic`
+    /// instead of the topic configured for the endpoint.
+    ///
+    /// Invoked by encoders when the connector is configured to compute the
+    /// destination of each record from its columns.  Transports that do not
+    /// support per-message routing return an error.
+    fn push_key_to_topic(
+        &mut self,
+        topic: &str,
+        _key: Option<&[u8]>,
+        _val: Option<&[u8]>,
+        _headers: &[(&str, Option<&[u8]>)],
+    ) -> AnyResult<()> {
+        Err(anyhow!(
+            "cannot write message to topic '{topic}': this transport does not support routing messages to topics"
+        ))
+    }
+
     /// Notifies the output endpoint that output for the current step is
     /// complete.
     ///
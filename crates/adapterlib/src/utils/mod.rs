@@ -1 +1,2 @@
 pub mod datafusion;
+pub mod indexed;
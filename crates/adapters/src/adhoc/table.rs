@@ -287,9 +287,12 @@ impl DataSink for AdHocTableSink {
                 output_buffer_config: Default::default(),
                 max_batch_size: default_max_batch_size(),
                 max_queued_records: default_max_queued_records(),
+                max_records_per_second: None,
+                max_bytes_per_second: None,
                 paused: false,
                 labels: vec![],
                 start_after: None,
+                dead_letter: None,
             },
         };
 
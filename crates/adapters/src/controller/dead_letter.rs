@@ -0,0 +1,418 @@
+//! Dead-letter routing.
+//!
+//! Records that an input connector fails to parse or an output connector fails
+//! to encode are reported via the error callback and counted in the connector
+//! stats.  When the connector is configured with a dead-letter destination
+//! (`ConnectorConfig::dead_letter`), we additionally write each such record to
+//! that destination, as a separate dead letter, so it can be inspected and
+//! re-driven later.
+//!
+//! Dead letters are delivered on a best-effort basis: failing to write a dead
+//! letter is logged, but doesn't affect the connector.
+//!
+//! Dead letters written to a table are inserted with ad-hoc `INSERT`
+//! statements, which bypass the input journal, so a fault-tolerant pipeline
+//! would insert them again when it replays the steps that produced them.  We
+//! therefore don't support dead-letter tables in fault-tolerant pipelines.
+
+use super::{ControllerError, ControllerInner};
+use crate::catalog::{RecordFormat, SerBatchReader};
+use crate::format::MAX_DUPLICATES;
+#[cfg(feature = "with-kafka")]
+use crate::transport::kafka::KafkaOutputEndpoint;
+use crate::transport::Step;
+#[cfg(feature = "with-kafka")]
+use crate::OutputEndpoint;
+use crate::ParseError;
+use anyhow::{anyhow, Error as AnyError, Result as AnyResult};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use datafusion::prelude::SessionContext;
+use dbsp::circuit::tokio::TOKIO;
+use feldera_types::config::{ConnectorConfig, DeadLetterConfig};
+use feldera_types::program_schema::SqlIdentifier;
+use serde_json::json;
+use std::fmt::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tokio::sync::mpsc::{channel, error::TrySendError, Receiver, Sender};
+use tracing::error;
+
+/// Columns that a dead-letter table must have.
+const DEAD_LETTER_COLUMNS: [&str; 6] = [
+    "connector",
+    "error",
+    "event_number",
+    "step",
+    "raw_text",
+    "raw_bytes",
+];
+
+/// Maximal number of dead letters inserted into a table by one statement.
+const MAX_TABLE_BATCH: usize = 1000;
+
+/// Maximal number of dead letters waiting to be inserted into a table.  Dead
+/// letters that arrive while the queue is full are dropped.
+const MAX_TABLE_QUEUE: usize = 10 * MAX_TABLE_BATCH;
+
+/// A record that failed to parse or encode.
+struct DeadLetter {
+    /// Error description.
+    error: String,
+
+    /// For input connectors, the parser's number for the record, if known.
+    ///
+    /// Parsers number the records they parse starting from 0, so this is
+    /// not the offset of the record in the transport's input, and, for
+    /// transports that create a parser per input file, it is relative to the
+    /// start of the file.
+    event_number: Option<u64>,
+
+    /// For output connectors, the step that produced the record.
+    step: Option<Step>,
+
+    /// The offending data, if it is valid UTF-8.
+    raw_text: Option<String>,
+
+    /// The offending data, if it is binary.
+    raw_bytes: Option<Vec<u8>>,
+}
+
+enum DeadLetterSink {
+    /// Dead letters are sent to a task that inserts them into the table.
+    Table {
+        sender: Sender<DeadLetter>,
+
+        /// Number of dead letters dropped because the queue was full.
+        dropped: AtomicU64,
+    },
+    File {
+        path: String,
+        file: Mutex<File>,
+    },
+    #[cfg(feature = "with-kafka")]
+    Kafka(Mutex<KafkaOutputEndpoint>),
+}
+
+/// Writes the dead letters of one connector to its dead-letter destination.
+pub(crate) struct DeadLetterWriter {
+    endpoint_name: String,
+
+    /// Output connector attached to an index: the records of its batches are
+    /// values rather than keys.
+    indexed: bool,
+
+    sink: DeadLetterSink,
+}
+
+impl DeadLetterWriter {
+    /// Creates a writer for the dead-letter destination of a connector, if
+    /// it has one.
+    pub(crate) fn new(
+        endpoint_name: &str,
+        connector_config: &ConnectorConfig,
+        controller: &ControllerInner,
+    ) -> Result<Option<Self>, ControllerError> {
+        let Some(config) = &connector_config.dead_letter else {
+            return Ok(None);
+        };
+
+        let sink = match config {
+            DeadLetterConfig::Table(table) => {
+                if controller.fault_tolerance.is_some() {
+                    return Err(ControllerError::invalid_dead_letter_configuration(
+                        endpoint_name,
+                        "dead-letter tables are not supported in fault-tolerant pipelines, because dead letters inserted into a table are not replayed consistently after a restart; use a file or a Kafka topic instead",
+                    ));
+                }
+                let name = SqlIdentifier::from(table);
+                let handle = controller
+                    .catalog
+                    .input_collection_handle(&name)
+                    .ok_or_else(|| {
+                        ControllerError::invalid_dead_letter_configuration(
+                            endpoint_name,
+                            &format!("table '{table}' does not exist"),
+                        )
+                    })?;
+                for column in DEAD_LETTER_COLUMNS {
+                    if handle.schema.field(column).is_none() {
+                        return Err(ControllerError::invalid_dead_letter_configuration(
+                            endpoint_name,
+                            &format!("table '{table}' does not have a '{column}' column; dead-letter tables must have columns {}", DEAD_LETTER_COLUMNS.join(", ")),
+                        ));
+                    }
+                }
+
+                let (sender, receiver) = channel(MAX_TABLE_QUEUE);
+                TOKIO.spawn(table_writer(
+                    endpoint_name.to_string(),
+                    name,
+                    controller.session_ctxt.clone(),
+                    receiver,
+                ));
+                DeadLetterSink::Table {
+                    sender,
+                    dropped: AtomicU64::new(0),
+                }
+            }
+            DeadLetterConfig::File(path) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| {
+                        ControllerError::invalid_dead_letter_configuration(
+                            endpoint_name,
+                            &format!("error opening file '{path}': {e}"),
+                        )
+                    })?;
+                DeadLetterSink::File {
+                    path: path.clone(),
+                    file: Mutex::new(file),
+                }
+            }
+            #[cfg(feature = "with-kafka")]
+            DeadLetterConfig::Kafka(config) => {
+                let endpoint = KafkaOutputEndpoint::new(config.to_output_config(), endpoint_name)
+                    .and_then(|mut endpoint| {
+                        let endpoint_name = endpoint_name.to_string();
+                        endpoint.connect(Box::new(move |_fatal, e| {
+                            error!("connector '{endpoint_name}': error writing dead letters to Kafka: {e}")
+                        }))?;
+                        Ok(endpoint)
+                    })
+                    .map_err(|e| {
+                        ControllerError::invalid_dead_letter_configuration(
+                            endpoint_name,
+                            &e.to_string(),
+                        )
+                    })?;
+                DeadLetterSink::Kafka(Mutex::new(endpoint))
+            }
+            #[cfg(not(feature = "with-kafka"))]
+            DeadLetterConfig::Kafka(_) => {
+                return Err(ControllerError::invalid_dead_letter_configuration(
+                    endpoint_name,
+                    "this pipeline was built without Kafka support",
+                ))
+            }
+        };
+
+        Ok(Some(Self {
+            endpoint_name: endpoint_name.to_string(),
+            indexed: connector_config.index.is_some(),
+            sink,
+        }))
+    }
+
+    /// Writes the input that caused a parse error.
+    pub(crate) fn parse_error(&self, error: &ParseError) {
+        self.write(DeadLetter {
+            error: error.description().to_string(),
+            event_number: error.event_number(),
+            step: None,
+            raw_text: error.invalid_text().map(str::to_string),
+            raw_bytes: error.invalid_bytes().map(<[u8]>::to_vec),
+        });
+    }
+
+    /// Writes the records of a batch that failed to encode.
+    ///
+    /// Each record is written as a separate dead letter, whose text is an
+    /// `{"insert": ...}` or `{"delete": ...}` JSON object.  The encoder may
+    /// have output some of the records before failing.
+    pub(crate) fn encode_error(&self, batch: &dyn SerBatchReader, step: Step, error: &AnyError) {
+        let dead_letter = |raw_text| DeadLetter {
+            error: error.to_string(),
+            event_number: None,
+            step: Some(step),
+            raw_text,
+            raw_bytes: None,
+        };
+        match batch_to_json(batch, self.indexed) {
+            Ok(records) => {
+                for record in records {
+                    self.write(dead_letter(Some(record)));
+                }
+            }
+            Err(e) => {
+                error!(
+                    "connector '{}': error converting a batch that failed to encode to JSON: {e}",
+                    self.endpoint_name
+                );
+                self.write(dead_letter(None));
+            }
+        }
+    }
+
+    fn write(&self, dead_letter: DeadLetter) {
+        let result = match &self.sink {
+            DeadLetterSink::Table { sender, dropped } => match sender.try_send(dead_letter) {
+                Err(TrySendError::Full(_)) => {
+                    // Log the first drop and then progressively less often.
+                    let dropped = dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                    if dropped.is_power_of_two() {
+                        Err(anyhow!(
+                            "too many dead letters waiting to be written to the table; {dropped} dead letters dropped so far"
+                        ))
+                    } else {
+                        Ok(())
+                    }
+                }
+                // The receiver only goes away if the runtime shuts down.
+                Err(TrySendError::Closed(_)) | Ok(()) => Ok(()),
+            },
+            DeadLetterSink::File { path, file } => {
+                let line = json!({
+                    "connector": &self.endpoint_name,
+                    "error": dead_letter.error,
+                    "event_number": dead_letter.event_number,
+                    "step": dead_letter.step,
+                    "raw_text": dead_letter.raw_text,
+                    "raw_bytes": dead_letter.raw_bytes.map(|bytes| BASE64_STANDARD.encode(bytes)),
+                });
+                writeln!(file.lock().unwrap(), "{line}")
+                    .map_err(|e| anyhow!("error writing to '{path}': {e}"))
+            }
+            #[cfg(feature = "with-kafka")]
+            DeadLetterSink::Kafka(endpoint) => {
+                let event_number = dead_letter.event_number.map(|n| n.to_string());
+                let step = dead_letter.step.map(|step| step.to_string());
+                let payload = match (&dead_letter.raw_bytes, &dead_letter.raw_text) {
+                    (Some(bytes), _) => Some(bytes.as_slice()),
+                    (None, Some(text)) => Some(text.as_bytes()),
+                    (None, None) => None,
+                };
+                endpoint.lock().unwrap().push_key(
+                    None,
+                    payload,
+                    &[
+                        ("connector", Some(self.endpoint_name.as_bytes())),
+                        ("error", Some(dead_letter.error.as_bytes())),
+                        ("event_number", event_number.as_ref().map(|n| n.as_bytes())),
+                        ("step", step.as_ref().map(|step| step.as_bytes())),
+                    ],
+                )
+            }
+        };
+        if let Err(e) = result {
+            error!(
+                "connector '{}': error writing dead letter: {e}",
+                self.endpoint_name
+            );
+        }
+    }
+}
+
+/// Converts the records in `batch` to JSON in the insert/delete format.
+fn batch_to_json(batch: &dyn SerBatchReader, indexed: bool) -> AnyResult<Vec<String>> {
+    let mut cursor = batch.cursor(RecordFormat::Json(Default::default()))?;
+    let mut records = Vec::new();
+
+    while cursor.key_valid() {
+        while cursor.val_valid() {
+            let w = cursor.weight();
+            // Don't expand records with huge weights, which may well be the
+            // reason the batch failed to encode.
+            let copies = if (-MAX_DUPLICATES..=MAX_DUPLICATES).contains(&w) {
+                w.unsigned_abs()
+            } else {
+                1
+            };
+            for _ in 0..copies {
+                let mut buffer = Vec::new();
+                if w > 0 {
+                    buffer.extend_from_slice(br#"{"insert":"#);
+                } else {
+                    buffer.extend_from_slice(br#"{"delete":"#);
+                }
+                if indexed {
+                    cursor.serialize_val(&mut buffer)?;
+                } else {
+                    cursor.serialize_key(&mut buffer)?;
+                }
+                buffer.push(b'}');
+                records.push(String::from_utf8(buffer)?);
+            }
+            cursor.step_val();
+        }
+        cursor.step_key();
+    }
+
+    Ok(records)
+}
+
+/// Inserts dead letters received from `receiver` into `table`, batching
+/// dead letters that arrive while a previous insert is running.
+async fn table_writer(
+    endpoint_name: String,
+    table: SqlIdentifier,
+    session_ctxt: SessionContext,
+    mut receiver: Receiver<DeadLetter>,
+) {
+    let mut dead_letters = Vec::new();
+    while receiver.recv_many(&mut dead_letters, MAX_TABLE_BATCH).await > 0 {
+        let statement = insert_statement(&endpoint_name, &table, &dead_letters);
+        dead_letters.clear();
+
+        let result = async { session_ctxt.sql(&statement).await?.collect().await }.await;
+        if let Err(e) = result {
+            error!(
+                "connector '{endpoint_name}': error writing dead letters to table '{table}': {e}"
+            );
+        }
+    }
+}
+
+/// Returns an `INSERT` statement that adds `dead_letters` to `table`.
+fn insert_statement(
+    endpoint_name: &str,
+    table: &SqlIdentifier,
+    dead_letters: &[DeadLetter],
+) -> String {
+    fn string_literal(s: &str) -> String {
+        format!("'{}'", s.replace('\'', "''"))
+    }
+
+    let mut statement = format!(
+        "INSERT INTO {} ({}) VALUES ",
+        table.sql_name(),
+        DEAD_LETTER_COLUMNS.join(", ")
+    );
+    for (i, dead_letter) in dead_letters.iter().enumerate() {
+        if i > 0 {
+            statement.push_str(", ");
+        }
+        let event_number = dead_letter
+            .event_number
+            .map_or_else(|| "NULL".to_string(), |n| n.to_string());
+        let step = dead_letter
+            .step
+            .map_or_else(|| "NULL".to_string(), |step| step.to_string());
+        let raw_text = dead_letter
+            .raw_text
+            .as_deref()
+            .map_or_else(|| "NULL".to_string(), string_literal);
+        let raw_bytes = dead_letter.raw_bytes.as_ref().map_or_else(
+            || "NULL".to_string(),
+            |bytes| {
+                let mut literal = String::from("X'");
+                for byte in bytes {
+                    write!(literal, "{byte:02x}").unwrap();
+                }
+                literal.push('\'');
+                literal
+            },
+        );
+        write!(
+            statement,
+            "({}, {}, {event_number}, {step}, {raw_text}, {raw_bytes})",
+            string_literal(endpoint_name),
+            string_literal(&dead_letter.error),
+        )
+        .unwrap();
+    }
+    statement
+}
@@ -65,6 +65,7 @@ use stats::StepResults;
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::future::Future;
 use std::io::ErrorKind;
 use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::sync::atomic::AtomicI64;
@@ -82,19 +83,22 @@ use std::{
     time::{Duration, Instant},
 };
 use tokio::sync::oneshot;
-use tokio::sync::Mutex as TokioMutex;
+use tokio::sync::{watch, Mutex as TokioMutex, OwnedMutexGuard};
 use tracing::{debug, error, info, trace, warn};
 use validate::validate_config;
 
 mod checkpoint;
+mod dead_letter;
 mod error;
 mod journal;
+mod rate_limit;
 mod stats;
 mod validate;
 
 use crate::adhoc::create_session_context;
 use crate::adhoc::table::AdHocTable;
 use crate::catalog::{SerBatchReader, SerTrace, SyncSerBatchReader};
+use crate::controller::dead_letter::DeadLetterWriter;
 use crate::format::parquet::relation_to_arrow_fields;
 use crate::format::{get_input_format, get_output_format};
 use crate::integrated::create_integrated_input_endpoint;
@@ -344,7 +348,56 @@ impl Controller {
         self.inner.fail_if_restoring()?;
 
         self.inner
-            .add_output_endpoint(endpoint_name, endpoint_config, Some(endpoint))
+            .add_output_endpoint(endpoint_name, endpoint_config, Some(endpoint), None)
+    }
+
+    /// Returns a future that locks the snapshot of materialized tables and
+    /// views that ad hoc queries read, for
+    /// [`Self::add_output_endpoint_with_snapshot`].
+    ///
+    /// After the pipeline resumes from a checkpoint, the snapshot is empty
+    /// until the circuit completes its first step, so the future waits for
+    /// that step.  The future does not borrow the controller, so it can be
+    /// awaited without holding a lock on it.
+    pub fn lock_trace_snapshot(&self) -> impl Future<Output = TraceSnapshotGuard> + 'static {
+        let trace_snapshot = self.inner.trace_snapshot.clone();
+        let mut ready = self.inner.trace_snapshot_ready.subscribe();
+        async move {
+            // The sender lives as long as the controller, and `trace_snapshot`
+            // is still usable if the controller is gone.
+            let _ = ready.wait_for(|ready| *ready).await;
+            trace_snapshot.lock_owned().await
+        }
+    }
+
+    /// Connect a previously instantiated output endpoint, starting with a
+    /// snapshot of its table or view.
+    ///
+    /// Like [`Self::add_output_endpoint`], but the endpoint first receives the
+    /// contents of the table or view in `snapshot`, obtained from
+    /// [`Self::lock_trace_snapshot`], as a single step labeled with the step
+    /// number that the snapshot reflects.  It then receives the changes
+    /// computed by all subsequent steps.
+    ///
+    /// Fails if the table or view is not materialized.
+    pub fn add_output_endpoint_with_snapshot(
+        &self,
+        endpoint_name: &str,
+        endpoint_config: &OutputEndpointConfig,
+        endpoint: Box<dyn OutputEndpoint>,
+        snapshot: TraceSnapshotGuard,
+    ) -> Result<EndpointId, ControllerError> {
+        debug!(
+            "Adding output endpoint '{endpoint_name}' with snapshot; config: {endpoint_config:?}"
+        );
+        self.inner.fail_if_restoring()?;
+
+        self.inner.add_output_endpoint(
+            endpoint_name,
+            endpoint_config,
+            Some(endpoint),
+            Some(snapshot),
+        )
     }
 
     /// Increment the number of active API connections.
@@ -650,6 +703,14 @@ impl CircuitThread {
         let lir = circuit.lir()?;
         let (parker, backpressure_thread, command_receiver, controller) =
             ControllerInner::new(pipeline_config, catalog, lir, error_cb, processed_records)?;
+        controller
+            .trace_snapshot_step
+            .store(step, Ordering::Release);
+        if input_metadata.is_some() {
+            // The restored tables and views aren't in `trace_snapshot` until
+            // the first step.
+            controller.trace_snapshot_ready.send_replace(false);
+        }
 
         controller
             .status
@@ -893,6 +954,12 @@ impl CircuitThread {
                 consistent_snapshot.insert(name.clone(), ih.take_from_all());
             }
         }
+        self.controller
+            .trace_snapshot_step
+            .store(self.step, Ordering::Release);
+        self.controller
+            .trace_snapshot_ready
+            .send_if_modified(|ready| !mem::replace(ready, true));
     }
 
     fn checkpoint_requested(&self) -> bool {
@@ -965,6 +1032,11 @@ impl CircuitThread {
             if let Some(ft) = &mut this.ft {
                 ft.checkpointed()?;
             }
+            for status in this.controller.status.input_status().values() {
+                if let Some(metadata) = checkpoint.input_metadata.0.get(&status.endpoint_name) {
+                    status.reader.checkpointed(metadata);
+                }
+            }
             Ok(checkpoint)
         }
 
@@ -1299,6 +1371,12 @@ impl CircuitThread {
             for (i, endpoint_id) in endpoints.iter().enumerate() {
                 let endpoint = outputs.lookup_by_id(endpoint_id).unwrap();
 
+                // The endpoint started with a snapshot that already includes
+                // the output of this step.
+                if self.step < endpoint.first_step {
+                    continue;
+                }
+
                 self.controller
                     .status
                     .enqueue_batch(*endpoint_id, num_delta_records);
@@ -1865,6 +1943,7 @@ impl ControllerInit {
                 max_parallel_connector_init: config.global.max_parallel_connector_init,
                 init_containers: config.global.init_containers,
                 checkpoint_during_suspend: config.global.checkpoint_during_suspend,
+                flight_port: config.global.flight_port,
                 dev_tweaks: BTreeMap::new(),
             },
 
@@ -1996,11 +2075,22 @@ impl BackpressureThread {
 
             let bootstrap_in_progress = controller.status.bootstrap_in_progress();
 
+            // Wake up when the first endpoint that exceeded its rate limits
+            // gets back within them.
+            let mut timeout: Option<Duration> = None;
+
             for (epid, ep) in controller.status.input_status().iter() {
+                let throttled_for = ep.throttled_for();
+                if let Some(throttled_for) = throttled_for {
+                    timeout = Some(timeout.map_or(throttled_for, |t| t.min(throttled_for)));
+                }
+                ep.throttled
+                    .store(throttled_for.is_some(), Ordering::Release);
                 let should_run = globally_running
                     && !bootstrap_in_progress
                     && !ep.is_paused_by_user()
-                    && !ep.is_full();
+                    && !ep.is_full()
+                    && throttled_for.is_none();
                 match should_run {
                     true => {
                         if running_endpoints.insert(*epid) {
@@ -2015,7 +2105,10 @@ impl BackpressureThread {
                 }
             }
 
-            parker.park();
+            match timeout {
+                Some(timeout) => parker.park_timeout(timeout),
+                None => parker.park(),
+            }
         }
     }
 }
@@ -2127,6 +2220,12 @@ struct OutputEndpointDescr {
 
     /// Unparker for the endpoint thread.
     unparker: Unparker,
+
+    /// First step whose output is sent to the endpoint.
+    ///
+    /// Nonzero for an endpoint that started with a snapshot of the stream,
+    /// which already includes the output of earlier steps.
+    first_step: Step,
 }
 
 impl OutputEndpointDescr {
@@ -2137,6 +2236,7 @@ impl OutputEndpointDescr {
             queue: Arc::new(SegQueue::new()),
             disconnect_flag: Arc::new(AtomicBool::new(false)),
             unparker,
+            first_step: 0,
         }
     }
 }
@@ -2285,6 +2385,10 @@ impl OutputBuffer {
 pub type ConsistentSnapshots =
     Arc<TokioMutex<BTreeMap<SqlIdentifier, Vec<Arc<dyn SyncSerBatchReader>>>>>;
 
+/// A locked [`ConsistentSnapshots`]; see [`Controller::lock_trace_snapshot`].
+pub type TraceSnapshotGuard =
+    OwnedMutexGuard<BTreeMap<SqlIdentifier, Vec<Arc<dyn SyncSerBatchReader>>>>;
+
 /// Controller state sharable across threads.
 ///
 /// A reference to this struct is held by each input probe and by both
@@ -2297,6 +2401,13 @@ pub struct ControllerInner {
     lir: LirCircuit,
     // Always lock this after the catalog is locked to avoid deadlocks
     trace_snapshot: ConsistentSnapshots,
+    /// The step that `trace_snapshot` reflects.  Only modified while holding
+    /// the `trace_snapshot` lock.
+    trace_snapshot_step: AtomicU64,
+    /// False while `trace_snapshot` is known not to reflect the circuit's
+    /// contents, that is, after restoring from a checkpoint and before the
+    /// first step.
+    trace_snapshot_ready: watch::Sender<bool>,
     next_input_id: Atomic<EndpointId>,
     outputs: ShardedLock<OutputEndpoints>,
     next_output_id: Atomic<EndpointId>,
@@ -2330,6 +2441,8 @@ impl ControllerInner {
             catalog: Arc::new(catalog),
             lir,
             trace_snapshot: Arc::new(TokioMutex::new(BTreeMap::new())),
+            trace_snapshot_step: AtomicU64::new(0),
+            trace_snapshot_ready: watch::Sender::new(true),
             next_input_id: Atomic::new(0),
             outputs: ShardedLock::new(OutputEndpoints::new()),
             next_output_id: Atomic::new(0),
@@ -2501,6 +2614,27 @@ impl ControllerInner {
                 ControllerError::unknown_input_stream(endpoint_name, &endpoint_config.stream)
             })?;
 
+        for (property, limit) in [
+            (
+                "max_records_per_second",
+                endpoint_config.connector_config.max_records_per_second,
+            ),
+            (
+                "max_bytes_per_second",
+                endpoint_config.connector_config.max_bytes_per_second,
+            ),
+        ] {
+            if limit == Some(0) {
+                return Err(ControllerError::invalid_rate_limit(
+                    endpoint_name,
+                    &format!("'{property}' must be greater than 0"),
+                ));
+            }
+        }
+
+        let dead_letter =
+            DeadLetterWriter::new(endpoint_name, &endpoint_config.connector_config, self)?;
+
         let endpoint_id = self.next_input_id.fetch_add(1, Ordering::AcqRel);
 
         let probe = Box::new(InputProbe::new(
@@ -2508,6 +2642,7 @@ impl ControllerInner {
             endpoint_name,
             &endpoint_config.connector_config,
             self.clone(),
+            dead_letter,
         ));
         let (reader, fault_tolerance) = match endpoint {
             Some(endpoint) => {
@@ -2624,6 +2759,7 @@ impl ControllerInner {
     ) -> Result<EndpointId, ControllerError> {
         let endpoint = output_transport_config_to_endpoint(
             endpoint_config.connector_config.transport.clone(),
+            endpoint_config.connector_config.format.as_ref(),
             endpoint_name,
             self.fault_tolerance == Some(FtModel::ExactlyOnce),
         )
@@ -2631,7 +2767,7 @@ impl ControllerInner {
 
         // If `endpoint` is `None`, it means that the endpoint config specifies an integrated
         // output connector.  Such endpoints are instantiated inside `add_output_endpoint`.
-        self.add_output_endpoint(endpoint_name, endpoint_config, endpoint)
+        self.add_output_endpoint(endpoint_name, endpoint_config, endpoint, None)
     }
 
     fn disconnect_output(&self, endpoint_id: &EndpointId) {
@@ -2651,6 +2787,7 @@ impl ControllerInner {
         endpoint_name: &str,
         endpoint_config: &OutputEndpointConfig,
         endpoint: Option<Box<dyn OutputEndpoint>>,
+        snapshots: Option<TraceSnapshotGuard>,
     ) -> Result<EndpointId, ControllerError> {
         // NOTE: We release the lock after the check below and then re-acquire it in the end of the function
         // to actually insert the new inpoint in the map. This means that this function is racey (a concurrent
@@ -2721,6 +2858,13 @@ impl ControllerInner {
             )
         };
 
+        if snapshots.is_some() && handles.integrate_handle.is_none() {
+            return Err(ControllerError::not_materialized(
+                endpoint_name,
+                &stream_name,
+            ));
+        }
+
         let endpoint_id = self.next_output_id.fetch_add(1, Ordering::AcqRel);
         let endpoint_name_str = endpoint_name.to_string();
 
@@ -2732,6 +2876,9 @@ impl ControllerInner {
             .validate()
             .map_err(|e| ControllerError::invalid_output_buffer_configuration(endpoint_name, &e))?;
 
+        let dead_letter =
+            DeadLetterWriter::new(endpoint_name, &endpoint_config.connector_config, self)?;
+
         let encoder = if let Some(mut endpoint) = endpoint {
             endpoint
                 .connect(Box::new(move |fatal: bool, e: AnyError| {
@@ -2782,16 +2929,33 @@ impl ControllerInner {
         };
 
         let parker = Parker::new();
-        let endpoint_descr =
+        let mut endpoint_descr =
             OutputEndpointDescr::new(endpoint_name, &stream_name, parker.unparker().clone());
         let queue = endpoint_descr.queue.clone();
         let disconnect_flag = endpoint_descr.disconnect_flag.clone();
         let controller = self.clone();
 
+        // Hold the snapshot lock until the endpoint is registered, so that the
+        // circuit thread cannot complete another step between taking the
+        // snapshot and sending its output to the endpoint.  The circuit thread
+        // may already have updated the snapshot for the current step but not
+        // yet pushed its output; `first_step` makes sure that the endpoint
+        // skips it.
+        let snapshot = snapshots.as_ref().map(|snapshots| {
+            let step = self.trace_snapshot_step.load(Ordering::Acquire);
+            endpoint_descr.first_step = step + 1;
+            let batches = snapshots
+                .get(&SqlIdentifier::from(&stream_name))
+                .cloned()
+                .unwrap_or_default();
+            (step, batches)
+        });
+
         self.outputs
             .write()
             .unwrap()
             .insert(endpoint_id, handles.clone(), endpoint_descr);
+        drop(snapshots);
 
         let endpoint_name_string = endpoint_name.to_string();
         let output_buffer_config = endpoint_config
@@ -2811,8 +2975,10 @@ impl ControllerInner {
                 output_buffer_config,
                 encoder,
                 parker,
+                snapshot,
                 queue,
                 disconnect_flag,
+                dead_letter,
                 controller,
             )
         });
@@ -2832,12 +2998,39 @@ impl ControllerInner {
         endpoint_name: &str,
         encoder: &mut dyn Encoder,
         step: Step,
+        dead_letter: Option<&DeadLetterWriter>,
         controller: &ControllerInner,
     ) {
         encoder.consumer().batch_start(step);
-        encoder
-            .encode(batch)
-            .unwrap_or_else(|e| controller.encode_error(endpoint_id, endpoint_name, e));
+        encoder.encode(batch).unwrap_or_else(|e| {
+            if let Some(dead_letter) = dead_letter {
+                dead_letter.encode_error(batch, step, &e);
+            }
+            controller.encode_error(endpoint_id, endpoint_name, e)
+        });
+        encoder.consumer().batch_end();
+    }
+
+    /// Push a snapshot of a stream, consisting of one batch per worker, to
+    /// the encoder as a single step.
+    fn push_snapshot_to_encoder(
+        snapshot: &[Arc<dyn SyncSerBatchReader>],
+        endpoint_id: EndpointId,
+        endpoint_name: &str,
+        encoder: &mut dyn Encoder,
+        step: Step,
+        dead_letter: Option<&DeadLetterWriter>,
+        controller: &ControllerInner,
+    ) {
+        encoder.consumer().batch_start(step);
+        for batch in snapshot {
+            encoder.encode(batch.as_ref()).unwrap_or_else(|e| {
+                if let Some(dead_letter) = dead_letter {
+                    dead_letter.encode_error(batch.as_ref(), step, &e);
+                }
+                controller.encode_error(endpoint_id, endpoint_name, e)
+            });
+        }
         encoder.consumer().batch_end();
     }
 
@@ -2848,12 +3041,27 @@ impl ControllerInner {
         output_buffer_config: OutputBufferConfig,
         mut encoder: Box<dyn Encoder>,
         parker: Parker,
+        snapshot: Option<(Step, Vec<Arc<dyn SyncSerBatchReader>>)>,
         queue: Arc<BatchQueue>,
         disconnect_flag: Arc<AtomicBool>,
+        dead_letter: Option<DeadLetterWriter>,
         controller: Arc<ControllerInner>,
     ) {
         let mut output_buffer = OutputBuffer::new(&endpoint_name);
 
+        // The snapshot precedes all batches in the queue.
+        if let Some((step, snapshot)) = snapshot {
+            Self::push_snapshot_to_encoder(
+                &snapshot,
+                endpoint_id,
+                &endpoint_name,
+                encoder.as_mut(),
+                step,
+                dead_letter.as_ref(),
+                &controller,
+            );
+        }
+
         loop {
             if controller.state() == PipelineState::Terminated {
                 return;
@@ -2873,6 +3081,7 @@ impl ControllerInner {
                     &endpoint_name,
                     encoder.as_mut(),
                     output_buffer.buffered_step,
+                    dead_letter.as_ref(),
                     &controller,
                 );
 
@@ -2904,6 +3113,7 @@ impl ControllerInner {
                         &endpoint_name,
                         encoder.as_mut(),
                         step,
+                        dead_letter.as_ref(),
                         &controller,
                     );
 
@@ -3220,6 +3430,7 @@ struct InputProbe {
     endpoint_name: String,
     controller: Arc<ControllerInner>,
     max_batch_size: usize,
+    dead_letter: Option<DeadLetterWriter>,
 }
 
 impl InputProbe {
@@ -3228,12 +3439,14 @@ impl InputProbe {
         endpoint_name: &str,
         connector_config: &ConnectorConfig,
         controller: Arc<ControllerInner>,
+        dead_letter: Option<DeadLetterWriter>,
     ) -> Self {
         Self {
             endpoint_id,
             endpoint_name: endpoint_name.to_owned(),
             controller,
             max_batch_size: connector_config.max_batch_size as usize,
+            dead_letter,
         }
     }
 }
@@ -3260,7 +3473,16 @@ impl InputConsumer for InputProbe {
     }
 
     fn parse_errors(&self, errors: Vec<ParseError>) {
+        // Inputs replayed from the journal were already dead-lettered before
+        // the restart.
+        let dead_letter = self
+            .dead_letter
+            .as_ref()
+            .filter(|_| !self.controller.restoring.load(Ordering::Acquire));
         for error in errors {
+            if let Some(dead_letter) = dead_letter {
+                dead_letter.parse_error(&error);
+            }
             self.controller
                 .parse_error(self.endpoint_id, &self.endpoint_name, error);
         }
@@ -3362,7 +3584,7 @@ impl OutputConsumer for OutputProbe {
     fn push_buffer(&mut self, buffer: &[u8], num_records: usize) {
         let num_bytes = buffer.len();
 
-        match self.endpoint.push_buffer(buffer) {
+        match self.endpoint.push_buffer_with_records(buffer, num_records) {
             Ok(()) => {
                 self.controller
                     .status
@@ -3406,6 +3628,34 @@ impl OutputConsumer for OutputProbe {
         }
     }
 
+    fn push_key_to_topic(
+        &mut self,
+        topic: &str,
+        key: Option<&[u8]>,
+        val: Option<&[u8]>,
+        headers: &[(&str, Option<&[u8]>)],
+        num_records: usize,
+    ) {
+        let num_bytes =
+            key.map(|k| k.len()).unwrap_or_default() + val.map(|v| v.len()).unwrap_or_default();
+
+        match self.endpoint.push_key_to_topic(topic, key, val, headers) {
+            Ok(()) => {
+                self.controller
+                    .status
+                    .output_buffer(self.endpoint_id, num_bytes, num_records);
+            }
+            Err(error) => {
+                self.controller.output_transport_error(
+                    self.endpoint_id,
+                    &self.endpoint_name,
+                    false,
+                    error,
+                );
+            }
+        }
+    }
+
     fn batch_end(&mut self) {
         self.endpoint.batch_end().unwrap_or_else(|e| {
             self.controller
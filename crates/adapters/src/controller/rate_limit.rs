@@ -0,0 +1,162 @@
+//! Ingestion rate limits for input connectors.
+//!
+//! Input connectors report the records and bytes they receive after the fact,
+//! via [`InputConsumer::buffered`](crate::InputConsumer::buffered), so the
+//! controller cannot hold back individual records.  Instead, it charges them to
+//! the connector's rate limiter, and the backpressure thread pauses the
+//! connector while it is over its limit, the same way it pauses connectors
+//! whose queue is full.
+
+use feldera_types::config::ConnectorConfig;
+use std::{
+    cmp::max,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// A connector may run ahead of its average rate by this much before it gets
+/// paused.
+const BURST: Duration = Duration::from_secs(1);
+
+/// A single rate limit, implemented using the generic cell rate algorithm
+/// (GCRA) modified to allow the connector to go into debt, since we can only
+/// charge records after they've been received.
+struct Limit {
+    /// Cost of one record or byte, in nanoseconds.
+    nanos_per_unit: f64,
+
+    /// Theoretical arrival time, in nanoseconds since
+    /// [`InputRateLimiter::start`]: the time at which the connector will have
+    /// paid off everything it received so far.
+    tat: AtomicU64,
+}
+
+impl Limit {
+    fn new(units_per_second: u64) -> Self {
+        Self {
+            nanos_per_unit: 1_000_000_000.0 / units_per_second as f64,
+            tat: AtomicU64::new(0),
+        }
+    }
+
+    /// Charges `n` units at time `now`.
+    fn charge(&self, now: u64, n: u64) {
+        if n > 0 {
+            let cost = (n as f64 * self.nanos_per_unit) as u64;
+            let _ = self
+                .tat
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |tat| {
+                    Some(max(tat, now).saturating_add(cost))
+                });
+        }
+    }
+
+    /// Returns the time at which the connector is back within its limit.
+    fn resume_at(&self) -> u64 {
+        self.tat
+            .load(Ordering::Acquire)
+            .saturating_sub(BURST.as_nanos() as u64)
+    }
+}
+
+/// Limits the rate at which an input connector ingests records and bytes.
+pub(crate) struct InputRateLimiter {
+    start: Instant,
+    records: Option<Limit>,
+    bytes: Option<Limit>,
+}
+
+impl InputRateLimiter {
+    /// Creates a rate limiter for a connector, if it has any rate limits.
+    pub(crate) fn new(connector_config: &ConnectorConfig) -> Option<Self> {
+        let records = connector_config.max_records_per_second.map(Limit::new);
+        let bytes = connector_config.max_bytes_per_second.map(Limit::new);
+        (records.is_some() || bytes.is_some()).then(|| Self {
+            start: Instant::now(),
+            records,
+            bytes,
+        })
+    }
+
+    fn now(&self) -> u64 {
+        self.start.elapsed().as_nanos() as u64
+    }
+
+    /// Charges records and bytes received by the connector.
+    ///
+    /// Returns `true` if this caused the connector to exceed its limits.
+    pub(crate) fn charge(&self, num_records: u64, num_bytes: u64) -> bool {
+        let now = self.now();
+        let was_throttled = self.resume_at() > now;
+        if let Some(records) = &self.records {
+            records.charge(now, num_records);
+        }
+        if let Some(bytes) = &self.bytes {
+            bytes.charge(now, num_bytes);
+        }
+        !was_throttled && self.resume_at() > now
+    }
+
+    fn resume_at(&self) -> u64 {
+        max(
+            self.records.as_ref().map_or(0, Limit::resume_at),
+            self.bytes.as_ref().map_or(0, Limit::resume_at),
+        )
+    }
+
+    /// Returns how much longer the connector must stay paused to get back
+    /// within its limits, or `None` if it is within its limits.
+    pub(crate) fn throttled_for(&self) -> Option<Duration> {
+        let now = self.now();
+        let resume_at = self.resume_at();
+        (resume_at > now).then(|| Duration::from_nanos(resume_at - now))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::InputRateLimiter;
+    use feldera_types::config::ConnectorConfig;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn rate_limiter(
+        max_records_per_second: Option<u64>,
+        max_bytes_per_second: Option<u64>,
+    ) -> Option<InputRateLimiter> {
+        let config: ConnectorConfig = serde_json::from_value(json!({
+            "transport": {"name": "file_input", "config": {"path": "input.json"}},
+            "max_records_per_second": max_records_per_second,
+            "max_bytes_per_second": max_bytes_per_second,
+        }))
+        .unwrap();
+        InputRateLimiter::new(&config)
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        assert!(rate_limiter(None, None).is_none());
+
+        // One second's worth of records is allowed as a burst.
+        let limiter = rate_limiter(Some(100), None).unwrap();
+        assert!(!limiter.charge(90, 1_000_000));
+        assert_eq!(limiter.throttled_for(), None);
+
+        // Exceeding it throttles the connector until it is back within the
+        // limit, which takes about 0.9 seconds here.
+        assert!(limiter.charge(100, 0));
+        let throttled_for = limiter.throttled_for().unwrap();
+        assert!(throttled_for > Duration::from_millis(800));
+        assert!(throttled_for <= Duration::from_millis(900));
+
+        // Charging more while throttled extends the pause.
+        assert!(!limiter.charge(100, 0));
+        assert!(limiter.throttled_for().unwrap() > Duration::from_millis(1800));
+
+        // Either limit can throttle the connector.
+        let limiter = rate_limiter(Some(1000), Some(1000)).unwrap();
+        assert!(!limiter.charge(10, 500));
+        assert!(limiter.charge(10, 1000));
+        assert!(limiter.throttled_for().unwrap() > Duration::from_millis(400));
+    }
+}
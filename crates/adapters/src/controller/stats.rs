@@ -30,6 +30,7 @@
 //! by the circuit, but the counter shows that 10 records are still
 //! pending.
 
+use super::rate_limit::InputRateLimiter;
 use super::{EndpointId, InputEndpointConfig, OutputEndpointConfig};
 use crate::{
     controller::journal::{InputChecksums, InputLog},
@@ -64,6 +65,7 @@ use std::{
         atomic::{AtomicBool, AtomicU64, Ordering},
         Arc, Mutex,
     },
+    time::Duration,
 };
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
@@ -880,7 +882,8 @@ impl ControllerStatus {
     ///   thread if the total number of buffered records exceeds
     ///   `min_batch_size_records`.
     /// * `backpressure_thread_unparker` - unparker used to wake up the
-    ///   backpressure thread if the endpoint is full.
+    ///   backpressure thread if the endpoint is full or exceeds its rate
+    ///   limits.
     pub(super) fn input_batch_from_endpoint(
         &self,
         endpoint_id: EndpointId,
@@ -898,6 +901,11 @@ impl ControllerStatus {
                 if old < threshold && old + num_records as u64 >= threshold {
                     backpressure_thread_unparker.unpark();
                 }
+                if let Some(rate_limiter) = &endpoint_stats.rate_limiter {
+                    if rate_limiter.charge(num_records as u64, num_bytes as u64) {
+                        backpressure_thread_unparker.unpark();
+                    }
+                }
             }
         }
     }
@@ -1411,9 +1419,21 @@ pub struct InputEndpointStatus {
     /// endpoint or endpoints advance beyond the barriers.
     pub barrier: AtomicBool,
 
+    /// Endpoint is paused because it exceeded its `max_records_per_second`
+    /// or `max_bytes_per_second` limit.
+    ///
+    /// Maintained by the backpressure thread, which pauses the endpoint until
+    /// it is back within its limits.
+    pub throttled: AtomicBool,
+
     /// Completion tokens associated with the endpoint.
     #[serde(skip)]
     completion_tokens: TokenList,
+
+    /// Enforces the endpoint's `max_records_per_second` and
+    /// `max_bytes_per_second` limits, if any.
+    #[serde(skip)]
+    rate_limiter: Option<InputRateLimiter>,
 }
 
 impl InputEndpointStatus {
@@ -1425,6 +1445,7 @@ impl InputEndpointStatus {
     ) -> Self {
         let paused_by_user =
             config.connector_config.paused || config.connector_config.start_after.is_some();
+        let rate_limiter = InputRateLimiter::new(&config.connector_config);
 
         Self {
             endpoint_name: endpoint_name.to_string(),
@@ -1434,9 +1455,11 @@ impl InputEndpointStatus {
             progress: Mutex::new(None),
             paused: AtomicBool::new(paused_by_user),
             barrier: AtomicBool::new(false),
+            throttled: AtomicBool::new(false),
             reader,
             fault_tolerance,
             completion_tokens: TokenList::new(),
+            rate_limiter,
         }
     }
 
@@ -1506,6 +1529,14 @@ impl InputEndpointStatus {
         buffered_records >= max_queued_records
     }
 
+    /// Returns how much longer the endpoint must stay paused to get back
+    /// within its rate limits, or `None` if it is within its limits.
+    pub fn throttled_for(&self) -> Option<Duration> {
+        self.rate_limiter
+            .as_ref()
+            .and_then(InputRateLimiter::throttled_for)
+    }
+
     /// Endpoint pushed additional records to the circuit.
     ///
     /// Updates:
@@ -1,13 +1,22 @@
 use crate::{
     test::{
-        generate_test_batch, init_test_logger, test_circuit, wait, TestStruct, DEFAULT_TIMEOUT_MS,
+        generate_test_batch, init_test_logger, test_circuit, wait, DeadLetterStruct, TestStruct,
+        DEFAULT_TIMEOUT_MS,
     },
     transport::set_barrier,
-    Controller, PipelineConfig,
+    Catalog, CircuitCatalog, Controller, PipelineConfig,
 };
 use csv::{ReaderBuilder as CsvReaderBuilder, WriterBuilder as CsvWriterBuilder};
+use dbsp::{
+    circuit::{tokio::TOKIO, CircuitConfig},
+    DBSPHandle, Runtime,
+};
+use feldera_types::program_schema::{Relation, SqlIdentifier};
+use futures::FutureExt;
+use serde_json::json;
 use std::{
     cmp::min,
+    collections::BTreeMap,
     fmt::Write as _,
     fs::{create_dir, remove_file, File},
     io::Write,
@@ -16,7 +25,7 @@ use std::{
     path::{Path, PathBuf},
     sync::{atomic::Ordering, mpsc},
     thread::sleep,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tempfile::{NamedTempFile, TempDir};
 use tokio::sync::oneshot;
@@ -174,6 +183,440 @@ inputs:
     controller.stop().unwrap();
 }
 
+#[test]
+fn test_dead_letter_file() {
+    init_test_logger();
+
+    let temp_input_file = NamedTempFile::new().unwrap();
+    temp_input_file
+        .as_file()
+        .write_all(
+            br#"{"id": 1, "b": true, "s": "foo"}
+{"id": "two", "b": true, "s": "foo"}
+{"id": 3, "b": true, "s": "foo"}
+"#,
+        )
+        .unwrap();
+    let dead_letter_dir = TempDir::new().unwrap();
+    let dead_letter_path = dead_letter_dir.path().join("dead_letters.json");
+
+    let config_str = format!(
+        r#"
+name: test
+workers: 4
+inputs:
+    test_input1.endpoint1:
+        stream: test_input1
+        transport:
+            name: file_input
+            config:
+                path: {:?}
+        format:
+            name: json
+            config:
+                update_format: raw
+        dead_letter:
+            file: {:?}
+    "#,
+        temp_input_file.path().to_str().unwrap(),
+        dead_letter_path.to_str().unwrap(),
+    );
+
+    let config: PipelineConfig = serde_yaml::from_str(&config_str).unwrap();
+    let controller = Controller::with_config(
+        |circuit_config| {
+            Ok(test_circuit::<TestStruct>(
+                circuit_config,
+                &TestStruct::schema(),
+                &[None],
+            ))
+        },
+        &config,
+        Box::new(|e| info!("error: {e}")),
+    )
+    .unwrap();
+
+    controller.start();
+    wait(|| controller.pipeline_complete(), DEFAULT_TIMEOUT_MS).unwrap();
+
+    // The valid records are processed; the invalid one is dead-lettered.
+    let result = controller
+        .execute_query_text_sync("select id from test_output1 order by id")
+        .unwrap();
+    let expected = r#"+----+
+| id |
++----+
+| 1  |
+| 3  |
++----+"#;
+    assert_eq!(&result, expected);
+    controller.stop().unwrap();
+
+    let dead_letters = std::fs::read_to_string(&dead_letter_path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0]["connector"], "test_input1.endpoint1");
+    assert_eq!(dead_letters[0]["event_number"], 2);
+    assert_eq!(dead_letters[0]["step"], serde_json::Value::Null);
+    assert_eq!(
+        dead_letters[0]["raw_text"],
+        r#"{"id": "two", "b": true, "s": "foo"}"#
+    );
+    assert!(dead_letters[0]["error"]
+        .as_str()
+        .unwrap()
+        .contains("failed to deserialize JSON record"));
+}
+
+/// The circuit built by [`test_circuit`] for `TestStruct`, plus a
+/// materialized `dead_letters` table.
+fn dead_letter_test_circuit(config: CircuitConfig) -> (DBSPHandle, Box<dyn CircuitCatalog>) {
+    let relation = |name: &str, schema, materialized| {
+        serde_json::to_string(&Relation::new(
+            name.into(),
+            schema,
+            materialized,
+            BTreeMap::new(),
+        ))
+        .unwrap()
+    };
+
+    let (circuit, catalog) = Runtime::init_circuit(config, move |circuit| {
+        let mut catalog = Catalog::new();
+
+        let (input, hinput) = circuit.add_input_zset::<TestStruct>();
+        input.set_persistent_mir_id("input");
+        catalog.register_materialized_input_zset(
+            input.clone(),
+            hinput,
+            &relation("test_input1", TestStruct::schema(), false),
+        );
+        catalog.register_materialized_output_zset_persistent(
+            None,
+            input,
+            &relation("test_output1", TestStruct::schema(), false),
+        );
+
+        let (dead_letters, hdead_letters) = circuit.add_input_zset::<DeadLetterStruct>();
+        dead_letters.set_persistent_mir_id("dead_letters");
+        catalog.register_materialized_input_zset(
+            dead_letters,
+            hdead_letters,
+            &relation("dead_letters", DeadLetterStruct::schema(), true),
+        );
+        Ok(catalog)
+    })
+    .unwrap();
+    (circuit, Box::new(catalog))
+}
+
+#[test]
+fn test_dead_letter_table() {
+    init_test_logger();
+
+    let temp_input_file = NamedTempFile::new().unwrap();
+    temp_input_file
+        .as_file()
+        .write_all(
+            br#"{"id": 1, "b": true, "s": "foo"}
+{"id": "two", "b": true, "s": "it's"}
+"#,
+        )
+        .unwrap();
+
+    let config_str = format!(
+        r#"
+name: test
+workers: 4
+inputs:
+    test_input1.endpoint1:
+        stream: test_input1
+        transport:
+            name: file_input
+            config:
+                path: {:?}
+        format:
+            name: json
+            config:
+                update_format: raw
+        dead_letter:
+            table: dead_letters
+    "#,
+        temp_input_file.path().to_str().unwrap(),
+    );
+
+    let config: PipelineConfig = serde_yaml::from_str(&config_str).unwrap();
+    let controller = Controller::with_config(
+        |circuit_config| Ok(dead_letter_test_circuit(circuit_config)),
+        &config,
+        Box::new(|e| info!("error: {e}")),
+    )
+    .unwrap();
+
+    controller.start();
+    wait(|| controller.pipeline_complete(), DEFAULT_TIMEOUT_MS).unwrap();
+
+    // Dead letters are inserted asynchronously.
+    let query = "select connector, event_number, step, raw_text, raw_bytes from dead_letters";
+    let expected = r#"+-----------------------+--------------+------+---------------------------------------+-----------+
+| connector             | event_number | step | raw_text                              | raw_bytes |
++-----------------------+--------------+------+---------------------------------------+-----------+
+| test_input1.endpoint1 | 2            |      | {"id": "two", "b": true, "s": "it's"} |           |
++-----------------------+--------------+------+---------------------------------------+-----------+"#;
+    wait(
+        || controller.execute_query_text_sync(query).unwrap() == expected,
+        DEFAULT_TIMEOUT_MS,
+    )
+    .unwrap();
+    controller.stop().unwrap();
+
+    // Dead-letter tables are not supported in fault-tolerant pipelines.
+    let storage_dir = TempDir::new().unwrap();
+    let ft_config_str = format!(
+        r#"
+storage_config:
+    path: {:?}
+storage: true
+fault_tolerance: {{}}
+{config_str}"#,
+        storage_dir.path()
+    );
+    let config: PipelineConfig = serde_yaml::from_str(&ft_config_str).unwrap();
+    let Err(err) = Controller::with_config(
+        |circuit_config| Ok(dead_letter_test_circuit(circuit_config)),
+        &config,
+        Box::new(|e| panic!("error: {e}")),
+    ) else {
+        panic!("expected to fail")
+    };
+    assert!(err
+        .to_string()
+        .contains("dead-letter tables are not supported in fault-tolerant pipelines"));
+
+    // The table must have the dead-letter columns.
+    let config_str = config_str.replace("table: dead_letters", "table: test_input1");
+    let config: PipelineConfig = serde_yaml::from_str(&config_str).unwrap();
+    let Err(err) = Controller::with_config(
+        |circuit_config| Ok(dead_letter_test_circuit(circuit_config)),
+        &config,
+        Box::new(|e| panic!("error: {e}")),
+    ) else {
+        panic!("expected to fail")
+    };
+    assert!(err
+        .to_string()
+        .contains("table 'test_input1' does not have a 'connector' column"));
+}
+
+/// Records that an output connector fails to encode are written to its
+/// dead-letter destination, one dead letter per record.
+#[test]
+fn test_dead_letter_encode_error() {
+    init_test_logger();
+
+    let temp_input_file = NamedTempFile::new().unwrap();
+    temp_input_file
+        .as_file()
+        .write_all(
+            br#"{"id": 1, "b": true, "s": "foo"}
+{"id": 2, "b": false, "s": "bar"}
+"#,
+        )
+        .unwrap();
+    let output_dir = TempDir::new().unwrap();
+    let dead_letter_path = output_dir.path().join("dead_letters.json");
+
+    // Every record exceeds the maximum object size, so the encoder fails.
+    let config_str = format!(
+        r#"
+name: test
+workers: 4
+inputs:
+    test_input1.endpoint1:
+        stream: test_input1
+        transport:
+            name: file_input
+            config:
+                path: {:?}
+        format:
+            name: json
+            config:
+                update_format: raw
+outputs:
+    test_output1:
+        stream: test_output1
+        transport:
+            name: object_store_output
+            config:
+                url: {:?}
+                max_object_size_bytes: 10
+        format:
+            name: json
+            config:
+                update_format: insert_delete
+        dead_letter:
+            file: {:?}
+    "#,
+        temp_input_file.path().to_str().unwrap(),
+        format!("file://{}/out", output_dir.path().display()),
+        dead_letter_path.to_str().unwrap(),
+    );
+
+    let config: PipelineConfig = serde_yaml::from_str(&config_str).unwrap();
+    let controller = Controller::with_config(
+        |circuit_config| {
+            Ok(test_circuit::<TestStruct>(
+                circuit_config,
+                &TestStruct::schema(),
+                &[None],
+            ))
+        },
+        &config,
+        Box::new(|e| info!("error: {e}")),
+    )
+    .unwrap();
+
+    controller.start();
+    let read_dead_letters = || {
+        std::fs::read_to_string(&dead_letter_path)
+            .unwrap_or_default()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>()
+    };
+    wait(|| read_dead_letters().len() >= 2, DEFAULT_TIMEOUT_MS).unwrap();
+    controller.stop().unwrap();
+
+    let mut dead_letters = read_dead_letters();
+    assert_eq!(dead_letters.len(), 2);
+    dead_letters.sort_by_key(|dead_letter| dead_letter["raw_text"].as_str().unwrap().to_string());
+    for (dead_letter, expected) in dead_letters.iter().zip([
+        json!({"insert": {"id": 1, "b": true, "i": null, "s": "foo"}}),
+        json!({"insert": {"id": 2, "b": false, "i": null, "s": "bar"}}),
+    ]) {
+        assert_eq!(dead_letter["connector"], "test_output1");
+        assert_eq!(dead_letter["event_number"], serde_json::Value::Null);
+        assert!(dead_letter["step"].is_u64());
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(dead_letter["raw_text"].as_str().unwrap())
+                .unwrap(),
+            expected
+        );
+        assert!(dead_letter["error"]
+            .as_str()
+            .unwrap()
+            .contains("exceeds maximum buffer size"));
+    }
+}
+
+/// A connector that exceeds `max_records_per_second` is paused by the
+/// backpressure thread and resumed once it is back within its limit.
+#[test]
+fn test_rate_limit() {
+    init_test_logger();
+
+    const NUM_RECORDS: u64 = 300;
+    const MAX_RECORDS_PER_SECOND: u64 = 100;
+
+    let temp_input_file = NamedTempFile::new().unwrap();
+    for id in 0..NUM_RECORDS {
+        writeln!(
+            temp_input_file.as_file(),
+            r#"{{"id": {id}, "b": true, "s": "foo"}}"#
+        )
+        .unwrap();
+    }
+
+    // A small read buffer makes the connector report records a few at a time,
+    // so that it can be paused partway through the file.
+    let config_str = format!(
+        r#"
+name: test
+workers: 4
+inputs:
+    test_input1.endpoint1:
+        stream: test_input1
+        transport:
+            name: file_input
+            config:
+                path: {:?}
+                buffer_size_bytes: 64
+        format:
+            name: json
+            config:
+                update_format: raw
+        max_records_per_second: {MAX_RECORDS_PER_SECOND}
+    "#,
+        temp_input_file.path().to_str().unwrap(),
+    );
+
+    let config: PipelineConfig = serde_yaml::from_str(&config_str).unwrap();
+    let controller = Controller::with_config(
+        |circuit_config| {
+            Ok(test_circuit::<TestStruct>(
+                circuit_config,
+                &TestStruct::schema(),
+                &[None],
+            ))
+        },
+        &config,
+        Box::new(|e| info!("error: {e}")),
+    )
+    .unwrap();
+
+    let endpoint_status = || {
+        controller
+            .input_endpoint_status("test_input1.endpoint1")
+            .unwrap()
+    };
+    assert_eq!(
+        endpoint_status()["config"]["max_records_per_second"],
+        MAX_RECORDS_PER_SECOND
+    );
+
+    let start = Instant::now();
+    controller.start();
+
+    // The connector runs through its one-second burst and gets throttled
+    // before it reaches the end of the file.
+    wait(
+        || endpoint_status()["throttled"] == true,
+        DEFAULT_TIMEOUT_MS,
+    )
+    .unwrap();
+    assert!(
+        endpoint_status()["metrics"]["total_records"]
+            .as_u64()
+            .unwrap()
+            < NUM_RECORDS
+    );
+
+    // The backpressure thread resumes it whenever it gets back within its
+    // limit, so it eventually ingests the whole file, but no faster than the
+    // limit allows beyond the burst.
+    wait(|| controller.pipeline_complete(), DEFAULT_TIMEOUT_MS).unwrap();
+    let elapsed = start.elapsed();
+    assert!(
+        elapsed >= Duration::from_secs(NUM_RECORDS / MAX_RECORDS_PER_SECOND - 2),
+        "ingested {NUM_RECORDS} records in {elapsed:?}"
+    );
+    assert_eq!(endpoint_status()["metrics"]["total_records"], NUM_RECORDS);
+    wait(
+        || endpoint_status()["throttled"] == false,
+        DEFAULT_TIMEOUT_MS,
+    )
+    .unwrap();
+
+    let result = controller
+        .execute_query_text_sync("select count(*) from test_output1")
+        .unwrap();
+    assert!(result.contains(&format!("| {NUM_RECORDS} ")), "{result}");
+    controller.stop().unwrap();
+}
+
 // TODO: Parameterize this with config string, so we can test different
 // input/output formats and transports when we support more than one.
 proptest! {
@@ -1209,6 +1652,103 @@ fn suspend_empty() {
     test_suspend(&[0, 2500, 0, 2500, 0, 0, 0, 2500, 2500, 2500, 0]);
 }
 
+/// After resuming from a suspend without fault tolerance, the snapshot for
+/// `snapshot_and_follow` subscribers becomes available only after the first
+/// step, and then includes the records ingested before the suspend.
+#[test]
+fn suspend_snapshot() {
+    init_test_logger();
+    let tempdir = TempDir::new().unwrap();
+    let storage_dir = tempdir.path().join("storage");
+    create_dir(&storage_dir).unwrap();
+    let input_path = tempdir.path().join("input.csv");
+    let input_file = File::create(&input_path).unwrap();
+    let output_path = tempdir.path().join("output.csv");
+
+    let config_str = format!(
+        r#"
+name: test
+workers: 4
+storage_config:
+    path: {storage_dir:?}
+storage: true
+clock_resolution_usecs: null
+inputs:
+    test_input1:
+        stream: test_input1
+        transport:
+            name: file_input
+            config:
+                path: {input_path:?}
+                follow: true
+        format:
+            name: csv
+outputs:
+    test_output1:
+        stream: test_output1
+        transport:
+            name: file_output
+            config:
+                path: {output_path:?}
+        format:
+            name: csv
+            config:
+        "#
+    );
+    let config: PipelineConfig = serde_yaml::from_str(&config_str).unwrap();
+    let start_controller = || {
+        Controller::with_config(
+            |circuit_config| {
+                Ok(test_circuit::<TestStruct>(
+                    circuit_config,
+                    &[],
+                    &[Some("output")],
+                ))
+            },
+            &config,
+            Box::new(|e| panic!("error: {e}")),
+        )
+        .unwrap()
+    };
+
+    let mut writer = CsvWriterBuilder::new()
+        .has_headers(false)
+        .from_writer(&input_file);
+    let mut write_records = |ids: Range<u32>| {
+        for id in ids {
+            writer.serialize(TestStruct::for_id(id)).unwrap();
+        }
+        writer.flush().unwrap();
+    };
+
+    write_records(0..5);
+    let controller = start_controller();
+    controller.start();
+    wait_for_output(
+        &controller,
+        &(0..5).map(TestStruct::for_id).collect::<Vec<_>>(),
+        &output_path,
+    );
+    controller.suspend().unwrap();
+    controller.stop().unwrap();
+
+    let controller = start_controller();
+    let mut snapshot = Box::pin(controller.lock_trace_snapshot());
+    assert!((&mut snapshot).now_or_never().is_none());
+
+    write_records(5..10);
+    controller.start();
+    let snapshot = TOKIO.block_on(snapshot);
+    let num_records = snapshot[&SqlIdentifier::from("test_output1")]
+        .iter()
+        .map(|batch| batch.len())
+        .sum::<usize>();
+    assert!(num_records >= 5, "snapshot has {num_records} records");
+    drop(snapshot);
+
+    controller.stop().unwrap();
+}
+
 fn input_path(storage_dir: &Path, i: usize) -> PathBuf {
     storage_dir.join(format!("input{}.csv", i + 1))
 }
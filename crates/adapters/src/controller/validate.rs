@@ -1,12 +1,15 @@
 use std::collections::{BTreeMap, HashSet};
 
 use feldera_adapterlib::errors::controller::ConfigError;
-use feldera_types::config::PipelineConfig;
+use feldera_types::config::{ConnectorConfig, PipelineConfig, TransportConfig};
+use feldera_types::format::json::{JsonParserConfig, JsonUpdateFormat};
 
 pub fn validate_config(config: &PipelineConfig) -> Result<(), ConfigError> {
     let mut dependencies = Vec::new();
 
     for (endpoint_name, input) in config.inputs.iter() {
+        validate_input_format(endpoint_name, &input.connector_config)?;
+
         if let Some(start_after) = input.connector_config.start_after.as_ref() {
             if start_after.is_empty() {
                 return Err(ConfigError::empty_start_after(endpoint_name));
@@ -31,6 +34,33 @@ pub fn validate_config(config: &PipelineConfig) -> Result<(), ConfigError> {
     Ok(())
 }
 
+/// Checks that the transport of an input connector can feed records to the
+/// parser configured for it.
+fn validate_input_format(
+    endpoint_name: &str,
+    connector_config: &ConnectorConfig,
+) -> Result<(), ConfigError> {
+    let Some(format) = &connector_config.format else {
+        return Ok(());
+    };
+
+    // Without a payload field, the Redis connector converts each stream entry
+    // into a JSON object.
+    if let TransportConfig::RedisInput(redis_config) = &connector_config.transport {
+        let raw_json = format.name == "json"
+            && serde_yaml::from_value::<JsonParserConfig>(format.config.clone())
+                .map_or(true, |config| config.update_format == JsonUpdateFormat::Raw);
+        if redis_config.payload_field.is_none() && !raw_json {
+            return Err(ConfigError::input_format_not_supported(
+                endpoint_name,
+                "without 'payload_field', the Redis input connector maps the fields of each stream entry to table columns, which requires the 'json' format with the 'raw' update format",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
 /// Detect the first cycle in a directed graph
 fn find_cycle(edges: &[(String, String, String)]) -> Option<Vec<(String, String)>> {
     let mut graph: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
@@ -0,0 +1,431 @@
+use std::borrow::Cow;
+use std::mem::take;
+use std::sync::Arc;
+
+use actix_web::HttpRequest;
+use anyhow::{bail, Result as AnyResult};
+use arrow::array::{Array, AsArray, Int64Array, RecordBatch, UInt32Array};
+use arrow::compute::{cast, take_record_batch};
+use arrow::datatypes::{DataType, Field as ArrowField, Int64Type, Schema};
+use arrow::ipc::reader::StreamReader;
+use arrow::ipc::writer::StreamWriter;
+use erased_serde::Serialize as ErasedSerialize;
+use feldera_adapterlib::catalog::ArrowStream;
+use feldera_types::config::{ConnectorConfig, TransportConfig};
+use feldera_types::format::arrow_ipc::{ArrowIpcEncoderConfig, ArrowIpcParserConfig};
+use feldera_types::program_schema::Relation;
+use serde::Deserialize;
+use serde_arrow::schema::SerdeArrowSchema;
+use serde_arrow::ArrayBuilder;
+use serde_urlencoded::Deserializer as UrlDeserializer;
+use serde_yaml::Value as YamlValue;
+
+use crate::catalog::{CursorWithPolarity, InputCollectionHandle, RecordFormat, SerBatchReader};
+use crate::format::parquet::{default_arrow_serde_config, relation_to_arrow_fields};
+use crate::format::{
+    Encoder, InputBuffer, InputFormat, OutputFormat, ParseError, Parser, Splitter, Sponge,
+    MAX_DUPLICATES,
+};
+use crate::{ControllerError, OutputConsumer, SerCursor};
+
+#[cfg(test)]
+mod test;
+
+/// Arrow IPC streaming format parser.
+pub struct ArrowIpcInputFormat;
+
+impl InputFormat for ArrowIpcInputFormat {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("arrow_ipc")
+    }
+
+    fn config_from_http_request(
+        &self,
+        endpoint_name: &str,
+        request: &HttpRequest,
+    ) -> Result<Box<dyn ErasedSerialize>, ControllerError> {
+        Ok(Box::new(
+            ArrowIpcParserConfig::deserialize(UrlDeserializer::new(form_urlencoded::parse(
+                request.query_string().as_bytes(),
+            )))
+            .map_err(|e| {
+                ControllerError::parser_config_parse_error(
+                    endpoint_name,
+                    &e,
+                    request.query_string(),
+                )
+            })?,
+        ))
+    }
+
+    fn new_parser(
+        &self,
+        endpoint_name: &str,
+        input_stream: &InputCollectionHandle,
+        config: &YamlValue,
+    ) -> Result<Box<dyn Parser>, ControllerError> {
+        let config = ArrowIpcParserConfig::deserialize(config).map_err(|e| {
+            ControllerError::parser_config_parse_error(
+                endpoint_name,
+                &e,
+                &serde_yaml::to_string(config).unwrap_or_default(),
+            )
+        })?;
+
+        if let Some(weight_column) = &config.weight_column {
+            if input_stream
+                .schema
+                .fields
+                .iter()
+                .any(|f| f.name.name() == *weight_column)
+            {
+                return Err(ControllerError::invalid_parser_configuration(
+                    endpoint_name,
+                    &format!(
+                        "weight column '{weight_column}' conflicts with a column of table {}",
+                        input_stream.schema.name.sql_name()
+                    ),
+                ));
+            }
+        }
+
+        let input_stream = input_stream
+            .handle
+            .configure_arrow_deserializer(default_arrow_serde_config().clone())?;
+        Ok(Box::new(ArrowIpcParser::new(input_stream, config)) as Box<dyn Parser>)
+    }
+}
+
+struct ArrowIpcParser {
+    /// Input handle to push parsed data to.
+    input_stream: Box<dyn ArrowStream>,
+    config: ArrowIpcParserConfig,
+}
+
+impl ArrowIpcParser {
+    fn new(input_stream: Box<dyn ArrowStream>, config: ArrowIpcParserConfig) -> Self {
+        Self {
+            input_stream,
+            config,
+        }
+    }
+
+    /// Pushes a record batch to the input stream, applying the weights from
+    /// the weight column, if one is configured.
+    fn push_batch(&mut self, batch: RecordBatch) -> Result<(), String> {
+        match &self.config.weight_column {
+            None => self
+                .input_stream
+                .insert(&batch)
+                .map_err(|e| format!("error deserializing record batch: {e}")),
+            Some(weight_column) => {
+                let (batch, polarities) = split_weights(batch, weight_column)?;
+                self.input_stream
+                    .insert_with_polarities(&batch, &polarities)
+                    .map_err(|e| format!("error deserializing record batch: {e}"))
+            }
+        }
+    }
+}
+
+/// Removes `weight_column` from `batch` and converts the weights it holds
+/// into one polarity per row, repeating rows whose weight is not 1 or -1.
+fn split_weights(
+    mut batch: RecordBatch,
+    weight_column: &str,
+) -> Result<(RecordBatch, Vec<bool>), String> {
+    let index = batch
+        .schema()
+        .index_of(weight_column)
+        .map_err(|_| format!("weight column '{weight_column}' not found in the record batch"))?;
+    let weights = batch.remove_column(index);
+    if weights.null_count() > 0 {
+        return Err(format!(
+            "weight column '{weight_column}' must not contain NULL values"
+        ));
+    }
+
+    match weights.data_type() {
+        DataType::Boolean => Ok((batch, weights.as_boolean().values().iter().collect())),
+        data_type if data_type.is_integer() => {
+            let weights = cast(&weights, &DataType::Int64).map_err(|e| {
+                format!("error converting weight column '{weight_column}' to BIGINT: {e}")
+            })?;
+            if weights.null_count() > 0 {
+                return Err(format!(
+                    "weight column '{weight_column}' contains values outside of the BIGINT range"
+                ));
+            }
+            let weights = weights.as_primitive::<Int64Type>().values();
+
+            if weights.iter().all(|w| *w == 1 || *w == -1) {
+                return Ok((batch, weights.iter().map(|w| *w > 0).collect()));
+            }
+
+            let mut indices = Vec::new();
+            let mut polarities = Vec::new();
+            for (i, w) in weights.iter().enumerate() {
+                if !(-MAX_DUPLICATES..=MAX_DUPLICATES).contains(w) {
+                    return Err(format!(
+                        "weight {w} in row {i} exceeds the maximum supported weight {MAX_DUPLICATES}"
+                    ));
+                }
+                for _ in 0..w.unsigned_abs() {
+                    indices.push(i as u32);
+                    polarities.push(*w > 0);
+                }
+            }
+            let batch = take_record_batch(&batch, &UInt32Array::from(indices))
+                .map_err(|e| format!("error applying weights to the record batch: {e}"))?;
+            Ok((batch, polarities))
+        }
+        data_type => Err(format!(
+            "weight column '{weight_column}' must have a boolean or integer type, but it has type {data_type}"
+        )),
+    }
+}
+
+impl Parser for ArrowIpcParser {
+    /// We get an entire IPC stream in `data` and parse it immediately.
+    fn parse(&mut self, data: &[u8]) -> (Option<Box<dyn InputBuffer>>, Vec<ParseError>) {
+        let reader = match StreamReader::try_new(data, None) {
+            Ok(reader) => reader,
+            Err(e) => {
+                return (
+                    None,
+                    vec![ParseError::bin_envelope_error(
+                        format!("error parsing Arrow IPC stream: {e}"),
+                        &[],
+                        None,
+                    )],
+                );
+            }
+        };
+
+        let mut errors = Vec::new();
+        for (batch_number, batch) in reader.enumerate() {
+            let result = batch
+                .map_err(|e| format!("error reading record batch: {e}"))
+                .and_then(|batch| self.push_batch(batch));
+            if let Err(e) = result {
+                errors.push(ParseError::bin_event_error(
+                    format!("error parsing Arrow IPC stream: {e}"),
+                    batch_number as u64,
+                    &[],
+                    None,
+                ));
+            }
+        }
+
+        (self.input_stream.take_all(), errors)
+    }
+
+    fn fork(&self) -> Box<dyn Parser> {
+        Box::new(Self::new(self.input_stream.fork(), self.config.clone()))
+    }
+
+    fn splitter(&self) -> Box<dyn Splitter> {
+        Box::new(Sponge)
+    }
+}
+
+/// Arrow IPC streaming format encoder.
+pub struct ArrowIpcOutputFormat;
+
+impl OutputFormat for ArrowIpcOutputFormat {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("arrow_ipc")
+    }
+
+    fn config_from_http_request(
+        &self,
+        endpoint_name: &str,
+        request: &HttpRequest,
+    ) -> Result<Box<dyn ErasedSerialize>, ControllerError> {
+        Ok(Box::new(
+            ArrowIpcEncoderConfig::deserialize(UrlDeserializer::new(form_urlencoded::parse(
+                request.query_string().as_bytes(),
+            )))
+            .map_err(|e| {
+                ControllerError::encoder_config_parse_error(
+                    endpoint_name,
+                    &e,
+                    request.query_string(),
+                )
+            })?,
+        ))
+    }
+
+    fn new_encoder(
+        &self,
+        endpoint_name: &str,
+        config: &ConnectorConfig,
+        key_schema: &Option<Relation>,
+        value_schema: &Relation,
+        consumer: Box<dyn OutputConsumer>,
+    ) -> Result<Box<dyn Encoder>, ControllerError> {
+        if key_schema.is_some() {
+            return Err(ControllerError::invalid_encoder_configuration(
+                endpoint_name,
+                "Arrow IPC encoder cannot be attached to an index",
+            ));
+        }
+
+        if matches!(config.transport, TransportConfig::RedisOutput(_)) {
+            return Err(ControllerError::invalid_encoder_configuration(
+                endpoint_name,
+                "'arrow_ipc' format not supported with Redis connector",
+            ));
+        }
+
+        let config = ArrowIpcEncoderConfig::deserialize(&config.format.as_ref().unwrap().config)
+            .map_err(|e| {
+                ControllerError::encoder_config_parse_error(
+                    endpoint_name,
+                    &e,
+                    &serde_yaml::to_string(&config).unwrap_or_default(),
+                )
+            })?;
+
+        Ok(Box::new(ArrowIpcEncoder::create(
+            endpoint_name,
+            consumer,
+            config,
+            value_schema,
+        )?))
+    }
+}
+
+struct ArrowIpcEncoder {
+    /// Consumer to push serialized data to.
+    output_consumer: Box<dyn OutputConsumer>,
+    config: ArrowIpcEncoderConfig,
+
+    /// Schema of the view, used to serialize records.
+    serde_schema: SerdeArrowSchema,
+
+    /// Schema of the output stream, including the weight column.
+    schema: Arc<Schema>,
+    buffer: Vec<u8>,
+}
+
+impl ArrowIpcEncoder {
+    fn create(
+        endpoint_name: &str,
+        output_consumer: Box<dyn OutputConsumer>,
+        config: ArrowIpcEncoderConfig,
+        value_schema: &Relation,
+    ) -> Result<Self, ControllerError> {
+        if config.buffer_size_records == 0 {
+            return Err(ControllerError::invalid_encoder_configuration(
+                endpoint_name,
+                "'buffer_size_records' must be greater than 0",
+            ));
+        }
+
+        let mut fields = relation_to_arrow_fields(&value_schema.fields, false);
+        let serde_schema = SerdeArrowSchema::try_from(fields.as_slice()).map_err(|e| {
+            ControllerError::SchemaParseError {
+                error: format!("Unable to convert schema to arrow: {e}"),
+            }
+        })?;
+
+        if let Some(weight_column) = &config.weight_column {
+            if fields.iter().any(|f| f.name() == weight_column) {
+                return Err(ControllerError::invalid_encoder_configuration(
+                    endpoint_name,
+                    &format!(
+                        "weight column '{weight_column}' conflicts with a column of view {}",
+                        value_schema.name.sql_name()
+                    ),
+                ));
+            }
+            fields.push(ArrowField::new(weight_column, DataType::Int64, false));
+        }
+
+        Ok(Self {
+            output_consumer,
+            config,
+            serde_schema,
+            schema: Arc::new(Schema::new(fields)),
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Writes records accumulated in `builder` as a single Arrow IPC stream
+    /// and sends it to the consumer.
+    fn push_stream(
+        &mut self,
+        builder: &mut ArrayBuilder,
+        weights: &mut Vec<i64>,
+        num_records: usize,
+    ) -> AnyResult<()> {
+        let mut columns = builder.to_record_batch()?.columns().to_vec();
+        if self.config.weight_column.is_some() {
+            columns.push(Arc::new(Int64Array::from(take(weights))));
+        }
+        let batch = RecordBatch::try_new(self.schema.clone(), columns)?;
+
+        self.buffer.clear();
+        let mut writer = StreamWriter::try_new(&mut self.buffer, &self.schema)?;
+        writer.write(&batch)?;
+        writer.finish()?;
+        drop(writer);
+
+        self.output_consumer.push_buffer(&self.buffer, num_records);
+        Ok(())
+    }
+}
+
+impl Encoder for ArrowIpcEncoder {
+    fn consumer(&mut self) -> &mut dyn OutputConsumer {
+        self.output_consumer.as_mut()
+    }
+
+    fn encode(&mut self, batch: &dyn SerBatchReader) -> AnyResult<()> {
+        let mut builder = ArrayBuilder::new(self.serde_schema.clone())?;
+        let mut weights = Vec::new();
+        let mut num_records = 0;
+
+        let mut cursor = CursorWithPolarity::new(
+            batch.cursor(RecordFormat::Parquet(default_arrow_serde_config().clone()))?,
+        );
+        while cursor.key_valid() {
+            if !cursor.val_valid() {
+                cursor.step_key();
+                continue;
+            }
+            let w = cursor.weight();
+            if !(-MAX_DUPLICATES..=MAX_DUPLICATES).contains(&w) {
+                bail!("Unable to output record with very large weight {w}. Consider adjusting your SQL queries to avoid duplicate output records, e.g., using 'SELECT DISTINCT'.");
+            }
+
+            // With a weight column, each record is output once along with its
+            // weight.  Without it, inserted records are repeated `w` times and
+            // deleted records are skipped.
+            let copies = if self.config.weight_column.is_some() {
+                weights.push(w);
+                1
+            } else {
+                w.max(0)
+            };
+
+            for _ in 0..copies {
+                cursor.serialize_key_to_arrow(&mut builder)?;
+                num_records += 1;
+
+                if num_records >= self.config.buffer_size_records {
+                    self.push_stream(&mut builder, &mut weights, num_records)?;
+                    num_records = 0;
+                }
+            }
+            cursor.step_key();
+        }
+
+        if num_records > 0 {
+            self.push_stream(&mut builder, &mut weights, num_records)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, BooleanArray, Int64Array, Int8Array, RecordBatch};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::ipc::reader::StreamReader;
+use arrow::ipc::writer::StreamWriter;
+use dbsp::utils::Tup2;
+use dbsp::OrdZSet;
+use feldera_types::format::arrow_ipc::{ArrowIpcEncoderConfig, ArrowIpcParserConfig};
+use feldera_types::program_schema::Relation;
+use pretty_assertions::assert_eq;
+
+use super::ArrowIpcEncoder;
+use crate::{
+    catalog::SerBatchReader,
+    format::Encoder,
+    static_compile::seroutput::SerBatchImpl,
+    test::{mock_parser_pipeline, MockOutputConsumer, MockUpdate, TestStruct2},
+    FormatConfig,
+};
+
+fn relation() -> Relation {
+    Relation::new("test".into(), TestStruct2::schema(), false, BTreeMap::new())
+}
+
+/// Serializes `data` along with an optional weight column as an Arrow IPC
+/// stream.
+fn ipc_stream(data: &[TestStruct2], weights: Option<(&str, ArrayRef)>) -> Vec<u8> {
+    let mut fields = TestStruct2::arrow_schema().fields().to_vec();
+    let mut columns = TestStruct2::make_arrow_array(data);
+    if let Some((name, weights)) = weights {
+        fields.push(Arc::new(Field::new(
+            name,
+            weights.data_type().clone(),
+            false,
+        )));
+        columns.push(weights);
+    }
+    let schema = Schema::new(fields);
+    let batch = RecordBatch::try_new(Arc::new(schema.clone()), columns).unwrap();
+
+    let mut buffer = Vec::new();
+    let mut writer = StreamWriter::try_new(&mut buffer, &schema).unwrap();
+    writer.write(&batch).unwrap();
+    writer.finish().unwrap();
+    drop(writer);
+    buffer
+}
+
+fn parser_config(weight_column: Option<&str>) -> FormatConfig {
+    FormatConfig {
+        name: Cow::from("arrow_ipc"),
+        config: serde_yaml::to_value(ArrowIpcParserConfig {
+            weight_column: weight_column.map(str::to_string),
+        })
+        .unwrap(),
+    }
+}
+
+#[test]
+fn arrow_ipc_input() {
+    let test_data = TestStruct2::data();
+
+    let (consumer, mut parser, outputs) =
+        mock_parser_pipeline::<TestStruct2, TestStruct2>(&relation(), &parser_config(None))
+            .unwrap();
+    consumer.on_error(Some(Box::new(|_, _| {})));
+
+    let (mut buffer, errors) = parser.parse(&ipc_stream(&test_data, None));
+    assert_eq!(errors, vec![]);
+    buffer.flush();
+
+    let (_buffer, errors) = parser.parse(b"not an arrow stream");
+    assert_eq!(errors.len(), 1);
+
+    assert_eq!(
+        outputs.state().flushed,
+        test_data
+            .into_iter()
+            .map(MockUpdate::Insert)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn arrow_ipc_input_with_weights() {
+    let test_data = TestStruct2::data();
+
+    let (consumer, mut parser, outputs) =
+        mock_parser_pipeline::<TestStruct2, TestStruct2>(&relation(), &parser_config(Some("__op")))
+            .unwrap();
+    consumer.on_error(Some(Box::new(|_, _| {})));
+
+    // Boolean insert/delete column.
+    let ops = Arc::new(BooleanArray::from(vec![true, false])) as ArrayRef;
+    let (mut buffer, errors) = parser.parse(&ipc_stream(&test_data, Some(("__op", ops))));
+    assert_eq!(errors, vec![]);
+    buffer.flush();
+
+    // Integer weight column.
+    let weights = Arc::new(Int8Array::from(vec![2, 0])) as ArrayRef;
+    let (mut buffer, errors) = parser.parse(&ipc_stream(&test_data, Some(("__op", weights))));
+    assert_eq!(errors, vec![]);
+    buffer.flush();
+
+    // Missing weight column.
+    let (_buffer, errors) = parser.parse(&ipc_stream(&test_data, None));
+    assert_eq!(errors.len(), 1);
+
+    assert_eq!(
+        outputs.state().flushed,
+        vec![
+            MockUpdate::Insert(test_data[0].clone()),
+            MockUpdate::Delete(test_data[1].clone()),
+            MockUpdate::Insert(test_data[0].clone()),
+            MockUpdate::Insert(test_data[0].clone()),
+        ]
+    );
+}
+
+/// Encodes a batch with one inserted and one deleted record and returns the
+/// record batches in the output streams.
+fn encode(config: ArrowIpcEncoderConfig) -> Vec<RecordBatch> {
+    let test_data = TestStruct2::data();
+    let consumer = MockOutputConsumer::new();
+    let consumer_data = consumer.data.clone();
+
+    let mut encoder = ArrowIpcEncoder::create(
+        "arrow_ipc_test_endpoint",
+        Box::new(consumer),
+        config,
+        &relation(),
+    )
+    .unwrap();
+    let zset = OrdZSet::from_keys(
+        (),
+        vec![
+            Tup2(test_data[0].clone(), 2),
+            Tup2(test_data[1].clone(), -1),
+        ],
+    );
+    let zset = &SerBatchImpl::<_, TestStruct2, ()>::new(zset) as &dyn SerBatchReader;
+
+    encoder.consumer().batch_start(0);
+    encoder.encode(zset).unwrap();
+    encoder.consumer().batch_end();
+
+    let data = consumer_data.lock().unwrap().clone();
+    data.into_iter()
+        .flat_map(|(_k, v, _headers)| {
+            StreamReader::try_new(v.unwrap().as_slice(), None)
+                .unwrap()
+                .map(|batch| batch.unwrap())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[test]
+fn arrow_ipc_output() {
+    let test_data = TestStruct2::data();
+
+    // Deleted records are skipped; records are repeated according to their weights.
+    let batches = encode(ArrowIpcEncoderConfig::default());
+    let expected = RecordBatch::try_new(
+        TestStruct2::arrow_schema(),
+        TestStruct2::make_arrow_array(&[test_data[0].clone(), test_data[0].clone()]),
+    )
+    .unwrap();
+    assert_eq!(batches, vec![expected]);
+}
+
+#[test]
+fn arrow_ipc_output_with_weights() {
+    let test_data = TestStruct2::data();
+
+    let batches = encode(ArrowIpcEncoderConfig {
+        buffer_size_records: 1,
+        weight_column: Some("__weight".to_string()),
+    });
+
+    let mut fields = TestStruct2::arrow_schema().fields().to_vec();
+    fields.push(Arc::new(Field::new("__weight", DataType::Int64, false)));
+    let schema = Arc::new(Schema::new(fields));
+
+    // Deletions are output before insertions.
+    let expected = [(&test_data[1], -1), (&test_data[0], 2)]
+        .into_iter()
+        .map(|(record, weight)| {
+            let mut columns = TestStruct2::make_arrow_array(&[record.clone()]);
+            columns.push(Arc::new(Int64Array::from(vec![weight])));
+            RecordBatch::try_new(schema.clone(), columns).unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(batches, expected);
+
+    // Streams produced by the encoder can be parsed back.
+    let (consumer, mut parser, outputs) = mock_parser_pipeline::<TestStruct2, TestStruct2>(
+        &relation(),
+        &parser_config(Some("__weight")),
+    )
+    .unwrap();
+    consumer.on_error(Some(Box::new(|_, _| {})));
+    for batch in batches {
+        let mut buffer = Vec::new();
+        let mut writer = StreamWriter::try_new(&mut buffer, &batch.schema()).unwrap();
+        writer.write(&batch).unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+
+        let (mut buffer, errors) = parser.parse(&buffer);
+        assert_eq!(errors, vec![]);
+        buffer.flush();
+    }
+    assert_eq!(
+        outputs.state().flushed,
+        vec![
+            MockUpdate::Delete(test_data[1].clone()),
+            MockUpdate::Insert(test_data[0].clone()),
+            MockUpdate::Insert(test_data[0].clone()),
+        ]
+    );
+}
+
+#[test]
+fn arrow_ipc_weight_column_conflict() {
+    let config = ArrowIpcEncoderConfig {
+        buffer_size_records: 10,
+        weight_column: Some("id".to_string()),
+    };
+    assert!(ArrowIpcEncoder::create(
+        "arrow_ipc_test_endpoint",
+        Box::new(MockOutputConsumer::new()),
+        config,
+        &relation(),
+    )
+    .is_err());
+}
@@ -1,14 +1,20 @@
 use crate::{
     catalog::{AvroStream, InputCollectionHandle},
     format::{
-        avro::schema::{schema_json, validate_struct_schema},
+        avro::schema::{reader_schema, schema_json, validate_struct_schema},
         Splitter, Sponge,
     },
     ControllerError, DeCollectionHandle, InputBuffer, InputFormat, ParseError, Parser,
 };
 use actix_web::HttpRequest;
-use apache_avro::{from_avro_datum, types::Value as AvroValue, Schema as AvroSchema};
+use apache_avro::{
+    from_avro_datum,
+    schema::{MapSchema, RecordField, SchemaKind, UnionSchema},
+    types::Value as AvroValue,
+    Reader as AvroReader, Schema as AvroSchema,
+};
 use erased_serde::Serialize as ErasedSerialize;
+use feldera_adapterlib::format::{ConnectorMetadata, MetadataValue};
 use feldera_types::{
     format::avro::{AvroParserConfig, AvroUpdateFormat},
     program_schema::Relation,
@@ -23,7 +29,7 @@ use serde_urlencoded::Deserializer as UrlDeserializer;
 use serde_yaml::Value as YamlValue;
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     sync::{Arc, Mutex},
 };
 use tracing::{debug, info};
@@ -53,6 +59,107 @@ pub const fn avro_de_config() -> &'static SqlSerdeConfig {
     }
 }
 
+/// Converts a connector metadata value to an Avro value, along with the
+/// schema to deserialize it with.
+fn metadata_to_avro(value: &MetadataValue) -> (AvroValue, AvroSchema) {
+    let null = || AvroValue::Union(0, Box::new(AvroValue::Null));
+    match value {
+        MetadataValue::Null => (
+            null(),
+            AvroSchema::Union(UnionSchema::new(vec![AvroSchema::Null]).unwrap()),
+        ),
+        MetadataValue::Integer(i) => (AvroValue::Long(*i), AvroSchema::Long),
+        MetadataValue::String(s) => (AvroValue::String(s.clone()), AvroSchema::String),
+        MetadataValue::Bytes(bytes) => (AvroValue::Bytes(bytes.clone()), AvroSchema::Bytes),
+        MetadataValue::Timestamp(millis) => (
+            AvroValue::TimestampMillis(*millis),
+            AvroSchema::TimestampMillis,
+        ),
+        MetadataValue::Map(map) => (
+            AvroValue::Map(
+                map.iter()
+                    .map(|(k, v)| {
+                        let v = match v {
+                            Some(v) => AvroValue::Union(1, Box::new(AvroValue::String(v.clone()))),
+                            None => null(),
+                        };
+                        (k.clone(), v)
+                    })
+                    .collect(),
+            ),
+            AvroSchema::Map(MapSchema {
+                types: Box::new(AvroSchema::Union(
+                    UnionSchema::new(vec![AvroSchema::Null, AvroSchema::String]).unwrap(),
+                )),
+                attributes: BTreeMap::new(),
+            }),
+        ),
+    }
+}
+
+/// Returns `schema`, a record schema, with a field for each connector
+/// `metadata` column.  Fields of the same name in `schema` are replaced.
+fn metadata_schema(
+    schema: &AvroSchema,
+    metadata: &[(String, AvroValue, AvroSchema)],
+) -> Result<AvroSchema, String> {
+    let AvroSchema::Record(record_schema) = schema else {
+        return Err(format!(
+            "connector metadata columns can only be added to Avro records, but the schema is {}",
+            schema_json(schema)
+        ));
+    };
+    let mut record_schema = record_schema.clone();
+    for (name, _, field_schema) in metadata {
+        match record_schema.lookup.get(name) {
+            Some(&position) => record_schema.fields[position].schema = field_schema.clone(),
+            None => {
+                let position = record_schema.fields.len();
+                record_schema.fields.push(
+                    RecordField::builder()
+                        .name(name.clone())
+                        .schema(field_schema.clone())
+                        .position(position)
+                        .build(),
+                );
+                record_schema.lookup.insert(name.clone(), position);
+            }
+        }
+    }
+    Ok(AvroSchema::Record(record_schema))
+}
+
+/// Returns `value`, a record that matches `schema`, with the connector
+/// `metadata` columns added to it, along with the schema to deserialize it
+/// with.  `metadata_schema` is `schema` extended by [metadata_schema], or
+/// `None` if there are no metadata columns.
+fn with_metadata<'a>(
+    value: &'a AvroValue,
+    schema: &'a AvroSchema,
+    metadata: &[(String, AvroValue, AvroSchema)],
+    metadata_schema: Option<&'a AvroSchema>,
+) -> Result<(Cow<'a, AvroValue>, &'a AvroSchema), String> {
+    let Some(metadata_schema) = metadata_schema else {
+        return Ok((Cow::Borrowed(value), schema));
+    };
+    let (AvroValue::Record(fields), AvroSchema::Record(record_schema)) = (value, metadata_schema)
+    else {
+        return Err(format!(
+            "connector metadata columns can only be added to Avro records, but found {value:?}"
+        ));
+    };
+    let mut fields = fields.clone();
+    for (name, value, _) in metadata {
+        let position = record_schema.lookup[name];
+        if position < fields.len() {
+            fields[position].1 = value.clone();
+        } else {
+            fields.push((name.clone(), value.clone()));
+        }
+    }
+    Ok((Cow::Owned(AvroValue::Record(fields)), metadata_schema))
+}
+
 pub struct AvroInputFormat;
 
 impl InputFormat for AvroInputFormat {
@@ -117,6 +224,14 @@ struct AvroParser {
     value_schema: Option<AvroSchema>,
     last_event_number: u64,
 
+    /// Connector metadata columns to add to each record, with their schemas,
+    /// while parsing data passed to [Parser::parse_with_metadata].
+    metadata: Vec<(String, AvroValue, AvroSchema)>,
+
+    /// `value_schema` extended with the `metadata` columns, cached along with
+    /// the names and kinds of the columns it was computed for.
+    metadata_schema: Option<(Vec<(String, SchemaKind)>, AvroSchema)>,
+
     /// Schema cache shared between all clones of this connector.
     ///
     /// When the connector first starts, the first worker to get a message will retrieve
@@ -138,6 +253,44 @@ impl AvroParser {
         let sr_settings = schema_registry_settings(&config.registry_config)
             .map_err(|e| ControllerError::invalid_parser_configuration(endpoint_name, &e))?;
 
+        if config.object_container_file {
+            for (enabled, property) in [
+                (config.schema.is_some(), "schema"),
+                (config.skip_schema_id, "skip_schema_id"),
+                (sr_settings.is_some(), "registry_urls"),
+            ] {
+                if enabled {
+                    return Err(ControllerError::invalid_parser_configuration(
+                        endpoint_name,
+                        &format!("'{property}' property cannot be used with 'object_container_file'; object container files embed the schema used to encode their records"),
+                    ));
+                }
+            }
+
+            if config.update_format != AvroUpdateFormat::Raw {
+                return Err(ControllerError::invalid_parser_configuration(
+                    endpoint_name,
+                    &format!("'{}' data change event format is not supported with 'object_container_file'; only the 'raw' format is supported", config.update_format),
+                ));
+            }
+
+            return Ok(Self {
+                endpoint_name: endpoint_name.to_string(),
+                input_stream: Some(input_handle.configure_avro_deserializer()?),
+                input_handle,
+                sr_settings,
+                config,
+                schema_id: None,
+                relation_schema: relation_schema.clone(),
+                schema: None,
+                value_schema: None,
+                last_event_number: 0,
+                metadata: Vec::new(),
+                metadata_schema: None,
+                schema_cache: Arc::new(Mutex::new(HashMap::new())),
+            });
+        }
+
         if config.schema.is_none() && sr_settings.is_none() {
             return Err(ControllerError::invalid_parser_configuration(
                 endpoint_name,
@@ -180,6 +333,8 @@ impl AvroParser {
             schema: None,
             value_schema: None,
             last_event_number: 0,
+            metadata: Vec::new(),
+            metadata_schema: None,
             schema_cache: Arc::new(Mutex::new(HashMap::new())),
         };
 
@@ -240,12 +395,37 @@ impl AvroParser {
         debug!("Setting Avro schema: {}", schema_json(&schema));
         self.schema = Some(schema);
         self.value_schema = Some(value_schema);
+        self.metadata_schema = None;
 
         self.input_stream = Some(self.input_handle.configure_avro_deserializer()?);
 
         Ok(())
     }
 
+    /// Updates `metadata_schema` for the current value schema and `metadata`
+    /// columns.
+    fn update_metadata_schema(&mut self) -> Result<(), String> {
+        let up_to_date = self.metadata_schema.as_ref().is_some_and(|(columns, _)| {
+            columns
+                .iter()
+                .map(|(name, kind)| (name.as_str(), *kind))
+                .eq(self
+                    .metadata
+                    .iter()
+                    .map(|(name, _, schema)| (name.as_str(), SchemaKind::from(schema))))
+        });
+        if !up_to_date {
+            let schema = metadata_schema(self.value_schema.as_ref().unwrap(), &self.metadata)?;
+            let columns = self
+                .metadata
+                .iter()
+                .map(|(name, _, schema)| (name.clone(), SchemaKind::from(schema)))
+                .collect();
+            self.metadata_schema = Some((columns, schema));
+        }
+        Ok(())
+    }
+
     fn value_schema<'a>(&self, schema: &'a AvroSchema) -> Result<&'a AvroSchema, ControllerError> {
         match self.config.update_format {
             AvroUpdateFormat::Raw => Ok(schema),
@@ -346,6 +526,18 @@ impl AvroParser {
         // the schema registry.
         assert!(self.input_stream.is_some());
         assert!(self.schema.is_some());
+
+        if !self.metadata.is_empty() {
+            self.update_metadata_schema()
+                .map_err(|e| ParseError::bin_event_error(e, self.last_event_number, data, None))?;
+        }
+        let metadata_schema = self
+            .metadata_schema
+            .as_ref()
+            .filter(|_| !self.metadata.is_empty())
+            .map(|(_, schema)| schema);
+        let metadata = &self.metadata;
+
         let schema = self.schema.as_ref().unwrap();
         let value_schema = self.value_schema.as_ref().unwrap();
 
@@ -361,21 +553,30 @@ impl AvroParser {
             )
         })?;
 
+        let add_metadata = |value, schema| {
+            with_metadata(value, schema, metadata, metadata_schema)
+                .map_err(|e| ParseError::bin_event_error(e, self.last_event_number, data, None))
+        };
+
         match self.config.update_format {
-            AvroUpdateFormat::Raw => input_stream.insert(&avro_value, schema).map_err(|e| {
-                ParseError::bin_event_error(
-                    format!(
-                        "error converting avro record to table row (record: {avro_value:?}): {e}"
-                    ),
-                    self.last_event_number,
-                    data,
-                    None,
-                )
-            })?,
+            AvroUpdateFormat::Raw => {
+                let (value, schema) = add_metadata(&avro_value, schema)?;
+                input_stream.insert(&value, schema).map_err(|e| {
+                    ParseError::bin_event_error(
+                        format!(
+                            "error converting avro record to table row (record: {avro_value:?}): {e}"
+                        ),
+                        self.last_event_number,
+                        data,
+                        None,
+                    )
+                })?
+            }
             AvroUpdateFormat::Debezium => {
                 let (before, after) = Self::extract_debezium_values(&avro_value)?;
                 if let Some(before) = before {
-                    input_stream.delete(before, value_schema).map_err(|e| {
+                    let (value, value_schema) = add_metadata(before, value_schema)?;
+                    input_stream.delete(&value, value_schema).map_err(|e| {
                         ParseError::bin_event_error(
                             format!(
                                 "error converting 'before' record to table row (record: {before:?}): {e}"
@@ -387,7 +588,8 @@ impl AvroParser {
                     })?;
                 }
                 if let Some(after) = after {
-                    input_stream.insert(after, value_schema).map_err(|e| {
+                    let (value, value_schema) = add_metadata(after, value_schema)?;
+                    input_stream.insert(&value, value_schema).map_err(|e| {
                             ParseError::bin_event_error(
                                 format!(
                                     "error converting 'after' record to table row (record: {after:?}): {e}"
@@ -407,6 +609,86 @@ impl AvroParser {
         Ok(())
     }
 
+    /// Parse an Avro object container file.
+    fn input_container_file(&mut self, data: &[u8]) -> Vec<ParseError> {
+        let writer_schema = match AvroReader::new(data) {
+            Ok(reader) => reader.writer_schema().clone(),
+            Err(e) => {
+                return vec![ParseError::bin_envelope_error(
+                    format!("error parsing Avro object container file header: {e}"),
+                    &[],
+                    None,
+                )];
+            }
+        };
+
+        let schema = match reader_schema(&writer_schema, &self.relation_schema.fields) {
+            Ok(schema) => schema,
+            Err(e) => {
+                return vec![ParseError::bin_envelope_error(
+                    format!(
+                        "error resolving Avro schema {} against the table schema: {e}",
+                        schema_json(&writer_schema)
+                    ),
+                    &[],
+                    None,
+                )];
+            }
+        };
+
+        let reader = match AvroReader::with_schema(&schema, data) {
+            Ok(reader) => reader,
+            Err(e) => {
+                return vec![ParseError::bin_envelope_error(
+                    format!("error parsing Avro object container file header: {e}"),
+                    &[],
+                    None,
+                )];
+            }
+        };
+
+        let extended_schema = if self.metadata.is_empty() {
+            None
+        } else {
+            match metadata_schema(&schema, &self.metadata) {
+                Ok(extended_schema) => Some(extended_schema),
+                Err(e) => return vec![ParseError::bin_envelope_error(e, &[], None)],
+            }
+        };
+
+        let input_stream = self.input_stream.as_mut().unwrap();
+        let mut errors = Vec::new();
+
+        for avro_value in reader {
+            self.last_event_number += 1;
+
+            let result = match avro_value {
+                Ok(avro_value) => {
+                    with_metadata(&avro_value, &schema, &self.metadata, extended_schema.as_ref())
+                        .and_then(|(value, schema)| {
+                            input_stream.insert(&value, schema).map_err(|e| {
+                                format!(
+                                    "error converting avro record to table row (record: {avro_value:?}): {e}"
+                                )
+                            })
+                        })
+                }
+                Err(e) => Err(format!("error reading Avro object container file: {e}")),
+            };
+
+            if let Err(e) = result {
+                errors.push(ParseError::bin_event_error(
+                    e,
+                    self.last_event_number,
+                    &[],
+                    None,
+                ));
+            }
+        }
+
+        errors
+    }
+
     /// Extract before and after fields from a debezium value.
     fn extract_debezium_values(
         value: &AvroValue,
@@ -456,11 +738,32 @@ impl Parser for AvroParser {
         Box::new(Sponge)
     }
     fn parse(&mut self, data: &[u8]) -> (Option<Box<dyn InputBuffer>>, Vec<ParseError>) {
-        let errors = self.input(data).map_or_else(|e| vec![e], |_| Vec::new());
+        let errors = if self.config.object_container_file {
+            self.input_container_file(data)
+        } else {
+            self.input(data).map_or_else(|e| vec![e], |_| Vec::new())
+        };
         let buffer = self.input_stream.as_mut().and_then(|avro| avro.take_all());
         (buffer, errors)
     }
 
+    fn parse_with_metadata(
+        &mut self,
+        data: &[u8],
+        metadata: &ConnectorMetadata,
+    ) -> (Option<Box<dyn InputBuffer>>, Vec<ParseError>) {
+        self.metadata = metadata
+            .iter()
+            .map(|(name, value)| {
+                let (value, schema) = metadata_to_avro(value);
+                (name.clone(), value, schema)
+            })
+            .collect();
+        let result = self.parse(data);
+        self.metadata.clear();
+        result
+    }
+
     fn fork(&self) -> Box<dyn Parser> {
         Box::new(AvroParser {
             endpoint_name: self.endpoint_name.clone(),
@@ -473,6 +776,8 @@ impl Parser for AvroParser {
             schema: self.schema.clone(),
             value_schema: self.value_schema.clone(),
             last_event_number: 0,
+            metadata: Vec::new(),
+            metadata_schema: None,
             schema_cache: self.schema_cache.clone(),
         })
     }
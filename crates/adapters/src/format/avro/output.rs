@@ -1,14 +1,18 @@
 use crate::catalog::{CursorWithPolarity, SerBatchReader};
 use crate::format::avro::schema::{schema_json, AvroSchemaBuilder};
 use crate::format::avro::schema_registry_settings;
+use crate::format::message_fields::{MessageAttributes, MessageFields};
 use crate::format::MAX_DUPLICATES;
+use crate::transport::Step;
 use crate::util::{indexed_operation_type, IndexedOperationType};
 use crate::{ControllerError, Encoder, OutputConsumer, OutputFormat, RecordFormat, SerCursor};
 use actix_web::HttpRequest;
 use anyhow::{anyhow, bail, Result as AnyResult};
 use apache_avro::schema::RecordField;
 use apache_avro::Schema;
-use apache_avro::{to_avro_datum, types::Value as AvroValue, Schema as AvroSchema};
+use apache_avro::{
+    to_avro_datum, types::Value as AvroValue, Schema as AvroSchema, Writer as AvroWriter,
+};
 use erased_serde::Serialize as ErasedSerialize;
 use feldera_types::config::{ConnectorConfig, TransportConfig};
 use feldera_types::format::avro::{
@@ -87,14 +91,40 @@ impl OutputFormat for AvroOutputFormat {
             _ => None,
         };
 
-        Ok(Box::new(AvroEncoder::create(
+        // The object store connector writes files rather than messages.
+        let container_files = match &config.transport {
+            TransportConfig::ObjectStoreOutput(object_store_config) => {
+                if avro_config.update_format != AvroUpdateFormat::Raw {
+                    return Err(ControllerError::invalid_encoder_configuration(
+                        endpoint_name,
+                        "only the 'raw' update format is supported when writing Avro object container files",
+                    ));
+                }
+                Some(object_store_config.max_records_per_object)
+            }
+            _ => None,
+        };
+
+        let message_fields = MessageFields::from_transport(&config.transport);
+        if let Some(message_fields) = &message_fields {
+            message_fields.validate(endpoint_name, value_schema)?;
+        }
+
+        let mut encoder = AvroEncoder::create(
             endpoint_name,
             key_schema,
             value_schema,
             consumer,
             avro_config,
             topic,
-        )?))
+        )?
+        .with_message_fields(message_fields);
+
+        if let Some(max_records) = container_files {
+            encoder = encoder.with_container_files(endpoint_name, max_records)?;
+        }
+
+        Ok(Box::new(encoder))
     }
 }
 
@@ -126,6 +156,16 @@ pub(crate) struct AvroEncoder {
 
     /// Avro Schema when the CDC field is Some.
     value_avro_schema_with_cdc: Option<AvroSchema>,
+
+    /// Columns used to compute the key, headers, and topic of each message.
+    message_fields: Option<MessageFields>,
+
+    /// Key, headers, and topic of the current message.
+    message_attributes: MessageAttributes,
+
+    /// `True` if the output consumer packs records into Avro object container
+    /// files, which cannot represent the `op` header.
+    container_files: bool,
 }
 
 /// `true` - this config will create messages with key and value components.
@@ -431,9 +471,49 @@ Consider defining an index with `CREATE INDEX` and setting `index` field in conn
             update_format: config.update_format,
             cdc_field: config.cdc_field,
             value_avro_schema_with_cdc,
+            message_fields: None,
+            message_attributes: MessageAttributes::default(),
+            container_files: false,
         })
     }
 
+    /// Computes the key, headers, and topic of each message from the columns
+    /// of the output record.
+    ///
+    /// The key computed from `message_fields`, if any, replaces the key
+    /// generated by the encoder.
+    pub(crate) fn with_message_fields(mut self, message_fields: Option<MessageFields>) -> Self {
+        self.message_fields = message_fields;
+        self
+    }
+
+    /// Writes the output as Avro object container files, with up to
+    /// `max_records` records per file, instead of individual messages.
+    ///
+    /// Container files only store record values, so only insertions can be
+    /// written, unless the operation type is recorded in the `cdc_field`.
+    pub(crate) fn with_container_files(
+        mut self,
+        endpoint_name: &str,
+        max_records: Option<u64>,
+    ) -> Result<Self, ControllerError> {
+        let schema = self
+            .value_avro_schema_with_cdc
+            .as_ref()
+            .unwrap_or(&self.value_avro_schema);
+        self.output_consumer = Box::new(
+            ContainerFileConsumer::new(self.output_consumer, schema, max_records).map_err(|e| {
+                ControllerError::invalid_encoder_configuration(
+                    endpoint_name,
+                    &format!("error generating Avro object container file header: {e}"),
+                )
+            })?,
+        );
+        self.skip_schema_id = true;
+        self.container_files = true;
+        Ok(self)
+    }
+
     fn view_name(&self) -> &SqlIdentifier {
         &self.value_sql_schema.name
     }
@@ -479,6 +559,21 @@ Consider defining an index with `CREATE INDEX` and setting `index` field in conn
                     );
                 }
 
+                if w < 0 && self.container_files {
+                    bail!(
+                        "Avro object container files cannot represent deletions. Consider configuring the connector with an 'index' and a 'cdc_field' to record the operation type of each record."
+                    );
+                }
+
+                if let Some(message_fields) = &self.message_fields {
+                    let op = if w > 0 { "insert" } else { "delete" };
+                    message_fields.encode_json(
+                        &cursor.key_to_json()?,
+                        op,
+                        &mut self.message_attributes,
+                    );
+                }
+
                 while w != 0 {
                     let avro_value = cursor
                         .key_to_avro(&self.value_avro_schema, &HashMap::new())
@@ -495,7 +590,8 @@ Consider defining an index with `CREATE INDEX` and setting `index` field in conn
                         AvroUpdateFormat::Raw => {
                             let op = if w > 0 { b"insert" } else { b"delete" };
 
-                            self.output_consumer.push_key(
+                            self.message_attributes.push(
+                                self.output_consumer.as_mut(),
                                 None,
                                 Some(&self.value_buffer),
                                 &[("op", Some(op))],
@@ -503,7 +599,8 @@ Consider defining an index with `CREATE INDEX` and setting `index` field in conn
                             );
                         }
                         AvroUpdateFormat::ConfluentJdbc if w > 0 => {
-                            self.output_consumer.push_key(
+                            self.message_attributes.push(
+                                self.output_consumer.as_mut(),
                                 Some(&self.value_buffer),
                                 Some(&self.value_buffer),
                                 &[],
@@ -511,8 +608,13 @@ Consider defining an index with `CREATE INDEX` and setting `index` field in conn
                             );
                         }
                         AvroUpdateFormat::ConfluentJdbc => {
-                            self.output_consumer
-                                .push_key(Some(&self.value_buffer), None, &[], 1);
+                            self.message_attributes.push(
+                                self.output_consumer.as_mut(),
+                                Some(&self.value_buffer),
+                                None,
+                                &[],
+                                1,
+                            );
                         }
                         AvroUpdateFormat::Debezium => unreachable!(),
                     }
@@ -539,10 +641,19 @@ Consider defining an index with `CREATE INDEX` and setting `index` field in conn
             return Ok(None);
         };
 
-        let cdc_field = match operation_type {
-            IndexedOperationType::Insert => "I",
-            IndexedOperationType::Delete => "D",
-            IndexedOperationType::Upsert => "U",
+        if self.container_files
+            && self.cdc_field.is_none()
+            && !matches!(operation_type, IndexedOperationType::Insert)
+        {
+            bail!(
+                "Avro object container files cannot represent updates and deletions without a 'cdc_field' that records the operation type of each record"
+            );
+        }
+
+        let (cdc_field, op) = match operation_type {
+            IndexedOperationType::Insert => ("I", "insert"),
+            IndexedOperationType::Delete => ("D", "delete"),
+            IndexedOperationType::Upsert => ("U", "update"),
         };
 
         // Second pass: serialize the key and value for the operation.
@@ -564,6 +675,19 @@ Consider defining an index with `CREATE INDEX` and setting `index` field in conn
         while cursor.val_valid() {
             let w = cursor.weight();
 
+            // Compute message attributes from the inserted record, or the
+            // deleted record for deletions.
+            if let Some(message_fields) = &self.message_fields {
+                let is_delete = matches!(operation_type, IndexedOperationType::Delete);
+                if (w == 1 && !is_delete) || (w == -1 && is_delete) {
+                    message_fields.encode_json(
+                        &cursor.val_to_json()?,
+                        op,
+                        &mut self.message_attributes,
+                    );
+                }
+            }
+
             if w == 1 {
                 match operation_type {
                     IndexedOperationType::Insert | IndexedOperationType::Upsert => {
@@ -649,10 +773,17 @@ Consider defining an index with `CREATE INDEX` and setting `index` field in conn
             match (operation_type, self.update_format.clone()) {
                 (None, _) => (),
                 (Some(IndexedOperationType::Delete), AvroUpdateFormat::ConfluentJdbc) => {
-                    self.output_consumer.push_key(key_buffer, None, &[], 1);
+                    self.message_attributes.push(
+                        self.output_consumer.as_mut(),
+                        key_buffer,
+                        None,
+                        &[],
+                        1,
+                    );
                 }
                 (Some(IndexedOperationType::Delete), AvroUpdateFormat::Raw) => {
-                    self.output_consumer.push_key(
+                    self.message_attributes.push(
+                        self.output_consumer.as_mut(),
                         key_buffer,
                         Some(&self.value_buffer),
                         &[("op", Some(b"delete"))],
@@ -663,11 +794,17 @@ Consider defining an index with `CREATE INDEX` and setting `index` field in conn
                     Some(IndexedOperationType::Insert) | Some(IndexedOperationType::Upsert),
                     AvroUpdateFormat::ConfluentJdbc,
                 ) => {
-                    self.output_consumer
-                        .push_key(key_buffer, Some(&self.value_buffer), &[], 1);
+                    self.message_attributes.push(
+                        self.output_consumer.as_mut(),
+                        key_buffer,
+                        Some(&self.value_buffer),
+                        &[],
+                        1,
+                    );
                 }
                 (Some(IndexedOperationType::Insert), AvroUpdateFormat::Raw) => {
-                    self.output_consumer.push_key(
+                    self.message_attributes.push(
+                        self.output_consumer.as_mut(),
                         key_buffer,
                         Some(&self.value_buffer),
                         &[("op", Some(b"insert"))],
@@ -675,7 +812,8 @@ Consider defining an index with `CREATE INDEX` and setting `index` field in conn
                     );
                 }
                 (Some(IndexedOperationType::Upsert), AvroUpdateFormat::Raw) => {
-                    self.output_consumer.push_key(
+                    self.message_attributes.push(
+                        self.output_consumer.as_mut(),
                         key_buffer,
                         Some(&self.value_buffer),
                         &[("op", Some(b"update"))],
@@ -747,3 +885,127 @@ fn publish_schema(
     );
     Ok(registered_schema.id)
 }
+
+/// Output consumer that packs the Avro datums produced by the encoder into
+/// Avro object container files, for transports that store files rather than
+/// messages, such as the object store connector.
+///
+/// Each file consists of a single data block and is passed to the inner
+/// consumer as one buffer.  A file is completed before it exceeds the maximum
+/// buffer size of the transport or `max_records` records, and at the end of
+/// every step.
+struct ContainerFileConsumer {
+    inner: Box<dyn OutputConsumer>,
+
+    /// File header, which includes the schema and ends with the sync marker.
+    header: Vec<u8>,
+
+    max_records: Option<u64>,
+
+    /// Serialized datums of the current file.
+    block: Vec<u8>,
+
+    /// Number of records in `block`.
+    num_records: usize,
+}
+
+/// Length of the sync marker that terminates the header and each block.
+const SYNC_MARKER_LEN: usize = 16;
+
+/// Upper bound on the size of the record count and block size, which are
+/// encoded as variable-length longs, in front of a block.
+const MAX_BLOCK_PREFIX_LEN: usize = 20;
+
+impl ContainerFileConsumer {
+    fn new(
+        inner: Box<dyn OutputConsumer>,
+        schema: &AvroSchema,
+        max_records: Option<u64>,
+    ) -> AnyResult<Self> {
+        let header = AvroWriter::new(schema, Vec::new()).into_inner()?;
+
+        Ok(Self {
+            inner,
+            header,
+            max_records,
+            block: Vec::new(),
+            num_records: 0,
+        })
+    }
+
+    fn is_full(&self, datum_len: usize, num_records: usize) -> bool {
+        self.header.len() + MAX_BLOCK_PREFIX_LEN + self.block.len() + datum_len + SYNC_MARKER_LEN
+            > self.inner.max_buffer_size_bytes()
+            || self
+                .max_records
+                .is_some_and(|max| (self.num_records + num_records) as u64 > max)
+    }
+
+    /// Pushes the current file, if any, to the inner consumer.
+    fn flush(&mut self) {
+        if self.num_records == 0 {
+            return;
+        }
+
+        let mut file = Vec::with_capacity(
+            self.header.len() + MAX_BLOCK_PREFIX_LEN + self.block.len() + SYNC_MARKER_LEN,
+        );
+        file.extend_from_slice(&self.header);
+        encode_long(self.num_records as i64, &mut file);
+        encode_long(self.block.len() as i64, &mut file);
+        file.append(&mut self.block);
+        file.extend_from_slice(&self.header[self.header.len() - SYNC_MARKER_LEN..]);
+
+        self.inner.push_buffer(&file, self.num_records);
+        self.num_records = 0;
+    }
+}
+
+/// Appends `n` to `buffer` as a zigzag-encoded variable-length integer.
+fn encode_long(n: i64, buffer: &mut Vec<u8>) {
+    let mut n = ((n << 1) ^ (n >> 63)) as u64;
+    while n >= 0x80 {
+        buffer.push((n & 0x7f) as u8 | 0x80);
+        n >>= 7;
+    }
+    buffer.push(n as u8);
+}
+
+impl OutputConsumer for ContainerFileConsumer {
+    fn max_buffer_size_bytes(&self) -> usize {
+        self.inner.max_buffer_size_bytes()
+    }
+
+    fn batch_start(&mut self, step: Step) {
+        self.inner.batch_start(step)
+    }
+
+    fn push_buffer(&mut self, buffer: &[u8], num_records: usize) {
+        self.flush();
+        self.inner.push_buffer(buffer, num_records)
+    }
+
+    fn push_key(
+        &mut self,
+        _key: Option<&[u8]>,
+        val: Option<&[u8]>,
+        _headers: &[(&str, Option<&[u8]>)],
+        num_records: usize,
+    ) {
+        let Some(val) = val else {
+            return;
+        };
+
+        if self.num_records > 0 && self.is_full(val.len(), num_records) {
+            self.flush();
+        }
+
+        self.block.extend_from_slice(val);
+        self.num_records += num_records;
+    }
+
+    fn batch_end(&mut self) {
+        self.flush();
+        self.inner.batch_end()
+    }
+}
@@ -339,6 +339,91 @@ pub fn validate_field_schema(
     Ok(())
 }
 
+/// Derive a reader schema used to resolve records written with `writer_schema`
+/// into rows of a table with the given columns, according to Avro schema
+/// resolution rules.
+///
+/// The reader schema contains a field for each table column.  Columns whose
+/// type is compatible with the writer schema keep the writer's field schema,
+/// so that, e.g., `timestamp-millis` values are not converted.  Other columns
+/// use the schema generated for the column type, letting schema resolution
+/// promote writer values to it (e.g., `int` to `long`).  Nullable columns
+/// default to `null` so that they can be missing in the writer schema.
+pub fn reader_schema(writer_schema: &AvroSchema, columns: &[Field]) -> Result<AvroSchema, String> {
+    let AvroSchema::Record(writer_record) = writer_schema else {
+        return Err(format!(
+            "expected schema of type 'record', but found {}",
+            schema_json(writer_schema)
+        ));
+    };
+
+    let builder = AvroSchemaBuilder::new();
+    let mut fields = Vec::with_capacity(columns.len());
+    let mut lookup = BTreeMap::new();
+
+    for (position, column) in columns.iter().enumerate() {
+        let writer_field = lookup_field(&writer_record.fields, column);
+        let name = writer_field
+            .map(|f| f.name.clone())
+            .unwrap_or_else(|| column.name.name());
+
+        let schema = match writer_field {
+            Some(writer_field)
+                if validate_field_schema(
+                    &writer_field.name,
+                    &writer_field.schema,
+                    &column.columntype,
+                )
+                .is_ok() =>
+            {
+                writer_field.schema.clone()
+            }
+            None if !column.columntype.nullable => {
+                return Err(format!(
+                    "column '{}' is missing in the Avro schema",
+                    column.name.name()
+                ));
+            }
+            _ => builder
+                .column_type_to_avro_schema(&column.columntype, false)
+                .map_err(|e| {
+                    format!(
+                        "error generating Avro schema for column '{}': {e}",
+                        column.name.name()
+                    )
+                })?,
+        };
+
+        // Avro requires the default value of a union to match its first
+        // variant, which is `null` in the schemas we generate.
+        let default = match schema_unwrap_optional(&schema) {
+            (_, OptionalField::Optional(1)) => Some(serde_json::Value::Null),
+            _ => None,
+        };
+
+        lookup.insert(name.clone(), position);
+        fields.push(RecordField {
+            name,
+            doc: None,
+            aliases: None,
+            default,
+            schema,
+            order: RecordFieldOrder::Ascending,
+            position,
+            custom_attributes: BTreeMap::new(),
+        });
+    }
+
+    Ok(AvroSchema::Record(RecordSchema {
+        name: writer_record.name.clone(),
+        aliases: None,
+        doc: None,
+        fields,
+        lookup,
+        attributes: BTreeMap::new(),
+    }))
+}
+
 pub fn is_valid_avro_identifier(ident: &str) -> bool {
     if ident.is_empty() {
         return false;
@@ -4,7 +4,7 @@ use super::{
     serializer::{avro_ser_config, AvroSchemaSerializer},
 };
 use crate::{
-    format::{avro::from_avro_value, InputBuffer, Parser},
+    format::{avro::from_avro_value, message_fields::MessageFields, InputBuffer, Parser},
     static_compile::seroutput::SerBatchImpl,
     test::{
         generate_test_batches, generate_test_batches_with_weights, mock_parser_pipeline, KeyStruct,
@@ -12,9 +12,13 @@ use crate::{
     },
     Encoder, FormatConfig, ParseError, SerBatch,
 };
-use apache_avro::{from_avro_datum, schema::ResolvedSchema, to_avro_datum, Schema as AvroSchema};
+use apache_avro::{
+    from_avro_datum, schema::ResolvedSchema, to_avro_datum, types::Value as AvroValue, Codec,
+    Reader as AvroReader, Schema as AvroSchema, Writer as AvroWriter,
+};
 use dbsp::{utils::Tup2, OrdIndexedZSet};
 use dbsp::{DBData, OrdZSet};
+use feldera_adapterlib::format::MetadataValue;
 use feldera_sqllib::ByteArray;
 use feldera_types::{
     deserialize_table_record,
@@ -239,6 +243,7 @@ where
         update_format: AvroUpdateFormat::Raw,
         schema: Some(avro_schema_str.to_string()),
         skip_schema_id: false,
+        object_container_file: false,
         registry_config: Default::default(),
     };
 
@@ -288,6 +293,7 @@ where
         update_format: AvroUpdateFormat::Debezium,
         schema: Some(schema_json(&debezium_schema)),
         skip_schema_id: false,
+        object_container_file: false,
         registry_config: Default::default(),
     };
 
@@ -439,6 +445,7 @@ fn test_extra_columns() {
             update_format: AvroUpdateFormat::Raw,
             schema: Some(schema_str.to_string()),
             skip_schema_id: false,
+            object_container_file: false,
             registry_config: Default::default(),
         },
         input_batches,
@@ -448,6 +455,123 @@ fn test_extra_columns() {
     run_parser_test(vec![test]);
 }
 
+/// Object container files are resolved against the table schema: fields are
+/// matched by name, extra fields are ignored, missing nullable columns are
+/// set to NULL, and values are promoted to the column type.
+#[test]
+fn test_object_container_file() {
+    let schema_str = r#"{
+        "type": "record",
+        "name": "Export",
+        "fields": [
+            { "name": "s", "type": "string" },
+            { "name": "id", "type": "int" },
+            { "name": "extra", "type": "double" },
+            { "name": "b", "type": "boolean" }
+        ]
+    }"#;
+    let schema = AvroSchema::parse_str(schema_str).unwrap();
+
+    let vals = (0..10)
+        .map(|id| TestStruct {
+            id,
+            b: id % 2 == 0,
+            i: None,
+            s: format!("s{id}"),
+        })
+        .collect::<Vec<_>>();
+
+    let mut writer = AvroWriter::with_codec(&schema, Vec::new(), Codec::Snappy);
+    for v in vals.iter() {
+        writer
+            .append(AvroValue::Record(vec![
+                ("s".to_string(), AvroValue::String(v.s.clone())),
+                ("id".to_string(), AvroValue::Int(v.id as i32)),
+                ("extra".to_string(), AvroValue::Double(0.5)),
+                ("b".to_string(), AvroValue::Boolean(v.b)),
+            ]))
+            .unwrap();
+    }
+    let file = writer.into_inner().unwrap();
+
+    let test = TestCase {
+        relation_schema: TestStruct::relation_schema(),
+        config: AvroParserConfig {
+            update_format: AvroUpdateFormat::Raw,
+            schema: None,
+            skip_schema_id: false,
+            object_container_file: true,
+            registry_config: Default::default(),
+        },
+        input_batches: vec![(file, vec![])],
+        expected_output: vals.into_iter().map(MockUpdate::Insert).collect(),
+    };
+
+    run_parser_test(vec![test]);
+}
+
+/// Connector metadata columns override the fields of the same name in each
+/// record.
+#[test]
+fn test_avro_metadata() {
+    let schema = AvroSchema::parse_str(TestStruct::avro_schema()).unwrap();
+    let format_config = FormatConfig {
+        name: Cow::from("avro"),
+        config: serde_yaml::to_value(AvroParserConfig {
+            update_format: AvroUpdateFormat::Raw,
+            schema: Some(TestStruct::avro_schema().to_string()),
+            skip_schema_id: false,
+            object_container_file: false,
+            registry_config: Default::default(),
+        })
+        .unwrap(),
+    };
+    let (consumer, mut parser, outputs) = mock_parser_pipeline::<TestStruct, TestStruct>(
+        &TestStruct::relation_schema(),
+        &format_config,
+    )
+    .unwrap();
+    consumer.on_error(Some(Box::new(|_, _| {})));
+
+    let record = TestStruct {
+        id: 1,
+        b: true,
+        i: Some(1),
+        s: "payload".to_string(),
+    };
+    for metadata in [
+        vec![
+            ("i".to_string(), MetadataValue::Integer(5)),
+            ("s".to_string(), MetadataValue::String("key".to_string())),
+        ],
+        vec![
+            ("i".to_string(), MetadataValue::Null),
+            ("s".to_string(), MetadataValue::Bytes(b"bytes".to_vec())),
+        ],
+    ] {
+        let (mut buffer, errors) =
+            parser.parse_with_metadata(&serialize_record(&record, &schema), &metadata);
+        assert_eq!(errors, vec![]);
+        buffer.flush();
+    }
+
+    assert_eq!(
+        outputs.state().flushed,
+        vec![
+            MockUpdate::Insert(TestStruct {
+                i: Some(5),
+                s: "key".to_string(),
+                ..record.clone()
+            }),
+            MockUpdate::Insert(TestStruct {
+                i: None,
+                s: "bytes".to_string(),
+                ..record
+            }),
+        ]
+    );
+}
+
 /// Deserializing non-optional fields into NULL-able columns.
 #[test]
 fn test_non_null_to_nullable() {
@@ -515,6 +639,7 @@ fn test_non_null_to_nullable() {
             update_format: AvroUpdateFormat::Raw,
             schema: Some(schema_str.to_string()),
             skip_schema_id: false,
+            object_container_file: false,
             registry_config: Default::default(),
         },
         input_batches,
@@ -591,6 +716,7 @@ fn test_nullable_to_non_nullable() {
             update_format: AvroUpdateFormat::Raw,
             schema: Some(schema_str.to_string()),
             skip_schema_id: false,
+            object_container_file: false,
             registry_config: Default::default(),
         },
         input_batches,
@@ -683,6 +809,7 @@ fn test_parse_binary() {
             update_format: AvroUpdateFormat::Raw,
             schema: Some(TestBinary::avro_schema().to_string()),
             skip_schema_id: false,
+            object_container_file: false,
             registry_config: Default::default(),
         },
         input_batches,
@@ -754,6 +881,7 @@ fn test_ms_time() {
             update_format: AvroUpdateFormat::Raw,
             schema: Some(schema_str.to_string()),
             skip_schema_id: false,
+            object_container_file: false,
             registry_config: Default::default(),
         },
         input_batches,
@@ -1229,6 +1357,164 @@ fn test_non_unique_keys() {
     encoder.consumer().batch_end();
 }
 
+#[test]
+fn test_avro_message_fields() {
+    let schema_str = TestStruct::avro_schema().to_string();
+    let config: AvroEncoderConfig = AvroEncoderConfig {
+        schema: Some(schema_str.clone()),
+        ..Default::default()
+    };
+    let consumer = MockOutputConsumer::new();
+    let consumer_data = consumer.data.clone();
+    let consumer_topics = consumer.topics.clone();
+
+    let mut encoder = AvroEncoder::create(
+        "avro_test_endpoint",
+        &None,
+        &TestStruct::relation_schema(),
+        Box::new(consumer),
+        config,
+        None,
+    )
+    .unwrap()
+    .with_message_fields(MessageFields::new(
+        Some(&["id".to_string()]),
+        &["b".to_string(), "i".to_string()],
+        Some("s"),
+        Some("change"),
+    ));
+
+    let v1 = TestStruct {
+        id: 1,
+        b: true,
+        i: None,
+        s: "foo".to_string(),
+    };
+    let v2 = TestStruct {
+        id: 2,
+        b: false,
+        i: Some(5),
+        s: "bar".to_string(),
+    };
+
+    let zset = OrdZSet::from_keys((), vec![Tup2(v1, 1), Tup2(v2, -1)]);
+    let zset = Arc::new(<SerBatchImpl<_, TestStruct, ()>>::new(zset)) as Arc<dyn SerBatch>;
+
+    encoder.consumer().batch_start(0);
+    encoder.encode(zset.as_batch_reader()).unwrap();
+    encoder.consumer().batch_end();
+
+    let actual_output = consumer_data
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(k, _v, headers)| {
+            (
+                String::from_utf8(k.clone().unwrap()).unwrap(),
+                headers
+                    .iter()
+                    .map(|(name, value)| {
+                        (
+                            name.clone(),
+                            String::from_utf8(value.clone().unwrap()).unwrap(),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        actual_output,
+        vec![
+            (
+                "2".to_string(),
+                vec![
+                    ("op".to_string(), "delete".to_string()),
+                    ("b".to_string(), "false".to_string()),
+                    ("i".to_string(), "5".to_string()),
+                    ("change".to_string(), "delete".to_string()),
+                ]
+            ),
+            (
+                "1".to_string(),
+                vec![
+                    ("op".to_string(), "insert".to_string()),
+                    ("b".to_string(), "true".to_string()),
+                    ("change".to_string(), "insert".to_string()),
+                ]
+            ),
+        ]
+    );
+    assert_eq!(
+        *consumer_topics.lock().unwrap(),
+        vec![Some("bar".to_string()), Some("foo".to_string())]
+    );
+}
+
+#[test]
+fn test_avro_container_files() {
+    let schema = TestStruct::avro_schema();
+    let config: AvroEncoderConfig = AvroEncoderConfig {
+        schema: Some(schema.to_string()),
+        ..Default::default()
+    };
+    let consumer = MockOutputConsumer::new();
+    let consumer_data = consumer.data.clone();
+
+    let mut encoder = AvroEncoder::create(
+        "avro_test_endpoint",
+        &None,
+        &TestStruct::relation_schema(),
+        Box::new(consumer),
+        config,
+        None,
+    )
+    .unwrap()
+    .with_container_files("avro_test_endpoint", Some(2))
+    .unwrap();
+
+    let records = (0..5)
+        .map(|id| TestStruct {
+            id,
+            b: id % 2 == 0,
+            i: Some(id as i64),
+            s: format!("s{id}"),
+        })
+        .collect::<Vec<_>>();
+
+    let zset = OrdZSet::from_keys((), records.iter().map(|r| Tup2(r.clone(), 1)).collect());
+    let zset = Arc::new(<SerBatchImpl<_, TestStruct, ()>>::new(zset)) as Arc<dyn SerBatch>;
+
+    encoder.consumer().batch_start(0);
+    encoder.encode(zset.as_batch_reader()).unwrap();
+    encoder.consumer().batch_end();
+
+    // Each file is a complete container file with at most 2 records.
+    let schema = AvroSchema::parse_str(schema).unwrap();
+    let files = consumer_data.lock().unwrap().clone();
+    assert_eq!(files.len(), 3);
+    let mut actual_output = Vec::new();
+    for (_k, v, _headers) in files.iter() {
+        let reader = AvroReader::new(v.as_deref().unwrap()).unwrap();
+        let values = reader
+            .map(|val| from_avro_value::<TestStruct>(&val.unwrap(), &schema).unwrap())
+            .collect::<Vec<_>>();
+        assert!(values.len() <= 2);
+        actual_output.extend(values);
+    }
+    assert_eq!(actual_output, records);
+
+    // Container files cannot represent deletions.
+    let zset = OrdZSet::from_keys((), vec![Tup2(records[0].clone(), -1)]);
+    let zset = Arc::new(<SerBatchImpl<_, TestStruct, ()>>::new(zset)) as Arc<dyn SerBatch>;
+
+    encoder.consumer().batch_start(1);
+    let err = encoder.encode(zset.as_batch_reader()).unwrap_err();
+    assert!(err.to_string().contains("cannot represent deletions"));
+    encoder.consumer().batch_end();
+}
+
 proptest! {
     #[test]
     fn proptest_raw_avro_output(data in generate_test_batches_with_weights(10, 20))
@@ -1,25 +1,27 @@
 use crate::{
     catalog::{CursorWithPolarity, DeCollectionStream, RecordFormat, SerCursor},
-    format::{Encoder, InputFormat, OutputFormat, ParseError, Parser},
+    format::{Encoder, InputFormat, OutputFormat, ParseError, Parser, MAX_DUPLICATES},
     util::truncate_ellipse,
     ControllerError, OutputConsumer,
 };
 use actix_web::HttpRequest;
-use anyhow::{bail, Result as AnyResult};
+use anyhow::{anyhow, bail, Result as AnyResult};
+use csv::{ByteRecord, QuoteStyle};
 use erased_serde::Serialize as ErasedSerialize;
 use feldera_types::{
     config::ConnectorConfig,
-    format::csv::{CsvEncoderConfig, CsvParserConfig},
+    format::csv::{CsvEncoderConfig, CsvParserConfig, CsvQuoteStyle},
 };
 use serde::Deserialize;
 use serde_urlencoded::Deserializer as UrlDeserializer;
-use std::{borrow::Cow, mem::take};
+use std::{borrow::Cow, collections::VecDeque, mem::take};
 
 pub(crate) mod deserializer;
+pub(crate) mod serializer;
 use crate::catalog::{InputCollectionHandle, SerBatchReader};
 pub use deserializer::byte_record_deserializer;
 pub use deserializer::string_record_deserializer;
-use feldera_types::program_schema::Relation;
+use feldera_types::program_schema::{Relation, SqlIdentifier};
 use serde_yaml::Value as YamlValue;
 
 use super::{InputBuffer, Splitter};
@@ -28,6 +30,78 @@ use super::{InputBuffer, Splitter};
 /// truncate it to `MAX_RECORD_LEN_IN_ERRMSG` bytes.
 static MAX_RECORD_LEN_IN_ERRMSG: usize = 4096;
 
+/// Returns a CSV reader builder configured according to `config`.
+pub(crate) fn csv_reader_builder(config: &CsvParserConfig) -> csv::ReaderBuilder {
+    let mut builder = csv::ReaderBuilder::new();
+    builder
+        // We skip the headers ourselves, without passing them to the
+        // reader, so we unconditionally turn off headers in the reader.
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(config.delimiter().0)
+        .quote(config.quote())
+        .escape(config.escape())
+        .trim(if config.trim {
+            csv::Trim::All
+        } else {
+            csv::Trim::None
+        });
+    builder
+}
+
+/// Returns a CSV writer builder configured according to `config`.
+pub(crate) fn csv_writer_builder(config: &CsvEncoderConfig) -> csv::WriterBuilder {
+    let mut builder = csv::WriterBuilder::new();
+    builder
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(config.delimiter().0)
+        .quote(config.quote())
+        .escape(config.escape().unwrap_or(b'\\'))
+        .double_quote(config.escape().is_none())
+        .quote_style(match config.quote_style {
+            CsvQuoteStyle::Necessary => QuoteStyle::Necessary,
+            CsvQuoteStyle::Always => QuoteStyle::Always,
+            CsvQuoteStyle::NonNumeric => QuoteStyle::NonNumeric,
+            CsvQuoteStyle::Never => QuoteStyle::Never,
+        });
+    builder
+}
+
+/// Returns an encoder configuration that produces records that can be parsed
+/// with `config`.
+pub(crate) fn csv_parser_encoder_config(config: &CsvParserConfig) -> CsvEncoderConfig {
+    CsvEncoderConfig {
+        delimiter: config.delimiter,
+        quote: config.quote,
+        escape: config.escape,
+        null_string: config.null_string.clone(),
+        ..Default::default()
+    }
+}
+
+/// Validates the characters in a CSV configuration.
+fn validate_chars(
+    delimiter: char,
+    quote: char,
+    escape: Option<char>,
+    comment: Option<char>,
+) -> Result<(), String> {
+    for (name, c) in [
+        ("delimiter", Some(delimiter)),
+        ("quote", Some(quote)),
+        ("escape", escape),
+        ("comment", comment),
+    ] {
+        if let Some(c) = c {
+            if !c.is_ascii() {
+                return Err(format!("'{name}' must be an ASCII character, found '{c}'"));
+            }
+        }
+    }
+    Ok(())
+}
+
 /// CSV format parser.
 pub struct CsvInputFormat;
 
@@ -41,10 +115,21 @@ impl InputFormat for CsvInputFormat {
     // HTTP query, but a specialized method gives us more flexibility.
     fn config_from_http_request(
         &self,
-        _endpoint_name: &str,
-        _request: &HttpRequest,
+        endpoint_name: &str,
+        request: &HttpRequest,
     ) -> Result<Box<dyn ErasedSerialize>, ControllerError> {
-        Ok(Box::new(CsvParserConfig::default()))
+        Ok(Box::new(
+            CsvParserConfig::deserialize(UrlDeserializer::new(form_urlencoded::parse(
+                request.query_string().as_bytes(),
+            )))
+            .map_err(|e| {
+                ControllerError::parser_config_parse_error(
+                    endpoint_name,
+                    &e,
+                    request.query_string(),
+                )
+            })?,
+        ))
     }
 
     fn new_parser(
@@ -61,11 +146,30 @@ impl InputFormat for CsvInputFormat {
             )
         })?;
 
-        let headers = config.headers;
-        let input_stream = input_stream
+        validate_chars(
+            config.delimiter,
+            config.quote,
+            config.escape,
+            config.comment,
+        )
+        .map_err(|e| ControllerError::invalid_parser_configuration(endpoint_name, &e))?;
+        if config.columns_by_name && !config.headers {
+            return Err(ControllerError::invalid_parser_configuration(
+                endpoint_name,
+                "'columns_by_name' requires 'headers' to be set",
+            ));
+        }
+
+        let columns = input_stream
+            .schema
+            .fields
+            .iter()
+            .map(|f| f.name.clone())
+            .collect();
+        let stream = input_stream
             .handle
-            .configure_deserializer(RecordFormat::Csv(config))?;
-        Ok(Box::new(CsvParser::new(input_stream, headers)) as Box<dyn Parser>)
+            .configure_deserializer(RecordFormat::Csv(config.clone()))?;
+        Ok(Box::new(CsvParser::new(stream, config, columns)) as Box<dyn Parser>)
     }
 }
 
@@ -73,80 +177,197 @@ struct CsvParser {
     /// Input handle to push parsed data to.
     input_stream: Box<dyn DeCollectionStream>,
 
-    /// Whether the input starts with a header row.
-    headers: bool,
+    config: CsvParserConfig,
+
+    /// Names of the columns of the table.
+    columns: Vec<SqlIdentifier>,
+
+    /// Whether the next record is the header line.
+    expect_header: bool,
+
+    /// With `columns_by_name`, the position of each column of the table in
+    /// input records, computed from the header line.
+    column_positions: Option<Vec<Option<usize>>>,
+
+    /// Finds record boundaries.
+    scanner: RecordScanner,
+
+    /// Reader used to split records into fields when we need to look at
+    /// individual fields, i.e., with `columns_by_name` or `weighted`.
+    reader: csv::Reader<VecDeque<u8>>,
+    record: ByteRecord,
+    writer_builder: csv::WriterBuilder,
 
     last_event_number: u64,
 }
 
 impl CsvParser {
-    fn new(input_stream: Box<dyn DeCollectionStream>, headers: bool) -> Self {
+    fn new(
+        input_stream: Box<dyn DeCollectionStream>,
+        config: CsvParserConfig,
+        columns: Vec<SqlIdentifier>,
+    ) -> Self {
         Self {
             input_stream,
+            expect_header: config.headers,
+            column_positions: None,
+            scanner: RecordScanner::new(&config),
+            reader: csv_reader_builder(&config).from_reader(VecDeque::new()),
+            record: ByteRecord::new(),
+            writer_builder: csv_writer_builder(&csv_parser_encoder_config(&config)),
+            columns,
+            config,
             last_event_number: 0,
-            headers,
         }
     }
 
+    fn is_comment(&self, record: &[u8]) -> bool {
+        self.config.comment().is_some() && record.first().copied() == self.config.comment()
+    }
+
     fn parse_record(&mut self, record: &[u8], errors: &mut Vec<ParseError>) {
-        if !self.headers || self.last_event_number > 0 {
-            if let Err(e) = self.input_stream.insert(record) {
-                errors.push(ParseError::text_event_error(
-                    "failed to deserialize CSV record",
-                    e,
-                    self.last_event_number + 1,
-                    Some(
-                        &std::str::from_utf8(record)
-                            .map(|s| s.to_string())
-                            .unwrap_or_else(|_| format!("{:?}", record))
-                            .to_string(),
-                    ),
-                    None,
-                ));
+        if self.is_comment(record) {
+            return;
+        }
+
+        let result = if self.expect_header {
+            self.expect_header = false;
+            if self.config.columns_by_name {
+                self.parse_header(record)
+            } else {
+                Ok(())
             }
+        } else {
+            self.push_record(record)
+        };
+
+        if let Err(e) = result {
+            errors.push(ParseError::text_event_error(
+                "failed to deserialize CSV record",
+                e,
+                self.last_event_number + 1,
+                Some(
+                    &std::str::from_utf8(record)
+                        .map(|s| s.to_string())
+                        .unwrap_or_else(|_| format!("{:?}", record))
+                        .to_string(),
+                ),
+                None,
+            ));
         }
     }
 
-    /// Tries to split `buffer` into a CSV record followed by any remaining text.
-    ///
-    /// A CSV record is usually one line that ends in a new-line, but it can
-    /// span multiple lines if new-lines are enclosed in double quotes.
-    fn split_record<'a>(&mut self, buffer: &'a [u8]) -> Option<(&'a [u8], &'a [u8])> {
-        // This uses the simple rule that a new-line ends a record if it is not
-        // in double quotes.  The "standard" format for CSV escapes double
-        // quotes by doubling them (e.g. `"a""b""c"` unescapes to `a"b"c`), so
-        // that any even number of quotes followed by a new-line ends a reocrd,
-        // but any odd number followed by a new-line is a continuation of the
-        // field.  This means that this rule properly handles escapes.
-        //
-        // If we allow the user to configure the CSV format used, we'll need to
-        // adjust this to match the configuration.
-        let mut quoted = false;
-        for (offset, c) in buffer.iter().enumerate() {
-            match c {
-                b'"' => quoted = !quoted,
-                b'\n' if !quoted => {
-                    return Some(buffer.split_at(offset + 1));
-                }
-                _ => (),
+    /// Splits `record` into fields, stored in `self.record`.
+    fn read_fields(&mut self, record: &[u8]) -> AnyResult<()> {
+        self.reader.get_mut().extend(record.iter());
+        if !self.reader.read_byte_record(&mut self.record)? {
+            bail!("empty CSV record");
+        }
+        Ok(())
+    }
+
+    /// Computes the position of each column of the table in the input from
+    /// the header line.
+    fn parse_header(&mut self, record: &[u8]) -> AnyResult<()> {
+        self.read_fields(record)?;
+        let mut names = self.record.iter().collect::<Vec<_>>();
+        if self.config.weighted {
+            names.pop();
+        }
+        let names = names
+            .into_iter()
+            .map(|name| SqlIdentifier::new(String::from_utf8_lossy(name), false))
+            .collect::<Vec<_>>();
+
+        self.column_positions = Some(
+            self.columns
+                .iter()
+                .map(|column| names.iter().position(|name| column == name))
+                .collect(),
+        );
+        Ok(())
+    }
+
+    fn push_record(&mut self, record: &[u8]) -> AnyResult<()> {
+        if self.column_positions.is_none() && !self.config.weighted {
+            return self.input_stream.insert(record);
+        }
+
+        self.read_fields(record)?;
+
+        let weight = if self.config.weighted {
+            let Some(weight) = self.record.get(self.record.len().wrapping_sub(1)) else {
+                bail!("missing weight column");
+            };
+            let weight = std::str::from_utf8(weight)
+                .ok()
+                .and_then(|w| w.trim().parse::<i64>().ok())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "invalid weight '{}': expected an integer",
+                        String::from_utf8_lossy(weight)
+                    )
+                })?;
+            if !(-MAX_DUPLICATES..=MAX_DUPLICATES).contains(&weight) {
+                bail!("weight {weight} exceeds the maximum supported weight {MAX_DUPLICATES}");
+            }
+            weight
+        } else {
+            1
+        };
+
+        // Reorder fields to match the columns of the table.  Fields that
+        // are not used by the table are dropped, and missing fields are
+        // set to NULL.
+        let reordered;
+        let record = if let Some(positions) = &self.column_positions {
+            let num_fields = if self.config.weighted {
+                self.record.len() - 1
+            } else {
+                self.record.len()
+            };
+            let null = self.config.null_string.as_bytes();
+            let fields = positions.iter().map(|position| match position {
+                Some(position) if *position < num_fields => &self.record[*position],
+                _ => null,
+            });
+            let mut writer = self.writer_builder.from_writer(Vec::new());
+            writer.write_record(fields)?;
+            reordered = writer.into_inner().map_err(|e| anyhow!(e.to_string()))?;
+            reordered.as_slice()
+        } else {
+            record
+        };
+
+        for _ in 0..weight.unsigned_abs() {
+            if weight > 0 {
+                self.input_stream.insert(record)?;
+            } else {
+                self.input_stream.delete(record)?;
             }
         }
-        None
+        Ok(())
     }
 }
 
 impl Parser for CsvParser {
     fn fork(&self) -> Box<dyn Parser> {
-        Box::new(Self::new(self.input_stream.fork(), self.headers))
+        Box::new(Self::new(
+            self.input_stream.fork(),
+            self.config.clone(),
+            self.columns.clone(),
+        ))
     }
 
     fn splitter(&self) -> Box<dyn super::Splitter> {
-        Box::new(CsvSplitter::new(self.headers))
+        Box::new(CsvSplitter::new(&self.config))
     }
 
     fn parse(&mut self, mut data: &[u8]) -> (Option<Box<dyn InputBuffer>>, Vec<ParseError>) {
         let mut errors = Vec::new();
-        while let Some((record, rest)) = self.split_record(data) {
+        self.scanner.reset();
+        while let Some(n) = self.scanner.scan(data) {
+            let (record, rest) = data.split_at(n);
             self.parse_record(record, &mut errors);
             self.last_event_number += 1;
             data = rest;
@@ -158,49 +379,110 @@ impl Parser for CsvParser {
     }
 }
 
-struct CsvSplitter {
+/// Finds the boundaries between CSV records.
+///
+/// This uses the simple rule that a new-line ends a record if it is not in
+/// quotes.  The "standard" format for CSV escapes quotes by doubling them
+/// (e.g. `"a""b""c"` unescapes to `a"b"c`), so that any even number of
+/// quotes followed by a new-line ends a record, but any odd number followed
+/// by a new-line is a continuation of the field.  This means that this rule
+/// properly handles escapes.  When an escape character is configured, the
+/// character that follows it within quotes is skipped.  Quotes in comment
+/// lines are ignored.
+#[derive(Clone)]
+struct RecordScanner {
+    quote: u8,
+    escape: Option<u8>,
+    comment: Option<u8>,
+
+    /// Inside a quoted field.
     quoted: bool,
+
+    /// The previous character is the escape character.
+    escaped: bool,
+
+    /// Inside a comment line.
+    in_comment: bool,
+
+    /// At the start of a line.
+    line_start: bool,
+}
+
+impl RecordScanner {
+    fn new(config: &CsvParserConfig) -> Self {
+        Self {
+            quote: config.quote(),
+            escape: config.escape(),
+            comment: config.comment(),
+            quoted: false,
+            escaped: false,
+            in_comment: false,
+            line_start: true,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.quoted = false;
+        self.escaped = false;
+        self.in_comment = false;
+        self.line_start = true;
+    }
+
+    /// Returns the offset just past the new-line that ends the first record
+    /// in `data`, or `None` if `data` does not contain a complete record.
+    fn scan(&mut self, data: &[u8]) -> Option<usize> {
+        for (offset, &c) in data.iter().enumerate() {
+            let line_start = take(&mut self.line_start);
+            if self.escaped {
+                self.escaped = false;
+            } else if c == b'\n' && !self.quoted {
+                self.in_comment = false;
+                self.line_start = true;
+                return Some(offset + 1);
+            } else if line_start && !self.quoted && Some(c) == self.comment {
+                self.in_comment = true;
+            } else if !self.in_comment {
+                if self.quoted && Some(c) == self.escape {
+                    self.escaped = true;
+                } else if c == self.quote {
+                    self.quoted = !self.quoted;
+                }
+            }
+        }
+        None
+    }
+}
+
+struct CsvSplitter {
+    scanner: RecordScanner,
     headers: bool,
 }
 
 impl CsvSplitter {
-    fn new(headers: bool) -> Self {
+    fn new(config: &CsvParserConfig) -> Self {
         Self {
-            quoted: false,
-            headers,
+            scanner: RecordScanner::new(config),
+            headers: config.headers,
         }
     }
 }
 
 impl Splitter for CsvSplitter {
     fn input(&mut self, data: &[u8]) -> Option<usize> {
-        // This uses the simple rule that a new-line ends a record if it is not
-        // in double quotes.  The "standard" format for CSV escapes double
-        // quotes by doubling them (e.g. `"a""b""c"` unescapes to `a"b"c`), so
-        // that any even number of quotes followed by a new-line ends a reocrd,
-        // but any odd number followed by a new-line is a continuation of the
-        // field.  This means that this rule properly handles escapes.
-        //
-        // If we allow the user to configure the CSV format used, we'll need to
-        // adjust this to match the configuration.
-        for (offset, c) in data.iter().enumerate() {
-            match c {
-                b'"' => self.quoted = !self.quoted,
-                b'\n' if !self.quoted => {
-                    if self.headers {
-                        self.headers = false;
-                    } else {
-                        return Some(offset + 1);
-                    }
-                }
-                _ => (),
+        let mut start = 0;
+        while let Some(n) = self.scanner.scan(&data[start..]) {
+            if self.headers {
+                self.headers = false;
+                start += n;
+            } else {
+                return Some(start + n);
             }
         }
         None
     }
 
     fn clear(&mut self) {
-        self.quoted = false;
+        self.scanner.reset();
     }
 }
 
@@ -236,7 +518,7 @@ impl OutputFormat for CsvOutputFormat {
         endpoint_name: &str,
         config: &ConnectorConfig,
         key_schema: &Option<Relation>,
-        _value_schema: &Relation,
+        value_schema: &Relation,
         consumer: Box<dyn OutputConsumer>,
     ) -> Result<Box<dyn Encoder>, ControllerError> {
         if key_schema.is_some() {
@@ -264,7 +546,19 @@ impl OutputFormat for CsvOutputFormat {
             ));
         }
 
-        Ok(Box::new(CsvEncoder::new(consumer, csv_config)))
+        validate_chars(
+            csv_config.delimiter,
+            csv_config.quote,
+            csv_config.escape,
+            None,
+        )
+        .map_err(|e| ControllerError::invalid_encoder_configuration(endpoint_name, &e))?;
+
+        Ok(Box::new(CsvEncoder::new(
+            consumer,
+            csv_config,
+            value_schema,
+        )))
     }
 }
 
@@ -275,16 +569,35 @@ struct CsvEncoder {
     config: CsvEncoderConfig,
     buffer: Vec<u8>,
     max_buffer_size: usize,
+
+    /// Header line to output before the first record, if any.
+    header: Option<Vec<u8>>,
 }
 
 impl CsvEncoder {
-    fn new(output_consumer: Box<dyn OutputConsumer>, config: CsvEncoderConfig) -> Self {
+    fn new(
+        output_consumer: Box<dyn OutputConsumer>,
+        config: CsvEncoderConfig,
+        value_schema: &Relation,
+    ) -> Self {
         let max_buffer_size = output_consumer.max_buffer_size_bytes();
+        let header = config.headers.then(|| {
+            let mut writer = csv_writer_builder(&config).from_writer(Vec::new());
+            let _ = writer.write_record(
+                value_schema
+                    .fields
+                    .iter()
+                    .map(|f| f.name.name())
+                    .chain(std::iter::once("weight".to_string())),
+            );
+            writer.into_inner().unwrap_or_default()
+        });
         Self {
             output_consumer,
             config,
             buffer: Vec::new(),
             max_buffer_size,
+            header,
         }
     }
 }
@@ -300,16 +613,18 @@ impl Encoder for CsvEncoder {
         let mut num_records = 0;
 
         let mut cursor =
-            CursorWithPolarity::new(batch.cursor(RecordFormat::Csv(CsvParserConfig {
-                delimiter: self.config.delimiter,
-                ..Default::default()
-            }))?);
+            CursorWithPolarity::new(batch.cursor(RecordFormat::CsvEncoder(self.config.clone()))?);
 
         while cursor.key_valid() {
             if !cursor.val_valid() {
                 cursor.step_key();
                 continue;
             }
+
+            // The header line precedes the first record.
+            if let Some(header) = self.header.take() {
+                buffer.extend_from_slice(&header);
+            }
             let prev_len = buffer.len();
 
             // `serialize_key_weight`
@@ -363,9 +678,19 @@ impl Encoder for CsvEncoder {
 
 #[cfg(test)]
 mod test {
+    use super::CsvEncoder;
     use crate::format::string_record_deserializer;
+    use crate::{
+        catalog::SerBatchReader,
+        static_compile::seroutput::SerBatchImpl,
+        test::{mock_parser_pipeline, MockOutputConsumer, MockUpdate, TestStruct},
+        Encoder, FormatConfig,
+    };
+    use dbsp::{utils::Tup2, OrdZSet};
     use feldera_types::deserialize_table_record;
+    use feldera_types::format::csv::{CsvEncoderConfig, CsvParserConfig, CsvQuoteStyle};
     use feldera_types::serde_with_context::{DeserializeWithContext, SqlSerdeConfig};
+    use std::borrow::Cow;
 
     #[derive(Debug, Eq, PartialEq)]
     #[allow(non_snake_case)]
@@ -413,4 +738,192 @@ true,bar,buzz"#;
             )
         );
     }
+
+    fn parse(config: CsvParserConfig, chunks: &[&str]) -> Vec<MockUpdate<TestStruct, TestStruct>> {
+        let format_config = FormatConfig {
+            name: Cow::from("csv"),
+            config: serde_yaml::to_value(config).unwrap(),
+        };
+        let (consumer, mut parser, outputs) =
+            mock_parser_pipeline(&TestStruct::relation_schema(), &format_config).unwrap();
+        consumer.on_error(Some(Box::new(|_, _| {})));
+
+        for chunk in chunks {
+            let (mut buffer, errors) = parser.parse(chunk.as_bytes());
+            assert_eq!(errors, vec![]);
+            buffer.flush();
+        }
+
+        let flushed = outputs.state().flushed.clone();
+        flushed
+    }
+
+    #[test]
+    fn csv_dialect() {
+        let config = CsvParserConfig {
+            delimiter: ';',
+            quote: '\'',
+            escape: Some('\\'),
+            null_string: "\\N".to_string(),
+            comment: Some('#'),
+            trim: true,
+            ..Default::default()
+        };
+        let updates = parse(
+            config,
+            &["# comment with an unmatched ' quote\n1; true ;\\N;'a;\\'b'\n2;false;5;\n"],
+        );
+        assert_eq!(
+            updates,
+            vec![
+                MockUpdate::Insert(TestStruct {
+                    id: 1,
+                    b: true,
+                    i: None,
+                    s: "a;'b".to_string()
+                }),
+                MockUpdate::Insert(TestStruct {
+                    id: 2,
+                    b: false,
+                    i: Some(5),
+                    s: "".to_string()
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_columns_by_name() {
+        let config = CsvParserConfig {
+            headers: true,
+            columns_by_name: true,
+            weighted: true,
+            ..Default::default()
+        };
+        let updates = parse(
+            config,
+            &[
+                "S,extra,ID,B,weight\nfoo,x,1,true,2\n",
+                "bar,y,2,false,-1\n",
+            ],
+        );
+        let v1 = TestStruct {
+            id: 1,
+            b: true,
+            i: None,
+            s: "foo".to_string(),
+        };
+        let v2 = TestStruct {
+            id: 2,
+            b: false,
+            i: None,
+            s: "bar".to_string(),
+        };
+        assert_eq!(
+            updates,
+            vec![
+                MockUpdate::Insert(v1.clone()),
+                MockUpdate::Insert(v1),
+                MockUpdate::Delete(v2),
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_encoder_headers() {
+        let config = CsvEncoderConfig {
+            headers: true,
+            quote_style: CsvQuoteStyle::Always,
+            ..Default::default()
+        };
+        let consumer = MockOutputConsumer::new();
+        let consumer_data = consumer.data.clone();
+        let mut encoder =
+            CsvEncoder::new(Box::new(consumer), config, &TestStruct::relation_schema());
+
+        let zset = OrdZSet::from_keys(
+            (),
+            vec![Tup2(
+                TestStruct {
+                    id: 1,
+                    b: true,
+                    i: Some(5),
+                    s: "foo".to_string(),
+                },
+                1,
+            )],
+        );
+        let zset = &SerBatchImpl::<_, TestStruct, ()>::new(zset) as &dyn SerBatchReader;
+        for _ in 0..2 {
+            encoder.consumer().batch_start(0);
+            encoder.encode(zset).unwrap();
+            encoder.consumer().batch_end();
+        }
+
+        let output = consumer_data
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_k, v, _headers)| String::from_utf8(v.clone().unwrap()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            output,
+            vec![
+                "\"id\",\"b\",\"i\",\"s\",\"weight\"\n\"1\",\"true\",\"5\",\"foo\",\"1\"\n"
+                    .to_string(),
+                "\"1\",\"true\",\"5\",\"foo\",\"1\"\n".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_encoder_null_string() {
+        let config = CsvEncoderConfig {
+            null_string: "\\N".to_string(),
+            ..Default::default()
+        };
+        let consumer = MockOutputConsumer::new();
+        let consumer_data = consumer.data.clone();
+        let mut encoder =
+            CsvEncoder::new(Box::new(consumer), config, &TestStruct::relation_schema());
+
+        let zset = OrdZSet::from_keys(
+            (),
+            vec![
+                Tup2(
+                    TestStruct {
+                        id: 1,
+                        b: true,
+                        i: None,
+                        s: "foo".to_string(),
+                    },
+                    1,
+                ),
+                Tup2(
+                    TestStruct {
+                        id: 2,
+                        b: false,
+                        i: Some(5),
+                        s: "".to_string(),
+                    },
+                    -1,
+                ),
+            ],
+        );
+        let zset = &SerBatchImpl::<_, TestStruct, ()>::new(zset) as &dyn SerBatchReader;
+        encoder.consumer().batch_start(0);
+        encoder.encode(zset).unwrap();
+        encoder.consumer().batch_end();
+
+        let output = consumer_data
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_k, v, _headers)| String::from_utf8(v.clone().unwrap()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            output,
+            vec!["1,true,\\N,foo,1\n2,false,5,,-1\n".to_string()]
+        );
+    }
 }
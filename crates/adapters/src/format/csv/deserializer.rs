@@ -48,6 +48,7 @@ pub fn string_record_deserializer<'de>(
         it: record.iter().peekable(),
         headers: headers.map(|r| r.iter()),
         field: 0,
+        null: b"",
     })
 }
 
@@ -60,6 +61,7 @@ pub fn byte_record_deserializer<'de>(
         it: record.iter().peekable(),
         headers: headers.map(|r| r.iter()),
         field: 0,
+        null: b"",
     })
 }
 
@@ -98,6 +100,9 @@ trait DeRecord<'r> {
     /// Peeks at the next field from the underlying record.
     fn peek_field(&mut self) -> Option<&'r [u8]>;
 
+    /// Returns the field value that represents `None`.
+    fn null_value(&self) -> &'r [u8];
+
     /// Returns an error corresponding to the most recently extracted field.
     fn error(&self, kind: DeserializeErrorKind) -> DeserializeError;
 
@@ -116,6 +121,15 @@ pub type StringRecordDeserializer<'r> = DeRecordWrap<DeStringRecord<'r>>;
 /// Deserializer for a single CSV record stored as raw bytes.
 pub type ByteRecordDeserializer<'r> = DeRecordWrap<DeByteRecord<'r>>;
 
+impl<'r> ByteRecordDeserializer<'r> {
+    /// Deserialize fields equal to `null` as `None` (by default, empty
+    /// fields are deserialized as `None`).
+    pub fn with_null_value(mut self, null: &'r [u8]) -> Self {
+        self.0.null = null;
+        self
+    }
+}
+
 impl<'r, T: DeRecord<'r>> DeRecord<'r> for DeRecordWrap<T> {
     #[inline]
     fn has_headers(&self) -> bool {
@@ -147,6 +161,11 @@ impl<'r, T: DeRecord<'r>> DeRecord<'r> for DeRecordWrap<T> {
         self.0.peek_field()
     }
 
+    #[inline]
+    fn null_value(&self) -> &'r [u8] {
+        self.0.null_value()
+    }
+
     #[inline]
     fn error(&self, kind: DeserializeErrorKind) -> DeserializeError {
         self.0.error(kind)
@@ -165,6 +184,7 @@ pub struct DeStringRecord<'r> {
     it: iter::Peekable<StringRecordIter<'r>>,
     headers: Option<StringRecordIter<'r>>,
     field: u64,
+    null: &'r [u8],
 }
 
 impl<'r> DeRecord<'r> for DeStringRecord<'r> {
@@ -207,6 +227,11 @@ impl<'r> DeRecord<'r> for DeStringRecord<'r> {
         self.it.peek().map(|s| s.as_bytes())
     }
 
+    #[inline]
+    fn null_value(&self) -> &'r [u8] {
+        self.null
+    }
+
     fn error(&self, kind: DeserializeErrorKind) -> DeserializeError {
         DeserializeError {
             field: Some(self.field.saturating_sub(1)),
@@ -247,6 +272,7 @@ pub struct DeByteRecord<'r> {
     it: iter::Peekable<ByteRecordIter<'r>>,
     headers: Option<ByteRecordIter<'r>>,
     field: u64,
+    null: &'r [u8],
 }
 
 impl<'r> DeRecord<'r> for DeByteRecord<'r> {
@@ -297,6 +323,11 @@ impl<'r> DeRecord<'r> for DeByteRecord<'r> {
         self.it.peek().map(|s| *s)
     }
 
+    #[inline]
+    fn null_value(&self) -> &'r [u8] {
+        self.null
+    }
+
     fn error(&self, kind: DeserializeErrorKind) -> DeserializeError {
         DeserializeError {
             field: Some(self.field.saturating_sub(1)),
@@ -429,8 +460,8 @@ impl<'a, 'de: 'a, T: DeRecord<'de>> Deserializer<'de> for &'a mut DeRecordWrap<T
     fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
         match self.peek_field() {
             None => visitor.visit_none(),
-            Some([]) => {
-                self.next_field().expect("empty field");
+            Some(field) if field == self.null_value() => {
+                self.next_field_bytes().expect("null field");
                 visitor.visit_none()
             }
             Some(_) => visitor.visit_some(self),
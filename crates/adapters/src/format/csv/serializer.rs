@@ -0,0 +1,252 @@
+//! Serde adapter that lets the CSV encoder output `NULL` as a configurable
+//! field value.
+//!
+//! The `csv` crate serializes `None` as an empty field.  [`NullAs`] wraps a
+//! value and serializes it the same way, except that `None` is serialized as
+//! a given string.
+
+use serde::ser::{
+    Serialize, SerializeSeq, SerializeStruct, SerializeTuple, SerializeTupleStruct, Serializer,
+};
+
+/// Serializes `value`, substituting `null` for `None`.
+pub(crate) struct NullAs<'a, T: ?Sized> {
+    value: &'a T,
+    null: &'a str,
+}
+
+impl<'a, T: ?Sized> NullAs<'a, T> {
+    pub(crate) fn new(value: &'a T, null: &'a str) -> Self {
+        Self { value, null }
+    }
+}
+
+impl<T> Serialize for NullAs<'_, T>
+where
+    T: ?Sized + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value.serialize(NullAsSerializer {
+            inner: serializer,
+            null: self.null,
+        })
+    }
+}
+
+struct NullAsSerializer<'a, S> {
+    inner: S,
+    null: &'a str,
+}
+
+macro_rules! forward {
+    ($($method:ident($($arg:ident: $ty:ty),*) -> $ret:ty;)*) => {
+        $(
+            fn $method(self, $($arg: $ty),*) -> Result<$ret, Self::Error> {
+                self.inner.$method($($arg),*)
+            }
+        )*
+    };
+}
+
+impl<'a, S> Serializer for NullAsSerializer<'a, S>
+where
+    S: Serializer,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = NullAsCompound<'a, S::SerializeSeq>;
+    type SerializeTuple = NullAsCompound<'a, S::SerializeTuple>;
+    type SerializeTupleStruct = NullAsCompound<'a, S::SerializeTupleStruct>;
+    type SerializeTupleVariant = S::SerializeTupleVariant;
+    type SerializeMap = S::SerializeMap;
+    type SerializeStruct = NullAsCompound<'a, S::SerializeStruct>;
+    type SerializeStructVariant = S::SerializeStructVariant;
+
+    forward! {
+        serialize_bool(v: bool) -> S::Ok;
+        serialize_i8(v: i8) -> S::Ok;
+        serialize_i16(v: i16) -> S::Ok;
+        serialize_i32(v: i32) -> S::Ok;
+        serialize_i64(v: i64) -> S::Ok;
+        serialize_i128(v: i128) -> S::Ok;
+        serialize_u8(v: u8) -> S::Ok;
+        serialize_u16(v: u16) -> S::Ok;
+        serialize_u32(v: u32) -> S::Ok;
+        serialize_u64(v: u64) -> S::Ok;
+        serialize_u128(v: u128) -> S::Ok;
+        serialize_f32(v: f32) -> S::Ok;
+        serialize_f64(v: f64) -> S::Ok;
+        serialize_char(v: char) -> S::Ok;
+        serialize_str(v: &str) -> S::Ok;
+        serialize_bytes(v: &[u8]) -> S::Ok;
+        serialize_unit() -> S::Ok;
+        serialize_unit_struct(name: &'static str) -> S::Ok;
+        serialize_unit_variant(name: &'static str, variant_index: u32, variant: &'static str) -> S::Ok;
+        serialize_tuple_variant(name: &'static str, variant_index: u32, variant: &'static str, len: usize) -> S::SerializeTupleVariant;
+        serialize_map(len: Option<usize>) -> S::SerializeMap;
+        serialize_struct_variant(name: &'static str, variant_index: u32, variant: &'static str, len: usize) -> S::SerializeStructVariant;
+    }
+
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_str(self.null)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.inner
+            .serialize_newtype_struct(name, &NullAs::new(value, self.null))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.inner.serialize_newtype_variant(
+            name,
+            variant_index,
+            variant,
+            &NullAs::new(value, self.null),
+        )
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        Ok(NullAsCompound {
+            inner: self.inner.serialize_seq(len)?,
+            null: self.null,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        Ok(NullAsCompound {
+            inner: self.inner.serialize_tuple(len)?,
+            null: self.null,
+        })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        Ok(NullAsCompound {
+            inner: self.inner.serialize_tuple_struct(name, len)?,
+            null: self.null,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        Ok(NullAsCompound {
+            inner: self.inner.serialize_struct(name, len)?,
+            null: self.null,
+        })
+    }
+}
+
+/// Wraps the elements of a sequence, tuple, or struct in [`NullAs`].
+struct NullAsCompound<'a, C> {
+    inner: C,
+    null: &'a str,
+}
+
+impl<C> SerializeSeq for NullAsCompound<'_, C>
+where
+    C: SerializeSeq,
+{
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), C::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.inner.serialize_element(&NullAs::new(value, self.null))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C> SerializeTuple for NullAsCompound<'_, C>
+where
+    C: SerializeTuple,
+{
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), C::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.inner.serialize_element(&NullAs::new(value, self.null))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C> SerializeTupleStruct for NullAsCompound<'_, C>
+where
+    C: SerializeTupleStruct,
+{
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), C::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.inner.serialize_field(&NullAs::new(value, self.null))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C> SerializeStruct for NullAsCompound<'_, C>
+where
+    C: SerializeStruct,
+{
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), C::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.inner
+            .serialize_field(key, &NullAs::new(value, self.null))
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), C::Error> {
+        self.inner.skip_field(key)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
@@ -9,14 +9,19 @@ use crate::{
     ControllerError,
 };
 use actix_web::HttpRequest;
+use chrono::DateTime;
 use erased_serde::Serialize as ErasedSerialize;
-use feldera_adapterlib::format::Splitter;
+use feldera_adapterlib::format::{ConnectorMetadata, MetadataValue, Splitter};
+use feldera_sqllib::ByteArray;
 use feldera_types::format::json::{JsonLines, JsonParserConfig, JsonUpdateFormat};
+use feldera_types::serde_with_context::{SerializationContext, SqlSerdeConfig, TimestampFormat};
 use serde::Deserialize;
 use serde_json::value::RawValue;
+use serde_json::Value as JsonValue;
 use serde_urlencoded::Deserializer as UrlDeserializer;
 use serde_yaml::Value as YamlValue;
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 
 /// JSON format parser.
 pub struct JsonInputFormat;
@@ -229,11 +234,50 @@ fn validate_parser_config(
     Ok(())
 }
 
+/// Converts a connector metadata value to JSON, using the representation
+/// expected for the column type by `serde_config`.
+fn metadata_to_json(value: &MetadataValue, serde_config: &SqlSerdeConfig) -> JsonValue {
+    match value {
+        MetadataValue::Null => JsonValue::Null,
+        MetadataValue::Integer(i) => JsonValue::from(*i),
+        MetadataValue::String(s) => JsonValue::from(s.as_str()),
+        MetadataValue::Bytes(bytes) => serde_json::to_value(SerializationContext::new(
+            serde_config,
+            &ByteArray::new(bytes),
+        ))
+        .unwrap_or(JsonValue::Null),
+        MetadataValue::Timestamp(millis) => match &serde_config.timestamp_format {
+            TimestampFormat::MillisSinceEpoch => JsonValue::from(*millis),
+            TimestampFormat::MicrosSinceEpoch => JsonValue::from(*millis * 1000),
+            format => {
+                let Some(timestamp) = DateTime::from_timestamp_millis(*millis) else {
+                    return JsonValue::Null;
+                };
+                match format {
+                    TimestampFormat::String(format) => {
+                        JsonValue::from(timestamp.format(format).to_string())
+                    }
+                    _ => JsonValue::from(timestamp.to_rfc3339()),
+                }
+            }
+        },
+        MetadataValue::Map(map) => JsonValue::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), v.as_deref().into()))
+                .collect(),
+        ),
+    }
+}
+
 struct JsonParser {
     /// Input handle to push parsed data to.
     input_stream: Box<dyn DeCollectionStream>,
     config: JsonParserConfig,
     last_event_number: u64,
+
+    /// Connector metadata columns to add to each record, while parsing data
+    /// passed to [Parser::parse_with_metadata].
+    metadata: Vec<(String, Box<RawValue>)>,
 }
 
 impl JsonParser {
@@ -242,11 +286,34 @@ impl JsonParser {
             input_stream,
             config,
             last_event_number: 0,
+            metadata: Vec::new(),
+        }
+    }
+
+    /// Returns `val` with the connector metadata columns added to it.
+    fn with_metadata<'a>(&self, val: &'a RawValue) -> Result<Cow<'a, str>, ParseError> {
+        if self.metadata.is_empty() {
+            return Ok(Cow::Borrowed(val.get()));
+        }
+        let mut record =
+            serde_json::from_str::<BTreeMap<Cow<str>, &RawValue>>(val.get()).map_err(|e| {
+                ParseError::text_event_error(
+                    "failed to add connector metadata to JSON record, which must be a JSON object",
+                    e,
+                    self.last_event_number + 1,
+                    Some(val.get()),
+                    None,
+                )
+            })?;
+        for (name, value) in &self.metadata {
+            record.insert(Cow::Borrowed(name.as_str()), &**value);
         }
+        Ok(Cow::Owned(serde_json::to_string(&record).unwrap()))
     }
 
     fn delete(&mut self, val: &RawValue) -> Result<(), ParseError> {
-        self.input_stream.delete(val.get().as_bytes()).map_err(|e| {
+        let record = self.with_metadata(val)?;
+        self.input_stream.delete(record.as_bytes()).map_err(|e| {
             ParseError::text_event_error(
                 "failed to deserialize JSON record",
                 e,
@@ -258,7 +325,8 @@ impl JsonParser {
     }
 
     fn insert(&mut self, val: &RawValue) -> Result<(), ParseError> {
-        self.input_stream.insert(val.get().as_bytes()).map_err(|e| {
+        let record = self.with_metadata(val)?;
+        self.input_stream.insert(record.as_bytes()).map_err(|e| {
             ParseError::text_event_error(
                 "failed to deserialize JSON record",
                 e,
@@ -270,7 +338,8 @@ impl JsonParser {
     }
 
     fn update(&mut self, val: &RawValue) -> Result<(), ParseError> {
-        self.input_stream.update(val.get().as_bytes()).map_err(|e| {
+        let record = self.with_metadata(val)?;
+        self.input_stream.update(record.as_bytes()).map_err(|e| {
             ParseError::text_event_error(
                 "failed to deserialize JSON record",
                 e,
@@ -371,6 +440,27 @@ impl Parser for JsonParser {
         (self.input_stream.take_all(), errors)
     }
 
+    fn parse_with_metadata(
+        &mut self,
+        data: &[u8],
+        metadata: &ConnectorMetadata,
+    ) -> (Option<Box<dyn InputBuffer>>, Vec<ParseError>) {
+        let serde_config = SqlSerdeConfig::from(self.config.json_flavor.clone());
+        self.metadata = metadata
+            .iter()
+            .map(|(name, value)| {
+                let value = metadata_to_json(value, &serde_config);
+                (
+                    name.clone(),
+                    serde_json::value::to_raw_value(&value).unwrap(),
+                )
+            })
+            .collect();
+        let result = self.parse(data);
+        self.metadata.clear();
+        result
+    }
+
     fn fork(&self) -> Box<dyn Parser> {
         Box::new(Self::new(self.input_stream.fork(), self.config.clone()))
     }
@@ -482,6 +572,7 @@ mod test {
         transport::InputConsumer,
         FormatConfig, ParseError,
     };
+    use feldera_adapterlib::format::MetadataValue;
     use feldera_adapterlib::format::Splitter;
     use feldera_types::{
         deserialize_table_record,
@@ -489,10 +580,12 @@ mod test {
         program_schema::Relation,
         serde_with_context::{DeserializeWithContext, SqlSerdeConfig},
     };
+    use serde_json::json;
+    use std::collections::BTreeMap;
     use std::{borrow::Cow, fmt::Debug, hash::Hash, panic::Location};
     use tracing::trace;
 
-    use super::JsonSplitter;
+    use super::{metadata_to_json, JsonSplitter};
 
     #[derive(PartialEq, Debug, Eq, Hash, Clone)]
     struct TestStruct {
@@ -1098,6 +1191,74 @@ mod test {
         run_test_cases(test_cases);
     }
 
+    #[test]
+    fn test_json_metadata() {
+        let format_config = FormatConfig {
+            name: Cow::from("json"),
+            config: serde_yaml::to_value(JsonParserConfig {
+                update_format: JsonUpdateFormat::InsertDelete,
+                json_flavor: JsonFlavor::Default,
+                array: false,
+                lines: JsonLines::Multiple,
+            })
+            .unwrap(),
+        };
+        let (consumer, mut parser, outputs) =
+            mock_parser_pipeline::<TestStruct, TestStructUpd>(&Relation::empty(), &format_config)
+                .unwrap();
+        let metadata = vec![
+            ("i".to_string(), MetadataValue::Integer(5)),
+            ("s".to_string(), MetadataValue::String("key".to_string())),
+        ];
+        let (mut buffer, errors) = parser.parse_with_metadata(
+            br#"{"insert": {"b": true, "i": 1}} {"delete": {"b": false}}"#,
+            &metadata,
+        );
+        assert_eq!(errors, vec![]);
+        buffer.flush();
+        consumer.eoi();
+        assert_eq!(
+            outputs.state().flushed,
+            vec![
+                MockUpdate::with_polarity(TestStruct::new(true, 5, Some("key")), true),
+                MockUpdate::with_polarity(TestStruct::new(false, 5, Some("key")), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_metadata_to_json() {
+        let timestamp = MetadataValue::Timestamp(1_700_000_000_123);
+        assert_eq!(
+            metadata_to_json(&timestamp, &SqlSerdeConfig::from(JsonFlavor::Default)),
+            json!("2023-11-14 22:13:20.123")
+        );
+        assert_eq!(
+            metadata_to_json(
+                &timestamp,
+                &SqlSerdeConfig::from(JsonFlavor::KafkaConnectJsonConverter)
+            ),
+            json!(1_700_000_000_123i64)
+        );
+        let headers = MetadataValue::Map(BTreeMap::from([
+            ("a".to_string(), Some("1".to_string())),
+            ("b".to_string(), None),
+        ]));
+        assert_eq!(
+            metadata_to_json(&headers, &SqlSerdeConfig::default()),
+            json!({"a": "1", "b": null})
+        );
+        let key = MetadataValue::Bytes(vec![0, 1, 255]);
+        assert_eq!(
+            metadata_to_json(&key, &SqlSerdeConfig::default()),
+            json!([0, 1, 255])
+        );
+        assert_eq!(
+            metadata_to_json(&key, &SqlSerdeConfig::from(JsonFlavor::ParquetConverter)),
+            json!("AAH/")
+        );
+    }
+
     #[test]
     fn test_json_splitter() {
         // Checks how we split different syntax. The `%` in each string is the
@@ -1,8 +1,9 @@
 use crate::catalog::SerBatchReader;
 use crate::format::json::schema::{build_key_schema, build_value_schema};
+use crate::format::message_fields::{MessageAttributes, MessageFields};
 use crate::format::{MAX_DUPLICATES, MAX_RECORD_LEN_IN_ERRMSG};
 use crate::{
-    catalog::{CursorWithPolarity, RecordFormat, SerCursor},
+    catalog::{CursorWithPolarity, RecordFormat},
     util::truncate_ellipse,
     ControllerError, Encoder, OutputConsumer, OutputFormat,
 };
@@ -86,11 +87,20 @@ impl OutputFormat for JsonOutputFormat {
             _ => None,
         };
 
+        let message_fields = MessageFields::from_transport(&config.transport);
+        if let Some(message_fields) = &message_fields {
+            message_fields.validate(endpoint_name, value_schema)?;
+
+            // Each message carries the key, headers, and topic of a single record.
+            json_config.buffer_size_records = 1;
+        }
+
         Ok(Box::new(JsonEncoder::new(
             consumer,
             json_config,
             value_schema,
             key_separator,
+            message_fields,
         )))
     }
 }
@@ -172,6 +182,10 @@ struct JsonEncoder {
     key_fields: Option<HashSet<String>>,
     ordered_key_fields: Option<Vec<String>>,
     key_separator: Option<String>,
+    message_fields: Option<MessageFields>,
+    /// Key, headers, and topic of the current message, when `message_fields`
+    /// is set.
+    message_attributes: MessageAttributes,
 }
 
 impl JsonEncoder {
@@ -180,6 +194,7 @@ impl JsonEncoder {
         mut config: JsonEncoderConfig,
         schema: &Relation,
         key_separator: Option<String>,
+        message_fields: Option<MessageFields>,
     ) -> Self {
         let max_buffer_size = output_consumer.max_buffer_size_bytes();
 
@@ -217,6 +232,31 @@ impl JsonEncoder {
             key_fields,
             ordered_key_fields,
             key_separator,
+            message_fields,
+            message_attributes: MessageAttributes::default(),
+        }
+    }
+
+    /// Pushes an encoded buffer to the output consumer, along with its key and
+    /// headers, if any.
+    fn push(&mut self, buffer: &[u8], key: &[u8], redis_deletion: bool, num_records: usize) {
+        if self.message_fields.is_some() {
+            self.message_attributes.push(
+                self.output_consumer.as_mut(),
+                (!key.is_empty()).then_some(key),
+                Some(buffer),
+                &[],
+                num_records,
+            );
+        } else if !key.is_empty() {
+            self.output_consumer.push_key(
+                Some(key),
+                if redis_deletion { None } else { Some(buffer) },
+                &[],
+                num_records,
+            );
+        } else {
+            self.output_consumer.push_buffer(buffer, num_records);
         }
     }
 }
@@ -423,6 +463,11 @@ impl Encoder for JsonEncoder {
                     }
                 }
 
+                if let Some(message_fields) = &self.message_fields {
+                    let op = if w > 0 { "insert" } else { "delete" };
+                    message_fields.encode(&mut cursor, op, &mut self.message_attributes)?;
+                }
+
                 // Drop the last encoded record if it exceeds max_buffer_size.
                 // The record will be included in the next buffer.
                 let buffer_full = buffer.len() > max_buffer_size;
@@ -459,16 +504,7 @@ impl Encoder for JsonEncoder {
                     //     "push_buffer: {} bytes",
                     //     buffer.len() /*std::str::from_utf8(&buffer).unwrap()*/
                     // );
-                    if !key_buffer.is_empty() {
-                        self.output_consumer.push_key(
-                            Some(&key_buffer),
-                            if redis_deletion { None } else { Some(&buffer) },
-                            &[],
-                            num_records,
-                        );
-                    } else {
-                        self.output_consumer.push_buffer(&buffer, num_records);
-                    }
+                    self.push(&buffer, &key_buffer, redis_deletion, num_records);
                     buffer.clear();
                     key_buffer.clear();
                     num_records = 0;
@@ -482,16 +518,7 @@ impl Encoder for JsonEncoder {
             if self.config.array {
                 buffer.extend_from_slice(b"]\n");
             }
-            if !key_buffer.is_empty() {
-                self.output_consumer.push_key(
-                    Some(&key_buffer),
-                    if redis_deletion { None } else { Some(&buffer) },
-                    &[],
-                    num_records,
-                );
-            } else {
-                self.output_consumer.push_buffer(&buffer, num_records);
-            }
+            self.push(&buffer, &key_buffer, redis_deletion, num_records);
 
             buffer.clear();
             key_buffer.clear();
@@ -506,7 +533,7 @@ impl Encoder for JsonEncoder {
 
 #[cfg(test)]
 mod test {
-    use super::{JsonEncoder, JsonEncoderConfig};
+    use super::{JsonEncoder, JsonEncoderConfig, MessageFields};
     use crate::catalog::SerBatchReader;
     use crate::format::json::{DebeziumOp, DebeziumPayload, DebeziumUpdate};
     use crate::{
@@ -637,6 +664,7 @@ mod test {
                 BTreeMap::new(),
             ),
             None,
+            None,
         );
         let zsets = batches
             .iter()
@@ -836,6 +864,7 @@ mod test {
                 BTreeMap::new(),
             ),
             None,
+            None,
         );
         let zset = OrdZSet::from_keys((), test_data()[0].clone());
 
@@ -869,6 +898,7 @@ mod test {
                 BTreeMap::new(),
             ),
             None,
+            None,
         );
         let zset = OrdZSet::from_keys((), test_data()[0].clone());
 
@@ -939,6 +969,7 @@ mod test {
                 BTreeMap::new(),
             ),
             None,
+            None,
         );
 
         let zset = OrdZSet::from_keys((), test_data()[0].clone());
@@ -971,6 +1002,93 @@ mod test {
         assert_eq!(actual_output, expected_output);
     }
 
+    #[test]
+    fn test_message_fields() {
+        let config = JsonEncoderConfig {
+            update_format: JsonUpdateFormat::InsertDelete,
+            json_flavor: None,
+            buffer_size_records: 1,
+            array: false,
+            key_fields: None,
+        };
+
+        let consumer = MockOutputConsumer::new();
+        let consumer_data = consumer.data.clone();
+        let consumer_topics = consumer.topics.clone();
+
+        let mut encoder = JsonEncoder::new(
+            Box::new(consumer),
+            config,
+            &Relation::new(
+                "TestStruct".into(),
+                TestStruct::schema(),
+                false,
+                BTreeMap::new(),
+            ),
+            None,
+            MessageFields::new(
+                Some(&["id".to_owned(), "s".to_owned()]),
+                &["b".to_owned(), "i".to_owned()],
+                Some("s"),
+                Some("op"),
+            ),
+        );
+
+        let zset = OrdZSet::from_keys((), test_data()[0].clone());
+
+        encoder
+            .encode(&SerBatchImpl::<_, TestStruct, ()>::new(zset) as &dyn SerBatchReader)
+            .unwrap();
+
+        let actual_output = consumer_data
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(k, v, headers)| {
+                (
+                    String::from_utf8(k.clone().unwrap()).unwrap(),
+                    serde_json::from_slice::<serde_json::Value>(v.as_ref().unwrap()).unwrap(),
+                    headers
+                        .iter()
+                        .map(|(name, value)| {
+                            (
+                                name.clone(),
+                                value.clone().map(|v| String::from_utf8(v).unwrap()),
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let expected_output = vec![
+            (
+                "1:bar".to_owned(),
+                json!({"delete": {"id":1, "s":"bar", "b":false, "i": 10}}),
+                vec![
+                    ("b".to_owned(), Some("false".to_owned())),
+                    ("i".to_owned(), Some("10".to_owned())),
+                    ("op".to_owned(), Some("delete".to_owned())),
+                ],
+            ),
+            (
+                "0:foo".to_owned(),
+                json!({"insert": {"id":0, "s":"foo", "b":true, "i": null}}),
+                vec![
+                    ("b".to_owned(), Some("true".to_owned())),
+                    ("i".to_owned(), None),
+                    ("op".to_owned(), Some("insert".to_owned())),
+                ],
+            ),
+        ];
+
+        assert_eq!(actual_output, expected_output);
+        assert_eq!(
+            *consumer_topics.lock().unwrap(),
+            vec![Some("bar".to_owned()), Some("foo".to_owned())]
+        );
+    }
+
     #[test]
     fn test_ndjson_insdel() {
         test_json::<InsDelUpdate<TestStruct>>(false, test_data());
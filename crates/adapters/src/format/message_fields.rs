@@ -0,0 +1,208 @@
+use crate::catalog::SerCursor;
+use crate::{ControllerError, OutputConsumer};
+use anyhow::Result as AnyResult;
+use feldera_types::config::TransportConfig;
+use feldera_types::program_schema::{canonical_identifier, Relation};
+use serde_json::Value as JsonValue;
+use std::collections::HashSet;
+
+/// Columns of the output record used to compute the key, the headers, and the
+/// destination topic of each message, for transports that attach them to
+/// individual messages (currently Kafka and Pub/Sub, where the key and headers
+/// become the ordering key and attributes).
+pub(crate) struct MessageFields {
+    /// Columns whose values, joined with `:`, form the message key.
+    key_fields: Option<Vec<String>>,
+
+    /// Columns whose values are attached to the message as headers.
+    header_fields: Vec<String>,
+
+    /// Column whose value is the topic to write the message to.
+    topic_field: Option<String>,
+
+    /// Header that carries the type of change of the message.
+    op_header: Option<String>,
+
+    /// Union of `key_fields`, `header_fields`, and `topic_field`.
+    all_fields: HashSet<String>,
+}
+
+impl MessageFields {
+    /// Returns `None` if no columns or headers are specified.
+    pub(crate) fn new(
+        key_fields: Option<&[String]>,
+        header_fields: &[String],
+        topic_field: Option<&str>,
+        op_header: Option<&str>,
+    ) -> Option<Self> {
+        if key_fields.is_none()
+            && header_fields.is_empty()
+            && topic_field.is_none()
+            && op_header.is_none()
+        {
+            return None;
+        }
+        let key_fields = key_fields.map(|fields| {
+            fields
+                .iter()
+                .map(|f| canonical_identifier(f))
+                .collect::<Vec<_>>()
+        });
+        let header_fields = header_fields
+            .iter()
+            .map(|f| canonical_identifier(f))
+            .collect::<Vec<_>>();
+        let topic_field = topic_field.map(canonical_identifier);
+        let all_fields = key_fields
+            .iter()
+            .flatten()
+            .chain(header_fields.iter())
+            .chain(topic_field.iter())
+            .cloned()
+            .collect();
+        Some(Self {
+            key_fields,
+            header_fields,
+            topic_field,
+            op_header: op_header.map(str::to_string),
+            all_fields,
+        })
+    }
+
+    /// Returns the message fields configured in the transport configuration,
+    /// if any.
+    pub(crate) fn from_transport(transport: &TransportConfig) -> Option<Self> {
+        match transport {
+            TransportConfig::KafkaOutput(config) => Self::new(
+                config.key_fields.as_deref(),
+                &config.header_fields,
+                config.topic_field.as_deref(),
+                config.op_header.as_deref(),
+            ),
+            TransportConfig::PubSubOutput(config) => Self::new(
+                config.ordering_key_fields.as_deref(),
+                &config.attribute_fields,
+                None,
+                None,
+            ),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn validate(
+        &self,
+        endpoint_name: &str,
+        value_schema: &Relation,
+    ) -> Result<(), ControllerError> {
+        for field in self.all_fields.iter() {
+            if value_schema.field(field).is_none() {
+                return Err(ControllerError::invalid_encoder_configuration(
+                    endpoint_name,
+                    &format!("transport configuration references unknown field '{field}'"),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Computes the key, headers, and topic of the message for the record
+    /// under `cursor`.  The cursor must serialize records as JSON.
+    ///
+    /// `op` is the type of change, e.g., `insert` or `delete`.
+    pub(crate) fn encode(
+        &self,
+        cursor: &mut dyn SerCursor,
+        op: &str,
+        attributes: &mut MessageAttributes,
+    ) -> AnyResult<()> {
+        let mut buf = Vec::new();
+        cursor.serialize_key_fields(&self.all_fields, &mut buf)?;
+        let json: JsonValue = serde_json::from_slice(&buf)?;
+        self.encode_json(&json, op, attributes);
+        Ok(())
+    }
+
+    /// Like [`MessageFields::encode`], but takes the record as a JSON object.
+    pub(crate) fn encode_json(
+        &self,
+        record: &JsonValue,
+        op: &str,
+        attributes: &mut MessageAttributes,
+    ) {
+        let value_to_string = |field: &String| match record.get(field) {
+            None | Some(JsonValue::Null) => None,
+            Some(JsonValue::String(s)) => Some(s.clone()),
+            Some(value) => Some(value.to_string()),
+        };
+
+        attributes.key = self.key_fields.as_ref().map(|key_fields| {
+            key_fields
+                .iter()
+                .map(|field| value_to_string(field).unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join(":")
+                .into_bytes()
+        });
+
+        attributes.headers.clear();
+        for field in self.header_fields.iter() {
+            if let Some(value) = value_to_string(field) {
+                attributes
+                    .headers
+                    .push((field.clone(), Some(value.into_bytes())));
+            }
+        }
+        if let Some(op_header) = &self.op_header {
+            attributes
+                .headers
+                .push((op_header.clone(), Some(op.as_bytes().to_vec())));
+        }
+
+        attributes.topic = self.topic_field.as_ref().and_then(value_to_string);
+    }
+}
+
+/// Key, headers, and topic of a message computed by [`MessageFields`].
+#[derive(Default)]
+pub(crate) struct MessageAttributes {
+    /// Message key; `None` if `key_fields` is not specified.
+    key: Option<Vec<u8>>,
+
+    /// Headers added to the message.  Columns whose value is `NULL` are
+    /// omitted.
+    headers: Vec<(String, Option<Vec<u8>>)>,
+
+    /// Destination topic; `None` to use the topic configured for the
+    /// endpoint.
+    topic: Option<String>,
+}
+
+impl MessageAttributes {
+    /// Pushes a message to `consumer`.
+    ///
+    /// The computed key, if any, replaces `key` generated by the encoder.  The
+    /// computed headers are appended to `headers`.
+    pub(crate) fn push(
+        &self,
+        consumer: &mut dyn OutputConsumer,
+        key: Option<&[u8]>,
+        val: Option<&[u8]>,
+        headers: &[(&str, Option<&[u8]>)],
+        num_records: usize,
+    ) {
+        let key = self.key.as_deref().or(key);
+        let headers = headers
+            .iter()
+            .copied()
+            .chain(
+                self.headers
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_deref())),
+            )
+            .collect::<Vec<_>>();
+        match &self.topic {
+            Some(topic) => consumer.push_key_to_topic(topic, key, val, &headers, num_records),
+            None => consumer.push_key(key, val, &headers, num_records),
+        }
+    }
+}
@@ -7,26 +7,32 @@ use std::ops::Range;
 use std::{
     cmp::max,
     collections::BTreeMap,
-    fs::File,
     io::{Error as IoError, Read},
 };
 
+pub(crate) mod arrow_ipc;
 #[cfg(feature = "with-avro")]
 pub(crate) mod avro;
 pub(crate) mod csv;
 mod json;
+pub(crate) mod message_fields;
 pub mod parquet;
+#[cfg(feature = "with-protobuf")]
+pub(crate) mod protobuf;
 pub(crate) mod raw;
 
 #[cfg(feature = "with-avro")]
 use crate::format::avro::output::AvroOutputFormat;
+#[cfg(feature = "with-protobuf")]
+use crate::format::protobuf::{input::ProtobufInputFormat, output::ProtobufOutputFormat};
 pub use parquet::relation_to_parquet_schema;
 
 pub use self::csv::{byte_record_deserializer, string_record_deserializer};
 use self::{
+    arrow_ipc::{ArrowIpcInputFormat, ArrowIpcOutputFormat},
     csv::{CsvInputFormat, CsvOutputFormat},
     json::{JsonInputFormat, JsonOutputFormat},
-    raw::RawInputFormat,
+    raw::{RawInputFormat, RawOutputFormat},
 };
 
 pub use feldera_adapterlib::format::*;
@@ -36,6 +42,10 @@ pub use feldera_adapterlib::format::*;
 // external crates to implement new formats.
 static INPUT_FORMATS: Lazy<BTreeMap<&'static str, Box<dyn InputFormat>>> = Lazy::new(|| {
     BTreeMap::from([
+        (
+            "arrow_ipc",
+            Box::new(ArrowIpcInputFormat) as Box<dyn InputFormat>,
+        ),
         ("csv", Box::new(CsvInputFormat) as Box<dyn InputFormat>),
         ("json", Box::new(JsonInputFormat) as Box<dyn InputFormat>),
         (
@@ -44,6 +54,11 @@ static INPUT_FORMATS: Lazy<BTreeMap<&'static str, Box<dyn InputFormat>>> = Lazy:
         ),
         #[cfg(feature = "with-avro")]
         ("avro", Box::new(AvroInputFormat) as Box<dyn InputFormat>),
+        #[cfg(feature = "with-protobuf")]
+        (
+            "protobuf",
+            Box::new(ProtobufInputFormat) as Box<dyn InputFormat>,
+        ),
         ("raw", Box::new(RawInputFormat) as Box<dyn InputFormat>),
     ])
 });
@@ -55,6 +70,10 @@ pub fn get_input_format(name: &str) -> Option<&'static dyn InputFormat> {
 /// Static map of supported output formats.
 static OUTPUT_FORMATS: Lazy<BTreeMap<&'static str, Box<dyn OutputFormat>>> = Lazy::new(|| {
     BTreeMap::from([
+        (
+            "arrow_ipc",
+            Box::new(ArrowIpcOutputFormat) as Box<dyn OutputFormat>,
+        ),
         ("csv", Box::new(CsvOutputFormat) as Box<dyn OutputFormat>),
         ("json", Box::new(JsonOutputFormat) as Box<dyn OutputFormat>),
         (
@@ -63,6 +82,12 @@ static OUTPUT_FORMATS: Lazy<BTreeMap<&'static str, Box<dyn OutputFormat>>> = Laz
         ),
         #[cfg(feature = "with-avro")]
         ("avro", Box::new(AvroOutputFormat) as Box<dyn OutputFormat>),
+        #[cfg(feature = "with-protobuf")]
+        (
+            "protobuf",
+            Box::new(ProtobufOutputFormat) as Box<dyn OutputFormat>,
+        ),
+        ("raw", Box::new(RawOutputFormat) as Box<dyn OutputFormat>),
     ])
 });
 
@@ -189,7 +214,7 @@ impl StreamSplitter {
     // of bytes read or an I/O error.
     pub fn read(
         &mut self,
-        file: &mut File,
+        file: &mut impl Read,
         buffer_size: usize,
         limit: usize,
     ) -> Result<usize, IoError> {
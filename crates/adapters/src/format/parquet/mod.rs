@@ -6,9 +6,11 @@ use actix_web::HttpRequest;
 use anyhow::{bail, Result as AnyResult};
 use arrow::datatypes::{
     DataType, Field as ArrowField, FieldRef, Fields, IntervalUnit as ArrowIntervalUnit, Schema,
-    TimeUnit,
+    SchemaRef, TimeUnit,
 };
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
 use bytes::Bytes;
+use chrono::{NaiveDate, NaiveDateTime};
 use erased_serde::Serialize as ErasedSerialize;
 use feldera_adapterlib::catalog::ArrowStream;
 use feldera_types::config::ConnectorConfig;
@@ -16,12 +18,17 @@ use feldera_types::serde_with_context::serde_config::{
     BinaryFormat, DecimalFormat, UuidFormat, VariantFormat,
 };
 use feldera_types::serde_with_context::{DateFormat, SqlSerdeConfig, TimeFormat, TimestampFormat};
-use parquet::arrow::arrow_reader::ParquetRecordBatchReader;
-use parquet::arrow::ArrowWriter;
+use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
+use parquet::arrow::{ArrowWriter, ProjectionMask};
+use parquet::basic::{ConvertedType, LogicalType, TimeUnit as ParquetTimeUnit};
+use parquet::errors::ParquetError;
+use parquet::file::metadata::RowGroupMetaData;
 use parquet::file::properties::WriterProperties;
+use parquet::file::statistics::{Statistics, ValueStatistics};
 use serde::Deserialize;
 use serde_arrow::schema::SerdeArrowSchema;
 use serde_arrow::ArrayBuilder;
+use serde_json::Value as JsonValue;
 use serde_urlencoded::Deserializer as UrlDeserializer;
 use serde_yaml::Value as YamlValue;
 
@@ -32,8 +39,12 @@ use crate::{
     format::{Encoder, InputFormat, OutputFormat, ParseError, Parser},
     ControllerError, OutputConsumer, SerCursor,
 };
-use feldera_types::format::parquet::{ParquetEncoderConfig, ParquetParserConfig};
-use feldera_types::program_schema::{ColumnType, Field, IntervalUnit, Relation, SqlType};
+use feldera_types::format::parquet::{
+    ParquetColumnRange, ParquetEncoderConfig, ParquetParserConfig,
+};
+use feldera_types::program_schema::{
+    ColumnType, Field, IntervalUnit, Relation, SqlIdentifier, SqlType,
+};
 
 use super::{InputBuffer, Sponge};
 
@@ -53,7 +64,7 @@ pub const fn default_arrow_serde_config() -> &'static SqlSerdeConfig {
     }
 }
 
-/// CSV format parser.
+/// Parquet format parser.
 pub struct ParquetInputFormat;
 
 impl InputFormat for ParquetInputFormat {
@@ -66,38 +77,367 @@ impl InputFormat for ParquetInputFormat {
     // HTTP query, but a specialized method gives us more flexibility.
     fn config_from_http_request(
         &self,
-        _endpoint_name: &str,
-        _request: &HttpRequest,
+        endpoint_name: &str,
+        request: &HttpRequest,
     ) -> Result<Box<dyn ErasedSerialize>, ControllerError> {
-        Ok(Box::new(ParquetParserConfig {}))
+        Ok(Box::new(
+            ParquetParserConfig::deserialize(UrlDeserializer::new(form_urlencoded::parse(
+                request.query_string().as_bytes(),
+            )))
+            .map_err(|e| {
+                ControllerError::parser_config_parse_error(
+                    endpoint_name,
+                    &e,
+                    request.query_string(),
+                )
+            })?,
+        ))
     }
 
     fn new_parser(
         &self,
-        _endpoint_name: &str,
+        endpoint_name: &str,
         input_stream: &InputCollectionHandle,
-        _config: &YamlValue,
+        config: &YamlValue,
     ) -> Result<Box<dyn Parser>, ControllerError> {
+        let config = ParquetParserConfig::deserialize(config).map_err(|e| {
+            ControllerError::parser_config_parse_error(
+                endpoint_name,
+                &e,
+                &serde_yaml::to_string(config).unwrap_or_default(),
+            )
+        })?;
+
+        for range in config.row_group_filter.iter() {
+            for bound in [&range.min, &range.max].into_iter().flatten() {
+                if !matches!(
+                    bound,
+                    JsonValue::Number(_) | JsonValue::String(_) | JsonValue::Bool(_)
+                ) {
+                    return Err(ControllerError::invalid_parser_configuration(
+                        endpoint_name,
+                        &format!(
+                            "invalid bound {bound} for column '{}' in 'row_group_filter': expected a number, string, or boolean",
+                            range.column
+                        ),
+                    ));
+                }
+            }
+        }
+
+        let columns = input_stream
+            .schema
+            .fields
+            .iter()
+            .map(|f| f.name.clone())
+            .collect();
         let input_stream = input_stream
             .handle
             .configure_arrow_deserializer(default_arrow_serde_config().clone())?;
-        Ok(Box::new(ParquetParser::new(input_stream)) as Box<dyn Parser>)
+        Ok(Box::new(ParquetParser::new(
+            input_stream,
+            config,
+            Arc::new(columns),
+        )))
     }
 }
 
 struct ParquetParser {
     /// Input handle to push parsed data to.
     input_stream: Box<dyn ArrowStream>,
+    config: ParquetParserConfig,
+    /// Table columns.
+    columns: Arc<Vec<SqlIdentifier>>,
     last_chunk_number: u64,
 }
 
 impl ParquetParser {
-    fn new(input_stream: Box<dyn ArrowStream>) -> Self {
+    fn new(
+        input_stream: Box<dyn ArrowStream>,
+        config: ParquetParserConfig,
+        columns: Arc<Vec<SqlIdentifier>>,
+    ) -> Self {
         Self {
             input_stream,
+            config,
+            columns,
             last_chunk_number: 0,
         }
     }
+
+    /// Returns the name under which Parquet column `name` is passed to the
+    /// deserializer: the name of the matching table column if there is one,
+    /// and the (possibly renamed) Parquet column name otherwise.
+    fn column_name(&self, name: &str) -> (String, bool) {
+        let name = self
+            .config
+            .rename
+            .get(name)
+            .map(String::as_str)
+            .unwrap_or(name);
+        let column = self.columns.iter().find(|column| {
+            if self.config.case_insensitive {
+                column.name().to_lowercase() == name.to_lowercase()
+            } else {
+                *column == &SqlIdentifier::new(name, false)
+            }
+        });
+        match column {
+            Some(column) => (column.name(), true),
+            None => (name.to_string(), false),
+        }
+    }
+
+    /// Creates a reader that decodes the selected columns and row groups of
+    /// a Parquet file.
+    ///
+    /// Also returns the schema of the record batches produced by the reader
+    /// with columns renamed to match the table, if any columns are renamed.
+    fn reader(
+        &self,
+        data: Bytes,
+    ) -> Result<(ParquetRecordBatchReader, Option<SchemaRef>), ParquetError> {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(data)?;
+
+        let file_schema = builder.schema().clone();
+        let projection = file_schema
+            .fields()
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| match &self.config.columns {
+                Some(columns) => columns.contains(field.name()),
+                None => self.column_name(field.name()).1,
+            })
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let projection = ProjectionMask::roots(builder.parquet_schema(), projection);
+
+        let row_groups = builder
+            .metadata()
+            .row_groups()
+            .iter()
+            .enumerate()
+            .filter(|(_, row_group)| {
+                self.config
+                    .row_group_filter
+                    .iter()
+                    .all(|range| may_match(row_group, &file_schema, range))
+            })
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+
+        let reader = builder
+            .with_projection(projection)
+            .with_row_groups(row_groups)
+            .with_batch_size(1_000_000)
+            .build()?;
+
+        let schema = reader.schema();
+        let mut renamed = false;
+        let fields = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                let (name, _) = self.column_name(field.name());
+                if &name != field.name() {
+                    renamed = true;
+                    Arc::new(field.as_ref().clone().with_name(name))
+                } else {
+                    field.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        let schema =
+            renamed.then(|| Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone())));
+
+        Ok((reader, schema))
+    }
+}
+
+/// A Parquet column statistics value or a bound of a [`ParquetColumnRange`],
+/// converted to a common representation for comparison.
+#[derive(Debug, PartialEq, PartialOrd)]
+enum StatValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Bytes(Vec<u8>),
+}
+
+impl StatValue {
+    /// Converts a range bound to the representation used by Parquet
+    /// statistics of a column of type `data_type`.
+    ///
+    /// `stats_unit` is the unit of the statistics of a timestamp column, if
+    /// it differs from the unit of `data_type`.  Numeric bounds on timestamp
+    /// columns are in the unit of `data_type`.
+    ///
+    /// Returns `None` if the bound is not comparable with the column.
+    fn from_bound(
+        bound: &JsonValue,
+        data_type: &DataType,
+        stats_unit: Option<&TimeUnit>,
+    ) -> Option<Self> {
+        match (data_type, bound) {
+            (DataType::Timestamp(unit, _), JsonValue::Number(n)) => {
+                convert_time_unit(n.as_i64()?, unit, stats_unit.unwrap_or(unit)).map(Self::Int)
+            }
+            (DataType::Boolean, JsonValue::Bool(b)) => Some(Self::Bool(*b)),
+            (
+                DataType::Int8
+                | DataType::Int16
+                | DataType::Int32
+                | DataType::Int64
+                | DataType::UInt8
+                | DataType::UInt16
+                | DataType::UInt32
+                | DataType::Date32,
+                JsonValue::Number(n),
+            ) => n.as_i64().map(Self::Int),
+            (DataType::Float16 | DataType::Float32 | DataType::Float64, JsonValue::Number(n)) => {
+                n.as_f64().map(Self::Float)
+            }
+            (DataType::Utf8 | DataType::LargeUtf8, JsonValue::String(s)) => {
+                Some(Self::Bytes(s.as_bytes().to_vec()))
+            }
+            (DataType::Date32, JsonValue::String(s)) => {
+                let date = NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok()?;
+                Some(Self::Int(
+                    (date - NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()).num_days(),
+                ))
+            }
+            (DataType::Timestamp(unit, _), JsonValue::String(s)) => {
+                let timestamp = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+                    .iter()
+                    .find_map(|format| NaiveDateTime::parse_from_str(s.trim(), format).ok())?
+                    .and_utc();
+                match stats_unit.unwrap_or(unit) {
+                    TimeUnit::Second => Some(timestamp.timestamp()),
+                    TimeUnit::Millisecond => Some(timestamp.timestamp_millis()),
+                    TimeUnit::Microsecond => Some(timestamp.timestamp_micros()),
+                    TimeUnit::Nanosecond => timestamp.timestamp_nanos_opt(),
+                }
+                .map(Self::Int)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the minimum and maximum values recorded in `statistics`, if
+    /// they can be compared with a bound of type `data_type`.
+    fn from_statistics(statistics: &Statistics, data_type: &DataType) -> Option<(Self, Self)> {
+        fn bounds<T, F>(statistics: &ValueStatistics<T>, f: F) -> Option<(StatValue, StatValue)>
+        where
+            F: Fn(&T) -> StatValue,
+        {
+            Some((f(statistics.min_opt()?), f(statistics.max_opt()?)))
+        }
+
+        match (statistics, data_type) {
+            (Statistics::Boolean(s), DataType::Boolean) => bounds(s, |v| Self::Bool(*v)),
+            (
+                Statistics::Int32(s),
+                DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Date32,
+            ) => bounds(s, |v| Self::Int(*v as i64)),
+            // Unsigned values are stored as signed integers of the same width.
+            (Statistics::Int32(s), DataType::UInt8 | DataType::UInt16 | DataType::UInt32) => {
+                bounds(s, |v| Self::Int(*v as u32 as i64))
+            }
+            (Statistics::Int64(s), DataType::Int64 | DataType::Timestamp(_, _)) => {
+                bounds(s, |v| Self::Int(*v))
+            }
+            (Statistics::Float(s), DataType::Float16 | DataType::Float32) => {
+                bounds(s, |v| Self::Float(*v as f64))
+            }
+            (Statistics::Double(s), DataType::Float64) => bounds(s, |v| Self::Float(*v)),
+            (Statistics::ByteArray(s), DataType::Utf8 | DataType::LargeUtf8) => {
+                bounds(s, |v| Self::Bytes(v.data().to_vec()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Converts `value` from `from` units to `to` units, rounding down.
+///
+/// Rounding down never causes a row group to be pruned incorrectly: a row
+/// group is pruned if its maximum is below the lower bound, or its minimum
+/// is above the upper bound.
+fn convert_time_unit(value: i64, from: &TimeUnit, to: &TimeUnit) -> Option<i64> {
+    fn units_per_second(unit: &TimeUnit) -> i64 {
+        match unit {
+            TimeUnit::Second => 1,
+            TimeUnit::Millisecond => 1_000,
+            TimeUnit::Microsecond => 1_000_000,
+            TimeUnit::Nanosecond => 1_000_000_000,
+        }
+    }
+
+    let (from, to) = (units_per_second(from), units_per_second(to));
+    if to >= from {
+        value.checked_mul(to / from)
+    } else {
+        Some(value.div_euclid(from / to))
+    }
+}
+
+/// Returns the unit of the values of a Parquet timestamp column.
+///
+/// This can differ from the unit of the Arrow column it is read as: Parquet
+/// has no seconds unit, so `Timestamp(Second)` columns are stored in
+/// milliseconds.
+fn parquet_time_unit(
+    logical_type: Option<LogicalType>,
+    converted_type: ConvertedType,
+) -> Option<TimeUnit> {
+    match logical_type {
+        Some(LogicalType::Timestamp { unit, .. }) => Some(match unit {
+            ParquetTimeUnit::MILLIS(_) => TimeUnit::Millisecond,
+            ParquetTimeUnit::MICROS(_) => TimeUnit::Microsecond,
+            ParquetTimeUnit::NANOS(_) => TimeUnit::Nanosecond,
+        }),
+        _ => match converted_type {
+            ConvertedType::TIMESTAMP_MILLIS => Some(TimeUnit::Millisecond),
+            ConvertedType::TIMESTAMP_MICROS => Some(TimeUnit::Microsecond),
+            _ => None,
+        },
+    }
+}
+
+/// Returns `false` if column statistics show that none of the rows in
+/// `row_group` fall within `range`.
+fn may_match(row_group: &RowGroupMetaData, schema: &Schema, range: &ParquetColumnRange) -> bool {
+    let Ok(field) = schema.field_with_name(&range.column) else {
+        return true;
+    };
+    let Some(column) = row_group
+        .columns()
+        .iter()
+        .find(|column| matches!(column.column_path().parts(), [name] if name == &range.column))
+    else {
+        return true;
+    };
+    let Some(statistics) = column.statistics() else {
+        return true;
+    };
+    let Some((min, max)) = StatValue::from_statistics(statistics, field.data_type()) else {
+        return true;
+    };
+    let descr = column.column_descr();
+    let stats_unit = parquet_time_unit(descr.logical_type(), descr.converted_type());
+
+    let below_min = range
+        .min
+        .as_ref()
+        .and_then(|bound| StatValue::from_bound(bound, field.data_type(), stats_unit.as_ref()))
+        .is_some_and(|bound| max < bound);
+    let above_max = range
+        .max
+        .as_ref()
+        .and_then(|bound| StatValue::from_bound(bound, field.data_type(), stats_unit.as_ref()))
+        .is_some_and(|bound| min > bound);
+
+    !below_min && !above_max
 }
 
 impl Parser for ParquetParser {
@@ -105,8 +445,8 @@ impl Parser for ParquetParser {
     fn parse(&mut self, data: &[u8]) -> (Option<Box<dyn InputBuffer>>, Vec<ParseError>) {
         let bytes = Bytes::copy_from_slice(data);
 
-        let parquet_reader = match ParquetRecordBatchReader::try_new(bytes, 1_000_000) {
-            Ok(parquet_reader) => parquet_reader,
+        let (parquet_reader, schema) = match self.reader(bytes) {
+            Ok(reader) => reader,
             Err(e) => {
                 return (
                     None,
@@ -121,6 +461,12 @@ impl Parser for ParquetParser {
 
         let mut errors = Vec::new();
         for batch in parquet_reader {
+            let batch = match (batch, &schema) {
+                (Ok(batch), Some(schema)) => {
+                    RecordBatch::try_new(schema.clone(), batch.columns().to_vec())
+                }
+                (batch, _) => batch,
+            };
             match batch {
                 Ok(batch) => {
                     if let Err(e) = self.input_stream.insert(&batch) {
@@ -153,7 +499,11 @@ impl Parser for ParquetParser {
     }
 
     fn fork(&self) -> Box<dyn Parser> {
-        Box::new(Self::new(self.input_stream.fork()))
+        Box::new(Self::new(
+            self.input_stream.fork(),
+            self.config.clone(),
+            self.columns.clone(),
+        ))
     }
 
     fn splitter(&self) -> Box<dyn super::Splitter> {
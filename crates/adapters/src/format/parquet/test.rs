@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Cursor;
@@ -6,11 +7,16 @@ use std::sync::{Arc, Mutex};
 use std::thread::sleep;
 use std::time::Duration;
 
-use arrow::array::RecordBatch;
+use arrow::array::{
+    ArrayRef, BooleanArray, Int64Array, RecordBatch, StringArray, TimestampSecondArray,
+};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
 use dbsp::utils::Tup2;
 use dbsp::OrdZSet;
 use feldera_types::format::json::JsonFlavor;
-use feldera_types::format::parquet::ParquetEncoderConfig;
+use feldera_types::format::parquet::{
+    ParquetColumnRange, ParquetEncoderConfig, ParquetParserConfig,
+};
 use feldera_types::program_schema::Relation;
 use feldera_types::serde_with_context::{DeserializeWithContext, SqlSerdeConfig};
 use parquet::arrow::ArrowWriter;
@@ -26,7 +32,11 @@ use crate::{
     catalog::SerBatchReader,
     format::{parquet::ParquetEncoder, Encoder},
     static_compile::seroutput::SerBatchImpl,
-    test::{mock_input_pipeline, wait, MockOutputConsumer, TestStruct2, DEFAULT_TIMEOUT_MS},
+    test::{
+        mock_input_pipeline, mock_parser_pipeline, wait, MockOutputConsumer, MockUpdate,
+        TestStruct, TestStruct2, DEFAULT_TIMEOUT_MS,
+    },
+    FormatConfig,
 };
 
 /// Parse Parquet file into an array of `T`.
@@ -113,6 +123,178 @@ format:
     }
 }
 
+/// Parquet file with one row per row group, whose column names don't match
+/// the `TestStruct` table.
+fn renamed_columns_file() -> Vec<u8> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("ID", DataType::Int64, false),
+        Field::new("B", DataType::Boolean, false),
+        Field::new("extra", DataType::Utf8, false),
+        Field::new("str", DataType::Utf8, false),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from(vec![1, 2, 3])),
+        Arc::new(BooleanArray::from(vec![true, false, true])),
+        Arc::new(StringArray::from(vec!["x", "y", "z"])),
+        Arc::new(StringArray::from(vec!["foo", "bar", "baz"])),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns).unwrap();
+
+    let props = WriterProperties::builder()
+        .set_max_row_group_size(1)
+        .build();
+    let mut buffer = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut buffer, schema, Some(props)).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+    buffer
+}
+
+fn parse_parquet(
+    config: ParquetParserConfig,
+    data: &[u8],
+) -> Vec<MockUpdate<TestStruct, TestStruct>> {
+    let format_config = FormatConfig {
+        name: Cow::from("parquet"),
+        config: serde_yaml::to_value(config).unwrap(),
+    };
+    let (consumer, mut parser, outputs) =
+        mock_parser_pipeline(&TestStruct::relation_schema(), &format_config).unwrap();
+    consumer.on_error(Some(Box::new(|_, _| {})));
+
+    let (mut buffer, errors) = parser.parse(data);
+    assert_eq!(errors, vec![]);
+    buffer.flush();
+
+    let flushed = outputs.state().flushed.clone();
+    flushed
+}
+
+#[test]
+fn parquet_input_options() {
+    let data = renamed_columns_file();
+    let test_struct = |id, b, s: &str| TestStruct {
+        id,
+        b,
+        i: None,
+        s: s.to_string(),
+    };
+
+    // Columns are matched by name ignoring case and renamed.
+    let config = ParquetParserConfig {
+        rename: BTreeMap::from([("str".to_string(), "s".to_string())]),
+        ..Default::default()
+    };
+    assert_eq!(
+        parse_parquet(config.clone(), &data),
+        vec![
+            MockUpdate::Insert(test_struct(1, true, "foo")),
+            MockUpdate::Insert(test_struct(2, false, "bar")),
+            MockUpdate::Insert(test_struct(3, true, "baz")),
+        ]
+    );
+
+    // Row groups outside the range are skipped.
+    let config = ParquetParserConfig {
+        row_group_filter: vec![ParquetColumnRange {
+            column: "ID".to_string(),
+            min: Some(serde_json::json!(2)),
+            max: None,
+        }],
+        ..config
+    };
+    assert_eq!(
+        parse_parquet(config.clone(), &data),
+        vec![
+            MockUpdate::Insert(test_struct(2, false, "bar")),
+            MockUpdate::Insert(test_struct(3, true, "baz")),
+        ]
+    );
+
+    // Unprojected columns are not read.
+    let config = ParquetParserConfig {
+        columns: Some(vec!["ID".to_string(), "B".to_string(), "str".to_string()]),
+        row_group_filter: vec![ParquetColumnRange {
+            column: "str".to_string(),
+            min: Some(serde_json::json!("bar")),
+            max: Some(serde_json::json!("baz")),
+        }],
+        ..config
+    };
+    assert_eq!(
+        parse_parquet(config, &data),
+        vec![
+            MockUpdate::Insert(test_struct(2, false, "bar")),
+            MockUpdate::Insert(test_struct(3, true, "baz")),
+        ]
+    );
+}
+
+/// Parquet file with one row per row group and a second-precision timestamp
+/// column, which Parquet stores in milliseconds.
+fn timestamp_seconds_file() -> Vec<u8> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("b", DataType::Boolean, false),
+        Field::new("s", DataType::Utf8, false),
+        Field::new("ts", DataType::Timestamp(TimeUnit::Second, None), false),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from(vec![1, 2, 3])),
+        Arc::new(BooleanArray::from(vec![true, false, true])),
+        Arc::new(StringArray::from(vec!["foo", "bar", "baz"])),
+        // 2024-01-01, 2024-01-02, and 2024-01-03 at midnight.
+        Arc::new(TimestampSecondArray::from(vec![
+            1_704_067_200,
+            1_704_153_600,
+            1_704_240_000,
+        ])),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns).unwrap();
+
+    let props = WriterProperties::builder()
+        .set_max_row_group_size(1)
+        .build();
+    let mut buffer = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut buffer, schema, Some(props)).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+    buffer
+}
+
+#[test]
+fn parquet_timestamp_row_group_filter() {
+    let data = timestamp_seconds_file();
+    let expected = vec![MockUpdate::Insert(TestStruct {
+        id: 2,
+        b: false,
+        i: None,
+        s: "bar".to_string(),
+    })];
+
+    // String bounds.
+    let config = ParquetParserConfig {
+        row_group_filter: vec![ParquetColumnRange {
+            column: "ts".to_string(),
+            min: Some(serde_json::json!("2024-01-02 00:00:00")),
+            max: Some(serde_json::json!("2024-01-02 12:00:00")),
+        }],
+        ..Default::default()
+    };
+    assert_eq!(parse_parquet(config, &data), expected);
+
+    // Numeric bounds are in the unit of the Arrow column.
+    let config = ParquetParserConfig {
+        row_group_filter: vec![ParquetColumnRange {
+            column: "ts".to_string(),
+            min: Some(serde_json::json!(1_704_153_600)),
+            max: Some(serde_json::json!(1_704_153_600)),
+        }],
+        ..Default::default()
+    };
+    assert_eq!(parse_parquet(config, &data), expected);
+}
+
 #[test]
 fn parquet_output() {
     let buffer = Arc::new(Mutex::new(Vec::with_capacity(4096)));
@@ -0,0 +1,208 @@
+use super::{
+    message_descriptor, record_descriptor,
+    schema::{message_to_json, validate_message},
+    Envelope,
+};
+use crate::{
+    catalog::{DeCollectionStream, InputCollectionHandle, RecordFormat},
+    format::{Splitter, Sponge},
+    ControllerError, InputBuffer, InputFormat, ParseError, Parser,
+};
+use actix_web::HttpRequest;
+use erased_serde::Serialize as ErasedSerialize;
+use feldera_types::{
+    format::{json::JsonFlavor, protobuf::ProtobufParserConfig},
+    program_schema::Relation,
+};
+use prost_reflect::{DynamicMessage, MessageDescriptor};
+use serde::Deserialize;
+use serde_urlencoded::Deserializer as UrlDeserializer;
+use serde_yaml::Value as YamlValue;
+use std::borrow::Cow;
+
+/// Protobuf format parser.
+pub struct ProtobufInputFormat;
+
+impl InputFormat for ProtobufInputFormat {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("protobuf")
+    }
+
+    fn new_parser(
+        &self,
+        endpoint_name: &str,
+        input_handle: &InputCollectionHandle,
+        config: &YamlValue,
+    ) -> Result<Box<dyn Parser>, ControllerError> {
+        let config = ProtobufParserConfig::deserialize(config).map_err(|e| {
+            ControllerError::parser_config_parse_error(
+                endpoint_name,
+                &e,
+                &serde_yaml::to_string(config).unwrap_or_default(),
+            )
+        })?;
+
+        let input_stream = input_handle
+            .handle
+            .configure_deserializer(RecordFormat::Json(JsonFlavor::ProtobufConverter))?;
+
+        Ok(Box::new(ProtobufParser::create(
+            endpoint_name,
+            &input_handle.schema,
+            input_stream,
+            &config,
+        )?) as Box<dyn Parser>)
+    }
+
+    fn config_from_http_request(
+        &self,
+        endpoint_name: &str,
+        request: &HttpRequest,
+    ) -> Result<Box<dyn ErasedSerialize>, ControllerError> {
+        Ok(Box::new(
+            ProtobufParserConfig::deserialize(UrlDeserializer::new(form_urlencoded::parse(
+                request.query_string().as_bytes(),
+            )))
+            .map_err(|e| {
+                ControllerError::parser_config_parse_error(
+                    endpoint_name,
+                    &e,
+                    request.query_string(),
+                )
+            })?,
+        ))
+    }
+}
+
+struct ProtobufParser {
+    input_stream: Box<dyn DeCollectionStream>,
+    relation_schema: Relation,
+
+    /// Top-level message type.
+    message: MessageDescriptor,
+
+    /// Set for the `insert_delete` update format.
+    envelope: Option<Envelope>,
+
+    last_event_number: u64,
+}
+
+impl ProtobufParser {
+    fn create(
+        endpoint_name: &str,
+        relation_schema: &Relation,
+        input_stream: Box<dyn DeCollectionStream>,
+        config: &ProtobufParserConfig,
+    ) -> Result<Self, ControllerError> {
+        let message = message_descriptor(&config.schema)
+            .map_err(|e| ControllerError::invalid_parser_configuration(endpoint_name, &e))?;
+        let (record, envelope) = record_descriptor(message.clone(), &config.update_format)
+            .map_err(|e| ControllerError::invalid_parser_configuration(endpoint_name, &e))?;
+
+        validate_message(&record, &relation_schema.fields, false).map_err(|e| {
+            ControllerError::schema_validation_error(&format!(
+                "error validating Protobuf message type '{}' against the schema of table {}: {e}",
+                record.full_name(),
+                relation_schema.name.sql_name()
+            ))
+        })?;
+
+        Ok(Self {
+            input_stream,
+            relation_schema: relation_schema.clone(),
+            message,
+            envelope,
+            last_event_number: 0,
+        })
+    }
+
+    fn input(&mut self, data: &[u8]) -> Result<(), ParseError> {
+        self.last_event_number += 1;
+
+        let message = DynamicMessage::decode(self.message.clone(), data).map_err(|e| {
+            ParseError::bin_envelope_error(
+                format!("error decoding Protobuf message: {e}"),
+                data,
+                None,
+            )
+        })?;
+
+        match self.envelope.clone() {
+            None => self.push(&message, data, true),
+            Some(envelope) => {
+                let has_delete = message.has_field(&envelope.delete);
+                let has_insert = message.has_field(&envelope.insert);
+                if !has_delete && !has_insert {
+                    return Err(ParseError::bin_envelope_error(
+                        "Protobuf message contains neither an 'insert' nor a 'delete' record"
+                            .to_string(),
+                        data,
+                        None,
+                    ));
+                }
+                if has_delete {
+                    let record = message.get_field(&envelope.delete);
+                    self.push(record.as_message().unwrap(), data, false)?;
+                }
+                if has_insert {
+                    let record = message.get_field(&envelope.insert);
+                    self.push(record.as_message().unwrap(), data, true)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Converts `record` to a table row and inserts or deletes it.
+    fn push(
+        &mut self,
+        record: &DynamicMessage,
+        data: &[u8],
+        insert: bool,
+    ) -> Result<(), ParseError> {
+        let json = message_to_json(record, &self.relation_schema.fields).map_err(|e| {
+            ParseError::bin_event_error(
+                format!("error converting Protobuf message to a table row: {e}"),
+                self.last_event_number,
+                data,
+                None,
+            )
+        })?;
+        let bytes = serde_json::to_vec(&json).unwrap();
+
+        let result = if insert {
+            self.input_stream.insert(&bytes)
+        } else {
+            self.input_stream.delete(&bytes)
+        };
+        result.map_err(|e| {
+            ParseError::bin_event_error(
+                format!("error deserializing Protobuf message as a table row (converted record: {json}): {e}"),
+                self.last_event_number,
+                data,
+                None,
+            )
+        })
+    }
+}
+
+impl Parser for ProtobufParser {
+    fn splitter(&self) -> Box<dyn Splitter> {
+        Box::new(Sponge)
+    }
+
+    fn parse(&mut self, data: &[u8]) -> (Option<Box<dyn InputBuffer>>, Vec<ParseError>) {
+        let errors = self.input(data).map_or_else(|e| vec![e], |_| Vec::new());
+        (self.input_stream.take_all(), errors)
+    }
+
+    fn fork(&self) -> Box<dyn Parser> {
+        Box::new(Self {
+            input_stream: self.input_stream.fork(),
+            relation_schema: self.relation_schema.clone(),
+            message: self.message.clone(),
+            envelope: self.envelope.clone(),
+            last_event_number: 0,
+        })
+    }
+}
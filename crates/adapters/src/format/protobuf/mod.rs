@@ -0,0 +1,127 @@
+use base64::{prelude::BASE64_STANDARD, Engine};
+use feldera_types::format::protobuf::{ProtobufSchemaConfig, ProtobufUpdateFormat};
+use prost_reflect::{DescriptorPool, FieldDescriptor, Kind, MessageDescriptor};
+use protox::file::{ChainFileResolver, File, FileResolver, GoogleFileResolver};
+
+pub mod input;
+pub mod output;
+mod schema;
+
+#[cfg(test)]
+mod test;
+
+/// Name of the file that holds the `proto` source in the in-memory file
+/// system used to compile it.
+const PROTO_FILE_NAME: &str = "schema.proto";
+
+/// Resolves the user-supplied `.proto` source.
+struct SourceFileResolver {
+    source: String,
+}
+
+impl FileResolver for SourceFileResolver {
+    fn open_file(&self, name: &str) -> Result<File, protox::Error> {
+        if name == PROTO_FILE_NAME {
+            File::from_source(name, &self.source)
+        } else {
+            Err(protox::Error::file_not_found(name))
+        }
+    }
+}
+
+/// Builds a descriptor pool from a base64-encoded descriptor set or `.proto`
+/// source.
+fn descriptor_pool(config: &ProtobufSchemaConfig) -> Result<DescriptorPool, String> {
+    match (&config.descriptor_set, &config.proto) {
+        (Some(_), Some(_)) => {
+            Err("'descriptor_set' and 'proto' properties are mutually exclusive".to_string())
+        }
+        (None, None) => {
+            Err("one of 'descriptor_set' or 'proto' properties is required".to_string())
+        }
+        (Some(descriptor_set), None) => {
+            let bytes = BASE64_STANDARD
+                .decode(descriptor_set.trim())
+                .map_err(|e| format!("'descriptor_set' is not a valid base64 string: {e}"))?;
+            DescriptorPool::decode(bytes.as_slice())
+                .map_err(|e| format!("error decoding 'descriptor_set': {e}"))
+        }
+        (None, Some(proto)) => {
+            let mut resolver = ChainFileResolver::new();
+            resolver.add(SourceFileResolver {
+                source: proto.clone(),
+            });
+            resolver.add(GoogleFileResolver::new());
+
+            let mut compiler = protox::Compiler::with_file_resolver(resolver);
+            compiler.include_imports(true);
+            compiler
+                .open_file(PROTO_FILE_NAME)
+                .map_err(|e| format!("error compiling 'proto' source: {e}"))?;
+            Ok(compiler.descriptor_pool())
+        }
+    }
+}
+
+/// Returns the descriptor of the message type specified in `config`.
+fn message_descriptor(config: &ProtobufSchemaConfig) -> Result<MessageDescriptor, String> {
+    let pool = descriptor_pool(config)?;
+    pool.get_message_by_name(&config.message).ok_or_else(|| {
+        format!(
+            "message type '{}' not found in the Protobuf schema",
+            config.message
+        )
+    })
+}
+
+/// `insert` and `delete` fields of an `insert_delete` envelope.
+#[derive(Clone)]
+struct Envelope {
+    insert: FieldDescriptor,
+    delete: FieldDescriptor,
+}
+
+/// Looks up a message-typed field of an `insert_delete` envelope.
+fn envelope_field(
+    envelope: &MessageDescriptor,
+    name: &str,
+) -> Result<(FieldDescriptor, MessageDescriptor), String> {
+    let field = envelope.get_field_by_name(name).ok_or_else(|| {
+        format!(
+            "message type '{}' used with the 'insert_delete' update format must have an '{name}' field",
+            envelope.full_name()
+        )
+    })?;
+    match field.kind() {
+        Kind::Message(record) if !field.is_list() && !field.is_map() => Ok((field, record)),
+        _ => Err(format!(
+            "field '{name}' of message type '{}' must be a message",
+            envelope.full_name()
+        )),
+    }
+}
+
+/// Returns the descriptor of the record type and, for the `insert_delete`
+/// update format, the fields of the envelope.
+fn record_descriptor(
+    message: MessageDescriptor,
+    update_format: &ProtobufUpdateFormat,
+) -> Result<(MessageDescriptor, Option<Envelope>), String> {
+    match update_format {
+        ProtobufUpdateFormat::Raw => Ok((message, None)),
+        ProtobufUpdateFormat::InsertDelete => {
+            let (insert, insert_type) = envelope_field(&message, "insert")?;
+            let (delete, delete_type) = envelope_field(&message, "delete")?;
+            if insert_type != delete_type {
+                return Err(format!(
+                    "'insert' and 'delete' fields of message type '{}' must have the same type, but they have types '{}' and '{}'",
+                    message.full_name(),
+                    insert_type.full_name(),
+                    delete_type.full_name()
+                ));
+            }
+
+            Ok((insert_type, Some(Envelope { insert, delete })))
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use super::{
+    message_descriptor, record_descriptor,
+    schema::{json_to_message, validate_message},
+    Envelope,
+};
+use crate::catalog::{CursorWithPolarity, SerBatchReader};
+use crate::format::message_fields::{MessageAttributes, MessageFields};
+use crate::format::MAX_DUPLICATES;
+use crate::{ControllerError, Encoder, OutputConsumer, OutputFormat, RecordFormat, SerCursor};
+use actix_web::HttpRequest;
+use anyhow::{anyhow, bail, Result as AnyResult};
+use erased_serde::Serialize as ErasedSerialize;
+use feldera_types::config::ConnectorConfig;
+use feldera_types::format::json::JsonFlavor;
+use feldera_types::format::protobuf::ProtobufEncoderConfig;
+use feldera_types::program_schema::Relation;
+use prost::Message;
+use prost_reflect::{DynamicMessage, MessageDescriptor, Value};
+use serde::Deserialize;
+use serde_urlencoded::Deserializer as UrlDeserializer;
+use std::borrow::Cow;
+
+/// Protobuf format encoder.
+pub struct ProtobufOutputFormat;
+
+impl OutputFormat for ProtobufOutputFormat {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("protobuf")
+    }
+
+    fn config_from_http_request(
+        &self,
+        endpoint_name: &str,
+        request: &HttpRequest,
+    ) -> Result<Box<dyn ErasedSerialize>, ControllerError> {
+        let config = ProtobufEncoderConfig::deserialize(UrlDeserializer::new(
+            form_urlencoded::parse(request.query_string().as_bytes()),
+        ))
+        .map_err(|e| {
+            ControllerError::encoder_config_parse_error(endpoint_name, &e, request.query_string())
+        })?;
+
+        Ok(Box::new(config))
+    }
+
+    fn new_encoder(
+        &self,
+        endpoint_name: &str,
+        config: &ConnectorConfig,
+        key_schema: &Option<Relation>,
+        value_schema: &Relation,
+        consumer: Box<dyn OutputConsumer>,
+    ) -> Result<Box<dyn Encoder>, ControllerError> {
+        let protobuf_config = ProtobufEncoderConfig::deserialize(
+            &config.format.as_ref().unwrap().config,
+        )
+        .map_err(|e| {
+            ControllerError::encoder_config_parse_error(
+                endpoint_name,
+                &e,
+                &serde_yaml::to_string(config).unwrap_or_default(),
+            )
+        })?;
+
+        if key_schema.is_some() {
+            return Err(ControllerError::invalid_encoder_configuration(
+                endpoint_name,
+                "Protobuf encoder cannot be attached to an index",
+            ));
+        }
+
+        let message_fields = MessageFields::from_transport(&config.transport);
+        if let Some(message_fields) = &message_fields {
+            message_fields.validate(endpoint_name, value_schema)?;
+        }
+
+        Ok(Box::new(
+            ProtobufEncoder::create(endpoint_name, value_schema, consumer, &protobuf_config)?
+                .with_message_fields(message_fields),
+        ))
+    }
+}
+
+pub(crate) struct ProtobufEncoder {
+    /// Consumer to push serialized data to.
+    output_consumer: Box<dyn OutputConsumer>,
+
+    value_schema: Relation,
+
+    /// Top-level message type.
+    message: MessageDescriptor,
+
+    /// Message type of a single record.
+    record: MessageDescriptor,
+
+    /// Set for the `insert_delete` update format.
+    envelope: Option<Envelope>,
+
+    /// Columns used to compute the key, headers, and topic of each message.
+    message_fields: Option<MessageFields>,
+
+    /// Key, headers, and topic of the current message.
+    message_attributes: MessageAttributes,
+}
+
+impl ProtobufEncoder {
+    pub(crate) fn create(
+        endpoint_name: &str,
+        value_schema: &Relation,
+        output_consumer: Box<dyn OutputConsumer>,
+        config: &ProtobufEncoderConfig,
+    ) -> Result<Self, ControllerError> {
+        let message = message_descriptor(&config.schema)
+            .map_err(|e| ControllerError::invalid_encoder_configuration(endpoint_name, &e))?;
+        let (record, envelope) = record_descriptor(message.clone(), &config.update_format)
+            .map_err(|e| ControllerError::invalid_encoder_configuration(endpoint_name, &e))?;
+
+        validate_message(&record, &value_schema.fields, true).map_err(|e| {
+            ControllerError::invalid_encoder_configuration(
+                endpoint_name,
+                &format!(
+                    "Protobuf message type '{}' is not compatible with the schema of view {}: {e}",
+                    record.full_name(),
+                    value_schema.name.sql_name()
+                ),
+            )
+        })?;
+
+        Ok(Self {
+            output_consumer,
+            value_schema: value_schema.clone(),
+            message,
+            record,
+            envelope,
+            message_fields: None,
+            message_attributes: MessageAttributes::default(),
+        })
+    }
+
+    /// Computes the key, headers, and topic of each message from the columns
+    /// of the output record.
+    pub(crate) fn with_message_fields(mut self, message_fields: Option<MessageFields>) -> Self {
+        self.message_fields = message_fields;
+        self
+    }
+}
+
+impl Encoder for ProtobufEncoder {
+    fn consumer(&mut self) -> &mut dyn OutputConsumer {
+        self.output_consumer.as_mut()
+    }
+
+    fn encode(&mut self, batch: &dyn SerBatchReader) -> AnyResult<()> {
+        let mut cursor = CursorWithPolarity::new(
+            batch.cursor(RecordFormat::Json(JsonFlavor::ProtobufConverter))?,
+        );
+
+        while cursor.key_valid() {
+            if !cursor.val_valid() {
+                cursor.step_key();
+                continue;
+            }
+
+            let mut w = cursor.weight();
+            if !(-MAX_DUPLICATES..=MAX_DUPLICATES).contains(&w) {
+                bail!(
+                    "Unable to output record with very large weight {w}. Consider adjusting your SQL queries to avoid duplicate output records, e.g., using 'SELECT DISTINCT'."
+                );
+            }
+            let op = if w > 0 { "insert" } else { "delete" };
+
+            let json = cursor.key_to_json()?;
+            let record = json_to_message(&json, &self.record, &self.value_schema.fields)
+                .map_err(|e| anyhow!("error converting record to Protobuf message: {e}"))?;
+
+            let (message, headers) = match &self.envelope {
+                None => (record, vec![("op", Some(op.as_bytes()))]),
+                Some(envelope) => {
+                    let mut message = DynamicMessage::new(self.message.clone());
+                    let field = if w > 0 {
+                        &envelope.insert
+                    } else {
+                        &envelope.delete
+                    };
+                    message.set_field(field, Value::Message(record));
+                    (message, vec![])
+                }
+            };
+            let buffer = message.encode_to_vec();
+
+            if let Some(message_fields) = &self.message_fields {
+                message_fields.encode_json(&json, op, &mut self.message_attributes);
+            }
+
+            while w != 0 {
+                self.message_attributes.push(
+                    self.output_consumer.as_mut(),
+                    None,
+                    Some(&buffer),
+                    &headers,
+                    1,
+                );
+
+                if w > 0 {
+                    w -= 1;
+                } else {
+                    w += 1;
+                }
+            }
+
+            cursor.step_key();
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,614 @@
+//! Conversion between Protobuf messages and SQL records.
+//!
+//! Records are converted to and from JSON values, which are serialized and
+//! deserialized using the `ProtobufConverter` JSON flavor.
+
+use chrono::{Datelike, NaiveDate};
+use feldera_types::program_schema::{ColumnType, Field, SqlType};
+use prost_reflect::{
+    DynamicMessage, EnumDescriptor, FieldDescriptor, Kind, MapKey, MessageDescriptor, Value,
+};
+use serde_json::{Map as JsonMap, Number, Value as JsonValue};
+use std::collections::HashMap;
+
+/// Days between 0001-01-01 and 1970-01-01.
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+const TIMESTAMP_TYPE: &str = "google.protobuf.Timestamp";
+const DATE_TYPE: &str = "google.type.Date";
+
+/// Looks up the Protobuf field that matches a SQL column.
+///
+/// Prefers an exact match, falling back to case-insensitive comparison.
+pub(super) fn lookup_field(message: &MessageDescriptor, field: &Field) -> Option<FieldDescriptor> {
+    let name = field.name.name();
+    message.get_field_by_name(&name).or_else(|| {
+        message
+            .fields()
+            .find(|f| f.name().eq_ignore_ascii_case(&name))
+    })
+}
+
+/// Returns the `value` field of a `google.protobuf.*Value` wrapper type.
+fn wrapper_value_field(message: &MessageDescriptor) -> Option<FieldDescriptor> {
+    match message.full_name() {
+        "google.protobuf.DoubleValue"
+        | "google.protobuf.FloatValue"
+        | "google.protobuf.Int64Value"
+        | "google.protobuf.UInt64Value"
+        | "google.protobuf.Int32Value"
+        | "google.protobuf.UInt32Value"
+        | "google.protobuf.BoolValue"
+        | "google.protobuf.StringValue"
+        | "google.protobuf.BytesValue" => message.get_field_by_name("value"),
+        _ => None,
+    }
+}
+
+fn is_integer_kind(kind: &Kind) -> bool {
+    matches!(
+        kind,
+        Kind::Int32
+            | Kind::Int64
+            | Kind::Uint32
+            | Kind::Uint64
+            | Kind::Sint32
+            | Kind::Sint64
+            | Kind::Fixed32
+            | Kind::Fixed64
+            | Kind::Sfixed32
+            | Kind::Sfixed64
+    )
+}
+
+fn kind_name(kind: &Kind) -> String {
+    match kind {
+        Kind::Message(message) => format!("message '{}'", message.full_name()),
+        Kind::Enum(enum_type) => format!("enum '{}'", enum_type.full_name()),
+        kind => format!("{kind:?}").to_lowercase(),
+    }
+}
+
+/// Checks that the Protobuf message type can be converted to and from a SQL
+/// struct with the specified fields.
+///
+/// When `require_all_columns` is `false`, nullable columns without a matching
+/// Protobuf field are allowed; they are set to `NULL` on input.
+pub(super) fn validate_message(
+    message: &MessageDescriptor,
+    fields: &[Field],
+    require_all_columns: bool,
+) -> Result<(), String> {
+    for field in fields {
+        let Some(proto_field) = lookup_field(message, field) else {
+            if !require_all_columns && field.columntype.nullable {
+                continue;
+            }
+            return Err(format!(
+                "column '{}' has no matching field in Protobuf message type '{}'",
+                field.name.name(),
+                message.full_name()
+            ));
+        };
+
+        validate_field(&proto_field, &field.columntype, require_all_columns).map_err(|e| {
+            format!(
+                "error validating Protobuf field '{}' for column '{}': {e}",
+                proto_field.full_name(),
+                field.name.name()
+            )
+        })?;
+    }
+    Ok(())
+}
+
+fn validate_field(
+    field: &FieldDescriptor,
+    column_type: &ColumnType,
+    require_all_columns: bool,
+) -> Result<(), String> {
+    if field.is_list() {
+        if column_type.typ != SqlType::Array {
+            return Err(format!(
+                "repeated field cannot be converted to SQL type {}",
+                column_type.typ
+            ));
+        }
+        let component = column_type
+            .component
+            .as_ref()
+            .ok_or_else(|| "internal error: array type without component type".to_string())?;
+        validate_kind(&field.kind(), component, require_all_columns)
+    } else if field.is_map() {
+        if column_type.typ != SqlType::Map {
+            return Err(format!(
+                "map field cannot be converted to SQL type {}",
+                column_type.typ
+            ));
+        }
+        let (Some(key_type), Some(value_type)) = (&column_type.key, &column_type.value) else {
+            return Err("internal error: map type without key or value type".to_string());
+        };
+        let entry = field.kind();
+        let entry = entry.as_message().unwrap();
+        validate_kind(
+            &entry.map_entry_key_field().kind(),
+            key_type,
+            require_all_columns,
+        )?;
+        validate_kind(
+            &entry.map_entry_value_field().kind(),
+            value_type,
+            require_all_columns,
+        )
+    } else {
+        validate_kind(&field.kind(), column_type, require_all_columns)
+    }
+}
+
+fn validate_kind(
+    kind: &Kind,
+    column_type: &ColumnType,
+    require_all_columns: bool,
+) -> Result<(), String> {
+    if let Kind::Message(message) = kind {
+        if let Some(value_field) = wrapper_value_field(message) {
+            return validate_kind(&value_field.kind(), column_type, require_all_columns);
+        }
+    }
+
+    let compatible = match column_type.typ {
+        SqlType::Boolean => matches!(kind, Kind::Bool),
+        SqlType::TinyInt | SqlType::SmallInt | SqlType::Int | SqlType::BigInt => {
+            is_integer_kind(kind) || matches!(kind, Kind::Enum(_))
+        }
+        SqlType::Real | SqlType::Double => {
+            is_integer_kind(kind) || matches!(kind, Kind::Float | Kind::Double)
+        }
+        SqlType::Decimal => {
+            is_integer_kind(kind) || matches!(kind, Kind::Float | Kind::Double | Kind::String)
+        }
+        SqlType::Char | SqlType::Varchar => matches!(kind, Kind::String | Kind::Enum(_)),
+        SqlType::Binary | SqlType::Varbinary => matches!(kind, Kind::Bytes),
+        SqlType::Uuid | SqlType::Variant => matches!(kind, Kind::String),
+        SqlType::Timestamp => match kind {
+            Kind::Message(message) => message.full_name() == TIMESTAMP_TYPE,
+            Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => true,
+            _ => false,
+        },
+        SqlType::Date => match kind {
+            Kind::Message(message) => message.full_name() == DATE_TYPE,
+            Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => true,
+            _ => false,
+        },
+        SqlType::Time => matches!(kind, Kind::Int64 | Kind::Sint64 | Kind::Sfixed64),
+        SqlType::Struct => match kind {
+            Kind::Message(message) => {
+                return validate_message(
+                    message,
+                    column_type.fields.as_deref().unwrap_or_default(),
+                    require_all_columns,
+                )
+            }
+            _ => false,
+        },
+        SqlType::Array | SqlType::Map => {
+            return Err(format!(
+                "SQL type {} can only be converted to a repeated or map field, and nested arrays and maps are not supported",
+                column_type.typ
+            ))
+        }
+        SqlType::Interval(_) | SqlType::Null => {
+            return Err(format!(
+                "SQL type {} is not supported by the Protobuf format",
+                column_type.typ
+            ))
+        }
+    };
+
+    if compatible {
+        Ok(())
+    } else {
+        Err(format!(
+            "Protobuf type {} cannot be converted to SQL type {}",
+            kind_name(kind),
+            column_type.typ
+        ))
+    }
+}
+
+/// Converts a Protobuf message to a JSON object with the specified SQL
+/// fields.
+///
+/// Columns without a matching field and fields that are not set are
+/// converted to `null`.
+pub(super) fn message_to_json(
+    message: &DynamicMessage,
+    fields: &[Field],
+) -> Result<JsonValue, String> {
+    let descriptor = message.descriptor();
+    let mut result = JsonMap::with_capacity(fields.len());
+
+    for field in fields {
+        let value = match lookup_field(&descriptor, field) {
+            Some(proto_field)
+                if !proto_field.supports_presence() || message.has_field(&proto_field) =>
+            {
+                field_to_json(
+                    &message.get_field(&proto_field),
+                    &proto_field,
+                    &field.columntype,
+                )
+                .map_err(|e| format!("error converting field '{}': {e}", proto_field.name()))?
+            }
+            _ => JsonValue::Null,
+        };
+        result.insert(field.name.name(), value);
+    }
+
+    Ok(JsonValue::Object(result))
+}
+
+fn field_to_json(
+    value: &Value,
+    field: &FieldDescriptor,
+    column_type: &ColumnType,
+) -> Result<JsonValue, String> {
+    match value {
+        Value::List(items) => {
+            let component = column_type.component.as_deref().unwrap();
+            items
+                .iter()
+                .map(|item| value_to_json(item, &field.kind(), component))
+                .collect::<Result<Vec<_>, _>>()
+                .map(JsonValue::Array)
+        }
+        Value::Map(entries) => {
+            let entry = field.kind();
+            let value_kind = entry.as_message().unwrap().map_entry_value_field().kind();
+            let value_type = column_type.value.as_deref().unwrap();
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    Ok((
+                        map_key_to_string(key),
+                        value_to_json(value, &value_kind, value_type)?,
+                    ))
+                })
+                .collect::<Result<JsonMap<_, _>, String>>()
+                .map(JsonValue::Object)
+        }
+        value => value_to_json(value, &field.kind(), column_type),
+    }
+}
+
+fn map_key_to_string(key: &MapKey) -> String {
+    match key {
+        MapKey::Bool(b) => b.to_string(),
+        MapKey::I32(n) => n.to_string(),
+        MapKey::I64(n) => n.to_string(),
+        MapKey::U32(n) => n.to_string(),
+        MapKey::U64(n) => n.to_string(),
+        MapKey::String(s) => s.clone(),
+    }
+}
+
+fn float_to_json(f: f64, column_type: &ColumnType) -> Result<JsonValue, String> {
+    if column_type.typ == SqlType::Decimal {
+        return Ok(JsonValue::String(f.to_string()));
+    }
+    Number::from_f64(f)
+        .map(JsonValue::Number)
+        .ok_or_else(|| format!("value {f} cannot be represented in SQL"))
+}
+
+fn integer_to_json<N>(n: N, column_type: &ColumnType) -> JsonValue
+where
+    N: ToString + Into<Number>,
+{
+    if column_type.typ == SqlType::Decimal {
+        JsonValue::String(n.to_string())
+    } else {
+        JsonValue::Number(n.into())
+    }
+}
+
+fn enum_to_json(number: i32, enum_type: &EnumDescriptor, column_type: &ColumnType) -> JsonValue {
+    if column_type.typ.is_string() {
+        match enum_type.get_value(number) {
+            Some(value) => JsonValue::String(value.name().to_string()),
+            None => JsonValue::String(number.to_string()),
+        }
+    } else {
+        JsonValue::Number(number.into())
+    }
+}
+
+fn value_to_json(
+    value: &Value,
+    kind: &Kind,
+    column_type: &ColumnType,
+) -> Result<JsonValue, String> {
+    Ok(match value {
+        Value::Bool(b) => JsonValue::Bool(*b),
+        Value::I32(n) => integer_to_json(*n, column_type),
+        Value::I64(n) => integer_to_json(*n, column_type),
+        Value::U32(n) => integer_to_json(*n, column_type),
+        Value::U64(n) => integer_to_json(*n, column_type),
+        Value::F32(f) => float_to_json(*f as f64, column_type)?,
+        Value::F64(f) => float_to_json(*f, column_type)?,
+        Value::String(s) => JsonValue::String(s.clone()),
+        Value::Bytes(bytes) => {
+            JsonValue::Array(bytes.iter().map(|b| JsonValue::from(*b)).collect())
+        }
+        Value::EnumNumber(number) => match kind {
+            Kind::Enum(enum_type) => enum_to_json(*number, enum_type, column_type),
+            _ => JsonValue::Number((*number).into()),
+        },
+        Value::Message(message) => {
+            let descriptor = message.descriptor();
+            if let Some(value_field) = wrapper_value_field(&descriptor) {
+                value_to_json(
+                    &message.get_field(&value_field),
+                    &value_field.kind(),
+                    column_type,
+                )?
+            } else if descriptor.full_name() == TIMESTAMP_TYPE {
+                let seconds = message
+                    .get_field_by_name("seconds")
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or_default();
+                let nanos = message
+                    .get_field_by_name("nanos")
+                    .and_then(|v| v.as_i32())
+                    .unwrap_or_default();
+                JsonValue::from(seconds * 1_000_000 + nanos as i64 / 1_000)
+            } else if descriptor.full_name() == DATE_TYPE {
+                let component = |name: &str| {
+                    message
+                        .get_field_by_name(name)
+                        .and_then(|v| v.as_i32())
+                        .unwrap_or_default()
+                };
+                let (year, month, day) = (component("year"), component("month"), component("day"));
+                let date = NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+                    .ok_or_else(|| format!("invalid date {year}-{month}-{day}"))?;
+                JsonValue::from(date.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE)
+            } else {
+                message_to_json(message, column_type.fields.as_deref().unwrap_or_default())?
+            }
+        }
+        Value::List(_) | Value::Map(_) => {
+            return Err("nested repeated and map fields are not supported".to_string())
+        }
+    })
+}
+
+/// Converts a JSON object with the specified SQL fields to a Protobuf
+/// message.
+///
+/// `null` values leave the corresponding fields unset.
+pub(super) fn json_to_message(
+    record: &JsonValue,
+    descriptor: &MessageDescriptor,
+    fields: &[Field],
+) -> Result<DynamicMessage, String> {
+    let mut message = DynamicMessage::new(descriptor.clone());
+
+    for field in fields {
+        let Some(proto_field) = lookup_field(descriptor, field) else {
+            continue;
+        };
+        let name = field.name.name();
+        match record.get(&name) {
+            None | Some(JsonValue::Null) => (),
+            Some(value) => {
+                let value = json_to_field(value, &proto_field, &field.columntype)
+                    .map_err(|e| format!("error converting column '{name}': {e}"))?;
+                message.set_field(&proto_field, value);
+            }
+        }
+    }
+
+    Ok(message)
+}
+
+fn json_to_field(
+    value: &JsonValue,
+    field: &FieldDescriptor,
+    column_type: &ColumnType,
+) -> Result<Value, String> {
+    if field.is_list() {
+        let component = column_type.component.as_deref().unwrap();
+        let items = value
+            .as_array()
+            .ok_or_else(|| format!("expected an array, found {value}"))?;
+        items
+            .iter()
+            .map(|item| {
+                if item.is_null() {
+                    Err("repeated fields cannot contain NULL values".to_string())
+                } else {
+                    json_to_value(item, &field.kind(), component)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::List)
+    } else if field.is_map() {
+        let entry = field.kind();
+        let entry = entry.as_message().unwrap();
+        let key_kind = entry.map_entry_key_field().kind();
+        let value_kind = entry.map_entry_value_field().kind();
+        let value_type = column_type.value.as_deref().unwrap();
+        let entries = value
+            .as_object()
+            .ok_or_else(|| format!("expected a map, found {value}"))?;
+        entries
+            .iter()
+            .map(|(key, value)| {
+                if value.is_null() {
+                    return Err("map fields cannot contain NULL values".to_string());
+                }
+                Ok((
+                    string_to_map_key(key, &key_kind)?,
+                    json_to_value(value, &value_kind, value_type)?,
+                ))
+            })
+            .collect::<Result<HashMap<_, _>, _>>()
+            .map(Value::Map)
+    } else {
+        json_to_value(value, &field.kind(), column_type)
+    }
+}
+
+fn string_to_map_key(key: &str, kind: &Kind) -> Result<MapKey, String> {
+    let error = || {
+        format!(
+            "invalid map key '{key}' for Protobuf type {}",
+            kind_name(kind)
+        )
+    };
+    Ok(match kind {
+        Kind::Bool => MapKey::Bool(key.parse().map_err(|_| error())?),
+        Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => {
+            MapKey::I32(key.parse().map_err(|_| error())?)
+        }
+        Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => {
+            MapKey::I64(key.parse().map_err(|_| error())?)
+        }
+        Kind::Uint32 | Kind::Fixed32 => MapKey::U32(key.parse().map_err(|_| error())?),
+        Kind::Uint64 | Kind::Fixed64 => MapKey::U64(key.parse().map_err(|_| error())?),
+        _ => MapKey::String(key.to_string()),
+    })
+}
+
+/// Extracts a number from a JSON value, accepting decimals encoded as strings.
+fn json_to_f64(value: &JsonValue) -> Result<f64, String> {
+    match value {
+        JsonValue::Number(n) => n.as_f64(),
+        JsonValue::String(s) => s.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| format!("expected a number, found {value}"))
+}
+
+fn json_to_i64(value: &JsonValue) -> Result<i64, String> {
+    match value {
+        JsonValue::Number(n) => n.as_i64(),
+        JsonValue::String(s) => s.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| format!("expected a signed integer, found {value}"))
+}
+
+fn json_to_u64(value: &JsonValue) -> Result<u64, String> {
+    match value {
+        JsonValue::Number(n) => n.as_u64(),
+        JsonValue::String(s) => s.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| format!("expected an unsigned integer, found {value}"))
+}
+
+fn out_of_range(value: &JsonValue, kind: &Kind) -> String {
+    format!(
+        "value {value} is out of range for Protobuf type {}",
+        kind_name(kind)
+    )
+}
+
+fn json_to_value(
+    value: &JsonValue,
+    kind: &Kind,
+    column_type: &ColumnType,
+) -> Result<Value, String> {
+    Ok(match kind {
+        Kind::Bool => Value::Bool(
+            value
+                .as_bool()
+                .ok_or_else(|| format!("expected a Boolean, found {value}"))?,
+        ),
+        Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => Value::I32(
+            json_to_i64(value)?
+                .try_into()
+                .map_err(|_| out_of_range(value, kind))?,
+        ),
+        Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => Value::I64(json_to_i64(value)?),
+        Kind::Uint32 | Kind::Fixed32 => Value::U32(
+            json_to_u64(value)?
+                .try_into()
+                .map_err(|_| out_of_range(value, kind))?,
+        ),
+        Kind::Uint64 | Kind::Fixed64 => Value::U64(json_to_u64(value)?),
+        Kind::Float => Value::F32(json_to_f64(value)? as f32),
+        Kind::Double => Value::F64(json_to_f64(value)?),
+        Kind::String => match value {
+            JsonValue::String(s) => Value::String(s.clone()),
+            value => Value::String(value.to_string()),
+        },
+        Kind::Bytes => {
+            let bytes = value
+                .as_array()
+                .ok_or_else(|| format!("expected a byte array, found {value}"))?
+                .iter()
+                .map(|b| {
+                    b.as_u64()
+                        .and_then(|b| u8::try_from(b).ok())
+                        .ok_or_else(|| format!("expected a byte, found {b}"))
+                })
+                .collect::<Result<Vec<u8>, _>>()?;
+            Value::Bytes(bytes.into())
+        }
+        Kind::Enum(enum_type) => match value {
+            JsonValue::String(name) => Value::EnumNumber(
+                enum_type
+                    .get_value_by_name(name)
+                    .ok_or_else(|| {
+                        format!(
+                            "'{name}' is not a valid value of enum '{}'",
+                            enum_type.full_name()
+                        )
+                    })?
+                    .number(),
+            ),
+            value => Value::EnumNumber(
+                json_to_i64(value)?
+                    .try_into()
+                    .map_err(|_| out_of_range(value, kind))?,
+            ),
+        },
+        Kind::Message(descriptor) => {
+            let mut message = DynamicMessage::new(descriptor.clone());
+            if let Some(value_field) = wrapper_value_field(descriptor) {
+                message.set_field(
+                    &value_field,
+                    json_to_value(value, &value_field.kind(), column_type)?,
+                );
+            } else if descriptor.full_name() == TIMESTAMP_TYPE {
+                let micros = json_to_i64(value)?;
+                message.set_field_by_name("seconds", Value::I64(micros.div_euclid(1_000_000)));
+                message.set_field_by_name(
+                    "nanos",
+                    Value::I32((micros.rem_euclid(1_000_000) * 1_000) as i32),
+                );
+            } else if descriptor.full_name() == DATE_TYPE {
+                let days = json_to_i64(value)?;
+                let date = i32::try_from(days)
+                    .ok()
+                    .and_then(|days| days.checked_add(UNIX_EPOCH_DAYS_FROM_CE))
+                    .and_then(NaiveDate::from_num_days_from_ce_opt)
+                    .ok_or_else(|| out_of_range(value, kind))?;
+                message.set_field_by_name("year", Value::I32(date.year()));
+                message.set_field_by_name("month", Value::I32(date.month() as i32));
+                message.set_field_by_name("day", Value::I32(date.day() as i32));
+            } else {
+                message = json_to_message(
+                    value,
+                    descriptor,
+                    column_type.fields.as_deref().unwrap_or_default(),
+                )?;
+            }
+            Value::Message(message)
+        }
+    })
+}
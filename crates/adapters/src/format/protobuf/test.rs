@@ -0,0 +1,284 @@
+use super::{message_descriptor, output::ProtobufEncoder};
+use crate::{
+    static_compile::seroutput::SerBatchImpl,
+    test::{mock_parser_pipeline, MockOutputConsumer, MockUpdate, TestStruct},
+    Encoder, FormatConfig, SerBatch,
+};
+use dbsp::{utils::Tup2, OrdZSet};
+use feldera_types::format::protobuf::{
+    ProtobufEncoderConfig, ProtobufParserConfig, ProtobufSchemaConfig, ProtobufUpdateFormat,
+};
+use prost::Message;
+use prost_reflect::{DynamicMessage, MessageDescriptor, Value};
+use std::{borrow::Cow, sync::Arc};
+
+const TEST_PROTO: &str = r#"
+syntax = "proto3";
+
+package test;
+
+message TestStruct {
+    int64 id = 1;
+    bool b = 2;
+    optional int64 i = 3;
+    string s = 4;
+}
+
+message Update {
+    TestStruct insert = 1;
+    TestStruct delete = 2;
+}
+"#;
+
+fn schema_config(message: &str) -> ProtobufSchemaConfig {
+    ProtobufSchemaConfig {
+        descriptor_set: None,
+        proto: Some(TEST_PROTO.to_string()),
+        message: message.to_string(),
+    }
+}
+
+fn record_descriptor() -> MessageDescriptor {
+    message_descriptor(&schema_config("test.TestStruct")).unwrap()
+}
+
+fn to_message(descriptor: &MessageDescriptor, val: &TestStruct) -> DynamicMessage {
+    let mut message = DynamicMessage::new(descriptor.clone());
+    message.set_field_by_name("id", Value::I64(val.id as i64));
+    message.set_field_by_name("b", Value::Bool(val.b));
+    if let Some(i) = val.i {
+        message.set_field_by_name("i", Value::I64(i));
+    }
+    message.set_field_by_name("s", Value::String(val.s.clone()));
+    message
+}
+
+fn from_message(message: &DynamicMessage) -> TestStruct {
+    TestStruct {
+        id: message.get_field_by_name("id").unwrap().as_i64().unwrap() as u32,
+        b: message.get_field_by_name("b").unwrap().as_bool().unwrap(),
+        i: message
+            .has_field_by_name("i")
+            .then(|| message.get_field_by_name("i").unwrap().as_i64().unwrap()),
+        s: message
+            .get_field_by_name("s")
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .to_string(),
+    }
+}
+
+fn test_data() -> (TestStruct, TestStruct) {
+    (
+        TestStruct {
+            id: 1,
+            b: true,
+            i: None,
+            s: "foo".to_string(),
+        },
+        TestStruct {
+            id: 2,
+            b: false,
+            i: Some(5),
+            s: "bar".to_string(),
+        },
+    )
+}
+
+#[test]
+fn test_protobuf_parser() {
+    let (v1, v2) = test_data();
+    let descriptor = record_descriptor();
+
+    let format_config = FormatConfig {
+        name: Cow::from("protobuf"),
+        config: serde_yaml::to_value(ProtobufParserConfig {
+            update_format: ProtobufUpdateFormat::Raw,
+            schema: schema_config("test.TestStruct"),
+        })
+        .unwrap(),
+    };
+
+    let (consumer, mut parser, outputs) =
+        mock_parser_pipeline(&TestStruct::relation_schema(), &format_config).unwrap();
+    consumer.on_error(Some(Box::new(|_, _| {})));
+
+    for val in [&v1, &v2] {
+        let (mut buffer, errors) = parser.parse(&to_message(&descriptor, val).encode_to_vec());
+        assert_eq!(errors, vec![]);
+        buffer.flush();
+    }
+
+    let (_buffer, errors) = parser.parse(b"\xff\xff\xff");
+    assert_eq!(errors.len(), 1);
+
+    assert_eq!(
+        outputs.state().flushed,
+        vec![MockUpdate::Insert(v1), MockUpdate::Insert(v2)]
+    );
+}
+
+#[test]
+fn test_protobuf_insert_delete_parser() {
+    let (v1, v2) = test_data();
+    let descriptor = record_descriptor();
+    let update_descriptor = message_descriptor(&schema_config("test.Update")).unwrap();
+
+    let format_config = FormatConfig {
+        name: Cow::from("protobuf"),
+        config: serde_yaml::to_value(ProtobufParserConfig {
+            update_format: ProtobufUpdateFormat::InsertDelete,
+            schema: schema_config("test.Update"),
+        })
+        .unwrap(),
+    };
+
+    let (consumer, mut parser, outputs) =
+        mock_parser_pipeline(&TestStruct::relation_schema(), &format_config).unwrap();
+    consumer.on_error(Some(Box::new(|_, _| {})));
+
+    let mut update = DynamicMessage::new(update_descriptor.clone());
+    update.set_field_by_name("insert", Value::Message(to_message(&descriptor, &v1)));
+    let (mut buffer, errors) = parser.parse(&update.encode_to_vec());
+    assert_eq!(errors, vec![]);
+    buffer.flush();
+
+    let mut update = DynamicMessage::new(update_descriptor.clone());
+    update.set_field_by_name("delete", Value::Message(to_message(&descriptor, &v1)));
+    update.set_field_by_name("insert", Value::Message(to_message(&descriptor, &v2)));
+    let (mut buffer, errors) = parser.parse(&update.encode_to_vec());
+    assert_eq!(errors, vec![]);
+    buffer.flush();
+
+    // Neither insert nor delete.
+    let (_buffer, errors) = parser.parse(&DynamicMessage::new(update_descriptor).encode_to_vec());
+    assert_eq!(errors.len(), 1);
+
+    assert_eq!(
+        outputs.state().flushed,
+        vec![
+            MockUpdate::Insert(v1.clone()),
+            MockUpdate::Delete(v1),
+            MockUpdate::Insert(v2)
+        ]
+    );
+}
+
+#[test]
+fn test_protobuf_schema_validation() {
+    // Non-nullable column `s` has no matching field.
+    let config = ProtobufEncoderConfig {
+        update_format: ProtobufUpdateFormat::Raw,
+        schema: ProtobufSchemaConfig {
+            descriptor_set: None,
+            proto: Some(
+                "syntax = \"proto3\"; message T { int64 id = 1; bool b = 2; int64 i = 3; }"
+                    .to_string(),
+            ),
+            message: "T".to_string(),
+        },
+    };
+    assert!(ProtobufEncoder::create(
+        "test",
+        &TestStruct::relation_schema(),
+        Box::new(MockOutputConsumer::new()),
+        &config,
+    )
+    .is_err());
+
+    // `insert_delete` requires an envelope type.
+    let config = ProtobufEncoderConfig {
+        update_format: ProtobufUpdateFormat::InsertDelete,
+        schema: schema_config("test.TestStruct"),
+    };
+    assert!(ProtobufEncoder::create(
+        "test",
+        &TestStruct::relation_schema(),
+        Box::new(MockOutputConsumer::new()),
+        &config,
+    )
+    .is_err());
+}
+
+type ConsumerData = Vec<(
+    Option<Vec<u8>>,
+    Option<Vec<u8>>,
+    Vec<(String, Option<Vec<u8>>)>,
+)>;
+
+fn run_encoder(update_format: ProtobufUpdateFormat, message: &str) -> ConsumerData {
+    let (v1, v2) = test_data();
+    let config = ProtobufEncoderConfig {
+        update_format,
+        schema: schema_config(message),
+    };
+    let consumer = MockOutputConsumer::new();
+    let consumer_data = consumer.data.clone();
+    let mut encoder = ProtobufEncoder::create(
+        "protobuf_test_endpoint",
+        &TestStruct::relation_schema(),
+        Box::new(consumer),
+        &config,
+    )
+    .unwrap();
+
+    let zset = OrdZSet::from_keys((), vec![Tup2(v1, 1), Tup2(v2, -1)]);
+    let zset = Arc::new(<SerBatchImpl<_, TestStruct, ()>>::new(zset)) as Arc<dyn SerBatch>;
+
+    encoder.consumer().batch_start(0);
+    encoder.encode(zset.as_batch_reader()).unwrap();
+    encoder.consumer().batch_end();
+
+    let data = consumer_data.lock().unwrap().clone();
+    data
+}
+
+#[test]
+fn test_protobuf_encoder() {
+    let (v1, v2) = test_data();
+    let descriptor = record_descriptor();
+    let actual_output = run_encoder(ProtobufUpdateFormat::Raw, "test.TestStruct")
+        .iter()
+        .map(|(_k, v, headers)| {
+            let message =
+                DynamicMessage::decode(descriptor.clone(), v.as_deref().unwrap()).unwrap();
+            (
+                from_message(&message),
+                String::from_utf8(headers[0].1.clone().unwrap()).unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        actual_output,
+        vec![(v2, "delete".to_string()), (v1, "insert".to_string())]
+    );
+}
+
+#[test]
+fn test_protobuf_insert_delete_encoder() {
+    let (v1, v2) = test_data();
+    let update_descriptor = message_descriptor(&schema_config("test.Update")).unwrap();
+    let actual_output = run_encoder(ProtobufUpdateFormat::InsertDelete, "test.Update")
+        .iter()
+        .map(|(_k, v, _headers)| {
+            let update =
+                DynamicMessage::decode(update_descriptor.clone(), v.as_deref().unwrap()).unwrap();
+            let record = |name: &str| {
+                update.has_field_by_name(name).then(|| {
+                    from_message(
+                        update
+                            .get_field_by_name(name)
+                            .unwrap()
+                            .as_message()
+                            .unwrap(),
+                    )
+                })
+            };
+            (record("insert"), record("delete"))
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(actual_output, vec![(None, Some(v2)), (Some(v1), None)]);
+}
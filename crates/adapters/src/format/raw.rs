@@ -1,16 +1,27 @@
 use crate::{
-    catalog::{DeCollectionStream, RecordFormat},
-    format::{InputFormat, ParseError, Parser},
-    ControllerError,
+    catalog::{CursorWithPolarity, DeCollectionStream, RecordFormat, SerBatchReader},
+    format::{message_fields::MessageFields, InputFormat, ParseError, Parser, MAX_DUPLICATES},
+    ControllerError, Encoder, OutputConsumer, OutputFormat, SerCursor,
 };
 use actix_web::HttpRequest;
+use anyhow::{bail, Result as AnyResult};
 use core::str;
 use erased_serde::Serialize as ErasedSerialize;
 use feldera_types::{
-    format::raw::{RawParserConfig, RawParserMode},
+    config::{ConnectorConfig, TransportConfig},
+    format::raw::{
+        RawDeletePolicy, RawEncoderConfig, RawEncoderMode, RawParserConfig, RawParserMode,
+    },
+    program_schema::{Relation, SqlType},
     serde_with_context::{serde_config::BinaryFormat, SqlSerdeConfig},
 };
-use serde::{de::Error as _, de::SeqAccess, forward_to_deserialize_any, Deserialize, Deserializer};
+use serde::{
+    de::Error as _,
+    de::SeqAccess,
+    forward_to_deserialize_any,
+    ser::{Error as _, Impossible, SerializeStruct},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 use serde_urlencoded::Deserializer as UrlDeserializer;
 use std::{borrow::Cow, fmt::Display};
 
@@ -283,10 +294,404 @@ impl<'de> Deserializer<'de> for RawDeserializer<'de> {
     }
 }
 
+/// Serializer that writes the value of the only column of a record to a byte
+/// buffer verbatim.
+///
+/// Only supports string and binary values.  `NULL` values are serialized as
+/// empty byte strings.
+pub(crate) struct RawRecordSerializer<'a> {
+    buf: &'a mut Vec<u8>,
+
+    /// `true` when serializing the column value, `false` when serializing the
+    /// record.
+    in_record: bool,
+}
+
+impl<'a> RawRecordSerializer<'a> {
+    pub(crate) fn new(buf: &'a mut Vec<u8>) -> Self {
+        Self {
+            buf,
+            in_record: false,
+        }
+    }
+
+    fn write(self, bytes: &[u8]) -> Result<(), RawSerializeError> {
+        if !self.in_record {
+            return Err(RawSerializeError::custom("expected a record"));
+        }
+        self.buf.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct RawSerializeError {
+    message: String,
+}
+
+impl Display for RawSerializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.message.fmt(f)
+    }
+}
+
+impl std::error::Error for RawSerializeError {}
+
+impl serde::ser::Error for RawSerializeError {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        Self {
+            message: msg.to_string(),
+        }
+    }
+}
+
+fn unsupported_type() -> RawSerializeError {
+    RawSerializeError::custom(
+        "'raw' output format only supports columns of type 'VARCHAR' or 'VARBINARY'",
+    )
+}
+
+macro_rules! unsupported_types {
+    ($($method:ident($($arg:ty),*) -> $ret:ty;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ret, Self::Error> {
+                Err(unsupported_type())
+            }
+        )*
+    };
+}
+
+impl<'a> Serializer for RawRecordSerializer<'a> {
+    type Ok = ();
+    type Error = RawSerializeError;
+    type SerializeSeq = Impossible<(), RawSerializeError>;
+    type SerializeTuple = Impossible<(), RawSerializeError>;
+    type SerializeTupleStruct = Impossible<(), RawSerializeError>;
+    type SerializeTupleVariant = Impossible<(), RawSerializeError>;
+    type SerializeMap = Impossible<(), RawSerializeError>;
+    type SerializeStruct = RawStructSerializer<'a>;
+    type SerializeStructVariant = Impossible<(), RawSerializeError>;
+
+    unsupported_types! {
+        serialize_bool(bool) -> ();
+        serialize_i8(i8) -> ();
+        serialize_i16(i16) -> ();
+        serialize_i32(i32) -> ();
+        serialize_i64(i64) -> ();
+        serialize_u8(u8) -> ();
+        serialize_u16(u16) -> ();
+        serialize_u32(u32) -> ();
+        serialize_u64(u64) -> ();
+        serialize_f32(f32) -> ();
+        serialize_f64(f64) -> ();
+        serialize_unit_struct(&'static str) -> ();
+        serialize_unit_variant(&'static str, u32, &'static str) -> ();
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Self::Error> {
+        self.write(v.encode_utf8(&mut [0; 4]).as_bytes())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Self::Error> {
+        self.write(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Self::Error> {
+        self.write(v)
+    }
+
+    fn serialize_none(self) -> Result<(), Self::Error> {
+        self.write(&[])
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Self::Error> {
+        self.write(&[])
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(unsupported_type())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        if self.in_record {
+            return Err(unsupported_type());
+        }
+        Ok(RawStructSerializer {
+            buf: self.buf,
+            num_columns: 0,
+        })
+    }
+}
+
+pub(crate) struct RawStructSerializer<'a> {
+    buf: &'a mut Vec<u8>,
+    num_columns: usize,
+}
+
+impl SerializeStruct for RawStructSerializer<'_> {
+    type Ok = ();
+    type Error = RawSerializeError;
+
+    fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.num_columns > 0 {
+            return Err(RawSerializeError::custom(
+                "'raw' output format can only be used with views that have a single column",
+            ));
+        }
+        self.num_columns += 1;
+        value.serialize(RawRecordSerializer {
+            buf: self.buf,
+            in_record: true,
+        })
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Raw format encoder.
+///
+/// Outputs the value of the only column of a view of type VARCHAR or VARBINARY
+/// verbatim.
+pub struct RawOutputFormat;
+
+impl OutputFormat for RawOutputFormat {
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("raw")
+    }
+
+    fn config_from_http_request(
+        &self,
+        endpoint_name: &str,
+        request: &HttpRequest,
+    ) -> Result<Box<dyn ErasedSerialize>, ControllerError> {
+        Ok(Box::new(
+            RawEncoderConfig::deserialize(UrlDeserializer::new(form_urlencoded::parse(
+                request.query_string().as_bytes(),
+            )))
+            .map_err(|e| {
+                ControllerError::encoder_config_parse_error(
+                    endpoint_name,
+                    &e,
+                    request.query_string(),
+                )
+            })?,
+        ))
+    }
+
+    fn new_encoder(
+        &self,
+        endpoint_name: &str,
+        config: &ConnectorConfig,
+        key_schema: &Option<Relation>,
+        value_schema: &Relation,
+        consumer: Box<dyn OutputConsumer>,
+    ) -> Result<Box<dyn Encoder>, ControllerError> {
+        let raw_config = RawEncoderConfig::deserialize(&config.format.as_ref().unwrap().config)
+            .map_err(|e| {
+                ControllerError::encoder_config_parse_error(
+                    endpoint_name,
+                    &e,
+                    &serde_yaml::to_string(config).unwrap_or_default(),
+                )
+            })?;
+
+        if key_schema.is_some() {
+            return Err(ControllerError::invalid_encoder_configuration(
+                endpoint_name,
+                "'raw' output format cannot be attached to an index",
+            ));
+        }
+
+        if MessageFields::from_transport(&config.transport).is_some() {
+            return Err(ControllerError::invalid_encoder_configuration(
+                endpoint_name,
+                "'raw' output format does not support computing message keys, headers, or topics from record columns",
+            ));
+        }
+
+        let num_columns = value_schema.fields.len();
+        if num_columns != 1 {
+            return Err(ControllerError::invalid_encoder_configuration(
+                endpoint_name,
+                &format!("'raw' output format can only be used with views that have a single column of type 'VARCHAR' or 'VARBINARY', but view '{}' has {num_columns} columns", value_schema.name),
+            ));
+        }
+
+        let typ = value_schema.fields[0].columntype.typ;
+        if !typ.is_string() && !matches!(typ, SqlType::Binary | SqlType::Varbinary) {
+            return Err(ControllerError::invalid_encoder_configuration(
+                endpoint_name,
+                &format!("'raw' output format can only be used with views that have a single column of type 'VARCHAR' or 'VARBINARY', but view '{}' has a column of type {typ}", value_schema.name),
+            ));
+        }
+
+        if raw_config.buffer_size_records == 0 {
+            return Err(ControllerError::invalid_encoder_configuration(
+                endpoint_name,
+                "'buffer_size_records' must be greater than 0",
+            ));
+        }
+
+        let mode = raw_config.mode.clone().unwrap_or(match &config.transport {
+            TransportConfig::KafkaOutput(_)
+            | TransportConfig::PubSubOutput(_)
+            | TransportConfig::MqttOutput(_)
+            | TransportConfig::NatsOutput(_) => RawEncoderMode::Blob,
+            _ => RawEncoderMode::Lines,
+        });
+
+        Ok(Box::new(RawEncoder::new(consumer, raw_config, mode)))
+    }
+}
+
+pub(crate) struct RawEncoder {
+    /// Consumer to push serialized data to.
+    output_consumer: Box<dyn OutputConsumer>,
+    config: RawEncoderConfig,
+    mode: RawEncoderMode,
+    buffer: Vec<u8>,
+}
+
+impl RawEncoder {
+    pub(crate) fn new(
+        output_consumer: Box<dyn OutputConsumer>,
+        config: RawEncoderConfig,
+        mode: RawEncoderMode,
+    ) -> Self {
+        Self {
+            output_consumer,
+            config,
+            mode,
+            buffer: Vec::new(),
+        }
+    }
+}
+
+impl Encoder for RawEncoder {
+    fn consumer(&mut self) -> &mut dyn OutputConsumer {
+        self.output_consumer.as_mut()
+    }
+
+    fn encode(&mut self, batch: &dyn SerBatchReader) -> AnyResult<()> {
+        let mut cursor = CursorWithPolarity::new(batch.cursor(RecordFormat::Raw)?);
+        let mut num_records = 0;
+
+        self.buffer.clear();
+
+        while cursor.key_valid() {
+            if !cursor.val_valid() {
+                cursor.step_key();
+                continue;
+            }
+
+            let mut w = cursor.weight();
+            if !(-MAX_DUPLICATES..=MAX_DUPLICATES).contains(&w) {
+                bail!(
+                    "Unable to output record with very large weight {w}. Consider adjusting your SQL queries to avoid duplicate output records, e.g., using 'SELECT DISTINCT'."
+                );
+            }
+
+            if w < 0 && self.config.delete_policy == RawDeletePolicy::Skip {
+                cursor.step_key();
+                continue;
+            }
+
+            while w != 0 {
+                if w < 0 {
+                    self.buffer
+                        .extend_from_slice(self.config.delete_marker.as_bytes());
+                }
+                cursor.serialize_key(&mut self.buffer)?;
+
+                match self.mode {
+                    RawEncoderMode::Blob => {
+                        let op: &[u8] = if w > 0 { b"insert" } else { b"delete" };
+                        self.output_consumer.push_key(
+                            None,
+                            Some(&self.buffer),
+                            &[("op", Some(op))],
+                            1,
+                        );
+                        self.buffer.clear();
+                    }
+                    RawEncoderMode::Lines => {
+                        self.buffer.push(b'\n');
+                        num_records += 1;
+                        if num_records >= self.config.buffer_size_records {
+                            self.output_consumer.push_buffer(&self.buffer, num_records);
+                            self.buffer.clear();
+                            num_records = 0;
+                        }
+                    }
+                }
+
+                if w > 0 {
+                    w -= 1;
+                } else {
+                    w += 1;
+                }
+            }
+
+            cursor.step_key();
+        }
+
+        if num_records > 0 {
+            self.output_consumer.push_buffer(&self.buffer, num_records);
+            self.buffer.clear();
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::test::{mock_parser_pipeline, MockUpdate};
-    use crate::FormatConfig;
+    use super::RawEncoder;
+    use crate::static_compile::seroutput::SerBatchImpl;
+    use crate::test::{mock_parser_pipeline, MockOutputConsumer, MockUpdate};
+    use crate::{Encoder, FormatConfig, SerBatch};
+    use dbsp::{utils::Tup2, OrdZSet};
     use feldera_adapterlib::{
         format::{InputBuffer, ParseError, Parser},
         transport::InputConsumer,
@@ -294,13 +699,31 @@ mod test {
     use feldera_sqllib::{ByteArray, SqlString};
     use feldera_types::{
         deserialize_table_record,
-        format::raw::{RawParserConfig, RawParserMode},
+        format::raw::{
+            RawDeletePolicy, RawEncoderConfig, RawEncoderMode, RawParserConfig, RawParserMode,
+        },
         program_schema::{ColumnType, Field, Relation, SqlIdentifier},
         serde_with_context::{DeserializeWithContext, SqlSerdeConfig},
+        serialize_table_record,
     };
-    use std::{borrow::Cow, collections::BTreeMap, fmt::Debug, hash::Hash};
-
-    #[derive(Eq, PartialEq, Debug, Hash, Clone)]
+    use size_of::SizeOf;
+    use std::{borrow::Cow, collections::BTreeMap, fmt::Debug, hash::Hash, sync::Arc};
+
+    #[derive(
+        Debug,
+        Default,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Clone,
+        Hash,
+        SizeOf,
+        rkyv::Archive,
+        rkyv::Serialize,
+        rkyv::Deserialize,
+    )]
+    #[archive_attr(derive(Ord, Eq, PartialEq, PartialOrd))]
     struct Binary {
         data: ByteArray,
     }
@@ -321,6 +744,10 @@ mod test {
         (data, "data", false, ByteArray, None)
     });
 
+    serialize_table_record!(Binary[1]{
+        data["data"]: ByteArray
+    });
+
     #[derive(Eq, PartialEq, Debug, Hash, Clone)]
     struct OptBinary {
         data: Option<ByteArray>,
@@ -529,4 +956,82 @@ mod test {
         let test_cases = vec![test1];
         run_test_cases(test_cases);
     }
+
+    type ConsumerData = Vec<(
+        Option<Vec<u8>>,
+        Option<Vec<u8>>,
+        Vec<(String, Option<Vec<u8>>)>,
+    )>;
+
+    fn run_encoder(config: RawEncoderConfig, mode: RawEncoderMode) -> ConsumerData {
+        let consumer = MockOutputConsumer::new();
+        let consumer_data = consumer.data.clone();
+        let mut encoder = RawEncoder::new(Box::new(consumer), config, mode);
+
+        let record = |data: &[u8]| Binary { data: data.into() };
+        let zset = OrdZSet::from_keys(
+            (),
+            vec![
+                Tup2(record(b"foo"), 1),
+                Tup2(record(b"bar"), -1),
+                Tup2(record(b"baz"), 2),
+            ],
+        );
+        let zset = Arc::new(<SerBatchImpl<_, Binary, ()>>::new(zset)) as Arc<dyn SerBatch>;
+
+        encoder.consumer().batch_start(0);
+        encoder.encode(zset.as_batch_reader()).unwrap();
+        encoder.consumer().batch_end();
+
+        let data = consumer_data.lock().unwrap().clone();
+        data
+    }
+
+    #[test]
+    fn test_raw_encoder_lines() {
+        let output = run_encoder(
+            RawEncoderConfig {
+                buffer_size_records: 2,
+                ..Default::default()
+            },
+            RawEncoderMode::Lines,
+        );
+
+        let buffers = output
+            .into_iter()
+            .map(|(_k, v, _headers)| String::from_utf8(v.unwrap()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(buffers, vec!["baz\nbaz\n", "foo\n"]);
+    }
+
+    #[test]
+    fn test_raw_encoder_blob() {
+        let output = run_encoder(
+            RawEncoderConfig {
+                delete_policy: RawDeletePolicy::Marker,
+                delete_marker: "DELETE ".to_string(),
+                ..Default::default()
+            },
+            RawEncoderMode::Blob,
+        );
+
+        let messages = output
+            .into_iter()
+            .map(|(_k, v, headers)| {
+                (
+                    String::from_utf8(v.unwrap()).unwrap(),
+                    String::from_utf8(headers[0].1.clone().unwrap()).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                ("DELETE bar".to_string(), "delete".to_string()),
+                ("baz".to_string(), "insert".to_string()),
+                ("baz".to_string(), "insert".to_string()),
+                ("foo".to_string(), "insert".to_string()),
+            ]
+        );
+    }
 }
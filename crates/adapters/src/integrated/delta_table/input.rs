@@ -110,7 +110,7 @@ static MAX_CONCURRENT_READERS_SET: AtomicBool = AtomicBool::new(false);
 
 /// Takes a column name from a DeltaLake schema and returns a qouted string
 /// that can be used in datafusion queries like `select "foo""bar" from my_table`.
-fn quote_sql_identifier<S: AsRef<str>>(ident: S) -> String {
+pub(crate) fn quote_sql_identifier<S: AsRef<str>>(ident: S) -> String {
     format!("\"{}\"", ident.as_ref().replace("\"", "\"\""))
 }
 
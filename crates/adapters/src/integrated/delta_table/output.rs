@@ -3,8 +3,10 @@ use crate::controller::{ControllerInner, EndpointId};
 use crate::format::parquet::relation_to_arrow_fields;
 use crate::format::relation_to_parquet_schema;
 use crate::format::MAX_DUPLICATES;
+use crate::integrated::delta_table::input::quote_sql_identifier;
 use crate::integrated::delta_table::register_storage_handlers;
 use crate::transport::Step;
+use crate::util::{indexed_operation_type, IndexedOperationType};
 use crate::{
     AsyncErrorCallback, ControllerError, Encoder, OutputConsumer, OutputEndpoint, RecordFormat,
     SerCursor,
@@ -14,13 +16,17 @@ use arrow::array::RecordBatch;
 use arrow::datatypes::{DataType as ArrowDataType, Field as ArrowField, Schema as ArrowSchema};
 use chrono::Utc;
 use dbsp::circuit::tokio::TOKIO;
-use deltalake::kernel::{Action, DataType, StructField};
+use deltalake::datafusion::common::Column;
+use deltalake::datafusion::prelude::SessionContext;
+use deltalake::kernel::{Action, DataType, StructField, Transaction};
 use deltalake::operations::create::CreateBuilder;
 use deltalake::operations::transaction;
-use deltalake::operations::transaction::{CommitBuilder, TableReference};
+use deltalake::operations::transaction::{CommitBuilder, CommitProperties, TableReference};
 use deltalake::operations::writer::{DeltaWriter, WriterConfig};
 use deltalake::protocol::{DeltaOperation, SaveMode};
-use deltalake::DeltaTable;
+use deltalake::{DeltaOps, DeltaTable};
+use feldera_types::config::FtModel;
+use feldera_types::program_schema::SqlIdentifier;
 use feldera_types::serde_with_context::serde_config::{
     BinaryFormat, DecimalFormat, UuidFormat, VariantFormat,
 };
@@ -52,8 +58,20 @@ struct DeltaTableWriterInner {
     endpoint_id: EndpointId,
     endpoint_name: String,
     config: DeltaTableWriterConfig,
+    /// Application id under which the connector records the last step
+    /// committed to the table in the Delta log (as a `txn` action).  `None`
+    /// if the connector doesn't record steps, because it is not fault
+    /// tolerant.
+    app_id: Option<String>,
+    view_name: SqlIdentifier,
+    /// Name of the index used as the key in `merge` mode.
+    index_name: Option<SqlIdentifier>,
+    /// Columns of the index used as the key in `merge` mode.
+    key_columns: Vec<String>,
+    /// Schema of the record batches produced by the encoder.
     serde_arrow_schema: SerdeArrowSchema,
     arrow_schema: Arc<ArrowSchema>,
+    /// Columns of the delta table.
     struct_fields: Vec<StructField>,
     controller: Weak<ControllerInner>,
 }
@@ -70,7 +88,7 @@ static CHUNK_SIZE: usize = 1_000_000;
 /// Commands sent to the tokio runtime that performs the actual
 /// delta table operations.
 enum Command {
-    BatchStart,
+    BatchStart(Step),
     Insert(RecordBatch),
     BatchEnd,
 }
@@ -84,18 +102,31 @@ impl DeltaTableWriter {
         schema: &Relation,
         controller: Weak<ControllerInner>,
     ) -> Result<Self, ControllerError> {
-        if key_schema.is_some() {
+        let merge = config.mode == DeltaTableWriteMode::Merge;
+
+        if key_schema.is_some() && !merge {
             return Err(ControllerError::not_supported(
-                "delta table output doesn't support indexes yet",
+                "delta table output only supports indexes in the 'merge' mode",
+            ));
+        }
+
+        if key_schema.is_none() && merge {
+            return Err(ControllerError::invalid_transport_configuration(
+                endpoint_name,
+                "the 'merge' mode requires the connector to be configured with an 'index'",
             ));
         }
 
         register_storage_handlers();
 
-        // Create arrow schema
-        let mut arrow_fields = relation_to_arrow_fields(&schema.fields, true);
+        // Create arrow schema.  In the `merge` mode, the metadata columns are
+        // only used to tell the worker how to apply each record; they are not
+        // part of the table.
+        let view_fields = relation_to_arrow_fields(&schema.fields, true);
+        let mut arrow_fields = view_fields.clone();
         arrow_fields.push(ArrowField::new("__feldera_op", ArrowDataType::Utf8, true));
         arrow_fields.push(ArrowField::new("__feldera_ts", ArrowDataType::Int64, true));
+        let table_fields = if merge { &view_fields } else { &arrow_fields };
 
         // Create serde arrow schema.
         let serde_arrow_schema =
@@ -105,11 +136,9 @@ impl DeltaTableWriter {
                 }
             })?;
 
-        let arrow_schema = Arc::new(ArrowSchema::new(arrow_fields));
-
         let mut struct_fields: Vec<_> = vec![];
 
-        for f in arrow_schema.fields.iter() {
+        for f in table_fields.iter() {
             let data_type = DataType::try_from(f.data_type()).map_err(|e| {
                 ControllerError::output_transport_error(
                     endpoint_name,
@@ -120,10 +149,59 @@ impl DeltaTableWriter {
             struct_fields.push(StructField::new(f.name(), data_type, f.is_nullable()));
         }
 
+        let arrow_schema = Arc::new(ArrowSchema::new(arrow_fields));
+
+        let key_columns = key_schema
+            .as_ref()
+            .map(|key_schema| {
+                relation_to_arrow_fields(&key_schema.fields, true)
+                    .iter()
+                    .map(|f| f.name().clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // A fault-tolerant pipeline replays steps after a restart, so in the
+        // `append` and `merge` modes we tag each commit with the step under
+        // an app ID, to recognize the steps that are already in the table.
+        // Other pipelines may write to the same table, so the app ID includes
+        // the pipeline name, which is unique.
+        let fault_tolerant_mode = matches!(
+            config.mode,
+            DeltaTableWriteMode::Append | DeltaTableWriteMode::Merge
+        );
+        let app_id = match controller.upgrade() {
+            Some(controller) => {
+                let pipeline_config = &controller.status.pipeline_config;
+                if fault_tolerant_mode
+                    && pipeline_config.global.fault_tolerance.model == Some(FtModel::ExactlyOnce)
+                {
+                    let Some(pipeline_name) = &pipeline_config.name else {
+                        return Err(ControllerError::invalid_transport_configuration(
+                            endpoint_name,
+                            "fault-tolerant output to Delta Lake requires the pipeline to have a name, which the connector uses to identify its transactions in the Delta log",
+                        ));
+                    };
+                    Some(format!("feldera-{pipeline_name}-{endpoint_name}"))
+                } else {
+                    None
+                }
+            }
+            // The writer is not part of a pipeline, which only happens in
+            // tests.
+            None => Some(format!("feldera-{endpoint_name}")),
+        };
+
         let inner = Arc::new(DeltaTableWriterInner {
             endpoint_id,
             endpoint_name: endpoint_name.to_string(),
             config: config.clone(),
+            app_id,
+            view_name: schema.name.clone(),
+            index_name: key_schema
+                .as_ref()
+                .map(|key_schema| key_schema.name.clone()),
+            key_columns,
             serde_arrow_schema,
             arrow_schema,
             struct_fields,
@@ -204,8 +282,8 @@ impl DeltaTableWriter {
 
         loop {
             match command_receiver.recv().await {
-                Some(Command::BatchStart) => {
-                    task.batch_start().await;
+                Some(Command::BatchStart(step)) => {
+                    task.batch_start(step).await;
                     // Ignore closed channel, we'll handle it at the next loop iteration.
                     let _ = response_sender.send(Ok(())).await;
                 }
@@ -242,6 +320,18 @@ struct WriterTask {
     delta_table: DeltaTable,
     writer: Option<DeltaWriter>,
     num_rows: usize,
+
+    /// Updates buffered in the `merge` mode until the end of the batch,
+    /// when they are applied to the table with a single `MERGE` operation.
+    merge_batches: Vec<RecordBatch>,
+
+    /// The step being written.
+    step: Step,
+
+    /// The first step that hasn't been committed to the table yet.  Steps
+    /// before it were committed in a previous run and are skipped when the
+    /// pipeline replays them after a restart.
+    next_step: Step,
 }
 
 impl WriterTask {
@@ -259,7 +349,7 @@ impl WriterTask {
         let save_mode = match inner.config.mode {
             // I expected `SaveMode::Append` to be the correct setting, but
             // that always returns an error.
-            DeltaTableWriteMode::Append => SaveMode::Ignore,
+            DeltaTableWriteMode::Append | DeltaTableWriteMode::Merge => SaveMode::Ignore,
             DeltaTableWriteMode::Truncate => SaveMode::Overwrite,
             DeltaTableWriteMode::ErrorIfExists => SaveMode::ErrorIfExists,
         };
@@ -289,20 +379,57 @@ impl WriterTask {
             delta_table.version()
         );
 
+        // In `truncate` mode, we've just discarded the contents of the table,
+        // including the output of any previous run, so we start from scratch.
+        let next_step = match &inner.app_id {
+            Some(app_id) if inner.config.mode != DeltaTableWriteMode::Truncate => delta_table
+                .get_app_transaction_version()
+                .get(app_id)
+                .map_or(0, |txn| txn.version as Step + 1),
+            _ => 0,
+        };
+        if next_step > 0 {
+            info!(
+                "delta_table {}: delta table '{}' contains output up to step {}",
+                &inner.endpoint_name,
+                &inner.config.uri,
+                next_step - 1
+            );
+        }
+
         Ok(Self {
             inner,
             delta_table,
             writer: None,
             num_rows: 0,
+            merge_batches: Vec::new(),
+            step: 0,
+            next_step,
         })
     }
-    async fn batch_start(&mut self) {
+
+    async fn batch_start(&mut self, step: Step) {
         trace!(
-            "delta_table {}: starting a new output batch",
+            "delta_table {}: starting a new output batch for step {step}",
             &self.inner.endpoint_name,
         );
 
         self.num_rows = 0;
+        self.step = step;
+        self.merge_batches.clear();
+
+        if step < self.next_step {
+            debug!(
+                "delta_table {}: skipping step {step} that was already committed to the table in a previous run",
+                &self.inner.endpoint_name,
+            );
+            self.writer = None;
+            return;
+        }
+
+        if self.inner.config.mode == DeltaTableWriteMode::Merge {
+            return;
+        }
 
         // TODO: make target_file_size configurable.
         // TODO: configure WriterProperties, e.g., do we want to set WriterProperties::sorting_columns?
@@ -322,12 +449,27 @@ impl WriterTask {
         ));
     }
 
+    /// Returns the `txn` action that records the current step, if the
+    /// connector records steps.
+    fn txn(&self) -> Option<Transaction> {
+        self.inner
+            .app_id
+            .as_ref()
+            .map(|app_id| Transaction::new(app_id, self.step as i64))
+    }
+
     async fn batch_end(&mut self) -> AnyResult<()> {
         trace!(
             "delta_table {}: finished writing output records, committing (current table version: {})",
             &self.inner.endpoint_name,
             self.delta_table.version()
         );
+        if self.step < self.next_step {
+            return Ok(());
+        }
+        if self.inner.config.mode == DeltaTableWriteMode::Merge {
+            return self.merge().await;
+        }
         if let Some(writer) = self.writer.take() {
             let actions = writer
                 .close()
@@ -347,8 +489,18 @@ impl WriterTask {
                 .await
                 .map_err(|e| anyhow!("error updating delta table version before commit: {e}"))?;
 
+            // Record the step in the same commit as the data, so that after a
+            // restart we can tell which steps are already in the table.
+            let txn = self.txn().map(Action::Txn);
+
             CommitBuilder::default()
-                .with_actions(actions.into_iter().map(Action::Add).collect::<Vec<_>>())
+                .with_actions(
+                    actions
+                        .into_iter()
+                        .map(Action::Add)
+                        .chain(txn)
+                        .collect::<Vec<_>>(),
+                )
                 .build(
                     self.delta_table
                         .state
@@ -363,6 +515,7 @@ impl WriterTask {
                 )
                 .await
                 .map_err(|e| anyhow!("error committing changes to the delta table: {e}"))?;
+            self.next_step = self.step + 1;
 
             if let Some(controller) = self.inner.controller.upgrade() {
                 controller
@@ -379,6 +532,14 @@ impl WriterTask {
     }
 
     async fn insert(&mut self, batch: RecordBatch) -> AnyResult<()> {
+        if self.step < self.next_step {
+            return Ok(());
+        }
+        if self.inner.config.mode == DeltaTableWriteMode::Merge {
+            self.num_rows += batch.num_rows();
+            self.merge_batches.push(batch);
+            return Ok(());
+        }
         if let Some(writer) = &mut self.writer {
             self.num_rows += batch.num_rows();
             trace!(
@@ -399,6 +560,107 @@ impl WriterTask {
             );
         }
     }
+
+    /// Apply the updates buffered in the current batch to the table.
+    ///
+    /// Updates are matched against existing rows using the key columns.
+    /// Deletes remove the matching row; inserts and updates replace it or
+    /// insert a new row if there's no match.
+    async fn merge(&mut self) -> AnyResult<()> {
+        let batches = std::mem::take(&mut self.merge_batches);
+        if batches.is_empty() {
+            return Ok(());
+        }
+
+        let num_bytes = batches
+            .iter()
+            .map(|batch| batch.get_array_memory_size())
+            .sum();
+
+        let source = SessionContext::new()
+            .read_batches(batches)
+            .map_err(|e| anyhow!("error preparing updates for the MERGE operation: {e}"))?;
+
+        let predicate = self
+            .inner
+            .key_columns
+            .iter()
+            .map(|c| {
+                let c = quote_sql_identifier(c);
+                format!("target.{c} = source.{c}")
+            })
+            .collect::<Vec<_>>()
+            .join(" AND ");
+
+        let columns = self
+            .inner
+            .struct_fields
+            .iter()
+            .map(|f| f.name().to_string())
+            .collect::<Vec<_>>();
+
+        self.delta_table
+            .update_incremental(None)
+            .await
+            .map_err(|e| anyhow!("error updating delta table version before merge: {e}"))?;
+
+        // Record the step in the same commit as the data, so that after a
+        // restart we can tell which steps are already in the table.
+        let mut commit_properties = CommitProperties::default();
+        if let Some(txn) = self.txn() {
+            commit_properties = commit_properties.with_application_transaction(txn);
+        }
+
+        let (delta_table, metrics) = DeltaOps(self.delta_table.clone())
+            .merge(source, predicate)
+            .with_source_alias("source")
+            .with_target_alias("target")
+            .with_commit_properties(commit_properties)
+            .when_matched_delete(|delete| delete.predicate("source.__feldera_op = 'd'"))?
+            .when_matched_update(|update| {
+                columns.iter().fold(
+                    update.predicate("source.__feldera_op <> 'd'"),
+                    |update, c| {
+                        update.update(
+                            Column::new_unqualified(c),
+                            format!("source.{}", quote_sql_identifier(c)),
+                        )
+                    },
+                )
+            })?
+            .when_not_matched_insert(|insert| {
+                columns.iter().fold(
+                    insert.predicate("source.__feldera_op <> 'd'"),
+                    |insert, c| {
+                        insert.set(
+                            Column::new_unqualified(c),
+                            format!("source.{}", quote_sql_identifier(c)),
+                        )
+                    },
+                )
+            })?
+            .await
+            .map_err(|e| anyhow!("error merging changes into the delta table: {e}"))?;
+        self.delta_table = delta_table;
+        self.next_step = self.step + 1;
+
+        debug!(
+            "delta_table {}: merged {} updates into the table (inserted: {}, updated: {}, deleted: {})",
+            &self.inner.endpoint_name,
+            self.num_rows,
+            metrics.num_target_rows_inserted,
+            metrics.num_target_rows_updated,
+            metrics.num_target_rows_deleted,
+        );
+
+        if let Some(controller) = self.inner.controller.upgrade() {
+            controller
+                .status
+                .output_buffer(self.inner.endpoint_id, num_bytes, self.num_rows)
+        };
+
+        Ok(())
+    }
 }
 
 impl OutputConsumer for DeltaTableWriter {
@@ -406,8 +668,8 @@ impl OutputConsumer for DeltaTableWriter {
         usize::MAX
     }
 
-    fn batch_start(&mut self, _step: Step) {
-        self.command(Command::BatchStart)
+    fn batch_start(&mut self, step: Step) {
+        self.command(Command::BatchStart(step))
             .unwrap_or_else(|(e, fatal)| {
                 if let Some(controller) = self.inner.controller.upgrade() {
                     controller.output_transport_error(
@@ -469,12 +731,72 @@ impl<'a> Meta<'a> {
     }
 }
 
+impl DeltaTableWriter {
+    /// Encode an indexed batch in the `merge` mode.
+    ///
+    /// Produces at most one record per key, tagged with the operation
+    /// (`i`, `u`, or `d`) that the worker applies to the matching row of the table.
+    fn encode_indexed(&mut self, batch: &dyn SerBatchReader) -> AnyResult<()> {
+        let micros = Utc::now().timestamp_micros();
+        let mut builder = ArrayBuilder::new(self.inner.serde_arrow_schema.clone())?;
+        let index_name = self.inner.index_name.clone().unwrap();
+
+        let mut num_records = 0;
+
+        let mut cursor = batch.cursor(RecordFormat::Parquet(delta_arrow_serde_config().clone()))?;
+        while cursor.key_valid() {
+            let Some(operation_type) =
+                indexed_operation_type(&self.inner.view_name, &index_name, cursor.as_mut())?
+            else {
+                cursor.step_key();
+                continue;
+            };
+
+            // Output the new value for inserts and updates and the old value for deletes.
+            let (op, weight) = match operation_type {
+                IndexedOperationType::Insert => ("i", 1),
+                IndexedOperationType::Upsert => ("u", 1),
+                IndexedOperationType::Delete => ("d", -1),
+            };
+
+            cursor.rewind_vals();
+            while cursor.val_valid() {
+                if cursor.weight() == weight {
+                    cursor.serialize_val_to_arrow_with_metadata(
+                        &Meta::new(op, micros),
+                        &mut builder,
+                    )?;
+                    num_records += 1;
+                    break;
+                }
+                cursor.step_val();
+            }
+
+            if num_records >= CHUNK_SIZE {
+                self.insert_record_batch(&mut builder)?;
+                num_records = 0;
+            }
+            cursor.step_key();
+        }
+
+        if num_records > 0 {
+            self.insert_record_batch(&mut builder)?;
+        }
+
+        Ok(())
+    }
+}
+
 impl Encoder for DeltaTableWriter {
     fn consumer(&mut self) -> &mut dyn OutputConsumer {
         self
     }
 
     fn encode(&mut self, batch: &dyn SerBatchReader) -> AnyResult<()> {
+        if self.inner.config.mode == DeltaTableWriteMode::Merge {
+            return self.encode_indexed(batch);
+        }
+
         let micros = Utc::now().timestamp_micros();
         let mut insert_builder = ArrayBuilder::new(self.inner.serde_arrow_schema.clone())?;
 
@@ -559,8 +881,13 @@ impl OutputEndpoint for DeltaTableWriter {
     }
 
     fn is_fault_tolerant(&self) -> bool {
-        // TODO: make this connector fault tolerant.  Delta tables already allow atomic
-        // updates, we just need to record the step-to-table-snapshot mapping somewhere.
-        false
+        // Each step is committed atomically together with a `txn` action that
+        // records the step number, which lets us skip steps that were already
+        // committed when the pipeline replays them after a restart.  This only
+        // works if we don't discard the table's contents on startup.
+        matches!(
+            self.inner.config.mode,
+            DeltaTableWriteMode::Append | DeltaTableWriteMode::Merge
+        )
     }
 }
@@ -1,12 +1,16 @@
-use crate::catalog::InputCollectionHandle;
+use crate::catalog::{InputCollectionHandle, SerBatchReader};
 use crate::format::avro::{input, output};
 use crate::format::parquet::relation_to_arrow_fields;
 use crate::format::parquet::test::load_parquet_file;
 use crate::format::relation_to_parquet_schema;
-use crate::integrated::delta_table::{delta_input_serde_config, register_storage_handlers};
+use crate::format::Encoder;
+use crate::integrated::delta_table::{
+    delta_input_serde_config, register_storage_handlers, DeltaTableWriter,
+};
+use crate::static_compile::seroutput::SerBatchImpl;
 use crate::test::{
     file_to_zset, list_files_recursive, test_circuit, wait, DatabricksPeople, DeltaTestStruct,
-    MockDeZSet, MockUpdate,
+    KeyStruct, MockDeZSet, MockUpdate, TestStruct,
 };
 use crate::{Controller, ControllerError, InputFormat};
 use anyhow::anyhow;
@@ -17,7 +21,7 @@ use chrono::{DateTime, NaiveDate};
 use dbsp::typed_batch::DynBatchReader;
 use dbsp::typed_batch::TypedBatch;
 use dbsp::utils::Tup2;
-use dbsp::{storage, BatchReader, DBData, OrdZSet, ZSet};
+use dbsp::{storage, BatchReader, DBData, OrdIndexedZSet, OrdZSet, ZSet};
 use deltalake::datafusion::dataframe::DataFrameWriteOptions;
 use deltalake::datafusion::logical_expr::Literal;
 use deltalake::datafusion::prelude::{col, SessionContext};
@@ -27,6 +31,7 @@ use deltalake::operations::create::CreateBuilder;
 use deltalake::protocol::SaveMode;
 use deltalake::{DeltaOps, DeltaTable, DeltaTableBuilder};
 use feldera_adapterlib::catalog::{OutputCollectionHandles, RecordFormat};
+use feldera_adapterlib::transport::{OutputEndpoint, Step};
 use feldera_adapterlib::utils::datafusion::execute_singleton_query;
 use feldera_types::config::PipelineConfig;
 use feldera_types::format::json::JsonFlavor;
@@ -37,7 +42,9 @@ use feldera_types::serde_with_context::{
     DateFormat, DeserializeWithContext, SerializeWithContext, SqlSerdeConfig, TimeFormat,
     TimestampFormat,
 };
-use feldera_types::transport::delta_table::DeltaTableIngestMode;
+use feldera_types::transport::delta_table::{
+    DeltaTableIngestMode, DeltaTableWriteMode, DeltaTableWriterConfig,
+};
 use futures::io::repeat;
 use parquet::file::reader::Length;
 use proptest::collection::vec;
@@ -47,7 +54,7 @@ use proptest::strategy::ValueTree;
 use proptest::test_runner::TestRunner;
 use serde_arrow::schema::SerdeArrowSchema;
 use std::cmp::min;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::ffi::OsStr;
 use std::fmt::Debug;
 use std::fs::File;
@@ -55,7 +62,7 @@ use std::io::{Read, Write};
 use std::mem::forget;
 use std::os::unix::ffi::OsStrExt;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use std::time::{Duration, Instant};
 use tempfile::{tempdir, NamedTempFile, TempDir};
 use tokio::sync::mpsc;
@@ -1413,6 +1420,142 @@ proptest! {
     }
 }
 
+/// In the `merge` mode, the table mirrors the current contents of an indexed view.
+#[test]
+fn delta_table_output_merge() {
+    let table_dir = TempDir::new().unwrap();
+    let table_uri = table_dir.path().display().to_string();
+
+    let config = DeltaTableWriterConfig {
+        uri: table_uri.clone(),
+        mode: DeltaTableWriteMode::Merge,
+        object_store_config: HashMap::new(),
+    };
+
+    let mut writer = DeltaTableWriter::new(
+        0,
+        "test_output",
+        &config,
+        &Some(KeyStruct::relation_schema()),
+        &TestStruct::relation_schema(),
+        Weak::new(),
+    )
+    .unwrap();
+
+    let record = |id: u32, s: &str| TestStruct {
+        id,
+        b: id % 2 == 0,
+        i: Some(id as i64),
+        s: s.to_string(),
+    };
+
+    let steps = vec![
+        // Insert five records.
+        (1..=5)
+            .map(|id| Tup2(Tup2(KeyStruct::for_id(id), record(id, "foo")), 1))
+            .collect::<Vec<_>>(),
+        // Update record 2, delete record 3, insert record 6.
+        vec![
+            Tup2(Tup2(KeyStruct::for_id(2), record(2, "foo")), -1),
+            Tup2(Tup2(KeyStruct::for_id(2), record(2, "bar")), 1),
+            Tup2(Tup2(KeyStruct::for_id(3), record(3, "foo")), -1),
+            Tup2(Tup2(KeyStruct::for_id(6), record(6, "foo")), 1),
+        ],
+    ];
+
+    for (step, updates) in steps.into_iter().enumerate() {
+        let zset = OrdIndexedZSet::from_tuples((), updates);
+        let zset = &SerBatchImpl::<_, KeyStruct, TestStruct>::new(zset) as &dyn SerBatchReader;
+        writer.consumer().batch_start(step as Step);
+        writer.encode(zset).unwrap();
+        writer.consumer().batch_end();
+    }
+
+    let mut json_file = delta_table_snapshot_to_json::<TestStruct>(
+        &table_uri,
+        &TestStruct::schema(),
+        &HashMap::new(),
+    );
+
+    let expected = vec![
+        record(1, "foo"),
+        record(2, "bar"),
+        record(4, "foo"),
+        record(5, "foo"),
+        record(6, "foo"),
+    ];
+    let expected_zset = OrdZSet::from_tuples(
+        (),
+        expected.into_iter().map(|x| Tup2(Tup2(x, ()), 1)).collect(),
+    );
+    let zset = file_to_zset::<TestStruct>(
+        json_file.as_file_mut(),
+        "json",
+        r#"update_format: "insert_delete""#,
+    );
+    assert_eq!(zset, expected_zset);
+}
+
+/// Replaying steps that have already been committed to the table, as a
+/// fault-tolerant pipeline does after a restart, must not duplicate them.
+#[test]
+fn delta_table_output_exactly_once() {
+    let mut runner = TestRunner::default();
+    let data = delta_data(2_000).new_tree(&mut runner).unwrap().current();
+    let (step0, step1) = data.split_at(data.len() / 2);
+    let (step1, step2) = step1.split_at(step1.len() / 2);
+
+    let table_dir = TempDir::new().unwrap();
+    let table_uri = table_dir.path().display().to_string();
+
+    let config = DeltaTableWriterConfig {
+        uri: table_uri.clone(),
+        mode: DeltaTableWriteMode::Append,
+        object_store_config: HashMap::new(),
+    };
+    let relation = Relation::new(
+        "test_table".into(),
+        DeltaTestStruct::schema(),
+        false,
+        BTreeMap::new(),
+    );
+
+    let write_steps = |steps: &[(Step, &[DeltaTestStruct])]| {
+        let mut writer =
+            DeltaTableWriter::new(0, "test_output", &config, &None, &relation, Weak::new())
+                .unwrap();
+        assert!(writer.is_fault_tolerant());
+
+        for (step, data) in steps {
+            let zset = OrdZSet::from_keys((), data.iter().map(|x| Tup2(x.clone(), 1)).collect());
+            let zset = &SerBatchImpl::<_, DeltaTestStruct, ()>::new(zset) as &dyn SerBatchReader;
+            writer.consumer().batch_start(*step);
+            writer.encode(zset).unwrap();
+            writer.consumer().batch_end();
+        }
+    };
+
+    write_steps(&[(0, step0), (1, step1)]);
+
+    // Restart from step 1: step 1 is already in the table and must be skipped.
+    write_steps(&[(1, step1), (2, step2)]);
+
+    let mut json_file = delta_table_snapshot_to_json::<DeltaTestStruct>(
+        &table_uri,
+        &DeltaTestStruct::schema(),
+        &HashMap::new(),
+    );
+
+    let expected_zset =
+        OrdZSet::from_tuples((), data.into_iter().map(|x| Tup2(Tup2(x, ()), 1)).collect());
+    let zset = file_to_zset::<DeltaTestStruct>(
+        json_file.as_file_mut(),
+        "json",
+        r#"update_format: "insert_delete""#,
+    );
+    assert_eq!(zset, expected_zset);
+}
+
 /// Read a large (2M records) dataset created in S3 from Databricks.
 /// This dataset was derived from
 /// `/databricks-datasets/learning-spark-v2/people/people-10m.delta`; however the full dataset
@@ -22,10 +22,10 @@ pub use delta_table::{DeltaTableInputEndpoint, DeltaTableWriter};
 #[cfg(feature = "with-deltalake")]
 use feldera_types::config::TransportConfig::DeltaTableInput;
 
-use crate::integrated::postgres::PostgresInputEndpoint;
+use crate::integrated::postgres::{PostgresCdcInputEndpoint, PostgresInputEndpoint};
 #[cfg(feature = "with-iceberg")]
 use feldera_types::config::TransportConfig::IcebergInput;
-use feldera_types::config::TransportConfig::PostgresInput;
+use feldera_types::config::TransportConfig::{PostgresCdcInput, PostgresInput};
 
 /// An integrated output connector implements both transport endpoint
 /// (`OutputEndpoint`) and `Encoder` traits.  It is used to implement
@@ -68,6 +68,36 @@ pub fn create_integrated_output_endpoint(
             schema,
             controller,
         )?),
+        #[cfg(feature = "with-iceberg")]
+        TransportConfig::IcebergOutput(config) => {
+            let error_controller = controller.clone();
+            let error_endpoint_name = endpoint_name.to_string();
+            let stats_controller = controller.clone();
+
+            Box::new(feldera_iceberg::IcebergOutputEndpoint::new(
+                endpoint_name,
+                config,
+                key_schema,
+                schema,
+                Box::new(move |fatal, e| {
+                    if let Some(controller) = error_controller.upgrade() {
+                        controller.output_transport_error(
+                            endpoint_id,
+                            &error_endpoint_name,
+                            fatal,
+                            e,
+                        )
+                    }
+                }),
+                Box::new(move |num_bytes, num_records| {
+                    if let Some(controller) = stats_controller.upgrade() {
+                        controller
+                            .status
+                            .output_buffer(endpoint_id, num_bytes, num_records)
+                    }
+                }),
+            )?)
+        }
         TransportConfig::PostgresOutput(config) => Box::new(PostgresOutputEndpoint::new(
             endpoint_id,
             endpoint_name,
@@ -118,6 +148,11 @@ pub fn create_integrated_input_endpoint(
         PostgresInput(config) => {
             Box::new(PostgresInputEndpoint::new(endpoint_name, config, consumer))
         }
+        PostgresCdcInput(config) => Box::new(PostgresCdcInputEndpoint::new(
+            endpoint_name,
+            config,
+            consumer,
+        )),
         transport => {
             return Err(ControllerError::unknown_input_transport(
                 endpoint_name,
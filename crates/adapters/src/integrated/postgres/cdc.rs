@@ -0,0 +1,1045 @@
+//! Postgres change data capture (CDC) input connector.
+//!
+//! The connector reads an initial snapshot of a table and then follows changes
+//! to the table through a logical replication slot that uses the built-in
+//! `pgoutput` plugin.
+//!
+//! Changes are read from the slot using `pg_logical_slot_peek_binary_changes`,
+//! which does not consume them.  The slot is only advanced once the position
+//! of the connector has been durably recorded in a checkpoint (or right away,
+//! if the pipeline is not fault tolerant), so that the connector can seek to
+//! and replay any step after the last checkpoint.
+//!
+//! Each peek decodes the slot from its confirmed position, including changes
+//! that the connector has already read.  To avoid decoding the whole backlog
+//! on every poll, the connector remembers how many rows preceded its position
+//! in the previous peek and bounds the next peek to that many rows plus
+//! `max_changes_per_poll`.
+//!
+//! Transactions that were committed when the snapshot was taken may still be
+//! in the slot.  The connector skips them by comparing their transaction ids
+//! against the transaction snapshot of the initial read.
+
+use super::pgoutput::{text_to_json, PgOutputMessage, PgTxnSnapshot, RelationBody, TupleValue};
+use crate::format::InputBuffer;
+use crate::transport::{InputEndpoint, InputQueue, InputReaderCommand, IntegratedInputEndpoint};
+use crate::{ControllerError, InputConsumer, InputReader, RecordFormat};
+use anyhow::{anyhow, bail, Result as AnyResult};
+use dbsp::circuit::tokio::TOKIO;
+use feldera_adapterlib::catalog::{DeCollectionStream, InputCollectionHandle};
+use feldera_adapterlib::format::ParseError;
+use feldera_adapterlib::transport::Resume;
+use feldera_types::config::FtModel;
+use feldera_types::format::json::JsonFlavor;
+use feldera_types::program_schema::Relation;
+use feldera_types::transport::postgres::PostgresCdcReaderConfig;
+use futures::pin_mut;
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::hash::Hasher;
+use std::mem;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::select;
+use tokio::sync::mpsc::{self, unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::time::sleep;
+use tokio_postgres::types::{PgLsn, ToSql};
+use tokio_postgres::{Client, IsolationLevel, NoTls, SimpleQueryMessage};
+use tracing::{debug, error, info};
+use xxhash_rust::xxh3::Xxh3Default;
+
+/// Number of rows fetched from the snapshot cursor at a time.
+const SNAPSHOT_FETCH_SIZE: usize = 10_000;
+
+/// Integrated input connector that reads changes to a Postgres table via
+/// logical replication.
+pub struct PostgresCdcInputEndpoint {
+    inner: Arc<PostgresCdcInputEndpointInner>,
+}
+
+impl PostgresCdcInputEndpoint {
+    pub fn new(
+        endpoint_name: &str,
+        config: &PostgresCdcReaderConfig,
+        consumer: Box<dyn InputConsumer>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(PostgresCdcInputEndpointInner::new(
+                endpoint_name,
+                config.clone(),
+                consumer,
+            )),
+        }
+    }
+}
+
+impl InputEndpoint for PostgresCdcInputEndpoint {
+    fn fault_tolerance(&self) -> Option<FtModel> {
+        Some(FtModel::ExactlyOnce)
+    }
+}
+
+impl IntegratedInputEndpoint for PostgresCdcInputEndpoint {
+    fn open(
+        self: Box<Self>,
+        input_handle: &InputCollectionHandle,
+    ) -> AnyResult<Box<dyn InputReader>> {
+        Ok(Box::new(PostgresCdcInputReader::new(
+            &self.inner,
+            input_handle,
+        )?))
+    }
+}
+
+/// Transactions committed before the initial snapshot was taken.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SnapshotFilter {
+    /// Transaction snapshot used to read the table.
+    snapshot: PgTxnSnapshot,
+
+    /// WAL insert position when the snapshot was taken.  Transactions that
+    /// commit at or after this position can't be part of the snapshot.
+    lsn: u64,
+}
+
+/// Position of the connector in the input, recorded as the seek metadata of
+/// each step.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CdcPosition {
+    /// Whether the initial snapshot has been ingested (or was disabled).
+    snapshot_complete: bool,
+
+    /// End LSN of the commit record of the last ingested transaction.
+    /// Transactions that commit before this position have already been
+    /// ingested.
+    lsn: u64,
+
+    /// Filter for transactions included in the snapshot, until the connector
+    /// has read past the point where the snapshot was taken.
+    snapshot_filter: Option<SnapshotFilter>,
+}
+
+impl CdcPosition {
+    fn initial(config: &PostgresCdcReaderConfig) -> Self {
+        Self {
+            snapshot_complete: !config.snapshot,
+            lsn: 0,
+            snapshot_filter: None,
+        }
+    }
+
+    /// Returns true if the transaction with id `xid` whose commit record is at
+    /// `commit_lsn` has already been ingested, either as part of a previous
+    /// step or as part of the snapshot.
+    fn includes(&self, xid: u32, commit_lsn: u64) -> bool {
+        commit_lsn < self.lsn
+            || self
+                .snapshot_filter
+                .as_ref()
+                .is_some_and(|filter| commit_lsn < filter.lsn && filter.snapshot.is_visible(xid))
+    }
+
+    /// Advances the position past a transaction whose commit record ends at
+    /// `end_lsn`.
+    fn advance(&mut self, end_lsn: u64) {
+        self.lsn = self.lsn.max(end_lsn);
+        if self
+            .snapshot_filter
+            .as_ref()
+            .is_some_and(|filter| self.lsn > filter.lsn)
+        {
+            self.snapshot_filter = None;
+        }
+    }
+}
+
+/// Data journaled for replaying a step.
+#[derive(Debug, Serialize, Deserialize)]
+enum CdcReplay {
+    /// The step ingested the initial snapshot, which can't be read again, so
+    /// we journal the records themselves.
+    Snapshot(Vec<String>),
+
+    /// The step ingested the transactions that follow `from`, up to the
+    /// seek position of the step.  These are still available in the
+    /// replication slot.
+    Changes { from: CdcPosition },
+}
+
+/// Auxiliary data attached to the buffers in the input queue.
+enum QueuedBatch {
+    /// Records from the initial snapshot.
+    ///
+    /// `records` is only populated in exactly-once mode, where the whole
+    /// snapshot is queued as a single batch.  `end` is the position after the
+    /// snapshot, set on the last batch of the snapshot.
+    Snapshot {
+        records: Vec<String>,
+        end: Option<CdcPosition>,
+    },
+
+    /// Changes from transactions up to `end`.
+    Changes { end: CdcPosition },
+}
+
+struct PostgresCdcInputReader {
+    sender: UnboundedSender<InputReaderCommand>,
+    inner: Arc<PostgresCdcInputEndpointInner>,
+}
+
+impl PostgresCdcInputReader {
+    fn new(
+        endpoint: &Arc<PostgresCdcInputEndpointInner>,
+        input_handle: &InputCollectionHandle,
+    ) -> AnyResult<Self> {
+        let (sender, receiver) = unbounded_channel();
+
+        // Used to communicate the status of connector initialization.
+        let (init_status_sender, mut init_status_receiver) =
+            mpsc::channel::<Result<(), ControllerError>>(1);
+
+        let input_stream = input_handle
+            .handle
+            .configure_deserializer(RecordFormat::Json(JsonFlavor::Datagen))?;
+        let schema = input_handle.schema.clone();
+
+        let endpoint_clone = endpoint.clone();
+        std::thread::spawn(move || {
+            TOKIO.block_on(async {
+                endpoint_clone
+                    .worker_task(input_stream, schema, receiver, init_status_sender)
+                    .await
+            })
+        });
+
+        init_status_receiver.blocking_recv().ok_or_else(|| {
+            ControllerError::input_transport_error(
+                &endpoint.endpoint_name,
+                true,
+                anyhow!("worker thread terminated unexpectedly during initialization"),
+            )
+        })??;
+
+        Ok(Self {
+            sender,
+            inner: endpoint.clone(),
+        })
+    }
+
+    fn queue(&self) {
+        let (total, hasher, batches) = self
+            .inner
+            .queue
+            .flush_with_aux_until(&|batch| matches!(batch, QueuedBatch::Snapshot { .. }));
+
+        let mut position = self.inner.position.lock().unwrap();
+        let from = position.clone();
+        let mut snapshot_records = None;
+        let mut in_snapshot = false;
+        for batch in batches {
+            match batch {
+                QueuedBatch::Snapshot { records, end } => {
+                    in_snapshot = end.is_none();
+                    if let Some(end) = end {
+                        *position = end;
+                    }
+                    snapshot_records = Some(records);
+                }
+                QueuedBatch::Changes { end } => *position = end,
+            }
+        }
+
+        let resume = if in_snapshot {
+            // We're in the middle of reading a snapshot that is split across
+            // multiple steps.  This only happens without exactly-once fault
+            // tolerance.
+            Resume::Barrier
+        } else {
+            let seek = serde_json::to_value(&*position).unwrap();
+            match hasher {
+                None => Resume::Seek { seek },
+                Some(hasher) => {
+                    let replay = match snapshot_records {
+                        Some(records) => CdcReplay::Snapshot(records),
+                        None => CdcReplay::Changes { from },
+                    };
+                    Resume::Replay {
+                        seek,
+                        replay: rmpv::ext::to_value(replay).unwrap(),
+                        hash: hasher.finish(),
+                    }
+                }
+            }
+        };
+        self.inner.consumer.extended(total, Some(resume));
+    }
+}
+
+impl InputReader for PostgresCdcInputReader {
+    fn request(&self, command: InputReaderCommand) {
+        match command {
+            InputReaderCommand::Queue { .. } => self.queue(),
+            command => {
+                match &command {
+                    InputReaderCommand::Seek(metadata)
+                    | InputReaderCommand::Replay { metadata, .. } => {
+                        if let Ok(position) = serde_json::from_value(metadata.clone()) {
+                            *self.inner.position.lock().unwrap() = position;
+                        }
+                    }
+                    _ => (),
+                }
+                let _ = self.sender.send(command);
+            }
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.inner.queue.is_empty() && self.sender.is_closed()
+    }
+
+    fn checkpointed(&self, metadata: &Value) {
+        if let Ok(position) = serde_json::from_value::<CdcPosition>(metadata.clone()) {
+            *self.inner.checkpointed_lsn.lock().unwrap() = Some(position.lsn);
+        }
+    }
+}
+
+impl Drop for PostgresCdcInputReader {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+struct PostgresCdcInputEndpointInner {
+    endpoint_name: String,
+    config: PostgresCdcReaderConfig,
+    consumer: Box<dyn InputConsumer>,
+    queue: InputQueue<QueuedBatch>,
+
+    /// Position at the end of the last step flushed to the circuit.
+    position: Mutex<CdcPosition>,
+
+    /// Position of the last checkpoint that hasn't been reflected in the
+    /// replication slot yet.
+    checkpointed_lsn: Mutex<Option<u64>>,
+}
+
+impl PostgresCdcInputEndpointInner {
+    fn new(
+        endpoint_name: &str,
+        config: PostgresCdcReaderConfig,
+        consumer: Box<dyn InputConsumer>,
+    ) -> Self {
+        let queue = InputQueue::new(consumer.clone());
+        let position = Mutex::new(CdcPosition::initial(&config));
+
+        Self {
+            endpoint_name: endpoint_name.to_string(),
+            config,
+            consumer,
+            queue,
+            position,
+            checkpointed_lsn: Mutex::new(None),
+        }
+    }
+
+    /// Returns the namespace and the name of the table to read.
+    fn table(&self) -> (&str, &str) {
+        self.config
+            .table
+            .split_once('.')
+            .unwrap_or(("public", &self.config.table))
+    }
+
+    fn exactly_once(&self) -> bool {
+        self.consumer.pipeline_fault_tolerance() == Some(FtModel::ExactlyOnce)
+    }
+
+    async fn worker_task(
+        self: Arc<Self>,
+        input_stream: Box<dyn DeCollectionStream>,
+        schema: Relation,
+        receiver: UnboundedReceiver<InputReaderCommand>,
+        init_status_sender: mpsc::Sender<Result<(), ControllerError>>,
+    ) {
+        let client = match self.connect_to_postgres().await {
+            Err(e) => {
+                let _ = init_status_sender.send(Err(e)).await;
+                return;
+            }
+            Ok(client) => client,
+        };
+
+        if let Err(e) = self.prepare_slot(&client).await {
+            let _ = init_status_sender.send(Err(e)).await;
+            return;
+        }
+        let _ = init_status_sender.send(Ok(())).await;
+
+        if let Err(e) = self
+            .worker_task_inner(client, input_stream, schema, receiver)
+            .await
+        {
+            error!("postgres_cdc {}: {e:#}", &self.endpoint_name);
+            self.consumer.error(true, e);
+        }
+        debug!(
+            "postgres_cdc {}: worker task terminated",
+            &self.endpoint_name
+        );
+    }
+
+    async fn worker_task_inner(
+        &self,
+        mut client: Client,
+        mut input_stream: Box<dyn DeCollectionStream>,
+        schema: Relation,
+        mut receiver: UnboundedReceiver<InputReaderCommand>,
+    ) -> AnyResult<()> {
+        // Position up to which we have queued input.
+        let mut position = CdcPosition::initial(&self.config);
+        let mut extending = false;
+        let mut more = false;
+
+        // Number of rows in the replication slot that precede `position`, as
+        // of the last time we read the slot.  This may overestimate the
+        // number if the slot has been advanced since then.
+        let mut rows_before_position = 0;
+
+        loop {
+            let poll_interval = if more {
+                Duration::ZERO
+            } else {
+                Duration::from_millis(self.config.poll_interval_ms)
+            };
+            let command = select! {
+                command = receiver.recv() => match command {
+                    Some(command) => Some(command),
+                    None => return Ok(()),
+                },
+                _ = sleep(poll_interval), if extending => None,
+            };
+
+            match command {
+                Some(InputReaderCommand::Seek(metadata)) => {
+                    position = serde_json::from_value(metadata)?;
+                    rows_before_position = 0;
+                    // The seek position comes from a checkpoint, so the pipeline
+                    // won't need anything before it.
+                    self.advance_slot(&client, position.lsn).await?;
+                }
+                Some(InputReaderCommand::Replay { metadata, data }) => {
+                    let end: CdcPosition = serde_json::from_value(metadata)?;
+                    let mut hasher = Xxh3Default::new();
+                    let num_records = match rmpv::ext::from_value::<CdcReplay>(data)? {
+                        CdcReplay::Snapshot(records) => {
+                            let mut num_bytes = 0;
+                            for record in &records {
+                                input_stream.insert(record.as_bytes())?;
+                                num_bytes += record.len();
+                            }
+                            self.consumer.buffered(input_stream.len(), num_bytes);
+                            input_stream.len()
+                        }
+                        CdcReplay::Changes { from } if !from.snapshot_complete => 0,
+                        CdcReplay::Changes { mut from } => {
+                            let mut reader =
+                                ChangeReader::new(self, &client, &schema, input_stream.as_mut());
+                            reader
+                                .read_changes(&mut from, Some(end.lsn), None, None)
+                                .await?;
+                            reader.finish()
+                        }
+                    };
+                    if let Some(mut buffer) = input_stream.take_all() {
+                        buffer.hash(&mut hasher);
+                        buffer.flush();
+                    }
+                    self.consumer.replayed(num_records, hasher.finish());
+                    position = end;
+                }
+                Some(InputReaderCommand::Extend) => extending = true,
+                Some(InputReaderCommand::Pause) => extending = false,
+                Some(InputReaderCommand::Queue { .. }) => unreachable!(),
+                Some(InputReaderCommand::Disconnect) => return Ok(()),
+                None => {
+                    let checkpointed_lsn = self.checkpointed_lsn.lock().unwrap().take();
+                    if let Some(lsn) = checkpointed_lsn {
+                        self.advance_slot(&client, lsn).await?;
+                    }
+
+                    if !position.snapshot_complete {
+                        position = self
+                            .read_snapshot(&mut client, &schema, input_stream.as_mut())
+                            .await?;
+                        more = true;
+                        continue;
+                    }
+
+                    let mut reader =
+                        ChangeReader::new(self, &client, &schema, input_stream.as_mut());
+                    let start = position.clone();
+                    more = reader
+                        .read_changes(
+                            &mut position,
+                            None,
+                            Some(self.config.max_changes_per_poll as usize),
+                            Some(rows_before_position + self.config.max_changes_per_poll as u64),
+                        )
+                        .await?;
+                    rows_before_position = reader.rows_before_position;
+                    let (errors, num_bytes) = reader.take_errors_and_bytes();
+                    if position != start {
+                        self.queue.push_with_aux(
+                            (input_stream.take_all(), errors),
+                            num_bytes,
+                            QueuedBatch::Changes {
+                                end: position.clone(),
+                            },
+                        );
+                        if self.consumer.pipeline_fault_tolerance().is_none() {
+                            // Nothing will ever ask us to replay input, so we
+                            // can release it from the slot right away.
+                            self.advance_slot(&client, position.lsn).await?;
+                        }
+                    } else {
+                        self.consumer.parse_errors(errors);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reads the initial snapshot of the table and queues it.
+    ///
+    /// Returns the position just after the snapshot.
+    async fn read_snapshot(
+        &self,
+        client: &mut Client,
+        schema: &Relation,
+        input_stream: &mut dyn DeCollectionStream,
+    ) -> AnyResult<CdcPosition> {
+        let (namespace, table) = self.table();
+        info!(
+            "postgres_cdc {}: reading snapshot of table {namespace}.{table}",
+            &self.endpoint_name
+        );
+
+        let transaction = client
+            .build_transaction()
+            .isolation_level(IsolationLevel::RepeatableRead)
+            .read_only(true)
+            .start()
+            .await?;
+
+        let row = transaction
+            .query_one(
+                "SELECT pg_current_snapshot()::text, pg_current_wal_insert_lsn()",
+                &[],
+            )
+            .await?;
+        let snapshot = PgTxnSnapshot::parse(row.get(0))?;
+        let lsn: PgLsn = row.get(1);
+
+        // Only read the columns that exist in the Feldera table.
+        let columns = transaction
+            .query(
+                "SELECT a.attname, a.atttypid FROM pg_attribute a \
+                 WHERE a.attrelid = $1::text::regclass AND a.attnum > 0 AND NOT a.attisdropped \
+                 ORDER BY a.attnum",
+                &[&format!(
+                    "{}.{}",
+                    quote_ident(namespace),
+                    quote_ident(table)
+                )],
+            )
+            .await?
+            .into_iter()
+            .map(|row| (row.get::<_, String>(0), row.get::<_, u32>(1)))
+            .filter(|(name, _)| schema.fields.iter().any(|f| &f.name == name))
+            .collect::<Vec<_>>();
+        if columns.is_empty() {
+            bail!("table {namespace}.{table} has no columns in common with the Feldera table");
+        }
+
+        transaction
+            .batch_execute(&format!(
+                "DECLARE feldera_snapshot NO SCROLL CURSOR FOR SELECT {} FROM {}.{}",
+                columns
+                    .iter()
+                    .map(|(name, _)| quote_ident(name))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                quote_ident(namespace),
+                quote_ident(table)
+            ))
+            .await?;
+
+        // In exactly-once mode, the snapshot is ingested in a single step,
+        // whose records we journal.  Otherwise, we split it into batches of
+        // the preferred size.
+        let exactly_once = self.exactly_once();
+        let batch_size = self.consumer.max_batch_size();
+        let mut records = Vec::new();
+        let mut errors = Vec::new();
+        let mut num_bytes = 0;
+        let mut num_events = 0;
+        loop {
+            let messages = transaction
+                .simple_query(&format!(
+                    "FETCH {SNAPSHOT_FETCH_SIZE} FROM feldera_snapshot"
+                ))
+                .await?;
+            let mut num_rows = 0;
+            for message in messages {
+                let SimpleQueryMessage::Row(row) = message else {
+                    continue;
+                };
+                num_rows += 1;
+                num_events += 1;
+
+                let mut record = serde_json::Map::new();
+                let mut error = None;
+                for (index, (name, type_oid)) in columns.iter().enumerate() {
+                    let value = match row.get(index) {
+                        None => Value::Null,
+                        Some(text) => match text_to_json(*type_oid, text) {
+                            Ok(value) => value,
+                            Err(e) => {
+                                error = Some(format!("error decoding column '{name}': {e}"));
+                                break;
+                            }
+                        },
+                    };
+                    record.insert(name.clone(), value);
+                }
+                let record = Value::Object(record).to_string();
+                num_bytes += record.len();
+                if let Some(error) = error {
+                    errors.push(ParseError::text_event_error(
+                        "Failed to decode table record from PostgreSQL",
+                        error,
+                        num_events,
+                        Some(&record),
+                        None,
+                    ));
+                    continue;
+                }
+                if let Err(e) = input_stream.insert(record.as_bytes()) {
+                    errors.push(ParseError::text_event_error(
+                        "Failed to deserialize table record from PostgreSQL",
+                        e,
+                        num_events,
+                        Some(&record),
+                        None,
+                    ));
+                    continue;
+                }
+                if exactly_once {
+                    records.push(record);
+                } else if input_stream.len() >= batch_size {
+                    self.queue.push_with_aux(
+                        (input_stream.take_all(), mem::take(&mut errors)),
+                        mem::take(&mut num_bytes),
+                        QueuedBatch::Snapshot {
+                            records: Vec::new(),
+                            end: None,
+                        },
+                    );
+                }
+            }
+            if num_rows < SNAPSHOT_FETCH_SIZE {
+                break;
+            }
+        }
+        transaction.commit().await?;
+
+        let end = CdcPosition {
+            snapshot_complete: true,
+            lsn: 0,
+            snapshot_filter: Some(SnapshotFilter {
+                snapshot,
+                lsn: lsn.into(),
+            }),
+        };
+        self.queue.push_with_aux(
+            (input_stream.take_all(), errors),
+            num_bytes,
+            QueuedBatch::Snapshot {
+                records,
+                end: Some(end.clone()),
+            },
+        );
+        info!(
+            "postgres_cdc {}: finished reading snapshot ({num_events} records)",
+            &self.endpoint_name
+        );
+
+        Ok(end)
+    }
+
+    /// Checks that the replication slot exists, creating it if necessary.
+    async fn prepare_slot(&self, client: &Client) -> Result<(), ControllerError> {
+        let error = |e: &dyn std::fmt::Display| {
+            ControllerError::invalid_transport_configuration(
+                &self.endpoint_name,
+                &format!(
+                    "error checking replication slot '{}': {e}",
+                    self.config.slot
+                ),
+            )
+        };
+
+        let rows = client
+            .query(
+                "SELECT plugin FROM pg_replication_slots WHERE slot_name = $1",
+                &[&self.config.slot],
+            )
+            .await
+            .map_err(|e| error(&e))?;
+        match rows.first() {
+            Some(row) => {
+                let plugin: Option<String> = row.get(0);
+                if plugin.as_deref() != Some("pgoutput") {
+                    return Err(error(&format!(
+                        "the slot uses plugin '{}', but the connector requires 'pgoutput'",
+                        plugin.unwrap_or_default()
+                    )));
+                }
+            }
+            None if self.config.create_slot => {
+                info!(
+                    "postgres_cdc {}: creating replication slot '{}'",
+                    &self.endpoint_name, &self.config.slot
+                );
+                client
+                    .execute(
+                        "SELECT pg_create_logical_replication_slot($1, 'pgoutput')",
+                        &[&self.config.slot],
+                    )
+                    .await
+                    .map_err(|e| error(&e))?;
+            }
+            None => {
+                return Err(error(
+                    &"the slot does not exist and 'create_slot' is set to false",
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Advances the replication slot to `lsn`, discarding the changes before
+    /// it, unless the slot is already past `lsn`.
+    async fn advance_slot(&self, client: &Client, lsn: u64) -> AnyResult<()> {
+        if lsn == 0 {
+            return Ok(());
+        }
+        debug!(
+            "postgres_cdc {}: advancing replication slot to {}",
+            &self.endpoint_name,
+            PgLsn::from(lsn)
+        );
+        client
+            .execute(
+                "SELECT pg_replication_slot_advance(slot_name, $2) FROM pg_replication_slots \
+                 WHERE slot_name = $1 AND confirmed_flush_lsn < $2",
+                &[&self.config.slot, &PgLsn::from(lsn)],
+            )
+            .await
+            .map_err(|e| anyhow!("error advancing replication slot: {e}"))?;
+        Ok(())
+    }
+
+    /// Open a connection to postgres.
+    async fn connect_to_postgres(&self) -> Result<Client, ControllerError> {
+        debug!(
+            "postgres_cdc {}: opening connection to '{}'",
+            &self.endpoint_name, &self.config.uri
+        );
+
+        let (client, connection) = tokio_postgres::connect(self.config.uri.as_str(), NoTls)
+            .await
+            .map_err(|e| {
+                ControllerError::invalid_transport_configuration(
+                    &self.endpoint_name,
+                    &format!(
+                        "Unable to connect to postgres instance '{}': {e}",
+                        self.config.uri
+                    ),
+                )
+            })?;
+
+        let endpoint_name = self.endpoint_name.clone();
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                error!("postgres_cdc {endpoint_name}: connection error: {e}");
+            }
+        });
+
+        info!(
+            "postgres_cdc {}: opened connection to '{}'",
+            &self.endpoint_name, &self.config.uri,
+        );
+
+        Ok(client)
+    }
+}
+
+/// State of the connector's reads from the replication slot.
+struct ChangeReader<'a> {
+    endpoint: &'a PostgresCdcInputEndpointInner,
+    client: &'a Client,
+    schema: &'a Relation,
+    input_stream: &'a mut dyn DeCollectionStream,
+
+    /// Description of the table that the connector reads, once the change
+    /// stream has described it.
+    relation: Option<Arc<RelationBody>>,
+
+    errors: Vec<ParseError>,
+    num_bytes: usize,
+    num_events: u64,
+
+    /// Number of rows read from the slot up to the end of the last
+    /// transaction that the position was advanced past.
+    rows_before_position: u64,
+}
+
+impl<'a> ChangeReader<'a> {
+    fn new(
+        endpoint: &'a PostgresCdcInputEndpointInner,
+        client: &'a Client,
+        schema: &'a Relation,
+        input_stream: &'a mut dyn DeCollectionStream,
+    ) -> Self {
+        Self {
+            endpoint,
+            client,
+            schema,
+            input_stream,
+            relation: None,
+            errors: Vec::new(),
+            num_bytes: 0,
+            num_events: 0,
+            rows_before_position: 0,
+        }
+    }
+
+    /// Reads changes from the replication slot, starting after `position`,
+    /// into the input stream, and advances `position` past the transactions
+    /// that were read, including transactions that were skipped because they
+    /// are part of the snapshot.
+    ///
+    /// Stops before the first transaction that commits at or after `until`,
+    /// if specified, or after the first transaction that brings the number of
+    /// changes read to `limit`, if specified.  If `max_rows` is specified,
+    /// asks Postgres to stop decoding the slot after the first transaction
+    /// that brings the number of rows to `max_rows`.  Returns true if it
+    /// stopped because of `limit` or `max_rows`.
+    async fn read_changes(
+        &mut self,
+        position: &mut CdcPosition,
+        until: Option<u64>,
+        limit: Option<usize>,
+        max_rows: Option<u64>,
+    ) -> AnyResult<bool> {
+        let config = &self.endpoint.config;
+        let upto_lsn = until.map(PgLsn::from);
+        let upto_nchanges = max_rows.map(|max_rows| max_rows.min(i32::MAX as u64) as i32);
+        let params: [&(dyn ToSql + Sync); 4] =
+            [&config.slot, &upto_lsn, &upto_nchanges, &config.publication];
+        let stream = self
+            .client
+            .query_raw(
+                "SELECT data FROM pg_logical_slot_peek_binary_changes($1, $2, $3, \
+                 'proto_version', '1', 'publication_names', $4)",
+                params,
+            )
+            .await
+            .map_err(|e| anyhow!("error reading from replication slot: {e}"))?;
+        pin_mut!(stream);
+
+        let (namespace, table) = self.endpoint.table();
+        let mut num_changes = 0;
+        let mut num_rows = 0;
+
+        // Whether the current transaction should be ingested.
+        let mut ingest = false;
+        while let Some(row) = stream
+            .try_next()
+            .await
+            .map_err(|e| anyhow!("error reading from replication slot: {e}"))?
+        {
+            num_rows += 1;
+            match PgOutputMessage::decode(row.get(0))? {
+                PgOutputMessage::Begin { final_lsn, xid } => {
+                    if until.is_some_and(|until| final_lsn >= until) {
+                        break;
+                    }
+                    ingest = !position.includes(xid, final_lsn);
+                }
+                PgOutputMessage::Commit { end_lsn, .. } => {
+                    // This is a no-op for transactions ingested by a previous
+                    // step, but it moves the position past transactions that
+                    // were skipped because they are part of the snapshot.
+                    position.advance(end_lsn);
+                    self.rows_before_position = num_rows;
+                    if ingest && limit.is_some_and(|limit| num_changes >= limit) {
+                        return Ok(true);
+                    }
+                    ingest = false;
+                }
+                PgOutputMessage::Relation(relation) => {
+                    if relation.namespace == namespace && relation.name == table {
+                        self.relation = Some(Arc::new(relation));
+                    } else if self.relation.as_ref().is_some_and(|r| r.id == relation.id) {
+                        // The table was renamed.
+                        self.relation = None;
+                    }
+                }
+                PgOutputMessage::Insert { relation_id, new } if ingest => {
+                    if let Some(relation) = self.relation(relation_id) {
+                        num_changes += 1;
+                        self.apply(&relation, None, Some(&new));
+                    }
+                }
+                PgOutputMessage::Update {
+                    relation_id,
+                    old,
+                    new,
+                } if ingest => {
+                    if let Some(relation) = self.relation(relation_id) {
+                        num_changes += 1;
+                        self.apply(&relation, old.as_deref(), Some(&new));
+                    }
+                }
+                PgOutputMessage::Delete { relation_id, old } if ingest => {
+                    if let Some(relation) = self.relation(relation_id) {
+                        num_changes += 1;
+                        self.apply(&relation, Some(&old), None);
+                    }
+                }
+                PgOutputMessage::Truncate { relation_ids } if ingest => {
+                    if relation_ids.iter().any(|id| self.relation(*id).is_some()) {
+                        self.endpoint.consumer.error(
+                            false,
+                            anyhow!("table {namespace}.{table} was truncated in Postgres; TRUNCATE is not supported by the CDC connector and was ignored"),
+                        );
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(max_rows.is_some_and(|max_rows| num_rows >= max_rows))
+    }
+
+    /// Returns the description of relation `relation_id` if it is the table
+    /// that the connector reads.
+    fn relation(&self, relation_id: u32) -> Option<Arc<RelationBody>> {
+        self.relation
+            .as_ref()
+            .filter(|relation| relation.id == relation_id)
+            .cloned()
+    }
+
+    /// Applies a change to the input stream: deletes `old`, if present, and
+    /// inserts `new`, if present.
+    fn apply(
+        &mut self,
+        relation: &RelationBody,
+        old: Option<&[TupleValue]>,
+        new: Option<&[TupleValue]>,
+    ) {
+        self.num_events += 1;
+        if let Some(old) = old {
+            match self.tuple_to_json(relation, old, None) {
+                Ok(record) => {
+                    self.num_bytes += record.len();
+                    if let Err(e) = self.input_stream.delete(record.as_bytes()) {
+                        self.push_error(e, &record);
+                    }
+                }
+                Err(record) => self.push_error("unable to decode old record", &record),
+            }
+        }
+        if let Some(new) = new {
+            match self.tuple_to_json(relation, new, old) {
+                Ok(record) => {
+                    self.num_bytes += record.len();
+                    if let Err(e) = self.input_stream.insert(record.as_bytes()) {
+                        self.push_error(e, &record);
+                    }
+                }
+                Err(record) => self.push_error("unable to decode new record", &record),
+            }
+        }
+    }
+
+    fn push_error<E: ToString>(&mut self, error: E, record: &str) {
+        self.errors.push(ParseError::text_event_error(
+            "Failed to deserialize table record from PostgreSQL",
+            error,
+            self.num_events,
+            Some(record),
+            None,
+        ));
+    }
+
+    /// Converts `tuple` to a JSON record with the columns of the Feldera
+    /// table.  Unchanged TOASTed values are taken from `old`, if available.
+    ///
+    /// On failure, returns a description of the error.
+    fn tuple_to_json(
+        &self,
+        relation: &RelationBody,
+        tuple: &[TupleValue],
+        old: Option<&[TupleValue]>,
+    ) -> Result<String, String> {
+        let mut record = serde_json::Map::new();
+        for (index, column) in relation.columns.iter().enumerate() {
+            if !self.schema.fields.iter().any(|f| f.name == column.name) {
+                continue;
+            }
+            let value = match (tuple.get(index), old.and_then(|old| old.get(index))) {
+                (Some(TupleValue::Text(text)), _)
+                | (Some(TupleValue::Unchanged), Some(TupleValue::Text(text))) => {
+                    text_to_json(column.type_oid, text)
+                        .map_err(|e| format!("column '{}': {e}", column.name))?
+                }
+                (Some(TupleValue::Null), _)
+                | (Some(TupleValue::Unchanged), Some(TupleValue::Null)) => Value::Null,
+                (Some(TupleValue::Unchanged), _) => {
+                    return Err(format!(
+                        "column '{}': value of unchanged TOASTed column is not available (consider setting REPLICA IDENTITY FULL on the table)",
+                        column.name
+                    ))
+                }
+                (None, _) => return Err(format!("column '{}' is missing", column.name)),
+            };
+            record.insert(column.name.clone(), value);
+        }
+        Ok(Value::Object(record).to_string())
+    }
+
+    /// Returns the number of records read into the input stream so far and
+    /// reports the errors to the consumer.
+    fn finish(mut self) -> usize {
+        let (errors, num_bytes) = self.take_errors_and_bytes();
+        self.endpoint.consumer.parse_errors(errors);
+        self.endpoint
+            .consumer
+            .buffered(self.input_stream.len(), num_bytes);
+        self.input_stream.len()
+    }
+
+    fn take_errors_and_bytes(&mut self) -> (Vec<ParseError>, usize) {
+        (mem::take(&mut self.errors), mem::take(&mut self.num_bytes))
+    }
+}
+
+fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
@@ -1,8 +1,11 @@
+mod cdc;
 mod input;
 mod output;
+mod pgoutput;
 
 #[cfg(test)]
 mod test;
 
+pub use cdc::PostgresCdcInputEndpoint;
 pub use input::PostgresInputEndpoint;
 pub use output::PostgresOutputEndpoint;
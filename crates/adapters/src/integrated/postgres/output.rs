@@ -19,12 +19,29 @@ use crate::{util::indexed_operation_type, ControllerError};
 use anyhow::{anyhow, bail, Result as AnyResult};
 use feldera_adapterlib::transport::{AsyncErrorCallback, Step};
 use feldera_types::{
+    config::FtModel,
     format::{csv::CsvParserConfig, json::JsonFlavor},
     program_schema::{Relation, SqlIdentifier},
-    transport::postgres::PostgresWriterConfig,
+    transport::postgres::{PostgresWriteMode, PostgresWriterConfig},
 };
 use postgres::{Client, NoTls, Statement};
-use tracing::{info_span, span::EnteredSpan};
+use tracing::{debug, info, info_span, span::EnteredSpan};
+
+/// Tracks the last step written to the table, for exactly-once output.
+///
+/// The step is stored in a side table and updated in the same transaction as
+/// the output of the step, so after a restart the connector knows exactly
+/// which of the replayed steps are already in the table.
+struct ProgressTracker {
+    /// Key of this connector in the progress table.
+    connector_id: String,
+
+    /// Statement that records the last step written to the table.
+    update: Statement,
+
+    /// The first step that hasn't been written to the table yet.
+    next_step: Step,
+}
 
 pub struct PostgresOutputEndpoint {
     endpoint_id: EndpointId,
@@ -40,6 +57,10 @@ pub struct PostgresOutputEndpoint {
     controller: Weak<ControllerInner>,
     num_bytes: usize,
     num_rows: usize,
+    /// The step being written.
+    step: Step,
+    /// `Some` if the connector provides exactly-once output.
+    progress: Option<ProgressTracker>,
     _pin: PhantomPinned,
 }
 
@@ -57,8 +78,58 @@ impl PostgresOutputEndpoint {
         key_schema: &Option<Relation>,
         value_schema: &Relation,
         controller: Weak<ControllerInner>,
+    ) -> Result<Self, ControllerError> {
+        let pipeline_config = controller
+            .upgrade()
+            .map(|controller| controller.status.pipeline_config.clone());
+        let exactly_once = pipeline_config.as_ref().is_some_and(|pipeline_config| {
+            pipeline_config.global.fault_tolerance.model == Some(FtModel::ExactlyOnce)
+        });
+
+        // The connector id is the key of this connector's row in the
+        // progress table, which may be shared by connectors of several
+        // pipelines writing to the same database.  Qualifying it with the
+        // pipeline name keeps their rows apart.
+        let connector_id = if exactly_once {
+            let Some(pipeline_name) =
+                pipeline_config.and_then(|pipeline_config| pipeline_config.name)
+            else {
+                return Err(ControllerError::invalid_transport_configuration(
+                    endpoint_name,
+                    "exactly-once output to Postgres requires the pipeline to have a name, which the connector uses to identify its progress in the progress table",
+                ));
+            };
+            Some(format!("feldera-{pipeline_name}-{endpoint_name}"))
+        } else {
+            None
+        };
+
+        Self::new_inner(
+            endpoint_id,
+            endpoint_name,
+            config,
+            key_schema,
+            value_schema,
+            controller,
+            connector_id,
+        )
+    }
+
+    /// Creates the endpoint.  If `connector_id` is `Some`, the endpoint
+    /// provides exactly-once output, recording its progress in the progress
+    /// table under this id.
+    fn new_inner(
+        endpoint_id: EndpointId,
+        endpoint_name: &str,
+        config: &PostgresWriterConfig,
+        key_schema: &Option<Relation>,
+        value_schema: &Relation,
+        controller: Weak<ControllerInner>,
+        connector_id: Option<String>,
     ) -> Result<Self, ControllerError> {
         let table = config.table.to_owned();
+        let mode = config.mode;
+        let progress_table = config.progress_table.to_owned();
 
         let config = postgres::Config::from_str(&config.uri).map_err(|e| {
             ControllerError::invalid_transport_configuration(
@@ -88,9 +159,34 @@ impl PostgresOutputEndpoint {
 
         let err_msg = "\nPlease ensure all field names that are quoted in PostgreSQL are quoted correctly in Feldera as well";
 
+        // In `upsert` mode, inserts and updates are both written with
+        // `INSERT ... ON CONFLICT DO UPDATE`, which overwrites existing rows
+        // with the same key.
+        let on_conflict = match mode {
+            PostgresWriteMode::Insert => String::new(),
+            PostgresWriteMode::Upsert => {
+                let columns = value_schema
+                    .fields
+                    .iter()
+                    .map(|f| f.name.sql_name())
+                    .filter(|f| !keys.contains(f))
+                    .map(|f| format!("{f} = EXCLUDED.{f}"))
+                    .collect::<Vec<_>>();
+                if columns.is_empty() {
+                    format!(" ON CONFLICT ({}) DO NOTHING", keys.join(", "))
+                } else {
+                    format!(
+                        " ON CONFLICT ({}) DO UPDATE SET {}",
+                        keys.join(", "),
+                        columns.join(", ")
+                    )
+                }
+            }
+        };
+
         let insert = {
             let insert = format!(
-                r#"INSERT INTO "{table}" SELECT * FROM jsonb_populate_recordset(NULL::"{table}", $1::jsonb)"#
+                r#"INSERT INTO "{table}" SELECT * FROM jsonb_populate_recordset(NULL::"{table}", $1::jsonb){on_conflict}"#
             );
             client
                 .prepare_typed(&insert, &[postgres::types::Type::VARCHAR])
@@ -129,7 +225,9 @@ impl PostgresOutputEndpoint {
                 })?
         };
 
-        let upsert = {
+        let upsert = if mode == PostgresWriteMode::Upsert {
+            insert.clone()
+        } else {
             let table_alias = "t";
             let new_alias = "n";
             let columns = value_schema
@@ -164,6 +262,12 @@ impl PostgresOutputEndpoint {
                 })?
         };
 
+        let progress = connector_id
+            .map(|connector_id| {
+                Self::init_progress(&mut client, endpoint_name, &progress_table, connector_id)
+            })
+            .transpose()?;
+
         let out = Self {
             endpoint_id,
             endpoint_name: endpoint_name.to_owned(),
@@ -178,6 +282,8 @@ impl PostgresOutputEndpoint {
             num_rows: 0,
             num_bytes: 0,
             value_schema: value_schema.to_owned(),
+            step: 0,
+            progress,
             _pin: PhantomPinned,
         };
 
@@ -186,6 +292,67 @@ impl PostgresOutputEndpoint {
         Ok(out)
     }
 
+    /// Creates the progress table if it doesn't exist and reads the last step
+    /// that this connector wrote to the output table.
+    fn init_progress(
+        client: &mut Client,
+        endpoint_name: &str,
+        progress_table: &str,
+        connector_id: String,
+    ) -> Result<ProgressTracker, ControllerError> {
+        let progress_error = |e: postgres::Error| {
+            ControllerError::output_transport_error(
+                endpoint_name,
+                true,
+                anyhow!("failed to access progress table \"{progress_table}\": {e}"),
+            )
+        };
+
+        client
+            .execute(
+                &format!(
+                    r#"CREATE TABLE IF NOT EXISTS "{progress_table}" (connector_id VARCHAR PRIMARY KEY, step BIGINT NOT NULL)"#
+                ),
+                &[],
+            )
+            .map_err(progress_error)?;
+
+        let next_step = client
+            .query_opt(
+                &format!(r#"SELECT step FROM "{progress_table}" WHERE connector_id = $1"#),
+                &[&connector_id],
+            )
+            .map_err(progress_error)?
+            .map_or(0, |row| row.get::<_, i64>(0) as Step + 1);
+
+        if next_step > 0 {
+            info!(
+                "postgres_output {endpoint_name}: table contains output up to step {}",
+                next_step - 1
+            );
+        }
+
+        let update = client
+            .prepare(&format!(
+                r#"INSERT INTO "{progress_table}" (connector_id, step) VALUES ($1, $2) ON CONFLICT (connector_id) DO UPDATE SET step = EXCLUDED.step"#
+            ))
+            .map_err(progress_error)?;
+
+        Ok(ProgressTracker {
+            connector_id,
+            update,
+            next_step,
+        })
+    }
+
+    /// Returns true if the current step was written to the table in a
+    /// previous run and is being replayed after a restart.
+    fn is_replayed_step(&self) -> bool {
+        self.progress
+            .as_ref()
+            .is_some_and(|progress| self.step < progress.next_step)
+    }
+
     fn view_name(&self) -> &SqlIdentifier {
         &self.value_schema.name
     }
@@ -245,10 +412,19 @@ impl PostgresOutputEndpoint {
         self.exec_statement(self.delete.clone(), value, "delete")
     }
 
+    /// Reports a failure to start or commit a transaction.
+    ///
+    /// These errors are fatal: the output of the step is lost.
+    fn report_error(&self, error: anyhow::Error) {
+        if let Some(controller) = self.controller.upgrade() {
+            controller.output_transport_error(self.endpoint_id, &self.endpoint_name, true, error);
+        }
+    }
+
     pub fn span(&self) -> EnteredSpan {
         info_span!(
             "postgres_output",
-            ft = false,
+            ft = self.progress.is_some(),
             id = self.endpoint_id,
             name = self.endpoint_name,
             pg_table = self.table,
@@ -263,29 +439,8 @@ impl OutputConsumer for PostgresOutputEndpoint {
         usize::pow(2, 20)
     }
 
-    fn batch_start(&mut self, _: Step) {
-        // Safety
-        //
-        // A transaction is a reference to the `client`. The `client`'s lifetime
-        // is longer than that of a transaction, which only lasts for the
-        // duration of a batch.
-        //
-        // We transmute from Transaction<'a> to Transaction<'static> as it is
-        // a reference to the `client` field in [`PostgresOutputEndpoint`].
-        // This means, that moving [`PostgresOutputEndpoint`] after a batch
-        // has been started, will result in `transaction` being a
-        // dangling pointer.
-        //
-        // TODO: Consider [`std::pin::Pin`]ing the integrated conenctor.
-        let transaction: postgres::Transaction<'static> = unsafe {
-            std::mem::transmute(
-                self.client
-                    .transaction()
-                    .expect("postgres: failed to start transaction"),
-            )
-        };
-
-        self.transaction = Some(transaction);
+    fn batch_start(&mut self, step: Step) {
+        OutputEndpoint::batch_start(self, step).unwrap_or_else(|e| self.report_error(e));
     }
 
     fn push_buffer(&mut self, _: &[u8], _: usize) {
@@ -303,20 +458,7 @@ impl OutputConsumer for PostgresOutputEndpoint {
     }
 
     fn batch_end(&mut self) {
-        let transaction: postgres::Transaction<'static> = self.transaction.take().expect(
-            "postgres: unreachable: attempted to commit a transaction that hasn't been started",
-        );
-        transaction
-            .commit()
-            .expect("postgres: failed to commit transaction");
-
-        if let Some(controller) = self.controller.upgrade() {
-            controller.status.output_buffer(
-                self.endpoint_id,
-                std::mem::take(&mut self.num_bytes),
-                std::mem::take(&mut self.num_rows),
-            )
-        }
+        OutputEndpoint::batch_end(self).unwrap_or_else(|e| self.report_error(e));
     }
 }
 
@@ -326,6 +468,10 @@ impl Encoder for PostgresOutputEndpoint {
     }
 
     fn encode(&mut self, batch: &dyn SerBatchReader) -> anyhow::Result<()> {
+        if self.is_replayed_step() {
+            return Ok(());
+        }
+
         let mut insert_buffer: Vec<u8> = vec![b'['];
         let mut delete_buffer: Vec<u8> = vec![b'['];
         let mut upsert_buffer: Vec<u8> = vec![b'['];
@@ -398,11 +544,11 @@ impl Encoder for PostgresOutputEndpoint {
 
 impl OutputEndpoint for PostgresOutputEndpoint {
     fn connect(&mut self, _: AsyncErrorCallback) -> anyhow::Result<()> {
-        todo!()
+        Ok(())
     }
 
     fn max_buffer_size_bytes(&self) -> usize {
-        todo!()
+        OutputConsumer::max_buffer_size_bytes(self)
     }
 
     fn push_buffer(&mut self, buffer: &[u8]) -> anyhow::Result<()> {
@@ -419,15 +565,84 @@ impl OutputEndpoint for PostgresOutputEndpoint {
     }
 
     fn is_fault_tolerant(&self) -> bool {
-        false
+        self.progress.is_some()
     }
 
-    fn batch_start(&mut self, _step: Step) -> AnyResult<()> {
-        todo!()
+    fn batch_start(&mut self, step: Step) -> AnyResult<()> {
+        self.step = step;
+
+        if self.is_replayed_step() {
+            debug!(
+                "postgres_output {}: skipping step {step} that was already written to the table in a previous run",
+                self.endpoint_name
+            );
+            return Ok(());
+        }
+
+        // Safety
+        //
+        // A transaction is a reference to the `client`. The `client`'s lifetime
+        // is longer than that of a transaction, which only lasts for the
+        // duration of a batch.
+        //
+        // We transmute from Transaction<'a> to Transaction<'static> as it is
+        // a reference to the `client` field in [`PostgresOutputEndpoint`].
+        // This means, that moving [`PostgresOutputEndpoint`] after a batch
+        // has been started, will result in `transaction` being a
+        // dangling pointer.
+        //
+        // TODO: Consider [`std::pin::Pin`]ing the integrated conenctor.
+        let transaction: postgres::Transaction<'static> = unsafe {
+            std::mem::transmute(
+                self.client
+                    .transaction()
+                    .map_err(|e| anyhow!("postgres: failed to start transaction: {e}"))?,
+            )
+        };
+
+        self.transaction = Some(transaction);
+        Ok(())
     }
 
     fn batch_end(&mut self) -> AnyResult<()> {
-        todo!()
+        if self.is_replayed_step() {
+            return Ok(());
+        }
+
+        let mut transaction: postgres::Transaction<'static> =
+            self.transaction.take().ok_or_else(|| {
+                anyhow!(
+                    "postgres: unreachable: attempted to commit a transaction that hasn't been started"
+                )
+            })?;
+
+        // Record the step in the same transaction as its output.
+        if let Some(progress) = &self.progress {
+            transaction
+                .execute(
+                    &progress.update,
+                    &[&progress.connector_id, &(self.step as i64)],
+                )
+                .map_err(|e| anyhow!("postgres: failed to update progress table: {e}"))?;
+        }
+
+        transaction
+            .commit()
+            .map_err(|e| anyhow!("postgres: failed to commit transaction: {e}"))?;
+
+        if let Some(progress) = &mut self.progress {
+            progress.next_step = self.step + 1;
+        }
+
+        if let Some(controller) = self.controller.upgrade() {
+            controller.status.output_buffer(
+                self.endpoint_id,
+                std::mem::take(&mut self.num_bytes),
+                std::mem::take(&mut self.num_rows),
+            )
+        }
+
+        Ok(())
     }
 }
 
@@ -438,12 +653,12 @@ mod tests {
     use feldera_adapterlib::errors::journal::ControllerError;
     use feldera_types::{
         program_schema::{ColumnType, Field, Relation, SqlType},
-        transport::postgres::PostgresWriterConfig,
+        transport::postgres::{PostgresWriteMode, PostgresWriterConfig},
     };
     use postgres::NoTls;
 
     use super::PostgresOutputEndpoint;
-    use crate::controller::EndpointId;
+    use crate::{controller::EndpointId, transport::OutputEndpoint};
 
     fn int_field(name: &str) -> Field {
         Field::new(
@@ -495,6 +710,8 @@ mod tests {
         PostgresWriterConfig {
             uri: postgres_url(),
             table: table.into(),
+            mode: PostgresWriteMode::Insert,
+            progress_table: "feldera_output_progress_test".into(),
         }
     }
 
@@ -513,6 +730,48 @@ mod tests {
         )
     }
 
+    /// Creates an endpoint for table `table (id int primary key, s varchar)`.
+    fn create_id_s_endpoint(
+        table: &str,
+        mode: PostgresWriteMode,
+        connector_id: Option<&str>,
+    ) -> PostgresOutputEndpoint {
+        let idx = relation("v1_idx", vec![int_field("id")], false);
+        let main = relation("v1", vec![int_field("id"), varchar_field("s")], true);
+        let config = PostgresWriterConfig {
+            mode,
+            ..make_config(table)
+        };
+        PostgresOutputEndpoint::new_inner(
+            EndpointId::default(),
+            "blah",
+            &config,
+            &Some(idx),
+            &main,
+            Weak::new(),
+            connector_id.map(String::from),
+        )
+        .unwrap()
+    }
+
+    /// Writes `records` to the table in one step.
+    fn write_step(endpoint: &mut PostgresOutputEndpoint, step: u64, records: &str) {
+        OutputEndpoint::batch_start(endpoint, step).unwrap();
+        if !endpoint.is_replayed_step() {
+            endpoint.insert(&mut records.as_bytes().to_vec()).unwrap();
+        }
+        OutputEndpoint::batch_end(endpoint).unwrap();
+    }
+
+    fn query_id_s(client: &mut postgres::Client, table: &str) -> Vec<(i32, String)> {
+        client
+            .query(&format!(r#"SELECT id, s FROM "{table}" ORDER BY id"#), &[])
+            .expect("failed to query table")
+            .into_iter()
+            .map(|row| (row.get(0), row.get(1)))
+            .collect()
+    }
+
     fn postgres_client() -> postgres::Client {
         postgres::Client::connect(&postgres_url(), NoTls).expect("failed to connect to postgres")
     }
@@ -640,4 +899,102 @@ mod tests {
 
         drop_table(&mut client, table);
     }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_postgres_upsert_mode() {
+        let mut client = postgres_client();
+        let table = "03JWRRNVP4CGER2E3SQQKCZFNQ";
+
+        client
+            .execute(
+                &format!(r#"CREATE TABLE "{table}" (id int primary key, s varchar)"#),
+                &[],
+            )
+            .expect("failed to create test table in postgres");
+        client
+            .execute(&format!(r#"INSERT INTO "{table}" VALUES (1, 'old')"#), &[])
+            .expect("failed to populate test table");
+
+        // In `upsert` mode, inserting an existing key overwrites the row.
+        let mut endpoint = create_id_s_endpoint(table, PostgresWriteMode::Upsert, None);
+        write_step(
+            &mut endpoint,
+            0,
+            r#"[{"id": 1, "s": "a"}, {"id": 2, "s": "b"}"#,
+        );
+        write_step(&mut endpoint, 1, r#"[{"id": 2, "s": "c"}"#);
+        drop(endpoint);
+
+        assert_eq!(
+            query_id_s(&mut client, table),
+            vec![(1, "a".to_string()), (2, "c".to_string())]
+        );
+
+        drop_table(&mut client, table);
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_postgres_exactly_once() {
+        let mut client = postgres_client();
+        let table = "04JWRRNVP4CGER2E3SQQKCZFNQ";
+
+        client
+            .execute("DROP TABLE IF EXISTS feldera_output_progress_test", &[])
+            .expect("failed to drop progress table");
+        client
+            .execute(
+                &format!(r#"CREATE TABLE "{table}" (id int primary key, s varchar)"#),
+                &[],
+            )
+            .expect("failed to create test table in postgres");
+
+        let mut endpoint =
+            create_id_s_endpoint(table, PostgresWriteMode::Insert, Some("test_exactly_once"));
+        assert!(endpoint.is_fault_tolerant());
+        write_step(&mut endpoint, 0, r#"[{"id": 1, "s": "a"}"#);
+        write_step(&mut endpoint, 1, r#"[{"id": 2, "s": "b"}"#);
+
+        // Start step 2 and "crash" before committing it.
+        OutputEndpoint::batch_start(&mut endpoint, 2).unwrap();
+        endpoint
+            .insert(&mut br#"[{"id": 3, "s": "c"}"#.to_vec())
+            .unwrap();
+        drop(endpoint);
+
+        assert_eq!(
+            query_id_s(&mut client, table),
+            vec![(1, "a".to_string()), (2, "b".to_string())]
+        );
+
+        // After restart, steps 0 and 1 are skipped, step 2 is written.  Replaying
+        // step 1 would fail with a primary key violation if it wasn't skipped.
+        let mut endpoint =
+            create_id_s_endpoint(table, PostgresWriteMode::Insert, Some("test_exactly_once"));
+        write_step(&mut endpoint, 1, r#"[{"id": 2, "s": "b"}"#);
+        write_step(&mut endpoint, 2, r#"[{"id": 3, "s": "c"}"#);
+        drop(endpoint);
+
+        assert_eq!(
+            query_id_s(&mut client, table),
+            vec![
+                (1, "a".to_string()),
+                (2, "b".to_string()),
+                (3, "c".to_string())
+            ]
+        );
+
+        let step: i64 = client
+            .query_one(
+                "SELECT step FROM feldera_output_progress_test WHERE connector_id = $1",
+                &[&"test_exactly_once"],
+            )
+            .unwrap()
+            .get(0);
+        assert_eq!(step, 2);
+
+        drop_table(&mut client, table);
+        drop_table(&mut client, "feldera_output_progress_test");
+    }
 }
@@ -0,0 +1,537 @@
+//! Decoder for the logical replication messages produced by the `pgoutput`
+//! plugin (protocol version 1), and for the text representation of column
+//! values that these messages carry.
+//!
+//! See <https://www.postgresql.org/docs/current/protocol-logicalrep-message-formats.html>.
+
+use anyhow::{anyhow, bail, Result as AnyResult};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio_postgres::types::{Kind, Type};
+
+/// A decoded `pgoutput` message.
+///
+/// Only the messages that the CDC connector acts on are decoded in full.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum PgOutputMessage {
+    Begin {
+        /// LSN of the commit record of the transaction.
+        final_lsn: u64,
+        xid: u32,
+    },
+    Commit {
+        commit_lsn: u64,
+        /// End LSN of the commit record.
+        end_lsn: u64,
+    },
+    Relation(RelationBody),
+    Insert {
+        relation_id: u32,
+        new: Vec<TupleValue>,
+    },
+    Update {
+        relation_id: u32,
+        /// Old tuple, present only when the replica identity of the table
+        /// includes columns modified by the update, or when the table uses
+        /// `REPLICA IDENTITY FULL`.
+        old: Option<Vec<TupleValue>>,
+        new: Vec<TupleValue>,
+    },
+    Delete {
+        relation_id: u32,
+        old: Vec<TupleValue>,
+    },
+    Truncate {
+        relation_ids: Vec<u32>,
+    },
+    /// Origin, type and logical decoding messages.
+    Other,
+}
+
+/// Description of a table sent before the first change to the table in
+/// each decoding session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct RelationBody {
+    pub id: u32,
+    pub namespace: String,
+    pub name: String,
+    pub columns: Vec<RelationColumn>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct RelationColumn {
+    pub name: String,
+    pub type_oid: u32,
+}
+
+/// A column value in a tuple.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum TupleValue {
+    Null,
+    /// Unchanged TOASTed value; the actual value is not sent.
+    Unchanged,
+    Text(String),
+}
+
+struct Cursor<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, n: usize) -> AnyResult<&'a [u8]> {
+        if self.data.len() < n {
+            bail!("unexpected end of message");
+        }
+        let (head, tail) = self.data.split_at(n);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> AnyResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> AnyResult<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> AnyResult<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> AnyResult<u64> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn cstr(&mut self) -> AnyResult<String> {
+        let len = self
+            .data
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| anyhow!("unterminated string"))?;
+        let s = std::str::from_utf8(self.take(len)?)?.to_string();
+        self.take(1)?;
+        Ok(s)
+    }
+
+    fn tuple(&mut self) -> AnyResult<Vec<TupleValue>> {
+        let ncols = self.u16()?;
+        let mut values = Vec::with_capacity(ncols as usize);
+        for _ in 0..ncols {
+            let value = match self.u8()? {
+                b'n' => TupleValue::Null,
+                b'u' => TupleValue::Unchanged,
+                b't' => {
+                    let len = self.u32()? as usize;
+                    TupleValue::Text(std::str::from_utf8(self.take(len)?)?.to_string())
+                }
+                tag => bail!("unexpected tuple value tag '{}'", tag as char),
+            };
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+impl PgOutputMessage {
+    pub(super) fn decode(data: &[u8]) -> AnyResult<Self> {
+        let mut cursor = Cursor { data };
+        let message = match cursor.u8()? {
+            b'B' => {
+                let final_lsn = cursor.u64()?;
+                let _commit_timestamp = cursor.u64()?;
+                let xid = cursor.u32()?;
+                Self::Begin { final_lsn, xid }
+            }
+            b'C' => {
+                let _flags = cursor.u8()?;
+                let commit_lsn = cursor.u64()?;
+                let end_lsn = cursor.u64()?;
+                Self::Commit {
+                    commit_lsn,
+                    end_lsn,
+                }
+            }
+            b'R' => {
+                let id = cursor.u32()?;
+                let namespace = cursor.cstr()?;
+                let name = cursor.cstr()?;
+                let _replica_identity = cursor.u8()?;
+                let ncols = cursor.u16()?;
+                let mut columns = Vec::with_capacity(ncols as usize);
+                for _ in 0..ncols {
+                    let _flags = cursor.u8()?;
+                    let name = cursor.cstr()?;
+                    let type_oid = cursor.u32()?;
+                    let _type_modifier = cursor.u32()?;
+                    columns.push(RelationColumn { name, type_oid });
+                }
+                Self::Relation(RelationBody {
+                    id,
+                    namespace,
+                    name,
+                    columns,
+                })
+            }
+            b'I' => {
+                let relation_id = cursor.u32()?;
+                match cursor.u8()? {
+                    b'N' => Self::Insert {
+                        relation_id,
+                        new: cursor.tuple()?,
+                    },
+                    tag => bail!("unexpected tag '{}' in INSERT message", tag as char),
+                }
+            }
+            b'U' => {
+                let relation_id = cursor.u32()?;
+                let (old, new) = match cursor.u8()? {
+                    b'K' | b'O' => {
+                        let old = cursor.tuple()?;
+                        match cursor.u8()? {
+                            b'N' => (Some(old), cursor.tuple()?),
+                            tag => bail!("unexpected tag '{}' in UPDATE message", tag as char),
+                        }
+                    }
+                    b'N' => (None, cursor.tuple()?),
+                    tag => bail!("unexpected tag '{}' in UPDATE message", tag as char),
+                };
+                Self::Update {
+                    relation_id,
+                    old,
+                    new,
+                }
+            }
+            b'D' => {
+                let relation_id = cursor.u32()?;
+                match cursor.u8()? {
+                    b'K' | b'O' => Self::Delete {
+                        relation_id,
+                        old: cursor.tuple()?,
+                    },
+                    tag => bail!("unexpected tag '{}' in DELETE message", tag as char),
+                }
+            }
+            b'T' => {
+                let nrelations = cursor.u32()?;
+                let _options = cursor.u8()?;
+                let relation_ids = (0..nrelations)
+                    .map(|_| cursor.u32())
+                    .collect::<AnyResult<Vec<_>>>()?;
+                Self::Truncate { relation_ids }
+            }
+            b'O' | b'Y' | b'M' => Self::Other,
+            tag => bail!("unknown pgoutput message type '{}'", tag as char),
+        };
+        Ok(message)
+    }
+}
+
+/// Snapshot of the set of transactions visible to a Postgres transaction, in
+/// the format returned by `pg_current_snapshot()`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) struct PgTxnSnapshot {
+    pub xmin: u32,
+    pub xmax: u32,
+    pub xip: Vec<u32>,
+}
+
+impl PgTxnSnapshot {
+    /// Parses the text representation `xmin:xmax:xip1,xip2,...`.
+    ///
+    /// Snapshot ids are 64-bit, epoch-extended transaction ids; we only keep
+    /// the 32-bit transaction id, which is what `pgoutput` reports.
+    pub(super) fn parse(s: &str) -> AnyResult<Self> {
+        let xid = |s: &str| -> AnyResult<u32> {
+            let xid = s
+                .trim()
+                .parse::<u64>()
+                .map_err(|e| anyhow!("invalid transaction id '{s}': {e}"))?;
+            Ok(xid as u32)
+        };
+        let mut parts = s.split(':');
+        let (Some(xmin), Some(xmax), Some(xip), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            bail!("invalid transaction snapshot '{s}'");
+        };
+        Ok(Self {
+            xmin: xid(xmin)?,
+            xmax: xid(xmax)?,
+            xip: xip
+                .split(',')
+                .filter(|s| !s.is_empty())
+                .map(xid)
+                .collect::<AnyResult<Vec<_>>>()?,
+        })
+    }
+
+    /// Returns true if transaction `xid` had committed when the snapshot was
+    /// taken.
+    pub(super) fn is_visible(&self, xid: u32) -> bool {
+        // Transaction ids wrap around, so compare them modulo 2^32, the same way
+        // Postgres does.
+        let precedes = |a: u32, b: u32| (a.wrapping_sub(b) as i32) < 0;
+        precedes(xid, self.xmin) || (precedes(xid, self.xmax) && !self.xip.contains(&xid))
+    }
+}
+
+/// Converts the text representation of a value of Postgres type `type_oid` to
+/// JSON accepted by the `datagen` JSON flavor.
+pub(super) fn text_to_json(type_oid: u32, text: &str) -> Result<Value, String> {
+    let Some(ty) = Type::from_oid(type_oid) else {
+        return Err(format!("unsupported column type (OID {type_oid})"));
+    };
+    type_text_to_json(&ty, text)
+}
+
+fn type_text_to_json(ty: &Type, text: &str) -> Result<Value, String> {
+    match *ty {
+        Type::BOOL => Ok(Value::Bool(text == "t")),
+        Type::INT2 | Type::INT4 | Type::INT8 | Type::OID => text
+            .parse::<i64>()
+            .map(Value::from)
+            .map_err(|e| format!("invalid integer '{text}': {e}")),
+        Type::FLOAT4 | Type::FLOAT8 => text
+            .parse::<f64>()
+            .map(|v| json!(v))
+            .map_err(|e| format!("invalid floating point value '{text}': {e}")),
+        Type::TIMESTAMP => NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f")
+            .map(|v| Value::String(Utc.from_utc_datetime(&v).to_rfc3339()))
+            .map_err(|e| format!("invalid timestamp '{text}': {e}")),
+        Type::TIMESTAMPTZ => DateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f%#z")
+            .map(|v| Value::String(v.with_timezone(&Utc).to_rfc3339()))
+            .map_err(|e| format!("invalid timestamp '{text}': {e}")),
+        Type::BYTEA => decode_bytea(text).map(|v| json!(v)),
+        Type::JSON | Type::JSONB => {
+            serde_json::from_str(text).map_err(|e| format!("invalid JSON value: {e}"))
+        }
+        Type::NUMERIC
+        | Type::BPCHAR
+        | Type::CHAR
+        | Type::VARCHAR
+        | Type::TEXT
+        | Type::NAME
+        | Type::DATE
+        | Type::TIME
+        | Type::UUID => Ok(Value::String(text.to_string())),
+        _ => match ty.kind() {
+            Kind::Array(element_type) => parse_array_literal(text)?
+                .into_iter()
+                .map(|element| match element {
+                    None => Ok(Value::Null),
+                    Some(element) => type_text_to_json(element_type, &element),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Array),
+            _ => Err(format!("unsupported column type '{ty}'")),
+        },
+    }
+}
+
+/// Decodes a `bytea` value in the `hex` output format, e.g., `\xdeadbeef`.
+fn decode_bytea(text: &str) -> Result<Vec<u8>, String> {
+    let hex = text.strip_prefix("\\x").ok_or_else(|| {
+        format!("invalid bytea value '{text}': only the 'hex' format is supported")
+    })?;
+    if hex.len() % 2 != 0 {
+        return Err(format!("invalid bytea value '{text}'"));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|e| format!("invalid bytea value '{text}': {e}"))
+        })
+        .collect()
+}
+
+/// Parses a one-dimensional array literal, e.g., `{1,NULL,"a b"}`, into
+/// the text representations of its elements.
+fn parse_array_literal(text: &str) -> Result<Vec<Option<String>>, String> {
+    let inner = text
+        .strip_prefix('{')
+        .and_then(|t| t.strip_suffix('}'))
+        .ok_or_else(|| format!("invalid array value '{text}'"))?;
+
+    let mut elements = Vec::new();
+    if inner.is_empty() {
+        return Ok(elements);
+    }
+
+    let mut chars = inner.chars().peekable();
+    loop {
+        let mut element = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('\\') => element.push(
+                        chars
+                            .next()
+                            .ok_or_else(|| format!("invalid array value '{text}'"))?,
+                    ),
+                    Some('"') => break,
+                    Some(c) => element.push(c),
+                    None => return Err(format!("invalid array value '{text}'")),
+                }
+            }
+            elements.push(Some(element));
+        } else {
+            while let Some(&c) = chars.peek() {
+                match c {
+                    ',' => break,
+                    '{' => return Err("multidimensional arrays are not supported".to_string()),
+                    _ => element.push(c),
+                }
+                chars.next();
+            }
+            elements.push((element != "NULL").then_some(element));
+        }
+
+        match chars.next() {
+            None => break,
+            Some(',') => continue,
+            Some(c) => {
+                return Err(format!(
+                    "unexpected character '{c}' in array value '{text}'"
+                ))
+            }
+        }
+    }
+
+    Ok(elements)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_array_literal, text_to_json, PgOutputMessage, PgTxnSnapshot, TupleValue};
+    use serde_json::json;
+    use tokio_postgres::types::Type;
+
+    fn tuple(values: &[Option<&str>]) -> Vec<u8> {
+        let mut data = (values.len() as u16).to_be_bytes().to_vec();
+        for value in values {
+            match value {
+                None => data.push(b'n'),
+                Some(value) => {
+                    data.push(b't');
+                    data.extend((value.len() as u32).to_be_bytes());
+                    data.extend(value.as_bytes());
+                }
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn test_decode_messages() {
+        let mut begin = vec![b'B'];
+        begin.extend(0x1234u64.to_be_bytes());
+        begin.extend(0u64.to_be_bytes());
+        begin.extend(42u32.to_be_bytes());
+        assert_eq!(
+            PgOutputMessage::decode(&begin).unwrap(),
+            PgOutputMessage::Begin {
+                final_lsn: 0x1234,
+                xid: 42
+            }
+        );
+
+        let mut relation = vec![b'R'];
+        relation.extend(16384u32.to_be_bytes());
+        relation.extend(b"public\0orders\0");
+        relation.push(b'd');
+        relation.extend(2u16.to_be_bytes());
+        for (name, oid) in [("id", Type::INT4.oid()), ("s", Type::TEXT.oid())] {
+            relation.push(1);
+            relation.extend(name.as_bytes());
+            relation.push(0);
+            relation.extend(oid.to_be_bytes());
+            relation.extend(u32::MAX.to_be_bytes());
+        }
+        let PgOutputMessage::Relation(body) = PgOutputMessage::decode(&relation).unwrap() else {
+            panic!("expected a relation message")
+        };
+        assert_eq!(body.id, 16384);
+        assert_eq!(body.namespace, "public");
+        assert_eq!(body.name, "orders");
+        assert_eq!(body.columns.len(), 2);
+        assert_eq!(body.columns[1].type_oid, Type::TEXT.oid());
+
+        let mut update = vec![b'U'];
+        update.extend(16384u32.to_be_bytes());
+        update.push(b'O');
+        update.extend(tuple(&[Some("1"), Some("foo")]));
+        update.push(b'N');
+        update.extend(tuple(&[Some("1"), None]));
+        assert_eq!(
+            PgOutputMessage::decode(&update).unwrap(),
+            PgOutputMessage::Update {
+                relation_id: 16384,
+                old: Some(vec![
+                    TupleValue::Text("1".to_string()),
+                    TupleValue::Text("foo".to_string())
+                ]),
+                new: vec![TupleValue::Text("1".to_string()), TupleValue::Null],
+            }
+        );
+
+        let mut commit = vec![b'C', 0];
+        commit.extend(0x1234u64.to_be_bytes());
+        commit.extend(0x1260u64.to_be_bytes());
+        commit.extend(0u64.to_be_bytes());
+        assert_eq!(
+            PgOutputMessage::decode(&commit).unwrap(),
+            PgOutputMessage::Commit {
+                commit_lsn: 0x1234,
+                end_lsn: 0x1260
+            }
+        );
+
+        assert!(PgOutputMessage::decode(&commit[0..10]).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_visibility() {
+        let snapshot = PgTxnSnapshot::parse("100:105:101,103").unwrap();
+        assert!(snapshot.is_visible(99));
+        assert!(snapshot.is_visible(100));
+        assert!(!snapshot.is_visible(101));
+        assert!(snapshot.is_visible(102));
+        assert!(!snapshot.is_visible(103));
+        assert!(!snapshot.is_visible(105));
+        assert!(!snapshot.is_visible(200));
+
+        let snapshot = PgTxnSnapshot::parse("4294967300:4294967300:").unwrap();
+        assert!(snapshot.is_visible(3));
+        assert!(!snapshot.is_visible(4));
+    }
+
+    #[test]
+    fn test_text_to_json() {
+        assert_eq!(text_to_json(Type::BOOL.oid(), "t"), Ok(json!(true)));
+        assert_eq!(text_to_json(Type::INT8.oid(), "-5"), Ok(json!(-5)));
+        assert_eq!(text_to_json(Type::NUMERIC.oid(), "1.50"), Ok(json!("1.50")));
+        assert_eq!(
+            text_to_json(Type::TIMESTAMP.oid(), "2025-01-31 12:34:56.5"),
+            Ok(json!("2025-01-31T12:34:56.500+00:00"))
+        );
+        assert_eq!(
+            text_to_json(Type::BYTEA.oid(), "\\xdead"),
+            Ok(json!([0xde, 0xad]))
+        );
+        assert_eq!(
+            text_to_json(Type::TEXT_ARRAY.oid(), r#"{a,"b c",NULL,"d\"e"}"#),
+            Ok(json!(["a", "b c", null, "d\"e"]))
+        );
+        assert_eq!(
+            text_to_json(Type::INT4_ARRAY.oid(), "{1,2}"),
+            Ok(json!([1, 2]))
+        );
+        assert!(text_to_json(Type::INT4.oid(), "x").is_err());
+        assert!(parse_array_literal("{{1,2},{3,4}}").is_err());
+        assert_eq!(parse_array_literal("{}"), Ok(vec![]));
+    }
+}
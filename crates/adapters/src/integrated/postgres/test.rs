@@ -19,7 +19,7 @@ use postgres::NoTls;
 use serde_json::{json, Value};
 use serial_test::serial;
 use std::{collections::BTreeMap, io::Write, str::FromStr};
-use tempfile::NamedTempFile;
+use tempfile::{NamedTempFile, TempDir};
 
 use crate::{
     test::{test_circuit, wait, TestStruct},
@@ -1097,3 +1097,400 @@ outputs:
         .execute(&format!("DROP TABLE {table_name}"), &[])
         .unwrap();
 }
+
+/// Panics if the server can't be used for CDC tests.
+fn assert_logical_wal_level(client: &mut postgres::Client) {
+    let wal_level: String = client.query_one("SHOW wal_level", &[]).unwrap().get(0);
+    assert_eq!(
+        wal_level, "logical",
+        "the CDC connector requires a Postgres server configured with wal_level=logical"
+    );
+}
+
+#[test]
+#[serial]
+fn test_pg_cdc() {
+    let url = postgres_url();
+    let source_table = "cdc_source";
+    let sink_table = "cdc_sink";
+    let publication = "cdc_test_publication";
+    let slot = "cdc_test_slot";
+
+    let mut client = postgres::Client::connect(&url, NoTls).expect("failed to connect to postgres");
+
+    assert_logical_wal_level(&mut client);
+
+    for table_name in [source_table, sink_table] {
+        client
+            .batch_execute(&format!(
+                r#"DROP TABLE IF EXISTS {table_name};
+CREATE TABLE {table_name} (
+    id int primary key,
+    b bool not null,
+    i int8,
+    s varchar not null
+);"#
+            ))
+            .expect("failed to create test table in postgres");
+    }
+    client
+        .batch_execute(&format!(
+            r#"ALTER TABLE {source_table} REPLICA IDENTITY FULL;
+DROP PUBLICATION IF EXISTS {publication};
+CREATE PUBLICATION {publication} FOR TABLE {source_table};
+SELECT pg_drop_replication_slot(slot_name) FROM pg_replication_slots WHERE slot_name = '{slot}';
+INSERT INTO {source_table} VALUES (1, true, NULL, 'test'), (2, false, 1, 'test');"#
+        ))
+        .expect("failed to prepare source table");
+
+    let idx = "v1_idx";
+    let schema = TestStruct::schema();
+    let config_str = format!(
+        r#"
+name: test
+workers: 4
+inputs:
+  cdc:
+    stream: test_input1
+    transport:
+      name: postgres_cdc_input
+      config:
+        uri: {url}
+        table: {source_table}
+        publication: {publication}
+        slot: {slot}
+        poll_interval_ms: 100
+outputs:
+  test_output1:
+    stream: test_output1
+    transport:
+      name: postgres_output
+      config:
+        uri: {url}
+        table: {sink_table}
+    index: {idx}
+"#
+    );
+
+    let config: PipelineConfig = serde_yaml::from_str(&config_str).unwrap();
+
+    let (err_sender, err_receiver) = crossbeam::channel::unbounded();
+    let controller = Controller::with_config(
+        move |workers| {
+            Ok({
+                let (circuit, catalog) = Runtime::init_circuit(workers, move |circuit| {
+                    let mut catalog = Catalog::new();
+                    let (input, hinput) = circuit.add_input_zset::<TestStruct>();
+
+                    let input_schema = serde_json::to_string(&Relation::new(
+                        "test_input1".into(),
+                        schema.clone(),
+                        false,
+                        BTreeMap::new(),
+                    ))
+                    .unwrap();
+
+                    let output_schema = serde_json::to_string(&Relation::new(
+                        "test_output1".into(),
+                        schema,
+                        false,
+                        BTreeMap::new(),
+                    ))
+                    .unwrap();
+
+                    catalog.register_materialized_input_zset::<_, TestStruct>(
+                        input.clone(),
+                        hinput,
+                        &input_schema,
+                    );
+
+                    #[derive(Clone, Debug, Eq, PartialEq, Default)]
+                    pub struct KeyStruct {
+                        field0: u32,
+                    }
+                    impl From<KeyStruct> for Tup1<u32> {
+                        fn from(t: KeyStruct) -> Self {
+                            Tup1::new(t.field0)
+                        }
+                    }
+                    impl From<Tup1<u32>> for KeyStruct {
+                        fn from(t: Tup1<u32>) -> Self {
+                            Self { field0: t.0 }
+                        }
+                    }
+                    deserialize_table_record!(KeyStruct["v1_idx", 1] {
+                        (field0, "id", false, u32, None)
+                    });
+                    serialize_table_record!(KeyStruct[1]{
+                        field0["id"]: u32
+                    });
+
+                    let indexed_input = input.map_index(|r| (Tup1(r.id), r.to_owned()));
+
+                    catalog
+                        .register_materialized_output_zset::<_, TestStruct>(input, &output_schema);
+
+                    catalog
+                        .register_index::<Tup1<u32>, KeyStruct, TestStruct, TestStruct>(
+                            indexed_input.clone(),
+                            &SqlIdentifier::from(idx),
+                            &SqlIdentifier::from("test_output1"),
+                            &[&SqlIdentifier::from("id")],
+                        )
+                        .expect("failed to register index");
+
+                    Ok(catalog)
+                })
+                .unwrap();
+                (circuit, Box::new(catalog))
+            })
+        },
+        &config,
+        Box::new(move |e| {
+            let msg = format!("postgres_cdc_test: error: {e}");
+            println!("{msg}");
+            err_sender.send(msg).unwrap()
+        }),
+    )
+    .unwrap();
+
+    controller.start();
+
+    let sink_contents = |client: &mut postgres::Client| -> Vec<TestStruct> {
+        client
+            .query(
+                &format!("SELECT id, b, i, s FROM {sink_table} ORDER BY id"),
+                &[],
+            )
+            .unwrap()
+            .into_iter()
+            .map(|row| TestStruct {
+                id: row.get::<_, i32>(0) as u32,
+                b: row.get(1),
+                i: row.get(2),
+                s: row.get(3),
+            })
+            .collect()
+    };
+
+    // The initial snapshot.
+    let expected = vec![
+        TestStruct {
+            id: 1,
+            b: true,
+            i: None,
+            s: "test".to_owned(),
+        },
+        TestStruct {
+            id: 2,
+            b: false,
+            i: Some(1),
+            s: "test".to_owned(),
+        },
+    ];
+    wait(
+        || sink_contents(&mut client) == expected || !err_receiver.is_empty(),
+        10_000,
+    )
+    .expect("timeout: snapshot was not ingested");
+    assert!(err_receiver.is_empty());
+
+    // Changes that follow the snapshot.
+    client
+        .batch_execute(&format!(
+            r#"INSERT INTO {source_table} VALUES (3, true, 3, 'new');
+UPDATE {source_table} SET s = 'updated' WHERE id = 1;
+DELETE FROM {source_table} WHERE id = 2;"#
+        ))
+        .unwrap();
+
+    let expected = vec![
+        TestStruct {
+            id: 1,
+            b: true,
+            i: None,
+            s: "updated".to_owned(),
+        },
+        TestStruct {
+            id: 3,
+            b: true,
+            i: Some(3),
+            s: "new".to_owned(),
+        },
+    ];
+    wait(
+        || sink_contents(&mut client) == expected || !err_receiver.is_empty(),
+        10_000,
+    )
+    .expect("timeout: changes were not ingested");
+    assert!(err_receiver.is_empty());
+
+    controller.stop().unwrap();
+
+    client
+        .batch_execute(&format!(
+            r#"SELECT pg_drop_replication_slot('{slot}');
+DROP PUBLICATION {publication};
+DROP TABLE {source_table};
+DROP TABLE {sink_table};"#
+        ))
+        .unwrap();
+}
+
+/// Restarts a fault-tolerant pipeline that reads from the CDC connector and
+/// checks that it resumes from the checkpointed position without losing or
+/// duplicating changes.
+#[test]
+#[serial]
+fn test_pg_cdc_restart() {
+    let url = postgres_url();
+    let source_table = "cdc_restart_source";
+    let publication = "cdc_restart_publication";
+    let slot = "cdc_restart_slot";
+
+    let mut client = postgres::Client::connect(&url, NoTls).expect("failed to connect to postgres");
+    assert_logical_wal_level(&mut client);
+
+    client
+        .batch_execute(&format!(
+            r#"DROP TABLE IF EXISTS {source_table};
+CREATE TABLE {source_table} (
+    id int primary key,
+    b bool not null,
+    i int8,
+    s varchar not null
+);
+DROP PUBLICATION IF EXISTS {publication};
+CREATE PUBLICATION {publication} FOR TABLE {source_table};
+SELECT pg_drop_replication_slot(slot_name) FROM pg_replication_slots WHERE slot_name = '{slot}';"#
+        ))
+        .expect("failed to prepare source table");
+    let mut insert = |ids: std::ops::Range<u32>| {
+        for id in ids {
+            client
+                .execute(
+                    &format!("INSERT INTO {source_table} VALUES ($1, false, NULL, '')"),
+                    &[&(id as i32)],
+                )
+                .unwrap();
+        }
+    };
+
+    let tempdir = TempDir::new().unwrap();
+    let storage_dir = tempdir.path().join("storage");
+    std::fs::create_dir(&storage_dir).unwrap();
+    let output_path = tempdir.path().join("output.csv");
+
+    let config_str = format!(
+        r#"
+name: test
+workers: 4
+storage_config:
+    path: {storage_dir:?}
+storage: true
+fault_tolerance: {{}}
+clock_resolution_usecs: null
+inputs:
+    cdc:
+        stream: test_input1
+        transport:
+            name: postgres_cdc_input
+            config:
+                uri: {url}
+                table: {source_table}
+                publication: {publication}
+                slot: {slot}
+                poll_interval_ms: 100
+                max_changes_per_poll: 1
+outputs:
+    test_output1:
+        stream: test_output1
+        transport:
+            name: file_output
+            config:
+                path: {output_path:?}
+        format:
+            name: csv
+"#
+    );
+    let config: PipelineConfig = serde_yaml::from_str(&config_str).unwrap();
+
+    let start = || {
+        let controller = Controller::with_config(
+            |circuit_config| {
+                Ok(test_circuit::<TestStruct>(
+                    circuit_config,
+                    &TestStruct::schema(),
+                    &[Some("output")],
+                ))
+            },
+            &config,
+            Box::new(|e| panic!("postgres_cdc_restart_test: error: {e}")),
+        )
+        .unwrap();
+        controller.start();
+        controller
+    };
+    let wait_for_output = |controller: &Controller, expected: u64| {
+        let transmitted = || {
+            controller
+                .status()
+                .output_status()
+                .get(&0)
+                .unwrap()
+                .transmitted_records()
+        };
+        wait(|| transmitted() >= expected, 10_000).unwrap_or_else(|_| {
+            panic!("timeout: received {} of {expected} records", transmitted())
+        });
+
+        // Give the pipeline a chance to output duplicates, if there's a bug.
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        assert_eq!(transmitted(), expected);
+    };
+
+    // Ingest the snapshot and some changes, checkpoint, then ingest more
+    // changes that are only in the journal.
+    insert(0..2);
+    let controller = start();
+    wait_for_output(&controller, 2);
+    insert(2..5);
+    wait_for_output(&controller, 5);
+    controller.checkpoint().unwrap();
+    insert(5..7);
+    wait_for_output(&controller, 7);
+    controller.stop().unwrap();
+
+    // Changes made while the pipeline is stopped.
+    insert(7..10);
+
+    // After restarting, the pipeline replays the steps after the checkpoint
+    // and then reads the new changes.  The file output connector is not fault
+    // tolerant and truncates its output when it starts, so it should contain
+    // exactly the records that follow the checkpoint.
+    let controller = start();
+    wait_for_output(&controller, 5);
+    controller.stop().unwrap();
+
+    let mut actual = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_path(&output_path)
+        .unwrap()
+        .deserialize::<(TestStruct, i32)>()
+        .map(|res| {
+            let (val, weight) = res.unwrap();
+            assert_eq!(weight, 1);
+            val
+        })
+        .collect::<Vec<_>>();
+    actual.sort();
+    assert_eq!(actual, (5..10).map(TestStruct::for_id).collect::<Vec<_>>());
+
+    client
+        .batch_execute(&format!(
+            r#"SELECT pg_drop_replication_slot('{slot}');
+DROP PUBLICATION {publication};
+DROP TABLE {source_table};"#
+        ))
+        .unwrap();
+}
@@ -0,0 +1,750 @@
+//! Arrow Flight service.
+//!
+//! The service exposes the tables and views of the pipeline over the Arrow
+//! Flight (gRPC) protocol, as an alternative to the `/ingress` and `/egress`
+//! endpoints for clients that exchange data in the Arrow format:
+//!
+//! - `DoPut` pushes record batches to a table.  The flight descriptor is
+//!   either a path consisting of the name of the table, or a command holding a
+//!   JSON-encoded [`FlightPutCommand`].  The service responds to each record
+//!   batch with a `PutResult` whose metadata holds a JSON-encoded
+//!   [`CompletionTokenResponse`].
+//!
+//! - `DoGet` streams changes to a table or view, or the contents of a
+//!   materialized table or view.  The ticket holds a JSON-encoded
+//!   [`FlightTicket`].
+//!
+//! - `GetFlightInfo` and `GetSchema` return the Arrow schema of the table or
+//!   view named by a path descriptor.
+//!
+//! Like the HTTP API, the service reuses the machinery of HTTP connectors, with
+//! data encoded in the `arrow_ipc` format.
+
+use super::{create_http_input_endpoint, missing_controller_error, PipelineError, ServerState};
+use crate::controller::EndpointId;
+use crate::format::parquet::relation_to_arrow_fields;
+use crate::transport::http::{HttpInputEndpoint, HttpOutputTransport};
+use crate::{
+    AsyncErrorCallback, ConnectorConfig, ControllerError, FormatConfig, OutputEndpoint,
+    OutputEndpointConfig,
+};
+use actix_web::{http::StatusCode, web::Data as WebData, ResponseError};
+use anyhow::{bail, Result as AnyResult};
+use arrow::datatypes::{DataType, Field as ArrowField, Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::ipc::reader::StreamReader;
+use arrow::ipc::writer::{IpcWriteOptions, StreamWriter};
+use arrow::record_batch::RecordBatch;
+use arrow_flight::{
+    encode::FlightDataEncoderBuilder,
+    error::FlightError,
+    flight_descriptor::DescriptorType,
+    flight_service_server::{FlightService, FlightServiceServer},
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
+    HandshakeRequest, HandshakeResponse, PollInfo, PutResult, SchemaAsIpc, SchemaResult, Ticket,
+};
+use async_stream::stream;
+use bytes::Bytes;
+use dbsp::circuit::tokio::TOKIO;
+use feldera_types::completion_token::CompletionTokenResponse;
+use feldera_types::config::default_max_batch_size;
+use feldera_types::flight::{FlightPutCommand, FlightTicket, FlightTicketMode};
+use feldera_types::format::arrow_ipc::{ArrowIpcEncoderConfig, ArrowIpcParserConfig};
+use feldera_types::program_schema::{Relation, SqlIdentifier};
+use futures::stream::{self, BoxStream};
+use futures::{StreamExt, TryStreamExt};
+use std::{
+    borrow::Cow,
+    collections::{hash_map::Entry, HashMap},
+    io::{Cursor, Error as IoError},
+    net::ToSocketAddrs,
+    sync::{Arc, Weak},
+};
+use tokio::sync::{mpsc, Mutex};
+use tonic::{
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status, Streaming,
+};
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+/// Maximum number of Arrow IPC streams buffered by a `DoGet` request.
+const MAX_BUFFERS: usize = 100;
+
+/// Starts the Arrow Flight service on `bind_address:port`.
+///
+/// The service runs in the background until the process terminates.
+pub(super) fn start_flight_server(
+    bind_address: &str,
+    port: u16,
+    state: WebData<ServerState>,
+) -> Result<(), ControllerError> {
+    let addr = (bind_address, port)
+        .to_socket_addrs()
+        .and_then(|mut addrs| {
+            addrs
+                .next()
+                .ok_or_else(|| IoError::other("address resolved to an empty list"))
+        })
+        .map_err(|e| {
+            ControllerError::io_error(
+                format!("resolving Arrow Flight bind address '{bind_address}'"),
+                e,
+            )
+        })?;
+
+    // `TcpIncoming` registers the listener with the runtime it is created in.
+    let incoming = {
+        let _guard = TOKIO.enter();
+        TcpIncoming::new(addr, true, None)
+    }
+    .map_err(|e| {
+        ControllerError::io_error(
+            format!("binding Arrow Flight service to TCP port {port}"),
+            IoError::other(e),
+        )
+    })?;
+
+    let service = FlightServiceServer::new(PipelineFlightService::new(state));
+    TOKIO.spawn(async move {
+        info!("Started Arrow Flight service on port {port}");
+        if let Err(e) = Server::builder()
+            .add_service(service)
+            .serve_with_incoming(incoming)
+            .await
+        {
+            error!("Arrow Flight service failed: {e}");
+        }
+    });
+    Ok(())
+}
+
+/// Converts a `PipelineError` into the gRPC status with the closest meaning.
+fn error_to_status(error: PipelineError) -> Status {
+    let message = error.to_string();
+    match error.status_code() {
+        StatusCode::BAD_REQUEST => Status::invalid_argument(message),
+        StatusCode::NOT_FOUND => Status::not_found(message),
+        StatusCode::TOO_MANY_REQUESTS => Status::resource_exhausted(message),
+        StatusCode::SERVICE_UNAVAILABLE | StatusCode::GONE => Status::unavailable(message),
+        StatusCode::NOT_IMPLEMENTED => Status::unimplemented(message),
+        _ => Status::internal(message),
+    }
+}
+
+/// Returns the name of the table or view named by a path descriptor.
+fn descriptor_path(descriptor: &FlightDescriptor) -> Result<&str, Status> {
+    match (descriptor.r#type(), descriptor.path.as_slice()) {
+        (DescriptorType::Path, [name]) => Ok(name.as_str()),
+        _ => Err(Status::invalid_argument(
+            "flight descriptor must be a path consisting of the name of a table or view",
+        )),
+    }
+}
+
+/// Returns the Arrow schema of `relation`, followed by `weight_column`, if
+/// specified.
+fn relation_schema(relation: &Relation, weight_column: Option<&str>) -> SchemaRef {
+    let mut fields = relation_to_arrow_fields(&relation.fields, false);
+    if let Some(weight_column) = weight_column {
+        fields.push(ArrowField::new(weight_column, DataType::Int64, false));
+    }
+    Arc::new(Schema::new(fields))
+}
+
+/// Output endpoint that forwards the Arrow IPC streams produced by the encoder
+/// to a `DoGet` response.
+struct FlightOutputEndpoint {
+    sender: mpsc::Sender<Bytes>,
+    backpressure: bool,
+}
+
+impl OutputEndpoint for FlightOutputEndpoint {
+    fn connect(&mut self, _async_error_callback: AsyncErrorCallback) -> AnyResult<()> {
+        Ok(())
+    }
+
+    fn max_buffer_size_bytes(&self) -> usize {
+        usize::MAX
+    }
+
+    fn push_buffer(&mut self, buffer: &[u8]) -> AnyResult<()> {
+        let buffer = Bytes::copy_from_slice(buffer);
+        // A failure means that the client has disconnected or, without
+        // backpressure, that it is not keeping up.
+        if self.backpressure {
+            let _ = self.sender.blocking_send(buffer);
+        } else {
+            let _ = self.sender.try_send(buffer);
+        }
+        Ok(())
+    }
+
+    fn push_key(
+        &mut self,
+        _key: Option<&[u8]>,
+        _val: Option<&[u8]>,
+        _headers: &[(&str, Option<&[u8]>)],
+    ) -> AnyResult<()> {
+        bail!("Arrow Flight output endpoint does not support key-value pairs")
+    }
+
+    fn is_fault_tolerant(&self) -> bool {
+        false
+    }
+}
+
+/// Disconnects the output endpoint of a `DoGet` request when its response
+/// stream is dropped.
+struct OutputEndpointGuard {
+    state: Weak<ServerState>,
+    endpoint_id: EndpointId,
+}
+
+impl Drop for OutputEndpointGuard {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.upgrade() {
+            if let Ok(guard) = state.controller.lock() {
+                if let Some(controller) = guard.as_ref() {
+                    controller.disconnect_output(&self.endpoint_id);
+                    controller.unregister_api_connection();
+                }
+            }
+        }
+    }
+}
+
+struct PipelineFlightService {
+    state: WebData<ServerState>,
+
+    /// Input endpoints created by `DoPut` requests, indexed by table name and
+    /// weight column.  All requests with the same table and weight column
+    /// share an endpoint.
+    ///
+    /// This is an async lock because it is held while a new endpoint is
+    /// created, so that concurrent requests don't create duplicate endpoints.
+    input_endpoints: Mutex<HashMap<(String, Option<String>), HttpInputEndpoint>>,
+}
+
+impl PipelineFlightService {
+    fn new(state: WebData<ServerState>) -> Self {
+        Self {
+            state,
+            input_endpoints: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the schema of table or view `name`.
+    fn relation(&self, name: &str) -> Result<Relation, PipelineError> {
+        match &*self.state.controller.lock().unwrap() {
+            None => Err(missing_controller_error(&self.state)),
+            Some(controller) => {
+                let catalog = controller.catalog();
+                let identifier = SqlIdentifier::from(name);
+                catalog
+                    .input_collection_handle(&identifier)
+                    .map(|handle| handle.schema.clone())
+                    .or_else(|| {
+                        catalog
+                            .output_handles(&identifier)
+                            .map(|handles| handles.value_schema.clone())
+                    })
+                    .ok_or_else(|| PipelineError::InvalidParam {
+                        error: format!("unknown table or view '{name}'"),
+                    })
+            }
+        }
+    }
+
+    /// Returns the input endpoint for the table and weight column in
+    /// `command`, creating it on first use.
+    async fn table_input_endpoint(
+        &self,
+        command: &FlightPutCommand,
+    ) -> Result<HttpInputEndpoint, PipelineError> {
+        let key = (command.table.clone(), command.weight_column.clone());
+        let mut input_endpoints = self.input_endpoints.lock().await;
+        let entry = match input_endpoints.entry(key) {
+            Entry::Occupied(entry) => return Ok(entry.get().clone()),
+            Entry::Vacant(entry) => entry,
+        };
+
+        let endpoint_name = format!("api-ingress-flight-{}-{}", command.table, Uuid::new_v4());
+        let config = ArrowIpcParserConfig {
+            weight_column: command.weight_column.clone(),
+        };
+        let format = FormatConfig {
+            name: Cow::from("arrow_ipc"),
+            config: serde_yaml::to_value(config).unwrap(),
+        };
+        let endpoint =
+            create_http_input_endpoint(&self.state, format, command.table.clone(), endpoint_name)
+                .await?;
+        Ok(entry.insert(endpoint).clone())
+    }
+
+    /// Streams changes to the table or view in `ticket` until the client
+    /// disconnects.
+    fn deltas(
+        &self,
+        ticket: FlightTicket,
+    ) -> Result<BoxStream<'static, Result<FlightData, Status>>, PipelineError> {
+        let relation = self.relation(&ticket.name)?;
+        let schema = relation_schema(&relation, ticket.weight_column.as_deref());
+
+        let endpoint_name = format!("api-flight-{}-{}", Uuid::new_v4(), ticket.name);
+        let encoder_config = ArrowIpcEncoderConfig {
+            weight_column: ticket.weight_column.clone(),
+            ..Default::default()
+        };
+        let config = OutputEndpointConfig {
+            stream: Cow::from(ticket.name),
+            connector_config: ConnectorConfig {
+                transport: HttpOutputTransport::config(),
+                format: Some(FormatConfig {
+                    name: Cow::from("arrow_ipc"),
+                    config: serde_yaml::to_value(encoder_config).unwrap(),
+                }),
+                index: None,
+                output_buffer_config: Default::default(),
+                max_batch_size: default_max_batch_size(),
+                max_queued_records: HttpOutputTransport::default_max_buffered_records(),
+                max_records_per_second: None,
+                max_bytes_per_second: None,
+                paused: false,
+                labels: vec![],
+                start_after: None,
+                dead_letter: None,
+            },
+        };
+
+        let (sender, mut receiver) = mpsc::channel(MAX_BUFFERS);
+        let endpoint = FlightOutputEndpoint {
+            sender,
+            backpressure: ticket.backpressure,
+        };
+
+        let guard = match &*self.state.controller.lock().unwrap() {
+            None => return Err(missing_controller_error(&self.state)),
+            Some(controller) => {
+                if controller.register_api_connection().is_err() {
+                    return Err(PipelineError::ApiConnectionLimit);
+                }
+                let endpoint_id = match controller.add_output_endpoint(
+                    &endpoint_name,
+                    &config,
+                    Box::new(endpoint) as Box<dyn OutputEndpoint>,
+                ) {
+                    Ok(endpoint_id) => endpoint_id,
+                    Err(e) => {
+                        controller.unregister_api_connection();
+                        Err(e)?
+                    }
+                };
+                OutputEndpointGuard {
+                    state: Arc::downgrade(&self.state.clone().into_inner()),
+                    endpoint_id,
+                }
+            }
+        };
+
+        // Each buffer produced by the encoder is a complete Arrow IPC stream.
+        let batches = stream! {
+            let _guard = guard;
+            while let Some(buffer) = receiver.recv().await {
+                match StreamReader::try_new(Cursor::new(buffer), None) {
+                    Ok(reader) => {
+                        for batch in reader {
+                            yield batch.map_err(FlightError::from);
+                        }
+                    }
+                    Err(e) => yield Err(FlightError::from(e)),
+                }
+            }
+        };
+
+        Ok(FlightDataEncoderBuilder::new()
+            .with_schema(schema)
+            .build(batches)
+            .map_err(Status::from)
+            .boxed())
+    }
+
+    /// Returns the contents of the materialized table or view in `ticket`.
+    async fn snapshot(
+        &self,
+        ticket: FlightTicket,
+    ) -> Result<BoxStream<'static, Result<FlightData, Status>>, PipelineError> {
+        let relation = self.relation(&ticket.name)?;
+        let session = match &*self.state.controller.lock().unwrap() {
+            None => return Err(missing_controller_error(&self.state)),
+            Some(controller) => controller.session_context()?,
+        };
+        let df = session
+            .sql(&format!("SELECT * FROM {}", relation.name.sql_name()))
+            .await?;
+        let batches = df.execute_stream().await?;
+        let schema = batches.schema();
+
+        Ok(FlightDataEncoderBuilder::new()
+            .with_schema(schema)
+            .build(batches.map_err(|e| FlightError::ExternalError(Box::new(e))))
+            .map_err(Status::from)
+            .boxed())
+    }
+}
+
+/// Pushes `batch` to `endpoint` and returns a completion token for the data
+/// pushed so far.
+async fn push_batch(
+    state: &ServerState,
+    endpoint: &HttpInputEndpoint,
+    batch: RecordBatch,
+    force: bool,
+) -> Result<PutResult, PipelineError> {
+    let encode = |buffer: &mut Vec<u8>| -> Result<(), ArrowError> {
+        let mut writer = StreamWriter::try_new(buffer, &batch.schema())?;
+        writer.write(&batch)?;
+        writer.finish()
+    };
+    let mut buffer = Vec::new();
+    encode(&mut buffer).map_err(|e| PipelineError::InvalidParam {
+        error: format!("error encoding record batch: {e}"),
+    })?;
+
+    endpoint.push_message(&buffer, force).await?;
+
+    let token = match &*state.controller.lock().unwrap() {
+        None => return Err(missing_controller_error(state)),
+        Some(controller) => controller.completion_token(endpoint.name())?.encode(),
+    };
+    Ok(PutResult {
+        app_metadata: serde_json::to_vec(&CompletionTokenResponse::new(token))
+            .unwrap()
+            .into(),
+    })
+}
+
+#[tonic::async_trait]
+impl FlightService for PipelineFlightService {
+    type HandshakeStream = BoxStream<'static, Result<HandshakeResponse, Status>>;
+    type ListFlightsStream = BoxStream<'static, Result<FlightInfo, Status>>;
+    type DoGetStream = BoxStream<'static, Result<FlightData, Status>>;
+    type DoPutStream = BoxStream<'static, Result<PutResult, Status>>;
+    type DoExchangeStream = BoxStream<'static, Result<FlightData, Status>>;
+    type DoActionStream = BoxStream<'static, Result<arrow_flight::Result, Status>>;
+    type ListActionsStream = BoxStream<'static, Result<ActionType, Status>>;
+
+    async fn handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest>>,
+    ) -> Result<Response<Self::HandshakeStream>, Status> {
+        Err(Status::unimplemented("handshake is not supported"))
+    }
+
+    async fn list_flights(
+        &self,
+        _request: Request<Criteria>,
+    ) -> Result<Response<Self::ListFlightsStream>, Status> {
+        Err(Status::unimplemented("list_flights is not supported"))
+    }
+
+    async fn get_flight_info(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let descriptor = request.into_inner();
+        let name = descriptor_path(&descriptor)?;
+        let relation = self.relation(name).map_err(error_to_status)?;
+        let ticket = FlightTicket::new(name, FlightTicketMode::Deltas);
+        let ticket = Ticket::new(serde_json::to_vec(&ticket).unwrap());
+
+        let info = FlightInfo::new()
+            .try_with_schema(&relation_schema(&relation, None))
+            .map_err(|e| Status::internal(format!("error encoding schema: {e}")))?
+            .with_endpoint(FlightEndpoint::new().with_ticket(ticket))
+            .with_descriptor(descriptor);
+        Ok(Response::new(info))
+    }
+
+    async fn poll_flight_info(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> Result<Response<PollInfo>, Status> {
+        Err(Status::unimplemented("poll_flight_info is not supported"))
+    }
+
+    async fn get_schema(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<SchemaResult>, Status> {
+        let name = descriptor_path(request.get_ref())?;
+        let relation = self.relation(name).map_err(error_to_status)?;
+        let schema = relation_schema(&relation, None);
+        let result = SchemaAsIpc::new(&schema, &IpcWriteOptions::default())
+            .try_into()
+            .map_err(|e| Status::internal(format!("error encoding schema: {e}")))?;
+        Ok(Response::new(result))
+    }
+
+    async fn do_get(
+        &self,
+        request: Request<Ticket>,
+    ) -> Result<Response<Self::DoGetStream>, Status> {
+        let ticket: FlightTicket = serde_json::from_slice(&request.get_ref().ticket)
+            .map_err(|e| Status::invalid_argument(format!("invalid ticket: {e}")))?;
+        debug!("Arrow Flight DoGet request: {ticket:?}");
+
+        let stream = match ticket.mode {
+            FlightTicketMode::Deltas => self.deltas(ticket),
+            FlightTicketMode::Snapshot => self.snapshot(ticket).await,
+        }
+        .map_err(error_to_status)?;
+        Ok(Response::new(stream))
+    }
+
+    async fn do_put(
+        &self,
+        request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoPutStream>, Status> {
+        let mut flight_data = request.into_inner();
+
+        // The first message carries the flight descriptor.
+        let first = flight_data
+            .message()
+            .await?
+            .ok_or_else(|| Status::invalid_argument("DoPut request contains no data"))?;
+        let command = match &first.flight_descriptor {
+            Some(descriptor) if descriptor.r#type() == DescriptorType::Cmd => {
+                serde_json::from_slice::<FlightPutCommand>(&descriptor.cmd)
+                    .map_err(|e| Status::invalid_argument(format!("invalid DoPut command: {e}")))?
+            }
+            Some(descriptor) => FlightPutCommand::new(descriptor_path(descriptor)?),
+            None => {
+                return Err(Status::invalid_argument(
+                    "DoPut request must specify a flight descriptor",
+                ))
+            }
+        };
+        debug!("Arrow Flight DoPut request: {command:?}");
+
+        let endpoint = self
+            .table_input_endpoint(&command)
+            .await
+            .map_err(error_to_status)?;
+
+        let mut batches = arrow_flight::decode::FlightRecordBatchStream::new_from_flight_data(
+            stream::once(async { Ok(first) })
+                .chain(flight_data)
+                .map_err(FlightError::from),
+        );
+        let state = self.state.clone();
+        let results = stream! {
+            while let Some(batch) = batches.next().await {
+                let result = match batch {
+                    Ok(batch) => push_batch(&state, &endpoint, batch, command.force)
+                        .await
+                        .map_err(error_to_status),
+                    Err(e) => Err(Status::from(e)),
+                };
+                let failed = result.is_err();
+                yield result;
+                if failed {
+                    break;
+                }
+            }
+        };
+        Ok(Response::new(results.boxed()))
+    }
+
+    async fn do_exchange(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoExchangeStream>, Status> {
+        Err(Status::unimplemented("do_exchange is not supported"))
+    }
+
+    async fn do_action(
+        &self,
+        _request: Request<Action>,
+    ) -> Result<Response<Self::DoActionStream>, Status> {
+        Err(Status::unimplemented("do_action is not supported"))
+    }
+
+    async fn list_actions(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<Self::ListActionsStream>, Status> {
+        Err(Status::unimplemented("list_actions is not supported"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::server::{bootstrap, ServerArgs, ServerState};
+    use crate::test::{test_circuit, TestStruct};
+    use actix_web::web::Data as WebData;
+    use arrow::array::{ArrayRef, AsArray, BooleanArray, Int64Array, RecordBatch, StringArray};
+    use arrow::datatypes::Int64Type;
+    use arrow_flight::{encode::FlightDataEncoderBuilder, FlightClient, FlightDescriptor, Ticket};
+    use dbsp::circuit::tokio::TOKIO;
+    use feldera_types::completion_token::CompletionTokenResponse;
+    use feldera_types::flight::{FlightTicket, FlightTicketMode};
+    use futures::{StreamExt, TryStreamExt};
+    use std::{
+        io::Write,
+        net::TcpListener,
+        sync::Arc,
+        thread,
+        thread::sleep,
+        time::{Duration, Instant},
+    };
+    use tempfile::NamedTempFile;
+    use tonic::transport::Channel;
+
+    #[test]
+    fn test_flight() {
+        // Find an unused port for the Flight service.
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let config_str = format!(
+            r#"
+name: test
+flight_port: {port}
+inputs: {{}}
+outputs: {{}}
+"#
+        );
+        let mut config_file = NamedTempFile::new().unwrap();
+        config_file.write_all(config_str.as_bytes()).unwrap();
+
+        let state = WebData::new(ServerState::new(None));
+        let state_clone = state.clone();
+        let args = ServerArgs {
+            config_file: config_file.path().display().to_string(),
+            metadata_file: None,
+            bind_address: "127.0.0.1".to_string(),
+            default_port: None,
+            storage_location: None,
+        };
+        thread::spawn(move || {
+            bootstrap(
+                args,
+                Box::new(|workers| {
+                    Ok(test_circuit::<TestStruct>(
+                        workers,
+                        &TestStruct::schema(),
+                        &[None],
+                    ))
+                }),
+                state_clone,
+                std::sync::mpsc::channel().0,
+            )
+        });
+
+        let start = Instant::now();
+        while state.controller.lock().unwrap().is_none() {
+            assert!(start.elapsed() < Duration::from_millis(20_000));
+            sleep(Duration::from_millis(200));
+        }
+        state.controller.lock().unwrap().as_ref().unwrap().start();
+
+        TOKIO.block_on(async {
+            let channel = Channel::from_shared(format!("http://127.0.0.1:{port}"))
+                .unwrap()
+                .connect()
+                .await
+                .unwrap();
+            let mut client = FlightClient::new(channel);
+
+            let schema = client
+                .get_schema(FlightDescriptor::new_path(vec!["test_output1".to_string()]))
+                .await
+                .unwrap();
+            let names = schema
+                .fields()
+                .iter()
+                .map(|f| f.name().as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(names, ["id", "b", "i", "s"]);
+
+            // Subscribe to the output before pushing any data.
+            let ticket = FlightTicket {
+                weight_column: Some("weight".to_string()),
+                backpressure: true,
+                ..FlightTicket::new("test_output1", FlightTicketMode::Deltas)
+            };
+            let mut output = client
+                .do_get(Ticket::new(serde_json::to_vec(&ticket).unwrap()))
+                .await
+                .unwrap();
+
+            let batch = RecordBatch::try_from_iter([
+                ("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+                (
+                    "b",
+                    Arc::new(BooleanArray::from(vec![true, false])) as ArrayRef,
+                ),
+                (
+                    "i",
+                    Arc::new(Int64Array::from(vec![Some(5), None])) as ArrayRef,
+                ),
+                (
+                    "s",
+                    Arc::new(StringArray::from(vec!["foo", "bar"])) as ArrayRef,
+                ),
+            ])
+            .unwrap();
+            let flight_data = FlightDataEncoderBuilder::new()
+                .with_flight_descriptor(Some(FlightDescriptor::new_path(vec![
+                    "test_input1".to_string()
+                ])))
+                .build(futures::stream::iter([Ok(batch.clone())]));
+            let results = client
+                .do_put(flight_data)
+                .await
+                .unwrap()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            assert_eq!(results.len(), 1);
+            let token: CompletionTokenResponse =
+                serde_json::from_slice(&results[0].app_metadata).unwrap();
+            assert!(!token.token.is_empty());
+
+            // Both records are output as insertions.
+            let mut ids = Vec::new();
+            while ids.len() < 2 {
+                let batch = output.next().await.unwrap().unwrap();
+                let weights = batch
+                    .column_by_name("weight")
+                    .unwrap()
+                    .as_primitive::<Int64Type>();
+                assert!(weights.values().iter().all(|w| *w == 1));
+                ids.extend(
+                    batch
+                        .column_by_name("id")
+                        .unwrap()
+                        .as_primitive::<Int64Type>()
+                        .values()
+                        .iter()
+                        .copied(),
+                );
+            }
+            ids.sort();
+            assert_eq!(ids, [1, 2]);
+
+            // Pushing to an unknown table fails.
+            let flight_data = FlightDataEncoderBuilder::new()
+                .with_flight_descriptor(Some(FlightDescriptor::new_path(vec![
+                    "no_such_table".to_string()
+                ])))
+                .build(futures::stream::iter([Ok(batch)]));
+            assert!(client.do_put(flight_data).await.is_err());
+        });
+    }
+}
@@ -37,7 +37,7 @@ use feldera_types::secret_resolver::{
 use feldera_types::suspend::{SuspendError, SuspendableResponse};
 use feldera_types::{
     config::{default_max_batch_size, TransportConfig},
-    transport::http::HttpInputConfig,
+    transport::http::{EgressMode, HttpInputConfig},
 };
 use feldera_types::{query::AdhocQueryArgs, transport::http::SERVER_PORT_FILE};
 use futures_util::FutureExt;
@@ -47,6 +47,7 @@ use serde_json::json;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
+use std::time::Duration;
 use std::{
     borrow::Cow,
     net::TcpListener,
@@ -73,6 +74,8 @@ use tracing_subscriber::EnvFilter;
 use uuid::Uuid;
 
 pub mod error;
+#[cfg(feature = "with-flight")]
+mod flight;
 mod prometheus;
 
 pub use self::error::{ErrorResponse, PipelineError, MAX_REPORTED_PARSE_ERRORS};
@@ -581,6 +584,16 @@ fn do_bootstrap(
         }
     };
 
+    if let Some(port) = config.global.flight_port {
+        #[cfg(feature = "with-flight")]
+        flight::start_flight_server(&args.bind_address, port, state.clone())?;
+
+        #[cfg(not(feature = "with-flight"))]
+        warn!(
+            "Ignoring 'flight_port' {port}: this pipeline was built without Arrow Flight support"
+        );
+    }
+
     let weak_state_ref = Arc::downgrade(state);
 
     let controller = Controller::with_config(
@@ -630,7 +643,9 @@ where
         .service(checkpoint_status)
         .service(suspend)
         .service(input_endpoint)
+        .service(input_endpoint_websocket)
         .service(output_endpoint)
+        .service(output_endpoint_websocket)
         .service(pause_input_endpoint)
         .service(start_input_endpoint)
         .service(input_endpoint_status)
@@ -1124,9 +1139,12 @@ async fn create_http_input_endpoint(
             output_buffer_config: Default::default(),
             max_batch_size: default_max_batch_size(),
             max_queued_records: HttpInputTransport::default_max_buffered_records(),
+            max_records_per_second: None,
+            max_bytes_per_second: None,
             paused: false,
             labels: vec![],
             start_after: None,
+            dead_letter: None,
         },
     };
 
@@ -1166,16 +1184,17 @@ async fn create_http_input_endpoint(
     Ok(endpoint)
 }
 
-#[post("/ingress/{table_name}")]
-async fn input_endpoint(
-    state: WebData<ServerState>,
-    req: HttpRequest,
-    args: Query<IngressArgs>,
-    payload: Payload,
-) -> impl Responder {
+/// Return the HTTP input endpoint for `table_name` and the format specified
+/// in the request, creating it on first use.
+///
+/// All `/ingress` requests for the same table and format share an endpoint.
+async fn table_input_endpoint(
+    state: &WebData<ServerState>,
+    req: &HttpRequest,
+    args: &IngressArgs,
+) -> Result<HttpInputEndpoint, PipelineError> {
     static TABLE_ENDPOINTS: LazyLock<RwLock<HashMap<(String, FormatConfig), HttpInputEndpoint>>> =
         LazyLock::new(|| RwLock::new(HashMap::new()));
-    debug!("{req:?}");
 
     let table_name = match req.match_info().get("table_name") {
         None => {
@@ -1188,7 +1207,7 @@ async fn input_endpoint(
 
     // Generate endpoint name.
     let endpoint_name = format!("api-ingress-{table_name}-{}", Uuid::new_v4());
-    let format = parser_config_from_http_request(&endpoint_name, &args.format, &req)?;
+    let format = parser_config_from_http_request(&endpoint_name, &args.format, req)?;
 
     let cached_endpoint = TABLE_ENDPOINTS
         .read()
@@ -1199,7 +1218,7 @@ async fn input_endpoint(
         Some(endpoint) => endpoint,
         None => {
             let endpoint = create_http_input_endpoint(
-                &state,
+                state,
                 format.clone(),
                 table_name.clone(),
                 endpoint_name.clone(),
@@ -1213,6 +1232,20 @@ async fn input_endpoint(
         }
     };
 
+    Ok(endpoint)
+}
+
+#[post("/ingress/{table_name}")]
+async fn input_endpoint(
+    state: WebData<ServerState>,
+    req: HttpRequest,
+    args: Query<IngressArgs>,
+    payload: Payload,
+) -> impl Responder {
+    debug!("{req:?}");
+
+    let endpoint = table_input_endpoint(&state, &req, &args).await?;
+
     // Call endpoint to complete request.
     endpoint
         .complete_request(payload, args.force)
@@ -1228,6 +1261,47 @@ async fn input_endpoint(
     }
 }
 
+/// Push data to a table over a WebSocket connection.
+///
+/// Each message received from the client must contain complete records in the
+/// format specified by the URL-encoded arguments.  The server responds to each
+/// message with an `IngressWebSocketResponse`.
+#[get("/ingress/{table_name}")]
+async fn input_endpoint_websocket(
+    state: WebData<ServerState>,
+    req: HttpRequest,
+    args: Query<IngressArgs>,
+    payload: Payload,
+) -> impl Responder {
+    debug!("{req:?}");
+
+    if !request_is_websocket(&req) {
+        return Err(PipelineError::InvalidParam {
+            error: "GET requests to '/ingress' must upgrade to a WebSocket connection; use POST to push data in the request body".to_string(),
+        });
+    }
+
+    let endpoint = table_input_endpoint(&state, &req, &args).await?;
+
+    // Use a downgraded reference to `state`, so that the connection doesn't
+    // prevent the controller from shutting down.
+    let weak_state = Arc::downgrade(&state.into_inner());
+    let endpoint_name = endpoint.name().to_string();
+    endpoint.websocket_request(
+        &req,
+        payload,
+        args.force,
+        Box::new(move || {
+            let state = weak_state.upgrade().ok_or(PipelineError::Terminating)?;
+            let controller = state.controller.lock().unwrap();
+            match &*controller {
+                Some(controller) => Ok(controller.completion_token(&endpoint_name)?.encode()),
+                None => Err(missing_controller_error(&state)),
+            }
+        }),
+    )
+}
+
 /// Create an instance of `FormatConfig` from format name and
 /// HTTP request using the `InputFormat::config_from_http_request` method.
 pub fn parser_config_from_http_request(
@@ -1289,18 +1363,29 @@ struct EgressArgs {
     /// 'json' etc.
     #[serde(default = "HttpOutputTransport::default_format")]
     format: String,
-}
 
-#[post("/egress/{table_name}")]
-async fn output_endpoint(
-    state: WebData<ServerState>,
-    req: HttpRequest,
-    args: Query<EgressArgs>,
-) -> impl Responder {
-    debug!("/egress request:{req:?}");
+    /// Interval, in milliseconds, after which a WebSocket connection sends a
+    /// heartbeat message if the view has produced no output.
+    #[serde(default = "HttpOutputTransport::default_heartbeat_ms")]
+    heartbeat_ms: u64,
 
-    let state = state.into_inner();
+    /// Send only changes (`watch_changes`, the default), or start with the
+    /// current contents of a materialized table or view
+    /// (`snapshot_and_follow`).
+    #[serde(default)]
+    mode: EgressMode,
+}
 
+/// Create an HTTP output endpoint for the table or view in the request and
+/// connect it to the controller.
+///
+/// Returns the endpoint along with a finalizer that disconnects it.
+async fn create_http_output_endpoint(
+    state: &Arc<ServerState>,
+    req: &HttpRequest,
+    args: &EgressArgs,
+    websocket: bool,
+) -> Result<(HttpOutputEndpoint, Box<dyn FnMut()>), PipelineError> {
     let table_name = match req.match_info().get("table_name") {
         None => {
             return Err(PipelineError::MissingUrlEncodedParam {
@@ -1315,8 +1400,16 @@ async fn output_endpoint(
 
     // debug!("Endpoint name: '{endpoint_name}'");
 
+    let with_snapshot = args.mode == EgressMode::SnapshotAndFollow;
+
     // Create HTTP endpoint.
-    let endpoint = HttpOutputEndpoint::new(&endpoint_name, &args.format, args.backpressure);
+    let endpoint = HttpOutputEndpoint::new(
+        &endpoint_name,
+        &args.format,
+        args.backpressure,
+        websocket,
+        with_snapshot,
+    );
 
     // Create endpoint config.
     let config = OutputEndpointConfig {
@@ -1326,30 +1419,60 @@ async fn output_endpoint(
             format: Some(encoder_config_from_http_request(
                 &endpoint_name,
                 &args.format,
-                &req,
+                req,
             )?),
             index: None,
             output_buffer_config: Default::default(),
             max_batch_size: default_max_batch_size(),
             max_queued_records: HttpOutputTransport::default_max_buffered_records(),
+            max_records_per_second: None,
+            max_bytes_per_second: None,
             paused: false,
             labels: vec![],
             start_after: None,
+            dead_letter: None,
         },
     };
 
+    // Lock the snapshot without holding the controller lock, since this may
+    // wait for the pipeline's first step.
+    let snapshot = if with_snapshot {
+        let snapshot = match &*state.controller.lock().unwrap() {
+            Some(controller) => controller.lock_trace_snapshot(),
+            None => return Err(missing_controller_error(state)),
+        };
+        Some(snapshot.await)
+    } else {
+        None
+    };
+
     // Connect endpoint.
-    let response = match &*state.controller.lock().unwrap() {
+    match &*state.controller.lock().unwrap() {
         Some(controller) => {
             if controller.register_api_connection().is_err() {
                 return Err(PipelineError::ApiConnectionLimit);
             }
 
-            let endpoint_id = match controller.add_output_endpoint(
-                &endpoint_name,
-                &config,
-                Box::new(endpoint.clone()) as Box<dyn OutputEndpoint>,
-            ) {
+            let result = match snapshot {
+                Some(snapshot) => {
+                    // The controller sends the snapshot as soon as the
+                    // endpoint is added, before the client starts receiving
+                    // output.
+                    endpoint.connect();
+                    controller.add_output_endpoint_with_snapshot(
+                        &endpoint_name,
+                        &config,
+                        Box::new(endpoint.clone()) as Box<dyn OutputEndpoint>,
+                        snapshot,
+                    )
+                }
+                None => controller.add_output_endpoint(
+                    &endpoint_name,
+                    &config,
+                    Box::new(endpoint.clone()) as Box<dyn OutputEndpoint>,
+                ),
+            };
+            let endpoint_id = match result {
                 Ok(endpoint_id) => endpoint_id,
                 Err(e) => {
                     controller.unregister_api_connection();
@@ -1360,11 +1483,9 @@ async fn output_endpoint(
             // We need to pass a callback to `request` to disconnect the endpoint when the
             // request completes.  Use a downgraded reference to `state`, so
             // this closure doesn't prevent the controller from shutting down.
-            let weak_state = Arc::downgrade(&state);
+            let weak_state = Arc::downgrade(state);
 
-            // Call endpoint to create a response with a streaming body, which will be
-            // evaluated after we return the response object to actix.
-            endpoint.request(Box::new(move || {
+            let finalizer: Box<dyn FnMut()> = Box::new(move || {
                 // Delete endpoint on completion/error.
                 // We don't control the lifetime of the response object after
                 // returning it to actix, so the only way to run cleanup code
@@ -1381,12 +1502,57 @@ async fn output_endpoint(
                         }
                     }
                 }
-            }))
+            });
+
+            Ok((endpoint, finalizer))
         }
-        None => return Err(missing_controller_error(&state)),
-    };
+        None => Err(missing_controller_error(state)),
+    }
+}
+
+#[post("/egress/{table_name}")]
+async fn output_endpoint(
+    state: WebData<ServerState>,
+    req: HttpRequest,
+    args: Query<EgressArgs>,
+) -> impl Responder {
+    debug!("/egress request:{req:?}");
 
-    Ok(response)
+    let (endpoint, finalizer) =
+        create_http_output_endpoint(&state.into_inner(), &req, &args, false).await?;
+
+    // Call endpoint to create a response with a streaming body, which will be
+    // evaluated after we return the response object to actix.
+    Ok::<_, PipelineError>(endpoint.request(finalizer))
+}
+
+/// Subscribe to the output of a table or view over a WebSocket connection.
+///
+/// The server sends an `EgressWebSocketMessage` for each chunk of output, at
+/// the end of each step, and as a heartbeat when there is no output.
+#[get("/egress/{table_name}")]
+async fn output_endpoint_websocket(
+    state: WebData<ServerState>,
+    req: HttpRequest,
+    args: Query<EgressArgs>,
+    payload: Payload,
+) -> impl Responder {
+    debug!("/egress request:{req:?}");
+
+    if !request_is_websocket(&req) {
+        return Err(PipelineError::InvalidParam {
+            error: "GET requests to '/egress' must upgrade to a WebSocket connection; use POST to receive output as a streaming HTTP response".to_string(),
+        });
+    }
+
+    let (endpoint, finalizer) =
+        create_http_output_endpoint(&state.into_inner(), &req, &args, true).await?;
+    endpoint.websocket_request(
+        &req,
+        payload,
+        Duration::from_millis(args.heartbeat_ms),
+        finalizer,
+    )
 }
 
 /// This service journals the paused state, but it does not wait for the journal
@@ -1497,6 +1663,136 @@ async fn completion_status(
     }
 }
 
+#[cfg(test)]
+mod test {
+    use super::{bootstrap, build_app, ServerArgs, ServerState};
+    use crate::test::{
+        async_wait,
+        http::{TestHttpReceiver, TestHttpSender, TestWebSocketReceiver},
+        test_circuit, TestStruct,
+    };
+    use actix_test::TestServer;
+    use actix_web::{http::StatusCode, web::Data as WebData, App};
+    use feldera_types::completion_token::{
+        CompletionStatus, CompletionStatusResponse, CompletionTokenResponse,
+    };
+    use std::{
+        io::Write,
+        thread,
+        thread::sleep,
+        time::{Duration, Instant},
+    };
+    use tempfile::NamedTempFile;
+
+    fn test_data(ids: std::ops::Range<u32>) -> Vec<Vec<TestStruct>> {
+        vec![ids
+            .map(|id| TestStruct {
+                id,
+                b: id % 2 == 0,
+                i: Some(id as i64),
+                s: format!("foo{id}"),
+            })
+            .collect()]
+    }
+
+    /// Push `data` to `test_input1` and wait for the pipeline to process it.
+    async fn ingest(server: &TestServer, data: &[Vec<TestStruct>]) {
+        let CompletionTokenResponse { token } = TestHttpSender::send_stream_deserialize_resp::<
+            CompletionTokenResponse,
+        >(server.post("/ingress/test_input1"), data)
+        .await;
+
+        async_wait(
+            || async {
+                let resp = server
+                    .get(format!("/completion_status?token={token}"))
+                    .send()
+                    .await
+                    .unwrap()
+                    .body()
+                    .await
+                    .unwrap();
+                let CompletionStatusResponse { status } = serde_json::from_slice(&resp).unwrap();
+                status == CompletionStatus::Complete
+            },
+            20_000,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[actix_web::test]
+    async fn test_egress_snapshot_and_follow() {
+        let mut config_file = NamedTempFile::new().unwrap();
+        config_file
+            .write_all(b"name: test\ninputs: {}\noutputs: {}\n")
+            .unwrap();
+
+        let state = WebData::new(ServerState::new(None));
+        let state_clone = state.clone();
+        let args = ServerArgs {
+            config_file: config_file.path().display().to_string(),
+            metadata_file: None,
+            bind_address: "127.0.0.1".to_string(),
+            default_port: None,
+            storage_location: None,
+        };
+        thread::spawn(move || {
+            bootstrap(
+                args,
+                Box::new(|workers| {
+                    Ok(test_circuit::<TestStruct>(
+                        workers,
+                        &TestStruct::schema(),
+                        &[None],
+                    ))
+                }),
+                state_clone,
+                std::sync::mpsc::channel().0,
+            )
+        });
+
+        let server = actix_test::start(move || build_app(App::new(), state.clone()));
+
+        let start = Instant::now();
+        while server.get("/stats").send().await.unwrap().status() == StatusCode::SERVICE_UNAVAILABLE
+        {
+            assert!(start.elapsed() < Duration::from_millis(20_000));
+            sleep(Duration::from_millis(200));
+        }
+        assert!(server
+            .get("/start")
+            .send()
+            .await
+            .unwrap()
+            .status()
+            .is_success());
+
+        let snapshot = test_data(0..100);
+        ingest(&server, &snapshot).await;
+
+        // Both connections start with the current contents of the view.
+        let mut egress = server
+            .ws_at("/egress/test_output1?backpressure=true&mode=snapshot_and_follow")
+            .await
+            .unwrap();
+        let mut resp = server
+            .post("/egress/test_output1?backpressure=true&mode=snapshot_and_follow")
+            .send()
+            .await
+            .unwrap();
+        let step = TestWebSocketReceiver::wait_for_snapshot(&mut egress, &snapshot).await;
+        assert!(step > 0);
+        TestHttpReceiver::wait_for_output_unordered(&mut resp, &snapshot).await;
+
+        // Followed by changes, without repeating the snapshot.
+        let changes = test_data(100..150);
+        ingest(&server, &changes).await;
+        TestWebSocketReceiver::wait_for_output_unordered(&mut egress, &changes).await;
+        TestHttpReceiver::wait_for_output_unordered(&mut resp, &changes).await;
+    }
+}
+
 #[cfg(test)]
 #[cfg(feature = "with-kafka")]
 mod test_with_kafka {
@@ -1506,7 +1802,7 @@ mod test_with_kafka {
         ensure_default_crypto_provider,
         test::{
             async_wait, generate_test_batches,
-            http::{TestHttpReceiver, TestHttpSender},
+            http::{TestHttpReceiver, TestHttpSender, TestWebSocketReceiver, TestWebSocketSender},
             kafka::{BufferConsumer, KafkaResources, TestProducer},
             test_circuit, TestStruct,
         },
@@ -1777,6 +2073,32 @@ outputs:
         TestHttpReceiver::wait_for_output_unordered(&mut resp1, &data).await;
         TestHttpReceiver::wait_for_output_unordered(&mut resp2, &data).await;
 
+        println!("WebSocket test");
+        let mut egress = server
+            .ws_at("/egress/test_output1?backpressure=true")
+            .await
+            .unwrap();
+        let mut ingress = server.ws_at("/ingress/test_input1").await.unwrap();
+
+        tokio::join!(
+            async {
+                let token = TestWebSocketSender::send(&mut ingress, &data).await;
+                println!("completion token: {token}");
+            },
+            // The HTTP output connectors apply backpressure, so they must
+            // receive the data in parallel.
+            async {
+                TestWebSocketReceiver::wait_for_output_unordered(&mut egress, &data).await;
+                TestHttpReceiver::wait_for_output_unordered(&mut resp1, &data).await;
+                TestHttpReceiver::wait_for_output_unordered(&mut resp2, &data).await;
+            }
+        );
+
+        buffer_consumer.wait_for_output_unordered(&data);
+        buffer_consumer.clear();
+        drop(ingress);
+        drop(egress);
+
         // Force-push data in paused state.
         println!("/pause");
         let resp = server.get("/pause").send().await.unwrap();
@@ -2,7 +2,7 @@
 use crate::catalog::AvroStream;
 #[cfg(feature = "with-avro")]
 use crate::format::avro::from_avro_value;
-use crate::format::csv::deserializer::ByteRecordDeserializer;
+use crate::format::csv::{csv_reader_builder, deserializer::ByteRecordDeserializer};
 use crate::format::raw::{raw_serde_config, RawDeserializer};
 use crate::{catalog::ArrowStream, format::InputBuffer};
 use crate::{
@@ -80,20 +80,17 @@ pub struct CsvDeserializerFromBytes {
     reader: csv::Reader<VecDeque<u8>>,
     // Byte record to read CSV records into.
     record: csv::ByteRecord,
+    // Field value that represents `NULL`.
+    null_string: Vec<u8>,
     config: SqlSerdeConfig,
 }
 
 impl DeserializerFromBytes<(SqlSerdeConfig, CsvParserConfig)> for CsvDeserializerFromBytes {
     fn create((serde_config, csv_config): (SqlSerdeConfig, CsvParserConfig)) -> Self {
         CsvDeserializerFromBytes {
-            reader: csv::ReaderBuilder::new()
-                // We skip the headers ourselves, without passing them to the
-                // reader, so we unconditionally turn off headers in the reader.
-                .has_headers(false)
-                .flexible(true)
-                .delimiter(csv_config.delimiter().0)
-                .from_reader(VecDeque::new()),
+            reader: csv_reader_builder(&csv_config).from_reader(VecDeque::new()),
             record: csv::ByteRecord::new(),
+            null_string: csv_config.null_string.into_bytes(),
             config: serde_config,
         }
     }
@@ -105,7 +102,8 @@ impl DeserializerFromBytes<(SqlSerdeConfig, CsvParserConfig)> for CsvDeserialize
         self.reader.get_mut().extend(data.iter());
         self.reader.read_byte_record(&mut self.record)?;
 
-        let mut deserializer = byte_record_deserializer(&self.record, None);
+        let mut deserializer =
+            byte_record_deserializer(&self.record, None).with_null_value(&self.null_string);
         let deserializer = csv_deserializer(&mut deserializer);
 
         T::deserialize_with_context(deserializer, &self.config).map_err(|e| anyhow!(e.to_string()))
@@ -268,7 +266,7 @@ where
                     config,
                 )))
             }
-            RecordFormat::Parquet(_) => {
+            RecordFormat::Parquet(_) | RecordFormat::CsvEncoder(_) => {
                 todo!()
             }
             #[cfg(feature = "with-avro")]
@@ -386,7 +384,7 @@ where
                     ),
                 ))
             }
-            RecordFormat::Parquet(_) => {
+            RecordFormat::Parquet(_) | RecordFormat::CsvEncoder(_) => {
                 todo!()
             }
             #[cfg(feature = "with-avro")]
@@ -784,7 +782,7 @@ where
                     ),
                 ))
             }
-            RecordFormat::Parquet(_) => {
+            RecordFormat::Parquet(_) | RecordFormat::CsvEncoder(_) => {
                 todo!()
             }
             #[cfg(feature = "with-avro")]
@@ -1216,7 +1214,7 @@ where
                 self.update_key_func.clone(),
                 SqlSerdeConfig::from(flavor),
             ))),
-            RecordFormat::Parquet(_) => {
+            RecordFormat::Parquet(_) | RecordFormat::CsvEncoder(_) => {
                 todo!()
             }
             #[cfg(feature = "with-avro")]
@@ -1,6 +1,8 @@
 use crate::catalog::{SerBatchReader, SerBatchReaderHandle, SerTrace, SyncSerBatchReader};
 #[cfg(feature = "with-avro")]
 use crate::format::avro::serializer::{avro_ser_config, AvroSchemaSerializer, AvroSerializerError};
+use crate::format::csv::{csv_parser_encoder_config, csv_writer_builder, serializer::NullAs};
+use crate::format::raw::{raw_serde_config, RawRecordSerializer};
 use crate::{
     catalog::{RecordFormat, SerBatch, SerCollectionHandle, SerCursor},
     ControllerError,
@@ -12,7 +14,7 @@ use apache_avro::schema::NamesRef;
 use apache_avro::types::Value as AvroValue;
 #[cfg(feature = "with-avro")]
 use apache_avro::Schema as AvroSchema;
-use csv::{Writer as CsvWriter, WriterBuilder as CsvWriterBuilder};
+use csv::Writer as CsvWriter;
 use dbsp::dynamic::DowncastTrait;
 use dbsp::trace::merge_batches;
 use dbsp::typed_batch::{DynBatchReader, DynSpine, DynTrace, Spine, TypedBatch};
@@ -22,7 +24,7 @@ use feldera_types::serde_with_context::serialize::{
     SerializeFieldsWithContextWrapper, SerializeWithContextWrapper,
 };
 use feldera_types::{
-    format::csv::CsvParserConfig,
+    format::csv::CsvEncoderConfig,
     serde_with_context::{SerializeWithContext, SqlSerdeConfig},
 };
 use serde::Serialize;
@@ -172,16 +174,14 @@ trait BytesSerializer: Send {
 
 struct CsvSerializer {
     writer: CsvWriter<SwappableWrite<Vec<u8>>>,
+    null_string: String,
 }
 
 impl CsvSerializer {
-    fn create(config: CsvParserConfig) -> Self {
+    fn create(config: CsvEncoderConfig) -> Self {
         Self {
-            writer: CsvWriterBuilder::new()
-                .has_headers(false)
-                .flexible(true)
-                .delimiter(config.delimiter().0)
-                .from_writer(SwappableWrite::new()),
+            writer: csv_writer_builder(&config).from_writer(SwappableWrite::new()),
+            null_string: config.null_string,
         }
     }
 }
@@ -190,7 +190,7 @@ impl BytesSerializer for CsvSerializer {
     fn serialize(&mut self, val: &dyn ErasedSerialize, buf: &mut Vec<u8>) -> AnyResult<()> {
         let owned_buf = std::mem::take(buf);
         self.writer.get_ref().swap(Some(owned_buf));
-        let res = self.writer.serialize(val);
+        let res = self.writer.serialize(NullAs::new(val, &self.null_string));
         let _ = self.writer.flush();
         *buf = self.writer.get_ref().swap(None).unwrap();
         Ok(res?)
@@ -212,6 +212,22 @@ impl BytesSerializer for JsonSerializer {
     }
 }
 
+/// Serializes the only column of a record verbatim.
+struct RawSerializer;
+
+impl RawSerializer {
+    fn create() -> Self {
+        Self
+    }
+}
+
+impl BytesSerializer for RawSerializer {
+    fn serialize(&mut self, val: &dyn ErasedSerialize, buf: &mut Vec<u8>) -> AnyResult<()> {
+        val.serialize(RawRecordSerializer::new(buf))?;
+        Ok(())
+    }
+}
+
 #[cfg(feature = "with-avro")]
 pub struct AvroSerializer;
 
@@ -418,6 +434,13 @@ where
     ) -> Result<Box<dyn SerCursor + Send + 'a>, ControllerError> {
         Ok(match record_format {
             RecordFormat::Csv(config) => {
+                Box::new(<SerCursorImpl<'a, CsvSerializer, B, KD, VD>>::new(
+                    &self.batch,
+                    SqlSerdeConfig::default(),
+                    CsvSerializer::create(csv_parser_encoder_config(&config)),
+                ))
+            }
+            RecordFormat::CsvEncoder(config) => {
                 Box::new(<SerCursorImpl<'a, CsvSerializer, B, KD, VD>>::new(
                     &self.batch,
                     SqlSerdeConfig::default(),
@@ -445,7 +468,11 @@ where
                 avro_ser_config(),
                 AvroSerializer::create(),
             )),
-            RecordFormat::Raw => todo!(),
+            RecordFormat::Raw => Box::new(<SerCursorImpl<'a, RawSerializer, B, KD, VD>>::new(
+                &self.batch,
+                raw_serde_config(),
+                RawSerializer::create(),
+            )),
         })
     }
 }
@@ -702,6 +729,25 @@ where
         )
     }
 
+    fn serialize_val_to_arrow(&mut self, dst: &mut ArrayBuilder) -> AnyResult<()> {
+        self.serializer.serialize_arrow(
+            &SerializeWithContextWrapper::new(self.val.as_ref().unwrap(), &self.serde_config),
+            dst,
+        )
+    }
+
+    fn serialize_val_to_arrow_with_metadata(
+        &mut self,
+        metadata: &dyn ErasedSerialize,
+        dst: &mut ArrayBuilder,
+    ) -> AnyResult<()> {
+        self.serializer.serialize_arrow_with_metadata(
+            &SerializeWithContextWrapper::new(self.val.as_ref().unwrap(), &self.serde_config),
+            metadata,
+            dst,
+        )
+    }
+
     fn val_to_json(&mut self) -> AnyResult<serde_json::Value> {
         serde_json::to_value(SerializeWithContextWrapper::new(
             self.val.as_ref().unwrap(),
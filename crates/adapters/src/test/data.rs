@@ -1236,3 +1236,60 @@ deserialize_table_record!(DeltaTestStruct["DeltaTestStruct", 20] {
     (variant, "variant", false, Variant, None),
     (uuid, "uuid", false, Uuid, None)
 });
+
+/// Record of a dead-letter table.
+#[derive(
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    Clone,
+    Hash,
+    SizeOf,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+)]
+#[archive_attr(derive(Ord, Eq, PartialEq, PartialOrd))]
+pub struct DeadLetterStruct {
+    pub connector: Option<String>,
+    pub error: Option<String>,
+    pub event_number: Option<i64>,
+    pub step: Option<i64>,
+    pub raw_text: Option<String>,
+    pub raw_bytes: Option<ByteArray>,
+}
+
+impl DeadLetterStruct {
+    pub fn schema() -> Vec<Field> {
+        vec![
+            Field::new("connector".into(), ColumnType::varchar(true)),
+            Field::new("error".into(), ColumnType::varchar(true)),
+            Field::new("event_number".into(), ColumnType::bigint(true)),
+            Field::new("step".into(), ColumnType::bigint(true)),
+            Field::new("raw_text".into(), ColumnType::varchar(true)),
+            Field::new("raw_bytes".into(), ColumnType::varbinary(true)),
+        ]
+    }
+}
+
+serialize_table_record!(DeadLetterStruct[6]{
+    connector["connector"]: Option<String>,
+    error["error"]: Option<String>,
+    event_number["event_number"]: Option<i64>,
+    step["step"]: Option<i64>,
+    raw_text["raw_text"]: Option<String>,
+    raw_bytes["raw_bytes"]: Option<ByteArray>
+});
+
+deserialize_table_record!(DeadLetterStruct["DeadLetterStruct", 6] {
+    (connector, "connector", false, Option<String>, Some(None)),
+    (error, "error", false, Option<String>, Some(None)),
+    (event_number, "event_number", false, Option<i64>, Some(None)),
+    (step, "step", false, Option<i64>, Some(None)),
+    (raw_text, "raw_text", false, Option<String>, Some(None)),
+    (raw_bytes, "raw_bytes", false, Option<ByteArray>, Some(None))
+});
@@ -3,15 +3,53 @@
 use crate::{test::TestStruct, transport::http::Chunk};
 use actix_web::web::Bytes;
 use async_stream::stream;
-use awc::{error::PayloadError, ClientRequest};
+use awc::{
+    error::PayloadError,
+    ws::{Frame, Message, ProtocolError},
+    ClientRequest,
+};
 use csv::ReaderBuilder as CsvReaderBuilder;
 use csv::WriterBuilder as CsvWriterBuilder;
-use futures::{Stream, StreamExt};
+use feldera_types::transport::http::{
+    EgressWebSocketMessage, EgressWebSocketMessageType, IngressWebSocketResponse,
+    IngressWebSocketResponseType,
+};
+use futures::{Sink, SinkExt, Stream, StreamExt};
 use serde::Deserialize;
 use tracing::trace;
 
 pub struct TestHttpSender;
 pub struct TestHttpReceiver;
+pub struct TestWebSocketSender;
+pub struct TestWebSocketReceiver;
+
+/// Serialize `batch` as `csv`.
+fn batch_to_csv(batch: &[TestStruct]) -> Bytes {
+    let mut writer = CsvWriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::with_capacity(batch.len() * 32));
+
+    for val in batch.iter().cloned() {
+        writer.serialize(val).unwrap();
+    }
+    writer.flush().unwrap();
+    Bytes::from(writer.into_inner().unwrap())
+}
+
+/// Parse `csv` produced by the `csv` encoder and append the records to
+/// `received`.
+fn parse_csv_output(csv: &str, received: &mut Vec<TestStruct>) {
+    let mut builder = CsvReaderBuilder::new();
+    builder.has_headers(false);
+    let mut reader = builder.from_reader(csv.as_bytes());
+    for (record, w) in reader
+        .deserialize::<(TestStruct, i32)>()
+        .map(Result::unwrap)
+    {
+        assert_eq!(w, 1);
+        received.push(record);
+    }
+}
 
 impl TestHttpSender {
     /// Serialize `data` as `csv` and send it as part of HTTP request.
@@ -22,16 +60,7 @@ impl TestHttpSender {
         let mut response = req
             .send_stream(stream! {
                 for batch in data.iter() {
-                    let mut writer = CsvWriterBuilder::new()
-                        .has_headers(false)
-                        .from_writer(Vec::with_capacity(batch.len() * 32));
-
-                    for val in batch.iter().cloned() {
-                        writer.serialize(val).unwrap();
-                    }
-                    writer.flush().unwrap();
-                    let bytes = writer.into_inner().unwrap();
-                    yield <Result<_, anyhow::Error>>::Ok(Bytes::from(bytes));
+                    yield <Result<_, anyhow::Error>>::Ok(batch_to_csv(batch));
                 }
             })
             .await
@@ -81,22 +110,8 @@ impl TestHttpReceiver {
                     let chunk = chunk.unwrap();
                     trace!("TestHttpReceiver: chunk {}", chunk.sequence_number);
 
-                    let mut builder = CsvReaderBuilder::new();
-                    builder.has_headers(false);
-                    let mut reader = builder.from_reader(if let Some(csv) = &chunk.text_data {
-                        csv.as_bytes()
-                    } else {
-                        continue;
-                    });
-                    // let mut num_received = 0;
-                    for (record, w) in reader
-                        .deserialize::<(TestStruct, i32)>()
-                        .map(Result::unwrap)
-                    {
-                        // num_received += 1;
-                        assert_eq!(w, 1);
-                        // println!("received record: {:?}", record);
-                        received.push(record);
+                    if let Some(csv) = &chunk.text_data {
+                        parse_csv_output(csv, &mut received);
                     }
                 }
                 data.clear();
@@ -107,3 +122,150 @@ impl TestHttpReceiver {
         assert_eq!(expected, received);
     }
 }
+
+/// Returns the next text message received over a WebSocket connection.
+async fn next_text_message<S>(connection: &mut S) -> Bytes
+where
+    S: Stream<Item = Result<Frame, ProtocolError>> + Unpin,
+{
+    loop {
+        if let Frame::Text(text) = connection.next().await.unwrap().unwrap() {
+            return text;
+        }
+    }
+}
+
+impl TestWebSocketSender {
+    /// Serialize each batch in `data` as `csv` and send it as a separate
+    /// message over an `/ingress` WebSocket connection, waiting for the
+    /// response to each message.  Returns the completion token in the response
+    /// to the last message.
+    pub async fn send<S>(connection: &mut S, data: &[Vec<TestStruct>]) -> String
+    where
+        S: Sink<Message, Error = ProtocolError>
+            + Stream<Item = Result<Frame, ProtocolError>>
+            + Unpin,
+    {
+        let mut token = String::new();
+        for (sequence_number, batch) in data.iter().enumerate() {
+            connection
+                .send(Message::Binary(batch_to_csv(batch)))
+                .await
+                .unwrap();
+            let response = serde_json::from_slice::<IngressWebSocketResponse>(
+                &next_text_message(connection).await,
+            )
+            .unwrap();
+            assert_eq!(
+                response.response_type,
+                IngressWebSocketResponseType::Ack,
+                "unexpected response: {response:?}"
+            );
+            assert_eq!(response.sequence_number, sequence_number as u64);
+            token = response.token.unwrap();
+        }
+        token
+    }
+}
+
+impl TestWebSocketReceiver {
+    /// Read the snapshot sent at the start of an `/egress` WebSocket
+    /// connection opened in `snapshot_and_follow` mode, which must consist of
+    /// the contents of `data`.  Returns the step that the snapshot reflects.
+    pub async fn wait_for_snapshot<S>(connection: &mut S, data: &[Vec<TestStruct>]) -> u64
+    where
+        S: Stream<Item = Result<Frame, ProtocolError>> + Unpin,
+    {
+        let mut expected = data
+            .iter()
+            .flat_map(|data| data.iter())
+            .cloned()
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        let mut received = Vec::with_capacity(expected.len());
+        let mut snapshot_step = None;
+        loop {
+            let message = serde_json::from_slice::<EgressWebSocketMessage>(
+                &next_text_message(connection).await,
+            )
+            .unwrap();
+            trace!("TestWebSocketReceiver: message {message:?}");
+
+            match message.message_type {
+                EgressWebSocketMessageType::Data => {
+                    let step = message.step.unwrap();
+                    assert_eq!(*snapshot_step.get_or_insert(step), step);
+                    if let Some(csv) = &message.text_data {
+                        parse_csv_output(csv, &mut received);
+                    }
+                }
+                EgressWebSocketMessageType::StepEnd => {
+                    let step = message.step.unwrap();
+                    assert_eq!(*snapshot_step.get_or_insert(step), step);
+                    break;
+                }
+                EgressWebSocketMessageType::Heartbeat => (),
+            }
+        }
+        received.sort();
+
+        assert_eq!(expected, received);
+        snapshot_step.unwrap()
+    }
+
+    /// Read from an `/egress` WebSocket connection until the entire contents
+    /// of `data` is received.
+    pub async fn wait_for_output_unordered<S>(connection: &mut S, data: &[Vec<TestStruct>])
+    where
+        S: Stream<Item = Result<Frame, ProtocolError>> + Unpin,
+    {
+        let num_records: usize = data.iter().map(Vec::len).sum();
+
+        let mut expected = data
+            .iter()
+            .flat_map(|data| data.iter())
+            .cloned()
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        let mut received = Vec::with_capacity(num_records);
+        let mut sequence_number = None;
+
+        // Wait for the end of the step that completes the output, so that the
+        // next call starts with the next step.
+        let mut step_ended = false;
+        while received.len() < num_records || !step_ended {
+            let message = serde_json::from_slice::<EgressWebSocketMessage>(
+                &next_text_message(connection).await,
+            )
+            .unwrap();
+            trace!("TestWebSocketReceiver: message {message:?}");
+
+            // The connection is created with backpressure, so no messages are
+            // dropped.
+            if let Some(sequence_number) = sequence_number {
+                assert_eq!(message.sequence_number, sequence_number + 1);
+            }
+            sequence_number = Some(message.sequence_number);
+
+            match message.message_type {
+                EgressWebSocketMessageType::Data => {
+                    assert!(message.step.is_some());
+                    step_ended = false;
+                    if let Some(csv) = &message.text_data {
+                        parse_csv_output(csv, &mut received);
+                    }
+                }
+                EgressWebSocketMessageType::StepEnd => {
+                    assert!(message.step.is_some());
+                    step_ended = true;
+                }
+                EgressWebSocketMessageType::Heartbeat => (),
+            }
+        }
+        received.sort();
+
+        assert_eq!(expected, received);
+    }
+}
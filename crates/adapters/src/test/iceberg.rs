@@ -284,6 +284,186 @@ fn iceberg_localfs_input_test(
     assert_eq!(zset, expected_zset);
 }
 
+/// Write a view to a new Iceberg table in the local FS without a catalog and
+/// read it back using the Iceberg input connector.
+#[test]
+#[cfg(feature = "iceberg-tests-fs")]
+fn iceberg_localfs_output_test() {
+    init_logging();
+
+    let data = data(100_000);
+
+    let table_dir = tempfile::TempDir::new().unwrap();
+    let table_path = table_dir.path().display().to_string();
+    let metadata_dir = format!("{table_path}/metadata");
+
+    let ndjson_file = data_to_ndjson(data.clone());
+
+    let config_str = format!(
+        r#"
+name: test
+workers: 4
+inputs:
+    test_input1:
+        stream: test_input1
+        transport:
+            name: file_input
+            config:
+                path: "{}"
+        format:
+            name: json
+            config:
+                update_format: "raw"
+outputs:
+    test_output1:
+        stream: test_output1
+        transport:
+            name: "iceberg_output"
+            config:
+                metadata_location: "{metadata_dir}/v0.metadata.json"
+        enable_output_buffer: true
+        max_output_buffer_size_records: 10000
+        max_output_buffer_time_millis: 100
+"#,
+        ndjson_file.path().display(),
+    );
+
+    let config: PipelineConfig = serde_yaml::from_str(&config_str).unwrap();
+
+    let controller = Controller::with_config(
+        |workers| {
+            Ok(test_circuit::<IcebergTestStruct>(
+                workers,
+                &IcebergTestStruct::schema_with_lateness(),
+                &[None],
+            ))
+        },
+        &config,
+        Box::new(move |e| panic!("iceberg_localfs_output_test: error: {e}")),
+    )
+    .unwrap();
+
+    controller.start();
+    wait(|| controller.status().pipeline_complete(), 100_000).unwrap();
+    controller.stop().unwrap();
+
+    // The connector records the version of the latest metadata file in the version hint.
+    let version = std::fs::read_to_string(format!("{metadata_dir}/version-hint.text")).unwrap();
+    let metadata_path = format!("{metadata_dir}/v{}.metadata.json", version.trim());
+
+    let mut json_file = iceberg_snapshot_to_json::<IcebergTestStruct>(
+        &IcebergTestStruct::schema_with_lateness(),
+        &[("metadata_location".to_string(), metadata_path)]
+            .into_iter()
+            .collect::<HashMap<_, _>>(),
+    );
+
+    let expected_zset = dbsp::OrdZSet::from_tuples(
+        (),
+        data.into_iter()
+            .map(|x| dbsp::utils::Tup2(dbsp::utils::Tup2(x, ()), 1))
+            .collect(),
+    );
+    let zset = file_to_zset::<IcebergTestStruct>(
+        json_file.as_file_mut(),
+        "json",
+        r#"update_format: "insert_delete""#,
+    );
+
+    assert_eq!(zset, expected_zset);
+}
+
+/// Write inserts, deletes, and updates to a new Iceberg table in the local FS
+/// in separate steps and read the table back using the Iceberg input connector.
+#[test]
+#[cfg(feature = "iceberg-tests-fs")]
+fn iceberg_localfs_output_deletes_test() {
+    use crate::format::Encoder;
+    use crate::{
+        catalog::SerBatchReader, static_compile::seroutput::SerBatchImpl, test::TestStruct,
+    };
+    use dbsp::{utils::Tup2, OrdZSet};
+    use feldera_adapterlib::transport::Step;
+    use feldera_iceberg::IcebergOutputEndpoint;
+    use feldera_types::transport::iceberg::IcebergWriterConfig;
+
+    init_logging();
+
+    let table_dir = tempfile::TempDir::new().unwrap();
+    let metadata_dir = format!("{}/metadata", table_dir.path().display());
+
+    let config: IcebergWriterConfig = serde_json::from_value(serde_json::json!({
+        "metadata_location": format!("{metadata_dir}/v0.metadata.json")
+    }))
+    .unwrap();
+    let mut endpoint = IcebergOutputEndpoint::new(
+        "test_output",
+        &config,
+        &None,
+        &TestStruct::relation_schema(),
+        Box::new(|fatal, e| {
+            panic!("iceberg_localfs_output_deletes_test: error (fatal: {fatal}): {e}")
+        }),
+        Box::new(|_num_bytes, _num_records| ()),
+    )
+    .unwrap();
+
+    let record = |id: u32, s: &str| TestStruct {
+        id,
+        b: id % 2 == 0,
+        i: Some(id as i64),
+        s: s.to_string(),
+    };
+
+    let steps = [
+        // Insert records 0..1000.
+        (0..1000)
+            .map(|id| Tup2(record(id, "old"), 1))
+            .collect::<Vec<_>>(),
+        // Delete records 0..100 and update records 100..200.
+        (0..200)
+            .map(|id| Tup2(record(id, "old"), -1))
+            .chain((100..200).map(|id| Tup2(record(id, "new"), 1)))
+            .collect(),
+        // Insert a record that was deleted in a previous step, which must
+        // not be affected by the earlier equality delete.
+        vec![Tup2(record(0, "old"), 1)],
+    ];
+    for (step, updates) in steps.into_iter().enumerate() {
+        let zset = OrdZSet::from_keys((), updates);
+        let batch = SerBatchImpl::<_, TestStruct, ()>::new(zset);
+        endpoint.consumer().batch_start(step as Step);
+        endpoint.encode(&batch as &dyn SerBatchReader).unwrap();
+        endpoint.consumer().batch_end();
+    }
+    drop(endpoint);
+
+    let version = std::fs::read_to_string(format!("{metadata_dir}/version-hint.text")).unwrap();
+    let metadata_path = format!("{metadata_dir}/v{}.metadata.json", version.trim());
+
+    let mut json_file = iceberg_snapshot_to_json::<TestStruct>(
+        &TestStruct::schema(),
+        &[("metadata_location".to_string(), metadata_path)]
+            .into_iter()
+            .collect::<HashMap<_, _>>(),
+    );
+
+    let expected_zset = OrdZSet::from_keys(
+        (),
+        std::iter::once(Tup2(record(0, "old"), 1))
+            .chain((100..200).map(|id| Tup2(record(id, "new"), 1)))
+            .chain((200..1000).map(|id| Tup2(record(id, "old"), 1)))
+            .collect(),
+    );
+    let zset = file_to_zset::<TestStruct>(
+        json_file.as_file_mut(),
+        "json",
+        r#"update_format: "insert_delete""#,
+    );
+
+    assert_eq!(zset, expected_zset);
+}
+
 #[test]
 #[cfg(feature = "iceberg-tests-glue")]
 fn iceberg_glue_s3_input_test() {
@@ -237,6 +237,14 @@ impl TestProducer {
         self.producer.flush(Timeout::Never).unwrap();
     }
 
+    pub fn send_keyed_string(&self, key: &str, string: &str, topic: &str) {
+        let record = <BaseRecord<str, str, ()>>::to(topic)
+            .key(key)
+            .payload(string);
+        self.producer.send(record).unwrap();
+        self.producer.flush(Timeout::Never).unwrap();
+    }
+
     pub fn send_string_partition(&self, string: &str, topic: &str, partition: i32) {
         let record = <BaseRecord<(), str, ()>>::to(topic)
             .partition(partition)
@@ -159,7 +159,7 @@ where
                 self.clone(),
                 SqlSerdeConfig::from(flavor),
             ))),
-            RecordFormat::Parquet(_) => {
+            RecordFormat::Parquet(_) | RecordFormat::CsvEncoder(_) => {
                 todo!()
             }
             #[cfg(feature = "with-avro")]
@@ -421,10 +421,22 @@ where
 
     fn insert_with_polarities(
         &mut self,
-        _data: &arrow::array::RecordBatch,
-        _polarities: &[bool],
+        data: &arrow::array::RecordBatch,
+        polarities: &[bool],
     ) -> AnyResult<()> {
-        todo!()
+        let deserializer = ArrowDeserializer::from_record_batch(data)?;
+        let deserializer =
+            &mut <dyn ErasedDeserializer>::erase(deserializer) as &mut dyn ErasedDeserializer;
+
+        let records = Vec::<T>::deserialize_with_context(deserializer, &self.config)?;
+        self.buffer.updates.extend(
+            records
+                .into_iter()
+                .zip(polarities)
+                .map(|(r, polarity)| MockUpdate::<T, U>::with_polarity(r, *polarity)),
+        );
+
+        Ok(())
     }
 
     fn fork(&self) -> Box<dyn ArrowStream> {
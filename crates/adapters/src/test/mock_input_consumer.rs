@@ -2,6 +2,7 @@ use crate::catalog::InputCollectionHandle;
 use crate::format::{get_input_format, InputBuffer, Splitter};
 use crate::{controller::FormatConfig, InputConsumer, ParseError, Parser};
 use anyhow::{anyhow, Error as AnyError};
+use feldera_adapterlib::format::ConnectorMetadata;
 use feldera_adapterlib::transport::Resume;
 use feldera_types::config::FtModel;
 use std::sync::{Arc, Mutex, MutexGuard};
@@ -178,9 +179,21 @@ impl MockInputParser {
 
 impl Parser for MockInputParser {
     fn parse(&mut self, data: &[u8]) -> (Option<Box<dyn InputBuffer>>, Vec<ParseError>) {
+        self.parse_with_metadata(data, &ConnectorMetadata::new())
+    }
+
+    fn parse_with_metadata(
+        &mut self,
+        data: &[u8],
+        metadata: &ConnectorMetadata,
+    ) -> (Option<Box<dyn InputBuffer>>, Vec<ParseError>) {
         let mut state = self.0.lock().unwrap();
         state.data.extend_from_slice(data);
-        let (buffer, errors) = state.parser.parse(data);
+        let (buffer, errors) = if metadata.is_empty() {
+            state.parser.parse(data)
+        } else {
+            state.parser.parse_with_metadata(data, metadata)
+        };
 
         for error in errors.iter() {
             // println!("parser returned '{:?}'", state.parser_result);
@@ -13,6 +13,9 @@ pub struct MockOutputConsumer {
             )>,
         >,
     >,
+    /// Topic of each message in `data`, if it was written with
+    /// `push_key_to_topic`.
+    pub topics: Arc<Mutex<Vec<Option<String>>>>,
     max_buffer_size_bytes: usize,
 }
 
@@ -40,6 +43,7 @@ impl MockOutputConsumer {
     pub fn with_max_buffer_size_bytes(bytes: usize) -> Self {
         Self {
             data: Arc::new(Mutex::new(Vec::new())),
+            topics: Arc::new(Mutex::new(Vec::new())),
             max_buffer_size_bytes: bytes,
         }
     }
@@ -57,6 +61,7 @@ impl MockOutputConsumer {
     ) -> Self {
         Self {
             data,
+            topics: Arc::new(Mutex::new(Vec::new())),
             max_buffer_size_bytes: usize::MAX,
         }
     }
@@ -72,7 +77,8 @@ impl OutputConsumer for MockOutputConsumer {
         self.data
             .lock()
             .unwrap()
-            .push((None, Some(buffer.to_vec()), vec![]))
+            .push((None, Some(buffer.to_vec()), vec![]));
+        self.topics.lock().unwrap().push(None);
     }
     fn push_key(
         &mut self,
@@ -89,7 +95,19 @@ impl OutputConsumer for MockOutputConsumer {
                 .iter()
                 .map(|(k, v)| (k.to_string(), v.map(|bytes| bytes.to_vec())))
                 .collect::<Vec<_>>(),
-        ))
+        ));
+        self.topics.lock().unwrap().push(None);
+    }
+    fn push_key_to_topic(
+        &mut self,
+        topic: &str,
+        key: Option<&[u8]>,
+        val: Option<&[u8]>,
+        headers: &[(&str, Option<&[u8]>)],
+        num_records: usize,
+    ) {
+        self.push_key(key, val, headers, num_records);
+        *self.topics.lock().unwrap().last_mut().unwrap() = Some(topic.to_string());
     }
     fn batch_end(&mut self) {}
 }
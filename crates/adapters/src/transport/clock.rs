@@ -71,9 +71,12 @@ pub fn now_endpoint_config(config: &PipelineConfig) -> InputEndpointConfig {
             max_batch_size: 1,
             // This must be >1; otherwise the controller will pause the connector after every input.
             max_queued_records: 2,
+            max_records_per_second: None,
+            max_bytes_per_second: None,
             paused: false,
             labels: vec![],
             start_after: None,
+            dead_letter: None,
         },
     }
 }
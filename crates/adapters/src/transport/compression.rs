@@ -0,0 +1,406 @@
+//! Transparent decompression for byte-stream input transports.
+//!
+//! [`Decompressor`] is a push-style decoder: transports feed it chunks of
+//! compressed data as they arrive and get back whatever decompressed data is
+//! available.  [`DecompressReader`] wraps it in a [`Read`] implementation for
+//! transports that pull data from a file.
+//!
+//! Offsets reported by transports that use these types are positions in the
+//! decompressed stream.  Compressed streams can't be seeked, so seeking to an
+//! offset other than zero requires rereading the compressed data from the
+//! beginning and discarding decompressed bytes up to the offset (see
+//! [`Decompressor::restart`]).
+
+use bytes::Bytes;
+use bzip2::write::BzDecoder;
+use feldera_types::transport::compression::Compression;
+use flate2::write::MultiGzDecoder;
+use std::cmp::min;
+use std::io::{Read, Result as IoResult, Seek, SeekFrom, Write};
+use xz2::write::XzDecoder;
+use zstd::stream::write::Decoder as ZstdDecoder;
+
+/// Number of bytes needed to recognize any of the supported formats by its
+/// magic bytes (xz has the longest magic number).
+const MAX_MAGIC_LEN: usize = 6;
+
+/// Size of the buffer used by [`DecompressReader`] to read compressed data.
+const READ_BUFFER_SIZE: usize = 65536;
+
+/// Returns the compression format indicated by the extension of `name`, which
+/// may be a file path, a URL, or an object key.
+fn compression_from_extension(name: &str) -> Option<Compression> {
+    // Ignore the query string and fragment of URLs.
+    let name = name.split(['?', '#']).next().unwrap_or_default();
+    let (_, extension) = name.rsplit_once('.')?;
+    match extension.to_ascii_lowercase().as_str() {
+        "gz" | "gzip" => Some(Compression::Gzip),
+        "zst" | "zstd" => Some(Compression::Zstd),
+        "bz2" => Some(Compression::Bzip2),
+        "xz" => Some(Compression::Xz),
+        _ => None,
+    }
+}
+
+/// Returns the compression format indicated by the magic bytes at the start of
+/// `data`.  Data that doesn't start with any known magic number is assumed to
+/// be uncompressed.
+fn compression_from_magic(data: &[u8]) -> Compression {
+    if data.starts_with(&[0x1f, 0x8b]) {
+        Compression::Gzip
+    } else if data.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        Compression::Zstd
+    } else if data.starts_with(b"BZh") {
+        Compression::Bzip2
+    } else if data.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+        Compression::Xz
+    } else {
+        Compression::None
+    }
+}
+
+/// Decoder for a single, known compression format.
+enum Decoder {
+    Identity,
+    Gzip(Box<MultiGzDecoder<Vec<u8>>>),
+    Zstd(Box<ZstdDecoder<'static, Vec<u8>>>),
+    Bzip2(Box<BzDecoder<Vec<u8>>>),
+    Xz(Box<XzDecoder<Vec<u8>>>),
+}
+
+impl Decoder {
+    fn new(compression: Compression) -> IoResult<Self> {
+        Ok(match compression {
+            Compression::None | Compression::Auto => Self::Identity,
+            Compression::Gzip => Self::Gzip(Box::new(MultiGzDecoder::new(Vec::new()))),
+            Compression::Zstd => Self::Zstd(Box::new(ZstdDecoder::new(Vec::new())?)),
+            Compression::Bzip2 => Self::Bzip2(Box::new(BzDecoder::new(Vec::new()))),
+            Compression::Xz => Self::Xz(Box::new(XzDecoder::new_multi_decoder(Vec::new()))),
+        })
+    }
+
+    /// Decompresses `data` and returns the decompressed data that is available
+    /// so far.
+    fn decode(&mut self, data: Bytes) -> IoResult<Bytes> {
+        match self {
+            Self::Identity => return Ok(data),
+            Self::Gzip(decoder) => decoder.write_all(&data)?,
+            Self::Zstd(decoder) => decoder.write_all(&data)?,
+            Self::Bzip2(decoder) => decoder.write_all(&data)?,
+            Self::Xz(decoder) => decoder.write_all(&data)?,
+        }
+        Ok(self.take_output())
+    }
+
+    /// Signals the end of the compressed data and returns the remaining
+    /// decompressed data.  Fails if the compressed data is truncated.
+    fn finish(&mut self) -> IoResult<Bytes> {
+        match self {
+            Self::Identity => return Ok(Bytes::new()),
+            Self::Gzip(decoder) => decoder.try_finish()?,
+            Self::Zstd(decoder) => decoder.flush()?,
+            Self::Bzip2(decoder) => decoder.try_finish()?,
+            Self::Xz(decoder) => decoder.try_finish()?,
+        }
+        Ok(self.take_output())
+    }
+
+    fn take_output(&mut self) -> Bytes {
+        let output = match self {
+            Self::Identity => return Bytes::new(),
+            Self::Gzip(decoder) => decoder.get_mut(),
+            Self::Zstd(decoder) => decoder.get_mut(),
+            Self::Bzip2(decoder) => decoder.get_mut(),
+            Self::Xz(decoder) => decoder.get_mut(),
+        };
+        Bytes::from(std::mem::take(output))
+    }
+}
+
+/// Incremental decompressor for a single stream of input data.
+pub(crate) struct Decompressor {
+    /// Configured compression, with `Auto` resolved based on the name of the
+    /// stream if possible.
+    compression: Compression,
+
+    /// Decoder for the stream, once the compression format is known.
+    decoder: Option<Decoder>,
+
+    /// Data received before the compression format is known.
+    pending: Vec<u8>,
+
+    /// Number of decompressed bytes still to be discarded.
+    skip: u64,
+}
+
+impl Decompressor {
+    /// Creates a decompressor for a stream named `name` (a file path, URL,
+    /// or object key) compressed according to `compression`.
+    pub fn new(compression: Compression, name: &str) -> Self {
+        let compression = match compression {
+            Compression::Auto => compression_from_extension(name).unwrap_or(Compression::Auto),
+            compression => compression,
+        };
+        let mut decompressor = Self {
+            compression,
+            decoder: None,
+            pending: Vec::new(),
+            skip: 0,
+        };
+        decompressor.restart(0);
+        decompressor
+    }
+
+    /// Returns true if the stream is known to be uncompressed, in which case
+    /// offsets in the decompressed stream are the same as offsets in the raw
+    /// stream and the raw stream can be seeked directly.
+    pub fn is_identity(&self) -> bool {
+        self.compression == Compression::None
+    }
+
+    /// Resets the decompressor to the start of the compressed stream.  The
+    /// first `skip` bytes of decompressed output will be discarded, which
+    /// allows resuming from offset `skip` in the decompressed stream.
+    pub fn restart(&mut self, skip: u64) {
+        self.decoder = self.is_identity().then_some(Decoder::Identity);
+        self.pending.clear();
+        self.skip = skip;
+    }
+
+    /// Feeds `data` to the decompressor and returns the decompressed data
+    /// that is available so far.
+    pub fn decompress(&mut self, data: Bytes) -> IoResult<Bytes> {
+        let output = match &mut self.decoder {
+            Some(decoder) => decoder.decode(data)?,
+            None => {
+                self.pending.extend_from_slice(&data);
+                if self.compression == Compression::Auto && self.pending.len() < MAX_MAGIC_LEN {
+                    return Ok(Bytes::new());
+                }
+                self.start_decoder()?
+            }
+        };
+        Ok(self.discard(output))
+    }
+
+    /// Signals the end of the compressed stream and returns the remaining
+    /// decompressed data.
+    pub fn finish(&mut self) -> IoResult<Bytes> {
+        let mut output = match &self.decoder {
+            Some(_) => Vec::new(),
+            None => self.start_decoder()?.to_vec(),
+        };
+        output.extend_from_slice(&self.decoder.as_mut().unwrap().finish()?);
+        Ok(self.discard(Bytes::from(output)))
+    }
+
+    /// Creates the decoder, detecting the compression format from the pending
+    /// data if necessary, and feeds it the pending data.
+    fn start_decoder(&mut self) -> IoResult<Bytes> {
+        let compression = match self.compression {
+            Compression::Auto => compression_from_magic(&self.pending),
+            compression => compression,
+        };
+        let mut decoder = Decoder::new(compression)?;
+        let output = decoder.decode(Bytes::from(std::mem::take(&mut self.pending)))?;
+        self.decoder = Some(decoder);
+        Ok(output)
+    }
+
+    fn discard(&mut self, mut output: Bytes) -> Bytes {
+        if self.skip > 0 {
+            let n = min(self.skip, output.len() as u64) as usize;
+            self.skip -= n as u64;
+            let _ = output.split_to(n);
+        }
+        output
+    }
+}
+
+/// A [`Read`] implementation that decompresses data read from `R`.
+pub(crate) struct DecompressReader<R> {
+    inner: R,
+    decompressor: Decompressor,
+
+    /// Decompressed data not yet returned to the caller.
+    buffer: Bytes,
+
+    /// Buffer for reading compressed data.
+    raw: Vec<u8>,
+
+    /// Whether the compressed data has been read to the end.
+    finished: bool,
+}
+
+impl<R> DecompressReader<R> {
+    pub fn new(inner: R, decompressor: Decompressor) -> Self {
+        Self {
+            inner,
+            decompressor,
+            buffer: Bytes::new(),
+            raw: Vec::new(),
+            finished: false,
+        }
+    }
+}
+
+impl<R: Seek> DecompressReader<R> {
+    /// Seeks to `offset` in the decompressed data.
+    pub fn seek(&mut self, offset: u64) -> IoResult<()> {
+        self.buffer = Bytes::new();
+        self.finished = false;
+        if self.decompressor.is_identity() {
+            self.inner.seek(SeekFrom::Start(offset))?;
+        } else {
+            self.inner.seek(SeekFrom::Start(0))?;
+            self.decompressor.restart(offset);
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecompressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+        if self.decompressor.is_identity() {
+            // Uncompressed data can be read directly, and reading past the
+            // end is allowed, to support following a growing file.
+            return self.inner.read(buf);
+        }
+        while self.buffer.is_empty() && !buf.is_empty() {
+            if self.finished {
+                return Ok(0);
+            }
+            self.raw.resize(READ_BUFFER_SIZE, 0);
+            let n = self.inner.read(&mut self.raw)?;
+            self.buffer = if n == 0 {
+                self.finished = true;
+                self.decompressor.finish()?
+            } else {
+                self.decompressor
+                    .decompress(Bytes::copy_from_slice(&self.raw[..n]))?
+            };
+        }
+        let n = min(buf.len(), self.buffer.len());
+        buf[..n].copy_from_slice(&self.buffer.split_to(n));
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DecompressReader, Decompressor};
+    use bytes::Bytes;
+    use feldera_types::transport::compression::Compression;
+    use std::io::{Cursor, Read, Write};
+
+    const DATA: &[u8] = b"1,foo,true\n2,bar,false\n3,baz,true\n";
+
+    fn compress(compression: Compression, data: &[u8]) -> Vec<u8> {
+        match compression {
+            Compression::None | Compression::Auto => data.to_vec(),
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Zstd => zstd::encode_all(data, 0).unwrap(),
+            Compression::Bzip2 => {
+                let mut encoder =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Xz => {
+                let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+        }
+    }
+
+    /// Decompresses `data` feeding it to the decompressor one byte at a time.
+    fn decompress(decompressor: &mut Decompressor, data: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+        for byte in data {
+            output.extend_from_slice(
+                &decompressor
+                    .decompress(Bytes::copy_from_slice(&[*byte]))
+                    .unwrap(),
+            );
+        }
+        output.extend_from_slice(&decompressor.finish().unwrap());
+        output
+    }
+
+    const FORMATS: [(Compression, &str); 5] = [
+        (Compression::None, "data.csv"),
+        (Compression::Gzip, "data.csv.gz"),
+        (Compression::Zstd, "data.csv.zst"),
+        (Compression::Bzip2, "data.csv.bz2"),
+        (Compression::Xz, "data.csv.xz"),
+    ];
+
+    #[test]
+    fn explicit() {
+        for (compression, _) in FORMATS {
+            let compressed = compress(compression, DATA);
+            let mut decompressor = Decompressor::new(compression, "data");
+            assert_eq!(decompress(&mut decompressor, &compressed), DATA);
+        }
+    }
+
+    #[test]
+    fn auto_extension() {
+        for (compression, name) in FORMATS {
+            let compressed = compress(compression, DATA);
+            let mut decompressor = Decompressor::new(Compression::Auto, name);
+            assert_eq!(decompress(&mut decompressor, &compressed), DATA);
+        }
+    }
+
+    #[test]
+    fn auto_magic() {
+        for (compression, _) in FORMATS {
+            let compressed = compress(compression, DATA);
+            let mut decompressor =
+                Decompressor::new(Compression::Auto, "https://example.com/data?format=csv");
+            assert_eq!(decompress(&mut decompressor, &compressed), DATA);
+        }
+
+        // Data shorter than the longest magic number.
+        let mut decompressor = Decompressor::new(Compression::Auto, "data");
+        assert_eq!(decompress(&mut decompressor, b"1\n"), b"1\n");
+    }
+
+    #[test]
+    fn truncated() {
+        let compressed = compress(Compression::Gzip, DATA);
+        let mut decompressor = Decompressor::new(Compression::Gzip, "data");
+        decompressor
+            .decompress(Bytes::copy_from_slice(&compressed[..compressed.len() / 2]))
+            .unwrap();
+        assert!(decompressor.finish().is_err());
+    }
+
+    #[test]
+    fn reader_seek() {
+        for (compression, name) in FORMATS {
+            let compressed = compress(compression, DATA);
+            let mut reader = DecompressReader::new(
+                Cursor::new(compressed),
+                Decompressor::new(Compression::Auto, name),
+            );
+            let mut output = Vec::new();
+            reader.read_to_end(&mut output).unwrap();
+            assert_eq!(output, DATA);
+
+            for offset in [0, 11, DATA.len()] {
+                reader.seek(offset as u64).unwrap();
+                let mut output = Vec::new();
+                reader.read_to_end(&mut output).unwrap();
+                assert_eq!(output, &DATA[offset..]);
+            }
+        }
+    }
+}
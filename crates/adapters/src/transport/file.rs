@@ -1,19 +1,21 @@
+use super::compression::{DecompressReader, Decompressor};
 use super::{
     InputConsumer, InputEndpoint, InputReader, InputReaderCommand, OutputEndpoint,
     TransportInputEndpoint,
 };
 use crate::format::StreamSplitter;
 use crate::{InputBuffer, Parser};
-use anyhow::{bail, Error as AnyError, Result as AnyResult};
+use anyhow::{anyhow, bail, Error as AnyError, Result as AnyResult};
 use feldera_adapterlib::transport::Resume;
 use feldera_types::config::FtModel;
 use feldera_types::program_schema::Relation;
+use feldera_types::transport::compression::Compression;
 use feldera_types::transport::file::{FileInputConfig, FileOutputConfig};
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::hash::Hasher;
-use std::io::{Seek, SeekFrom};
 use std::ops::Range;
+use std::path::Path;
 use std::thread::{self, Thread};
 use std::{fs::File, io::Write, thread::spawn, time::Duration};
 use tokio::sync::mpsc::error::TryRecvError;
@@ -42,13 +44,81 @@ pub fn set_barrier(name: &str, value: usize) {
     BARRIERS.lock().unwrap().insert(name.into(), value);
 }
 
+/// The files read by a [FileInputEndpoint].
+#[derive(Clone)]
+enum InputFiles {
+    /// A single file.
+    File(String),
+
+    /// All the files in a directory.
+    Directory(String),
+
+    /// All the files that match a glob pattern.
+    Glob(String),
+}
+
+impl InputFiles {
+    fn new(path: &str, glob: bool) -> AnyResult<Self> {
+        if glob {
+            glob::Pattern::new(path).map_err(|e| anyhow!("invalid glob pattern '{path}': {e}"))?;
+            Ok(Self::Glob(path.to_string()))
+        } else if Path::new(path).is_dir() {
+            Ok(Self::Directory(path.to_string()))
+        } else {
+            Ok(Self::File(path.to_string()))
+        }
+    }
+
+    /// Returns true if the endpoint reads more than one file.
+    fn is_multiple(&self) -> bool {
+        !matches!(self, Self::File(_))
+    }
+
+    /// Returns the paths of the files currently present, in the order they
+    /// should be read.
+    fn list(&self) -> AnyResult<Vec<String>> {
+        let mut paths = match self {
+            Self::File(path) => return Ok(vec![path.clone()]),
+            Self::Directory(path) => {
+                let mut paths = Vec::new();
+                for entry in std::fs::read_dir(path)
+                    .map_err(|e| anyhow!("Failed to read input directory '{path}': {e}"))?
+                {
+                    let entry = entry?;
+                    if entry.file_type()?.is_file() {
+                        paths.push(entry.path().to_string_lossy().into_owned());
+                    }
+                }
+                paths
+            }
+            Self::Glob(pattern) => {
+                let mut paths = Vec::new();
+                for entry in glob::glob(pattern)? {
+                    let path = entry?;
+                    if path.is_file() {
+                        paths.push(path.to_string_lossy().into_owned());
+                    }
+                }
+                paths
+            }
+        };
+        paths.sort();
+        Ok(paths)
+    }
+}
+
 pub(crate) struct FileInputEndpoint {
     config: FileInputConfig,
+    files: InputFiles,
 }
 
 impl FileInputEndpoint {
-    pub(crate) fn new(config: FileInputConfig) -> Self {
-        Self { config }
+    pub(crate) fn new(config: FileInputConfig) -> AnyResult<Self> {
+        let files = InputFiles::new(&config.path, config.glob)?;
+        if config.follow && !files.is_multiple() && config.compression != Compression::None {
+            bail!("'follow' mode is not supported for compressed files; set 'compression' to 'none' or disable 'follow'");
+        }
+        Ok(Self { config, files })
     }
 }
 
@@ -73,6 +143,116 @@ impl TransportInputEndpoint for FileInputEndpoint {
     }
 }
 
+/// Parsed data from one of the input files, or, if `buffer` is `None`, a marker
+/// for the end of the file.
+struct QueuedBuffer {
+    path: String,
+    offsets: Range<u64>,
+    buffer: Option<Box<dyn InputBuffer>>,
+}
+
+/// The file currently being read.
+struct OpenFile {
+    path: String,
+    reader: DecompressReader<File>,
+}
+
+/// Tracks which input files have been read.
+///
+/// Files are read in order of their paths, so the cursor only needs to
+/// remember the last file it opened: all files that sort before it have been
+/// read.
+struct FileCursor {
+    files: InputFiles,
+    compression: Compression,
+
+    /// The last file that was opened.
+    last: Option<String>,
+
+    /// Files discovered but not yet opened.
+    pending: VecDeque<String>,
+
+    /// File to resume reading from and the offset to resume from.
+    resume: Option<(String, u64)>,
+
+    /// The file currently being read.
+    current: Option<OpenFile>,
+}
+
+impl FileCursor {
+    fn new(files: InputFiles, compression: Compression) -> Self {
+        Self {
+            files,
+            compression,
+            last: None,
+            pending: VecDeque::new(),
+            resume: None,
+            current: None,
+        }
+    }
+
+    /// Resets the cursor to the position recorded in `metadata`.
+    fn seek(&mut self, metadata: Metadata) {
+        self.resume = self
+            .metadata_path(metadata.path)
+            .map(|path| (path, metadata.offsets.end));
+        self.last = self.resume.as_ref().map(|(path, _)| path.clone());
+        self.pending.clear();
+        self.current = None;
+    }
+
+    /// Returns the path of the file that metadata refers to, or `None` if no
+    /// file had been read yet.
+    fn metadata_path(&self, path: Option<String>) -> Option<String> {
+        match &self.files {
+            InputFiles::File(path) => Some(path.clone()),
+            InputFiles::Directory(_) | InputFiles::Glob(_) => path,
+        }
+    }
+
+    /// Returns the path to record in metadata for the file at `path`.  The
+    /// path is implied when reading a single file.
+    fn metadata_path_for(&self, path: Option<String>) -> Option<String> {
+        path.filter(|_| self.files.is_multiple())
+    }
+
+    /// Opens `path` and positions it at `offset` in the decompressed data.
+    fn open(&self, path: &str, offset: u64) -> AnyResult<DecompressReader<File>> {
+        let file =
+            File::open(path).map_err(|e| anyhow!("Failed to open input file '{path}': {e}"))?;
+        let mut reader = DecompressReader::new(file, Decompressor::new(self.compression, path));
+        if offset > 0 {
+            reader.seek(offset)?;
+        }
+        Ok(reader)
+    }
+
+    /// Opens the next file to read, if there is one, and returns the offset in
+    /// the file to start reading from.
+    fn open_next(&mut self) -> AnyResult<Option<u64>> {
+        let path = match self.resume.take() {
+            Some((path, offset)) => Some((path, offset)),
+            None => {
+                if self.pending.is_empty() {
+                    for path in self.files.list()? {
+                        if self.last.as_ref().is_none_or(|last| &path > last) {
+                            self.pending.push_back(path);
+                        }
+                    }
+                }
+                self.pending.pop_front().map(|path| (path, 0))
+            }
+        };
+        let Some((path, offset)) = path else {
+            return Ok(None);
+        };
+        let reader = self.open(&path, offset)?;
+        self.last = Some(path.clone());
+        self.current = Some(OpenFile { path, reader });
+        Ok(Some(offset))
+    }
+}
+
 pub struct FileInputReader {
     sender: UnboundedSender<InputReaderCommand>,
     thread: Thread,
@@ -85,9 +265,13 @@ impl FileInputReader {
         parser: Box<dyn Parser>,
     ) -> AnyResult<Self> {
         let config = &endpoint.config;
-        let file = File::open(&config.path).map_err(|e| {
-            AnyError::msg(format!("Failed to open input file '{}': {e}", config.path))
-        })?;
+        let mut cursor = FileCursor::new(endpoint.files.clone(), config.compression);
+        if let InputFiles::File(path) = &endpoint.files {
+            // Report a missing file when the connector is created.
+            cursor.open(path, 0)?;
+        } else {
+            cursor.files.list()?;
+        }
 
         let (sender, receiver) = unbounded_channel();
         let join_handle = spawn({
@@ -100,7 +284,7 @@ impl FileInputReader {
             move || {
                 let _guard = info_span!("file_input", path).entered();
                 if let Err(error) = Self::worker_thread(
-                    file,
+                    cursor,
                     path,
                     buffer_size,
                     consumer.as_ref(),
@@ -120,7 +304,7 @@ impl FileInputReader {
     }
 
     fn worker_thread(
-        mut file: File,
+        mut cursor: FileCursor,
         path: String,
         buffer_size: usize,
         consumer: &dyn InputConsumer,
@@ -130,10 +314,25 @@ impl FileInputReader {
     ) -> AnyResult<()> {
         let mut splitter = StreamSplitter::new(parser.splitter());
 
-        let mut queue = VecDeque::<(Range<u64>, Box<dyn InputBuffer>)>::new();
+        // Starts parsing a file at `offset`.  Each of multiple files gets a
+        // fresh parser, so that, e.g., the header line of each CSV file is
+        // skipped.
+        let multiple = cursor.files.is_multiple();
+        let start_file = |parser: &mut Box<dyn Parser>, splitter: &mut StreamSplitter, offset| {
+            if multiple {
+                *parser = parser.fork();
+                *splitter = StreamSplitter::new(parser.splitter());
+            }
+            splitter.seek(offset);
+        };
+
+        let mut queue = VecDeque::<QueuedBuffer>::new();
         let mut extending = false;
-        let mut eof = false;
+        let mut eoi = false;
         let mut num_records = 0;
+
+        // The file and offset of the end of the data ingested so far.
+        let mut consumed = (cursor.metadata_path(None), 0);
         loop {
             loop {
                 let msg = receiver.try_recv();
@@ -148,11 +347,28 @@ impl FileInputReader {
                         let mut total = 0;
                         let mut hasher = consumer.hasher();
                         let limit = consumer.max_batch_size();
-                        let mut range: Option<Range<u64>> = None;
-                        while let Some((offsets, mut buffer)) = queue.pop_front() {
+                        let mut range: Option<(Option<String>, Range<u64>)> = None;
+                        while let Some(queued) = queue.front() {
+                            // Never combine data from multiple files in one step,
+                            // so that each step can be replayed from a single file.
+                            if range
+                                .as_ref()
+                                .is_some_and(|(path, _)| path.as_ref() != Some(&queued.path))
+                            {
+                                break;
+                            }
+                            let QueuedBuffer {
+                                path: file_path,
+                                offsets,
+                                buffer,
+                            } = queue.pop_front().unwrap();
+                            let Some(mut buffer) = buffer else {
+                                range = Some((Some(file_path), offsets));
+                                continue;
+                            };
                             range = match range {
-                                Some(range) => Some(range.start..offsets.end),
-                                None => Some(offsets),
+                                Some((path, range)) => Some((path, range.start..offsets.end)),
+                                None => Some((Some(file_path), offsets)),
                             };
                             total += buffer.len();
                             if let Some(hasher) = hasher.as_mut() {
@@ -164,12 +380,20 @@ impl FileInputReader {
                             }
                         }
 
-                        let offsets = range.unwrap_or_else(|| {
-                            let ofs = splitter.position();
-                            ofs..ofs
+                        let (file_path, offsets) = range.unwrap_or_else(|| match &cursor.current {
+                            Some(file) => {
+                                let ofs = splitter.position();
+                                (Some(file.path.clone()), ofs..ofs)
+                            }
+                            None => {
+                                let (path, ofs) = consumed.clone();
+                                (path, ofs..ofs)
+                            }
                         });
+                        consumed = (file_path.clone(), offsets.end);
                         let seek = serde_json::to_value(Metadata {
                             offsets: offsets.clone(),
+                            path: cursor.metadata_path_for(file_path),
                         })
                         .unwrap();
 
@@ -188,35 +412,47 @@ impl FileInputReader {
                         consumer.extended(total, Some(resume));
                     }
                     Ok(InputReaderCommand::Seek(metadata)) => {
-                        let Metadata { offsets } = serde_json::from_value(metadata)?;
-                        let offset = offsets.end;
-                        file.seek(SeekFrom::Start(offset))?;
-                        splitter.seek(offset);
+                        let metadata: Metadata = serde_json::from_value(metadata)?;
+                        consumed = (
+                            cursor.metadata_path(metadata.path.clone()),
+                            metadata.offsets.end,
+                        );
+                        cursor.seek(metadata);
+                        eoi = false;
                     }
                     Ok(InputReaderCommand::Replay { metadata, .. }) => {
-                        let Metadata { offsets } = serde_json::from_value(metadata)?;
-                        file.seek(SeekFrom::Start(offsets.start))?;
-                        splitter.seek(offsets.start);
+                        let metadata: Metadata = serde_json::from_value(metadata)?;
+                        let offsets = metadata.offsets.clone();
+                        let replay_path = cursor.metadata_path(metadata.path.clone());
                         let mut remainder = (offsets.end - offsets.start) as usize;
                         let mut num_records = 0;
                         let mut hasher = Xxh3Default::new();
-                        while remainder > 0 {
-                            let n = splitter.read(&mut file, buffer_size, remainder)?;
-                            if n == 0 {
-                                error!("file truncated since originally read");
-                                break;
-                            }
-                            remainder -= n;
-                            while let Some(chunk) = splitter.next(remainder == 0) {
-                                let (mut buffer, errors) = parser.parse(chunk);
-                                consumer.parse_errors(errors);
-                                consumer.buffered(buffer.len(), chunk.len());
-                                num_records += buffer.len();
-                                buffer.hash(&mut hasher);
-                                buffer.flush();
+                        if let Some(replay_path) = replay_path.as_ref().filter(|_| remainder > 0) {
+                            let mut file = cursor.open(replay_path, offsets.start)?;
+                            start_file(&mut parser, &mut splitter, offsets.start);
+                            while remainder > 0 {
+                                let n = splitter.read(&mut file, buffer_size, remainder)?;
+                                if n == 0 {
+                                    error!("file truncated since originally read");
+                                    break;
+                                }
+                                remainder -= n;
+                                while let Some(chunk) = splitter.next(remainder == 0) {
+                                    let (mut buffer, errors) = parser.parse(chunk);
+                                    consumer.parse_errors(errors);
+                                    consumer.buffered(buffer.len(), chunk.len());
+                                    num_records += buffer.len();
+                                    buffer.hash(&mut hasher);
+                                    buffer.flush();
+                                }
                             }
                         }
                         consumer.replayed(num_records, hasher.finish());
+
+                        // Continue reading from the end of the replayed data.
+                        consumed = (replay_path, offsets.end);
+                        cursor.seek(metadata);
+                        eoi = false;
                     }
                     Ok(InputReaderCommand::Disconnect) => return Ok(()),
                     Err(TryRecvError::Empty) => break,
@@ -224,19 +460,34 @@ impl FileInputReader {
                 }
             }
 
-            if !extending || eof {
+            if !extending || eoi {
                 thread::park();
                 continue;
             }
 
-            let n = splitter.read(&mut file, buffer_size, usize::MAX)?;
-            if n == 0 {
-                if follow {
-                    thread::park_timeout(SLEEP);
-                    continue;
+            if cursor.current.is_none() {
+                match cursor.open_next()? {
+                    Some(offset) => start_file(&mut parser, &mut splitter, offset),
+                    None if follow => {
+                        // Wait for new files to appear.
+                        thread::park_timeout(SLEEP);
+                        continue;
+                    }
+                    None => {
+                        eoi = true;
+                        consumer.eoi();
+                        continue;
+                    }
                 }
-                eof = true;
             }
+            let file = cursor.current.as_mut().unwrap();
+
+            let n = splitter.read(&mut file.reader, buffer_size, usize::MAX)?;
+            if n == 0 && follow && !cursor.files.is_multiple() {
+                thread::park_timeout(SLEEP);
+                continue;
+            }
+            let eof = n == 0;
             loop {
                 let start = splitter.position();
                 let Some(chunk) = splitter.next(eof) else {
@@ -248,11 +499,25 @@ impl FileInputReader {
 
                 if let Some(buffer) = buffer {
                     let end = splitter.position();
-                    queue.push_back((start..end, buffer));
+                    queue.push_back(QueuedBuffer {
+                        path: file.path.clone(),
+                        offsets: start..end,
+                        buffer: Some(buffer),
+                    });
                 }
             }
-            if n == 0 && !follow {
-                consumer.eoi();
+            if eof {
+                if cursor.files.is_multiple() {
+                    // Record the end of the file, so that the cursor moves
+                    // past files that contain no records.
+                    let end = splitter.position();
+                    queue.push_back(QueuedBuffer {
+                        path: file.path.clone(),
+                        offsets: end..end,
+                        buffer: None,
+                    });
+                }
+                cursor.current = None;
             }
         }
     }
@@ -329,11 +594,14 @@ however the File transport does not support this representation."
 
 #[cfg(test)]
 mod test {
-    use crate::test::{mock_input_pipeline, wait, DEFAULT_TIMEOUT_MS};
+    use crate::test::{mock_input_pipeline, wait, MockDeZSet, DEFAULT_TIMEOUT_MS};
+    use crate::InputReader;
     use csv::WriterBuilder as CsvWriterBuilder;
     use feldera_types::deserialize_without_context;
     use feldera_types::program_schema::Relation;
+    use flate2::{write::GzEncoder, Compression as GzCompression};
     use serde::{Deserialize, Serialize};
+    use serde_json::json;
     use std::{io::Write, thread::sleep, time::Duration};
     use tempfile::NamedTempFile;
 
@@ -416,6 +684,229 @@ format:
         }
     }
 
+    #[test]
+    fn test_csv_file_gzip() {
+        let test_data = [
+            TestStruct::new("foo".to_string(), true, 10),
+            TestStruct::new("bar".to_string(), false, -10),
+        ];
+        let temp_file = tempfile::Builder::new()
+            .suffix(".csv.gz")
+            .tempfile()
+            .unwrap();
+
+        // Rely on the `.gz` extension to detect the compression format.
+        let config_str = format!(
+            r#"
+stream: test_input
+transport:
+    name: file_input
+    config:
+        path: {:?}
+        buffer_size_bytes: 5
+        compression: auto
+format:
+    name: csv
+"#,
+            temp_file.path().to_str().unwrap()
+        );
+
+        let encoder = GzEncoder::new(temp_file.as_file(), GzCompression::default());
+        let mut writer = CsvWriterBuilder::new()
+            .has_headers(false)
+            .from_writer(encoder);
+        for val in test_data.iter().cloned() {
+            writer.serialize(val).unwrap();
+        }
+        writer.into_inner().unwrap().finish().unwrap();
+
+        let (endpoint, _consumer, _parser, zset) = mock_input_pipeline::<TestStruct, TestStruct>(
+            serde_yaml::from_str(&config_str).unwrap(),
+            Relation::empty(),
+        )
+        .unwrap();
+
+        endpoint.extend();
+        wait(
+            || {
+                endpoint.queue(false);
+                zset.state().flushed.len() == test_data.len()
+            },
+            DEFAULT_TIMEOUT_MS,
+        )
+        .unwrap();
+        for (i, upd) in zset.state().flushed.iter().enumerate() {
+            assert_eq!(upd.unwrap_insert(), &test_data[i]);
+        }
+    }
+
+    #[test]
+    fn test_csv_directory_follow() {
+        let test_data = [
+            TestStruct::new("foo".to_string(), true, 10),
+            TestStruct::new("bar".to_string(), false, -10),
+            TestStruct::new("baz".to_string(), true, 0),
+        ];
+        let dir = tempfile::tempdir().unwrap();
+        let write_file = |name: &str, val: &TestStruct| {
+            // Write the file elsewhere and rename it, so that the endpoint
+            // never sees a partially written file.
+            let tmp_path = dir.path().join(format!(".{name}.tmp"));
+            let mut writer = CsvWriterBuilder::new()
+                .has_headers(false)
+                .from_path(&tmp_path)
+                .unwrap();
+            writer.serialize(val.clone()).unwrap();
+            writer.flush().unwrap();
+            std::fs::rename(&tmp_path, dir.path().join(name)).unwrap();
+        };
+
+        // Written out of order to test that files are read in order of their names.
+        write_file("2.csv", &test_data[1]);
+        write_file("1.csv", &test_data[0]);
+
+        let config_str = format!(
+            r#"
+stream: test_input
+transport:
+    name: file_input
+    config:
+        path: {:?}
+        glob: true
+        follow: true
+format:
+    name: csv
+"#,
+            dir.path().join("*.csv").to_str().unwrap()
+        );
+
+        let (endpoint, consumer, _parser, zset) = mock_input_pipeline::<TestStruct, TestStruct>(
+            serde_yaml::from_str(&config_str).unwrap(),
+            Relation::empty(),
+        )
+        .unwrap();
+
+        endpoint.extend();
+        wait(
+            || {
+                endpoint.queue(false);
+                zset.state().flushed.len() == 2
+            },
+            DEFAULT_TIMEOUT_MS,
+        )
+        .unwrap();
+
+        // A new file is picked up.
+        write_file("3.csv", &test_data[2]);
+        wait(
+            || {
+                endpoint.queue(false);
+                zset.state().flushed.len() == test_data.len()
+            },
+            DEFAULT_TIMEOUT_MS,
+        )
+        .unwrap();
+        for (i, upd) in zset.state().flushed.iter().enumerate() {
+            assert_eq!(upd.unwrap_insert(), &test_data[i]);
+        }
+        assert!(!consumer.state().eoi);
+
+        endpoint.disconnect();
+    }
+
+    /// Seeking and replaying in a directory of CSV files with headers.
+    #[test]
+    fn test_csv_directory_seek_replay() {
+        let test_data = [
+            TestStruct::new("foo".to_string(), true, 10),
+            TestStruct::new("bar".to_string(), false, -10),
+            TestStruct::new("baz".to_string(), true, 0),
+            TestStruct::new("qux".to_string(), false, 5),
+        ];
+        let dir = tempfile::tempdir().unwrap();
+        let write_file = |name: &str, vals: &[TestStruct]| {
+            let path = dir.path().join(name);
+            let mut writer = CsvWriterBuilder::new()
+                .has_headers(true)
+                .from_path(&path)
+                .unwrap();
+            for val in vals {
+                writer.serialize(val.clone()).unwrap();
+            }
+            writer.flush().unwrap();
+            (
+                path.to_str().unwrap().to_string(),
+                std::fs::metadata(&path).unwrap().len(),
+            )
+        };
+        let (path1, len1) = write_file("1.csv", &test_data[0..1]);
+        let (path2, len2) = write_file("2.csv", &test_data[1..3]);
+        write_file("3.csv", &test_data[3..4]);
+
+        let config_str = format!(
+            r#"
+stream: test_input
+transport:
+    name: file_input
+    config:
+        path: {:?}
+        buffer_size_bytes: 5
+format:
+    name: csv
+    config:
+        headers: true
+"#,
+            dir.path().to_str().unwrap()
+        );
+        let open = || {
+            mock_input_pipeline::<TestStruct, TestStruct>(
+                serde_yaml::from_str(&config_str).unwrap(),
+                Relation::empty(),
+            )
+            .unwrap()
+        };
+        let read_all =
+            |endpoint: &dyn InputReader, zset: &MockDeZSet<TestStruct, TestStruct>, n: usize| {
+                endpoint.extend();
+                wait(
+                    || {
+                        endpoint.queue(false);
+                        zset.state().flushed.len() == n
+                    },
+                    DEFAULT_TIMEOUT_MS,
+                )
+                .unwrap();
+                zset.state()
+                    .flushed
+                    .iter()
+                    .map(|upd| upd.unwrap_insert().clone())
+                    .collect::<Vec<_>>()
+            };
+
+        // Without metadata, all the files are read, skipping the header of
+        // each one.
+        let (endpoint, consumer, _parser, zset) = open();
+        assert_eq!(read_all(endpoint.as_ref(), &zset, 4), test_data);
+        wait(|| consumer.state().eoi, DEFAULT_TIMEOUT_MS).unwrap();
+        drop(endpoint);
+
+        // Seeking to the end of the first file skips it.
+        let (endpoint, _consumer, _parser, zset) = open();
+        endpoint.seek(json!({"offsets": {"start": 0, "end": len1}, "path": path1}));
+        assert_eq!(read_all(endpoint.as_ref(), &zset, 3), test_data[1..]);
+        drop(endpoint);
+
+        // Replaying the second file re-ingests its records, and reading
+        // resumes with the third file.
+        let (endpoint, _consumer, _parser, zset) = open();
+        endpoint.replay(
+            json!({"offsets": {"start": 0, "end": len2}, "path": path2}),
+            rmpv::Value::Nil,
+        );
+        assert_eq!(read_all(endpoint.as_ref(), &zset, 3), test_data[1..]);
+        drop(endpoint);
+    }
+
     #[test]
     fn test_csv_file_follow() {
         let test_data = [
@@ -510,4 +1001,10 @@ format:
 #[derive(Serialize, Deserialize)]
 struct Metadata {
     offsets: Range<u64>,
+
+    /// The file that `offsets` refer to, when reading a directory or glob
+    /// pattern.  Files that sort before it have been ingested completely.
+    /// `None` before any file has been read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
 }
@@ -1,12 +1,13 @@
 use crate::format::StreamSplitter;
 use crate::transport::{InputEndpoint, InputQueue, InputReaderCommand};
 use crate::{
-    server::{PipelineError, MAX_REPORTED_PARSE_ERRORS},
+    server::{ErrorResponse, PipelineError, MAX_REPORTED_PARSE_ERRORS},
     transport::InputReader,
     ControllerError, InputConsumer, PipelineState, TransportInputEndpoint,
 };
 use crate::{InputBuffer, ParseError, Parser};
-use actix_web::web::Payload;
+use actix_web::{rt, web::Payload, HttpRequest, HttpResponse};
+use actix_ws::AggregatedMessage;
 use anyhow::{anyhow, Error as AnyError, Result as AnyResult};
 use atomic::Atomic;
 use circular_queue::CircularQueue;
@@ -14,7 +15,8 @@ use dbsp::circuit::tokio::TOKIO;
 use feldera_adapterlib::transport::Resume;
 use feldera_types::config::FtModel;
 use feldera_types::program_schema::Relation;
-use feldera_types::transport::http::HttpInputConfig;
+use feldera_types::query::MAX_WS_FRAME_SIZE;
+use feldera_types::transport::http::{HttpInputConfig, IngressWebSocketResponse};
 use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 use serde_bytes::ByteBuf;
@@ -25,7 +27,7 @@ use std::{
 };
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 use tokio::{sync::watch, time::timeout};
-use tracing::{debug, info_span};
+use tracing::{debug, info_span, Instrument};
 use xxhash_rust::xxh3::Xxh3Default;
 
 #[derive(Clone, Debug, Deserialize)]
@@ -283,6 +285,113 @@ impl HttpInputEndpoint {
             Err(PipelineError::parse_errors(num_errors, errors.asc_iter()))
         }
     }
+
+    /// Push a single self-contained message to the pipeline.
+    ///
+    /// Unlike a request body, which is a stream that may split records across
+    /// chunks, the message must consist of complete records.  Waits for the
+    /// pipeline to run unless `force` is set.
+    pub(crate) async fn push_message(
+        &self,
+        message: &[u8],
+        force: bool,
+    ) -> Result<(), PipelineError> {
+        let mut status_watch = self.inner.status_notifier.subscribe();
+        loop {
+            match self.state() {
+                PipelineState::Paused if !force => {
+                    let _ = status_watch.changed().await;
+                }
+                PipelineState::Terminated => return Err(PipelineError::Terminating),
+                _ => break,
+            }
+        }
+
+        let mut errors = CircularQueue::with_capacity(MAX_REPORTED_PARSE_ERRORS);
+        let num_errors = self.push(Some(message), &mut errors) + self.push(None, &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(PipelineError::parse_errors(num_errors, errors.asc_iter()))
+        }
+    }
+
+    /// Upgrade `req` to a WebSocket connection and push each message received
+    /// over it to the pipeline.
+    ///
+    /// Each text or binary message must consist of complete records.  The
+    /// endpoint responds to each message with an [`IngressWebSocketResponse`]
+    /// that carries a completion token obtained from `completion_token` once
+    /// the message has been ingested.
+    pub(crate) fn websocket_request(
+        &self,
+        req: &HttpRequest,
+        payload: Payload,
+        force: bool,
+        completion_token: Box<dyn Fn() -> Result<String, PipelineError>>,
+    ) -> Result<HttpResponse, PipelineError> {
+        let (response, mut session, stream) =
+            actix_ws::handle(req, payload).map_err(|e| PipelineError::InvalidParam {
+                error: format!("unable to initialize WebSocket connection: {e}"),
+            })?;
+        let mut stream = stream
+            .max_frame_size(MAX_WS_FRAME_SIZE)
+            .aggregate_continuations()
+            .max_continuation_size(4 * MAX_WS_FRAME_SIZE);
+
+        let endpoint = self.clone();
+        rt::spawn(
+            async move {
+                debug!(
+                    "HTTP input endpoint '{}': WebSocket connection opened",
+                    endpoint.name()
+                );
+                let mut sequence_number = 0;
+                while let Some(message) = stream.next().await {
+                    let data = match message {
+                        Ok(AggregatedMessage::Text(text)) => text.into_bytes(),
+                        Ok(AggregatedMessage::Binary(bytes)) => bytes,
+                        Ok(AggregatedMessage::Ping(bytes)) => {
+                            if session.pong(&bytes).await.is_err() {
+                                break;
+                            }
+                            continue;
+                        }
+                        Ok(AggregatedMessage::Pong(_)) => continue,
+                        Ok(AggregatedMessage::Close(_)) | Err(_) => break,
+                    };
+
+                    let result = endpoint.push_message(&data, force).await;
+                    let terminating = matches!(result, Err(PipelineError::Terminating));
+                    let response = match result.and_then(|()| completion_token()) {
+                        Ok(token) => IngressWebSocketResponse::ack(sequence_number, token),
+                        Err(e) => IngressWebSocketResponse::error(
+                            sequence_number,
+                            serde_json::to_value(ErrorResponse::from_error(&e)).unwrap_or_default(),
+                        ),
+                    };
+                    sequence_number += 1;
+
+                    if session
+                        .text(serde_json::to_string(&response).unwrap())
+                        .await
+                        .is_err()
+                        || terminating
+                    {
+                        break;
+                    }
+                }
+                debug!(
+                    "HTTP input endpoint '{}': WebSocket connection closed",
+                    endpoint.name()
+                );
+                let _ = session.close(None).await;
+            }
+            .instrument(info_span!("http_input")),
+        );
+
+        Ok(response)
+    }
 }
 
 impl InputEndpoint for HttpInputEndpoint {
@@ -1,18 +1,32 @@
-use crate::{AsyncErrorCallback, OutputEndpoint, TransportConfig};
-use actix_web::{http::header::ContentType, web::Bytes, HttpResponse};
+use crate::{
+    server::PipelineError, transport::Step, AsyncErrorCallback, OutputEndpoint, TransportConfig,
+};
+use actix_web::{
+    http::header::CONTENT_TYPE,
+    rt,
+    web::{Bytes, Payload},
+    HttpRequest, HttpResponse,
+};
+use actix_ws::Message;
 use anyhow::{anyhow, bail, Result as AnyResult};
+use arrow::ipc::{reader::StreamReader, writer::StreamWriter};
 use async_stream::stream;
+use base64::{prelude::BASE64_STANDARD, Engine};
+use bytestring::ByteString;
 use crossbeam::sync::ShardedLock;
+use feldera_types::query::MAX_WS_FRAME_SIZE;
+use futures_util::StreamExt;
 use serde::{ser::SerializeStruct, Serializer};
 use serde_json::value::RawValue;
 use std::{
     sync::{
         atomic::{AtomicU64, Ordering},
-        Arc,
+        Arc, Mutex,
     },
     time::Duration,
 };
 use tokio::{
+    select,
     sync::{mpsc, oneshot},
     time::timeout,
 };
@@ -21,11 +35,18 @@ use tracing::{debug, error, info_span};
 // TODO: make this configurable via endpoint config.
 const MAX_BUFFERS: usize = 100;
 
+/// Content type of Arrow IPC streams.
+const ARROW_STREAM_CONTENT_TYPE: &str = "application/vnd.apache.arrow.stream";
+
 enum Format {
     Binary,
     Text,
     #[allow(dead_code)]
     Json,
+
+    /// Arrow IPC chunks sent as a single raw IPC stream, rather than as JSON
+    /// objects.  Only used for streaming HTTP responses.
+    ArrowIpc,
 }
 
 /// HTTP output transport.
@@ -47,6 +68,10 @@ impl HttpOutputTransport {
     pub(crate) fn default_max_buffered_records() -> u64 {
         100_000
     }
+
+    pub(crate) fn default_heartbeat_ms() -> u64 {
+        3_000
+    }
 }
 
 #[derive(Clone)]
@@ -66,6 +91,25 @@ impl Buffer {
 
 type SendRequest = (Buffer, Option<oneshot::Sender<()>>);
 
+/// Type of a message sent over a WebSocket connection
+/// (see `EgressWebSocketMessage`).
+#[derive(Clone, Copy)]
+enum MessageType {
+    Data,
+    StepEnd,
+    Heartbeat,
+}
+
+impl MessageType {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Data => "data",
+            Self::StepEnd => "step_end",
+            Self::Heartbeat => "heartbeat",
+        }
+    }
+}
+
 struct HttpOutputEndpointInner {
     name: String,
     format: Format,
@@ -78,38 +122,115 @@ struct HttpOutputEndpointInner {
     // connector sends one chunk at a time, eventually forcing the pipeline
     // to wait for the slow client to receive the data.
     backpressure: bool,
+
+    // Send output over a WebSocket connection rather than as a streaming HTTP
+    // response.  WebSocket messages are tagged with their type and step, and
+    // the end of each step is signaled by a separate message.
+    websocket: bool,
+
+    // Tag chunks sent as a streaming HTTP response with their step.  Chunks
+    // sent over a WebSocket connection are always tagged.
+    with_steps: bool,
     total_buffers: AtomicU64,
+    step: AtomicU64,
     sender: ShardedLock<Option<mpsc::Sender<SendRequest>>>,
+
+    // Receiving end of the channel created by `HttpOutputEndpoint::connect`,
+    // until the request that streams its contents to the client takes it.
+    receiver: Mutex<Option<mpsc::Receiver<SendRequest>>>,
+
+    // With `Format::ArrowIpc`, writes the record batches of all chunks to a
+    // single IPC stream.  `None` until the schema has been sent.
+    arrow_writer: Mutex<Option<StreamWriter<Vec<u8>>>>,
     // async_error_callback: RwLock<Option<AsyncErrorCallback>>,
 }
 
 impl HttpOutputEndpointInner {
-    pub(crate) fn new(name: &str, format: Format, backpressure: bool) -> Self {
+    pub(crate) fn new(
+        name: &str,
+        format: Format,
+        backpressure: bool,
+        websocket: bool,
+        with_steps: bool,
+    ) -> Self {
         Self {
             name: name.to_string(),
             format,
             backpressure,
+            websocket,
+            with_steps,
             total_buffers: AtomicU64::new(0),
+            step: AtomicU64::new(0),
             sender: ShardedLock::new(None),
+            receiver: Mutex::new(None),
+            arrow_writer: Mutex::new(None),
             // async_error_callback: RwLock::new(None),
         }
     }
 
     fn push_buffer(&self, buffer: Option<&[u8]>, blocking: bool) -> AnyResult<()> {
+        if let Format::ArrowIpc = self.format {
+            // An IPC stream has no room for empty chunks, so we don't send
+            // heartbeats.
+            return match buffer {
+                Some(buffer) => self.push_arrow_ipc(buffer, blocking),
+                None => Ok(()),
+            };
+        }
+        let message_type = if buffer.is_some() {
+            MessageType::Data
+        } else {
+            MessageType::Heartbeat
+        };
+        self.push_message(message_type, buffer, blocking)
+    }
+
+    fn push_message(
+        &self,
+        message_type: MessageType,
+        buffer: Option<&[u8]>,
+        blocking: bool,
+    ) -> AnyResult<()> {
         let seq_number = self.total_buffers.fetch_add(1, Ordering::AcqRel);
 
         let json_buf = Vec::with_capacity(buffer.map(|b| b.len()).unwrap_or(0) + 1024);
         let mut serializer = serde_json::Serializer::new(json_buf);
+        let with_step = match message_type {
+            MessageType::Data => self.websocket || self.with_steps,
+            MessageType::StepEnd => true,
+            MessageType::Heartbeat => false,
+        };
+        let mut num_fields = if buffer.is_some() { 2 } else { 1 };
+        if self.websocket {
+            num_fields += 1;
+        }
+        if with_step {
+            num_fields += 1;
+        }
         let mut struct_serializer = serializer
-            .serialize_struct("Chunk", if buffer.is_some() { 2 } else { 1 })
+            .serialize_struct("Chunk", num_fields)
             .map_err(|e| anyhow!("error serializing 'Chunk' struct: '{e}'"))?;
+        if self.websocket {
+            struct_serializer
+                .serialize_field("type", message_type.name())
+                .map_err(|e| anyhow!("error serializing 'type' field: '{e}'"))?;
+        }
+        if with_step {
+            struct_serializer
+                .serialize_field("step", &self.step.load(Ordering::Acquire))
+                .map_err(|e| anyhow!("error serializing 'step' field: '{e}'"))?;
+        }
         struct_serializer
             .serialize_field("sequence_number", &seq_number)
             .map_err(|e| anyhow!("error serializing 'sequence_number' field: '{e}'"))?;
 
         if let Some(buffer) = buffer {
             match self.format {
-                Format::Binary => unimplemented!(),
+                Format::Binary | Format::ArrowIpc => {
+                    struct_serializer
+                        .serialize_field("bin_data", &BASE64_STANDARD.encode(buffer))
+                        .map_err(|e| anyhow!("error serializing 'bin_data' field: {e}"))?;
+                }
                 Format::Text => {
                     let data_str = std::str::from_utf8(buffer).map_err(|e| {
                         anyhow!("received an invalid UTF8 string from encoder: {e}")
@@ -138,9 +259,47 @@ impl HttpOutputEndpointInner {
             .map_err(|e| anyhow!("error serializing chunk: '{e}'"))?;
 
         let mut json_buf = serializer.into_inner();
-        json_buf.push(b'\r');
-        json_buf.push(b'\n');
+        if !self.websocket {
+            json_buf.push(b'\r');
+            json_buf.push(b'\n');
+        }
 
+        self.send(Buffer::new(seq_number, Bytes::from(json_buf)), blocking);
+        Ok(())
+    }
+
+    /// Sends the record batches in `buffer`, which holds a complete Arrow IPC
+    /// stream, as part of the IPC stream sent to the client.
+    ///
+    /// The first chunk sent to the client starts with the schema, and later
+    /// chunks only contain record batches, so that the client can read the
+    /// response body with a standard IPC stream reader.
+    fn push_arrow_ipc(&self, buffer: &[u8], blocking: bool) -> AnyResult<()> {
+        let seq_number = self.total_buffers.fetch_add(1, Ordering::AcqRel);
+        let reader = StreamReader::try_new(buffer, None)
+            .map_err(|e| anyhow!("received an invalid Arrow IPC stream from encoder: {e}"))?;
+
+        let mut arrow_writer = self.arrow_writer.lock().unwrap();
+        let with_schema = arrow_writer.is_none();
+        if with_schema {
+            *arrow_writer = Some(StreamWriter::try_new(Vec::new(), &reader.schema())?);
+        }
+        let writer = arrow_writer.as_mut().unwrap();
+        for batch in reader {
+            writer.write(&batch?)?;
+        }
+        let data = std::mem::take(writer.get_mut());
+
+        if !self.send(Buffer::new(seq_number, Bytes::from(data)), blocking) && with_schema {
+            // Send the schema again with the next chunk.
+            *arrow_writer = None;
+        }
+        Ok(())
+    }
+
+    /// Queues `buffer` for the client.  Returns `false` if there is no client
+    /// or the queue is full.
+    fn send(&self, buffer: Buffer, blocking: bool) -> bool {
         // In blocking mode, create a one-shot acknowledgement channel for the sender thread
         // to notify us when it's done sending the chunk.
         let (ack_sender, ack_receiver) = if blocking {
@@ -150,15 +309,20 @@ impl HttpOutputEndpointInner {
             (None, None)
         };
         // A failure simply means that there are no receivers.
-        if let Some(Ok(_)) = self.sender.read().unwrap().as_ref().map(|sender| {
-            sender.try_send((Buffer::new(seq_number, Bytes::from(json_buf)), ack_sender))
-        }) {
+        if let Some(Ok(_)) = self
+            .sender
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|sender| sender.try_send((buffer, ack_sender)))
+        {
             if let Some(ack_receiver) = ack_receiver {
                 let _ = ack_receiver.blocking_recv();
             }
+            true
+        } else {
+            false
         }
-
-        Ok(())
     }
 }
 
@@ -189,14 +353,27 @@ pub(crate) struct HttpOutputEndpoint {
 }
 
 impl HttpOutputEndpoint {
-    pub(crate) fn new(name: &str, format: &str, backpressure: bool) -> Self {
+    pub(crate) fn new(
+        name: &str,
+        format: &str,
+        backpressure: bool,
+        websocket: bool,
+        with_steps: bool,
+    ) -> Self {
         let format = match format {
             "csv" => Format::Text,
             "json" => Format::Json,
+            "arrow_ipc" if !websocket => Format::ArrowIpc,
             _ => Format::Binary,
         };
         Self {
-            inner: Arc::new(HttpOutputEndpointInner::new(name, format, backpressure)),
+            inner: Arc::new(HttpOutputEndpointInner::new(
+                name,
+                format,
+                backpressure,
+                websocket,
+                with_steps,
+            )),
         }
     }
 
@@ -204,11 +381,26 @@ impl HttpOutputEndpoint {
         self.inner.name.as_str()
     }
 
-    fn connect(&self) -> mpsc::Receiver<SendRequest> {
+    /// Start queuing output for the client.
+    ///
+    /// Output pushed to the endpoint before it is connected is discarded.
+    /// [`Self::request`] and [`Self::websocket_request`] connect the endpoint
+    /// if it is not already connected; call this method earlier to make sure
+    /// that the client receives all output, e.g., the initial snapshot of a
+    /// view.  Up to `MAX_BUFFERS` chunks are queued until the client starts
+    /// receiving them.
+    pub(crate) fn connect(&self) {
         let (sender, receiver) = mpsc::channel(MAX_BUFFERS);
+        *self.inner.receiver.lock().unwrap() = Some(receiver);
         *self.inner.sender.write().unwrap() = Some(sender);
+    }
 
-        receiver
+    fn take_receiver(&self) -> mpsc::Receiver<SendRequest> {
+        if let Some(receiver) = self.inner.receiver.lock().unwrap().take() {
+            return receiver;
+        }
+        self.connect();
+        self.inner.receiver.lock().unwrap().take().unwrap()
     }
 
     /// Create an HTTP response object with a streaming body that
@@ -219,14 +411,19 @@ impl HttpOutputEndpoint {
     /// object can be returned to the actix framework, which will
     /// run its streaming body and invoke `finalizer` upon completion.
     pub(crate) fn request(&self, finalizer: Box<dyn FnMut()>) -> HttpResponse {
-        let mut receiver = self.connect();
+        let mut receiver = self.take_receiver();
         let name = self.name().to_string();
         let guard = RequestGuard::new(finalizer);
 
         let inner = self.inner.clone();
 
+        let content_type = match self.inner.format {
+            Format::ArrowIpc => ARROW_STREAM_CONTENT_TYPE,
+            _ => "application/json",
+        };
+
         HttpResponse::Ok()
-            .insert_header(ContentType::json())
+            .insert_header((CONTENT_TYPE, content_type))
             .streaming(stream! {
                 let _guard = guard;
                 loop {
@@ -259,6 +456,82 @@ impl HttpOutputEndpoint {
                 }
             })
     }
+
+    /// Upgrade `req` to a WebSocket connection that will continue sending
+    /// output updates until the circuit terminates or the client
+    /// disconnects.
+    ///
+    /// The endpoint must have been created with `websocket` set to `true`.
+    /// Messages received from the client are ignored.  When no output is
+    /// produced for `heartbeat_interval`, the endpoint sends a heartbeat
+    /// message.  `finalizer` is invoked when the connection closes.
+    pub(crate) fn websocket_request(
+        &self,
+        req: &HttpRequest,
+        payload: Payload,
+        heartbeat_interval: Duration,
+        finalizer: Box<dyn FnMut()>,
+    ) -> Result<HttpResponse, PipelineError> {
+        debug_assert!(self.inner.websocket);
+
+        // Disconnect the endpoint if the connection cannot be established.
+        let guard = RequestGuard::new(finalizer);
+        let (response, mut session, stream) =
+            actix_ws::handle(req, payload).map_err(|e| PipelineError::InvalidParam {
+                error: format!("unable to initialize WebSocket connection: {e}"),
+            })?;
+        let mut stream = stream.max_frame_size(MAX_WS_FRAME_SIZE);
+
+        let mut receiver = self.take_receiver();
+        let name = self.name().to_string();
+        let inner = self.inner.clone();
+
+        rt::spawn(async move {
+            let _guard = guard;
+            loop {
+                select! {
+                    message = stream.next() => match message {
+                        Some(Ok(Message::Ping(bytes))) => {
+                            if session.pong(&bytes).await.is_err() {
+                                break;
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                        Some(Ok(_)) => (),
+                    },
+                    request = timeout(heartbeat_interval, receiver.recv()) => match request {
+                        Err(_) => {
+                            let _ = inner.push_buffer(None, false);
+                        }
+                        Ok(None) => break,
+                        Ok(Some((buffer, ack_sender))) => {
+                            debug!(
+                                "HTTP output endpoint '{}': sending message #{} ({} bytes)",
+                                name,
+                                buffer.sequence_number,
+                                buffer.data.len(),
+                            );
+                            // Messages are serialized as JSON, so this only
+                            // fails if the encoder produced invalid UTF-8.
+                            let Ok(text) = ByteString::try_from(buffer.data) else {
+                                error!("HTTP output endpoint '{name}': invalid UTF-8 message");
+                                break;
+                            };
+                            if session.text(text).await.is_err() {
+                                break;
+                            }
+                            if let Some(ack_sender) = ack_sender {
+                                let _ = ack_sender.send(());
+                            }
+                        }
+                    },
+                }
+            }
+            let _ = session.close(None).await;
+        });
+
+        Ok(response)
+    }
 }
 
 impl OutputEndpoint for HttpOutputEndpoint {
@@ -272,6 +545,11 @@ impl OutputEndpoint for HttpOutputEndpoint {
         usize::MAX
     }
 
+    fn batch_start(&mut self, step: Step) -> AnyResult<()> {
+        self.inner.step.store(step, Ordering::Release);
+        Ok(())
+    }
+
     fn push_buffer(&mut self, buffer: &[u8]) -> AnyResult<()> {
         let _guard = info_span!("http_output").entered();
         self.inner
@@ -292,6 +570,11 @@ however the HTTP transport does not support this representation."
     }
 
     fn batch_end(&mut self) -> AnyResult<()> {
+        if self.inner.websocket {
+            let _guard = info_span!("http_output").entered();
+            self.inner
+                .push_message(MessageType::StepEnd, None, self.inner.backpressure)?;
+        }
         Ok(())
     }
 
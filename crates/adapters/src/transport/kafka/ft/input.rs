@@ -13,14 +13,17 @@ use crate::{InputBuffer, Parser};
 use anyhow::{anyhow, bail, Error as AnyError, Result as AnyResult};
 use crossbeam::queue::ArrayQueue;
 use crossbeam::sync::{Parker, Unparker};
+use feldera_adapterlib::format::{ConnectorMetadata, MetadataValue};
 use feldera_adapterlib::transport::{InputEndpoint, InputReaderCommand, Resume};
 use feldera_types::config::FtModel;
-use feldera_types::program_schema::Relation;
-use feldera_types::transport::kafka::{KafkaInputConfig, KafkaStartFromConfig};
+use feldera_types::program_schema::{Relation, SqlType};
+use feldera_types::transport::kafka::{
+    KafkaInputConfig, KafkaMetadataColumns, KafkaStartFromConfig,
+};
 use rdkafka::client::OAuthToken;
 use rdkafka::config::RDKafkaLogLevel;
 use rdkafka::consumer::base_consumer::PartitionQueue;
-use rdkafka::message::BorrowedMessage;
+use rdkafka::message::{BorrowedMessage, Headers};
 use rdkafka::{
     config::FromClientConfigAndContext,
     consumer::{BaseConsumer, Consumer, ConsumerContext},
@@ -177,6 +180,7 @@ impl KafkaFtInputReaderInner {
         config: Arc<KafkaInputConfig>,
         consumer: &Box<dyn InputConsumer>,
         mut parser: Box<dyn Parser>,
+        binary_key: bool,
         n_partitions: usize,
         command_receiver: UnboundedReceiver<InputReaderCommand>,
     ) -> AnyResult<()> {
@@ -283,7 +287,13 @@ impl KafkaFtInputReaderInner {
                 move || unparker.unpark()
             });
 
-            let receiver = Arc::new(PartitionReceiver::new(*partition, queue, next_offset));
+            let receiver = Arc::new(PartitionReceiver::new(
+                *partition,
+                queue,
+                next_offset,
+                config.metadata_columns.clone(),
+                binary_key,
+            ));
             receivers.insert(partition, receiver.clone());
             thread.receivers.push(receiver);
         }
@@ -541,6 +551,7 @@ impl KafkaFtInputReader {
         config: &Arc<KafkaInputConfig>,
         consumer: Box<dyn InputConsumer>,
         parser: Box<dyn Parser>,
+        binary_key: bool,
     ) -> AnyResult<Self> {
         let _guard = span(&config.topic);
 
@@ -585,6 +596,7 @@ impl KafkaFtInputReader {
                     config.clone(),
                     &consumer,
                     parser,
+                    binary_key,
                     config
                         .partitions
                         .as_deref()
@@ -616,12 +628,34 @@ impl TransportInputEndpoint for KafkaFtInputEndpoint {
         &self,
         consumer: Box<dyn InputConsumer>,
         parser: Box<dyn Parser>,
-        _schema: Relation,
+        schema: Relation,
     ) -> AnyResult<Box<dyn InputReader>> {
+        for column in self.config.metadata_columns.columns() {
+            if schema.field(column).is_none() {
+                bail!(
+                    "metadata column '{column}' does not exist in table '{}'",
+                    schema.name
+                );
+            }
+        }
+
+        // Binary key columns receive the key as is; other columns receive it
+        // as a string.
+        let binary_key = self
+            .config
+            .metadata_columns
+            .key
+            .as_ref()
+            .and_then(|column| schema.field(column))
+            .is_some_and(|field| {
+                matches!(field.columntype.typ, SqlType::Binary | SqlType::Varbinary)
+            });
+
         Ok(Box::new(KafkaFtInputReader::new(
             &self.config,
             consumer,
             parser,
+            binary_key,
         )?))
     }
 }
@@ -711,6 +745,12 @@ struct PartitionReceiver {
     /// Parsed messages and errors.
     messages: Mutex<BTreeSet<Msg>>,
 
+    /// Columns that receive message metadata.
+    metadata_columns: KafkaMetadataColumns,
+
+    /// Whether the key column is `BINARY` or `VARBINARY`.
+    binary_key: bool,
+
     eof: AtomicBool,
     fatal_error: AtomicBool,
 }
@@ -720,6 +760,8 @@ impl PartitionReceiver {
         partition: i32,
         queue: PartitionQueue<KafkaFtInputContext>,
         next_offset: i64,
+        metadata_columns: KafkaMetadataColumns,
+        binary_key: bool,
     ) -> Self {
         Self {
             partition,
@@ -728,6 +770,8 @@ impl PartitionReceiver {
             next_offset: AtomicI64::new(next_offset),
             initial_next_offset: next_offset,
             messages: Mutex::new(BTreeSet::new()),
+            metadata_columns,
+            binary_key,
             eof: AtomicBool::new(false),
             fatal_error: AtomicBool::new(false),
         }
@@ -785,7 +829,11 @@ impl PartitionReceiver {
                     self.next_offset.store(offset + 1, Ordering::Relaxed);
 
                     let payload = message.payload().unwrap_or(&[]);
-                    let (buffer, errors) = parser.parse(payload);
+                    let (buffer, errors) = if self.metadata_columns.is_empty() {
+                        parser.parse(payload)
+                    } else {
+                        parser.parse_with_metadata(payload, &self.message_metadata(&message))
+                    };
                     let len = buffer.len();
                     self.messages.lock().unwrap().insert(Msg { offset, buffer });
                     consumer.buffered(len, payload.len());
@@ -800,6 +848,57 @@ impl PartitionReceiver {
         }
     }
 
+    /// Returns the metadata of `message` to store in the configured metadata
+    /// columns.
+    fn message_metadata(&self, message: &BorrowedMessage<'_>) -> ConnectorMetadata {
+        let columns = &self.metadata_columns;
+        let mut metadata = Vec::new();
+        if let Some(column) = &columns.key {
+            let key = match message.key() {
+                Some(key) if self.binary_key => MetadataValue::Bytes(key.to_vec()),
+                Some(key) => MetadataValue::String(String::from_utf8_lossy(key).into_owned()),
+                None => MetadataValue::Null,
+            };
+            metadata.push((column.clone(), key));
+        }
+        if let Some(column) = &columns.headers {
+            let headers = message
+                .headers()
+                .map(|headers| {
+                    headers
+                        .iter()
+                        .map(|header| {
+                            (
+                                header.key.to_string(),
+                                header
+                                    .value
+                                    .map(|value| String::from_utf8_lossy(value).into_owned()),
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            metadata.push((column.clone(), MetadataValue::Map(headers)));
+        }
+        if let Some(column) = &columns.timestamp {
+            let timestamp = match message.timestamp().to_millis() {
+                Some(millis) => MetadataValue::Timestamp(millis),
+                None => MetadataValue::Null,
+            };
+            metadata.push((column.clone(), timestamp));
+        }
+        if let Some(column) = &columns.partition {
+            metadata.push((
+                column.clone(),
+                MetadataValue::Integer(message.partition() as i64),
+            ));
+        }
+        if let Some(column) = &columns.offset {
+            metadata.push((column.clone(), MetadataValue::Integer(message.offset())));
+        }
+        metadata
+    }
+
     fn run(
         &self,
         base_consumer: &BaseConsumer<KafkaFtInputContext>,
@@ -9,10 +9,10 @@ use crate::{
 use anyhow::{anyhow, bail, Context, Error as AnyError, Result as AnyResult};
 use feldera_types::transport::kafka::KafkaOutputConfig;
 use rdkafka::client::OAuthToken;
-use rdkafka::message::OwnedHeaders;
+use rdkafka::message::{Header, Headers, OwnedHeaders};
 use rdkafka::{
     config::FromClientConfigAndContext,
-    consumer::BaseConsumer,
+    consumer::{BaseConsumer, ConsumerContext},
     error::KafkaError,
     producer::{BaseRecord, DeliveryResult, Producer, ProducerContext, ThreadedProducer},
     types::RDKafkaErrorCode,
@@ -52,9 +52,14 @@ enum State {
     BatchClosed(Step),
 }
 
+/// Header that carries the [OutputPosition] of messages whose key is computed
+/// from the output record, e.g., with `key_fields`.
+const POSITION_HEADER: &str = "feldera_position";
+
 /// A position in the output partition.
 ///
-/// This is stored as the Kafka message key.
+/// This is stored as the Kafka message key, or, for messages that have their
+/// own key, in the [POSITION_HEADER] header.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 struct OutputPosition {
     /// The step number.
@@ -76,19 +81,34 @@ impl OutputPosition {
     where
         M: Message,
     {
-        Ok(serde_json::from_slice(msg.key().unwrap_or(&[]))?)
+        let header = msg.headers().and_then(|headers| {
+            headers
+                .iter()
+                .find(|header| header.key == POSITION_HEADER)
+                .and_then(|header| header.value)
+        });
+        Ok(serde_json::from_slice(header.or(msg.key()).unwrap_or(&[]))?)
     }
 }
 
 pub struct KafkaOutputEndpoint {
     kafka_producer: ThreadedProducer<DataProducerContext>,
     topic: String,
+
+    /// Topic where we record steps that didn't write to `topic`.
+    progress_topic: Option<String>,
+
     headers: OwnedHeaders,
     next_partition: usize,
     n_partitions: usize,
     max_message_size: usize,
     next_step: Step,
     state: State,
+
+    /// Whether the current step wrote any messages to `topic` and to other
+    /// topics, respectively.
+    wrote_to_topic: bool,
+    wrote_to_other_topics: bool,
 }
 
 pub fn span(topic: &str) -> EnteredSpan {
@@ -99,6 +119,9 @@ impl KafkaOutputEndpoint {
     pub fn new(config: KafkaOutputConfig) -> AnyResult<Self> {
         let _guard = span(&config.topic);
         let ft = config.clone().fault_tolerance.unwrap_or_default();
+        if config.topic_field.is_some() && ft.progress_topic.is_none() {
+            bail!("'fault_tolerance.progress_topic' must be set when 'topic_field' is set, so that the connector can track the progress of steps that don't write to '{}'", config.topic);
+        }
         let mut common = CommonConfig::new(
             &config.kafka_options,
             &ft.consumer_options,
@@ -145,35 +168,125 @@ impl KafkaOutputEndpoint {
 
         // Read the number of partitions and the next step number.  We do this
         // after initializing transactions to avoid a race.
-        let (n_partitions, next_step) =
-            Self::read_next_step(&common.seekable_consumer_config, &config)?;
+        let (n_partitions, next_step) = Self::read_next_step(
+            &common.seekable_consumer_config,
+            &config,
+            ft.progress_topic.as_deref(),
+        )?;
 
         Ok(Self {
             kafka_producer,
             topic: config.topic.clone(),
+            progress_topic: ft.progress_topic,
             headers: build_headers(&config.headers),
             n_partitions,
             next_partition: 0,
             max_message_size,
             next_step,
             state: State::New,
+            wrote_to_topic: false,
+            wrote_to_other_topics: false,
         })
     }
 
-    /// Reads the tail of `topic` using `seekable_consumer_config`. Returns the
-    /// number of partitions in `topic` and the step number for the next step to
-    /// be written.
+    /// Writes a message to `topic`, or to the configured topic if `topic` is
+    /// `None`, as part of the current step.
+    ///
+    /// Messages written to the configured topic without a key of their own
+    /// are distributed across its partitions in round-robin fashion.
+    fn send(
+        &mut self,
+        topic: Option<&str>,
+        key: Option<&[u8]>,
+        val: Option<&[u8]>,
+        headers: &[(&str, Option<&[u8]>)],
+    ) -> AnyResult<()> {
+        let State::BatchOpen(OutputPosition { step, substep }) = self.state else {
+            unreachable!(
+                "state should be BatchOpen (not {:?}) when writing a message",
+                self.state
+            )
+        };
+        self.state = State::BatchOpen(OutputPosition {
+            step,
+            substep: substep + 1,
+        });
+
+        if step >= self.next_step {
+            let topic = topic.unwrap_or(&self.topic);
+            let position = serde_json::to_string(&OutputPosition { step, substep }).unwrap();
+
+            let mut all_headers = self.headers.clone();
+            for (key, value) in headers {
+                all_headers = all_headers.insert(Header { key, value: *value });
+            }
+            let mut record = match key {
+                Some(key) => {
+                    all_headers = all_headers.insert(Header {
+                        key: POSITION_HEADER,
+                        value: Some(position.as_bytes()),
+                    });
+                    <BaseRecord<[u8], [u8], ()>>::to(topic).key(key)
+                }
+                None => {
+                    let mut record =
+                        <BaseRecord<[u8], [u8], ()>>::to(topic).key(position.as_bytes());
+                    if topic == self.topic {
+                        record = record.partition(self.next_partition as i32);
+                        self.next_partition += 1;
+                        if self.next_partition >= self.n_partitions {
+                            self.next_partition = 0;
+                        }
+                    }
+                    record
+                }
+            };
+            if let Some(val) = val {
+                record = record.payload(val);
+            }
+            record = record.headers(all_headers);
+            kafka_send(&self.kafka_producer, topic, record)?;
+
+            if topic == self.topic {
+                self.wrote_to_topic = true;
+            } else {
+                self.wrote_to_other_topics = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the tail of `topic` and `progress_topic`, if any, using
+    /// `seekable_consumer_config`. Returns the number of partitions in `topic`
+    /// and the step number for the next step to be written.
     fn read_next_step(
         seekable_consumer_config: &ClientConfig,
         kafka_config: &KafkaOutputConfig,
+        progress_topic: Option<&str>,
     ) -> AnyResult<(usize, Step)> {
-        let topic = &kafka_config.topic;
         let context = DataConsumerContext::new(|error| warn!("{error}"), kafka_config)?;
         let consumer = BaseConsumer::from_config_and_context(seekable_consumer_config, context)?;
-        let n_partitions = count_partitions_in_topic(&consumer, topic)?;
+        let (n_partitions, mut next_step) =
+            Self::read_topic_next_step(&consumer, &kafka_config.topic)?;
+        if let Some(progress_topic) = progress_topic {
+            next_step = max(
+                next_step,
+                Self::read_topic_next_step(&consumer, progress_topic)?.1,
+            );
+        }
+        Ok((n_partitions, next_step))
+    }
+
+    /// Reads the tail of `topic`.  Returns the number of partitions in `topic`
+    /// and the step number that follows the last step written to it.
+    fn read_topic_next_step<C>(consumer: &BaseConsumer<C>, topic: &str) -> AnyResult<(usize, Step)>
+    where
+        C: ConsumerContext,
+    {
+        let n_partitions = count_partitions_in_topic(consumer, topic)?;
         let mut next_step = 0;
         for partition in 0..n_partitions {
-            let ctp = Ctp::new(&consumer, topic, partition as i32);
+            let ctp = Ctp::new(consumer, topic, partition as i32);
             let watermarks = ctp.fetch_watermarks(None)?;
             if !watermarks.is_empty() {
                 if let Some(msg) = ctp.read_last_message(&watermarks)? {
@@ -207,6 +320,14 @@ impl KafkaOutputEndpoint {
     }
 }
 
+#[cfg(test)]
+impl KafkaOutputEndpoint {
+    /// Returns the step number for the next step to be written.
+    pub(super) fn next_step(&self) -> Step {
+        self.next_step
+    }
+}
+
 impl OutputEndpoint for KafkaOutputEndpoint {
     fn connect(&mut self, async_error_callback: AsyncErrorCallback) -> AnyResult<()> {
         debug_assert_eq!(self.state, State::New);
@@ -228,42 +349,28 @@ impl OutputEndpoint for KafkaOutputEndpoint {
 
     fn push_buffer(&mut self, buffer: &[u8]) -> AnyResult<()> {
         let _guard = span(&self.topic);
-        let State::BatchOpen(OutputPosition { step, substep }) = self.state else {
-            unreachable!(
-                "state should be BatchOpen (not {:?}) in `push_buffer()`",
-                self.state
-            )
-        };
-        self.state = State::BatchOpen(OutputPosition {
-            step,
-            substep: substep + 1,
-        });
-
-        if step >= self.next_step {
-            let key = OutputPosition { step, substep };
-            let key = serde_json::to_string(&key).unwrap();
-            let record = BaseRecord::to(&self.topic)
-                .key(&key)
-                .partition(self.next_partition as i32)
-                .payload(buffer)
-                .headers(self.headers.clone());
-            kafka_send(&self.kafka_producer, &self.topic, record)?;
-
-            self.next_partition += 1;
-            if self.next_partition >= self.n_partitions {
-                self.next_partition = 0;
-            }
-        }
-        Ok(())
+        self.send(None, None, Some(buffer), &[])
     }
 
     fn push_key(
         &mut self,
-        _key: Option<&[u8]>,
-        _val: Option<&[u8]>,
-        _headers: &[(&str, Option<&[u8]>)],
+        key: Option<&[u8]>,
+        val: Option<&[u8]>,
+        headers: &[(&str, Option<&[u8]>)],
+    ) -> AnyResult<()> {
+        let _guard = span(&self.topic);
+        self.send(None, key, val, headers)
+    }
+
+    fn push_key_to_topic(
+        &mut self,
+        topic: &str,
+        key: Option<&[u8]>,
+        val: Option<&[u8]>,
+        headers: &[(&str, Option<&[u8]>)],
     ) -> AnyResult<()> {
-        todo!()
+        let _guard = span(&self.topic);
+        self.send(Some(topic), key, val, headers)
     }
 
     fn batch_end(&mut self) -> AnyResult<()> {
@@ -274,6 +381,15 @@ impl OutputEndpoint for KafkaOutputEndpoint {
                 self.state
             )
         };
+
+        // We find the next step on restart by reading the configured topic, so
+        // a step that only wrote to other topics must be recorded in the
+        // progress topic.
+        if position.step >= self.next_step && self.wrote_to_other_topics && !self.wrote_to_topic {
+            if let Some(progress_topic) = self.progress_topic.clone() {
+                self.send(Some(&progress_topic), None, None, &[])?;
+            }
+        }
         self.state = State::BatchClosed(position.step);
 
         if position.step >= self.next_step {
@@ -313,6 +429,8 @@ impl OutputEndpoint for KafkaOutputEndpoint {
             );
         }
         self.state = State::BatchOpen(OutputPosition { step, substep: 0 });
+        self.wrote_to_topic = false;
+        self.wrote_to_other_topics = false;
         Ok(())
     }
 
@@ -5,6 +5,7 @@ use crate::test::{
     wait_for_output_unordered,
 };
 use crate::transport::kafka::ft::input::Metadata;
+use crate::transport::kafka::KafkaFtOutputEndpoint;
 use crate::transport::{input_transport_config_to_endpoint, output_transport_config_to_endpoint};
 use crate::{
     test::{
@@ -13,7 +14,7 @@ use crate::{
     },
     Controller, InputConsumer, ParseError, PipelineConfig,
 };
-use crate::{InputBuffer, InputReader, Parser, TransportInputEndpoint};
+use crate::{InputBuffer, InputReader, OutputEndpoint, Parser, TransportInputEndpoint};
 use anyhow::Error as AnyError;
 use crossbeam::sync::{Parker, Unparker};
 use csv::ReaderBuilder as CsvReaderBuilder;
@@ -25,7 +26,7 @@ use feldera_types::config::{
 use feldera_types::program_schema::Relation;
 use feldera_types::transport::kafka::{
     default_group_join_timeout_secs, default_redpanda_server, KafkaInputConfig, KafkaLogLevel,
-    KafkaStartFromConfig,
+    KafkaOutputConfig, KafkaStartFromConfig,
 };
 use parquet::data_type::AsBytes;
 use proptest::prelude::*;
@@ -572,10 +573,14 @@ config:
 "#
     );
 
-    let mut endpoint =
-        output_transport_config_to_endpoint(serde_yaml::from_str(&config_str).unwrap(), "", true)
-            .unwrap()
-            .unwrap();
+    let mut endpoint = output_transport_config_to_endpoint(
+        serde_yaml::from_str(&config_str).unwrap(),
+        None,
+        "",
+        true,
+    )
+    .unwrap()
+    .unwrap();
     assert!(endpoint.is_fault_tolerant());
     endpoint
         .connect(Box::new(|fatal, error| info!("({fatal:?}, {error:?})")))
@@ -589,6 +594,61 @@ config:
     }
 }
 
+/// A step that only writes to other topics, or only writes messages with keys
+/// of their own, is not output again after a restart.
+#[test]
+fn ft_output_routing() {
+    init_test_logger();
+    let topic = "ft_output_routing_topic";
+    let other_topic = "ft_output_routing_other_topic";
+    let progress_topic = "ft_output_routing_progress_topic";
+    let _kafka_resources =
+        KafkaResources::create_topics(&[(topic, 1), (other_topic, 1), (progress_topic, 1)]);
+
+    let config: KafkaOutputConfig = serde_yaml::from_str(&format!(
+        r#"
+topic: {topic}
+topic_field: t
+fault_tolerance:
+    progress_topic: {progress_topic}
+"#
+    ))
+    .unwrap();
+    let open = || {
+        let mut endpoint = KafkaFtOutputEndpoint::new(config.clone()).unwrap();
+        endpoint
+            .connect(Box::new(|fatal, error| info!("({fatal:?}, {error:?})")))
+            .unwrap();
+        endpoint
+    };
+
+    let mut endpoint = open();
+    assert_eq!(endpoint.next_step(), 0);
+    endpoint.batch_start(0).unwrap();
+    endpoint
+        .push_key_to_topic(
+            other_topic,
+            Some(b"key0".as_slice()),
+            Some(b"value0".as_slice()),
+            &[("header", Some(b"x".as_slice()))],
+        )
+        .unwrap();
+    endpoint.batch_end().unwrap();
+    drop(endpoint);
+
+    // Step 0 is recorded in the progress topic.
+    let mut endpoint = open();
+    assert_eq!(endpoint.next_step(), 1);
+    endpoint.batch_start(1).unwrap();
+    endpoint
+        .push_key(Some(b"key1".as_slice()), Some(b"value1".as_slice()), &[])
+        .unwrap();
+    endpoint.batch_end().unwrap();
+    drop(endpoint);
+
+    assert_eq!(open().next_step(), 2);
+}
+
 fn _test() {
     let config_str = r#"
 name: kafka_output
@@ -596,10 +656,14 @@ config:
     topic: my_topic
 "#;
 
-    let mut endpoint =
-        output_transport_config_to_endpoint(serde_yaml::from_str(config_str).unwrap(), "", true)
-            .unwrap()
-            .unwrap();
+    let mut endpoint = output_transport_config_to_endpoint(
+        serde_yaml::from_str(config_str).unwrap(),
+        None,
+        "",
+        true,
+    )
+    .unwrap()
+    .unwrap();
     assert!(endpoint.is_fault_tolerant());
     endpoint
         .connect(Box::new(|fatal, error| info!("({fatal:?}, {error:?})")))
@@ -1140,6 +1204,7 @@ fn test_offset(
                 start_from: start_from.clone(),
                 region: None,
                 partitions: None,
+                metadata_columns: Default::default(),
             }),
             format: Some(FormatConfig {
                 name: Cow::from("csv"),
@@ -1149,9 +1214,12 @@ fn test_offset(
             output_buffer_config: OutputBufferConfig::default(),
             max_batch_size: default_max_batch_size(),
             max_queued_records: default_max_queued_records(),
+            max_records_per_second: None,
+            max_bytes_per_second: None,
             paused: false,
             labels: Vec::new(),
             start_after: None,
+            dead_letter: None,
         },
     };
 
@@ -1546,6 +1614,7 @@ fn test_input_partition(
                 start_from: start_from.clone(),
                 region: None,
                 partitions: Some(partitions.clone()),
+                metadata_columns: Default::default(),
             }),
             format: Some(FormatConfig {
                 name: Cow::from("csv"),
@@ -1555,9 +1624,12 @@ fn test_input_partition(
             output_buffer_config: OutputBufferConfig::default(),
             max_batch_size: default_max_batch_size(),
             max_queued_records: default_max_queued_records(),
+            max_records_per_second: None,
+            max_bytes_per_second: None,
             paused: false,
             labels: Vec::new(),
             start_after: None,
+            dead_letter: None,
         },
     };
 
@@ -2020,6 +2092,205 @@ outputs:
     running.store(false, Ordering::Release);
 }
 
+/// Message keys and offsets are stored in the columns named in
+/// `metadata_columns`.
+#[test]
+fn test_kafka_input_metadata_columns() {
+    init_test_logger();
+    let topic = "test_kafka_input_metadata_columns";
+    let _kafka_resources = KafkaResources::create_topics(&[(topic, 1)]);
+
+    let config_str = format!(
+        r#"
+stream: test_input
+transport:
+    name: kafka_input
+    config:
+        auto.offset.reset: "earliest"
+        topic: {topic}
+        metadata_columns:
+            key: s
+            offset: i
+format:
+    name: json
+    config:
+        update_format: raw
+"#
+    );
+    let producer = TestProducer::new();
+    producer.send_keyed_string("key0", r#"{"id": 0, "b": true}"#, topic);
+    producer.send_keyed_string("key1", r#"{"id": 1, "b": false, "s": "payload"}"#, topic);
+
+    let (endpoint, _consumer, _parser, zset) = mock_input_pipeline::<TestStruct, TestStruct>(
+        serde_yaml::from_str(&config_str).unwrap(),
+        TestStruct::relation_schema(),
+    )
+    .unwrap();
+    endpoint.extend();
+
+    // Metadata overrides columns of the same name in the payload.
+    let expected = vec![vec![
+        TestStruct {
+            id: 0,
+            b: true,
+            i: Some(0),
+            s: "key0".to_string(),
+        },
+        TestStruct {
+            id: 1,
+            b: false,
+            i: Some(1),
+            s: "key1".to_string(),
+        },
+    ]];
+    wait_for_output_ordered(&zset, &expected, || endpoint.queue(false));
+    endpoint.disconnect();
+}
+
+/// Messages are rejected by formats that don't support `metadata_columns`.
+#[test]
+fn test_kafka_input_metadata_columns_format() {
+    init_test_logger();
+    let topic = "test_kafka_input_metadata_columns_format";
+    let _kafka_resources = KafkaResources::create_topics(&[(topic, 1)]);
+
+    let config_str = format!(
+        r#"
+stream: test_input
+transport:
+    name: kafka_input
+    config:
+        auto.offset.reset: "earliest"
+        topic: {topic}
+        metadata_columns:
+            key: s
+format:
+    name: csv
+"#
+    );
+    let producer = TestProducer::new();
+    producer.send_keyed_string("key0", "0,true,,\n", topic);
+
+    let (endpoint, _consumer, parser, zset) = mock_input_pipeline::<TestStruct, TestStruct>(
+        serde_yaml::from_str(&config_str).unwrap(),
+        TestStruct::relation_schema(),
+    )
+    .unwrap();
+    parser.on_error(Some(Box::new(|_, _| {})));
+    endpoint.extend();
+
+    wait(
+        || {
+            endpoint.queue(false);
+            parser
+                .state()
+                .parser_result
+                .as_ref()
+                .is_some_and(|errors| !errors.is_empty())
+        },
+        20_000,
+    )
+    .unwrap();
+    let errors = parser.state().parser_result.clone().unwrap();
+    assert!(
+        errors[0]
+            .to_string()
+            .contains("does not support connector metadata columns"),
+        "unexpected error: {}",
+        errors[0]
+    );
+    assert!(zset.state().flushed.is_empty());
+    endpoint.disconnect();
+}
+
+/// Records that fail to parse are written to the dead-letter topic, with the
+/// error and the record's event number in message headers.
+#[test]
+fn test_kafka_dead_letter() {
+    init_test_logger();
+    let topic = "test_kafka_dead_letter_input";
+    let dead_letter_topic = "test_kafka_dead_letter_output";
+    let _kafka_resources = KafkaResources::create_topics(&[(topic, 1), (dead_letter_topic, 1)]);
+
+    let config_str = format!(
+        r#"
+name: test
+workers: 1
+inputs:
+    test_input1:
+        stream: test_input1
+        transport:
+            name: kafka_input
+            config:
+                auto.offset.reset: "earliest"
+                topic: {topic}
+        format:
+            name: json
+            config:
+                update_format: raw
+        dead_letter:
+            kafka:
+                bootstrap.servers: {}
+                topic: {dead_letter_topic}
+"#,
+        default_redpanda_server()
+    );
+
+    // Collect the payload and headers of dead letters.
+    let dead_letters = Arc::new(Mutex::new(Vec::new()));
+    let dead_letters_clone = dead_letters.clone();
+    let cb = Box::new(move |message: &BorrowedMessage| {
+        let headers = message
+            .headers()
+            .map(|headers| {
+                headers
+                    .iter()
+                    .map(|header| {
+                        (
+                            header.key.to_string(),
+                            String::from_utf8(header.value.unwrap().to_vec()).unwrap(),
+                        )
+                    })
+                    .collect::<BTreeMap<_, _>>()
+            })
+            .unwrap_or_default();
+        let payload = String::from_utf8(message.payload().unwrap().to_vec()).unwrap();
+        dead_letters_clone.lock().unwrap().push((payload, headers));
+    });
+    let _buffer_consumer = BufferConsumer::new(dead_letter_topic, "json", "", Some(cb));
+
+    let producer = TestProducer::new();
+    producer.send_string(r#"{"id": 1, "b": true, "s": "foo"}"#, topic);
+    producer.send_string(r#"{"id": "two", "b": true, "s": "foo"}"#, topic);
+
+    let config: PipelineConfig = serde_yaml::from_str(&config_str).unwrap();
+    let controller = Controller::with_config(
+        |workers| {
+            Ok(test_circuit::<TestStruct>(
+                workers,
+                &TestStruct::schema(),
+                &[None],
+            ))
+        },
+        &config,
+        Box::new(|e| info!("error: {e}")),
+    )
+    .unwrap();
+    controller.start();
+
+    wait(|| !dead_letters.lock().unwrap().is_empty(), 20_000).unwrap();
+    controller.stop().unwrap();
+
+    let dead_letters = dead_letters.lock().unwrap();
+    assert_eq!(dead_letters.len(), 1);
+    let (payload, headers) = &dead_letters[0];
+    assert_eq!(payload, r#"{"id": "two", "b": true, "s": "foo"}"#);
+    assert_eq!(headers["connector"], "test_input1");
+    assert!(headers["error"].contains("failed to deserialize JSON record"));
+    assert!(headers.contains_key("event_number"));
+    assert!(!headers.contains_key("step"));
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(2))]
 
@@ -168,6 +168,36 @@ impl KafkaOutputEndpoint {
             max_message_size,
         })
     }
+
+    /// Writes a key/value message with the configured headers, followed by
+    /// `headers`, to `topic`.
+    fn send(
+        &self,
+        topic: &str,
+        key: Option<&[u8]>,
+        val: Option<&[u8]>,
+        headers: &[(&str, Option<&[u8]>)],
+    ) -> AnyResult<()> {
+        let _guard = span(&self.config.topic);
+        let mut record = <BaseRecord<[u8], [u8], ()>>::to(topic);
+
+        if let Some(key) = key {
+            record = record.key(key);
+        }
+
+        if let Some(val) = val {
+            record = record.payload(val);
+        }
+
+        let mut all_headers = self.headers.clone();
+
+        for (key, value) in headers {
+            all_headers = all_headers.insert(Header { key, value: *value });
+        }
+
+        record = record.headers(all_headers);
+        kafka_send(&self.kafka_producer, topic, record)
+    }
 }
 
 impl OutputEndpoint for KafkaOutputEndpoint {
@@ -221,25 +251,17 @@ impl OutputEndpoint for KafkaOutputEndpoint {
         val: Option<&[u8]>,
         headers: &[(&str, Option<&[u8]>)],
     ) -> AnyResult<()> {
-        let _guard = span(&self.config.topic);
-        let mut record = <BaseRecord<[u8], [u8], ()>>::to(&self.config.topic);
-
-        if let Some(key) = key {
-            record = record.key(key);
-        }
-
-        if let Some(val) = val {
-            record = record.payload(val);
-        }
-
-        let mut all_headers = self.headers.clone();
-
-        for (key, value) in headers {
-            all_headers = all_headers.insert(Header { key, value: *value });
-        }
+        self.send(&self.config.topic, key, val, headers)
+    }
 
-        record = record.headers(all_headers);
-        kafka_send(&self.kafka_producer, &self.config.topic, record)
+    fn push_key_to_topic(
+        &mut self,
+        topic: &str,
+        key: Option<&[u8]>,
+        val: Option<&[u8]>,
+        headers: &[(&str, Option<&[u8]>)],
+    ) -> AnyResult<()> {
+        self.send(topic, key, val, headers)
     }
 
     fn is_fault_tolerant(&self) -> bool {
@@ -25,15 +25,17 @@ use anyhow::Result as AnyResult;
 use clock::ClockEndpoint;
 use http::HttpInputEndpoint;
 #[cfg(feature = "with-pubsub")]
-use pubsub::PubSubInputEndpoint;
+use pubsub::{PubSubInputEndpoint, PubSubOutputEndpoint};
 
 pub mod adhoc;
 mod file;
 pub mod http;
+mod object_store;
 
 pub mod url;
 
 pub mod clock;
+mod compression;
 mod s3;
 
 #[cfg(feature = "with-kafka")]
@@ -48,10 +50,22 @@ mod pubsub;
 #[cfg(feature = "with-redis")]
 mod redis;
 
-use feldera_types::config::TransportConfig;
+#[cfg(feature = "with-mqtt")]
+mod mqtt;
+
+#[cfg(feature = "with-nats")]
+mod nats;
+
+use feldera_types::config::{FormatConfig, TransportConfig};
 
 #[cfg(feature = "with-redis")]
-use redis::output::RedisOutputEndpoint;
+use redis::{input::RedisInputEndpoint, output::RedisOutputEndpoint};
+
+#[cfg(feature = "with-mqtt")]
+use mqtt::{MqttInputEndpoint, MqttOutputEndpoint};
+
+#[cfg(feature = "with-nats")]
+use nats::{NatsInputEndpoint, NatsOutputEndpoint};
 
 #[cfg(test)]
 pub use crate::transport::file::set_barrier;
@@ -60,6 +74,7 @@ use crate::transport::file::{FileInputEndpoint, FileOutputEndpoint};
 use crate::transport::kafka::{KafkaFtInputEndpoint, KafkaFtOutputEndpoint, KafkaOutputEndpoint};
 #[cfg(feature = "with-nexmark")]
 use crate::transport::nexmark::NexmarkEndpoint;
+use crate::transport::object_store::ObjectStoreOutputEndpoint;
 use crate::transport::s3::S3InputEndpoint;
 use crate::transport::url::UrlInputEndpoint;
 use feldera_datagen::GeneratorEndpoint;
@@ -76,7 +91,7 @@ pub fn input_transport_config_to_endpoint(
     endpoint_name: &str,
 ) -> AnyResult<Option<Box<dyn TransportInputEndpoint>>> {
     let endpoint: Box<dyn TransportInputEndpoint> = match config {
-        TransportConfig::FileInput(config) => Box::new(FileInputEndpoint::new(config)),
+        TransportConfig::FileInput(config) => Box::new(FileInputEndpoint::new(config)?),
         #[cfg(feature = "with-kafka")]
         TransportConfig::KafkaInput(config) => Box::new(KafkaFtInputEndpoint::new(config)?),
         #[cfg(not(feature = "with-kafka"))]
@@ -95,15 +110,33 @@ pub fn input_transport_config_to_endpoint(
         TransportConfig::HttpInput(config) => Box::new(HttpInputEndpoint::new(config)),
         TransportConfig::AdHocInput(config) => Box::new(AdHocInputEndpoint::new(config)),
         TransportConfig::ClockInput(config) => Box::new(ClockEndpoint::new(config)?),
+        #[cfg(feature = "with-redis")]
+        TransportConfig::RedisInput(config) => Box::new(RedisInputEndpoint::new(config)?),
+        #[cfg(not(feature = "with-redis"))]
+        TransportConfig::RedisInput(_) => return Ok(None),
+        #[cfg(feature = "with-mqtt")]
+        TransportConfig::MqttInput(config) => Box::new(MqttInputEndpoint::new(config)?),
+        #[cfg(not(feature = "with-mqtt"))]
+        TransportConfig::MqttInput(_) => return Ok(None),
+        #[cfg(feature = "with-nats")]
+        TransportConfig::NatsInput(config) => Box::new(NatsInputEndpoint::new(config)?),
+        #[cfg(not(feature = "with-nats"))]
+        TransportConfig::NatsInput(_) => return Ok(None),
         TransportConfig::FileOutput(_)
         | TransportConfig::KafkaOutput(_)
+        | TransportConfig::PubSubOutput(_)
+        | TransportConfig::ObjectStoreOutput(_)
         | TransportConfig::DeltaTableInput(_)
         | TransportConfig::DeltaTableOutput(_)
         | TransportConfig::PostgresInput(_)
+        | TransportConfig::PostgresCdcInput(_)
         | TransportConfig::PostgresOutput(_)
         | TransportConfig::HttpOutput
         | TransportConfig::RedisOutput(_)
-        | TransportConfig::IcebergInput(_) => return Ok(None),
+        | TransportConfig::MqttOutput(_)
+        | TransportConfig::NatsOutput(_)
+        | TransportConfig::IcebergInput(_)
+        | TransportConfig::IcebergOutput(_) => return Ok(None),
     };
     Ok(Some(endpoint))
 }
@@ -114,16 +147,23 @@ pub fn input_transport_config_to_endpoint(
 /// fault-tolerant output endpoint (but it will still return a non-FT endpoint
 /// if that's all it can do).
 ///
+/// `format` is the format configuration of the connector, if any.  Endpoints
+/// that write files use it to choose file names.
+///
 /// Returns an error if there is a invalid configuration for the endpoint.
 /// Returns `None` if the transport configuration variant is incompatible with an output endpoint.
 #[allow(unused_variables)]
 pub fn output_transport_config_to_endpoint(
     config: TransportConfig,
+    format: Option<&FormatConfig>,
     endpoint_name: &str,
     fault_tolerant: bool,
 ) -> AnyResult<Option<Box<dyn OutputEndpoint>>> {
     match config {
         TransportConfig::FileOutput(config) => Ok(Some(Box::new(FileOutputEndpoint::new(config)?))),
+        TransportConfig::ObjectStoreOutput(config) => Ok(Some(Box::new(
+            ObjectStoreOutputEndpoint::new(config, format, endpoint_name, fault_tolerant)?,
+        ))),
         #[cfg(feature = "with-kafka")]
         TransportConfig::KafkaOutput(config) => match fault_tolerant {
             false => Ok(Some(Box::new(KafkaOutputEndpoint::new(
@@ -132,10 +172,22 @@ pub fn output_transport_config_to_endpoint(
             )?))),
             true => Ok(Some(Box::new(KafkaFtOutputEndpoint::new(config)?))),
         },
+        #[cfg(feature = "with-pubsub")]
+        TransportConfig::PubSubOutput(config) => {
+            Ok(Some(Box::new(PubSubOutputEndpoint::new(config)?)))
+        }
         #[cfg(feature = "with-redis")]
         TransportConfig::RedisOutput(config) => {
             Ok(Some(Box::new(RedisOutputEndpoint::new(config)?)))
         }
+        #[cfg(feature = "with-mqtt")]
+        TransportConfig::MqttOutput(config) => Ok(Some(Box::new(MqttOutputEndpoint::new(config)?))),
+        #[cfg(feature = "with-nats")]
+        TransportConfig::NatsOutput(config) => Ok(Some(Box::new(NatsOutputEndpoint::new(
+            config,
+            endpoint_name,
+            fault_tolerant,
+        )?))),
         _ => Ok(None),
     }
 }
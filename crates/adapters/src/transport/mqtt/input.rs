@@ -0,0 +1,289 @@
+use super::{mqtt_options, qos, ConnectionOptions, RECONNECT_DELAY, REQUEST_CHANNEL_CAPACITY};
+use crate::{
+    transport::{InputQueue, InputReaderCommand, NonFtInputReaderCommand},
+    InputConsumer, InputEndpoint, InputReader, Parser, PipelineState, TransportInputEndpoint,
+};
+use anyhow::{anyhow, bail, Error as AnyError, Result as AnyResult};
+use dbsp::circuit::tokio::TOKIO;
+use feldera_types::{config::FtModel, program_schema::Relation, transport::mqtt::MqttInputConfig};
+use rumqttc::{
+    valid_filter, AsyncClient, Event, EventLoop, Packet, SubscribeFilter, SubscribeReasonCode,
+};
+use std::{sync::Arc, thread};
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+    time::sleep,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info_span, Instrument};
+
+pub struct MqttInputEndpoint {
+    config: Arc<MqttInputConfig>,
+}
+
+impl MqttInputEndpoint {
+    pub fn new(config: MqttInputConfig) -> Result<Self, AnyError> {
+        // Validation.
+        if config.topics.is_empty() {
+            bail!("'topics' must contain at least one topic filter");
+        }
+        for topic in &config.topics {
+            if !valid_filter(topic) {
+                bail!("'{topic}' is not a valid MQTT topic filter");
+            }
+        }
+        qos(config.qos)?;
+        mqtt_options(&ConnectionOptions::from(&config))?;
+
+        Ok(Self {
+            config: Arc::new(config),
+        })
+    }
+}
+
+impl InputEndpoint for MqttInputEndpoint {
+    fn fault_tolerance(&self) -> Option<FtModel> {
+        None
+    }
+}
+
+impl TransportInputEndpoint for MqttInputEndpoint {
+    fn open(
+        &self,
+        consumer: Box<dyn InputConsumer>,
+        parser: Box<dyn Parser>,
+        _schema: Relation,
+    ) -> AnyResult<Box<dyn InputReader>> {
+        Ok(Box::new(MqttReader::new(
+            self.config.clone(),
+            consumer,
+            parser,
+        )?))
+    }
+}
+
+struct MqttReader {
+    state_sender: UnboundedSender<NonFtInputReaderCommand>,
+}
+
+impl MqttReader {
+    fn new(
+        config: Arc<MqttInputConfig>,
+        consumer: Box<dyn InputConsumer>,
+        parser: Box<dyn Parser>,
+    ) -> AnyResult<Self> {
+        let span = info_span!("mqtt_input", topics = config.topics.join(","));
+        let (state_sender, state_receiver) = unbounded_channel();
+        let subscriber =
+            TOKIO.block_on(Subscriber::connect(config.clone()).instrument(span.clone()))?;
+        thread::spawn({
+            move || {
+                let consumer_clone = consumer.clone();
+                TOKIO.block_on(async {
+                    Self::worker_task(subscriber, consumer_clone, parser, state_receiver)
+                        .instrument(span)
+                        .await
+                        .unwrap_or_else(|e| consumer.error(true, e));
+                })
+            }
+        });
+
+        Ok(Self { state_sender })
+    }
+
+    async fn worker_task(
+        subscriber: Subscriber,
+        consumer: Box<dyn InputConsumer>,
+        parser: Box<dyn Parser>,
+        mut state_receiver: UnboundedReceiver<NonFtInputReaderCommand>,
+    ) -> Result<(), AnyError> {
+        // While the connector is running, the subscriber is owned by the task
+        // that polls the event loop, which returns it when cancelled.
+        let mut subscriber = Some(subscriber);
+        let mut cancel: Option<(CancellationToken, JoinHandle<Subscriber>)> = None;
+        let queue = Arc::new(InputQueue::new(consumer.clone()));
+        while let Some(command) = state_receiver.recv().await {
+            match command {
+                NonFtInputReaderCommand::Queue => queue.queue(),
+                NonFtInputReaderCommand::Transition(state) => match state {
+                    PipelineState::Paused => {
+                        if let Some((token, handle)) = cancel.take() {
+                            token.cancel();
+                            subscriber = Some(handle.await?);
+                        }
+                    }
+                    PipelineState::Running => {
+                        if let Some(mut subscriber) = subscriber.take() {
+                            let token = CancellationToken::new();
+                            let handle = tokio::spawn({
+                                let token = token.clone();
+                                let queue = queue.clone();
+                                let consumer = consumer.clone();
+                                let mut parser = parser.fork();
+                                async move {
+                                    subscriber
+                                        .run(&token, &queue, consumer.as_ref(), parser.as_mut())
+                                        .await;
+                                    subscriber
+                                }
+                            });
+                            cancel = Some((token, handle));
+                        }
+                    }
+                    PipelineState::Terminated => break,
+                },
+            }
+        }
+        if let Some((token, handle)) = cancel.take() {
+            token.cancel();
+            subscriber = handle.await.ok();
+        }
+        if let Some(subscriber) = subscriber {
+            let _ = subscriber.client.try_disconnect();
+        }
+        Ok(())
+    }
+}
+
+impl InputReader for MqttReader {
+    fn request(&self, command: InputReaderCommand) {
+        let _ = self.state_sender.send(command.as_nonft().unwrap());
+    }
+
+    fn is_closed(&self) -> bool {
+        self.state_sender.is_closed()
+    }
+}
+
+/// MQTT client and its event loop.
+///
+/// The event loop only makes progress, i.e., exchanges packets with the broker,
+/// while it is being polled.  The reader stops polling it while the pipeline is
+/// paused, so the broker may close the connection once the keep-alive interval
+/// expires; the event loop reconnects the next time it is polled.
+struct Subscriber {
+    config: Arc<MqttInputConfig>,
+    client: AsyncClient,
+    eventloop: EventLoop,
+}
+
+impl Subscriber {
+    /// Connects to the broker and subscribes to the configured topics.
+    async fn connect(config: Arc<MqttInputConfig>) -> Result<Self, AnyError> {
+        let options = mqtt_options(&ConnectionOptions::from(config.as_ref()))?;
+        let (client, mut eventloop) = AsyncClient::new(options, REQUEST_CHANNEL_CAPACITY);
+
+        debug!("MQTT input endpoint: connecting to broker {}", &config.host);
+
+        let session_present = loop {
+            match eventloop.poll().await {
+                Ok(Event::Incoming(Packet::ConnAck(connack))) => break connack.session_present,
+                Ok(_) => (),
+                Err(e) => bail!("error connecting to MQTT broker '{}': {e}", config.host),
+            }
+        };
+        let mut subscriber = Self {
+            config,
+            client,
+            eventloop,
+        };
+
+        // The broker kept the subscriptions of a previous session.
+        if session_present {
+            return Ok(subscriber);
+        }
+
+        // Make sure that the broker accepts the subscriptions.
+        subscriber.subscribe()?;
+        loop {
+            match subscriber.eventloop.poll().await {
+                Ok(Event::Incoming(Packet::SubAck(suback))) => {
+                    subscriber.check_suback(&suback.return_codes)?;
+                    return Ok(subscriber);
+                }
+                Ok(_) => (),
+                Err(e) => bail!(
+                    "error subscribing to topics on MQTT broker '{}': {e}",
+                    subscriber.config.host
+                ),
+            }
+        }
+    }
+
+    /// Subscribes to the configured topics.
+    fn subscribe(&mut self) -> Result<(), AnyError> {
+        let qos = qos(self.config.qos)?;
+        self.client
+            .try_subscribe_many(
+                self.config
+                    .topics
+                    .iter()
+                    .map(|topic| SubscribeFilter::new(topic.clone(), qos)),
+            )
+            .map_err(|e| anyhow!("error subscribing to MQTT topics: {e}"))
+    }
+
+    fn check_suback(&self, return_codes: &[SubscribeReasonCode]) -> Result<(), AnyError> {
+        for (topic, code) in self.config.topics.iter().zip(return_codes) {
+            if *code == SubscribeReasonCode::Failure {
+                bail!("MQTT broker rejected subscription to topic filter '{topic}'");
+            }
+        }
+        Ok(())
+    }
+
+    /// Polls the event loop, feeding received messages to `parser`, until
+    /// `token` is cancelled.
+    async fn run(
+        &mut self,
+        token: &CancellationToken,
+        queue: &InputQueue,
+        consumer: &dyn InputConsumer,
+        parser: &mut dyn Parser,
+    ) {
+        loop {
+            let event = tokio::select! {
+                _ = token.cancelled() => break,
+                event = self.eventloop.poll() => event,
+            };
+            match event {
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    if self.config.skip_retained && publish.retain {
+                        continue;
+                    }
+                    let data = &publish.payload;
+                    queue.push(parser.parse(data), data.len());
+                }
+                // Subscriptions are lost when the broker does not resume the
+                // previous session after a reconnect.
+                Ok(Event::Incoming(Packet::ConnAck(connack))) => {
+                    if !connack.session_present {
+                        if let Err(e) = self.subscribe() {
+                            consumer.error(false, e);
+                        }
+                    }
+                }
+                Ok(Event::Incoming(Packet::SubAck(suback))) => {
+                    if let Err(e) = self.check_suback(&suback.return_codes) {
+                        consumer.error(true, e);
+                    }
+                }
+                Ok(_) => (),
+                Err(e) => {
+                    consumer.error(
+                        false,
+                        anyhow!(
+                            "error receiving messages from MQTT broker '{}' (will reconnect): {e}",
+                            self.config.host
+                        ),
+                    );
+                    tokio::select! {
+                        _ = token.cancelled() => break,
+                        _ = sleep(RECONNECT_DELAY) => (),
+                    }
+                }
+            }
+        }
+    }
+}
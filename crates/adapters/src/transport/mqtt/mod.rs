@@ -0,0 +1,160 @@
+//! Transport adapter for MQTT brokers.
+
+mod input;
+mod output;
+
+#[cfg(test)]
+mod test;
+
+use anyhow::{anyhow, bail, Error as AnyError};
+use feldera_types::transport::mqtt::{MqttInputConfig, MqttOutputConfig};
+use rumqttc::{MqttOptions, QoS, Transport};
+use std::time::Duration;
+use uuid::Uuid;
+
+pub use input::MqttInputEndpoint;
+pub use output::MqttOutputEndpoint;
+
+/// Default port for unencrypted MQTT connections.
+const DEFAULT_PORT: u16 = 1883;
+
+/// Default port for MQTT connections over TLS.
+const DEFAULT_TLS_PORT: u16 = 8883;
+
+/// Capacity of the request channel between the client and its event loop.
+const REQUEST_CHANNEL_CAPACITY: usize = 100;
+
+/// Delay before polling the event loop again after a connection error.  The
+/// event loop reconnects to the broker on the next poll.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Options for connecting to the broker, shared by the input and output
+/// connector configurations.
+struct ConnectionOptions<'a> {
+    host: &'a str,
+    port: Option<u16>,
+    client_id: &'a Option<String>,
+    username: &'a Option<String>,
+    password: &'a Option<String>,
+    tls: bool,
+    tls_ca_certificate: &'a Option<String>,
+    tls_client_certificate: &'a Option<String>,
+    tls_client_key: &'a Option<String>,
+    keep_alive_secs: u64,
+    max_packet_size: usize,
+    clean_session: bool,
+}
+
+impl<'a> From<&'a MqttInputConfig> for ConnectionOptions<'a> {
+    fn from(config: &'a MqttInputConfig) -> Self {
+        Self {
+            host: &config.host,
+            port: config.port,
+            client_id: &config.client_id,
+            username: &config.username,
+            password: &config.password,
+            tls: config.tls,
+            tls_ca_certificate: &config.tls_ca_certificate,
+            tls_client_certificate: &config.tls_client_certificate,
+            tls_client_key: &config.tls_client_key,
+            keep_alive_secs: config.keep_alive_secs,
+            max_packet_size: config.max_packet_size,
+            clean_session: config.clean_session,
+        }
+    }
+}
+
+impl<'a> From<&'a MqttOutputConfig> for ConnectionOptions<'a> {
+    fn from(config: &'a MqttOutputConfig) -> Self {
+        Self {
+            host: &config.host,
+            port: config.port,
+            client_id: &config.client_id,
+            username: &config.username,
+            password: &config.password,
+            tls: config.tls,
+            tls_ca_certificate: &config.tls_ca_certificate,
+            tls_client_certificate: &config.tls_client_certificate,
+            tls_client_key: &config.tls_client_key,
+            keep_alive_secs: config.keep_alive_secs,
+            max_packet_size: config.max_packet_size,
+            clean_session: true,
+        }
+    }
+}
+
+/// Validates connection options and converts them into `MqttOptions`.
+fn mqtt_options(config: &ConnectionOptions<'_>) -> Result<MqttOptions, AnyError> {
+    if config.host.is_empty() {
+        bail!("'host' must not be empty");
+    }
+    if config.password.is_some() && config.username.is_none() {
+        bail!("'password' requires 'username' to be set");
+    }
+    if !config.tls
+        && (config.tls_ca_certificate.is_some()
+            || config.tls_client_certificate.is_some()
+            || config.tls_client_key.is_some())
+    {
+        bail!("TLS certificate options require 'tls' to be set to 'true'");
+    }
+    if config.tls_client_certificate.is_some() != config.tls_client_key.is_some() {
+        bail!("'tls_client_certificate' and 'tls_client_key' must be set together");
+    }
+    if config.tls_client_certificate.is_some() && config.tls_ca_certificate.is_none() {
+        bail!("'tls_client_certificate' requires 'tls_ca_certificate' to be set");
+    }
+    if !config.clean_session && config.client_id.is_none() {
+        bail!("'clean_session: false' requires 'client_id' to be set");
+    }
+    if config.max_packet_size == 0 {
+        bail!("'max_packet_size' must be greater than 0");
+    }
+
+    let client_id = match config.client_id {
+        Some(client_id) => client_id.clone(),
+        None => format!("feldera-{}", Uuid::new_v4()),
+    };
+    let port = config.port.unwrap_or(if config.tls {
+        DEFAULT_TLS_PORT
+    } else {
+        DEFAULT_PORT
+    });
+
+    let mut options = MqttOptions::new(client_id, config.host, port);
+    options
+        .set_keep_alive(Duration::from_secs(config.keep_alive_secs))
+        .set_max_packet_size(config.max_packet_size, config.max_packet_size)
+        .set_clean_session(config.clean_session);
+
+    if let Some(username) = config.username {
+        options.set_credentials(username, config.password.clone().unwrap_or_default());
+    }
+
+    if config.tls {
+        let transport = match config.tls_ca_certificate {
+            Some(ca) => {
+                let client_auth = config
+                    .tls_client_certificate
+                    .as_ref()
+                    .zip(config.tls_client_key.as_ref())
+                    .map(|(cert, key)| (cert.as_bytes().to_vec(), key.as_bytes().to_vec()));
+                Transport::tls(ca.as_bytes().to_vec(), client_auth, None)
+            }
+            None => Transport::tls_with_default_config(),
+        };
+        options.set_transport(transport);
+    }
+
+    Ok(options)
+}
+
+/// Converts the QoS level in the connector configuration to `QoS`.
+fn qos(qos: u8) -> Result<QoS, AnyError> {
+    match qos {
+        0 => Ok(QoS::AtMostOnce),
+        1 => Ok(QoS::AtLeastOnce),
+        2 => Ok(QoS::ExactlyOnce),
+        _ => Err(anyhow!("invalid 'qos' value {qos}: must be 0, 1, or 2")),
+    }
+}
@@ -0,0 +1,212 @@
+use super::{mqtt_options, qos, ConnectionOptions, RECONNECT_DELAY, REQUEST_CHANNEL_CAPACITY};
+use crate::transport::{AsyncErrorCallback, OutputEndpoint};
+use anyhow::{anyhow, bail, Error as AnyError, Result as AnyResult};
+use dbsp::circuit::tokio::TOKIO;
+use feldera_types::transport::mqtt::MqttOutputConfig;
+use rumqttc::{valid_topic, AsyncClient, Event, EventLoop, Outgoing, Packet, QoS};
+use tokio::{
+    sync::watch::{channel, Receiver, Sender},
+    task::JoinHandle,
+    time::sleep,
+};
+use tracing::{debug, info_span, Instrument};
+
+/// Maximum size of the fixed header, topic length and packet identifier of a
+/// PUBLISH packet, in addition to the topic itself.
+const PUBLISH_OVERHEAD: usize = 9;
+
+/// Publishes output to an MQTT topic.
+///
+/// Each buffer produced by the encoder is published as a separate message.
+/// The connector waits for the broker to acknowledge all messages published
+/// during a step at the end of the step.
+pub struct MqttOutputEndpoint {
+    config: MqttOutputConfig,
+    qos: QoS,
+    client: Option<AsyncClient>,
+
+    /// Task that polls the event loop.
+    eventloop_task: Option<JoinHandle<()>>,
+
+    /// Number of messages acknowledged by the broker (or, with QoS 0, written
+    /// to the network), updated by `eventloop_task`.
+    completed: Option<Receiver<u64>>,
+
+    /// Number of messages published so far.
+    published: u64,
+}
+
+impl MqttOutputEndpoint {
+    pub fn new(config: MqttOutputConfig) -> AnyResult<Self> {
+        if !valid_topic(&config.topic) || config.topic.is_empty() {
+            bail!(
+                "'{}' is not a valid MQTT topic: topics must be non-empty and must not contain wildcards",
+                config.topic
+            );
+        }
+        let qos = qos(config.qos)?;
+        mqtt_options(&ConnectionOptions::from(&config))?;
+
+        Ok(Self {
+            config,
+            qos,
+            client: None,
+            eventloop_task: None,
+            completed: None,
+            published: 0,
+        })
+    }
+
+    async fn connect_client(
+        config: &MqttOutputConfig,
+    ) -> Result<(AsyncClient, EventLoop), AnyError> {
+        let options = mqtt_options(&ConnectionOptions::from(config))?;
+        let (client, mut eventloop) = AsyncClient::new(options, REQUEST_CHANNEL_CAPACITY);
+
+        debug!(
+            "MQTT output endpoint: connecting to broker {}",
+            &config.host
+        );
+
+        loop {
+            match eventloop.poll().await {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => return Ok((client, eventloop)),
+                Ok(_) => (),
+                Err(e) => bail!("error connecting to MQTT broker '{}': {e}", config.host),
+            }
+        }
+    }
+
+    /// Polls the event loop, counting completed messages in `completed`.
+    ///
+    /// The event loop retransmits unacknowledged messages after reconnecting
+    /// to the broker.
+    async fn eventloop_task(
+        mut eventloop: EventLoop,
+        qos: QoS,
+        host: String,
+        completed: Sender<u64>,
+        async_error_callback: AsyncErrorCallback,
+    ) {
+        loop {
+            match eventloop.poll().await {
+                Ok(event) => {
+                    let done = matches!(
+                        (qos, &event),
+                        (QoS::AtMostOnce, Event::Outgoing(Outgoing::Publish(_)))
+                            | (QoS::AtLeastOnce, Event::Incoming(Packet::PubAck(_)))
+                            | (QoS::ExactlyOnce, Event::Incoming(Packet::PubComp(_)))
+                    );
+                    if done {
+                        completed.send_modify(|n| *n += 1);
+                    }
+                }
+                Err(e) => {
+                    async_error_callback(
+                        false,
+                        anyhow!(
+                            "error sending messages to MQTT broker '{host}' (will reconnect): {e}"
+                        ),
+                    );
+                    sleep(RECONNECT_DELAY).await;
+                }
+            }
+        }
+    }
+}
+
+impl OutputEndpoint for MqttOutputEndpoint {
+    fn connect(&mut self, async_error_callback: AsyncErrorCallback) -> AnyResult<()> {
+        let span = info_span!("mqtt_output", topic = self.config.topic.clone());
+        let (client, eventloop) =
+            TOKIO.block_on(Self::connect_client(&self.config).instrument(span.clone()))?;
+        let (sender, receiver) = channel(0);
+        self.eventloop_task = Some(
+            TOKIO.spawn(
+                Self::eventloop_task(
+                    eventloop,
+                    self.qos,
+                    self.config.host.clone(),
+                    sender,
+                    async_error_callback,
+                )
+                .instrument(span),
+            ),
+        );
+        self.client = Some(client);
+        self.completed = Some(receiver);
+        Ok(())
+    }
+
+    fn max_buffer_size_bytes(&self) -> usize {
+        self.config
+            .max_packet_size
+            .saturating_sub(self.config.topic.len() + PUBLISH_OVERHEAD)
+    }
+
+    fn push_buffer(&mut self, buffer: &[u8]) -> AnyResult<()> {
+        let Some(client) = &self.client else {
+            bail!("MQTT output endpoint is not connected");
+        };
+        TOKIO
+            .block_on(client.publish(
+                &self.config.topic,
+                self.qos,
+                self.config.retain,
+                buffer.to_vec(),
+            ))
+            .map_err(|e| {
+                anyhow!(
+                    "error publishing message to MQTT topic '{}': {e}",
+                    self.config.topic
+                )
+            })?;
+        self.published += 1;
+        Ok(())
+    }
+
+    fn push_key(
+        &mut self,
+        _key: Option<&[u8]>,
+        val: Option<&[u8]>,
+        _headers: &[(&str, Option<&[u8]>)],
+    ) -> AnyResult<()> {
+        // MQTT 3.1.1 messages have neither keys nor headers, and there is no
+        // way to represent a deletion without a value.
+        match val {
+            Some(val) => self.push_buffer(val),
+            None => Ok(()),
+        }
+    }
+
+    fn batch_end(&mut self) -> AnyResult<()> {
+        let Some(completed) = &mut self.completed else {
+            bail!("MQTT output endpoint is not connected");
+        };
+        let published = self.published;
+        TOKIO
+            .block_on(completed.wait_for(|n| *n >= published))
+            .map(|_| ())
+            .map_err(|_| {
+                anyhow!(
+                    "MQTT connection closed before the broker acknowledged all messages published to topic '{}'",
+                    self.config.topic
+                )
+            })
+    }
+
+    fn is_fault_tolerant(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for MqttOutputEndpoint {
+    fn drop(&mut self) {
+        if let Some(client) = self.client.take() {
+            let _ = client.try_disconnect();
+        }
+        if let Some(task) = self.eventloop_task.take() {
+            task.abort();
+        }
+    }
+}
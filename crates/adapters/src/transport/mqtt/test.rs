@@ -0,0 +1,332 @@
+//! MQTT connector tests.
+//!
+//! The tests require an MQTT broker, e.g., Mosquitto, running on `MQTT_HOST`
+//! (`localhost` by default), port 1883, that accepts anonymous connections:
+//!
+//! ```bash
+//! docker run -p 1883:1883 eclipse-mosquitto:1.6
+//! ```
+
+use super::MqttOutputEndpoint;
+use crate::{
+    test::{data::TestStruct, mock_input_pipeline, wait, DEFAULT_TIMEOUT_MS},
+    transport::OutputEndpoint,
+};
+use feldera_types::{config::InputEndpointConfig, transport::mqtt::MqttOutputConfig};
+use rumqttc::{Client, Event, MqttOptions, Packet, Publish, QoS};
+use std::{
+    env,
+    sync::mpsc::{channel, Receiver},
+    thread,
+    time::Duration,
+};
+
+fn mqtt_host() -> String {
+    env::var("MQTT_HOST").unwrap_or("localhost".to_string())
+}
+
+/// Connects a test client to the broker.  Returns the client and a channel
+/// that receives all packets sent to the client by the broker.
+fn test_client(client_id: &str) -> (Client, Receiver<Packet>) {
+    let (client, mut connection) = Client::new(MqttOptions::new(client_id, mqtt_host(), 1883), 10);
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+        for event in connection.iter() {
+            match event {
+                Ok(Event::Incoming(packet)) => {
+                    if sender.send(packet).is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => (),
+                Err(_) => break,
+            }
+        }
+    });
+    (client, receiver)
+}
+
+/// Waits for the next packet that satisfies `predicate`.
+fn recv_packet<T>(receiver: &Receiver<Packet>, predicate: impl Fn(Packet) -> Option<T>) -> T {
+    loop {
+        let packet = receiver
+            .recv_timeout(Duration::from_millis(DEFAULT_TIMEOUT_MS as u64))
+            .expect("timeout waiting for MQTT packet");
+        if let Some(result) = predicate(packet) {
+            return result;
+        }
+    }
+}
+
+fn recv_publish(receiver: &Receiver<Packet>) -> Publish {
+    recv_packet(receiver, |packet| match packet {
+        Packet::Publish(publish) => Some(publish),
+        _ => None,
+    })
+}
+
+/// Publishes `data` as JSON to `topic` and waits for the broker to acknowledge
+/// each message.
+fn publish(client: &Client, receiver: &Receiver<Packet>, topic: &str, data: &[TestStruct]) {
+    for val in data {
+        client
+            .publish(
+                topic,
+                QoS::AtLeastOnce,
+                false,
+                serde_json::to_vec(val).unwrap(),
+            )
+            .unwrap();
+        recv_packet(receiver, |packet| match packet {
+            Packet::PubAck(_) => Some(()),
+            _ => None,
+        });
+    }
+}
+
+fn mqtt_input_config(topics: &[&str], extra: &str) -> InputEndpointConfig {
+    let config_str = format!(
+        r#"
+stream: test_input
+transport:
+    name: mqtt_input
+    config:
+        host: {}
+        topics: {topics:?}
+        {extra}
+format:
+    name: json
+    config:
+        update_format: raw
+        array: false
+"#,
+        mqtt_host()
+    );
+    serde_yaml::from_str(&config_str).unwrap()
+}
+
+fn test_data(ids: std::ops::Range<u32>) -> Vec<TestStruct> {
+    ids.map(|id| TestStruct {
+        id,
+        b: id % 2 == 0,
+        i: if id % 3 == 0 { None } else { Some(id as i64) },
+        s: format!("foo{id}"),
+    })
+    .collect()
+}
+
+#[test]
+fn test_mqtt_input() {
+    let (endpoint, _consumer, _parser, zset) = mock_input_pipeline::<TestStruct, TestStruct>(
+        mqtt_input_config(&["feldera/test_mqtt_input/+/data"], ""),
+        TestStruct::relation_schema(),
+    )
+    .unwrap();
+    endpoint.extend();
+
+    let (client, receiver) = test_client("test_mqtt_input");
+    let data1 = test_data(0..10);
+    let data2 = test_data(10..20);
+    publish(&client, &receiver, "feldera/test_mqtt_input/1/data", &data1);
+    publish(&client, &receiver, "feldera/test_mqtt_input/2/data", &data2);
+
+    // Doesn't match the topic filter.
+    publish(
+        &client,
+        &receiver,
+        "feldera/test_mqtt_input/3/other",
+        &data1,
+    );
+
+    let expected = [data1, data2].concat();
+    wait(
+        || {
+            endpoint.queue(false);
+            zset.state().flushed.len() >= expected.len()
+        },
+        DEFAULT_TIMEOUT_MS,
+    )
+    .unwrap();
+
+    // Messages from different topics can arrive in any order.
+    let mut received = zset
+        .state()
+        .flushed
+        .iter()
+        .map(|upd| upd.unwrap_insert().clone())
+        .collect::<Vec<_>>();
+    received.sort_by_key(|val| val.id);
+    assert_eq!(received, expected);
+
+    endpoint.disconnect();
+}
+
+#[test]
+fn test_mqtt_input_retained() {
+    let topic = "feldera/test_mqtt_input_retained";
+    let (client, receiver) = test_client("test_mqtt_input_retained");
+    let retained = test_data(0..1);
+    client
+        .publish(
+            topic,
+            QoS::AtLeastOnce,
+            true,
+            serde_json::to_vec(&retained[0]).unwrap(),
+        )
+        .unwrap();
+    recv_packet(&receiver, |packet| match packet {
+        Packet::PubAck(_) => Some(()),
+        _ => None,
+    });
+
+    let (endpoint1, _consumer1, _parser1, zset1) = mock_input_pipeline::<TestStruct, TestStruct>(
+        mqtt_input_config(&[topic], ""),
+        TestStruct::relation_schema(),
+    )
+    .unwrap();
+    endpoint1.extend();
+
+    let (endpoint2, _consumer2, _parser2, zset2) = mock_input_pipeline::<TestStruct, TestStruct>(
+        mqtt_input_config(&[topic], "skip_retained: true"),
+        TestStruct::relation_schema(),
+    )
+    .unwrap();
+    endpoint2.extend();
+
+    let data = test_data(1..5);
+    publish(&client, &receiver, topic, &data);
+
+    // The first endpoint receives the retained message followed by the new
+    // messages; the second endpoint only receives the new messages.
+    for (endpoint, zset, expected) in [
+        (
+            &endpoint1,
+            &zset1,
+            [retained.clone(), data.clone()].concat(),
+        ),
+        (&endpoint2, &zset2, data.clone()),
+    ] {
+        wait(
+            || {
+                endpoint.queue(false);
+                zset.state().flushed.len() >= expected.len()
+            },
+            DEFAULT_TIMEOUT_MS,
+        )
+        .unwrap();
+        let received = zset
+            .state()
+            .flushed
+            .iter()
+            .map(|upd| upd.unwrap_insert().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(received, expected);
+        endpoint.disconnect();
+    }
+
+    // Clear the retained message.
+    client
+        .publish(topic, QoS::AtLeastOnce, true, Vec::new())
+        .unwrap();
+    recv_packet(&receiver, |packet| match packet {
+        Packet::PubAck(_) => Some(()),
+        _ => None,
+    });
+}
+
+#[test]
+fn test_mqtt_output() {
+    let topic = "feldera/test_mqtt_output";
+    for qos in 0..=2 {
+        let (client, receiver) = test_client(&format!("test_mqtt_output_{qos}"));
+        client.subscribe(topic, QoS::ExactlyOnce).unwrap();
+        recv_packet(&receiver, |packet| match packet {
+            Packet::SubAck(_) => Some(()),
+            _ => None,
+        });
+
+        let config: MqttOutputConfig = serde_json::from_value(serde_json::json!({
+            "host": mqtt_host(),
+            "topic": topic,
+            "qos": qos,
+        }))
+        .unwrap();
+        let mut endpoint = MqttOutputEndpoint::new(config).unwrap();
+        endpoint.connect(Box::new(|_, _| ())).unwrap();
+
+        endpoint.batch_start(0).unwrap();
+        endpoint.push_buffer(b"first").unwrap();
+        endpoint.push_key(None, Some(b"second"), &[]).unwrap();
+        endpoint.batch_end().unwrap();
+
+        endpoint.batch_start(1).unwrap();
+        endpoint.push_buffer(b"third").unwrap();
+        endpoint.batch_end().unwrap();
+
+        for expected in [b"first".as_slice(), b"second", b"third"] {
+            let publish = recv_publish(&receiver);
+            assert_eq!(publish.topic, topic);
+            assert_eq!(publish.payload.as_ref(), expected);
+        }
+    }
+}
+
+#[test]
+fn test_mqtt_config_errors() {
+    let config = |value: serde_json::Value| -> MqttOutputConfig {
+        let mut config = serde_json::json!({"host": "localhost", "topic": "foo/bar"});
+        config
+            .as_object_mut()
+            .unwrap()
+            .extend(value.as_object().unwrap().clone());
+        serde_json::from_value(config).unwrap()
+    };
+
+    for (value, error) in [
+        (
+            serde_json::json!({"topic": "foo/+"}),
+            "not a valid MQTT topic",
+        ),
+        (serde_json::json!({"qos": 3}), "invalid 'qos' value 3"),
+        (
+            serde_json::json!({"password": "secret"}),
+            "'password' requires 'username'",
+        ),
+        (
+            serde_json::json!({"tls_ca_certificate": "cert"}),
+            "require 'tls' to be set",
+        ),
+        (
+            serde_json::json!({"tls": true, "tls_client_certificate": "cert"}),
+            "must be set together",
+        ),
+    ] {
+        let Err(err) = MqttOutputEndpoint::new(config(value.clone())) else {
+            panic!("configuration {value} should be rejected");
+        };
+        assert!(
+            err.to_string().contains(error),
+            "unexpected error for {value}: {err}"
+        );
+    }
+
+    let Err(err) = crate::transport::input_transport_config_to_endpoint(
+        mqtt_input_config(&["foo/#/bar"], "")
+            .connector_config
+            .transport,
+        "",
+    ) else {
+        panic!("invalid topic filter should be rejected");
+    };
+    assert!(err.to_string().contains("not a valid MQTT topic filter"));
+
+    let Err(err) = crate::transport::input_transport_config_to_endpoint(
+        mqtt_input_config(&["foo"], "clean_session: false")
+            .connector_config
+            .transport,
+        "",
+    ) else {
+        panic!("persistent session without client id should be rejected");
+    };
+    assert!(err.to_string().contains("requires 'client_id'"));
+}
@@ -0,0 +1,546 @@
+//! Input transport that reads messages from a NATS JetStream stream using a
+//! durable pull consumer.
+//!
+//! The connector records the range of stream sequence numbers that make up
+//! each step as the step's metadata.  To replay a step after a failure, it
+//! re-reads the range using a temporary consumer.  Messages are only
+//! acknowledged after a checkpoint that includes them has been written.  After
+//! a restart, the connector recreates the durable consumer to start right
+//! after the last checkpointed message, so that messages that were delivered
+//! to the connector, but not yet checkpointed, are delivered again, in order.
+
+use super::ConnectionOptions;
+use crate::transport::{
+    InputConsumer, InputEndpoint, InputReader, InputReaderCommand, TransportInputEndpoint,
+};
+use crate::{InputBuffer, Parser};
+use anyhow::{anyhow, bail, Error as AnyError, Result as AnyResult};
+use async_nats::jetstream::{
+    self,
+    consumer::{pull, AckPolicy, Consumer, DeliverPolicy},
+    message::Acker,
+    stream::Stream,
+};
+use dbsp::circuit::tokio::TOKIO;
+use feldera_adapterlib::transport::Resume;
+use feldera_types::config::FtModel;
+use feldera_types::program_schema::Relation;
+use feldera_types::transport::nats::{NatsDeliverPolicy, NatsInputConfig};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, VecDeque};
+use std::hash::Hasher;
+use std::thread::spawn;
+use std::time::Duration;
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::time::sleep;
+use tracing::{error, info_span, Instrument};
+use xxhash_rust::xxh3::Xxh3Default;
+
+/// Delay before requesting more messages after a failed request.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Time after which the server deletes the temporary consumers used to replay
+/// steps.
+const REPLAY_CONSUMER_INACTIVE_THRESHOLD: Duration = Duration::from_secs(60);
+
+/// Metadata of a step: the stream sequence numbers of the first and last
+/// messages in the step.
+///
+/// `first` is `None` if the step contains no messages, in which case `last` is
+/// the sequence number of the last message read before the step, if any.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Metadata {
+    first: Option<u64>,
+    last: Option<u64>,
+}
+
+pub(crate) struct NatsInputEndpoint {
+    config: NatsInputConfig,
+}
+
+impl NatsInputEndpoint {
+    pub(crate) fn new(config: NatsInputConfig) -> AnyResult<Self> {
+        ConnectionOptions::from(&config).validate()?;
+        if config.stream.is_empty() {
+            bail!("'stream' must not be empty");
+        }
+        if config.consumer_name.is_empty()
+            || config
+                .consumer_name
+                .contains(|c: char| c == '.' || c == '*' || c == '>' || c.is_whitespace())
+        {
+            bail!(
+                "invalid 'consumer_name' '{}': consumer names must be non-empty and must not contain '.', '*', '>', or whitespace",
+                config.consumer_name
+            );
+        }
+        if config.batch_size == 0 {
+            bail!("'batch_size' must be greater than 0");
+        }
+        if config.fetch_timeout_ms == 0 {
+            bail!("'fetch_timeout_ms' must be greater than 0");
+        }
+        Ok(Self { config })
+    }
+}
+
+impl InputEndpoint for NatsInputEndpoint {
+    fn fault_tolerance(&self) -> Option<FtModel> {
+        Some(FtModel::ExactlyOnce)
+    }
+}
+
+impl TransportInputEndpoint for NatsInputEndpoint {
+    fn open(
+        &self,
+        consumer: Box<dyn InputConsumer>,
+        parser: Box<dyn Parser>,
+        _schema: Relation,
+    ) -> AnyResult<Box<dyn InputReader>> {
+        Ok(Box::new(NatsInputReader::new(
+            self.config.clone(),
+            consumer,
+            parser,
+        )?))
+    }
+}
+
+/// Messages from [NatsInputReader] to its worker thread.
+enum Command {
+    Reader(InputReaderCommand),
+
+    /// A checkpoint that includes all messages up to and including this stream
+    /// sequence number has been written.
+    Checkpointed(u64),
+}
+
+struct NatsInputReader {
+    sender: UnboundedSender<Command>,
+}
+
+impl NatsInputReader {
+    fn new(
+        config: NatsInputConfig,
+        consumer: Box<dyn InputConsumer>,
+        parser: Box<dyn Parser>,
+    ) -> AnyResult<Self> {
+        let span = info_span!("nats_input", stream = config.stream.clone());
+        let (stream, durable) =
+            TOKIO.block_on(Self::open_consumer(&config).instrument(span.clone()))?;
+
+        let (sender, receiver) = unbounded_channel();
+        spawn(move || {
+            let mut worker = NatsInputWorker {
+                config,
+                stream,
+                durable,
+                consumer,
+                parser,
+                queue: VecDeque::new(),
+                position: None,
+                unacked: BTreeMap::new(),
+                extending: false,
+                reset: false,
+            };
+            TOKIO.block_on(async {
+                if let Err(error) = worker.run(receiver).instrument(span).await {
+                    worker.consumer.error(true, error);
+                }
+            })
+        });
+
+        Ok(Self { sender })
+    }
+
+    /// Connects to the server and opens the durable consumer, creating it if
+    /// it doesn't exist.
+    async fn open_consumer(
+        config: &NatsInputConfig,
+    ) -> AnyResult<(Stream, Consumer<pull::Config>)> {
+        let client = ConnectionOptions::from(config).connect().await?;
+        let context = jetstream::new(client);
+        let stream = context
+            .get_stream(&config.stream)
+            .await
+            .map_err(|e| anyhow!("error opening JetStream stream '{}': {e}", config.stream))?;
+        let deliver_policy = match config.deliver_policy {
+            NatsDeliverPolicy::All => DeliverPolicy::All,
+            NatsDeliverPolicy::New => DeliverPolicy::New,
+            NatsDeliverPolicy::Last => DeliverPolicy::Last,
+        };
+        let durable = stream
+            .get_or_create_consumer(
+                &config.consumer_name,
+                durable_config(config, deliver_policy),
+            )
+            .await
+            .map_err(|e| {
+                anyhow!(
+                    "error opening consumer '{}' of JetStream stream '{}': {e}",
+                    config.consumer_name,
+                    config.stream
+                )
+            })?;
+        Ok((stream, durable))
+    }
+
+    fn send(&self, command: Command) {
+        let _ = self.sender.send(command);
+    }
+}
+
+impl InputReader for NatsInputReader {
+    fn request(&self, command: InputReaderCommand) {
+        self.send(Command::Reader(command));
+    }
+
+    fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    fn checkpointed(&self, metadata: &JsonValue) {
+        match serde_json::from_value::<Metadata>(metadata.clone()) {
+            Ok(Metadata {
+                last: Some(last), ..
+            }) => self.send(Command::Checkpointed(last)),
+            Ok(_) => (),
+            Err(e) => error!("invalid checkpoint metadata for NATS input: {e}"),
+        }
+    }
+}
+
+impl Drop for NatsInputReader {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+/// Configuration of the durable consumer.
+fn durable_config(config: &NatsInputConfig, deliver_policy: DeliverPolicy) -> pull::Config {
+    pull::Config {
+        durable_name: Some(config.consumer_name.clone()),
+        deliver_policy,
+        // Acknowledging a message acknowledges all messages delivered before
+        // it.
+        ack_policy: AckPolicy::All,
+        ack_wait: Duration::from_secs(config.ack_wait_secs),
+        max_ack_pending: config.max_ack_pending,
+        filter_subjects: config.filter_subjects.clone(),
+        ..Default::default()
+    }
+}
+
+struct NatsInputWorker {
+    config: NatsInputConfig,
+    stream: Stream,
+    durable: Consumer<pull::Config>,
+    consumer: Box<dyn InputConsumer>,
+    parser: Box<dyn Parser>,
+
+    /// Parsed messages that haven't been flushed to the circuit yet.
+    queue: VecDeque<(u64, Box<dyn InputBuffer>)>,
+
+    /// Stream sequence number of the last message read, if any.
+    position: Option<u64>,
+
+    /// Acknowledgment handles of messages delivered to the connector that
+    /// haven't been acknowledged yet, indexed by stream sequence number.
+    unacked: BTreeMap<u64, Acker>,
+
+    /// Whether the controller asked for more data.
+    extending: bool,
+
+    /// Whether the durable consumer must be recreated to start reading after
+    /// `position` before reading new messages, because the connector was
+    /// restarted from a checkpoint.
+    reset: bool,
+}
+
+impl NatsInputWorker {
+    async fn run(&mut self, mut receiver: UnboundedReceiver<Command>) -> AnyResult<()> {
+        loop {
+            loop {
+                match receiver.try_recv() {
+                    Ok(command) => {
+                        if !self.command(command).await? {
+                            return Ok(());
+                        }
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return Ok(()),
+                }
+            }
+
+            if !self.extending {
+                match receiver.recv().await {
+                    Some(command) => {
+                        if !self.command(command).await? {
+                            return Ok(());
+                        }
+                    }
+                    None => return Ok(()),
+                }
+                continue;
+            }
+
+            if self.reset {
+                self.reset_durable().await?;
+                self.reset = false;
+            }
+
+            if let Err(e) = self.fetch().await {
+                self.consumer.error(false, e);
+                sleep(RETRY_DELAY).await;
+            }
+        }
+    }
+
+    /// Executes `command`.  Returns `false` if the connector should
+    /// disconnect.
+    async fn command(&mut self, command: Command) -> AnyResult<bool> {
+        match command {
+            Command::Reader(InputReaderCommand::Extend) => self.extending = true,
+            Command::Reader(InputReaderCommand::Pause) => self.extending = false,
+            Command::Reader(InputReaderCommand::Queue { .. }) => self.queue().await?,
+            Command::Reader(InputReaderCommand::Seek(metadata)) => {
+                let Metadata { last, .. } = serde_json::from_value(metadata)?;
+                self.position = last;
+                self.reset = true;
+            }
+            Command::Reader(InputReaderCommand::Replay { metadata, .. }) => {
+                self.replay(serde_json::from_value(metadata)?).await?
+            }
+            Command::Reader(InputReaderCommand::Disconnect) => return Ok(false),
+            Command::Checkpointed(last) => self.ack(last).await?,
+        }
+        Ok(true)
+    }
+
+    /// Recreates the durable consumer, so that it starts reading right after
+    /// `position`.
+    ///
+    /// Messages that were delivered to the previous incarnation of the
+    /// connector but not acknowledged would otherwise only be delivered again
+    /// once their acknowledgment timeout expires, after newer messages.
+    async fn reset_durable(&mut self) -> AnyResult<()> {
+        let deliver_policy = match self.position {
+            Some(position) => DeliverPolicy::ByStartSequence {
+                start_sequence: position + 1,
+            },
+            None => match self.config.deliver_policy {
+                NatsDeliverPolicy::All => DeliverPolicy::All,
+                NatsDeliverPolicy::New => DeliverPolicy::New,
+                NatsDeliverPolicy::Last => DeliverPolicy::Last,
+            },
+        };
+        self.stream
+            .delete_consumer(&self.config.consumer_name)
+            .await
+            .map_err(|e| {
+                anyhow!(
+                    "error deleting consumer '{}' of JetStream stream '{}': {e}",
+                    self.config.consumer_name,
+                    self.config.stream
+                )
+            })?;
+        self.durable = self
+            .stream
+            .create_consumer(durable_config(&self.config, deliver_policy))
+            .await
+            .map_err(|e| {
+                anyhow!(
+                    "error creating consumer '{}' of JetStream stream '{}': {e}",
+                    self.config.consumer_name,
+                    self.config.stream
+                )
+            })?;
+        self.unacked.clear();
+        Ok(())
+    }
+
+    /// Requests up to `batch_size` messages from the durable consumer and
+    /// processes them.
+    async fn fetch(&mut self) -> AnyResult<()> {
+        let mut messages = self
+            .durable
+            .batch()
+            .max_messages(self.config.batch_size)
+            .expires(Duration::from_millis(self.config.fetch_timeout_ms))
+            .messages()
+            .await
+            .map_err(|e| {
+                anyhow!(
+                    "error requesting messages from JetStream stream '{}': {e}",
+                    self.config.stream
+                )
+            })?;
+        while let Some(message) = messages.next().await {
+            let message = message.map_err(|e| {
+                anyhow!(
+                    "error receiving message from JetStream stream '{}': {e}",
+                    self.config.stream
+                )
+            })?;
+            let sequence = message
+                .info()
+                .map_err(|e| anyhow!("invalid JetStream message metadata: {e}"))?
+                .stream_sequence;
+            let (message, acker) = message.split();
+
+            // Messages are delivered again if they are not acknowledged in
+            // time.  Those up to `position` have already been read.
+            if self.position.is_some_and(|position| sequence <= position) {
+                continue;
+            }
+            self.unacked.insert(sequence, acker);
+            self.position = Some(sequence);
+            let (buffer, errors) = self.parser.parse(&message.payload);
+            self.consumer.parse_errors(errors);
+            if let Some(buffer) = buffer {
+                self.consumer.buffered(buffer.len(), message.payload.len());
+                self.queue.push_back((sequence, buffer));
+            }
+        }
+        Ok(())
+    }
+
+    /// Flushes up to `max_batch_size` queued records and reports the step.
+    async fn queue(&mut self) -> AnyResult<()> {
+        let mut total = 0;
+        let mut hasher = self.consumer.hasher();
+        let limit = self.consumer.max_batch_size();
+        let mut range: Option<(u64, u64)> = None;
+        while let Some((sequence, mut buffer)) = self.queue.pop_front() {
+            range = Some((range.map_or(sequence, |(first, _)| first), sequence));
+            total += buffer.len();
+            if let Some(hasher) = hasher.as_mut() {
+                buffer.hash(hasher);
+            }
+            buffer.flush();
+            if total >= limit {
+                break;
+            }
+        }
+
+        // Messages that didn't produce any records are part of the step as
+        // long as no later messages are still queued.
+        let last = match self.queue.front() {
+            None => self.position,
+            Some(_) => range.map(|(_, last)| last),
+        };
+        let metadata = Metadata {
+            first: range.map(|(first, _)| first),
+            last,
+        };
+
+        if self.consumer.pipeline_fault_tolerance().is_none() {
+            if let Some(last) = last {
+                self.ack(last).await?;
+            }
+        }
+
+        let seek = serde_json::to_value(metadata).unwrap();
+        self.consumer
+            .extended(total, Some(Resume::new_metadata_only(seek, hasher)));
+        Ok(())
+    }
+
+    /// Re-reads the messages in the step described by `metadata` using a
+    /// temporary consumer.
+    async fn replay(&mut self, metadata: Metadata) -> AnyResult<()> {
+        let mut num_records = 0;
+        let mut hasher = Xxh3Default::new();
+        if let (Some(first), Some(last)) = (metadata.first, metadata.last) {
+            let replay_consumer = self
+                .stream
+                .create_consumer(pull::Config {
+                    deliver_policy: DeliverPolicy::ByStartSequence {
+                        start_sequence: first,
+                    },
+                    ack_policy: AckPolicy::None,
+                    filter_subjects: self.config.filter_subjects.clone(),
+                    inactive_threshold: REPLAY_CONSUMER_INACTIVE_THRESHOLD,
+                    ..Default::default()
+                })
+                .await
+                .map_err(|e| {
+                    anyhow!(
+                        "error creating consumer to replay JetStream stream '{}': {e}",
+                        self.config.stream
+                    )
+                })?;
+
+            // Messages in the range may have been deleted from the stream,
+            // so stop when there are no more messages.
+            'replay: loop {
+                let mut messages = replay_consumer
+                    .fetch()
+                    .max_messages(self.config.batch_size)
+                    .messages()
+                    .await
+                    .map_err(|e| {
+                        anyhow!(
+                            "error requesting messages from JetStream stream '{}': {e}",
+                            self.config.stream
+                        )
+                    })?;
+                let mut received = false;
+                while let Some(message) = messages.next().await {
+                    received = true;
+                    let message = message.map_err(|e| {
+                        anyhow!(
+                            "error receiving message from JetStream stream '{}': {e}",
+                            self.config.stream
+                        )
+                    })?;
+                    let sequence = message
+                        .info()
+                        .map_err(|e| anyhow!("invalid JetStream message metadata: {e}"))?
+                        .stream_sequence;
+                    if sequence > last {
+                        break 'replay;
+                    }
+                    let (buffer, errors) = self.parser.parse(&message.payload);
+                    self.consumer.parse_errors(errors);
+                    if let Some(mut buffer) = buffer {
+                        self.consumer.buffered(buffer.len(), message.payload.len());
+                        num_records += buffer.len();
+                        buffer.hash(&mut hasher);
+                        buffer.flush();
+                    }
+                    if sequence == last {
+                        break 'replay;
+                    }
+                }
+                if !received {
+                    break;
+                }
+            }
+        }
+        self.position = metadata.last;
+        self.consumer.replayed(num_records, hasher.finish());
+        Ok(())
+    }
+
+    /// Acknowledges all messages up to and including stream sequence number
+    /// `last`.
+    async fn ack(&mut self, last: u64) -> AnyResult<()> {
+        let rest = self.unacked.split_off(&(last + 1));
+        let acked = std::mem::replace(&mut self.unacked, rest);
+
+        // With `AckPolicy::All`, acknowledging the last message acknowledges
+        // all earlier ones.
+        if let Some((_, acker)) = acked.into_iter().next_back() {
+            acker.ack().await.map_err(|e| {
+                anyhow!(
+                    "error acknowledging messages in JetStream stream '{}': {e}",
+                    self.config.stream
+                )
+            })?;
+        }
+        Ok(())
+    }
+}
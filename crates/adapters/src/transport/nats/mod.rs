@@ -0,0 +1,120 @@
+//! Transport adapter for NATS JetStream.
+
+mod input;
+mod output;
+
+#[cfg(test)]
+mod test;
+
+use anyhow::{anyhow, bail, Error as AnyError};
+use async_nats::{Client, ConnectOptions};
+use feldera_types::transport::nats::{NatsInputConfig, NatsOutputConfig};
+use std::path::PathBuf;
+use tracing::debug;
+
+pub use input::NatsInputEndpoint;
+pub use output::NatsOutputEndpoint;
+
+/// Options for connecting to the NATS server, shared by the input and output
+/// connector configurations.
+struct ConnectionOptions<'a> {
+    server_url: &'a str,
+    username: &'a Option<String>,
+    password: &'a Option<String>,
+    token: &'a Option<String>,
+    credentials: &'a Option<String>,
+    tls_required: bool,
+    tls_ca_file: &'a Option<String>,
+    tls_client_cert_file: &'a Option<String>,
+    tls_client_key_file: &'a Option<String>,
+}
+
+impl<'a> From<&'a NatsInputConfig> for ConnectionOptions<'a> {
+    fn from(config: &'a NatsInputConfig) -> Self {
+        Self {
+            server_url: &config.server_url,
+            username: &config.username,
+            password: &config.password,
+            token: &config.token,
+            credentials: &config.credentials,
+            tls_required: config.tls_required,
+            tls_ca_file: &config.tls_ca_file,
+            tls_client_cert_file: &config.tls_client_cert_file,
+            tls_client_key_file: &config.tls_client_key_file,
+        }
+    }
+}
+
+impl<'a> From<&'a NatsOutputConfig> for ConnectionOptions<'a> {
+    fn from(config: &'a NatsOutputConfig) -> Self {
+        Self {
+            server_url: &config.server_url,
+            username: &config.username,
+            password: &config.password,
+            token: &config.token,
+            credentials: &config.credentials,
+            tls_required: config.tls_required,
+            tls_ca_file: &config.tls_ca_file,
+            tls_client_cert_file: &config.tls_client_cert_file,
+            tls_client_key_file: &config.tls_client_key_file,
+        }
+    }
+}
+
+impl ConnectionOptions<'_> {
+    fn validate(&self) -> Result<(), AnyError> {
+        if self.server_url.is_empty() {
+            bail!("'server_url' must not be empty");
+        }
+        if self.username.is_some() != self.password.is_some() {
+            bail!("'username' and 'password' must be set together");
+        }
+        let methods = [
+            self.username.is_some(),
+            self.token.is_some(),
+            self.credentials.is_some(),
+        ];
+        if methods.into_iter().filter(|set| *set).count() > 1 {
+            bail!("at most one of 'username', 'token', and 'credentials' can be set");
+        }
+        if self.tls_client_cert_file.is_some() != self.tls_client_key_file.is_some() {
+            bail!("'tls_client_cert_file' and 'tls_client_key_file' must be set together");
+        }
+        Ok(())
+    }
+
+    /// Connects to the NATS server.
+    async fn connect(&self) -> Result<Client, AnyError> {
+        let mut options = ConnectOptions::new()
+            .name("feldera")
+            .require_tls(self.tls_required);
+
+        if let (Some(username), Some(password)) = (self.username, self.password) {
+            options = options.user_and_password(username.clone(), password.clone());
+        }
+        if let Some(token) = self.token {
+            options = options.token(token.clone());
+        }
+        if let Some(credentials) = self.credentials {
+            options = options
+                .credentials(credentials)
+                .map_err(|e| anyhow!("error parsing NATS credentials: {e}"))?;
+        }
+        if let Some(ca_file) = self.tls_ca_file {
+            options = options.add_root_certificates(PathBuf::from(ca_file));
+        }
+        if let (Some(cert_file), Some(key_file)) =
+            (self.tls_client_cert_file, self.tls_client_key_file)
+        {
+            options =
+                options.add_client_certificate(PathBuf::from(cert_file), PathBuf::from(key_file));
+        }
+
+        debug!("NATS endpoint: connecting to server {}", self.server_url);
+
+        options
+            .connect(self.server_url)
+            .await
+            .map_err(|e| anyhow!("error connecting to NATS server '{}': {e}", self.server_url))
+    }
+}
@@ -0,0 +1,196 @@
+use super::ConnectionOptions;
+use crate::transport::{AsyncErrorCallback, OutputEndpoint, Step};
+use anyhow::{anyhow, bail, Error as AnyError, Result as AnyResult};
+use async_nats::{
+    header::NATS_MESSAGE_ID,
+    jetstream::{self, context::PublishAckFuture, Context},
+    HeaderMap,
+};
+use dbsp::circuit::tokio::TOKIO;
+use feldera_types::transport::nats::NatsOutputConfig;
+use std::mem::take;
+use tracing::{info, info_span, Instrument};
+
+/// Default maximum size of a message, used until the connector learns the
+/// limit configured in the server.
+const DEFAULT_MAX_PAYLOAD: usize = 1024 * 1024;
+
+/// Publishes output to a NATS JetStream subject.
+///
+/// Each buffer produced by the encoder is published as a separate message.
+/// In a fault-tolerant pipeline, each message carries a de-duplication id
+/// derived from the step and the position of the message in the step, so that
+/// the server discards messages published again when a step is replayed after
+/// a restart.  (Other pipelines number steps from 0 after every restart, so
+/// ids would not be unique.)  The server only remembers ids for the stream's
+/// duplicate window, so replayed messages are only discarded if the pipeline
+/// restarts within the window.  The connector waits for the server to
+/// acknowledge all messages published during a step at the end of the step.
+pub struct NatsOutputEndpoint {
+    config: NatsOutputConfig,
+
+    /// Prefix of de-duplication ids, or `None` if messages are published
+    /// without ids.
+    message_id_prefix: Option<String>,
+    context: Option<Context>,
+    max_payload: usize,
+
+    /// Current step and the number of messages published in it so far.
+    step: Step,
+    sequence: usize,
+
+    /// Messages published in the current step that haven't been acknowledged
+    /// yet.
+    pending: Vec<PublishAckFuture>,
+}
+
+impl NatsOutputEndpoint {
+    pub fn new(
+        config: NatsOutputConfig,
+        endpoint_name: &str,
+        fault_tolerant: bool,
+    ) -> AnyResult<Self> {
+        ConnectionOptions::from(&config).validate()?;
+        if config.subject.is_empty()
+            || config
+                .subject
+                .split('.')
+                .any(|token| token.is_empty() || token == "*" || token == ">")
+        {
+            bail!(
+                "'{}' is not a valid NATS subject: subjects must consist of non-empty tokens separated by '.' and must not contain wildcards",
+                config.subject
+            );
+        }
+        let message_id_prefix = fault_tolerant.then(|| {
+            config
+                .message_id_prefix
+                .clone()
+                .unwrap_or_else(|| endpoint_name.to_string())
+        });
+        Ok(Self {
+            config,
+            message_id_prefix,
+            context: None,
+            max_payload: DEFAULT_MAX_PAYLOAD,
+            step: 0,
+            sequence: 0,
+            pending: Vec::new(),
+        })
+    }
+
+    fn publish(&mut self, headers: HeaderMap, payload: &[u8]) -> AnyResult<()> {
+        let Some(context) = &self.context else {
+            bail!("NATS output endpoint is not connected");
+        };
+        let mut headers = headers;
+        if let Some(prefix) = &self.message_id_prefix {
+            let message_id = format!("{prefix}:{}:{}", self.step, self.sequence);
+            headers.insert(NATS_MESSAGE_ID, message_id.as_str());
+        }
+        self.sequence += 1;
+        let ack = TOKIO
+            .block_on(context.publish_with_headers(
+                self.config.subject.clone(),
+                headers,
+                payload.to_vec().into(),
+            ))
+            .map_err(|e| {
+                anyhow!(
+                    "error publishing message to NATS subject '{}': {e}",
+                    self.config.subject
+                )
+            })?;
+        self.pending.push(ack);
+        Ok(())
+    }
+}
+
+impl OutputEndpoint for NatsOutputEndpoint {
+    fn connect(&mut self, _async_error_callback: AsyncErrorCallback) -> AnyResult<()> {
+        let span = info_span!("nats_output", subject = self.config.subject.clone());
+        let client = TOKIO.block_on(
+            ConnectionOptions::from(&self.config)
+                .connect()
+                .instrument(span),
+        )?;
+        self.max_payload = client.server_info().max_payload;
+        let context = jetstream::new(client);
+        if self.message_id_prefix.is_some() {
+            let duplicate_window = TOKIO
+                .block_on(async {
+                    let stream = context
+                        .stream_by_subject(self.config.subject.clone())
+                        .await?;
+                    let stream = context.get_stream(stream).await?;
+                    Ok::<_, AnyError>(stream.cached_info().config.duplicate_window)
+                })
+                .map_err(|e| {
+                    anyhow!(
+                        "error looking up the JetStream stream bound to NATS subject '{}': {e}",
+                        self.config.subject
+                    )
+                })?;
+            if duplicate_window.is_zero() {
+                bail!(
+                    "the JetStream stream bound to NATS subject '{}' does not de-duplicate messages (its duplicate window is 0), which fault-tolerant output requires",
+                    self.config.subject
+                );
+            }
+            info!(
+                "NATS output to subject '{}': messages replayed after a restart are de-duplicated within the stream's duplicate window of {duplicate_window:?}",
+                self.config.subject
+            );
+        }
+        self.context = Some(context);
+        Ok(())
+    }
+
+    fn max_buffer_size_bytes(&self) -> usize {
+        self.max_payload
+    }
+
+    fn batch_start(&mut self, step: Step) -> AnyResult<()> {
+        self.step = step;
+        self.sequence = 0;
+        Ok(())
+    }
+
+    fn push_buffer(&mut self, buffer: &[u8]) -> AnyResult<()> {
+        self.publish(HeaderMap::new(), buffer)
+    }
+
+    fn push_key(
+        &mut self,
+        _key: Option<&[u8]>,
+        val: Option<&[u8]>,
+        headers: &[(&str, Option<&[u8]>)],
+    ) -> AnyResult<()> {
+        let mut header_map = HeaderMap::new();
+        for (name, value) in headers {
+            if let Some(value) = value {
+                header_map.insert(*name, String::from_utf8_lossy(value).as_ref());
+            }
+        }
+        self.publish(header_map, val.unwrap_or_default())
+    }
+
+    fn batch_end(&mut self) -> AnyResult<()> {
+        let pending = take(&mut self.pending);
+        TOKIO.block_on(async {
+            for ack in pending {
+                ack.await.map_err(|e| {
+                    anyhow!(
+                        "error publishing message to NATS subject '{}': {e}",
+                        self.config.subject
+                    )
+                })?;
+            }
+            Ok(())
+        })
+    }
+
+    fn is_fault_tolerant(&self) -> bool {
+        self.message_id_prefix.is_some()
+    }
+}
@@ -0,0 +1,282 @@
+//! NATS JetStream connector tests.
+//!
+//! The tests require a NATS server with JetStream enabled running on
+//! `NATS_URL` (`nats://localhost:4222` by default):
+//!
+//! ```bash
+//! docker run -p 4222:4222 nats:2.10 -js
+//! ```
+
+use super::NatsOutputEndpoint;
+use crate::{
+    test::{data::TestStruct, mock_input_pipeline, wait, MockDeZSet, DEFAULT_TIMEOUT_MS},
+    transport::OutputEndpoint,
+    InputReader,
+};
+use async_nats::jetstream::{self, consumer::pull, stream, Context};
+use dbsp::circuit::tokio::TOKIO;
+use feldera_types::{config::InputEndpointConfig, transport::nats::NatsOutputConfig};
+use futures::StreamExt;
+use rmpv::Value as RmpValue;
+use serde_json::json;
+use std::{env, time::Duration};
+
+fn nats_url() -> String {
+    env::var("NATS_URL").unwrap_or("nats://localhost:4222".to_string())
+}
+
+/// Connects to the server and (re)creates stream `name` with subjects
+/// `<name>.>`, so that tests start from scratch.
+fn create_stream(name: &str) -> Context {
+    TOKIO.block_on(async {
+        let client = async_nats::connect(nats_url()).await.unwrap();
+        let context = jetstream::new(client);
+        let _ = context.delete_stream(name).await;
+        context
+            .create_stream(stream::Config {
+                name: name.to_string(),
+                subjects: vec![format!("{name}.>")],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        context
+    })
+}
+
+fn publish(context: &Context, subject: &str, data: &[TestStruct]) {
+    TOKIO.block_on(async {
+        for val in data {
+            context
+                .publish(subject.to_string(), serde_json::to_vec(val).unwrap().into())
+                .await
+                .unwrap()
+                .await
+                .unwrap();
+        }
+    })
+}
+
+fn num_ack_pending(context: &Context, stream: &str) -> usize {
+    TOKIO.block_on(async {
+        let stream = context.get_stream(stream).await.unwrap();
+        let mut consumer: jetstream::consumer::Consumer<pull::Config> =
+            stream.get_consumer("feldera").await.unwrap();
+        consumer.info().await.unwrap().num_ack_pending
+    })
+}
+
+fn nats_input_config(stream: &str) -> InputEndpointConfig {
+    let config_str = format!(
+        r#"
+stream: test_input
+transport:
+    name: nats_input
+    config:
+        server_url: {}
+        stream: {stream}
+format:
+    name: json
+    config:
+        update_format: raw
+        array: false
+"#,
+        nats_url()
+    );
+    serde_yaml::from_str(&config_str).unwrap()
+}
+
+fn test_data(ids: std::ops::Range<u32>) -> Vec<TestStruct> {
+    ids.map(|id| TestStruct {
+        id,
+        b: id % 2 == 0,
+        i: if id % 3 == 0 { None } else { Some(id as i64) },
+        s: format!("foo{id}"),
+    })
+    .collect()
+}
+
+fn wait_for_records(
+    endpoint: &dyn InputReader,
+    zset: &MockDeZSet<TestStruct, TestStruct>,
+    expected: &[TestStruct],
+) {
+    wait(
+        || {
+            endpoint.queue(false);
+            zset.state().flushed.len() >= expected.len()
+        },
+        DEFAULT_TIMEOUT_MS,
+    )
+    .unwrap();
+    let received = zset
+        .state()
+        .flushed
+        .iter()
+        .map(|upd| upd.unwrap_insert().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(received, expected);
+}
+
+#[test]
+fn test_nats_input() {
+    let stream = "test_nats_input";
+    let context = create_stream(stream);
+    let data = test_data(0..3);
+    publish(&context, "test_nats_input.a", &data);
+
+    let (endpoint, _consumer, _parser, zset) = mock_input_pipeline::<TestStruct, TestStruct>(
+        nats_input_config(stream),
+        TestStruct::relation_schema(),
+    )
+    .unwrap();
+    endpoint.extend();
+    wait_for_records(endpoint.as_ref(), &zset, &data);
+
+    // Messages are only acknowledged once they are checkpointed.
+    assert_eq!(num_ack_pending(&context, stream), data.len());
+    endpoint.checkpointed(&json!({"first": 1, "last": 3}));
+    wait(
+        || num_ack_pending(&context, stream) == 0,
+        DEFAULT_TIMEOUT_MS,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_nats_input_restart() {
+    let stream = "test_nats_input_restart";
+    let context = create_stream(stream);
+    let data = test_data(0..3);
+    publish(&context, "test_nats_input_restart.a", &data[..2]);
+
+    let (endpoint, _consumer, _parser, zset) = mock_input_pipeline::<TestStruct, TestStruct>(
+        nats_input_config(stream),
+        TestStruct::relation_schema(),
+    )
+    .unwrap();
+    endpoint.extend();
+    wait_for_records(endpoint.as_ref(), &zset, &data[..2]);
+
+    // Make sure that the reader has stopped before publishing more messages.
+    endpoint.disconnect();
+    wait(|| endpoint.is_closed(), DEFAULT_TIMEOUT_MS).unwrap();
+    drop(endpoint);
+
+    // Restart from a checkpoint that only includes the first message.  The
+    // second message was delivered before the restart but never
+    // acknowledged, so it must be read again, before the third message.
+    let (endpoint, _consumer, _parser, zset) = mock_input_pipeline::<TestStruct, TestStruct>(
+        nats_input_config(stream),
+        TestStruct::relation_schema(),
+    )
+    .unwrap();
+    endpoint.seek(json!({"first": 1, "last": 1}));
+    endpoint.extend();
+    publish(&context, "test_nats_input_restart.a", &data[2..]);
+    wait_for_records(endpoint.as_ref(), &zset, &data[1..]);
+}
+
+#[test]
+fn test_nats_input_replay() {
+    let stream = "test_nats_input_replay";
+    let context = create_stream(stream);
+    let data = test_data(0..4);
+    publish(&context, "test_nats_input_replay.a", &data[..3]);
+
+    // Replay a step that consists of the second and third messages.  The
+    // connector then continues after the replayed step.
+    let (endpoint, _consumer, _parser, zset) = mock_input_pipeline::<TestStruct, TestStruct>(
+        nats_input_config(stream),
+        TestStruct::relation_schema(),
+    )
+    .unwrap();
+    endpoint.replay(json!({"first": 2, "last": 3}), RmpValue::Nil);
+    wait(|| zset.state().flushed.len() == 2, DEFAULT_TIMEOUT_MS).unwrap();
+    zset.reset();
+
+    endpoint.extend();
+    publish(&context, "test_nats_input_replay.a", &data[3..]);
+    wait_for_records(endpoint.as_ref(), &zset, &data[3..]);
+}
+
+#[test]
+fn test_nats_output() {
+    let stream = "test_nats_output";
+    let context = create_stream(stream);
+
+    let config: NatsOutputConfig = serde_json::from_value(json!({
+        "server_url": nats_url(),
+        "subject": "test_nats_output.out",
+    }))
+    .unwrap();
+
+    // Publish the same step twice, as a fault-tolerant pipeline does when it
+    // replays a step after a restart.
+    for _ in 0..2 {
+        let mut endpoint = NatsOutputEndpoint::new(config.clone(), "output", true).unwrap();
+        endpoint.connect(Box::new(|_, _| ())).unwrap();
+        endpoint.batch_start(5).unwrap();
+        endpoint.push_buffer(b"first").unwrap();
+        endpoint
+            .push_key(None, Some(b"second"), &[("op", Some(b"insert"))])
+            .unwrap();
+        endpoint.batch_end().unwrap();
+    }
+
+    let messages = TOKIO.block_on(async {
+        let stream = context.get_stream(stream).await.unwrap();
+        let consumer = stream
+            .create_consumer(pull::Config::default())
+            .await
+            .unwrap();
+        let mut batch = consumer
+            .fetch()
+            .max_messages(10)
+            .expires(Duration::from_secs(1))
+            .messages()
+            .await
+            .unwrap();
+        let mut messages = Vec::new();
+        while let Some(message) = batch.next().await {
+            messages.push(message.unwrap());
+        }
+        messages
+    });
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].payload.as_ref(), b"first");
+    assert_eq!(messages[1].payload.as_ref(), b"second");
+    let headers = messages[1].headers.as_ref().unwrap();
+    assert_eq!(headers.get("Nats-Msg-Id").unwrap().as_str(), "output:5:1");
+    assert_eq!(headers.get("op").unwrap().as_str(), "insert");
+}
+
+#[test]
+fn test_nats_config_errors() {
+    let config = |value: serde_json::Value| -> NatsOutputConfig {
+        let mut config = json!({"server_url": "nats://localhost:4222", "subject": "foo.bar"});
+        config
+            .as_object_mut()
+            .unwrap()
+            .extend(value.as_object().unwrap().clone());
+        serde_json::from_value(config).unwrap()
+    };
+
+    for (value, error) in [
+        (json!({"subject": "foo.*"}), "not a valid NATS subject"),
+        (json!({"subject": "foo..bar"}), "not a valid NATS subject"),
+        (json!({"username": "user"}), "must be set together"),
+        (
+            json!({"username": "user", "password": "secret", "token": "token"}),
+            "at most one of",
+        ),
+    ] {
+        let Err(err) = NatsOutputEndpoint::new(config(value.clone()), "output", false) else {
+            panic!("configuration {value} should be rejected");
+        };
+        assert!(
+            err.to_string().contains(error),
+            "unexpected error for {value}: {err}"
+        );
+    }
+}
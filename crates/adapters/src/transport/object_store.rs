@@ -0,0 +1,483 @@
+//! Output transport that writes data to an object store (S3, GCS, Azure Blob
+//! Storage, or the local file system).
+//!
+//! The connector accumulates encoded buffers in memory and uploads them as
+//! objects under the configured prefix, starting a new object when the current
+//! one reaches the size, record count, or age limit.
+//!
+//! In fault-tolerant mode an object never spans multiple steps, and object
+//! names are derived from the step and the position of the object within the
+//! step.  Objects are only uploaded in `batch_end`.  When a step is replayed
+//! after a restart, the connector overwrites the objects it wrote for the step
+//! before the failure instead of creating duplicates.
+
+use crate::transport::{AsyncErrorCallback, OutputEndpoint, Step};
+use anyhow::{anyhow, bail, Result as AnyResult};
+use chrono::Utc;
+use dbsp::circuit::tokio::TOKIO;
+use feldera_types::config::FormatConfig;
+use feldera_types::transport::object_store::ObjectStoreOutputConfig;
+use log::{debug, error, info};
+use object_store::{parse_url_opts, path::Path, ObjectStore, PutPayload};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// An object that the connector is currently appending to.
+struct OpenObject {
+    path: Path,
+    data: Vec<u8>,
+    num_records: u64,
+    created: Instant,
+}
+
+pub(crate) struct ObjectStoreOutputEndpoint {
+    endpoint_name: String,
+    config: ObjectStoreOutputConfig,
+    store: Arc<dyn ObjectStore>,
+    prefix: Path,
+
+    /// File name suffix derived from the data format, e.g., `.csv`.
+    suffix: &'static str,
+
+    /// Each buffer produced by the encoder is a complete file (e.g., Parquet
+    /// or an Avro object container file) that cannot be concatenated with
+    /// other buffers.
+    buffer_per_object: bool,
+
+    /// Unique id of this run of the connector, used to avoid overwriting
+    /// objects written by previous runs in non-fault-tolerant mode.
+    run_id: Option<String>,
+
+    fault_tolerant: bool,
+
+    /// Current step.
+    step: Step,
+
+    /// Number of objects started in the current step.
+    part: u64,
+
+    open: Option<OpenObject>,
+
+    /// Completed objects that haven't been uploaded yet, in order.
+    pending: VecDeque<(Path, Vec<u8>)>,
+}
+
+impl ObjectStoreOutputEndpoint {
+    pub(crate) fn new(
+        config: ObjectStoreOutputConfig,
+        format: Option<&FormatConfig>,
+        endpoint_name: &str,
+        fault_tolerant: bool,
+    ) -> AnyResult<Self> {
+        if config.max_object_size_bytes == 0 {
+            bail!("'max_object_size_bytes' must be greater than 0");
+        }
+        if config.max_records_per_object == Some(0) {
+            bail!("'max_records_per_object' must be greater than 0");
+        }
+
+        let url = Url::parse(&config.url)
+            .map_err(|e| anyhow!("invalid object store URL '{}': {e}", config.url))?;
+        let (store, prefix) = parse_url_opts(&url, config.object_store_config.iter())
+            .map_err(|e| anyhow!("error configuring object store for '{}': {e}", config.url))?;
+
+        let format_name = format
+            .map(|format| format.name.as_ref())
+            .unwrap_or_default();
+        let suffix = match format_name {
+            "csv" => ".csv",
+            "json" => ".json",
+            "parquet" => ".parquet",
+            "avro" => ".avro",
+            _ => "",
+        };
+
+        let run_id = (!fault_tolerant).then(|| Utc::now().format("%Y%m%dT%H%M%S%.3fZ").to_string());
+
+        Ok(Self {
+            endpoint_name: endpoint_name.to_string(),
+            config,
+            store: Arc::from(store),
+            prefix,
+            suffix,
+            buffer_per_object: matches!(format_name, "parquet" | "avro"),
+            run_id,
+            fault_tolerant,
+            step: 0,
+            part: 0,
+            open: None,
+            pending: VecDeque::new(),
+        })
+    }
+
+    /// Path of the next object started in the current step.
+    fn next_path(&mut self) -> Path {
+        let name = format!("{:020}-{:05}{}", self.step, self.part, self.suffix);
+        let name = match &self.run_id {
+            None => name,
+            Some(run_id) => format!("{run_id}-{name}"),
+        };
+        self.part += 1;
+        self.prefix.child(name)
+    }
+
+    fn max_age(&self) -> Option<Duration> {
+        self.config.max_object_age_secs.map(Duration::from_secs)
+    }
+
+    /// Returns true if adding `num_bytes` bytes and `num_records` records to
+    /// the open object would exceed the configured limits.
+    fn would_overflow(&self, open: &OpenObject, num_bytes: usize, num_records: u64) -> bool {
+        if open.data.is_empty() {
+            return false;
+        }
+        open.data.len() as u64 + num_bytes as u64 > self.config.max_object_size_bytes
+            || self
+                .config
+                .max_records_per_object
+                .is_some_and(|max| open.num_records + num_records > max)
+    }
+
+    fn is_full(&self, open: &OpenObject) -> bool {
+        open.data.len() as u64 >= self.config.max_object_size_bytes
+            || self
+                .config
+                .max_records_per_object
+                .is_some_and(|max| open.num_records >= max)
+    }
+
+    /// Returns true if the open object has reached the age limit.
+    ///
+    /// Always false in fault-tolerant mode, where the objects written for a
+    /// step must only depend on the data, so that a replayed step overwrites
+    /// exactly the objects written before the failure.
+    fn is_expired(&self, open: &OpenObject) -> bool {
+        !self.fault_tolerant
+            && self
+                .max_age()
+                .is_some_and(|max_age| open.created.elapsed() >= max_age)
+    }
+
+    /// Closes the open object, if any, and queues it for upload.
+    fn close_object(&mut self) {
+        if let Some(open) = self.open.take() {
+            if !open.data.is_empty() {
+                self.pending.push_back((open.path, open.data));
+            }
+        }
+    }
+
+    /// Uploads completed objects in order.
+    ///
+    /// Objects that fail to upload stay in the queue and are retried on the
+    /// next call.
+    fn upload_pending(&mut self) -> AnyResult<()> {
+        while let Some((path, data)) = self.pending.front() {
+            let size = data.len();
+            TOKIO
+                .block_on(self.store.put(path, PutPayload::from(data.clone())))
+                .map_err(|e| anyhow!("error uploading object '{path}': {e}"))?;
+            debug!(
+                "object_store_output {}: uploaded object '{path}' ({size} bytes)",
+                self.endpoint_name
+            );
+            self.pending.pop_front();
+        }
+        Ok(())
+    }
+
+    /// In non-fault-tolerant mode, objects are uploaded as soon as they are
+    /// complete; in fault-tolerant mode, they wait for `batch_end`.
+    fn maybe_upload_pending(&mut self) -> AnyResult<()> {
+        if self.fault_tolerant {
+            Ok(())
+        } else {
+            self.upload_pending()
+        }
+    }
+}
+
+impl OutputEndpoint for ObjectStoreOutputEndpoint {
+    fn connect(&mut self, _async_error_callback: AsyncErrorCallback) -> AnyResult<()> {
+        info!(
+            "object_store_output {}: writing objects to '{}'",
+            self.endpoint_name, self.config.url
+        );
+        Ok(())
+    }
+
+    fn max_buffer_size_bytes(&self) -> usize {
+        self.config
+            .max_object_size_bytes
+            .try_into()
+            .unwrap_or(usize::MAX)
+    }
+
+    fn batch_start(&mut self, step: Step) -> AnyResult<()> {
+        if self.step != step {
+            self.step = step;
+            self.part = 0;
+        }
+        Ok(())
+    }
+
+    fn push_buffer(&mut self, buffer: &[u8]) -> AnyResult<()> {
+        self.push_buffer_with_records(buffer, 0)
+    }
+
+    fn push_buffer_with_records(&mut self, buffer: &[u8], num_records: usize) -> AnyResult<()> {
+        if buffer.is_empty() {
+            return Ok(());
+        }
+
+        if self.buffer_per_object {
+            let path = self.next_path();
+            self.pending.push_back((path, buffer.to_vec()));
+            return self.maybe_upload_pending();
+        }
+
+        let num_records = num_records as u64;
+        if let Some(open) = &self.open {
+            if self.would_overflow(open, buffer.len(), num_records) || self.is_expired(open) {
+                self.close_object();
+            }
+        }
+
+        if self.open.is_none() {
+            self.open = Some(OpenObject {
+                path: self.next_path(),
+                data: Vec::new(),
+                num_records: 0,
+                created: Instant::now(),
+            });
+        }
+
+        let open = self.open.as_mut().unwrap();
+        open.data.extend_from_slice(buffer);
+        open.num_records += num_records;
+
+        if self.is_full(self.open.as_ref().unwrap()) {
+            self.close_object();
+        }
+
+        self.maybe_upload_pending()
+    }
+
+    fn push_key(
+        &mut self,
+        _key: Option<&[u8]>,
+        _val: Option<&[u8]>,
+        _headers: &[(&str, Option<&[u8]>)],
+    ) -> AnyResult<()> {
+        bail!(
+            "Object store output transport does not support key-value pairs. \
+This output endpoint was configured with a data format that produces outputs as key-value pairs; \
+however the object store transport does not support this representation."
+        );
+    }
+
+    fn batch_end(&mut self) -> AnyResult<()> {
+        // In fault-tolerant mode, objects must not span steps, so that a replayed
+        // step produces exactly the same objects.
+        if self.fault_tolerant || self.open.as_ref().is_some_and(|open| self.is_expired(open)) {
+            self.close_object();
+        }
+
+        self.upload_pending()
+    }
+
+    fn is_fault_tolerant(&self) -> bool {
+        self.fault_tolerant
+    }
+}
+
+impl Drop for ObjectStoreOutputEndpoint {
+    fn drop(&mut self) {
+        // In fault-tolerant mode, the open object is discarded: it belongs to a
+        // step that hasn't completed and will be replayed after restart.
+        if self.fault_tolerant {
+            return;
+        }
+
+        self.close_object();
+        if let Err(e) = self.upload_pending() {
+            error!(
+                "object_store_output {}: error uploading the last output object: {e}",
+                self.endpoint_name
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ObjectStoreOutputEndpoint;
+    use crate::transport::OutputEndpoint;
+    use feldera_types::config::FormatConfig;
+    use feldera_types::transport::object_store::ObjectStoreOutputConfig;
+    use std::borrow::Cow;
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn config(dir: &Path) -> ObjectStoreOutputConfig {
+        ObjectStoreOutputConfig {
+            url: format!("file://{}/out", dir.display()),
+            max_object_size_bytes: 1024,
+            max_records_per_object: Some(2),
+            max_object_age_secs: None,
+            object_store_config: HashMap::new(),
+        }
+    }
+
+    fn csv_format() -> FormatConfig {
+        FormatConfig {
+            name: Cow::from("csv"),
+            config: Default::default(),
+        }
+    }
+
+    /// Returns the contents of all objects under `dir/out`, keyed by name.
+    fn read_objects(dir: &Path) -> BTreeMap<String, String> {
+        let Ok(entries) = std::fs::read_dir(dir.join("out")) else {
+            return BTreeMap::new();
+        };
+        entries
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (
+                    entry.file_name().into_string().unwrap(),
+                    std::fs::read_to_string(entry.path()).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    fn write_step(endpoint: &mut ObjectStoreOutputEndpoint, step: u64, records: &[&str]) {
+        endpoint.batch_start(step).unwrap();
+        for record in records {
+            endpoint
+                .push_buffer_with_records(record.as_bytes(), 1)
+                .unwrap();
+        }
+        endpoint.batch_end().unwrap();
+    }
+
+    #[test]
+    fn test_object_store_output_ft() {
+        let dir = TempDir::new().unwrap();
+        let format = csv_format();
+
+        let mut endpoint =
+            ObjectStoreOutputEndpoint::new(config(dir.path()), Some(&format), "test", true)
+                .unwrap();
+
+        // Nothing is visible before the end of the step.
+        endpoint.batch_start(0).unwrap();
+        endpoint.push_buffer_with_records(b"1\n", 1).unwrap();
+        endpoint.push_buffer_with_records(b"2\n", 1).unwrap();
+        endpoint.push_buffer_with_records(b"3\n", 1).unwrap();
+        assert!(read_objects(dir.path()).is_empty());
+        endpoint.batch_end().unwrap();
+
+        write_step(&mut endpoint, 1, &["4\n"]);
+
+        let expected = BTreeMap::from([
+            (
+                "00000000000000000000-00000.csv".to_string(),
+                "1\n2\n".to_string(),
+            ),
+            (
+                "00000000000000000000-00001.csv".to_string(),
+                "3\n".to_string(),
+            ),
+            (
+                "00000000000000000001-00000.csv".to_string(),
+                "4\n".to_string(),
+            ),
+        ]);
+        assert_eq!(read_objects(dir.path()), expected);
+
+        // Start step 2, then "crash" before finishing it.
+        endpoint.batch_start(2).unwrap();
+        endpoint.push_buffer_with_records(b"5\n", 1).unwrap();
+        drop(endpoint);
+        assert_eq!(read_objects(dir.path()), expected);
+
+        // Replay step 1 and finish step 2.  Replayed objects overwrite existing ones.
+        let mut endpoint =
+            ObjectStoreOutputEndpoint::new(config(dir.path()), Some(&format), "test", true)
+                .unwrap();
+        write_step(&mut endpoint, 1, &["4\n"]);
+        write_step(&mut endpoint, 2, &["5\n", "6\n", "7\n"]);
+
+        let mut expected = expected;
+        expected.insert(
+            "00000000000000000002-00000.csv".to_string(),
+            "5\n6\n".to_string(),
+        );
+        expected.insert(
+            "00000000000000000002-00001.csv".to_string(),
+            "7\n".to_string(),
+        );
+        assert_eq!(read_objects(dir.path()), expected);
+    }
+
+    /// Age-based splitting would make the objects of a replayed step depend
+    /// on timing, so it's disabled in fault-tolerant mode.
+    #[test]
+    fn test_object_store_output_ft_ignores_age() {
+        let dir = TempDir::new().unwrap();
+        let format = csv_format();
+        let mut config = config(dir.path());
+        config.max_object_age_secs = Some(0);
+
+        let mut endpoint =
+            ObjectStoreOutputEndpoint::new(config, Some(&format), "test", true).unwrap();
+        write_step(&mut endpoint, 0, &["1\n", "2\n", "3\n"]);
+
+        let expected = BTreeMap::from([
+            (
+                "00000000000000000000-00000.csv".to_string(),
+                "1\n2\n".to_string(),
+            ),
+            (
+                "00000000000000000000-00001.csv".to_string(),
+                "3\n".to_string(),
+            ),
+        ]);
+        assert_eq!(read_objects(dir.path()), expected);
+    }
+
+    #[test]
+    fn test_object_store_output_rolling() {
+        let dir = TempDir::new().unwrap();
+        let format = csv_format();
+        let mut config = config(dir.path());
+        config.max_object_size_bytes = 6;
+        config.max_records_per_object = None;
+
+        let mut endpoint =
+            ObjectStoreOutputEndpoint::new(config, Some(&format), "test", false).unwrap();
+
+        // Objects span steps in non-fault-tolerant mode, and are rolled when
+        // they reach the size limit.
+        write_step(&mut endpoint, 0, &["aa\n", "bb\n", "cc\n"]);
+        write_step(&mut endpoint, 1, &["dd\n", "eeeeeeeee\n", "ff\n"]);
+
+        // The open object is uploaded on shutdown.
+        drop(endpoint);
+
+        let objects = read_objects(dir.path());
+        let contents = objects.values().cloned().collect::<Vec<_>>();
+        assert_eq!(
+            contents,
+            vec!["aa\nbb\n", "cc\ndd\n", "eeeeeeeee\n", "ff\n"]
+        );
+        for name in objects.keys() {
+            assert!(name.ends_with(".csv"));
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use super::{pubsub_config, ConnectionOptions};
 use crate::{
     transport::{InputQueue, InputReaderCommand, NonFtInputReaderCommand},
     InputConsumer, InputEndpoint, InputReader, Parser, PipelineState, TransportInputEndpoint,
@@ -9,16 +10,11 @@ use feldera_types::{
     config::FtModel, program_schema::Relation, transport::pubsub::PubSubInputConfig,
 };
 use futures::StreamExt;
-use google_cloud_gax::conn::Environment;
 use google_cloud_pubsub::{
-    client::{google_cloud_auth::credentials::CredentialsFile, Client, ClientConfig},
+    client::Client,
     subscription::{SeekTo, Subscription},
 };
-use std::{
-    sync::Arc,
-    thread,
-    time::{Duration, SystemTime},
-};
+use std::{sync::Arc, thread, time::SystemTime};
 use tokio::{
     sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
     task::JoinHandle,
@@ -92,7 +88,7 @@ impl PubSubReader {
     }
 
     async fn subscribe(config: &PubSubInputConfig) -> Result<Subscription, AnyError> {
-        let client_config = pubsub_config(config).await?;
+        let client_config = pubsub_config(&ConnectionOptions::from(config)).await?;
 
         let client = Client::new(client_config)
             .await
@@ -193,55 +189,3 @@ impl InputReader for PubSubReader {
         self.state_sender.is_closed()
     }
 }
-
-/// Create `ClientConfig` from connector config.  Performs authentication when
-/// `config.credentials` is specified.
-async fn pubsub_config(config: &PubSubInputConfig) -> Result<ClientConfig, AnyError> {
-    let mut client_config = ClientConfig::default();
-
-    if let Some(project_id) = &config.project_id {
-        client_config.project_id = Some(project_id.to_string());
-    }
-
-    if let Some(pool_size) = config.pool_size {
-        client_config.pool_size = Some(pool_size as usize);
-    }
-
-    if let Some(endpoint) = &config.endpoint {
-        client_config.endpoint = endpoint.to_string();
-    }
-
-    if let Some(connect_timeout_seconds) = config.connect_timeout_seconds {
-        client_config.connection_option.connect_timeout =
-            Some(Duration::from_secs(connect_timeout_seconds as u64));
-    }
-
-    if let Some(timeout_seconds) = config.timeout_seconds {
-        client_config.connection_option.timeout = Some(Duration::from_secs(timeout_seconds as u64));
-    }
-
-    // Use credentials file if specified.
-    // Otherwise, use application default credentials, unless emulator is configured.
-    if let Some(credentials) = &config.credentials {
-        let credentials_file = serde_json::from_str::<CredentialsFile>(credentials)
-            .map_err(|e| anyhow!("error parsing credentials: {e}"))?;
-
-        debug!("Pub/Sub input endpoint: authenticating using provided credentials");
-        client_config = client_config
-            .with_credentials(credentials_file)
-            .await
-            .map_err(|e| anyhow!("authentication error: {e}"))?;
-    }
-    if let Some(emulator) = &config.emulator {
-        client_config.environment = Environment::Emulator(emulator.clone());
-    } else {
-        debug!("Pub/Sub input endpoint: authenticating using Application Default Credentials");
-
-        client_config = client_config
-            .with_auth()
-            .await
-            .map_err(|e| anyhow!("authentication error: {e}"))?;
-    }
-
-    Ok(client_config)
-}
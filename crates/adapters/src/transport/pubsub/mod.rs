@@ -1,9 +1,110 @@
 //! Transport adapter for Google PubSub
 
 mod input;
+mod output;
 
 #[cfg(any(feature = "pubsub-emulator-test", feature = "pubsub-gcp-test"))]
 #[cfg(test)]
 mod test;
 
+use anyhow::{anyhow, Error as AnyError};
+use feldera_types::transport::pubsub::{PubSubInputConfig, PubSubOutputConfig};
+use google_cloud_gax::conn::Environment;
+use google_cloud_pubsub::client::{google_cloud_auth::credentials::CredentialsFile, ClientConfig};
+use std::time::Duration;
+use tracing::debug;
+
 pub use input::PubSubInputEndpoint;
+pub use output::PubSubOutputEndpoint;
+
+/// Options for connecting to the Pub/Sub service, shared by the input and
+/// output connector configurations.
+struct ConnectionOptions<'a> {
+    emulator: &'a Option<String>,
+    credentials: &'a Option<String>,
+    endpoint: &'a Option<String>,
+    pool_size: Option<u32>,
+    timeout_seconds: Option<u32>,
+    connect_timeout_seconds: Option<u32>,
+    project_id: &'a Option<String>,
+}
+
+impl<'a> From<&'a PubSubInputConfig> for ConnectionOptions<'a> {
+    fn from(config: &'a PubSubInputConfig) -> Self {
+        Self {
+            emulator: &config.emulator,
+            credentials: &config.credentials,
+            endpoint: &config.endpoint,
+            pool_size: config.pool_size,
+            timeout_seconds: config.timeout_seconds,
+            connect_timeout_seconds: config.connect_timeout_seconds,
+            project_id: &config.project_id,
+        }
+    }
+}
+
+impl<'a> From<&'a PubSubOutputConfig> for ConnectionOptions<'a> {
+    fn from(config: &'a PubSubOutputConfig) -> Self {
+        Self {
+            emulator: &config.emulator,
+            credentials: &config.credentials,
+            endpoint: &config.endpoint,
+            pool_size: config.pool_size,
+            timeout_seconds: config.timeout_seconds,
+            connect_timeout_seconds: config.connect_timeout_seconds,
+            project_id: &config.project_id,
+        }
+    }
+}
+
+/// Create `ClientConfig` from connector config.  Performs authentication when
+/// `config.credentials` is specified.
+async fn pubsub_config(config: &ConnectionOptions<'_>) -> Result<ClientConfig, AnyError> {
+    let mut client_config = ClientConfig::default();
+
+    if let Some(project_id) = config.project_id {
+        client_config.project_id = Some(project_id.to_string());
+    }
+
+    if let Some(pool_size) = config.pool_size {
+        client_config.pool_size = Some(pool_size as usize);
+    }
+
+    if let Some(endpoint) = config.endpoint {
+        client_config.endpoint = endpoint.to_string();
+    }
+
+    if let Some(connect_timeout_seconds) = config.connect_timeout_seconds {
+        client_config.connection_option.connect_timeout =
+            Some(Duration::from_secs(connect_timeout_seconds as u64));
+    }
+
+    if let Some(timeout_seconds) = config.timeout_seconds {
+        client_config.connection_option.timeout = Some(Duration::from_secs(timeout_seconds as u64));
+    }
+
+    // Use credentials file if specified.
+    // Otherwise, use application default credentials, unless emulator is configured.
+    if let Some(credentials) = config.credentials {
+        let credentials_file = serde_json::from_str::<CredentialsFile>(credentials)
+            .map_err(|e| anyhow!("error parsing credentials: {e}"))?;
+
+        debug!("Pub/Sub endpoint: authenticating using provided credentials");
+        client_config = client_config
+            .with_credentials(credentials_file)
+            .await
+            .map_err(|e| anyhow!("authentication error: {e}"))?;
+    }
+    if let Some(emulator) = config.emulator {
+        client_config.environment = Environment::Emulator(emulator.clone());
+    } else {
+        debug!("Pub/Sub endpoint: authenticating using Application Default Credentials");
+
+        client_config = client_config
+            .with_auth()
+            .await
+            .map_err(|e| anyhow!("authentication error: {e}"))?;
+    }
+
+    Ok(client_config)
+}
@@ -0,0 +1,159 @@
+use super::{pubsub_config, ConnectionOptions};
+use crate::transport::{AsyncErrorCallback, OutputEndpoint};
+use anyhow::{anyhow, bail, Error as AnyError, Result as AnyResult};
+use dbsp::circuit::tokio::TOKIO;
+use feldera_types::transport::pubsub::PubSubOutputConfig;
+use google_cloud_googleapis::pubsub::v1::PubsubMessage;
+use google_cloud_pubsub::{
+    client::Client,
+    publisher::{Awaiter, Publisher, PublisherConfig},
+};
+use std::{mem::take, time::Duration};
+use tracing::{debug, info_span, Instrument};
+
+/// Maximum size of a Pub/Sub message.
+const MAX_MESSAGE_SIZE: usize = 10_000_000;
+
+/// Publishes output to a Google Pub/Sub topic.
+///
+/// Each buffer produced by the encoder is published as a separate message.
+/// Messages are batched by the Pub/Sub client; the connector waits for all
+/// messages published during a step to be acknowledged by the service at the
+/// end of the step.
+pub struct PubSubOutputEndpoint {
+    config: PubSubOutputConfig,
+    publisher: Option<Publisher>,
+
+    /// Messages published in the current step that haven't been acknowledged
+    /// yet.
+    pending: Vec<Awaiter>,
+}
+
+impl PubSubOutputEndpoint {
+    pub fn new(config: PubSubOutputConfig) -> AnyResult<Self> {
+        if config.batch_size == 0 {
+            bail!("'batch_size' must be greater than 0");
+        }
+        if config.workers == 0 {
+            bail!("'workers' must be greater than 0");
+        }
+        Ok(Self {
+            config,
+            publisher: None,
+            pending: Vec::new(),
+        })
+    }
+
+    async fn create_publisher(config: &PubSubOutputConfig) -> Result<Publisher, AnyError> {
+        let client_config = pubsub_config(&ConnectionOptions::from(config)).await?;
+
+        let client = Client::new(client_config)
+            .await
+            .map_err(|e| anyhow!("error connecting to the Pub/Sub service: {e}"))?;
+
+        debug!(
+            "Pub/Sub output endpoint: connecting to topic {}",
+            &config.topic
+        );
+
+        let topic = client.topic(&config.topic);
+        if !topic.exists(None).await? {
+            bail!("topic '{}' does not exist", config.topic);
+        }
+
+        Ok(topic.new_publisher(Some(PublisherConfig {
+            workers: config.workers,
+            flush_interval: Duration::from_millis(config.batch_delay_ms),
+            bundle_size: config.batch_size,
+            ..Default::default()
+        })))
+    }
+
+    fn publish(&mut self, message: PubsubMessage) -> AnyResult<()> {
+        let Some(publisher) = &self.publisher else {
+            bail!("Pub/Sub output endpoint is not connected");
+        };
+        self.pending
+            .push(TOKIO.block_on(publisher.publish(message)));
+        Ok(())
+    }
+}
+
+impl OutputEndpoint for PubSubOutputEndpoint {
+    fn connect(&mut self, _async_error_callback: AsyncErrorCallback) -> AnyResult<()> {
+        let span = info_span!("pub_sub_output", topic = self.config.topic.clone());
+        let publisher = TOKIO.block_on(Self::create_publisher(&self.config).instrument(span))?;
+        self.publisher = Some(publisher);
+        Ok(())
+    }
+
+    fn max_buffer_size_bytes(&self) -> usize {
+        MAX_MESSAGE_SIZE
+    }
+
+    fn push_buffer(&mut self, buffer: &[u8]) -> AnyResult<()> {
+        self.publish(PubsubMessage {
+            data: buffer.to_vec(),
+            ..Default::default()
+        })
+    }
+
+    fn push_key(
+        &mut self,
+        key: Option<&[u8]>,
+        val: Option<&[u8]>,
+        headers: &[(&str, Option<&[u8]>)],
+    ) -> AnyResult<()> {
+        // Only use the key as an ordering key if it was computed from
+        // `ordering_key_fields`; keys produced by other encoders, e.g., Avro,
+        // are not valid ordering keys.
+        let ordering_key = match (&self.config.ordering_key_fields, key) {
+            (Some(_), Some(key)) => String::from_utf8_lossy(key).into_owned(),
+            _ => String::new(),
+        };
+        let attributes = headers
+            .iter()
+            .filter_map(|(name, value)| {
+                value.map(|value| {
+                    (
+                        name.to_string(),
+                        String::from_utf8_lossy(value).into_owned(),
+                    )
+                })
+            })
+            .collect();
+        self.publish(PubsubMessage {
+            data: val.unwrap_or_default().to_vec(),
+            attributes,
+            ordering_key,
+            ..Default::default()
+        })
+    }
+
+    fn batch_end(&mut self) -> AnyResult<()> {
+        let pending = take(&mut self.pending);
+        TOKIO.block_on(async {
+            for awaiter in pending {
+                awaiter.get().await.map_err(|e| {
+                    anyhow!(
+                        "error publishing message to Pub/Sub topic '{}': {e}",
+                        self.config.topic
+                    )
+                })?;
+            }
+            Ok(())
+        })
+    }
+
+    fn is_fault_tolerant(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for PubSubOutputEndpoint {
+    fn drop(&mut self) {
+        if let Some(mut publisher) = self.publisher.take() {
+            TOKIO.block_on(publisher.shutdown());
+        }
+    }
+}
@@ -433,6 +433,80 @@ fn test_pubsub_input_errors() {
     };
 }
 
+// Running this test requires starting a Pub/Sub emulator, see
+// `proptest_pubsub_input_with_emulator`.
+#[cfg(feature = "pubsub-emulator-test")]
+#[test]
+fn test_pubsub_output() {
+    use super::PubSubOutputEndpoint;
+    use crate::transport::OutputEndpoint;
+    use feldera_types::transport::pubsub::PubSubOutputConfig;
+
+    init_test_logger();
+
+    let emulator =
+        std::env::var("PUBSUB_EMULATOR_HOST").unwrap_or_else(|_| "127.0.0.1:8685".to_string());
+    if !probe_port(&emulator) {
+        panic!("Pub/Sub emulator not found on port {emulator}', start the emulator before running the tests: 'gcloud beta emulators pubsub start --project=feldera-test --host-port={emulator}'")
+    }
+
+    let topic = "test_pubsub_output";
+    let publisher = TestPublisher::with_emulator(topic, &emulator);
+    let subscription = publisher.create_subscription(topic, "");
+
+    let config = PubSubOutputConfig {
+        emulator: Some(emulator.clone()),
+        credentials: None,
+        endpoint: None,
+        pool_size: None,
+        timeout_seconds: None,
+        connect_timeout_seconds: None,
+        project_id: Some(EMULATOR_PROJECT_ID.to_string()),
+        topic: topic.to_string(),
+        ordering_key_fields: Some(vec!["id".to_string()]),
+        attribute_fields: vec!["b".to_string()],
+        batch_size: 10,
+        batch_delay_ms: 10,
+        workers: 1,
+    };
+    let mut endpoint = PubSubOutputEndpoint::new(config).unwrap();
+    endpoint.connect(Box::new(|_, _| ())).unwrap();
+
+    endpoint.batch_start(0).unwrap();
+    endpoint
+        .push_key(
+            Some(b"1"),
+            Some(b"first"),
+            &[("b", Some(b"true")), ("i", None)],
+        )
+        .unwrap();
+    endpoint.push_buffer(b"second").unwrap();
+    endpoint.batch_end().unwrap();
+
+    let mut messages = TOKIO.block_on(async {
+        let subscription = publisher.client.subscription(&subscription);
+        let mut messages = Vec::new();
+        while messages.len() < 2 {
+            for message in subscription.pull(10, None).await.unwrap() {
+                message.ack().await.unwrap();
+                messages.push(message.message);
+            }
+        }
+        messages
+    });
+    messages.sort_by(|m1, m2| m1.data.cmp(&m2.data));
+
+    assert_eq!(messages[0].data, b"first");
+    assert_eq!(messages[0].ordering_key, "1");
+    assert_eq!(
+        messages[0].attributes,
+        HashMap::from([("b".to_string(), "true".to_string())])
+    );
+    assert_eq!(messages[1].data, b"second");
+    assert_eq!(messages[1].ordering_key, "");
+    assert!(messages[1].attributes.is_empty());
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(2))]
 
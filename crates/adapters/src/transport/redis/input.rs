@@ -0,0 +1,528 @@
+//! Input transport that reads entries from a Redis stream using a consumer
+//! group.
+//!
+//! The connector records the range of entry ids that make up each step as the
+//! step's metadata.  Entries are only acknowledged (`XACK`) after a checkpoint
+//! that includes them has been written, so that entries that were delivered to
+//! the connector, but not yet checkpointed, are delivered again after a
+//! restart.  To replay a step after a failure, the connector re-reads the
+//! range from its own pending entries list, which excludes entries in the
+//! range that the consumer group delivered to other consumers.
+
+use crate::transport::{
+    InputConsumer, InputEndpoint, InputReader, InputReaderCommand, TransportInputEndpoint,
+};
+use crate::{InputBuffer, ParseError, Parser};
+use anyhow::{anyhow, bail, Error as AnyError, Result as AnyResult};
+use feldera_adapterlib::transport::Resume;
+use feldera_types::config::FtModel;
+use feldera_types::program_schema::{Relation, SqlType};
+use feldera_types::transport::redis::RedisInputConfig;
+use redis::streams::{StreamId, StreamReadOptions, StreamReadReply};
+use redis::{Commands, Connection, ConnectionInfo, Value as RedisValue};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::hash::Hasher;
+use std::str::FromStr;
+use std::thread::{self, spawn, Thread};
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tracing::{error, info_span};
+use xxhash_rust::xxh3::Xxh3Default;
+
+/// Id of a Redis stream entry, e.g., `1526919030474-55`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+struct EntryId {
+    ms: u64,
+    seq: u64,
+}
+
+impl EntryId {
+    /// Returns the greatest id that is less than this one.
+    fn predecessor(self) -> Self {
+        match self.seq {
+            0 => Self {
+                ms: self.ms.saturating_sub(1),
+                seq: u64::MAX,
+            },
+            seq => Self {
+                ms: self.ms,
+                seq: seq - 1,
+            },
+        }
+    }
+}
+
+impl Display for EntryId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+impl FromStr for EntryId {
+    type Err = AnyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (ms, seq) = s.split_once('-').unwrap_or((s, "0"));
+        match (ms.parse(), seq.parse()) {
+            (Ok(ms), Ok(seq)) => Ok(Self { ms, seq }),
+            _ => Err(anyhow!("invalid Redis stream entry id '{s}'")),
+        }
+    }
+}
+
+impl TryFrom<String> for EntryId {
+    type Error = AnyError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<EntryId> for String {
+    fn from(value: EntryId) -> Self {
+        value.to_string()
+    }
+}
+
+/// Metadata of a step: the ids of the first and last entries in the step.
+///
+/// `first` is `None` if the step contains no entries, in which case `last` is
+/// the id of the last entry read before the step, if any.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Metadata {
+    first: Option<EntryId>,
+    last: Option<EntryId>,
+}
+
+pub(crate) struct RedisInputEndpoint {
+    config: RedisInputConfig,
+    connection_info: ConnectionInfo,
+}
+
+impl RedisInputEndpoint {
+    pub(crate) fn new(config: RedisInputConfig) -> AnyResult<Self> {
+        if config.batch_size == 0 {
+            bail!("'batch_size' must be greater than 0");
+        }
+        if config.block_ms == 0 {
+            bail!("'block_ms' must be greater than 0");
+        }
+        let connection_info = ConnectionInfo::from_str(&config.connection_string)
+            .map_err(|e| anyhow!("error parsing Redis connection string: {e}"))?;
+        Ok(Self {
+            config,
+            connection_info,
+        })
+    }
+}
+
+impl InputEndpoint for RedisInputEndpoint {
+    fn fault_tolerance(&self) -> Option<FtModel> {
+        Some(FtModel::ExactlyOnce)
+    }
+}
+
+impl TransportInputEndpoint for RedisInputEndpoint {
+    fn open(
+        &self,
+        consumer: Box<dyn InputConsumer>,
+        parser: Box<dyn Parser>,
+        schema: Relation,
+    ) -> AnyResult<Box<dyn InputReader>> {
+        Ok(Box::new(RedisInputReader::new(
+            self, consumer, parser, schema,
+        )?))
+    }
+}
+
+/// Messages from [RedisInputReader] to its worker thread.
+enum Command {
+    Reader(InputReaderCommand),
+
+    /// A checkpoint that includes all entries up to and including this one has
+    /// been written.
+    Checkpointed(EntryId),
+}
+
+struct RedisInputReader {
+    sender: UnboundedSender<Command>,
+    thread: Thread,
+}
+
+impl RedisInputReader {
+    fn new(
+        endpoint: &RedisInputEndpoint,
+        consumer: Box<dyn InputConsumer>,
+        parser: Box<dyn Parser>,
+        schema: Relation,
+    ) -> AnyResult<Self> {
+        let config = endpoint.config.clone();
+        let client = redis::Client::open(endpoint.connection_info.clone())
+            .map_err(|e| anyhow!("error connecting to the Redis server: {e}"))?;
+        let mut connection = client
+            .get_connection()
+            .map_err(|e| anyhow!("error connecting to the Redis server: {e}"))?;
+
+        match connection.xgroup_create_mkstream::<_, _, _, ()>(
+            &config.stream,
+            &config.consumer_group,
+            &config.start_id,
+        ) {
+            Ok(()) => (),
+            Err(e) if e.code() == Some("BUSYGROUP") => (),
+            Err(e) => bail!(
+                "error creating consumer group '{}' for Redis stream '{}': {e}",
+                config.consumer_group,
+                config.stream
+            ),
+        }
+
+        let (sender, receiver) = unbounded_channel();
+        let join_handle = spawn(move || {
+            let _guard = info_span!("redis_input", stream = config.stream.clone()).entered();
+            let mut worker = RedisInputWorker {
+                config,
+                connection,
+                consumer,
+                parser,
+                schema,
+                queue: VecDeque::new(),
+                position: None,
+                unacked: BTreeSet::new(),
+            };
+            if let Err(error) = worker.run(receiver) {
+                worker.consumer.error(true, error);
+            }
+        });
+
+        Ok(Self {
+            sender,
+            thread: join_handle.thread().clone(),
+        })
+    }
+
+    fn send(&self, command: Command) {
+        let _ = self.sender.send(command);
+        self.thread.unpark();
+    }
+}
+
+impl InputReader for RedisInputReader {
+    fn request(&self, command: InputReaderCommand) {
+        self.send(Command::Reader(command));
+    }
+
+    fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    fn checkpointed(&self, metadata: &JsonValue) {
+        match serde_json::from_value::<Metadata>(metadata.clone()) {
+            Ok(Metadata {
+                last: Some(last), ..
+            }) => self.send(Command::Checkpointed(last)),
+            Ok(_) => (),
+            Err(e) => error!("invalid checkpoint metadata for Redis input: {e}"),
+        }
+    }
+}
+
+impl Drop for RedisInputReader {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+struct RedisInputWorker {
+    config: RedisInputConfig,
+    connection: Connection,
+    consumer: Box<dyn InputConsumer>,
+    parser: Box<dyn Parser>,
+    schema: Relation,
+
+    /// Parsed entries that haven't been flushed to the circuit yet.
+    queue: VecDeque<(EntryId, Box<dyn InputBuffer>)>,
+
+    /// Id of the last entry read from the stream, if any.
+    position: Option<EntryId>,
+
+    /// Ids of entries delivered to this consumer that haven't been
+    /// acknowledged yet.
+    unacked: BTreeSet<EntryId>,
+}
+
+impl RedisInputWorker {
+    fn run(&mut self, mut receiver: UnboundedReceiver<Command>) -> AnyResult<()> {
+        let mut extending = false;
+
+        // Before reading new entries, re-read the entries that were delivered
+        // to this consumer before a restart but never acknowledged.  This is
+        // the id of the last such entry read so far, or `None` once all of them
+        // have been read.
+        let mut pending_cursor = Some(EntryId { ms: 0, seq: 0 });
+
+        loop {
+            loop {
+                match receiver.try_recv() {
+                    Ok(Command::Reader(InputReaderCommand::Extend)) => extending = true,
+                    Ok(Command::Reader(InputReaderCommand::Pause)) => extending = false,
+                    Ok(Command::Reader(InputReaderCommand::Queue { .. })) => self.queue()?,
+                    Ok(Command::Reader(InputReaderCommand::Seek(metadata))) => {
+                        let Metadata { last, .. } = serde_json::from_value(metadata)?;
+                        self.position = last;
+                    }
+                    Ok(Command::Reader(InputReaderCommand::Replay { metadata, .. })) => {
+                        self.replay(serde_json::from_value(metadata)?)?
+                    }
+                    Ok(Command::Reader(InputReaderCommand::Disconnect)) => return Ok(()),
+                    Ok(Command::Checkpointed(last)) => self.ack(last)?,
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return Ok(()),
+                }
+            }
+
+            if !extending {
+                thread::park();
+                continue;
+            }
+
+            match pending_cursor {
+                Some(cursor) => {
+                    let entries = self.read_group(&cursor.to_string(), None)?;
+                    pending_cursor = match entries.last() {
+                        Some(entry) => Some(entry.id.parse()?),
+                        None => None,
+                    };
+                    for entry in entries {
+                        // Entries up to `position` were read before the restart
+                        // and are part of a checkpointed or replayed step.
+                        let id = entry.id.parse()?;
+                        self.unacked.insert(id);
+                        if self.position.is_none_or(|position| id > position) {
+                            self.process(id, &entry);
+                        }
+                    }
+                }
+                None => {
+                    for entry in self.read_group(">", Some(self.config.block_ms))? {
+                        let id = entry.id.parse()?;
+                        self.unacked.insert(id);
+                        self.process(id, &entry);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reads up to `batch_size` entries with ids greater than `id` from the
+    /// consumer group.  `id` is `>` to read entries that were never delivered
+    /// to any consumer.
+    fn read_group(&mut self, id: &str, block_ms: Option<usize>) -> AnyResult<Vec<StreamId>> {
+        let mut options = StreamReadOptions::default()
+            .group(&self.config.consumer_group, &self.config.consumer_name)
+            .count(self.config.batch_size);
+        if let Some(block_ms) = block_ms {
+            options = options.block(block_ms);
+        }
+        let reply: Option<StreamReadReply> = self
+            .connection
+            .xread_options(&[&self.config.stream], &[id], &options)
+            .map_err(|e| {
+                anyhow!(
+                    "error reading from Redis stream '{}': {e}",
+                    self.config.stream
+                )
+            })?;
+        Ok(reply
+            .into_iter()
+            .flat_map(|reply| reply.keys)
+            .flat_map(|key| key.ids)
+            .collect())
+    }
+
+    /// Parses `entry` and queues the result.
+    fn process(&mut self, id: EntryId, entry: &StreamId) {
+        self.position = Some(id);
+        if let Some((buffer, num_bytes)) = self.parse(id, entry) {
+            self.consumer.buffered(buffer.len(), num_bytes);
+            self.queue.push_back((id, buffer));
+        }
+    }
+
+    /// Parses `entry`, returning the parsed records, if any, and the size of
+    /// the parsed data in bytes.
+    fn parse(&mut self, id: EntryId, entry: &StreamId) -> Option<(Box<dyn InputBuffer>, usize)> {
+        // Entries that were deleted after being delivered to this consumer
+        // have no fields.
+        if entry.map.is_empty() {
+            return None;
+        }
+
+        let data = match &self.config.payload_field {
+            Some(field) => match entry.map.get(field) {
+                Some(value) => value_to_bytes(value),
+                None => {
+                    self.consumer
+                        .parse_errors(vec![ParseError::bin_envelope_error(
+                            format!("Redis stream entry {id} has no field '{field}'"),
+                            &[],
+                            None,
+                        )]);
+                    return None;
+                }
+            },
+            None => {
+                let object = entry
+                    .map
+                    .iter()
+                    .map(|(name, value)| {
+                        (
+                            name.clone(),
+                            field_to_json(&self.schema, name, &value_to_bytes(value)),
+                        )
+                    })
+                    .collect::<JsonMap<_, _>>();
+                serde_json::to_vec(&object).unwrap()
+            }
+        };
+
+        let (buffer, errors) = self.parser.parse(&data);
+        self.consumer.parse_errors(errors);
+        buffer.map(|buffer| (buffer, data.len()))
+    }
+
+    /// Flushes up to `max_batch_size` queued records and reports the step.
+    fn queue(&mut self) -> AnyResult<()> {
+        let mut total = 0;
+        let mut hasher = self.consumer.hasher();
+        let limit = self.consumer.max_batch_size();
+        let mut range: Option<(EntryId, EntryId)> = None;
+        while let Some((id, mut buffer)) = self.queue.pop_front() {
+            range = Some((range.map_or(id, |(first, _)| first), id));
+            total += buffer.len();
+            if let Some(hasher) = hasher.as_mut() {
+                buffer.hash(hasher);
+            }
+            buffer.flush();
+            if total >= limit {
+                break;
+            }
+        }
+
+        // Entries that didn't produce any records are part of the step as long
+        // as no later entries are still queued.
+        let last = match self.queue.front() {
+            None => self.position,
+            Some(_) => range.map(|(_, last)| last),
+        };
+        let metadata = Metadata {
+            first: range.map(|(first, _)| first),
+            last,
+        };
+
+        if self.consumer.pipeline_fault_tolerance().is_none() {
+            if let Some(last) = last {
+                self.ack(last)?;
+            }
+        }
+
+        let seek = serde_json::to_value(metadata).unwrap();
+        self.consumer
+            .extended(total, Some(Resume::new_metadata_only(seek, hasher)));
+        Ok(())
+    }
+
+    fn replay(&mut self, metadata: Metadata) -> AnyResult<()> {
+        let mut num_records = 0;
+        let mut hasher = Xxh3Default::new();
+        if let (Some(first), Some(last)) = (metadata.first, metadata.last) {
+            // The entries of the step haven't been acknowledged, so they are
+            // still in this consumer's pending entries list.
+            let mut cursor = first.predecessor();
+            'read: loop {
+                let entries = self.read_group(&cursor.to_string(), None)?;
+                if entries.is_empty() {
+                    break;
+                }
+                for entry in entries {
+                    let id = entry.id.parse()?;
+                    if id > last {
+                        break 'read;
+                    }
+                    cursor = id;
+                    self.unacked.insert(id);
+                    if let Some((mut buffer, num_bytes)) = self.parse(id, &entry) {
+                        self.consumer.buffered(buffer.len(), num_bytes);
+                        num_records += buffer.len();
+                        buffer.hash(&mut hasher);
+                        buffer.flush();
+                    }
+                }
+            }
+        }
+        self.position = metadata.last;
+        self.consumer.replayed(num_records, hasher.finish());
+        Ok(())
+    }
+
+    /// Acknowledges all entries up to and including `last`.
+    fn ack(&mut self, last: EntryId) -> AnyResult<()> {
+        let acked = self.unacked.range(..=last).copied().collect::<Vec<_>>();
+        if acked.is_empty() {
+            return Ok(());
+        }
+        let ids = acked.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        self.connection
+            .xack::<_, _, _, ()>(&self.config.stream, &self.config.consumer_group, &ids)
+            .map_err(|e| {
+                anyhow!(
+                    "error acknowledging entries in Redis stream '{}': {e}",
+                    self.config.stream
+                )
+            })?;
+        for id in &acked {
+            self.unacked.remove(id);
+        }
+        Ok(())
+    }
+}
+
+fn value_to_bytes(value: &RedisValue) -> Vec<u8> {
+    redis::from_redis_value::<Vec<u8>>(value).unwrap_or_default()
+}
+
+/// Converts the value of field `name` of a stream entry to JSON, based on the
+/// type of the column with the same name.
+///
+/// Redis stores all values as strings.  Values of boolean, numeric, and
+/// compound columns are parsed as JSON; all other values, and values that
+/// aren't valid JSON, are passed to the parser as strings.
+fn field_to_json(schema: &Relation, name: &str, value: &[u8]) -> JsonValue {
+    let text = String::from_utf8_lossy(value);
+    let parse_json = schema.field(name).is_some_and(|field| {
+        matches!(
+            field.columntype.typ,
+            SqlType::Boolean
+                | SqlType::TinyInt
+                | SqlType::SmallInt
+                | SqlType::Int
+                | SqlType::BigInt
+                | SqlType::Real
+                | SqlType::Double
+                | SqlType::Array
+                | SqlType::Map
+                | SqlType::Struct
+                | SqlType::Variant
+        )
+    });
+    if parse_json {
+        if let Ok(value) = serde_json::from_str(&text) {
+            return value;
+        }
+    }
+    JsonValue::String(text.into_owned())
+}
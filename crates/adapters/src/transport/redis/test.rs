@@ -1,10 +1,13 @@
 use feldera_sqllib::{ByteArray, Date, SqlDecimal, SqlString, Timestamp, Uuid, Variant, F32, F64};
 use feldera_types::{
-    config::PipelineConfig,
+    config::{InputEndpointConfig, PipelineConfig},
     format::json::JsonFlavor,
     serde_with_context::{SerializeWithContext, SqlSerdeConfig},
 };
-use redis::Commands;
+use redis::{
+    streams::{StreamPendingReply, StreamReadOptions, StreamReadReply},
+    Commands,
+};
 use serde_json::json;
 use std::{
     collections::{BTreeMap, HashSet},
@@ -15,7 +18,10 @@ use std::{
 use tempfile::NamedTempFile;
 
 use crate::{
-    test::{data::TestStruct, test_circuit, wait, DeltaTestStruct},
+    test::{
+        data::TestStruct, mock_input_pipeline, test_circuit, wait, DeltaTestStruct,
+        DEFAULT_TIMEOUT_MS,
+    },
     Controller,
 };
 
@@ -313,3 +319,324 @@ outputs:
 
     assert!(err.to_string().contains("not yet supported"));
 }
+
+/// Connects to Redis and deletes `stream`, so that tests start from scratch.
+fn redis_connection(stream: &str) -> redis::Connection {
+    let mut connection = redis::Client::open(redis_url())
+        .unwrap()
+        .get_connection()
+        .unwrap();
+    let _: () = connection.del(stream).unwrap();
+    connection
+}
+
+fn redis_input_config(stream: &str, payload_field: Option<&str>) -> InputEndpointConfig {
+    let payload_field = payload_field
+        .map(|field| format!("payload_field: {field}"))
+        .unwrap_or_default();
+    let config_str = format!(
+        r#"
+stream: test_input
+transport:
+    name: redis_input
+    config:
+        connection_string: {}
+        stream: {stream}
+        consumer_group: test_group
+        {payload_field}
+format:
+    name: json
+    config:
+        update_format: raw
+"#,
+        redis_url()
+    );
+    serde_yaml::from_str(&config_str).unwrap()
+}
+
+fn num_pending(connection: &mut redis::Connection, stream: &str) -> usize {
+    let reply: StreamPendingReply = connection.xpending(stream, "test_group").unwrap();
+    reply.count()
+}
+
+#[test]
+fn test_redis_input_fields() {
+    let stream = "test_redis_input_fields";
+    let mut connection = redis_connection(stream);
+
+    let (endpoint, _consumer, _parser, zset) = mock_input_pipeline::<TestStruct, TestStruct>(
+        redis_input_config(stream, None),
+        TestStruct::relation_schema(),
+    )
+    .unwrap();
+
+    let test_data = [
+        TestStruct {
+            id: 1,
+            b: true,
+            i: None,
+            s: "foo".to_string(),
+        },
+        TestStruct {
+            id: 2,
+            b: false,
+            i: Some(-5),
+            s: "bar".to_string(),
+        },
+        TestStruct {
+            id: 3,
+            b: true,
+            i: Some(10),
+            s: "10".to_string(),
+        },
+    ];
+    let mut ids = Vec::new();
+    for val in &test_data {
+        let mut fields = vec![
+            ("id", val.id.to_string()),
+            ("b", val.b.to_string()),
+            ("s", val.s.clone()),
+        ];
+        if let Some(i) = val.i {
+            fields.push(("i", i.to_string()));
+        }
+        let id: String = connection.xadd(stream, "*", &fields).unwrap();
+        ids.push(id);
+    }
+
+    endpoint.extend();
+    wait(
+        || {
+            endpoint.queue(false);
+            zset.state().flushed.len() == test_data.len()
+        },
+        DEFAULT_TIMEOUT_MS,
+    )
+    .unwrap();
+    for (i, upd) in zset.state().flushed.iter().enumerate() {
+        assert_eq!(upd.unwrap_insert(), &test_data[i]);
+    }
+
+    // Entries are only acknowledged once they are checkpointed.
+    assert_eq!(num_pending(&mut connection, stream), test_data.len());
+    endpoint.checkpointed(&json!({"first": ids[0], "last": ids[2]}));
+    wait(
+        || num_pending(&mut connection, stream) == 0,
+        DEFAULT_TIMEOUT_MS,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_redis_input_payload() {
+    let stream = "test_redis_input_payload";
+    let mut connection = redis_connection(stream);
+
+    let test_data = [
+        TestStruct {
+            id: 1,
+            b: true,
+            i: None,
+            s: "foo".to_string(),
+        },
+        TestStruct {
+            id: 2,
+            b: false,
+            i: Some(-5),
+            s: "bar".to_string(),
+        },
+        TestStruct {
+            id: 3,
+            b: true,
+            i: Some(10),
+            s: "baz".to_string(),
+        },
+    ];
+    let add = |connection: &mut redis::Connection, val: &TestStruct| -> String {
+        let payload = serde_json::to_string(val).unwrap();
+        connection
+            .xadd(
+                stream,
+                "*",
+                &[("data", payload), ("other", "x".to_string())],
+            )
+            .unwrap()
+    };
+    let id0 = add(&mut connection, &test_data[0]);
+    add(&mut connection, &test_data[1]);
+
+    let (endpoint, _consumer, _parser, zset) = mock_input_pipeline::<TestStruct, TestStruct>(
+        redis_input_config(stream, Some("data")),
+        TestStruct::relation_schema(),
+    )
+    .unwrap();
+    endpoint.extend();
+    wait(
+        || {
+            endpoint.queue(false);
+            zset.state().flushed.len() == 2
+        },
+        DEFAULT_TIMEOUT_MS,
+    )
+    .unwrap();
+
+    // Make sure that the reader has stopped before adding more entries.
+    endpoint.disconnect();
+    wait(|| endpoint.is_closed(), DEFAULT_TIMEOUT_MS).unwrap();
+    drop(endpoint);
+
+    // Restart from a checkpoint that only includes the first entry.  The
+    // second entry was delivered before the restart but never acknowledged,
+    // so it must be read again.
+    let (endpoint, _consumer, _parser, zset) = mock_input_pipeline::<TestStruct, TestStruct>(
+        redis_input_config(stream, Some("data")),
+        TestStruct::relation_schema(),
+    )
+    .unwrap();
+    endpoint.seek(json!({"first": id0, "last": id0}));
+    endpoint.extend();
+    add(&mut connection, &test_data[2]);
+    wait(
+        || {
+            endpoint.queue(false);
+            zset.state().flushed.len() == 2
+        },
+        DEFAULT_TIMEOUT_MS,
+    )
+    .unwrap();
+    for (i, upd) in zset.state().flushed.iter().enumerate() {
+        assert_eq!(upd.unwrap_insert(), &test_data[i + 1]);
+    }
+}
+
+/// Replaying a step only re-ingests the entries that were delivered to this
+/// consumer, even if the consumer group delivered entries in the middle of
+/// the step's range to another consumer.
+#[test]
+fn test_redis_input_replay_shared_group() {
+    let stream = "test_redis_input_replay_shared_group";
+    let mut connection = redis_connection(stream);
+
+    let test_data = (1..=3)
+        .map(|id| TestStruct {
+            id,
+            b: true,
+            i: Some(id as i64),
+            s: "foo".to_string(),
+        })
+        .collect::<Vec<_>>();
+    let add = |connection: &mut redis::Connection, val: &TestStruct| -> String {
+        let payload = serde_json::to_string(val).unwrap();
+        connection.xadd(stream, "*", &[("data", payload)]).unwrap()
+    };
+
+    let (endpoint, _consumer, _parser, zset) = mock_input_pipeline::<TestStruct, TestStruct>(
+        redis_input_config(stream, Some("data")),
+        TestStruct::relation_schema(),
+    )
+    .unwrap();
+
+    // The connector reads the first entry.
+    let first = add(&mut connection, &test_data[0]);
+    endpoint.extend();
+    wait(
+        || {
+            endpoint.queue(false);
+            zset.state().flushed.len() == 1
+        },
+        DEFAULT_TIMEOUT_MS,
+    )
+    .unwrap();
+
+    // Another consumer in the same group reads the second entry while the
+    // connector is paused.
+    endpoint.pause();
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    add(&mut connection, &test_data[1]);
+    let reply: StreamReadReply = connection
+        .xread_options(
+            &[stream],
+            &[">"],
+            &StreamReadOptions::default().group("test_group", "other"),
+        )
+        .unwrap();
+    assert_eq!(reply.keys[0].ids.len(), 1);
+
+    // The connector reads the third entry.
+    let last = add(&mut connection, &test_data[2]);
+    endpoint.extend();
+    wait(
+        || {
+            endpoint.queue(false);
+            zset.state().flushed.len() == 2
+        },
+        DEFAULT_TIMEOUT_MS,
+    )
+    .unwrap();
+    endpoint.disconnect();
+    wait(|| endpoint.is_closed(), DEFAULT_TIMEOUT_MS).unwrap();
+    drop(endpoint);
+
+    // Replay a step that spans all three entries.
+    let (endpoint, _consumer, _parser, zset) = mock_input_pipeline::<TestStruct, TestStruct>(
+        redis_input_config(stream, Some("data")),
+        TestStruct::relation_schema(),
+    )
+    .unwrap();
+    endpoint.replay(json!({"first": first, "last": last}), rmpv::Value::Nil);
+    wait(|| zset.state().flushed.len() >= 2, DEFAULT_TIMEOUT_MS).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    let replayed = zset
+        .state()
+        .flushed
+        .iter()
+        .map(|upd| upd.unwrap_insert().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(replayed, vec![test_data[0].clone(), test_data[2].clone()]);
+}
+
+/// Without a payload field, only the raw JSON format is accepted.
+#[test]
+fn test_redis_input_format_validation() {
+    for format in [
+        "name: csv",
+        "name: json\n            config:\n                update_format: insert_delete",
+    ] {
+        let config_str = format!(
+            r#"
+name: test
+workers: 1
+inputs:
+    test_input1:
+        stream: test_input1
+        transport:
+            name: redis_input
+            config:
+                connection_string: {}
+                stream: test_redis_input_format_validation
+                consumer_group: test_group
+        format:
+            {format}
+"#,
+            redis_url()
+        );
+        let config: PipelineConfig = serde_yaml::from_str(&config_str).unwrap();
+        let Err(err) = Controller::with_config(
+            |circuit_config| {
+                Ok(test_circuit::<TestStruct>(
+                    circuit_config,
+                    &TestStruct::schema(),
+                    &[None],
+                ))
+            },
+            &config,
+            Box::new(|e| panic!("error: {e}")),
+        ) else {
+            panic!("format '{format}' should be rejected");
+        };
+        assert!(
+            err.to_string().contains("requires the 'json' format"),
+            "unexpected error: {err}"
+        );
+    }
+}
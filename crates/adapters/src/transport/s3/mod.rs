@@ -3,6 +3,7 @@ use std::{
     sync::Arc,
 };
 
+use super::compression::Decompressor;
 use super::InputReaderCommand;
 use crate::transport::InputEndpoint;
 use crate::{
@@ -465,6 +466,7 @@ impl S3InputReader {
         let mut join_handles = Vec::new();
 
         let bucket_name = config.bucket_name.clone();
+        let compression = config.compression;
 
         // Spawn reader tasks that dequeue object names from the channel, fetch and parse them.
         for _i in 0..config.max_concurrent_fetches {
@@ -506,11 +508,23 @@ impl S3InputReader {
                                 continue;
                             };
 
+                            // Offsets are positions in the decompressed object.  Compressed
+                            // objects must be fetched from the beginning, discarding data
+                            // up to `start_offset` after decompression.
+                            let mut decompressor =
+                                Decompressor::new(compression, &partially_processed_key.key);
+                            let fetch_offset = if decompressor.is_identity() {
+                                start_offset
+                            } else {
+                                decompressor.restart(start_offset);
+                                0
+                            };
+
                             let result = client
                                 .get_object(
                                     &bucket_name,
                                     &partially_processed_key.key,
-                                    start_offset,
+                                    fetch_offset,
                                     None,
                                 )
                                 .await;
@@ -541,8 +555,32 @@ impl S3InputReader {
                                             &partially_processed_key.key
                                         ),
                                     ),
-                                    Some(Ok(bytes)) => splitter.append(&bytes),
-                                    None => eoi = true,
+                                    Some(Ok(bytes)) => match decompressor.decompress(bytes) {
+                                        Ok(bytes) => splitter.append(&bytes),
+                                        Err(e) => {
+                                            consumer.error(
+                                                false,
+                                                anyhow!(
+                                                    "error decompressing object '{}': {e}",
+                                                    &partially_processed_key.key
+                                                ),
+                                            );
+                                            eoi = true;
+                                        }
+                                    },
+                                    None => {
+                                        match decompressor.finish() {
+                                            Ok(bytes) => splitter.append(&bytes),
+                                            Err(e) => consumer.error(
+                                                false,
+                                                anyhow!(
+                                                    "error decompressing object '{}': {e}",
+                                                    &partially_processed_key.key
+                                                ),
+                                            ),
+                                        }
+                                        eoi = true;
+                                    }
                                 };
                                 loop {
                                     start_offset = splitter.position();
@@ -762,8 +800,10 @@ mod test {
         deserialize_without_context,
         program_schema::Relation,
     };
+    use flate2::{write::GzEncoder, Compression as GzCompression};
     use mockall::predicate::eq;
     use serde::{Deserialize, Serialize};
+    use std::io::Write;
     use std::sync::Arc;
 
     #[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Clone, PartialOrd, Ord)]
@@ -888,6 +928,37 @@ format:
         run_test(SINGLE_KEY_CONFIG_STR, mock, test_data);
     }
 
+    #[test]
+    fn compressed_objects_read() {
+        let config_str =
+            MULTI_KEY_CONFIG_STR.replace("prefix: ''", "prefix: ''\n        compression: auto");
+        let mut mock = super::MockS3Client::default();
+        mock.expect_get_object_keys()
+            .with(eq("test-bucket"), eq(""), eq(&None), eq(&None))
+            .return_once(|_, _, _, _| Ok((vec!["obj1.gz".to_string(), "obj2".to_string()], None)));
+        mock.expect_get_object()
+            .with(eq("test-bucket"), eq("obj1.gz"), eq(0), eq(&None))
+            .return_once(|_, _, _, _| {
+                let mut encoder = GzEncoder::new(Vec::new(), GzCompression::default());
+                encoder.write_all(b"1\n2\n3\n").unwrap();
+                Ok(GetObjectOutput::builder()
+                    .body(ByteStream::from(encoder.finish().unwrap()))
+                    .build())
+            });
+        // No extension: detected by magic bytes.
+        mock.expect_get_object()
+            .with(eq("test-bucket"), eq("obj2"), eq(0), eq(&None))
+            .return_once(|_, _, _, _| {
+                Ok(GetObjectOutput::builder()
+                    .body(ByteStream::from(
+                        zstd::encode_all(&b"4\n5\n6\n"[..], 0).unwrap(),
+                    ))
+                    .build())
+            });
+        let test_data: Vec<TestStruct> = (1..7).map(|i| TestStruct { i }).collect();
+        run_test(&config_str, mock, test_data);
+    }
+
     #[test]
     fn single_object_with_prefix_read() {
         let mut mock = super::MockS3Client::default();
@@ -1,3 +1,4 @@
+use super::compression::Decompressor;
 use super::{
     InputConsumer, InputEndpoint, InputReader, InputReaderCommand, TransportInputEndpoint,
 };
@@ -15,6 +16,7 @@ use bytes::Bytes;
 use feldera_adapterlib::transport::{InputCommandReceiver, Resume};
 use feldera_types::config::FtModel;
 use feldera_types::program_schema::Relation;
+use feldera_types::transport::compression::Compression;
 use feldera_types::transport::url::UrlInputConfig;
 use futures::{future::OptionFuture, StreamExt};
 use serde::{Deserialize, Serialize};
@@ -76,8 +78,19 @@ struct UrlStream<'a> {
     response: Option<ClientResponse<Decompress<Payload>>>,
     next_read_ofs: u64,
 
+    /// Raw data received from the server, starting at offset `ofs`.
     buffer: Bytes,
     ofs: u64,
+
+    decompressor: Decompressor,
+
+    /// Decompressed data, starting at offset `position` in the decompressed
+    /// stream.
+    decompressed: Bytes,
+    position: u64,
+
+    /// Whether the raw data has been read to the end.
+    finished: bool,
 }
 
 /// Starting offset of the response sent by the server, determined based on
@@ -129,7 +142,7 @@ impl<'a> UrlStream<'a> {
     /// This does not include the time to receive the content itself following it.
     const HTTP_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
-    pub fn new(path: &'a str) -> Self {
+    pub fn new(path: &'a str, compression: Compression) -> Self {
         Self {
             client: Client::builder().connector(Connector::new()).finish(),
             path,
@@ -137,10 +150,36 @@ impl<'a> UrlStream<'a> {
             next_read_ofs: 0,
             buffer: Bytes::new(),
             ofs: 0,
+            decompressor: Decompressor::new(compression, path),
+            decompressed: Bytes::new(),
+            position: 0,
+            finished: false,
         }
     }
 
+    /// Reads up to `limit` bytes of decompressed data.
     pub async fn read(&mut self, limit: usize) -> AnyResult<Option<Bytes>> {
+        loop {
+            if !self.decompressed.is_empty() {
+                let n = min(limit, self.decompressed.len());
+                self.position += n as u64;
+                return Ok(Some(self.decompressed.split_to(n)));
+            }
+            if self.finished {
+                return Ok(None);
+            }
+            self.decompressed = match self.read_raw(usize::MAX).await? {
+                Some(bytes) => self.decompressor.decompress(bytes)?,
+                None => {
+                    self.finished = true;
+                    self.decompressor.finish()?
+                }
+            };
+        }
+    }
+
+    /// Reads up to `limit` bytes of raw data, as sent by the server.
+    async fn read_raw(&mut self, limit: usize) -> AnyResult<Option<Bytes>> {
         loop {
             if !self.buffer.is_empty() {
                 let n = min(limit, self.buffer.len());
@@ -224,7 +263,25 @@ impl<'a> UrlStream<'a> {
         }
     }
 
+    /// Seeks to offset `ofs` in the decompressed stream.
+    ///
+    /// Compressed data can only be read from the beginning, so this restarts
+    /// the download unless the data is uncompressed.
     fn seek(&mut self, ofs: u64) {
+        if ofs != self.position {
+            self.position = ofs;
+            self.decompressed = Bytes::new();
+            self.finished = false;
+            if self.decompressor.is_identity() {
+                self.seek_raw(ofs);
+            } else {
+                self.seek_raw(0);
+                self.decompressor.restart(ofs);
+            }
+        }
+    }
+
+    fn seek_raw(&mut self, ofs: u64) {
         if ofs != self.ofs {
             self.ofs = ofs;
             self.disconnect();
@@ -285,7 +342,7 @@ impl UrlInputReader {
         };
 
         let mut splitter = StreamSplitter::new(parser.splitter());
-        let mut stream = UrlStream::new(&config.path);
+        let mut stream = UrlStream::new(&config.path, config.compression);
         stream.seek(offset);
         splitter.seek(offset);
 
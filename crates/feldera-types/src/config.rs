@@ -11,12 +11,17 @@ use crate::transport::datagen::DatagenInputConfig;
 use crate::transport::delta_table::{DeltaTableReaderConfig, DeltaTableWriterConfig};
 use crate::transport::file::{FileInputConfig, FileOutputConfig};
 use crate::transport::http::HttpInputConfig;
-use crate::transport::iceberg::IcebergReaderConfig;
-use crate::transport::kafka::{KafkaInputConfig, KafkaOutputConfig};
+use crate::transport::iceberg::{IcebergReaderConfig, IcebergWriterConfig};
+use crate::transport::kafka::{KafkaDeadLetterConfig, KafkaInputConfig, KafkaOutputConfig};
+use crate::transport::mqtt::{MqttInputConfig, MqttOutputConfig};
+use crate::transport::nats::{NatsInputConfig, NatsOutputConfig};
 use crate::transport::nexmark::NexmarkInputConfig;
-use crate::transport::postgres::{PostgresReaderConfig, PostgresWriterConfig};
-use crate::transport::pubsub::PubSubInputConfig;
-use crate::transport::redis::RedisOutputConfig;
+use crate::transport::object_store::ObjectStoreOutputConfig;
+use crate::transport::postgres::{
+    PostgresCdcReaderConfig, PostgresReaderConfig, PostgresWriterConfig,
+};
+use crate::transport::pubsub::{PubSubInputConfig, PubSubOutputConfig};
+use crate::transport::redis::{RedisInputConfig, RedisOutputConfig};
 use crate::transport::s3::S3InputConfig;
 use crate::transport::url::UrlInputConfig;
 use core::fmt;
@@ -454,6 +459,17 @@ pub struct RuntimeConfig {
     ///   by invoking the `/checkpoint` API.
     pub checkpoint_during_suspend: bool,
 
+    /// TCP port of the pipeline's Arrow Flight service.
+    ///
+    /// When set, the pipeline serves the Arrow Flight (gRPC) protocol on this
+    /// port, in addition to its HTTP API.  Clients can push Arrow record
+    /// batches to tables with `DoPut`, subscribe to changes to views or read
+    /// their snapshots with `DoGet`, and read the schema of tables and views
+    /// with `GetFlightInfo` and `GetSchema`.
+    ///
+    /// The default is `null`, which disables the service.
+    pub flight_port: Option<u16>,
+
     /// Optional settings for tweaking Feldera internals.
     ///
     /// The available key-value pairs change from one version of Feldera to
@@ -591,6 +607,7 @@ impl Default for RuntimeConfig {
             max_parallel_connector_init: None,
             init_containers: None,
             checkpoint_during_suspend: true,
+            flight_port: None,
             dev_tweaks: BTreeMap::default(),
         }
     }
@@ -960,6 +977,33 @@ pub struct ConnectorConfig {
     #[serde(default = "default_max_queued_records")]
     pub max_queued_records: u64,
 
+    /// Maximum rate, in records per second, at which the connector ingests
+    /// data.
+    ///
+    /// When an input connector exceeds this rate, the controller pauses it
+    /// until its average rate drops back below the limit.  This prevents a
+    /// connector, e.g., one that backfills historical data, from starving
+    /// other connectors of the same pipeline.  The limit is approximate: the
+    /// connector can exceed it briefly by up to one second's worth of
+    /// records, plus any records it receives before it pauses.
+    ///
+    /// This property is valid for input connectors only.  By default, the
+    /// rate is not limited.
+    #[serde(default)]
+    pub max_records_per_second: Option<u64>,
+
+    /// Maximum rate, in bytes per second, at which the connector ingests
+    /// data.
+    ///
+    /// This limit works like `max_records_per_second`, and applies to the
+    /// bytes that the connector receives from its transport.  Connectors that
+    /// don't report the number of bytes they receive are not limited.
+    ///
+    /// This property is valid for input connectors only.  By default, the
+    /// rate is not limited.
+    #[serde(default)]
+    pub max_bytes_per_second: Option<u64>,
+
     /// Create connector in paused state.
     ///
     /// The default is `false`.
@@ -981,6 +1025,38 @@ pub struct ConnectorConfig {
     #[serde(deserialize_with = "deserialize_start_after")]
     #[serde(default)]
     pub start_after: Option<Vec<String>>,
+
+    /// Where to send records that fail to parse (for input connectors) or
+    /// encode (for output connectors).
+    ///
+    /// By default, such records are dropped after the error is reported.
+    #[serde(default)]
+    pub dead_letter: Option<DeadLetterConfig>,
+}
+
+/// Destination for records that a connector fails to parse or encode.
+///
+/// Each dead letter carries the name of the connector, a description of the
+/// error, the position of the record in the connector's input (for input
+/// connectors) or the step that produced it (for output connectors), and the
+/// offending data.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadLetterConfig {
+    /// Insert dead letters into the named SQL table of the pipeline.
+    ///
+    /// The table must have columns `connector VARCHAR`, `error VARCHAR`,
+    /// `event_number BIGINT`, `step BIGINT`, `raw_text VARCHAR`, and
+    /// `raw_bytes VARBINARY`.
+    ///
+    /// Not supported in fault-tolerant pipelines.
+    Table(String),
+
+    /// Append dead letters as JSON lines to the file at the given path.
+    File(String),
+
+    /// Write dead letters to a Kafka topic.
+    Kafka(KafkaDeadLetterConfig),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, ToSchema)]
@@ -1085,14 +1161,23 @@ pub enum TransportConfig {
     KafkaInput(KafkaInputConfig),
     KafkaOutput(KafkaOutputConfig),
     PubSubInput(PubSubInputConfig),
+    PubSubOutput(PubSubOutputConfig),
     UrlInput(UrlInputConfig),
     S3Input(S3InputConfig),
+    ObjectStoreOutput(ObjectStoreOutputConfig),
     DeltaTableInput(DeltaTableReaderConfig),
     DeltaTableOutput(DeltaTableWriterConfig),
+    RedisInput(RedisInputConfig),
     RedisOutput(RedisOutputConfig),
+    MqttInput(MqttInputConfig),
+    MqttOutput(MqttOutputConfig),
+    NatsInput(NatsInputConfig),
+    NatsOutput(NatsOutputConfig),
     // Prevent rust from complaining about large size difference between enum variants.
     IcebergInput(Box<IcebergReaderConfig>),
+    IcebergOutput(Box<IcebergWriterConfig>),
     PostgresInput(PostgresReaderConfig),
+    PostgresCdcInput(PostgresCdcReaderConfig),
     PostgresOutput(PostgresWriterConfig),
     Datagen(DatagenInputConfig),
     Nexmark(NexmarkInputConfig),
@@ -1113,19 +1198,28 @@ impl TransportConfig {
             TransportConfig::KafkaInput(_) => "kafka_input".to_string(),
             TransportConfig::KafkaOutput(_) => "kafka_output".to_string(),
             TransportConfig::PubSubInput(_) => "pub_sub_input".to_string(),
+            TransportConfig::PubSubOutput(_) => "pub_sub_output".to_string(),
             TransportConfig::UrlInput(_) => "url_input".to_string(),
             TransportConfig::S3Input(_) => "s3_input".to_string(),
+            TransportConfig::ObjectStoreOutput(_) => "object_store_output".to_string(),
             TransportConfig::DeltaTableInput(_) => "delta_table_input".to_string(),
             TransportConfig::DeltaTableOutput(_) => "delta_table_output".to_string(),
             TransportConfig::IcebergInput(_) => "iceberg_input".to_string(),
+            TransportConfig::IcebergOutput(_) => "iceberg_output".to_string(),
             TransportConfig::PostgresInput(_) => "postgres_input".to_string(),
+            TransportConfig::PostgresCdcInput(_) => "postgres_cdc_input".to_string(),
             TransportConfig::PostgresOutput(_) => "postgres_output".to_string(),
             TransportConfig::Datagen(_) => "datagen".to_string(),
             TransportConfig::Nexmark(_) => "nexmark".to_string(),
             TransportConfig::HttpInput(_) => "http_input".to_string(),
             TransportConfig::HttpOutput => "http_output".to_string(),
             TransportConfig::AdHocInput(_) => "adhoc_input".to_string(),
+            TransportConfig::RedisInput(_) => "redis_input".to_string(),
             TransportConfig::RedisOutput(_) => "redis_output".to_string(),
+            TransportConfig::MqttInput(_) => "mqtt_input".to_string(),
+            TransportConfig::MqttOutput(_) => "mqtt_output".to_string(),
+            TransportConfig::NatsInput(_) => "nats_input".to_string(),
+            TransportConfig::NatsOutput(_) => "nats_output".to_string(),
             TransportConfig::ClockInput(_) => "clock".to_string(),
         }
     }
@@ -0,0 +1,94 @@
+//! Messages exchanged with the pipeline's Arrow Flight service.
+//!
+//! The service is enabled by setting `flight_port` in the runtime
+//! configuration of the pipeline.  Commands and tickets are JSON-encoded.
+
+use serde::{Deserialize, Serialize};
+
+/// Command carried by the flight descriptor of a `DoPut` request.
+///
+/// A `DoPut` request can also use a path descriptor consisting of the name of
+/// the table, which is equivalent to a command with default settings.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct FlightPutCommand {
+    /// Name of the table to push record batches to.
+    pub table: String,
+
+    /// Name of a column of the record batches that specifies the change type
+    /// of each row: a boolean, where `false` marks a deletion, or an integer
+    /// weight (see `ArrowIpcParserConfig::weight_column`).
+    ///
+    /// When not specified, all rows are treated as insertions.
+    #[serde(default)]
+    pub weight_column: Option<String>,
+
+    /// Push data to the pipeline even if the pipeline is paused.
+    ///
+    /// The default is `false`.
+    #[serde(default)]
+    pub force: bool,
+}
+
+impl FlightPutCommand {
+    pub fn new(table: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            weight_column: None,
+            force: false,
+        }
+    }
+}
+
+/// Data returned by a `DoGet` request.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlightTicketMode {
+    /// Stream changes to the table or view, starting from the next step,
+    /// until the client disconnects.
+    #[default]
+    Deltas,
+
+    /// Return the current contents of a materialized table or view.
+    Snapshot,
+}
+
+/// Ticket of a `DoGet` request.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct FlightTicket {
+    /// Name of the table or view to read.
+    pub name: String,
+
+    /// Data to return.
+    ///
+    /// The default is `deltas`.
+    #[serde(default)]
+    pub mode: FlightTicketMode,
+
+    /// In `deltas` mode, name of a `BIGINT NOT NULL` column added to each
+    /// record batch that holds the weight of each row: positive for
+    /// insertions and negative for deletions.
+    ///
+    /// When not specified, deleted rows are not returned.
+    #[serde(default)]
+    pub weight_column: Option<String>,
+
+    /// In `deltas` mode, apply backpressure on the pipeline when the client
+    /// cannot receive data fast enough.
+    ///
+    /// When `false` (the default), record batches are dropped if the client
+    /// is not keeping up, so that a slow client doesn't slow down the
+    /// pipeline.
+    #[serde(default)]
+    pub backpressure: bool,
+}
+
+impl FlightTicket {
+    pub fn new(name: &str, mode: FlightTicketMode) -> Self {
+        Self {
+            name: name.to_string(),
+            mode,
+            weight_column: None,
+            backpressure: false,
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Arrow IPC input format configuration.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, ToSchema)]
+#[serde(default)]
+pub struct ArrowIpcParserConfig {
+    /// Name of a column that specifies the change type of each row.
+    ///
+    /// The column must have a boolean type, where `true` marks an insertion
+    /// and `false` marks a deletion, or an integer type, where the value is
+    /// the weight of the row: a positive weight `n` inserts `n` copies of the
+    /// row, a negative weight `-n` deletes `n` copies of the row.  The column
+    /// is not part of the record and must not contain `NULL`s.
+    ///
+    /// When not specified, all rows are treated as insertions.
+    pub weight_column: Option<String>,
+}
+
+/// Arrow IPC output format configuration.
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[serde(default)]
+pub struct ArrowIpcEncoderConfig {
+    /// Maximal number of records in a single Arrow IPC stream.
+    ///
+    /// The default is 10_000.
+    pub buffer_size_records: usize,
+
+    /// Name of a `BIGINT NOT NULL` column added to each output record that
+    /// holds the weight of the record: positive for insertions and negative
+    /// for deletions.
+    ///
+    /// When not specified, deleted records are not output and records
+    /// inserted multiple times are output once for each copy.
+    pub weight_column: Option<String>,
+}
+
+impl Default for ArrowIpcEncoderConfig {
+    fn default() -> Self {
+        Self {
+            buffer_size_records: 10_000,
+            weight_column: None,
+        }
+    }
+}
@@ -124,6 +124,28 @@ pub struct AvroParserConfig {
     #[serde(default)]
     pub skip_schema_id: bool,
 
+    /// `true` if the input consists of Avro Object Container Files rather
+    /// than individual messages.
+    ///
+    /// An object container file embeds the writer schema in its header and
+    /// stores records in blocks, optionally compressed with the `deflate`,
+    /// `snappy`, or `zstandard` codec.  The connector resolves the writer
+    /// schema against the table schema using Avro schema resolution rules:
+    /// fields are matched by name, fields not present in the table are
+    /// ignored, nullable columns missing from the file are set to `NULL`,
+    /// and values are promoted to wider types (e.g., `int` to `long`) as
+    /// needed.
+    ///
+    /// Each input chunk must contain a complete file, which is the case for
+    /// file-based transports such as `file_input`, `url_input`, and
+    /// `s3_input`.  Only the `raw` update format is supported.  This setting
+    /// is mutually exclusive with `schema`, `skip_schema_id`, and
+    /// `registry_urls`.
+    ///
+    /// The default value is `false`.
+    #[serde(default)]
+    pub object_container_file: bool,
+
     /// Schema registry configuration.
     #[serde(flatten)]
     pub registry_config: AvroSchemaRegistryConfig,
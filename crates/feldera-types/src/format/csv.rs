@@ -11,14 +11,67 @@ pub struct CsvParserConfig {
     /// This must be an ASCII character.
     pub delimiter: char,
 
-    /// Whether the input begins with a header line (which is ignored).
+    /// Whether the input begins with a header line.
+    ///
+    /// The header line is ignored unless `columns_by_name` is set.
     pub headers: bool,
+
+    /// Match columns of the input with columns of the table by the names in
+    /// the header line rather than by position.
+    ///
+    /// Requires `headers`.  Input columns that do not match any column of
+    /// the table are ignored, and nullable table columns missing from the
+    /// input are set to `NULL`.  Column names are matched case-insensitively,
+    /// except for case-sensitive SQL column names.
+    pub columns_by_name: bool,
+
+    /// Quote character (default `'"'`).
+    ///
+    /// This must be an ASCII character.
+    pub quote: char,
+
+    /// Escape character used to escape quotes within quoted fields, e.g.,
+    /// `'\\'`.
+    ///
+    /// When not set, quotes are escaped by doubling them.  This must be an
+    /// ASCII character.
+    pub escape: Option<char>,
+
+    /// Field value that represents `NULL` (default: empty string).
+    pub null_string: String,
+
+    /// Lines that begin with this character are ignored.
+    ///
+    /// This must be an ASCII character.
+    pub comment: Option<char>,
+
+    /// Remove leading and trailing whitespace from fields.
+    pub trim: bool,
+
+    /// Each record ends with an extra column that contains the weight of
+    /// the record: a positive weight `n` inserts `n` copies of the record,
+    /// a negative weight `-n` deletes `n` copies of the record.
+    ///
+    /// This is the format produced by the CSV encoder.
+    pub weighted: bool,
 }
 
 impl CsvParserConfig {
     pub fn delimiter(&self) -> CsvDelimiter {
         self.delimiter.into()
     }
+
+    pub fn quote(&self) -> u8 {
+        ascii_char(self.quote).unwrap_or(b'"')
+    }
+
+    pub fn escape(&self) -> Option<u8> {
+        self.escape.and_then(ascii_char)
+    }
+
+    pub fn comment(&self) -> Option<u8> {
+        self.comment.and_then(ascii_char)
+    }
 }
 
 impl Default for CsvParserConfig {
@@ -26,10 +79,21 @@ impl Default for CsvParserConfig {
         Self {
             delimiter: CsvDelimiter::default().0.into(),
             headers: false,
+            columns_by_name: false,
+            quote: '"',
+            escape: None,
+            null_string: String::new(),
+            comment: None,
+            trim: false,
+            weighted: false,
         }
     }
 }
 
+fn ascii_char(c: char) -> Option<u8> {
+    c.try_into().ok().filter(u8::is_ascii)
+}
+
 /// A delimiter between CSV records, typically `b','`.
 #[derive(Copy, Clone)]
 pub struct CsvDelimiter(pub u8);
@@ -58,7 +122,29 @@ impl From<char> for CsvDelimiter {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, ToSchema)]
+/// When the CSV encoder encloses fields in quotes.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub enum CsvQuoteStyle {
+    /// Quote fields that contain the delimiter, the quote character, or a
+    /// line break.
+    #[default]
+    #[serde(rename = "necessary")]
+    Necessary,
+
+    /// Quote all fields.
+    #[serde(rename = "always")]
+    Always,
+
+    /// Quote all fields that are not numbers.
+    #[serde(rename = "non_numeric")]
+    NonNumeric,
+
+    /// Never quote fields, even if the output is not valid CSV as a result.
+    #[serde(rename = "never")]
+    Never,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
 #[serde(default)]
 pub struct CsvEncoderConfig {
     /// Field delimiter (default `','`).
@@ -67,6 +153,44 @@ pub struct CsvEncoderConfig {
     pub delimiter: char,
 
     pub buffer_size_records: usize,
+
+    /// Quote character (default `'"'`).
+    ///
+    /// This must be an ASCII character.
+    pub quote: char,
+
+    /// Escape character used to escape quotes within quoted fields, e.g.,
+    /// `'\\'`.
+    ///
+    /// When not set, quotes are escaped by doubling them.  This must be an
+    /// ASCII character.
+    pub escape: Option<char>,
+
+    /// When to enclose fields in quotes.
+    pub quote_style: CsvQuoteStyle,
+
+    /// Field value output for `NULL` (default: empty string).
+    pub null_string: String,
+
+    /// Output a header line with column names before the first record.
+    ///
+    /// The last column of each record, which contains the weight of the
+    /// record, is named `weight`.
+    pub headers: bool,
+}
+
+impl CsvEncoderConfig {
+    pub fn delimiter(&self) -> CsvDelimiter {
+        self.delimiter.into()
+    }
+
+    pub fn quote(&self) -> u8 {
+        ascii_char(self.quote).unwrap_or(b'"')
+    }
+
+    pub fn escape(&self) -> Option<u8> {
+        self.escape.and_then(ascii_char)
+    }
 }
 
 impl Default for CsvEncoderConfig {
@@ -74,6 +198,11 @@ impl Default for CsvEncoderConfig {
         Self {
             delimiter: CsvDelimiter::default().0.into(),
             buffer_size_records: 10_000,
+            quote: '"',
+            escape: None,
+            quote_style: CsvQuoteStyle::default(),
+            null_string: String::new(),
+            headers: false,
         }
     }
 }
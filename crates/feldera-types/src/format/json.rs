@@ -213,6 +213,10 @@ pub enum JsonFlavor {
     /// (For internal use only)
     #[serde(skip)]
     ParquetConverter,
+    /// Protobuf to-json format.
+    /// (For internal use only)
+    #[serde(skip)]
+    ProtobufConverter,
     /// Used by the clock input connector.
     ClockInput,
     /// Datagen format.
@@ -1,5 +1,7 @@
+pub mod arrow_ipc;
 pub mod avro;
 pub mod csv;
 pub mod json;
 pub mod parquet;
+pub mod protobuf;
 pub mod raw;
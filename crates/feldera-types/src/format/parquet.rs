@@ -1,9 +1,64 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
 use utoipa::ToSchema;
 
 /// Configuration for the parquet parser.
-#[derive(Deserialize, Serialize, ToSchema)]
-pub struct ParquetParserConfig {}
+#[derive(Clone, Debug, Default, Deserialize, Serialize, ToSchema)]
+#[serde(default)]
+pub struct ParquetParserConfig {
+    /// Names of the Parquet columns to read.
+    ///
+    /// Other columns are skipped without being decoded.  Table columns that
+    /// don't have a matching Parquet column are set to `NULL`.
+    ///
+    /// When not specified, the parser reads the Parquet columns that match
+    /// a table column (after applying `rename` and `case_insensitive`).
+    pub columns: Option<Vec<String>>,
+
+    /// Match Parquet column names against table column names ignoring case,
+    /// including case-sensitive (quoted) table columns.
+    ///
+    /// Case-insensitive (unquoted) table columns always match Parquet column
+    /// names regardless of case.  The default is `false`.
+    pub case_insensitive: bool,
+
+    /// Maps Parquet column names to table column names.
+    ///
+    /// Parquet columns not listed here are matched against table columns
+    /// by their own names.
+    pub rename: BTreeMap<String, String>,
+
+    /// Skip row groups that cannot contain matching rows.
+    ///
+    /// Each predicate bounds the values of a Parquet column.  A row group is
+    /// skipped when the column statistics recorded in the Parquet file show
+    /// that none of its rows fall within the bounds of a predicate.  This is
+    /// an optimization, not a filter: rows outside the bounds are still
+    /// ingested if they share a row group with rows inside the bounds.
+    pub row_group_filter: Vec<ParquetColumnRange>,
+}
+
+/// A range of values of a Parquet column used to prune row groups.
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct ParquetColumnRange {
+    /// Name of a top-level Parquet column.
+    pub column: String,
+
+    /// Inclusive lower bound.
+    ///
+    /// A number, string, or boolean.  For `DATE` and `TIMESTAMP` columns,
+    /// a string in the `YYYY-MM-DD` or `YYYY-MM-DD HH:MM:SS.fff` format.
+    #[schema(value_type = Option<Object>)]
+    #[serde(default)]
+    pub min: Option<JsonValue>,
+
+    /// Inclusive upper bound, in the same format as `min`.
+    #[schema(value_type = Option<Object>)]
+    #[serde(default)]
+    pub max: Option<JsonValue>,
+}
 
 #[derive(Deserialize, Serialize, ToSchema)]
 #[serde(default)]
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use utoipa::ToSchema;
+
+/// Supported Protobuf data change event formats.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq, ToSchema)]
+pub enum ProtobufUpdateFormat {
+    /// Raw encoding.
+    ///
+    /// Each message contains a single record of the type specified in the
+    /// `message` property.
+    ///
+    /// ### Input Connectors
+    /// Each message is inserted into the table.
+    ///
+    /// ### Output Connectors
+    /// The message value contains the record to be inserted or deleted.
+    /// The operation type is specified in the `op` message header field,
+    /// which can be `insert` or `delete`.
+    #[default]
+    #[serde(rename = "raw")]
+    Raw,
+
+    /// Insert/delete envelope.
+    ///
+    /// The message type specified in the `message` property is an envelope
+    /// with `insert` and `delete` fields, both of which have the type of
+    /// the record, e.g.:
+    ///
+    /// ```protobuf
+    /// message OrderUpdate {
+    ///   Order insert = 1;
+    ///   Order delete = 2;
+    /// }
+    /// ```
+    ///
+    /// Each message carries a record to insert, a record to delete, or both,
+    /// in which case the deletion is applied first.
+    #[serde(rename = "insert_delete")]
+    InsertDelete,
+}
+
+impl Display for ProtobufUpdateFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Raw => f.write_str("raw"),
+            Self::InsertDelete => f.write_str("insert_delete"),
+        }
+    }
+}
+
+/// Protobuf schema, specified either as a compiled descriptor set or as
+/// `.proto` source.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, ToSchema)]
+pub struct ProtobufSchemaConfig {
+    /// Base64-encoded `FileDescriptorSet` containing the message type and all
+    /// its dependencies, e.g., generated with
+    /// `protoc --include_imports --descriptor_set_out=schema.desc schema.proto`.
+    ///
+    /// This property is mutually exclusive with `proto`.
+    pub descriptor_set: Option<String>,
+
+    /// Contents of a `.proto` file that defines the message type.
+    ///
+    /// The file can import the Google well-known types, e.g.,
+    /// `google/protobuf/timestamp.proto`, but no other files.
+    ///
+    /// This property is mutually exclusive with `descriptor_set`.
+    pub proto: Option<String>,
+
+    /// Fully qualified name of the message type, e.g., `shop.Order`.
+    pub message: String,
+}
+
+/// Protobuf input format configuration.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, ToSchema)]
+pub struct ProtobufParserConfig {
+    /// Format used to encode data change events in this stream.
+    ///
+    /// The default value is `raw`.
+    #[serde(default)]
+    pub update_format: ProtobufUpdateFormat,
+
+    /// Protobuf schema of input messages.
+    #[serde(flatten)]
+    pub schema: ProtobufSchemaConfig,
+}
+
+/// Protobuf output format configuration.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, ToSchema)]
+pub struct ProtobufEncoderConfig {
+    /// Format used to encode data change events in this stream.
+    ///
+    /// The default value is `raw`.
+    #[serde(default)]
+    pub update_format: ProtobufUpdateFormat,
+
+    /// Protobuf schema of output messages.
+    ///
+    /// Every column of the view must have a matching field in the message
+    /// type.  Fields without a matching column are left unset.
+    #[serde(flatten)]
+    pub schema: ProtobufSchemaConfig,
+}
@@ -18,3 +18,60 @@ pub enum RawParserMode {
 pub struct RawParserConfig {
     pub mode: RawParserMode,
 }
+
+/// How the raw encoder splits output records into messages.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub enum RawEncoderMode {
+    /// Each record is sent as a separate message.
+    #[serde(rename = "blob")]
+    Blob,
+
+    /// Records are separated by newlines and sent in batches of up to
+    /// `buffer_size_records` records.
+    #[serde(rename = "lines")]
+    Lines,
+}
+
+/// How the raw encoder handles deleted records.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub enum RawDeletePolicy {
+    /// Deleted records are not output.
+    #[default]
+    #[serde(rename = "skip")]
+    Skip,
+
+    /// Deleted records are output prefixed with `delete_marker`.
+    #[serde(rename = "marker")]
+    Marker,
+}
+
+/// Raw output format configuration.
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[serde(default)]
+pub struct RawEncoderConfig {
+    /// How output records are split into messages.
+    ///
+    /// Defaults to `blob` for message-oriented transports (Kafka, Pub/Sub,
+    /// MQTT and NATS) and to `lines` for all other transports.
+    pub mode: Option<RawEncoderMode>,
+
+    /// How deleted records are handled.
+    pub delete_policy: RawDeletePolicy,
+
+    /// Prefix prepended to deleted records when `delete_policy` is `marker`.
+    pub delete_marker: String,
+
+    /// Maximal number of records in a single message in the `lines` mode.
+    pub buffer_size_records: usize,
+}
+
+impl Default for RawEncoderConfig {
+    fn default() -> Self {
+        Self {
+            mode: None,
+            delete_policy: RawDeletePolicy::default(),
+            delete_marker: "-".to_string(),
+            buffer_size_records: 10_000,
+        }
+    }
+}
@@ -2,6 +2,7 @@ pub mod checkpoint;
 pub mod completion_token;
 pub mod config;
 pub mod error;
+pub mod flight;
 pub mod format;
 pub mod program_schema;
 pub mod query;
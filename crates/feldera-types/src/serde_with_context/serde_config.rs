@@ -266,6 +266,15 @@ impl From<JsonFlavor> for SqlSerdeConfig {
                 binary_format: BinaryFormat::Base64,
                 uuid_format: UuidFormat::String,
             },
+            JsonFlavor::ProtobufConverter => Self {
+                time_format: TimeFormat::Micros,
+                date_format: DateFormat::DaysSinceEpoch,
+                timestamp_format: TimestampFormat::MicrosSinceEpoch,
+                decimal_format: DecimalFormat::String,
+                variant_format: VariantFormat::JsonString,
+                binary_format: BinaryFormat::Array,
+                uuid_format: UuidFormat::String,
+            },
             JsonFlavor::ClockInput => {
                 SqlSerdeConfig::default().with_timestamp_format(TimestampFormat::MillisSinceEpoch)
             }
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Compression of the data read by an input connector.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    /// The data is not compressed.
+    #[default]
+    None,
+
+    /// Detect the compression format from the file extension (`.gz`, `.zst`,
+    /// `.bz2`, or `.xz`) or, if the extension isn't recognized, from the magic
+    /// bytes at the start of the data.  Data that doesn't match any format is
+    /// read as is.
+    Auto,
+
+    /// gzip compression.
+    Gzip,
+
+    /// Zstandard compression.
+    Zstd,
+
+    /// bzip2 compression.
+    Bzip2,
+
+    /// xz compression.
+    Xz,
+}
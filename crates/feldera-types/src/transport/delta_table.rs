@@ -22,6 +22,16 @@ pub enum DeltaTableWriteMode {
     /// If a table exists at the specified location, the operation must fail.
     #[serde(rename = "error_if_exists")]
     ErrorIfExists,
+
+    /// Apply updates to the existing table at the target location, so that
+    /// the table mirrors the current contents of the view.
+    ///
+    /// Requires the connector to be configured with an `index`.  The index
+    /// columns are used as the key to match output records against table rows:
+    /// inserts and updates are merged into the table with `MERGE`, deletes
+    /// remove the matching row.  The table is created if it doesn't exist.
+    #[serde(rename = "merge")]
+    Merge,
 }
 
 /// Delta table output connector configuration.
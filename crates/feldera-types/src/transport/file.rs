@@ -1,3 +1,4 @@
+use crate::transport::compression::Compression;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -5,8 +6,21 @@ use utoipa::ToSchema;
 #[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
 pub struct FileInputConfig {
     /// File path.
+    ///
+    /// This may also be a directory, in which case all files in the directory
+    /// are read, or, if `glob` is `true`, a glob pattern such as
+    /// `/data/orders/*.csv`, in which case all matching files are read.
+    /// Multiple files are read one at a time, in lexicographic order of their
+    /// paths.
     pub path: String,
 
+    /// Interpret `path` as a glob pattern.
+    ///
+    /// When `false`, the default, `path` is a literal path, even if it
+    /// contains characters such as `*`, `?`, or `[`.
+    #[serde(default)]
+    pub glob: bool,
+
     /// Read buffer size.
     ///
     /// Default: when this parameter is not specified, a platform-specific
@@ -19,8 +33,22 @@ pub struct FileInputConfig {
     /// message and stops upon reaching the end of file.  When `true`, the
     /// endpoint will keep watching the file and outputting any new content
     /// appended to it.
+    ///
+    /// When `path` is a directory or a glob pattern, `true` makes the endpoint
+    /// keep watching for new files instead.  Each file is read once, up to its
+    /// end, so files should be created atomically, e.g. by writing them
+    /// elsewhere and then renaming them into place.  The endpoint only picks up
+    /// new files whose paths sort after the last file it has read, e.g.,
+    /// files named after the time they were created.
     #[serde(default)]
     pub follow: bool,
+
+    /// Compression of the file.  A single compressed file can't be followed.
+    ///
+    /// When set, the connector decompresses the data before passing it to
+    /// the parser.  The default is `none`.
+    #[serde(default)]
+    pub compression: Compression,
 }
 
 /// Configuration for writing data to a file with `FileOutputTransport`.
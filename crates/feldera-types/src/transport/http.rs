@@ -23,6 +23,10 @@ pub struct HttpInputConfig {
 pub struct Chunk {
     pub sequence_number: u64,
 
+    /// Step that produced the chunk.  Only set when the connection was
+    /// opened in `snapshot_and_follow` mode.
+    pub step: Option<u64>,
+
     // Exactly one of the following fields must be set.
     // This should be an enum inlined with `#[serde(flatten)]`, but `utoipa`
     // struggles to generate a schema for that.
@@ -37,5 +41,130 @@ pub struct Chunk {
     pub json_data: Option<JsonValue>,
 }
 
+/// Data sent over an `/egress` connection.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EgressMode {
+    /// Send the changes to the table or view computed by each step, starting
+    /// from the next step.
+    #[default]
+    WatchChanges,
+
+    /// Send the current contents of the materialized table or view, as seen by
+    /// ad hoc queries, followed by the changes computed by each subsequent
+    /// step.
+    ///
+    /// The snapshot is sent as a set of insertions tagged with the step that
+    /// it reflects; changes are sent starting from the next step.
+    SnapshotAndFollow,
+}
+
+/// Type of an [`IngressWebSocketResponse`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IngressWebSocketResponseType {
+    /// All records in the message were ingested.
+    Ack,
+
+    /// The message could not be ingested.
+    ///
+    /// When the message contained records that failed to parse, the
+    /// remaining records in the message were still ingested.
+    Error,
+}
+
+/// Message sent by the pipeline in response to a message received over an
+/// `/ingress` WebSocket connection.
+///
+/// The pipeline sends exactly one response for each message, in the order in
+/// which the messages were received.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct IngressWebSocketResponse {
+    #[serde(rename = "type")]
+    pub response_type: IngressWebSocketResponseType,
+
+    /// Position of the message in the connection, starting from 0.
+    pub sequence_number: u64,
+
+    // This should be an enum tagged with `type`, but internally tagged enums
+    // cannot be deserialized with serde_json's `arbitrary_precision` feature.
+    /// Completion token that can be passed to the `/completion_status`
+    /// endpoint to check whether the pipeline has fully processed the
+    /// message.  Set in `ack` responses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+
+    /// Error description, in the same form as the error response returned by
+    /// the REST API.  Set in `error` responses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub error: Option<JsonValue>,
+}
+
+impl IngressWebSocketResponse {
+    pub fn ack(sequence_number: u64, token: String) -> Self {
+        Self {
+            response_type: IngressWebSocketResponseType::Ack,
+            sequence_number,
+            token: Some(token),
+            error: None,
+        }
+    }
+
+    pub fn error(sequence_number: u64, error: JsonValue) -> Self {
+        Self {
+            response_type: IngressWebSocketResponseType::Error,
+            sequence_number,
+            token: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// Type of an [`EgressWebSocketMessage`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EgressWebSocketMessageType {
+    /// A set of updates to the table or view produced by `step`.
+    Data,
+
+    /// All updates produced by `step` have been sent.
+    StepEnd,
+
+    /// Sent when the pipeline has not produced any output for the heartbeat
+    /// interval of the connection.
+    Heartbeat,
+}
+
+/// Message sent by the pipeline over an `/egress` WebSocket connection.
+///
+/// `sequence_number` counts all messages sent over the connection, starting
+/// from 0.  When the connection was opened without backpressure, the pipeline
+/// drops messages that the client is not receiving fast enough, which shows as
+/// a gap in sequence numbers.
+///
+/// The payload of `data` messages is stored in the `bin_data`, `text_data`, or
+/// `json_data` field depending on the data format used, as in [`Chunk`].
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct EgressWebSocketMessage {
+    #[serde(rename = "type")]
+    pub message_type: EgressWebSocketMessageType,
+
+    pub sequence_number: u64,
+
+    /// Step that produced the output.  Set in `data` and `step_end` messages.
+    pub step: Option<u64>,
+
+    /// Base64 encoded binary payload, e.g., Avro.
+    pub bin_data: Option<Vec<u8>>,
+
+    /// Text payload, e.g., CSV.
+    pub text_data: Option<String>,
+
+    /// JSON payload.
+    #[schema(value_type = Option<Object>)]
+    pub json_data: Option<JsonValue>,
+}
+
 // This file indicates the port used by the server
 pub const SERVER_PORT_FILE: &str = "port";
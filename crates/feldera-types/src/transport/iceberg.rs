@@ -206,10 +206,24 @@ pub struct IcebergReaderConfig {
     /// is used.
     pub datetime: Option<String>,
 
+    /// Iceberg catalog and storage configuration.
+    #[serde(flatten)]
+    pub catalog_config: IcebergCatalogConfig,
+}
+
+/// Iceberg catalog and storage configuration shared by the Iceberg input and
+/// output connectors.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct IcebergCatalogConfig {
     /// Location of the table metadata JSON file.
     ///
     /// This propery is used to access an Iceberg table without a catalog. It is mutually
     /// exclusive with the `catalog_type` property.
+    ///
+    /// The output connector uses the directory that contains this file as a
+    /// file-based catalog: it creates the table if the file does not exist,
+    /// writes a new `v<N>.metadata.json` file to the directory on each commit,
+    /// and records the latest version in `version-hint.text`.
     pub metadata_location: Option<String>,
 
     /// Specifies the Iceberg table name in the "namespace.table" format.
@@ -235,8 +249,8 @@ pub struct IcebergReaderConfig {
     pub fileio_config: HashMap<String, String>,
 }
 
-impl IcebergReaderConfig {
-    pub fn validate_catalog_config(&self) -> Result<(), String> {
+impl IcebergCatalogConfig {
+    pub fn validate(&self) -> Result<(), String> {
         self.validate_metadata_location()?;
         self.validate_table_name()?;
         self.validate_glue_catalog_config()?;
@@ -354,3 +368,11 @@ impl IcebergReaderConfig {
         )
     }
 }
+
+/// Iceberg output connector configuration.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct IcebergWriterConfig {
+    /// Iceberg catalog and storage configuration.
+    #[serde(flatten)]
+    pub catalog_config: IcebergCatalogConfig,
+}
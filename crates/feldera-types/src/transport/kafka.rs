@@ -67,6 +67,11 @@ pub struct KafkaInputConfig {
     ///
     /// If offsets are provided for all partitions, this field can be omitted.
     pub partitions: Option<Vec<i32>>,
+
+    /// Table columns that receive metadata of each Kafka message, in addition
+    /// to the columns parsed from the message payload.
+    #[serde(default)]
+    pub metadata_columns: KafkaMetadataColumns,
 }
 
 impl KafkaInputConfig {
@@ -88,6 +93,56 @@ impl<'de> Deserialize<'de> for KafkaInputConfig {
     }
 }
 
+/// Table columns that receive Kafka message metadata.
+///
+/// Each property names the column that receives the corresponding value for
+/// every record parsed from a message.  Metadata that isn't mapped to a column
+/// is not ingested.  Only the JSON format supports metadata columns.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct KafkaMetadataColumns {
+    /// Column for the message key, with type `VARBINARY` or `VARCHAR`.  A
+    /// `VARCHAR` column receives the key decoded as UTF-8.  The column is
+    /// `NULL` for messages without a key.
+    pub key: Option<String>,
+
+    /// Column for the message headers, with type `MAP<VARCHAR, VARCHAR>`.
+    /// Header values are decoded as UTF-8.  If a header occurs more than once,
+    /// the last value is used.
+    pub headers: Option<String>,
+
+    /// Column for the message timestamp, with type `TIMESTAMP`.  The column is
+    /// `NULL` for messages without a timestamp.
+    pub timestamp: Option<String>,
+
+    /// Column for the partition of the message, with type `INT`.
+    pub partition: Option<String>,
+
+    /// Column for the offset of the message within its partition, with type
+    /// `BIGINT`.
+    pub offset: Option<String>,
+}
+
+impl KafkaMetadataColumns {
+    /// Returns true if no metadata is mapped to a column.
+    pub fn is_empty(&self) -> bool {
+        self.columns().next().is_none()
+    }
+
+    /// Returns the names of the columns that receive metadata.
+    pub fn columns(&self) -> impl Iterator<Item = &str> {
+        [
+            &self.key,
+            &self.headers,
+            &self.timestamp,
+            &self.partition,
+            &self.offset,
+        ]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+    }
+}
+
 /// Where to begin reading a Kafka topic.
 #[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
@@ -295,6 +350,46 @@ pub struct KafkaHeader {
     pub value: Option<KafkaHeaderValue>,
 }
 
+/// Configuration for writing dead letters to a Kafka topic.
+///
+/// The payload of each message is the offending data.  The name of the
+/// connector, the error, the parser's event number, and the step are passed
+/// in the `connector`, `error`, `event_number`, and `step` headers; headers
+/// for missing values are omitted.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct KafkaDeadLetterConfig {
+    /// Options passed directly to `rdkafka`.
+    ///
+    /// See [`librdkafka` options](https://github.com/edenhill/librdkafka/blob/master/CONFIGURATION.md)
+    /// used to configure the Kafka producer.
+    #[serde(flatten)]
+    pub kafka_options: BTreeMap<String, String>,
+
+    /// Topic to write to.
+    pub topic: String,
+}
+
+impl KafkaDeadLetterConfig {
+    /// Returns the configuration of an output connector that writes to the
+    /// dead-letter topic.
+    pub fn to_output_config(&self) -> KafkaOutputConfig {
+        KafkaOutputConfig {
+            kafka_options: self.kafka_options.clone(),
+            topic: self.topic.clone(),
+            headers: Vec::new(),
+            topic_field: None,
+            key_fields: None,
+            header_fields: Vec::new(),
+            op_header: None,
+            log_level: None,
+            initialization_timeout_secs: default_initialization_timeout_secs(),
+            fault_tolerance: None,
+            kafka_service: None,
+            region: None,
+        }
+    }
+}
+
 /// Configuration for writing data to a Kafka topic with `OutputTransport`.
 #[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
 pub struct KafkaOutputConfig {
@@ -312,6 +407,29 @@ pub struct KafkaOutputConfig {
     #[serde(default)]
     pub headers: Vec<KafkaHeader>,
 
+    /// Column of the view whose value is the name of the topic that each
+    /// record is written to.
+    ///
+    /// Records where the column is `NULL` are written to `topic`.  All
+    /// topics must exist.
+    pub topic_field: Option<String>,
+
+    /// Columns of the view whose values form the key of each message.
+    ///
+    /// The values of the columns are converted to strings and joined with `:`.
+    pub key_fields: Option<Vec<String>>,
+
+    /// Columns of the view whose values are added to each message as headers,
+    /// using column names as header names.
+    ///
+    /// Columns whose value is `NULL` are omitted.
+    #[serde(default)]
+    pub header_fields: Vec<String>,
+
+    /// Name of a header that carries the type of change, `insert` or
+    /// `delete`, of each message.
+    pub op_header: Option<String>,
+
     /// The log level of the client.
     ///
     /// If not specified, the log level will be calculated based on the global
@@ -352,6 +470,13 @@ pub struct KafkaOutputFtConfig {
     ///
     /// These options override `kafka_options` for producers, and may be empty.
     pub producer_options: BTreeMap<String, String>,
+
+    /// Topic where the connector records the steps that only wrote to topics
+    /// other than `topic`, so that it can find the next step to write after a
+    /// restart.
+    ///
+    /// Required when `topic_field` is set.  The topic must exist.
+    pub progress_topic: Option<String>,
 }
 
 impl KafkaOutputConfig {
@@ -412,7 +537,7 @@ mod compat {
 
     use serde::Deserialize;
 
-    use crate::transport::kafka::{KafkaLogLevel, KafkaStartFromConfig};
+    use crate::transport::kafka::{KafkaLogLevel, KafkaMetadataColumns, KafkaStartFromConfig};
 
     #[derive(Deserialize)]
     pub struct KafkaInputConfigCompat {
@@ -455,6 +580,10 @@ mod compat {
 
         /// The Kafka partitions to read from.
         partitions: Option<Vec<i32>>,
+
+        /// Table columns that receive message metadata.
+        #[serde(default)]
+        metadata_columns: KafkaMetadataColumns,
     }
 
     impl TryFrom<KafkaInputConfigCompat> for super::KafkaInputConfig {
@@ -521,6 +650,7 @@ mod compat {
                 start_from,
                 region: compat.region,
                 partitions: compat.partitions,
+                metadata_columns: compat.metadata_columns,
             })
         }
     }
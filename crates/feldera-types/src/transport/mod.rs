@@ -1,12 +1,16 @@
 pub mod adhoc;
 pub mod clock;
+pub mod compression;
 pub mod datagen;
 pub mod delta_table;
 pub mod file;
 pub mod http;
 pub mod iceberg;
 pub mod kafka;
+pub mod mqtt;
+pub mod nats;
 pub mod nexmark;
+pub mod object_store;
 pub mod postgres;
 pub mod pubsub;
 pub mod redis;
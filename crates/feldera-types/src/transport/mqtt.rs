@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// MQTT input connector configuration.
+///
+/// The connector subscribes to one or more topics on an MQTT broker using
+/// MQTT version 3.1.1.  The payload of each message is parsed using the
+/// format configured for the connector.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct MqttInputConfig {
+    /// Host name or IP address of the broker.
+    pub host: String,
+
+    /// Broker port.
+    ///
+    /// The default is 8883 when `tls` is enabled and 1883 otherwise.
+    pub port: Option<u16>,
+
+    /// Client identifier used to connect to the broker.
+    ///
+    /// Required when `clean_session` is `false`, since the broker identifies
+    /// persistent sessions by client identifier.  When not specified, the
+    /// connector generates a random identifier.
+    pub client_id: Option<String>,
+
+    /// Username used to authenticate with the broker.
+    pub username: Option<String>,
+
+    /// Password used to authenticate with the broker.
+    ///
+    /// Requires `username` to be set.
+    pub password: Option<String>,
+
+    /// Connect to the broker over TLS.
+    ///
+    /// The default is `false`.
+    #[serde(default)]
+    pub tls: bool,
+
+    /// PEM-encoded certificate of the certificate authority used to verify
+    /// the broker's certificate.
+    ///
+    /// When not specified, the connector uses the system's root certificates.
+    /// Requires `tls` to be set.
+    pub tls_ca_certificate: Option<String>,
+
+    /// PEM-encoded client certificate used to authenticate with the broker.
+    ///
+    /// Requires `tls_ca_certificate` and `tls_client_key` to be set.
+    pub tls_client_certificate: Option<String>,
+
+    /// PEM-encoded private key of the client certificate.
+    ///
+    /// Requires `tls_client_certificate` to be set.
+    pub tls_client_key: Option<String>,
+
+    /// Keep-alive interval, in seconds.
+    ///
+    /// The default is 30.
+    #[serde(default = "default_keep_alive_secs")]
+    pub keep_alive_secs: u64,
+
+    /// Maximum size of a message, in bytes.
+    ///
+    /// The default is 1048576 (1 MiB).
+    #[serde(default = "default_max_packet_size")]
+    pub max_packet_size: usize,
+
+    /// Topic filters to subscribe to.
+    ///
+    /// Filters can contain the `+` (single level) and `#` (multi-level)
+    /// wildcards, e.g., `sensors/+/temperature` or `sensors/#`.
+    pub topics: Vec<String>,
+
+    /// Quality of service level requested for the subscriptions: 0 (at most
+    /// once), 1 (at least once), or 2 (exactly once).
+    ///
+    /// The default is 1.
+    #[serde(default = "default_qos")]
+    pub qos: u8,
+
+    /// Start a clean session on every connection.
+    ///
+    /// When `false`, the broker keeps the session of the connector, including
+    /// its subscriptions and QoS 1 and 2 messages that arrive while the
+    /// connector is disconnected or paused, and delivers them when the
+    /// connector reconnects.  Requires `client_id` to be set.
+    ///
+    /// The default is `true`.
+    #[serde(default = "default_clean_session")]
+    pub clean_session: bool,
+
+    /// Ignore retained messages.
+    ///
+    /// When the connector subscribes to a topic, the broker delivers the
+    /// last retained message of each matching topic.  Set this option to
+    /// `true` to only ingest messages published after the subscription.
+    ///
+    /// The default is `false`.
+    #[serde(default)]
+    pub skip_retained: bool,
+}
+
+/// MQTT output connector configuration.
+///
+/// The connector publishes each buffer produced by the encoder as a message
+/// to an MQTT broker using MQTT version 3.1.1.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct MqttOutputConfig {
+    /// Host name or IP address of the broker.
+    pub host: String,
+
+    /// Broker port.
+    ///
+    /// The default is 8883 when `tls` is enabled and 1883 otherwise.
+    pub port: Option<u16>,
+
+    /// Client identifier used to connect to the broker.
+    ///
+    /// When not specified, the connector generates a random identifier.
+    pub client_id: Option<String>,
+
+    /// Username used to authenticate with the broker.
+    pub username: Option<String>,
+
+    /// Password used to authenticate with the broker.
+    ///
+    /// Requires `username` to be set.
+    pub password: Option<String>,
+
+    /// Connect to the broker over TLS.
+    ///
+    /// The default is `false`.
+    #[serde(default)]
+    pub tls: bool,
+
+    /// PEM-encoded certificate of the certificate authority used to verify
+    /// the broker's certificate.
+    ///
+    /// When not specified, the connector uses the system's root certificates.
+    /// Requires `tls` to be set.
+    pub tls_ca_certificate: Option<String>,
+
+    /// PEM-encoded client certificate used to authenticate with the broker.
+    ///
+    /// Requires `tls_ca_certificate` and `tls_client_key` to be set.
+    pub tls_client_certificate: Option<String>,
+
+    /// PEM-encoded private key of the client certificate.
+    ///
+    /// Requires `tls_client_certificate` to be set.
+    pub tls_client_key: Option<String>,
+
+    /// Keep-alive interval, in seconds.
+    ///
+    /// The default is 30.
+    #[serde(default = "default_keep_alive_secs")]
+    pub keep_alive_secs: u64,
+
+    /// Maximum size of a message, in bytes.
+    ///
+    /// The default is 1048576 (1 MiB).
+    #[serde(default = "default_max_packet_size")]
+    pub max_packet_size: usize,
+
+    /// Topic to publish to.  Must not contain wildcards.
+    pub topic: String,
+
+    /// Quality of service level used to publish messages: 0 (at most once),
+    /// 1 (at least once), or 2 (exactly once).
+    ///
+    /// With QoS 1 and 2, the connector waits for the broker to acknowledge
+    /// all messages published during a step at the end of the step.
+    ///
+    /// The default is 1.
+    #[serde(default = "default_qos")]
+    pub qos: u8,
+
+    /// Publish messages with the retain flag set, so that the broker
+    /// delivers the last message to new subscribers of the topic.
+    ///
+    /// The default is `false`.
+    #[serde(default)]
+    pub retain: bool,
+}
+
+const fn default_keep_alive_secs() -> u64 {
+    30
+}
+
+const fn default_max_packet_size() -> usize {
+    1024 * 1024
+}
+
+const fn default_qos() -> u8 {
+    1
+}
+
+const fn default_clean_session() -> bool {
+    true
+}
@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Where a newly created JetStream consumer starts reading the stream.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NatsDeliverPolicy {
+    /// Read all messages in the stream.
+    #[default]
+    All,
+
+    /// Read only messages published after the consumer was created.
+    New,
+
+    /// Start with the last message in the stream.
+    Last,
+}
+
+/// NATS JetStream input connector configuration.
+///
+/// The connector reads messages from a JetStream stream using a durable pull
+/// consumer.  The payload of each message is parsed using the format
+/// configured for the connector.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct NatsInputConfig {
+    /// NATS server URL, e.g., `nats://localhost:4222`.
+    pub server_url: String,
+
+    /// Username used to authenticate with the server.
+    ///
+    /// Requires `password` to be set.
+    pub username: Option<String>,
+
+    /// Password used to authenticate with the server.
+    pub password: Option<String>,
+
+    /// Token used to authenticate with the server.
+    pub token: Option<String>,
+
+    /// Content of a NATS credentials (`.creds`) file, containing a user JWT
+    /// and NKey seed, used to authenticate with the server.
+    pub credentials: Option<String>,
+
+    /// Require a TLS connection to the server.
+    ///
+    /// The default is `false`.
+    #[serde(default)]
+    pub tls_required: bool,
+
+    /// Path to a PEM file with the certificates of the certificate
+    /// authorities used to verify the server's certificate.
+    pub tls_ca_file: Option<String>,
+
+    /// Path to a PEM file with the client certificate used to authenticate
+    /// with the server.
+    ///
+    /// Requires `tls_client_key_file` to be set.
+    pub tls_client_cert_file: Option<String>,
+
+    /// Path to a PEM file with the private key of the client certificate.
+    pub tls_client_key_file: Option<String>,
+
+    /// Name of the JetStream stream to read from.  The stream must exist.
+    pub stream: String,
+
+    /// Name of the durable consumer used to read the stream.
+    ///
+    /// The connector creates the consumer if it doesn't exist.  The name must
+    /// not change across pipeline restarts, and the consumer must not be
+    /// shared with other applications or pipelines.
+    ///
+    /// The default is `feldera`.
+    #[serde(default = "default_consumer_name")]
+    pub consumer_name: String,
+
+    /// Subjects to read from the stream.  Subjects can contain the `*` and
+    /// `>` wildcards.
+    ///
+    /// When empty (the default), the connector reads all messages in the
+    /// stream.
+    #[serde(default)]
+    pub filter_subjects: Vec<String>,
+
+    /// Where the consumer starts reading the stream when the connector
+    /// creates it.
+    ///
+    /// Ignored if the consumer already exists.
+    ///
+    /// The default is `all`.
+    #[serde(default)]
+    pub deliver_policy: NatsDeliverPolicy,
+
+    /// Maximum number of messages to request from the server at a time.
+    ///
+    /// The default is 1000.
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+
+    /// Maximum time, in milliseconds, that a request for messages waits for
+    /// new messages when the stream has none.
+    ///
+    /// The default is 100.
+    #[serde(default = "default_fetch_timeout_ms")]
+    pub fetch_timeout_ms: u64,
+
+    /// Time, in seconds, that the server waits for the connector to
+    /// acknowledge a message before delivering it again.
+    ///
+    /// In a fault-tolerant pipeline, the connector acknowledges messages
+    /// after they have been checkpointed, so this should be longer than the
+    /// checkpoint interval.  Messages that are delivered again are ignored.
+    /// Ignored if the consumer already exists.
+    ///
+    /// The default is 600.
+    #[serde(default = "default_ack_wait_secs")]
+    pub ack_wait_secs: u64,
+
+    /// Maximum number of messages that have been delivered to the connector
+    /// but not acknowledged yet.  The server stops delivering messages when
+    /// the limit is reached.
+    ///
+    /// Ignored if the consumer already exists.
+    ///
+    /// The default is 100000.
+    #[serde(default = "default_max_ack_pending")]
+    pub max_ack_pending: i64,
+}
+
+/// NATS JetStream output connector configuration.
+///
+/// The connector publishes each buffer produced by the encoder as a message
+/// to a JetStream subject.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct NatsOutputConfig {
+    /// NATS server URL, e.g., `nats://localhost:4222`.
+    pub server_url: String,
+
+    /// Username used to authenticate with the server.
+    ///
+    /// Requires `password` to be set.
+    pub username: Option<String>,
+
+    /// Password used to authenticate with the server.
+    pub password: Option<String>,
+
+    /// Token used to authenticate with the server.
+    pub token: Option<String>,
+
+    /// Content of a NATS credentials (`.creds`) file, containing a user JWT
+    /// and NKey seed, used to authenticate with the server.
+    pub credentials: Option<String>,
+
+    /// Require a TLS connection to the server.
+    ///
+    /// The default is `false`.
+    #[serde(default)]
+    pub tls_required: bool,
+
+    /// Path to a PEM file with the certificates of the certificate
+    /// authorities used to verify the server's certificate.
+    pub tls_ca_file: Option<String>,
+
+    /// Path to a PEM file with the client certificate used to authenticate
+    /// with the server.
+    ///
+    /// Requires `tls_client_key_file` to be set.
+    pub tls_client_cert_file: Option<String>,
+
+    /// Path to a PEM file with the private key of the client certificate.
+    pub tls_client_key_file: Option<String>,
+
+    /// Subject to publish to.  The subject must be bound to a JetStream
+    /// stream and must not contain wildcards.
+    pub subject: String,
+
+    /// Prefix of the de-duplication id (`Nats-Msg-Id` header) attached to
+    /// each message in a fault-tolerant pipeline.
+    ///
+    /// The id of each message consists of the prefix, the step that
+    /// produced the message, and the position of the message in the step.
+    /// When a pipeline replays a step after a restart, the server discards
+    /// messages whose ids it has seen within the stream's `duplicate_window`
+    /// (2 minutes by default).  Output is therefore only exactly-once if the
+    /// pipeline restarts within the duplicate window; streams written by
+    /// pipelines that may take longer to recover need a longer window.  The
+    /// connector refuses to publish to a stream whose duplicate window is 0.
+    ///
+    /// The prefix must be unique among all writers to the stream.  The
+    /// default is the name of the connector.
+    pub message_id_prefix: Option<String>,
+}
+
+fn default_consumer_name() -> String {
+    "feldera".to_string()
+}
+
+const fn default_batch_size() -> usize {
+    1000
+}
+
+const fn default_fetch_timeout_ms() -> u64 {
+    100
+}
+
+const fn default_ack_wait_secs() -> u64 {
+    600
+}
+
+const fn default_max_ack_pending() -> i64 {
+    100_000
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+fn default_max_object_size_bytes() -> u64 {
+    128 * 1024 * 1024
+}
+
+/// Configuration for writing data to an object store (AWS S3, Google Cloud Storage,
+/// Azure Blob Storage, or the local file system).
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct ObjectStoreOutputConfig {
+    /// URL of the prefix under which the connector creates output objects,
+    /// e.g., `s3://bucket/path/to/output`.
+    ///
+    /// Supported URL schemes: `s3://` (AWS S3 and S3-compatible services),
+    /// `gs://` (Google Cloud Storage), `az://` and `abfs://` (Azure Blob Storage),
+    /// and `file://` (local file system).
+    pub url: String,
+
+    /// Maximum size of an output object in bytes.
+    ///
+    /// The connector starts a new object once the current object reaches this size.
+    /// An object can exceed this size if a single buffer produced by the encoder
+    /// is larger than the limit.
+    ///
+    /// Default: 128 MiB.
+    #[serde(default = "default_max_object_size_bytes")]
+    pub max_object_size_bytes: u64,
+
+    /// Maximum number of records in an output object.
+    ///
+    /// Default: unlimited.
+    pub max_records_per_object: Option<u64>,
+
+    /// Maximum time, in seconds, during which the connector keeps adding records
+    /// to the same object.
+    ///
+    /// Ignored in fault-tolerant pipelines, where objects are closed at the end
+    /// of every step.
+    ///
+    /// Default: unlimited.
+    pub max_object_age_secs: Option<u64>,
+
+    /// Storage options for configuring backend object store.
+    ///
+    /// For specific options available for different storage backends, see:
+    /// * [Azure options](https://docs.rs/object_store/latest/object_store/azure/enum.AzureConfigKey.html)
+    /// * [Amazon S3 options](https://docs.rs/object_store/latest/object_store/aws/enum.AmazonS3ConfigKey.html)
+    /// * [Google Cloud Storage options](https://docs.rs/object_store/latest/object_store/gcp/enum.GoogleConfigKey.html)
+    #[serde(flatten)]
+    pub object_store_config: HashMap<String, String>,
+}
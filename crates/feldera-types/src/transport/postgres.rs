@@ -12,6 +12,31 @@ pub struct PostgresReaderConfig {
     pub query: String,
 }
 
+/// Postgres output connector write mode.
+///
+/// Determines how the connector applies inserts and updates to the table.
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+pub enum PostgresWriteMode {
+    /// New rows are added with `INSERT`, modified rows are updated with `UPDATE`.
+    ///
+    /// Inserting a row whose key already exists in the table fails if the table
+    /// has a unique constraint on the key columns, or creates a duplicate row
+    /// otherwise.
+    #[default]
+    #[serde(rename = "insert")]
+    Insert,
+
+    /// New and modified rows are written with
+    /// `INSERT ... ON CONFLICT (<key>) DO UPDATE`, replacing any existing row
+    /// with the same key.
+    ///
+    /// The key columns are the columns of the index that the connector is
+    /// attached to.  The table must have a primary key or a unique constraint
+    /// on exactly these columns.
+    #[serde(rename = "upsert")]
+    Upsert,
+}
+
 /// Postgres output connector configuration.
 #[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
 pub struct PostgresWriterConfig {
@@ -21,4 +46,97 @@ pub struct PostgresWriterConfig {
 
     /// The table to write the output to.
     pub table: String,
+
+    /// Determines how inserts and updates are applied to the table.
+    ///
+    /// The default is `insert`.
+    #[serde(default)]
+    pub mode: PostgresWriteMode,
+
+    /// Table in which the connector records the last step written to `table`.
+    ///
+    /// Only used when the pipeline is configured with exactly-once fault
+    /// tolerance.  The connector updates this table in the same transaction
+    /// as the output of each step, so that after a restart it can skip steps
+    /// that were already written.  The table is created if it doesn't exist,
+    /// and can be shared by multiple connectors.
+    ///
+    /// The default is `feldera_output_progress`.
+    #[serde(default = "default_progress_table")]
+    pub progress_table: String,
+}
+
+fn default_progress_table() -> String {
+    "feldera_output_progress".to_string()
+}
+
+/// Postgres CDC input connector configuration.
+///
+/// The connector reads an initial snapshot of a Postgres table and then
+/// follows changes to the table through a logical replication slot using
+/// the built-in `pgoutput` plugin.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct PostgresCdcReaderConfig {
+    /// Postgres URI.
+    /// See: <https://docs.rs/tokio-postgres/0.7.12/tokio_postgres/config/struct.Config.html>
+    pub uri: String,
+
+    /// The table to replicate, optionally qualified with a schema name,
+    /// e.g., `public.orders`.
+    ///
+    /// When the schema is not specified, `public` is assumed.
+    pub table: String,
+
+    /// Name of the publication to subscribe to.
+    ///
+    /// The publication must include `table`, e.g.,
+    /// `CREATE PUBLICATION feldera_pub FOR TABLE orders`.
+    pub publication: String,
+
+    /// Name of the logical replication slot used by the connector.
+    ///
+    /// The slot must not be shared with other consumers.
+    pub slot: String,
+
+    /// Create the replication slot if it does not exist.
+    ///
+    /// The default is `true`.
+    #[serde(default = "default_true")]
+    pub create_slot: bool,
+
+    /// Ingest a consistent snapshot of the table before streaming changes.
+    ///
+    /// When `false`, the connector only ingests changes committed after the
+    /// replication slot's current position.
+    ///
+    /// The default is `true`.
+    #[serde(default = "default_true")]
+    pub snapshot: bool,
+
+    /// How often to poll the replication slot for new changes, in
+    /// milliseconds.
+    ///
+    /// The default is 1000.
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+
+    /// Maximum number of changes to fetch from the replication slot in one
+    /// poll.  The connector always fetches complete transactions, so it may
+    /// fetch more changes than this to reach the end of a transaction.
+    ///
+    /// The default is 10,000.
+    #[serde(default = "default_max_changes_per_poll")]
+    pub max_changes_per_poll: u32,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+const fn default_poll_interval_ms() -> u64 {
+    1000
+}
+
+const fn default_max_changes_per_poll() -> u32 {
+    10_000
 }
@@ -61,3 +61,89 @@ pub struct PubSubInputConfig {
     /// This option is mutually exclusive with the `snapshot` option.
     pub timestamp: Option<String>,
 }
+
+/// Google Pub/Sub output connector configuration.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct PubSubOutputConfig {
+    /// Set in order to use a Pub/Sub [emulator](https://cloud.google.com/pubsub/docs/emulator)
+    /// instead of the production service, e.g., 'localhost:8681'.
+    pub emulator: Option<String>,
+
+    /// The content of a Google Cloud credentials JSON file.
+    ///
+    /// When this option is specified, the connector will use the provided credentials for
+    /// authentication.  Otherwise, it will use Application Default Credentials (ADC) configured
+    /// in the environment where the Feldera service is running.
+    pub credentials: Option<String>,
+
+    /// Override the default service endpoint 'pubsub.googleapis.com'
+    pub endpoint: Option<String>,
+
+    /// gRPC channel pool size.
+    pub pool_size: Option<u32>,
+
+    /// gRPC request timeout.
+    pub timeout_seconds: Option<u32>,
+
+    /// gRPC connection timeout.
+    pub connect_timeout_seconds: Option<u32>,
+
+    /// Google Cloud project_id.
+    ///
+    /// When not specified, the connector will use the project id associated
+    /// with the authenticated account.
+    pub project_id: Option<String>,
+
+    /// Topic name.  The topic must exist.
+    pub topic: String,
+
+    /// Columns of the view whose values form the ordering key of each message.
+    ///
+    /// Messages with the same ordering key are delivered to subscribers that
+    /// enable message ordering in the order in which they were published.
+    /// The values of the columns are converted to strings and joined with `:`.
+    ///
+    /// When this option or `attribute_fields` is set, the connector publishes
+    /// one message per record.  Supported with the `json` and `avro` formats.
+    pub ordering_key_fields: Option<Vec<String>>,
+
+    /// Columns of the view whose values are attached to each message as
+    /// attributes, using column names as attribute names.
+    ///
+    /// Attributes can be used to filter messages in a subscription.  `NULL`
+    /// values are omitted.  Supported with the `json` and `avro` formats.
+    #[serde(default)]
+    pub attribute_fields: Vec<String>,
+
+    /// Maximum number of messages in a batch sent to the Pub/Sub service
+    /// in a single request.
+    ///
+    /// The default is 100.
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+
+    /// Maximum time, in milliseconds, that a message waits to be included in a
+    /// batch before the batch is sent.
+    ///
+    /// The default is 100.
+    #[serde(default = "default_batch_delay_ms")]
+    pub batch_delay_ms: u64,
+
+    /// Number of concurrent publisher tasks, each with its own gRPC channel.
+    ///
+    /// The default is 3.
+    #[serde(default = "default_workers")]
+    pub workers: usize,
+}
+
+const fn default_batch_size() -> usize {
+    100
+}
+
+const fn default_batch_delay_ms() -> u64 {
+    100
+}
+
+const fn default_workers() -> usize {
+    3
+}
@@ -16,3 +16,83 @@ pub struct RedisOutputConfig {
 fn default_separator() -> String {
     ":".to_string()
 }
+
+/// Redis Streams input connector configuration.
+///
+/// The connector reads entries from a stream as a member of a consumer group
+/// (using `XREADGROUP`).
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct RedisInputConfig {
+    /// The URL format: `redis://[<username>][:<password>@]<hostname>[:port][/[<db>][?protocol=<protocol>]]`
+    /// This is parsed by the [redis](https://docs.rs/redis/latest/redis/#connection-parameters) crate.
+    pub connection_string: String,
+
+    /// Key of the stream to read from.
+    pub stream: String,
+
+    /// Name of the consumer group used to read the stream.
+    ///
+    /// The connector creates the group (and the stream) if it doesn't exist.
+    pub consumer_group: String,
+
+    /// Name of the consumer within the consumer group.
+    ///
+    /// The name must not change across pipeline restarts: after a restart,
+    /// the connector re-reads entries that were delivered to this consumer
+    /// but not yet acknowledged.
+    ///
+    /// The default is `feldera`.
+    #[serde(default = "default_consumer_name")]
+    pub consumer_name: String,
+
+    /// Id of the first entry to read when the connector creates the
+    /// consumer group: `0` to read the entire stream, or `$` to read only
+    /// entries added after the group was created.
+    ///
+    /// Ignored if the consumer group already exists.
+    ///
+    /// The default is `0`.
+    #[serde(default = "default_start_id")]
+    pub start_id: String,
+
+    /// Name of the field that contains the payload of each entry.
+    ///
+    /// When set, the value of this field is parsed using the format
+    /// configured for the connector (e.g., JSON or Avro), and the other
+    /// fields of the entry are ignored.
+    ///
+    /// When not set, the field/value pairs of each entry are mapped to the
+    /// columns of the table with the same names.  The connector converts
+    /// the entry to a JSON object, so it must be configured with the `json`
+    /// format and the `raw` update format.
+    pub payload_field: Option<String>,
+
+    /// Maximum number of entries to read with each `XREADGROUP` command.
+    ///
+    /// The default is 1000.
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+
+    /// Maximum time, in milliseconds, that `XREADGROUP` waits for new
+    /// entries when the stream has none.
+    ///
+    /// The default is 100.
+    #[serde(default = "default_block_ms")]
+    pub block_ms: usize,
+}
+
+fn default_consumer_name() -> String {
+    "feldera".to_string()
+}
+
+fn default_start_id() -> String {
+    "0".to_string()
+}
+
+const fn default_batch_size() -> usize {
+    1000
+}
+
+const fn default_block_ms() -> usize {
+    100
+}
@@ -1,3 +1,4 @@
+use crate::transport::compression::Compression;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -42,4 +43,11 @@ pub struct S3InputConfig {
     /// Recommended range: 1–10. Default: 8.
     #[serde(default = "default_max_concurrent_fetches")]
     pub max_concurrent_fetches: u32,
+
+    /// Compression of the objects.
+    ///
+    /// With `auto`, the compression format is detected separately for each
+    /// object, based on its key or its first few bytes.  The default is `none`.
+    #[serde(default)]
+    pub compression: Compression,
 }
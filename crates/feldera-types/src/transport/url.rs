@@ -1,3 +1,4 @@
+use crate::transport::compression::Compression;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -17,6 +18,13 @@ pub struct UrlInputConfig {
     /// automatically reconnect when the input adapter starts running again.
     #[serde(default = "default_pause_timeout")]
     pub pause_timeout: u32,
+
+    /// Compression of the data at the URL.
+    ///
+    /// When set, the connector decompresses the data before passing it to
+    /// the parser.  The default is `none`.
+    #[serde(default)]
+    pub compression: Compression,
 }
 
 const fn default_pause_timeout() -> u32 {
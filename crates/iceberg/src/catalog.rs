@@ -0,0 +1,298 @@
+//! Access to Iceberg tables via the catalog configured for the connector.
+//!
+//! Shared by the input and output connectors.
+
+use anyhow::{anyhow, Error as AnyError};
+use feldera_adapterlib::errors::journal::ControllerError;
+use feldera_types::transport::iceberg::{IcebergCatalogConfig, IcebergCatalogType};
+use iceberg::{io::FileIO, spec::TableMetadata, table::Table as IcebergTable, Catalog, TableIdent};
+use iceberg_catalog_glue::{
+    GlueCatalog, GlueCatalogConfig, AWS_ACCESS_KEY_ID, AWS_PROFILE_NAME, AWS_REGION_NAME,
+    AWS_SECRET_ACCESS_KEY, AWS_SESSION_TOKEN,
+};
+use iceberg_catalog_rest::{RestCatalog, RestCatalogConfig};
+use log::debug;
+use std::sync::Arc;
+
+/// Constructor of a transport error: `ControllerError::input_transport_error`
+/// or `ControllerError::output_transport_error`, depending on the connector type.
+pub(crate) type TransportErrorFn = fn(&str, bool, AnyError) -> ControllerError;
+
+/// Opens Iceberg tables using connector configuration.
+pub(crate) struct CatalogClient<'a> {
+    endpoint_name: &'a str,
+    config: &'a IcebergCatalogConfig,
+    transport_error: TransportErrorFn,
+}
+
+impl<'a> CatalogClient<'a> {
+    pub(crate) fn new(
+        endpoint_name: &'a str,
+        config: &'a IcebergCatalogConfig,
+        transport_error: TransportErrorFn,
+    ) -> Self {
+        Self {
+            endpoint_name,
+            config,
+            transport_error,
+        }
+    }
+
+    pub(crate) fn table_ident(&self) -> Option<Result<TableIdent, ControllerError>> {
+        self.config.table_name.as_ref().map(|table_name| {
+            TableIdent::from_strs(table_name.split('.')).map_err(|e| {
+                ControllerError::invalid_transport_configuration(
+                    self.endpoint_name,
+                    &format!("'table_name' property specifies an invalid Iceberg table name: {e}"),
+                )
+            })
+        })
+    }
+
+    /// Open existing iceberg table.
+    pub(crate) async fn open_table(&self) -> Result<IcebergTable, ControllerError> {
+        debug!("iceberg {}: opening iceberg table", self.endpoint_name);
+
+        match self.catalog().await? {
+            None => self.open_table_no_catalog().await,
+            Some(catalog) => {
+                let table_ident = self.table_ident().unwrap()?;
+
+                catalog.load_table(&table_ident).await.map_err(|e| {
+                    (self.transport_error)(
+                        self.endpoint_name,
+                        true,
+                        anyhow!("error loading Iceberg table: {e}"),
+                    )
+                })
+            }
+        }
+    }
+
+    /// Create a client for the catalog specified in the connector configuration.
+    ///
+    /// Returns `None` if the table is accessed directly via its metadata file.
+    pub(crate) async fn catalog(&self) -> Result<Option<Arc<dyn Catalog>>, ControllerError> {
+        match self.config.catalog_type {
+            None => Ok(None),
+            Some(IcebergCatalogType::Glue) => Ok(Some(Arc::new(self.glue_catalog().await?))),
+            Some(IcebergCatalogType::Rest) => Ok(Some(Arc::new(self.rest_catalog()))),
+        }
+    }
+
+    /// Create a `FileIO` object for a table or metadata file at `location`.
+    pub(crate) fn file_io(&self, location: &str) -> Result<FileIO, ControllerError> {
+        FileIO::from_path(location)
+            .map_err(|e| {
+                ControllerError::invalid_transport_configuration(
+                    self.endpoint_name,
+                    &format!("invalid 'metadata_location' value: {e}"),
+                )
+            })?
+            .with_props(&self.config.fileio_config)
+            .build()
+            .map_err(|e| {
+                ControllerError::invalid_transport_configuration(
+                    self.endpoint_name,
+                    &format!("invalid storage configuration: {e}"),
+                )
+            })
+    }
+
+    /// Read and parse table metadata file.
+    pub(crate) async fn read_metadata(
+        &self,
+        file_io: &FileIO,
+        metadata_location: &str,
+    ) -> Result<TableMetadata, ControllerError> {
+        let metadata_file = file_io.new_input(metadata_location).map_err(|e| {
+            ControllerError::invalid_transport_configuration(
+                self.endpoint_name,
+                &format!("error opening metadata file at '{metadata_location}': {e}"),
+            )
+        })?;
+        let metadata_content = metadata_file.read().await.map_err(|e| {
+            ControllerError::invalid_transport_configuration(
+                self.endpoint_name,
+                &format!("error reading metadatafile '{metadata_location}': {e}"),
+            )
+        })?;
+        serde_json::from_slice::<TableMetadata>(&metadata_content).map_err(|e| {
+            ControllerError::invalid_transport_configuration(
+                self.endpoint_name,
+                &format!("error parsing table metadata: {e}"),
+            )
+        })
+    }
+
+    /// Build a table object from metadata stored at `metadata_location`.
+    pub(crate) fn table_from_metadata(
+        &self,
+        file_io: FileIO,
+        metadata_location: &str,
+        metadata: TableMetadata,
+    ) -> Result<IcebergTable, ControllerError> {
+        let table_ident = TableIdent::from_strs(["default", "table"]).unwrap();
+
+        IcebergTable::builder()
+            .file_io(file_io)
+            .metadata_location(metadata_location)
+            .metadata(metadata)
+            .identifier(table_ident)
+            .build()
+            .map_err(|e| {
+                ControllerError::invalid_transport_configuration(
+                    self.endpoint_name,
+                    &format!("error configuring Iceberg table: {e}"),
+                )
+            })
+    }
+
+    async fn open_table_no_catalog(&self) -> Result<IcebergTable, ControllerError> {
+        // Safe due to checks in 'validate'.
+        let metadata_location = self.config.metadata_location.as_ref().unwrap();
+
+        let file_io = self.file_io(metadata_location)?;
+        let metadata = self.read_metadata(&file_io, metadata_location).await?;
+
+        self.table_from_metadata(file_io, metadata_location, metadata)
+    }
+
+    async fn glue_catalog(&self) -> Result<GlueCatalog, ControllerError> {
+        let builder = GlueCatalogConfig::builder().warehouse(
+            self.config
+                .glue_catalog_config
+                .warehouse
+                .as_ref()
+                .unwrap()
+                .clone(),
+        );
+
+        let builder = builder.catalog_id_opt(self.config.glue_catalog_config.id.clone());
+        let builder = builder.uri_opt(self.config.glue_catalog_config.endpoint.clone());
+
+        let mut props = self.config.fileio_config.clone();
+
+        self.config
+            .glue_catalog_config
+            .access_key_id
+            .as_ref()
+            .map(|aws_access_key_id| {
+                props.insert(AWS_ACCESS_KEY_ID.to_string(), aws_access_key_id.clone())
+            });
+
+        self.config
+            .glue_catalog_config
+            .secret_access_key
+            .as_ref()
+            .map(|aws_secret_access_key| {
+                props.insert(
+                    AWS_SECRET_ACCESS_KEY.to_string(),
+                    aws_secret_access_key.clone(),
+                )
+            });
+
+        self.config
+            .glue_catalog_config
+            .session_token
+            .as_ref()
+            .map(|session_token| {
+                props.insert(AWS_SESSION_TOKEN.to_string(), session_token.clone())
+            });
+
+        self.config
+            .glue_catalog_config
+            .profile_name
+            .as_ref()
+            .map(|profile_name| props.insert(AWS_PROFILE_NAME.to_string(), profile_name.clone()));
+
+        self.config
+            .glue_catalog_config
+            .region
+            .as_ref()
+            .map(|region_name| props.insert(AWS_REGION_NAME.to_string(), region_name.clone()));
+
+        let builder = builder.props(props);
+
+        let catalog_config = builder.build();
+
+        GlueCatalog::new(catalog_config).await.map_err(|e| {
+            (self.transport_error)(
+                self.endpoint_name,
+                true,
+                anyhow!("error creating Glue catalog client: {e}"),
+            )
+        })
+    }
+
+    fn rest_catalog(&self) -> RestCatalog {
+        let builder = RestCatalogConfig::builder().uri(
+            self.config
+                .rest_catalog_config
+                .uri
+                .as_ref()
+                .unwrap()
+                .clone(),
+        );
+
+        let builder = builder.warehouse_opt(self.config.rest_catalog_config.warehouse.clone());
+
+        let mut props = self.config.fileio_config.clone();
+
+        self.config
+            .rest_catalog_config
+            .audience
+            .as_ref()
+            .map(|audience| props.insert("audience".to_string(), audience.clone()));
+
+        self.config
+            .rest_catalog_config
+            .resource
+            .as_ref()
+            .map(|resource| props.insert("resource".to_string(), resource.clone()));
+
+        self.config
+            .rest_catalog_config
+            .credential
+            .as_ref()
+            .map(|credential| props.insert("credential".to_string(), credential.clone()));
+
+        self.config
+            .rest_catalog_config
+            .oauth2_server_uri
+            .as_ref()
+            .map(|oauth2_server_uri| {
+                props.insert("oauth2-server-uri".to_string(), oauth2_server_uri.clone())
+            });
+
+        self.config
+            .rest_catalog_config
+            .prefix
+            .as_ref()
+            .map(|prefix| props.insert("prefix".to_string(), prefix.clone()));
+
+        self.config
+            .rest_catalog_config
+            .scope
+            .as_ref()
+            .map(|scope| props.insert("scope".to_string(), scope.clone()));
+
+        self.config
+            .rest_catalog_config
+            .token
+            .as_ref()
+            .map(|token| props.insert("token".to_string(), token.clone()));
+
+        if let Some(headers) = &self.config.rest_catalog_config.headers {
+            for (header, val) in headers.iter() {
+                props.insert(format!("header.{header}"), val.clone());
+            }
+        };
+
+        let builder = builder.props(props);
+
+        let catalog_config = builder.build();
+
+        RestCatalog::new(catalog_config)
+    }
+}
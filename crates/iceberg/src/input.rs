@@ -1,3 +1,4 @@
+use crate::catalog::CatalogClient;
 use crate::iceberg_input_serde_config;
 use anyhow::{anyhow, bail, Error as AnyError, Result as AnyResult};
 use chrono::{DateTime, Utc};
@@ -21,17 +22,10 @@ use feldera_adapterlib::{
     PipelineState,
 };
 use feldera_types::{
-    config::FtModel,
-    program_schema::Relation,
-    transport::iceberg::{IcebergCatalogType, IcebergReaderConfig},
+    config::FtModel, program_schema::Relation, transport::iceberg::IcebergReaderConfig,
 };
 use futures_util::StreamExt;
-use iceberg::{io::FileIO, spec::TableMetadata, table::Table as IcebergTable, Catalog, TableIdent};
-use iceberg_catalog_glue::{
-    GlueCatalog, GlueCatalogConfig, AWS_ACCESS_KEY_ID, AWS_PROFILE_NAME, AWS_REGION_NAME,
-    AWS_SECRET_ACCESS_KEY, AWS_SESSION_TOKEN,
-};
-use iceberg_catalog_rest::{RestCatalog, RestCatalogConfig};
+use iceberg::table::Table as IcebergTable;
 use iceberg_datafusion::IcebergTableProvider;
 use log::{debug, info, trace};
 use std::sync::Arc;
@@ -104,7 +98,8 @@ impl IcebergInputReader {
         // TODO: perform validation as part of config deserialization.
         endpoint
             .config
-            .validate_catalog_config()
+            .catalog_config
+            .validate()
             .map_err(|e| anyhow!(e))?;
 
         if endpoint.config.follow() {
@@ -186,17 +181,6 @@ impl IcebergInputEndpointInner {
         }
     }
 
-    fn table_ident(&self) -> Option<Result<TableIdent, ControllerError>> {
-        self.config.table_name.as_ref().map(|table_name| {
-            TableIdent::from_strs(table_name.split('.')).map_err(|e| {
-                ControllerError::invalid_transport_configuration(
-                    &self.endpoint_name,
-                    &format!("'table_name' property specifies an invalid Iceberg table name: {e}"),
-                )
-            })
-        })
-    }
-
     fn snapshot_descr(&self) -> Result<SnapshotDescr, ControllerError> {
         match &self.config {
             IcebergReaderConfig {
@@ -435,232 +419,15 @@ impl IcebergInputEndpointInner {
         self.consumer.eoi();
     }
 
-    /// Open existing iceberg table.  Use snapshot id or timestamp specified in the configuration, if any.
+    /// Open existing iceberg table.
     async fn open_table(&self) -> Result<IcebergTable, ControllerError> {
-        debug!("iceberg {}: opening iceberg table", &self.endpoint_name);
-
-        match self.config.catalog_type {
-            None => self.open_table_no_catalog().await,
-            Some(IcebergCatalogType::Glue) => self.open_table_glue().await,
-            Some(IcebergCatalogType::Rest) => self.open_table_rest().await,
-        }
-
-        // // TODO: Validate that table schema matches relation schema
-
-        // // TODO: Validate that timestamp is a valid column.
-    }
-
-    async fn open_table_no_catalog(&self) -> Result<IcebergTable, ControllerError> {
-        // Safe due to checks in 'validate_catalog_config'.
-        let metadata_location = self.config.metadata_location.as_ref().unwrap();
-
-        let file_io = FileIO::from_path(metadata_location)
-            .map_err(|e| {
-                ControllerError::invalid_transport_configuration(
-                    &self.endpoint_name,
-                    &format!("invalid 'metadata_location' value: {e}"),
-                )
-            })?
-            .with_props(&self.config.fileio_config)
-            .build()
-            .map_err(|e| {
-                ControllerError::invalid_transport_configuration(
-                    &self.endpoint_name,
-                    &format!("invalid storage configuration: {e}"),
-                )
-            })?;
-
-        let metadata_file = file_io.new_input(metadata_location).map_err(|e| {
-            ControllerError::invalid_transport_configuration(
-                &self.endpoint_name,
-                &format!("error opening metadata file at '{metadata_location}': {e}"),
-            )
-        })?;
-        let metadata_content = metadata_file.read().await.map_err(|e| {
-            ControllerError::invalid_transport_configuration(
-                &self.endpoint_name,
-                &format!("error reading metadatafile '{metadata_location}': {e}"),
-            )
-        })?;
-        let metadata = serde_json::from_slice::<TableMetadata>(&metadata_content).map_err(|e| {
-            ControllerError::invalid_transport_configuration(
-                &self.endpoint_name,
-                &format!("error parsing table metadata: {e}"),
-            )
-        })?;
-
-        let table_ident = TableIdent::from_strs(["default", "table"]).unwrap();
-
-        IcebergTable::builder()
-            .file_io(file_io)
-            .metadata_location(metadata_location)
-            .metadata(metadata)
-            .identifier(table_ident)
-            .build()
-            .map_err(|e| {
-                ControllerError::invalid_transport_configuration(
-                    &self.endpoint_name,
-                    &format!("error configuring Iceberg table: {e}"),
-                )
-            })
-    }
-
-    async fn open_table_glue(&self) -> Result<IcebergTable, ControllerError> {
-        let builder = GlueCatalogConfig::builder().warehouse(
-            self.config
-                .glue_catalog_config
-                .warehouse
-                .as_ref()
-                .unwrap()
-                .clone(),
-        );
-
-        let builder = builder.catalog_id_opt(self.config.glue_catalog_config.id.clone());
-        let builder = builder.uri_opt(self.config.glue_catalog_config.endpoint.clone());
-
-        let mut props = self.config.fileio_config.clone();
-
-        self.config
-            .glue_catalog_config
-            .access_key_id
-            .as_ref()
-            .map(|aws_access_key_id| {
-                props.insert(AWS_ACCESS_KEY_ID.to_string(), aws_access_key_id.clone())
-            });
-
-        self.config
-            .glue_catalog_config
-            .secret_access_key
-            .as_ref()
-            .map(|aws_secret_access_key| {
-                props.insert(
-                    AWS_SECRET_ACCESS_KEY.to_string(),
-                    aws_secret_access_key.clone(),
-                )
-            });
-
-        self.config
-            .glue_catalog_config
-            .session_token
-            .as_ref()
-            .map(|session_token| {
-                props.insert(AWS_SESSION_TOKEN.to_string(), session_token.clone())
-            });
-
-        self.config
-            .glue_catalog_config
-            .profile_name
-            .as_ref()
-            .map(|profile_name| props.insert(AWS_PROFILE_NAME.to_string(), profile_name.clone()));
-
-        self.config
-            .glue_catalog_config
-            .region
-            .as_ref()
-            .map(|region_name| props.insert(AWS_REGION_NAME.to_string(), region_name.clone()));
-
-        let builder = builder.props(props);
-
-        let catalog_config = builder.build();
-
-        let catalog = GlueCatalog::new(catalog_config).await.map_err(|e| {
-            ControllerError::input_transport_error(
-                &self.endpoint_name,
-                true,
-                anyhow!("error creating Glue catalog client: {e}"),
-            )
-        })?;
-
-        let table_ident = self.table_ident().unwrap()?;
-
-        catalog.load_table(&table_ident).await.map_err(|e| {
-            ControllerError::input_transport_error(
-                &self.endpoint_name,
-                true,
-                anyhow!("error loading Iceberg table: {e}"),
-            )
-        })
-    }
-
-    async fn open_table_rest(&self) -> Result<IcebergTable, ControllerError> {
-        let builder = RestCatalogConfig::builder().uri(
-            self.config
-                .rest_catalog_config
-                .uri
-                .as_ref()
-                .unwrap()
-                .clone(),
-        );
-
-        let builder = builder.warehouse_opt(self.config.rest_catalog_config.warehouse.clone());
-
-        let mut props = self.config.fileio_config.clone();
-
-        self.config
-            .rest_catalog_config
-            .audience
-            .as_ref()
-            .map(|audience| props.insert("audience".to_string(), audience.clone()));
-
-        self.config
-            .rest_catalog_config
-            .resource
-            .as_ref()
-            .map(|resource| props.insert("resource".to_string(), resource.clone()));
-
-        self.config
-            .rest_catalog_config
-            .credential
-            .as_ref()
-            .map(|credential| props.insert("credential".to_string(), credential.clone()));
-
-        self.config
-            .rest_catalog_config
-            .oauth2_server_uri
-            .as_ref()
-            .map(|oauth2_server_uri| {
-                props.insert("oauth2-server-uri".to_string(), oauth2_server_uri.clone())
-            });
-
-        self.config
-            .rest_catalog_config
-            .prefix
-            .as_ref()
-            .map(|prefix| props.insert("prefix".to_string(), prefix.clone()));
-
-        self.config
-            .rest_catalog_config
-            .scope
-            .as_ref()
-            .map(|scope| props.insert("scope".to_string(), scope.clone()));
-
-        self.config
-            .rest_catalog_config
-            .token
-            .as_ref()
-            .map(|token| props.insert("token".to_string(), token.clone()));
-
-        if let Some(headers) = &self.config.rest_catalog_config.headers {
-            for (header, val) in headers.iter() {
-                props.insert(format!("header.{header}"), val.clone());
-            }
-        };
-
-        let builder = builder.props(props);
-
-        let catalog_config = builder.build();
-
-        let catalog = RestCatalog::new(catalog_config);
-
-        let table_ident = self.table_ident().unwrap()?;
-
-        catalog.load_table(&table_ident).await.map_err(|e| {
-            ControllerError::input_transport_error(
-                &self.endpoint_name,
-                true,
-                anyhow!("error loading Iceberg table: {e}"),
-            )
-        })
+        CatalogClient::new(
+            &self.endpoint_name,
+            &self.config.catalog_config,
+            ControllerError::input_transport_error,
+        )
+        .open_table()
+        .await
     }
 
     /// Validate the filter expression specified in the 'snapshot_filter' parameter.
@@ -1,9 +1,14 @@
+mod catalog;
 mod input;
+mod output;
+mod schema;
 
 pub use input::IcebergInputEndpoint;
+pub use output::{IcebergOutputEndpoint, OutputStatsCallback};
 
 use feldera_types::serde_with_context::{
-    serde_config::DecimalFormat, DateFormat, SqlSerdeConfig, TimestampFormat,
+    serde_config::{BinaryFormat, DecimalFormat, UuidFormat, VariantFormat},
+    DateFormat, SqlSerdeConfig, TimeFormat, TimestampFormat,
 };
 
 pub fn iceberg_input_serde_config() -> SqlSerdeConfig {
@@ -20,3 +25,18 @@ pub fn iceberg_input_serde_config() -> SqlSerdeConfig {
         .with_date_format(DateFormat::DaysSinceEpoch)
         .with_decimal_format(DecimalFormat::String)
 }
+
+/// Serde config used to encode records written to Iceberg tables.
+///
+/// Matches the Arrow representation of Iceberg types, as produced by
+/// `iceberg::arrow::schema_to_arrow_schema`.
+pub fn iceberg_output_serde_config() -> SqlSerdeConfig {
+    SqlSerdeConfig::default()
+        .with_timestamp_format(TimestampFormat::MicrosSinceEpoch)
+        .with_time_format(TimeFormat::Micros)
+        .with_date_format(DateFormat::DaysSinceEpoch)
+        .with_decimal_format(DecimalFormat::String)
+        .with_variant_format(VariantFormat::JsonString)
+        .with_binary_format(BinaryFormat::Bytes)
+        .with_uuid_format(UuidFormat::Binary)
+}
@@ -0,0 +1,1067 @@
+use crate::catalog::CatalogClient;
+use crate::iceberg_output_serde_config;
+use crate::schema::{is_equality_field_type, relation_to_iceberg_schema, validate_table_schema};
+use anyhow::{anyhow, bail, Error as AnyError, Result as AnyResult};
+use arrow::array::RecordBatch;
+use arrow::datatypes::Schema as ArrowSchema;
+use bytes::Bytes;
+use chrono::Utc;
+use dbsp::circuit::tokio::TOKIO;
+use feldera_adapterlib::{
+    catalog::{CursorWithPolarity, RecordFormat, SerBatchReader},
+    errors::journal::ControllerError,
+    format::{Encoder, OutputConsumer, MAX_DUPLICATES},
+    transport::{AsyncErrorCallback, OutputEndpoint, Step},
+    utils::indexed::indexed_operation_type,
+};
+use feldera_types::{
+    program_schema::{Relation, SqlIdentifier},
+    transport::iceberg::IcebergWriterConfig,
+};
+use iceberg::{
+    arrow::{arrow_schema_to_schema, schema_to_arrow_schema},
+    io::FileIO,
+    spec::{
+        DataFile, DataFileFormat, FormatVersion, Manifest, ManifestContentType, ManifestEntry,
+        ManifestFile, ManifestListWriter, ManifestMetadata, ManifestStatus, ManifestWriter,
+        Operation, SchemaRef, Snapshot, SnapshotReference, SnapshotRetention, Summary,
+        TableMetadata, TableMetadataBuilder, MAIN_BRANCH,
+    },
+    table::Table as IcebergTable,
+    writer::{
+        base_writer::{
+            data_file_writer::{DataFileWriter, DataFileWriterBuilder},
+            equality_delete_writer::{
+                EqualityDeleteFileWriter, EqualityDeleteFileWriterBuilder,
+                EqualityDeleteWriterConfig,
+            },
+        },
+        file_writer::{
+            location_generator::{DefaultFileNameGenerator, DefaultLocationGenerator},
+            ParquetWriterBuilder,
+        },
+        IcebergWriter, IcebergWriterBuilder,
+    },
+    Catalog, TableCommit, TableCreation, TableRequirement, TableUpdate,
+};
+use log::{debug, info, trace};
+use parquet::file::properties::WriterProperties;
+use serde_arrow::{schema::SerdeArrowSchema, ArrayBuilder};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use uuid::Uuid;
+
+/// Callback used to report the number of bytes and records written to the
+/// table after each commit.
+pub type OutputStatsCallback = Box<dyn Fn(usize, usize) + Send + Sync>;
+
+/// Limit on the number of records buffered in memory in the encoder.
+static CHUNK_SIZE: usize = 1_000_000;
+
+/// File in the metadata directory of a table without a catalog that stores
+/// the version of the latest metadata file (`v<version>.metadata.json`).
+const VERSION_HINT_FILE: &str = "version-hint.text";
+
+type ParquetWriter = ParquetWriterBuilder<DefaultLocationGenerator, DefaultFileNameGenerator>;
+
+/// Commands sent to the tokio runtime that performs the actual
+/// Iceberg table operations.
+enum Command {
+    BatchStart(Step),
+    Insert(RecordBatch),
+    Delete(RecordBatch),
+    BatchEnd,
+}
+
+struct IcebergOutputEndpointInner {
+    endpoint_name: String,
+    config: IcebergWriterConfig,
+    view_name: SqlIdentifier,
+    /// Name of the index, if the connector is configured with one.
+    index_name: Option<SqlIdentifier>,
+    key_schema: Option<Relation>,
+    schema: Relation,
+    stats_callback: OutputStatsCallback,
+}
+
+/// Iceberg output connector.
+///
+/// Writes inserted records to Parquet data files and deleted records to
+/// equality delete files, and commits the files written during each step
+/// as a new snapshot of the table.
+pub struct IcebergOutputEndpoint {
+    inner: Arc<IcebergOutputEndpointInner>,
+    /// Schema of the record batches produced by the encoder.
+    serde_arrow_schema: SerdeArrowSchema,
+    command_sender: Sender<Command>,
+    response_receiver: Receiver<Result<(), (AnyError, bool)>>,
+    error_callback: AsyncErrorCallback,
+}
+
+impl IcebergOutputEndpoint {
+    pub fn new(
+        endpoint_name: &str,
+        config: &IcebergWriterConfig,
+        key_schema: &Option<Relation>,
+        schema: &Relation,
+        error_callback: AsyncErrorCallback,
+        stats_callback: OutputStatsCallback,
+    ) -> Result<Self, ControllerError> {
+        config
+            .catalog_config
+            .validate()
+            .map_err(|e| ControllerError::invalid_transport_configuration(endpoint_name, &e))?;
+
+        let inner = Arc::new(IcebergOutputEndpointInner {
+            endpoint_name: endpoint_name.to_string(),
+            config: config.clone(),
+            view_name: schema.name.clone(),
+            index_name: key_schema
+                .as_ref()
+                .map(|key_schema| key_schema.name.clone()),
+            key_schema: key_schema.clone(),
+            schema: schema.clone(),
+            stats_callback,
+        });
+        let inner_clone = inner.clone();
+
+        let (command_sender, command_receiver) = channel::<Command>(1);
+        let (response_sender, response_receiver) = channel::<Result<(), (AnyError, bool)>>(1);
+        let (init_sender, mut init_receiver) = channel::<AnyResult<Arc<ArrowSchema>>>(1);
+
+        // Start tokio runtime.
+        std::thread::spawn(move || {
+            TOKIO.block_on(async {
+                Self::worker_task(inner_clone, command_receiver, response_sender, init_sender).await
+            })
+        });
+
+        let arrow_schema = init_receiver
+            .blocking_recv()
+            .ok_or_else(|| {
+                ControllerError::output_transport_error(
+                    endpoint_name,
+                    true,
+                    anyhow!("worker thread terminated unexpectedly during initialization"),
+                )
+            })?
+            .map_err(|e| ControllerError::output_transport_error(endpoint_name, true, e))?;
+
+        let serde_arrow_schema = SerdeArrowSchema::try_from(arrow_schema.fields().as_ref())
+            .map_err(|e| ControllerError::SchemaParseError {
+                error: format!("Unable to convert Iceberg table schema to arrow: {e}"),
+            })?;
+
+        Ok(Self {
+            inner,
+            serde_arrow_schema,
+            command_sender,
+            response_receiver,
+            error_callback,
+        })
+    }
+
+    fn command(&mut self, command: Command) -> Result<(), (AnyError, bool)> {
+        self.command_sender
+            .blocking_send(command)
+            .map_err(|_| (anyhow!("worker thread terminated unexpectedly"), true))?;
+        self.response_receiver
+            .blocking_recv()
+            .ok_or_else(|| (anyhow!("worker thread terminated unexpectedly"), true))?
+    }
+
+    /// Send records accumulated in `builder` to the worker.
+    fn send_record_batch(&mut self, builder: &mut ArrayBuilder, delete: bool) -> AnyResult<()> {
+        let batch = builder
+            .to_record_batch()
+            .map_err(|e| anyhow!("error generating arrow arrays: {e}"))?;
+        let command = if delete {
+            Command::Delete(batch)
+        } else {
+            Command::Insert(batch)
+        };
+        match self.command(command) {
+            Ok(()) => Ok(()),
+            Err((e, false)) => Err(e),
+            // Fatal errors are reported via the error callback, which stops the pipeline.
+            Err((e, true)) => {
+                (self.error_callback)(true, e);
+                Ok(())
+            }
+        }
+    }
+
+    async fn worker_task(
+        inner: Arc<IcebergOutputEndpointInner>,
+        mut command_receiver: Receiver<Command>,
+        response_sender: Sender<Result<(), (AnyError, bool)>>,
+        init_sender: Sender<AnyResult<Arc<ArrowSchema>>>,
+    ) {
+        let mut task = match WriterTask::create(inner.clone()).await {
+            Ok(task) => {
+                let _ = init_sender.send(Ok(task.arrow_schema.clone())).await;
+                task
+            }
+            Err(e) => {
+                let _ = init_sender
+                    .send(Err(anyhow!("error creating or opening Iceberg table: {e}")))
+                    .await;
+                return;
+            }
+        };
+
+        loop {
+            let result = match command_receiver.recv().await {
+                Some(Command::BatchStart(step)) => {
+                    task.batch_start(step);
+                    Ok(())
+                }
+                Some(Command::Insert(batch)) => task.insert(batch).await.map_err(|e| (e, false)),
+                Some(Command::Delete(batch)) => task.delete(batch).await,
+                Some(Command::BatchEnd) => task.batch_end().await.map_err(|e| (e, false)),
+                None => {
+                    trace!(
+                        "iceberg {}: endpoint is shutting down",
+                        &inner.endpoint_name
+                    );
+                    return;
+                }
+            };
+            let fatal = matches!(result, Err((_, true)));
+            // Ignore closed channel, we'll handle it at the next loop iteration.
+            let _ = response_sender.send(result).await;
+
+            // Don't commit any more changes after a fatal error, since the
+            // table would no longer reflect the contents of the view.
+            if fatal {
+                return;
+            }
+        }
+    }
+}
+
+struct WriterTask {
+    inner: Arc<IcebergOutputEndpointInner>,
+    /// `None` if the table is accessed directly via its metadata file.
+    catalog: Option<Arc<dyn Catalog>>,
+    table: IcebergTable,
+    /// Directory where the metadata files of a table without a catalog are stored.
+    metadata_dir: String,
+    /// Version of the metadata file `table` was loaded from (tables without a catalog only).
+    metadata_version: u64,
+    arrow_schema: Arc<ArrowSchema>,
+    /// Ids of the columns that identify deleted rows in equality delete files.
+    /// `None` if the table doesn't support deletes.
+    equality_ids: Option<Vec<i32>>,
+    data_writer: Option<DataFileWriter<ParquetWriter>>,
+    delete_writer: Option<EqualityDeleteFileWriter<ParquetWriter>>,
+    step: Step,
+    num_rows: usize,
+}
+
+impl WriterTask {
+    async fn create(inner: Arc<IcebergOutputEndpointInner>) -> AnyResult<Self> {
+        let client = CatalogClient::new(
+            &inner.endpoint_name,
+            &inner.config.catalog_config,
+            ControllerError::output_transport_error,
+        );
+
+        let catalog = client.catalog().await?;
+        let metadata_location = inner.config.catalog_config.metadata_location.as_deref();
+        let metadata_dir = metadata_location
+            .map(|location| parent_dir(location).to_string())
+            .unwrap_or_default();
+
+        let (table, metadata_version) = match &catalog {
+            None => open_or_create_file_table(&inner, &client, &metadata_dir).await?,
+            Some(catalog) => (
+                open_or_create_catalog_table(&inner, &client, catalog.as_ref()).await?,
+                0,
+            ),
+        };
+
+        let metadata = table.metadata();
+        if !metadata.default_partition_spec().is_unpartitioned() {
+            bail!("writing to partitioned Iceberg tables is not supported");
+        }
+
+        let table_schema = metadata.current_schema();
+        validate_table_schema(&inner.schema, table_schema)?;
+
+        let arrow_schema = Arc::new(
+            schema_to_arrow_schema(table_schema)
+                .map_err(|e| anyhow!("error converting Iceberg schema to arrow: {e}"))?,
+        );
+
+        // Rows are deleted by key when the connector is configured with an index
+        // and by value otherwise.
+        let equality_ids = match &inner.key_schema {
+            Some(key_schema) => Some(
+                key_schema
+                    .fields
+                    .iter()
+                    .map(|f| {
+                        let name = f.name.name();
+                        match table_schema.field_by_name(&name) {
+                            Some(field) if is_equality_field_type(&field.field_type) => {
+                                Ok(field.id)
+                            }
+                            Some(_) => Err(anyhow!("index column '{name}' has a type that cannot be used to identify rows in an Iceberg equality delete file; only primitive non-floating point columns are supported")),
+                            None => Err(anyhow!("index column '{name}' not found in the Iceberg table")),
+                        }
+                    })
+                    .collect::<AnyResult<Vec<_>>>()?,
+            ),
+            None => table_schema
+                .as_struct()
+                .fields()
+                .iter()
+                .map(|field| is_equality_field_type(&field.field_type).then_some(field.id))
+                .collect::<Option<Vec<_>>>(),
+        };
+
+        Ok(Self {
+            inner,
+            catalog,
+            table,
+            metadata_dir,
+            metadata_version,
+            arrow_schema,
+            equality_ids,
+            data_writer: None,
+            delete_writer: None,
+            step: 0,
+            num_rows: 0,
+        })
+    }
+
+    fn batch_start(&mut self, step: Step) {
+        trace!(
+            "iceberg {}: starting step {step}",
+            &self.inner.endpoint_name
+        );
+        self.step = step;
+        self.num_rows = 0;
+    }
+
+    async fn insert(&mut self, batch: RecordBatch) -> AnyResult<()> {
+        let batch = batch
+            .with_schema(self.arrow_schema.clone())
+            .map_err(|e| anyhow!("record batch doesn't match the table schema: {e}"))?;
+
+        if self.data_writer.is_none() {
+            let schema = self.table.metadata().current_schema().clone();
+            let writer = DataFileWriterBuilder::new(self.parquet_writer(schema, "data")?, None)
+                .build()
+                .await
+                .map_err(|e| anyhow!("error creating Parquet data file writer: {e}"))?;
+            self.data_writer = Some(writer);
+        }
+
+        self.num_rows += batch.num_rows();
+        self.data_writer
+            .as_mut()
+            .unwrap()
+            .write(batch)
+            .await
+            .map_err(|e| anyhow!("error writing Parquet data file: {e}"))
+    }
+
+    /// Write deleted records to an equality delete file.
+    ///
+    /// Returns a fatal error if the table doesn't support deletes.
+    async fn delete(&mut self, batch: RecordBatch) -> Result<(), (AnyError, bool)> {
+        let Some(equality_ids) = self.equality_ids.clone() else {
+            return Err((anyhow!("view '{}' contains deletions, but its columns cannot be used to identify rows in an Iceberg equality delete file, which only supports primitive non-floating point columns; configure the connector with an 'index' over a subset of columns that uniquely identifies rows in the view", self.inner.view_name), true));
+        };
+
+        if self.table.metadata().format_version() == FormatVersion::V1 {
+            return Err((anyhow!("view '{}' contains deletions, but Iceberg format version 1 tables don't support delete files", self.inner.view_name), true));
+        }
+
+        self.write_deletes(equality_ids, batch)
+            .await
+            .map_err(|e| (e, false))
+    }
+
+    async fn write_deletes(&mut self, equality_ids: Vec<i32>, batch: RecordBatch) -> AnyResult<()> {
+        let batch = batch
+            .with_schema(self.arrow_schema.clone())
+            .map_err(|e| anyhow!("record batch doesn't match the table schema: {e}"))?;
+
+        if self.delete_writer.is_none() {
+            let config = EqualityDeleteWriterConfig::new(
+                equality_ids,
+                self.table.metadata().current_schema().clone(),
+                None,
+            )
+            .map_err(|e| anyhow!("error configuring equality delete writer: {e}"))?;
+            let delete_schema = Arc::new(
+                arrow_schema_to_schema(config.projected_arrow_schema_ref())
+                    .map_err(|e| anyhow!("error generating equality delete file schema: {e}"))?,
+            );
+            let writer = EqualityDeleteFileWriterBuilder::new(
+                self.parquet_writer(delete_schema, "eq-delete")?,
+                config,
+            )
+            .build()
+            .await
+            .map_err(|e| anyhow!("error creating equality delete file writer: {e}"))?;
+            self.delete_writer = Some(writer);
+        }
+
+        self.num_rows += batch.num_rows();
+        self.delete_writer
+            .as_mut()
+            .unwrap()
+            .write(batch)
+            .await
+            .map_err(|e| anyhow!("error writing equality delete file: {e}"))
+    }
+
+    fn parquet_writer(&self, schema: SchemaRef, kind: &str) -> AnyResult<ParquetWriter> {
+        let location_generator = DefaultLocationGenerator::new(self.table.metadata().clone())
+            .map_err(|e| anyhow!("error determining the location of data files: {e}"))?;
+        let file_name_generator = DefaultFileNameGenerator::new(
+            format!("feldera-{kind}-{}", Uuid::new_v4()),
+            None,
+            DataFileFormat::Parquet,
+        );
+
+        Ok(ParquetWriterBuilder::new(
+            WriterProperties::builder().build(),
+            schema,
+            self.table.file_io().clone(),
+            location_generator,
+            file_name_generator,
+        ))
+    }
+
+    async fn batch_end(&mut self) -> AnyResult<()> {
+        let data_files = match self.data_writer.take() {
+            Some(mut writer) => writer
+                .close()
+                .await
+                .map_err(|e| anyhow!("error flushing {} Parquet rows: {e}", self.num_rows))?,
+            None => Vec::new(),
+        };
+        let delete_files = match self.delete_writer.take() {
+            Some(mut writer) => writer
+                .close()
+                .await
+                .map_err(|e| anyhow!("error flushing equality delete file: {e}"))?,
+            None => Vec::new(),
+        };
+
+        if data_files.is_empty() && delete_files.is_empty() {
+            return Ok(());
+        }
+
+        trace!(
+            "iceberg {}: committing {} data files and {} delete files for step {}",
+            &self.inner.endpoint_name,
+            data_files.len(),
+            delete_files.len(),
+            self.step
+        );
+
+        let num_bytes = data_files
+            .iter()
+            .chain(delete_files.iter())
+            .map(|file| file.file_size_in_bytes() as usize)
+            .sum();
+
+        let snapshot = self.write_snapshot(data_files, delete_files).await?;
+        match self.catalog.clone() {
+            Some(catalog) => self.commit_to_catalog(catalog.as_ref(), snapshot).await?,
+            None => self.commit_to_metadata_file(snapshot).await?,
+        }
+
+        (self.inner.stats_callback)(num_bytes, self.num_rows);
+
+        Ok(())
+    }
+
+    /// Write the manifests and manifest list of a new snapshot that adds
+    /// `data_files` and `delete_files` to the current snapshot of the table.
+    async fn write_snapshot(
+        &self,
+        data_files: Vec<DataFile>,
+        delete_files: Vec<DataFile>,
+    ) -> AnyResult<Snapshot> {
+        let metadata = self.table.metadata();
+        let file_io = self.table.file_io();
+
+        let snapshot_id = new_snapshot_id(metadata);
+        let parent_snapshot_id = metadata.current_snapshot_id();
+        let sequence_number = metadata.next_sequence_number();
+        let commit_uuid = Uuid::new_v4();
+        let manifest_dir = format!("{}/metadata", metadata.location());
+
+        let operation = if delete_files.is_empty() {
+            Operation::Append
+        } else {
+            Operation::Overwrite
+        };
+
+        // Manifests added by this snapshot, followed by the manifests of the parent snapshot.
+        let mut manifests = Vec::new();
+        for (files, content) in [
+            (data_files, ManifestContentType::Data),
+            (delete_files, ManifestContentType::Deletes),
+        ] {
+            if files.is_empty() {
+                continue;
+            }
+            let path = format!("{manifest_dir}/{commit_uuid}-m{}.avro", manifests.len());
+            manifests
+                .push(write_manifest(metadata, file_io, &path, snapshot_id, content, files).await?);
+        }
+
+        if let Some(parent) = metadata.current_snapshot() {
+            let manifest_list = parent
+                .load_manifest_list(file_io, metadata)
+                .await
+                .map_err(|e| anyhow!("error reading manifest list of the current snapshot: {e}"))?;
+            manifests.extend(manifest_list.entries().iter().cloned());
+        }
+
+        let manifest_list_path = format!("{manifest_dir}/snap-{snapshot_id}-0-{commit_uuid}.avro");
+        let manifest_list_output = file_io
+            .new_output(&manifest_list_path)
+            .map_err(|e| anyhow!("error creating manifest list file: {e}"))?;
+        let mut manifest_list_writer = if metadata.format_version() == FormatVersion::V1 {
+            ManifestListWriter::v1(manifest_list_output, snapshot_id, parent_snapshot_id)
+        } else {
+            ManifestListWriter::v2(
+                manifest_list_output,
+                snapshot_id,
+                parent_snapshot_id,
+                sequence_number,
+            )
+        };
+        manifest_list_writer
+            .add_manifests(manifests.into_iter())
+            .map_err(|e| anyhow!("error writing manifest list: {e}"))?;
+        manifest_list_writer
+            .close()
+            .await
+            .map_err(|e| anyhow!("error writing manifest list: {e}"))?;
+
+        Ok(Snapshot::builder()
+            .with_snapshot_id(snapshot_id)
+            .with_parent_snapshot_id(parent_snapshot_id)
+            .with_sequence_number(sequence_number)
+            .with_timestamp_ms(Utc::now().timestamp_millis())
+            .with_manifest_list(manifest_list_path)
+            .with_schema_id(metadata.current_schema_id())
+            .with_summary(Summary {
+                operation,
+                additional_properties: HashMap::new(),
+            })
+            .build())
+    }
+
+    /// Commit a new snapshot through the catalog.
+    ///
+    /// The commit adds the snapshot and makes it the head of the main branch
+    /// in a single catalog transaction, which fails if another writer has
+    /// committed to the table since we loaded it.
+    async fn commit_to_catalog(
+        &mut self,
+        catalog: &dyn Catalog,
+        snapshot: Snapshot,
+    ) -> AnyResult<()> {
+        let metadata = self.table.metadata();
+        let snapshot_id = snapshot.snapshot_id();
+
+        let commit = TableCommit::builder()
+            .ident(self.table.identifier().clone())
+            .updates(vec![
+                TableUpdate::AddSnapshot { snapshot },
+                TableUpdate::SetSnapshotRef {
+                    ref_name: MAIN_BRANCH.to_string(),
+                    reference: SnapshotReference::new(
+                        snapshot_id,
+                        SnapshotRetention::branch(None, None, None),
+                    ),
+                },
+            ])
+            .requirements(vec![
+                TableRequirement::UuidMatch {
+                    uuid: metadata.uuid(),
+                },
+                TableRequirement::RefSnapshotIdMatch {
+                    r#ref: MAIN_BRANCH.to_string(),
+                    snapshot_id: metadata.current_snapshot_id(),
+                },
+            ])
+            .build();
+
+        self.table = catalog
+            .update_table(commit)
+            .await
+            .map_err(|e| anyhow!("error committing changes to the Iceberg table: {e}"))?;
+
+        debug!(
+            "iceberg {}: committed snapshot {snapshot_id}",
+            &self.inner.endpoint_name
+        );
+
+        Ok(())
+    }
+
+    /// Commit a new snapshot to a table without a catalog.
+    ///
+    /// Writes a new metadata file, `v<version+1>.metadata.json`, next to the
+    /// current one and records its version in `version-hint.text`.  The connector
+    /// assumes that it is the only writer to the table.
+    async fn commit_to_metadata_file(&mut self, snapshot: Snapshot) -> AnyResult<()> {
+        let metadata = self.table.metadata();
+        let file_io = self.table.file_io();
+        let snapshot_id = snapshot.snapshot_id();
+
+        let new_metadata = TableMetadataBuilder::new_from_metadata(
+            metadata.clone(),
+            self.table.metadata_location().map(str::to_string),
+        )
+        .add_snapshot(snapshot)
+        .and_then(|builder| {
+            builder.set_ref(
+                MAIN_BRANCH,
+                SnapshotReference::new(snapshot_id, SnapshotRetention::branch(None, None, None)),
+            )
+        })
+        .and_then(|builder| builder.build())
+        .map_err(|e| anyhow!("error updating table metadata: {e}"))?
+        .metadata;
+
+        let new_version = self.metadata_version + 1;
+        let new_metadata_location = format!("{}/v{new_version}.metadata.json", self.metadata_dir);
+        write_metadata(file_io, &new_metadata_location, &new_metadata).await?;
+        write_file(
+            file_io,
+            &format!("{}/{VERSION_HINT_FILE}", self.metadata_dir),
+            new_version.to_string().into_bytes(),
+        )
+        .await?;
+
+        debug!(
+            "iceberg {}: committed snapshot {snapshot_id} ({new_metadata_location})",
+            &self.inner.endpoint_name
+        );
+
+        self.table = CatalogClient::new(
+            &self.inner.endpoint_name,
+            &self.inner.config.catalog_config,
+            ControllerError::output_transport_error,
+        )
+        .table_from_metadata(file_io.clone(), &new_metadata_location, new_metadata)?;
+        self.metadata_version = new_version;
+
+        Ok(())
+    }
+}
+
+/// Open the table at the configured metadata location; create a new table
+/// if the metadata file doesn't exist.
+///
+/// If the metadata directory contains a version hint left by a previous run
+/// of the connector (or another writer that follows the same convention), the
+/// latest version of the table is opened instead.
+///
+/// Returns the table and the version of its metadata file.
+async fn open_or_create_file_table(
+    inner: &IcebergOutputEndpointInner,
+    client: &CatalogClient<'_>,
+    metadata_dir: &str,
+) -> AnyResult<(IcebergTable, u64)> {
+    // Safe due to checks in 'validate'.
+    let configured_location = inner
+        .config
+        .catalog_config
+        .metadata_location
+        .as_ref()
+        .unwrap();
+    let file_io = client.file_io(configured_location)?;
+
+    let version_hint_location = format!("{metadata_dir}/{VERSION_HINT_FILE}");
+    let (metadata_location, version) = if exists(&file_io, &version_hint_location).await? {
+        let hint = file_io
+            .new_input(&version_hint_location)
+            .map_err(|e| anyhow!("error opening '{version_hint_location}': {e}"))?
+            .read()
+            .await
+            .map_err(|e| anyhow!("error reading '{version_hint_location}': {e}"))?;
+        let version = std::str::from_utf8(&hint)
+            .ok()
+            .and_then(|hint| hint.trim().parse::<u64>().ok())
+            .ok_or_else(|| {
+                anyhow!("'{version_hint_location}' doesn't contain a valid version number")
+            })?;
+        (format!("{metadata_dir}/v{version}.metadata.json"), version)
+    } else {
+        (
+            configured_location.clone(),
+            metadata_version(configured_location).unwrap_or(0),
+        )
+    };
+
+    if exists(&file_io, &metadata_location).await? {
+        info!(
+            "iceberg {}: opening table at '{metadata_location}'",
+            &inner.endpoint_name
+        );
+        let metadata = client.read_metadata(&file_io, &metadata_location).await?;
+        let table = client.table_from_metadata(file_io, &metadata_location, metadata)?;
+        return Ok((table, version));
+    }
+
+    // Tables are stored in '<table location>/metadata/'.
+    let table_location = metadata_dir
+        .strip_suffix("/metadata")
+        .unwrap_or(metadata_dir)
+        .to_string();
+
+    info!(
+        "iceberg {}: creating table at '{table_location}'",
+        &inner.endpoint_name
+    );
+
+    let creation = TableCreation::builder()
+        .name(inner.view_name.name())
+        .location(table_location)
+        .schema(relation_to_iceberg_schema(&inner.schema)?)
+        .build();
+    let metadata = TableMetadataBuilder::from_table_creation(creation)
+        .and_then(|builder| builder.build())
+        .map_err(|e| anyhow!("error creating table metadata: {e}"))?
+        .metadata;
+    write_metadata(&file_io, &metadata_location, &metadata).await?;
+
+    let table = client.table_from_metadata(file_io, &metadata_location, metadata)?;
+    Ok((table, version))
+}
+
+/// Open the table in the catalog; create a new table if it doesn't exist.
+async fn open_or_create_catalog_table(
+    inner: &IcebergOutputEndpointInner,
+    client: &CatalogClient<'_>,
+    catalog: &dyn Catalog,
+) -> AnyResult<IcebergTable> {
+    // Safe due to checks in 'validate'.
+    let table_ident = client.table_ident().unwrap()?;
+
+    let table_exists = catalog
+        .table_exists(&table_ident)
+        .await
+        .map_err(|e| anyhow!("error looking up table '{table_ident}' in the catalog: {e}"))?;
+
+    if table_exists {
+        return Ok(client.open_table().await?);
+    }
+
+    info!(
+        "iceberg {}: creating table '{table_ident}'",
+        &inner.endpoint_name
+    );
+
+    let creation = TableCreation::builder()
+        .name(table_ident.name().to_string())
+        .schema(relation_to_iceberg_schema(&inner.schema)?)
+        .build();
+
+    catalog
+        .create_table(table_ident.namespace(), creation)
+        .await
+        .map_err(|e| anyhow!("error creating table '{table_ident}': {e}"))
+}
+
+/// Write a manifest file listing `files`, all of which are added by snapshot `snapshot_id`.
+async fn write_manifest(
+    metadata: &TableMetadata,
+    file_io: &FileIO,
+    path: &str,
+    snapshot_id: i64,
+    content: ManifestContentType,
+    files: Vec<DataFile>,
+) -> AnyResult<ManifestFile> {
+    let entries = files
+        .into_iter()
+        .map(|data_file| {
+            // The sequence number is inherited from the manifest list entry.
+            ManifestEntry::builder()
+                .status(ManifestStatus::Added)
+                .snapshot_id(snapshot_id)
+                .data_file(data_file)
+                .build()
+        })
+        .collect::<Vec<_>>();
+
+    let manifest_metadata = ManifestMetadata::builder()
+        .schema(metadata.current_schema().clone())
+        .schema_id(metadata.current_schema_id())
+        .format_version(metadata.format_version())
+        .partition_spec(metadata.default_partition_spec().as_ref().clone())
+        .content(content)
+        .build();
+
+    let output = file_io
+        .new_output(path)
+        .map_err(|e| anyhow!("error creating manifest file '{path}': {e}"))?;
+
+    ManifestWriter::new(output, snapshot_id, vec![])
+        .write(Manifest::new(manifest_metadata, entries))
+        .await
+        .map_err(|e| anyhow!("error writing manifest file '{path}': {e}"))
+}
+
+async fn write_metadata(
+    file_io: &FileIO,
+    location: &str,
+    metadata: &TableMetadata,
+) -> AnyResult<()> {
+    let json = serde_json::to_vec(metadata)
+        .map_err(|e| anyhow!("error serializing table metadata: {e}"))?;
+    write_file(file_io, location, json).await
+}
+
+async fn write_file(file_io: &FileIO, location: &str, content: Vec<u8>) -> AnyResult<()> {
+    file_io
+        .new_output(location)
+        .map_err(|e| anyhow!("error creating '{location}': {e}"))?
+        .write(Bytes::from(content))
+        .await
+        .map_err(|e| anyhow!("error writing '{location}': {e}"))
+}
+
+async fn exists(file_io: &FileIO, location: &str) -> AnyResult<bool> {
+    file_io
+        .exists(location)
+        .await
+        .map_err(|e| anyhow!("error accessing '{location}': {e}"))
+}
+
+fn parent_dir(location: &str) -> &str {
+    location
+        .rsplit_once('/')
+        .map(|(dir, _file)| dir)
+        .unwrap_or_default()
+}
+
+/// Extract `N` from a metadata file name of the form `v<N>.metadata.json`.
+fn metadata_version(location: &str) -> Option<u64> {
+    location
+        .rsplit('/')
+        .next()?
+        .strip_prefix('v')?
+        .strip_suffix(".metadata.json")?
+        .parse()
+        .ok()
+}
+
+/// Generate a snapshot id that isn't used by the table yet.
+fn new_snapshot_id(metadata: &TableMetadata) -> i64 {
+    loop {
+        let (hi, lo) = Uuid::new_v4().as_u64_pair();
+        let snapshot_id = ((hi ^ lo) & i64::MAX as u64) as i64;
+        if metadata.snapshot_by_id(snapshot_id).is_none() {
+            return snapshot_id;
+        }
+    }
+}
+
+impl OutputConsumer for IcebergOutputEndpoint {
+    fn max_buffer_size_bytes(&self) -> usize {
+        usize::MAX
+    }
+
+    fn batch_start(&mut self, step: Step) {
+        self.command(Command::BatchStart(step))
+            .unwrap_or_else(|(e, fatal)| (self.error_callback)(fatal, e));
+    }
+
+    fn push_buffer(&mut self, _buffer: &[u8], _num_records: usize) {
+        unreachable!()
+    }
+
+    fn push_key(
+        &mut self,
+        _key: Option<&[u8]>,
+        _val: Option<&[u8]>,
+        _headers: &[(&str, Option<&[u8]>)],
+        _num_records: usize,
+    ) {
+        unreachable!()
+    }
+
+    fn batch_end(&mut self) {
+        self.command(Command::BatchEnd)
+            .unwrap_or_else(|(e, fatal)| (self.error_callback)(fatal, e));
+    }
+}
+
+/// Record batches being accumulated by the encoder.
+struct Builders {
+    inserts: ArrayBuilder,
+    num_inserts: usize,
+    deletes: ArrayBuilder,
+    num_deletes: usize,
+}
+
+impl IcebergOutputEndpoint {
+    fn builders(&self) -> AnyResult<Builders> {
+        Ok(Builders {
+            inserts: ArrayBuilder::new(self.serde_arrow_schema.clone())?,
+            num_inserts: 0,
+            deletes: ArrayBuilder::new(self.serde_arrow_schema.clone())?,
+            num_deletes: 0,
+        })
+    }
+
+    /// Split the batch into chunks to limit the amount of memory used by the builders.
+    fn flush_full(&mut self, builders: &mut Builders) -> AnyResult<()> {
+        if builders.num_inserts >= CHUNK_SIZE {
+            self.send_record_batch(&mut builders.inserts, false)?;
+            builders.num_inserts = 0;
+        }
+        if builders.num_deletes >= CHUNK_SIZE {
+            self.send_record_batch(&mut builders.deletes, true)?;
+            builders.num_deletes = 0;
+        }
+        Ok(())
+    }
+
+    fn flush_all(&mut self, builders: &mut Builders) -> AnyResult<()> {
+        if builders.num_deletes > 0 {
+            self.send_record_batch(&mut builders.deletes, true)?;
+        }
+        if builders.num_inserts > 0 {
+            self.send_record_batch(&mut builders.inserts, false)?;
+        }
+        Ok(())
+    }
+
+    /// Encode a batch of updates to an indexed view.
+    ///
+    /// Deletes and updates delete the old value of the key (the equality delete
+    /// only uses the key columns); inserts and updates write the new value.
+    fn encode_indexed(&mut self, batch: &dyn SerBatchReader) -> AnyResult<()> {
+        let mut builders = self.builders()?;
+        let index_name = self.inner.index_name.clone().unwrap();
+
+        let mut cursor = batch.cursor(RecordFormat::Parquet(iceberg_output_serde_config()))?;
+        while cursor.key_valid() {
+            if indexed_operation_type(&self.inner.view_name, &index_name, cursor.as_mut())?
+                .is_some()
+            {
+                cursor.rewind_vals();
+                while cursor.val_valid() {
+                    match cursor.weight() {
+                        1 => {
+                            cursor.serialize_val_to_arrow(&mut builders.inserts)?;
+                            builders.num_inserts += 1;
+                        }
+                        -1 => {
+                            cursor.serialize_val_to_arrow(&mut builders.deletes)?;
+                            builders.num_deletes += 1;
+                        }
+                        _ => (),
+                    }
+                    cursor.step_val();
+                }
+                self.flush_full(&mut builders)?;
+            }
+            cursor.step_key();
+        }
+
+        self.flush_all(&mut builders)
+    }
+}
+
+impl Encoder for IcebergOutputEndpoint {
+    fn consumer(&mut self) -> &mut dyn OutputConsumer {
+        self
+    }
+
+    fn encode(&mut self, batch: &dyn SerBatchReader) -> AnyResult<()> {
+        if self.inner.index_name.is_some() {
+            return self.encode_indexed(batch);
+        }
+
+        let mut builders = self.builders()?;
+
+        let mut cursor = CursorWithPolarity::new(
+            batch.cursor(RecordFormat::Parquet(iceberg_output_serde_config()))?,
+        );
+        while cursor.key_valid() {
+            if !cursor.val_valid() {
+                cursor.step_key();
+                continue;
+            }
+            let w = cursor.weight();
+            if !(-MAX_DUPLICATES..=MAX_DUPLICATES).contains(&w) {
+                bail!("Unable to output record with very large weight {w}. Consider adjusting your SQL queries to avoid duplicate output records, e.g., using 'SELECT DISTINCT'.");
+            }
+
+            if w > 0 {
+                for _ in 0..w {
+                    cursor.serialize_key_to_arrow(&mut builders.inserts)?;
+                    builders.num_inserts += 1;
+                    self.flush_full(&mut builders)?;
+                }
+            } else if w < 0 {
+                // An equality delete removes all rows that match the record,
+                // so it can only express deleting every copy of the record.
+                // Deleting some of the copies of a duplicate record would
+                // silently delete the others from the table too.
+                if w < -1 {
+                    bail!("Unable to delete {} copies of a record: Iceberg equality deletes remove all copies of a record, so the connector can only delete records that occur once in the view. Consider adjusting your SQL queries to avoid duplicate output records, e.g., using 'SELECT DISTINCT', or configuring the connector with an 'index'.", -w);
+                }
+                cursor.serialize_key_to_arrow(&mut builders.deletes)?;
+                builders.num_deletes += 1;
+                self.flush_full(&mut builders)?;
+            }
+            cursor.step_key();
+        }
+
+        self.flush_all(&mut builders)
+    }
+}
+
+impl OutputEndpoint for IcebergOutputEndpoint {
+    fn connect(&mut self, async_error_callback: AsyncErrorCallback) -> AnyResult<()> {
+        self.error_callback = async_error_callback;
+        Ok(())
+    }
+
+    fn max_buffer_size_bytes(&self) -> usize {
+        usize::MAX
+    }
+
+    fn batch_start(&mut self, _step: Step) -> AnyResult<()> {
+        unreachable!()
+    }
+
+    fn push_buffer(&mut self, _buffer: &[u8]) -> AnyResult<()> {
+        unreachable!()
+    }
+
+    fn push_key(
+        &mut self,
+        _key: Option<&[u8]>,
+        _val: Option<&[u8]>,
+        _headers: &[(&str, Option<&[u8]>)],
+    ) -> AnyResult<()> {
+        unreachable!()
+    }
+
+    fn batch_end(&mut self) -> AnyResult<()> {
+        unreachable!()
+    }
+
+    fn is_fault_tolerant(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,154 @@
+//! Conversion between SQL relation schemas and Iceberg table schemas.
+
+use anyhow::{anyhow, bail, Result as AnyResult};
+use feldera_types::program_schema::{ColumnType, Field, Relation, SqlType};
+use iceberg::spec::{
+    ListType, MapType, NestedField, NestedFieldRef, PrimitiveType, Schema, StructType, Type,
+};
+use std::sync::Arc;
+
+/// Generates unique field ids for a new Iceberg schema.
+struct FieldIds(i32);
+
+impl FieldIds {
+    fn next(&mut self) -> i32 {
+        self.0 += 1;
+        self.0
+    }
+}
+
+/// Generate the schema of a new Iceberg table that stores the contents of `relation`.
+pub(crate) fn relation_to_iceberg_schema(relation: &Relation) -> AnyResult<Schema> {
+    let mut ids = FieldIds(0);
+    let fields = fields_to_iceberg(&relation.fields, &mut ids)?;
+
+    Schema::builder()
+        .with_fields(fields)
+        .build()
+        .map_err(|e| anyhow!("error creating Iceberg schema for '{}': {e}", relation.name))
+}
+
+/// Check that an existing Iceberg table can store the contents of `relation`.
+///
+/// The table must have the same columns as the relation, in the same order.
+/// Column types are not compared: values are converted to the types of the
+/// table when encoding them, and incompatible values are reported at that point.
+pub(crate) fn validate_table_schema(relation: &Relation, schema: &Schema) -> AnyResult<()> {
+    let table_columns = schema
+        .as_struct()
+        .fields()
+        .iter()
+        .map(|f| f.name.clone())
+        .collect::<Vec<_>>();
+    let view_columns = relation
+        .fields
+        .iter()
+        .map(|f| f.name.name())
+        .collect::<Vec<_>>();
+
+    if table_columns != view_columns {
+        bail!(
+            "the columns of the Iceberg table ({}) don't match the columns of the view ({})",
+            table_columns.join(", "),
+            view_columns.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns `true` if a column of type `typ` can be used to identify rows in
+/// an equality delete file.
+///
+/// The Iceberg spec only allows primitive, non-floating point columns.
+pub(crate) fn is_equality_field_type(typ: &Type) -> bool {
+    matches!(typ, Type::Primitive(p) if !matches!(p, PrimitiveType::Float | PrimitiveType::Double))
+}
+
+fn fields_to_iceberg(fields: &[Field], ids: &mut FieldIds) -> AnyResult<Vec<NestedFieldRef>> {
+    // Like other Iceberg implementations, assign ids to all fields at the
+    // current nesting level before descending into nested types.
+    let field_ids = fields.iter().map(|_| ids.next()).collect::<Vec<_>>();
+
+    fields
+        .iter()
+        .zip(field_ids)
+        .map(|(f, id)| {
+            let typ = columntype_to_iceberg(&f.columntype, ids)
+                .map_err(|e| anyhow!("column '{}': {e}", f.name))?;
+            Ok(Arc::new(NestedField::new(
+                id,
+                f.name.name(),
+                typ,
+                !f.columntype.nullable,
+            )))
+        })
+        .collect()
+}
+
+fn columntype_to_iceberg(c: &ColumnType, ids: &mut FieldIds) -> AnyResult<Type> {
+    let typ = match c.typ {
+        SqlType::Boolean => Type::Primitive(PrimitiveType::Boolean),
+        SqlType::TinyInt | SqlType::SmallInt | SqlType::Int => Type::Primitive(PrimitiveType::Int),
+        SqlType::BigInt => Type::Primitive(PrimitiveType::Long),
+        SqlType::Real => Type::Primitive(PrimitiveType::Float),
+        SqlType::Double => Type::Primitive(PrimitiveType::Double),
+        SqlType::Decimal => Type::Primitive(PrimitiveType::Decimal {
+            precision: c.precision.unwrap_or(0).try_into()?,
+            scale: c.scale.unwrap_or(0).try_into()?,
+        }),
+        SqlType::Char | SqlType::Varchar | SqlType::Variant => {
+            Type::Primitive(PrimitiveType::String)
+        }
+        SqlType::Binary | SqlType::Varbinary => Type::Primitive(PrimitiveType::Binary),
+        SqlType::Time => Type::Primitive(PrimitiveType::Time),
+        SqlType::Date => Type::Primitive(PrimitiveType::Date),
+        SqlType::Timestamp => Type::Primitive(PrimitiveType::Timestamp),
+        SqlType::Uuid => Type::Primitive(PrimitiveType::Uuid),
+        SqlType::Array => {
+            let element = c
+                .component
+                .as_ref()
+                .ok_or_else(|| anyhow!("ARRAY type without an element type"))?;
+            let id = ids.next();
+            let element_type = columntype_to_iceberg(element, ids)?;
+            Type::List(ListType {
+                element_field: Arc::new(NestedField::list_element(
+                    id,
+                    element_type,
+                    !element.nullable,
+                )),
+            })
+        }
+        SqlType::Map => {
+            let key = c
+                .key
+                .as_ref()
+                .ok_or_else(|| anyhow!("MAP type without a key type"))?;
+            let value = c
+                .value
+                .as_ref()
+                .ok_or_else(|| anyhow!("MAP type without a value type"))?;
+            let key_id = ids.next();
+            let value_id = ids.next();
+            let key_type = columntype_to_iceberg(key, ids)?;
+            let value_type = columntype_to_iceberg(value, ids)?;
+            Type::Map(MapType {
+                key_field: Arc::new(NestedField::map_key_element(key_id, key_type)),
+                value_field: Arc::new(NestedField::map_value_element(
+                    value_id,
+                    value_type,
+                    !value.nullable,
+                )),
+            })
+        }
+        SqlType::Struct => Type::Struct(StructType::new(fields_to_iceberg(
+            c.fields.as_deref().unwrap_or_default(),
+            ids,
+        )?)),
+        SqlType::Interval(_) => bail!("INTERVAL type is not supported by Iceberg"),
+        SqlType::Null => bail!("NULL type is not supported by Iceberg"),
+    };
+
+    Ok(typ)
+}
@@ -138,7 +138,9 @@ pub(crate) async fn http_input(
         ("array" = Option<bool>, Query, description = "Set to `true` to group updates in this stream into JSON arrays (used in conjunction with `format=json`). The default value is `false`"),
         ("backpressure" = Option<bool>, Query, description = r#"Apply backpressure on the pipeline when the HTTP client cannot receive data fast enough.
         When this flag is set to false (the default), the HTTP connector drops data chunks if the client is not keeping up with its output.  This prevents a slow HTTP client from slowing down the entire pipeline.
-        When the flag is set to true, the connector waits for the client to receive each chunk and blocks the pipeline if the client cannot keep up."#)
+        When the flag is set to true, the connector waits for the client to receive each chunk and blocks the pipeline if the client cannot keep up."#),
+        ("mode" = Option<EgressMode>, Query, description = r#"Set to `snapshot_and_follow` to start with the current contents of a materialized table or view, followed by all subsequent changes.
+        Chunks are then tagged with the step that produced them; the snapshot is tagged with the step that it reflects.  The default value is `watch_changes`, which only sends changes."#),
     ),
     responses(
         (status = OK
@@ -204,6 +206,154 @@ pub(crate) async fn http_output(
         .await
 }
 
+/// Push data to a SQL table over a WebSocket connection.
+///
+/// The client sends messages with data encoded using the format specified in
+/// the `?format=` parameter.  Unlike the body of a `POST` request, which is a
+/// stream, each message must consist of complete records.
+///
+/// The pipeline responds to each message with an `IngressWebSocketResponse`,
+/// which contains either a completion token that can be passed to the
+/// '/completion_status' endpoint, or an error.  Responses are sent in the
+/// order in which the messages were received.
+#[utoipa::path(
+    context_path = "/v0",
+    security(("JSON web token (JWT) or API key" = [])),
+    params(
+        ("pipeline_name" = String, Path, description = "Unique pipeline name"),
+        ("table_name" = String, Path,
+            description = "SQL table name. Unquoted SQL names have to be capitalized. Quoted SQL names have to exactly match the case from the SQL program."),
+        ("force" = bool, Query, description = "When `true`, push data to the pipeline even if the pipeline is paused. The default value is `false`"),
+        ("format" = String, Query, description = "Input data format, e.g., 'csv' or 'json'."),
+        ("array" = Option<bool>, Query, description = "Set to `true` if updates in this stream are packaged into JSON arrays (used in conjunction with `format=json`). The default values is `false`."),
+        ("update_format" = Option<JsonUpdateFormat>, Query, description = "JSON data change event format (used in conjunction with `format=json`).  The default value is 'insert_delete'."),
+    ),
+    responses(
+        (status = SWITCHING_PROTOCOLS
+            , description = "WebSocket connection established. The pipeline sends an `IngressWebSocketResponse` for each message received from the client."
+            , body = IngressWebSocketResponse),
+        (status = NOT_FOUND
+            , body = ErrorResponse
+            , description = "Pipeline and/or table with that name does not exist"
+            , examples(
+                ("Pipeline with that name does not exist" = (value = json!(examples::error_unknown_pipeline_name()))),
+            )
+        ),
+        (status = BAD_REQUEST
+            , body = ErrorResponse
+            , description = "The request is not a WebSocket upgrade request"),
+        (status = SERVICE_UNAVAILABLE
+            , body = ErrorResponse
+            , examples(
+                ("Pipeline is not deployed" = (value = json!(examples::error_pipeline_interaction_not_deployed()))),
+                ("Pipeline is currently unavailable" = (value = json!(examples::error_pipeline_interaction_currently_unavailable()))),
+            )
+        ),
+        (status = INTERNAL_SERVER_ERROR, body = ErrorResponse),
+    ),
+    tag = "Pipeline interaction",
+)]
+#[get("/pipelines/{pipeline_name}/ingress/{table_name}")]
+pub(crate) async fn websocket_input(
+    state: WebData<ServerState>,
+    client: WebData<awc::Client>,
+    tenant_id: ReqData<TenantId>,
+    req: HttpRequest,
+    body: web::Payload,
+) -> Result<HttpResponse, ManagerError> {
+    let pipeline_name = parse_url_parameter(&req, "pipeline_name")?;
+    let table_name = parse_url_parameter(&req, "table_name")?;
+    let endpoint = format!("ingress/{table_name}");
+    state
+        .runner
+        .forward_websocket_request_to_pipeline_by_name(
+            client.as_ref(),
+            *tenant_id,
+            &pipeline_name,
+            &endpoint,
+            req,
+            body,
+        )
+        .await
+}
+
+/// Subscribe to a stream of updates from a SQL view or table over a WebSocket
+/// connection.
+///
+/// The pipeline sends an `EgressWebSocketMessage` for each chunk of changes
+/// to the specified table or view, encoded using the format specified in the
+/// `?format=` parameter.  A `step_end` message follows the changes produced by
+/// each step, and a `heartbeat` message is sent when the table or view has not
+/// changed for the heartbeat interval.
+///
+/// The pipeline continues sending updates until the client closes the
+/// connection or the pipeline is shut down.  Messages sent by the client are
+/// ignored.
+#[utoipa::path(
+    context_path = "/v0",
+    security(("JSON web token (JWT) or API key" = [])),
+    params(
+        ("pipeline_name" = String, Path, description = "Unique pipeline name"),
+        ("table_name" = String, Path,
+            description = "SQL table name. Unquoted SQL names have to be capitalized. Quoted SQL names have to exactly match the case from the SQL program."),
+        ("format" = String, Query, description = "Output data format, e.g., 'csv' or 'json'."),
+        ("array" = Option<bool>, Query, description = "Set to `true` to group updates in this stream into JSON arrays (used in conjunction with `format=json`). The default value is `false`"),
+        ("backpressure" = Option<bool>, Query, description = r#"Apply backpressure on the pipeline when the client cannot receive data fast enough.
+        When this flag is set to false (the default), the connector drops messages if the client is not keeping up with its output, which shows as a gap in message sequence numbers.
+        When the flag is set to true, the connector waits for the client to receive each message and blocks the pipeline if the client cannot keep up."#),
+        ("heartbeat_ms" = Option<u64>, Query, description = "Interval, in milliseconds, after which the pipeline sends a heartbeat message if the table or view has not changed. The default value is 3000."),
+        ("mode" = Option<EgressMode>, Query, description = r#"Set to `snapshot_and_follow` to start with the current contents of a materialized table or view, sent as the data messages of the step that the snapshot reflects, followed by all subsequent changes.
+        The default value is `watch_changes`, which only sends changes."#),
+    ),
+    responses(
+        (status = SWITCHING_PROTOCOLS
+            , description = "WebSocket connection established. The pipeline sends a stream of `EgressWebSocketMessage`s."
+            , body = EgressWebSocketMessage),
+        (status = NOT_FOUND
+            , body = ErrorResponse
+            , description = "Pipeline and/or table/view with that name does not exist"
+            , examples(
+                ("Pipeline with that name does not exist" = (value = json!(examples::error_unknown_pipeline_name()))),
+            )
+        ),
+        (status = BAD_REQUEST
+            , body = ErrorResponse
+            , description = "The request is not a WebSocket upgrade request"),
+        (status = SERVICE_UNAVAILABLE
+            , body = ErrorResponse
+            , examples(
+                ("Pipeline is not deployed" = (value = json!(examples::error_pipeline_interaction_not_deployed()))),
+                ("Pipeline is currently unavailable" = (value = json!(examples::error_pipeline_interaction_currently_unavailable()))),
+            )
+        ),
+        (status = INTERNAL_SERVER_ERROR, body = ErrorResponse),
+    ),
+    tag = "Pipeline interaction"
+)]
+#[get("/pipelines/{pipeline_name}/egress/{table_name}")]
+pub(crate) async fn websocket_output(
+    state: WebData<ServerState>,
+    client: WebData<awc::Client>,
+    tenant_id: ReqData<TenantId>,
+    req: HttpRequest,
+    body: web::Payload,
+) -> Result<HttpResponse, ManagerError> {
+    let pipeline_name = parse_url_parameter(&req, "pipeline_name")?;
+    let table_name = parse_url_parameter(&req, "table_name")?;
+    let endpoint = format!("egress/{table_name}");
+    state
+        .runner
+        .forward_websocket_request_to_pipeline_by_name(
+            client.as_ref(),
+            *tenant_id,
+            &pipeline_name,
+            &endpoint,
+            req,
+            body,
+        )
+        .await
+}
+
 /// Start (resume) or pause the input connector.
 ///
 /// The following values of the `action` argument are accepted: `start` and `pause`.
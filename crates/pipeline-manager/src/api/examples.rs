@@ -104,6 +104,7 @@ fn extended_pipeline_2() -> ExtendedPipelineDescr {
             max_parallel_connector_init: Some(10),
             init_containers: None,
             checkpoint_during_suspend: false,
+            flight_port: None,
             dev_tweaks: BTreeMap::new(),
         })
         .unwrap(),
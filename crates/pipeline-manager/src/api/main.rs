@@ -75,7 +75,9 @@ only the program-related core fields, and is used by the compiler to discern whe
 
         // Pipeline interaction endpoints
         endpoints::pipeline_interaction::http_input,
+        endpoints::pipeline_interaction::websocket_input,
         endpoints::pipeline_interaction::http_output,
+        endpoints::pipeline_interaction::websocket_output,
         endpoints::pipeline_interaction::post_pipeline_input_connector_action,
         endpoints::pipeline_interaction::get_pipeline_input_connector_status,
         endpoints::pipeline_interaction::get_pipeline_output_connector_status,
@@ -162,6 +164,7 @@ only the program-related core fields, and is used by the compiler to discern whe
         feldera_types::config::InputEndpointConfig,
         feldera_types::config::ConnectorConfig,
         feldera_types::config::OutputBufferConfig,
+        feldera_types::config::DeadLetterConfig,
         feldera_types::config::OutputEndpointConfig,
         feldera_types::config::TransportConfig,
         feldera_types::config::FormatConfig,
@@ -170,6 +173,7 @@ only the program-related core fields, and is used by the compiler to discern whe
         feldera_types::config::FtModel,
         feldera_types::transport::adhoc::AdHocInputConfig,
         feldera_types::transport::clock::ClockConfig,
+        feldera_types::transport::compression::Compression,
         feldera_types::transport::file::FileInputConfig,
         feldera_types::transport::file::FileOutputConfig,
         feldera_types::transport::http::HttpInputConfig,
@@ -178,11 +182,15 @@ only the program-related core fields, and is used by the compiler to discern whe
         feldera_types::transport::kafka::KafkaHeaderValue,
         feldera_types::transport::kafka::KafkaLogLevel,
         feldera_types::transport::kafka::KafkaInputConfig,
+        feldera_types::transport::kafka::KafkaMetadataColumns,
         feldera_types::transport::kafka::KafkaOutputConfig,
+        feldera_types::transport::kafka::KafkaDeadLetterConfig,
         feldera_types::transport::kafka::KafkaOutputFtConfig,
         feldera_types::transport::kafka::KafkaStartFromConfig,
         feldera_types::transport::pubsub::PubSubInputConfig,
+        feldera_types::transport::pubsub::PubSubOutputConfig,
         feldera_types::transport::s3::S3InputConfig,
+        feldera_types::transport::object_store::ObjectStoreOutputConfig,
         feldera_types::transport::datagen::DatagenStrategy,
         feldera_types::transport::datagen::RngFieldSettings,
         feldera_types::transport::datagen::GenerationPlan,
@@ -195,14 +203,29 @@ only the program-related core fields, and is used by the compiler to discern whe
         feldera_types::transport::delta_table::DeltaTableReaderConfig,
         feldera_types::transport::delta_table::DeltaTableWriterConfig,
         feldera_types::transport::iceberg::IcebergReaderConfig,
+        feldera_types::transport::iceberg::IcebergWriterConfig,
+        feldera_types::transport::iceberg::IcebergCatalogConfig,
         feldera_types::transport::iceberg::IcebergIngestMode,
         feldera_types::transport::iceberg::IcebergCatalogType,
         feldera_types::transport::iceberg::RestCatalogConfig,
         feldera_types::transport::iceberg::GlueCatalogConfig,
         feldera_types::transport::postgres::PostgresReaderConfig,
+        feldera_types::transport::postgres::PostgresCdcReaderConfig,
         feldera_types::transport::postgres::PostgresWriterConfig,
+        feldera_types::transport::postgres::PostgresWriteMode,
+        feldera_types::transport::mqtt::MqttInputConfig,
+        feldera_types::transport::mqtt::MqttOutputConfig,
+        feldera_types::transport::nats::NatsDeliverPolicy,
+        feldera_types::transport::nats::NatsInputConfig,
+        feldera_types::transport::nats::NatsOutputConfig,
+        feldera_types::transport::redis::RedisInputConfig,
         feldera_types::transport::redis::RedisOutputConfig,
         feldera_types::transport::http::Chunk,
+        feldera_types::transport::http::IngressWebSocketResponseType,
+        feldera_types::transport::http::IngressWebSocketResponse,
+        feldera_types::transport::http::EgressWebSocketMessageType,
+        feldera_types::transport::http::EgressWebSocketMessage,
+        feldera_types::transport::http::EgressMode,
         feldera_types::transport::clock::ClockConfig,
         feldera_types::query::AdhocQueryArgs,
         feldera_types::query::AdHocResultFormat,
@@ -269,7 +292,9 @@ fn api_scope() -> Scope {
         .service(endpoints::pipeline_management::get_program_info)
         // Pipeline interaction endpoints
         .service(endpoints::pipeline_interaction::http_input)
+        .service(endpoints::pipeline_interaction::websocket_input)
         .service(endpoints::pipeline_interaction::http_output)
+        .service(endpoints::pipeline_interaction::websocket_output)
         .service(endpoints::pipeline_interaction::checkpoint_pipeline)
         .service(endpoints::pipeline_interaction::get_checkpoint_status)
         .service(endpoints::pipeline_interaction::post_pipeline_input_connector_action)
@@ -284,6 +284,7 @@ fn map_val_to_limited_runtime_config(val: RuntimeConfigPropVal) -> serde_json::V
             max_parallel_connector_init: None,
             init_containers: None,
             checkpoint_during_suspend: val.val15,
+            flight_port: None,
             dev_tweaks: BTreeMap::new(),
         })
         .unwrap()
@@ -1035,6 +1036,7 @@ async fn pipeline_versioning() {
         max_parallel_connector_init: None,
         init_containers: None,
         checkpoint_during_suspend: true,
+        flight_port: None,
         dev_tweaks: BTreeMap::new(),
     })
     .unwrap();
@@ -1597,6 +1599,7 @@ async fn pipeline_provision_version_guard() {
                     max_parallel_connector_init: None,
                     init_containers: None,
                     checkpoint_during_suspend: false,
+                    flight_port: None,
                     dev_tweaks: BTreeMap::new(),
                 })
                 .unwrap(),
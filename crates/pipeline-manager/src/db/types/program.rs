@@ -540,7 +540,11 @@ pub fn generate_program_info(
                 | TransportConfig::S3Input(_)
                 | TransportConfig::DeltaTableInput(_)
                 | TransportConfig::PostgresInput(_)
+                | TransportConfig::PostgresCdcInput(_)
                 | TransportConfig::IcebergInput(_)
+                | TransportConfig::RedisInput(_)
+                | TransportConfig::MqttInput(_)
+                | TransportConfig::NatsInput(_)
                 | TransportConfig::Datagen(_)
                 | TransportConfig::Nexmark(_) => {}
                 _ => {
@@ -588,8 +592,13 @@ pub fn generate_program_info(
                 TransportConfig::FileOutput(_)
                 | TransportConfig::PostgresOutput(_)
                 | TransportConfig::KafkaOutput(_)
+                | TransportConfig::PubSubOutput(_)
+                | TransportConfig::ObjectStoreOutput(_)
                 | TransportConfig::DeltaTableOutput(_)
-                | TransportConfig::RedisOutput(_) => {}
+                | TransportConfig::IcebergOutput(_)
+                | TransportConfig::RedisOutput(_)
+                | TransportConfig::MqttOutput(_)
+                | TransportConfig::NatsOutput(_) => {}
                 _ => {
                     return Err(ConnectorGenerationError::ExpectedOutputConnector {
                         position: origin_value.value_position,
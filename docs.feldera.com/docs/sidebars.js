@@ -157,6 +157,7 @@ const interface = {
     items: [
         'interface/web-console',
         'interface/cli',
+        'interface/arrow-flight',
         {
             type: 'link',
             label: "Python SDK",
@@ -227,6 +228,7 @@ const connectors = {
         'connectors/orchestration',
         'connectors/completion-tokens',
         'connectors/secret-references',
+        'connectors/dead-letter',
         {
             type: 'category',
             label: 'Input',
@@ -265,6 +267,21 @@ const connectors = {
                     id: 'connectors/sources/pubsub',
                     label: 'Google Pub/Sub'
                 },
+                {
+                    type: 'doc',
+                    id: 'connectors/sources/mqtt',
+                    label: 'MQTT'
+                },
+                {
+                    type: 'doc',
+                    id: 'connectors/sources/nats',
+                    label: 'NATS JetStream'
+                },
+                {
+                    type: 'doc',
+                    id: 'connectors/sources/redis',
+                    label: 'Redis Streams'
+                },
                 {
                     type: 'doc',
                     id: 'connectors/sources/debezium',
@@ -275,6 +292,11 @@ const connectors = {
                     id: 'connectors/sources/postgresql',
                     label: 'PostgreSQL'
                 },
+                {
+                    type: 'doc',
+                    id: 'connectors/sources/postgresql-cdc',
+                    label: 'PostgreSQL CDC'
+                },
                 {
                     type: 'doc',
                     id: 'connectors/sources/s3',
@@ -305,11 +327,36 @@ const connectors = {
                     id: 'connectors/sinks/delta',
                     label: 'Delta Lake'
                 },
+                {
+                    type: 'doc',
+                    id: 'connectors/sinks/iceberg',
+                    label: 'Apache Iceberg'
+                },
+                {
+                    type: 'doc',
+                    id: 'connectors/sinks/object-store',
+                    label: 'S3 and Object Stores'
+                },
                 {
                     type: 'doc',
                     id: 'connectors/sinks/kafka',
                     label: 'Kafka'
                 },
+                {
+                    type: 'doc',
+                    id: 'connectors/sinks/pubsub',
+                    label: 'Google Pub/Sub'
+                },
+                {
+                    type: 'doc',
+                    id: 'connectors/sinks/mqtt',
+                    label: 'MQTT'
+                },
+                {
+                    type: 'doc',
+                    id: 'connectors/sinks/nats',
+                    label: 'NATS JetStream'
+                },
                 {
                     type: 'doc',
                     id: 'connectors/sinks/confluent-jdbc',